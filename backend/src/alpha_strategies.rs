@@ -5,6 +5,12 @@ use anchor_lang::prelude::*;
 use std::collections::HashMap;
 use tokio::sync::RwLock;
 use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::trade_executor::{OrderSide, OrderStatus, OrderType, TradeExecutor, TradeOrder};
+use crate::circuit_breaker::CircuitBreaker;
+use crate::bundles::{BundleBuilder, BundleManager, TrackedBundle};
 
 // ============================================================================
 // 1. JIT (Just-In-Time) Liquidity Strategy
@@ -167,39 +173,32 @@ impl CrossChainArbitrageBot {
 // 4. MEV Protection and Extraction
 // ============================================================================
 pub struct MEVProtectionExtractor {
-    private_mempool: Arc<PrivateMempool>,
-    flashbots_client: Arc<FlashbotsClient>,
-    bundle_builder: Arc<BundleBuilder>,
+    bundle_manager: Arc<BundleManager>,
 }
 
 impl MEVProtectionExtractor {
     pub fn new() -> Self {
-        Self {
-            private_mempool: Arc::new(PrivateMempool::new()),
-            flashbots_client: Arc::new(FlashbotsClient::new()),
-            bundle_builder: Arc::new(BundleBuilder::new()),
-        }
+        Self::with_bundle_manager(Arc::new(BundleManager::new()))
     }
 
-    pub async fn protect_and_extract(&self, user_tx: Transaction) -> Result<()> {
-        // 1. Analyze transaction for MEV opportunities
-        let mev_analysis = self.analyze_mev_potential(&user_tx).await?;
-        
-        if mev_analysis.extractable_value > 0.1 { // 0.1 SOL threshold
-            // 2. Build protection bundle
-            let bundle = self.bundle_builder.create_protected_bundle(
-                user_tx,
-                mev_analysis
-            ).await?;
-            
-            // 3. Submit to private mempool
-            self.private_mempool.submit_bundle(bundle).await?;
-        } else {
-            // Regular submission
-            self.submit_regular_tx(user_tx).await?;
-        }
-        
-        Ok(())
+    pub fn with_bundle_manager(bundle_manager: Arc<BundleManager>) -> Self {
+        Self { bundle_manager }
+    }
+
+    pub fn bundle_manager(&self) -> Arc<BundleManager> {
+        self.bundle_manager.clone()
+    }
+
+    /// Keep the transactions out of the public mempool: wrap them with a tip and send as one Jito bundle
+    pub async fn protect_solana(
+        &self,
+        transactions: Vec<solana_sdk::transaction::VersionedTransaction>,
+        payer: &Keypair,
+        tip_lamports: u64,
+        recent_blockhash: solana_sdk::hash::Hash,
+    ) -> anyhow::Result<Vec<TrackedBundle>> {
+        let bundle = BundleBuilder::jito(transactions, payer, tip_lamports, recent_blockhash)?;
+        self.bundle_manager.send(bundle).await
     }
 }
 
@@ -244,26 +243,13 @@ impl LiquiditySniperBot {
 // ============================================================================
 // 6. Advanced Order Types
 // ============================================================================
-pub struct AdvancedOrderEngine {
-    order_book: Arc<RwLock<OrderBook>>,
-    execution_engine: Arc<ExecutionEngine>,
-}
-
-impl AdvancedOrderEngine {
-    pub fn new() -> Self {
-        Self {
-            order_book: Arc::new(RwLock::new(OrderBook::new())),
-            execution_engine: Arc::new(ExecutionEngine::new()),
-        }
-    }
-}
-
-#[derive(Clone)]
+/// Parent order specification; sizes are in units of the input token and prices are quote per base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AdvancedOrder {
     Iceberg {
         total_size: f64,
         visible_size: f64,
-        price: f64,
+        price: f64, // Limit: buys slice at or below, sells at or above
     },
     TWAP {
         total_size: f64,
@@ -271,15 +257,488 @@ pub enum AdvancedOrder {
         intervals: u32,
     },
     StopLossWithTrailing {
+        size: f64,
         trigger_price: f64,
         trailing_percent: f64,
     },
     ConditionalBundle {
         conditions: Vec<MarketCondition>,
-        orders: Vec<Order>,
+        orders: Vec<TradeOrder>,
     },
 }
 
+impl AdvancedOrder {
+    fn total_size(&self) -> f64 {
+        match self {
+            AdvancedOrder::Iceberg { total_size, .. } | AdvancedOrder::TWAP { total_size, .. } => *total_size,
+            AdvancedOrder::StopLossWithTrailing { size, .. } => *size,
+            AdvancedOrder::ConditionalBundle { orders, .. } => orders.iter().map(|o| o.amount_in).sum(),
+        }
+    }
+}
+
+/// All conditions of a bundle must hold at the same tick before its orders are sent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketCondition {
+    PriceAbove { base: String, quote: String, price: f64 },
+    PriceBelow { base: String, quote: String, price: f64 },
+    After { timestamp: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParentOrderStatus {
+    Active,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentOrderRequest {
+    pub wallet: String,
+    pub token_in: String,
+    pub token_out: String,
+    pub side: OrderSide,
+    pub slippage: f64, // Percent tolerance per child order
+    pub order: AdvancedOrder,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentOrder {
+    pub id: String,
+    pub request: ParentOrderRequest,
+    pub status: ParentOrderStatus,
+    pub total_size: f64,
+    pub filled_size: f64, // Input token spent
+    pub received: f64, // Output token received
+    pub arrival_price: Option<f64>,
+    pub average_price: Option<f64>,
+    pub slippage_bps: Option<f64>, // Versus arrival price, positive is worse
+    pub child_order_ids: Vec<String>,
+    pub slices_sent: u32,
+    pub next_slice_at: u64, // Unix ms
+    pub trail_extreme: Option<f64>, // Best price seen by a trailing stop
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub error: Option<String>,
+}
+
+impl ParentOrder {
+    /// Buys spend quote for base; sells and swaps spend base for quote
+    fn base_quote(&self) -> (&str, &str) {
+        match self.request.side {
+            OrderSide::Buy => (&self.request.token_out, &self.request.token_in),
+            _ => (&self.request.token_in, &self.request.token_out),
+        }
+    }
+
+    fn remaining(&self) -> f64 {
+        (self.total_size - self.filled_size).max(0.0)
+    }
+
+    fn record_fill(&mut self, amount_in: f64, amount_out: f64) {
+        self.filled_size += amount_in;
+        self.received += amount_out;
+        if self.filled_size <= 0.0 || self.received <= 0.0 {
+            return;
+        }
+        let average = match self.request.side {
+            OrderSide::Buy => self.filled_size / self.received,
+            _ => self.received / self.filled_size,
+        };
+        self.average_price = Some(average);
+        if let Some(arrival) = self.arrival_price.filter(|p| *p > 0.0) {
+            let worse = match self.request.side {
+                OrderSide::Buy => average - arrival,
+                _ => arrival - average,
+            };
+            self.slippage_bps = Some(worse / arrival * 10_000.0);
+        }
+    }
+}
+
+/// What a tick decided to do with one parent order.
+enum ScheduledAction {
+    Slice(f64),
+    Bundle(Vec<TradeOrder>),
+}
+
+pub struct AdvancedOrderEngine {
+    executor: Arc<TradeExecutor>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    orders: Arc<RwLock<HashMap<String, ParentOrder>>>,
+    state_path: PathBuf,
+    tick_ms: u64,
+}
+
+impl AdvancedOrderEngine {
+    pub fn new(executor: Arc<TradeExecutor>) -> Self {
+        let state_path = std::env::var("DEXTER_ADVANCED_ORDER_STATE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("data/advanced_orders.json"));
+        let orders = Self::load_state(&state_path);
+        if !orders.is_empty() {
+            log::info!("📂 Restored {} advanced orders from {}", orders.len(), state_path.display());
+        }
+
+        Self {
+            executor,
+            circuit_breaker: None,
+            orders: Arc::new(RwLock::new(orders)),
+            state_path,
+            tick_ms: 500,
+        }
+    }
+
+    /// Hold child slices while the global, "AdvancedOrders" or venue breaker is open
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Drive all active parent orders until the process exits
+    pub async fn start(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(self.tick_ms));
+        loop {
+            interval.tick().await;
+            self.tick().await;
+        }
+    }
+
+    pub async fn submit(&self, request: ParentOrderRequest) -> std::result::Result<String, String> {
+        let total_size = request.order.total_size();
+        if total_size <= 0.0 {
+            return Err("Parent order size must be positive".to_string());
+        }
+        match &request.order {
+            AdvancedOrder::Iceberg { visible_size, .. } if *visible_size <= 0.0 => {
+                return Err("Iceberg visible size must be positive".to_string());
+            }
+            AdvancedOrder::TWAP { intervals: 0, .. } => {
+                return Err("TWAP needs at least one interval".to_string());
+            }
+            _ => {}
+        }
+
+        let now = now_ms();
+        let mut parent = ParentOrder {
+            id: format!("parent_{}", uuid::Uuid::new_v4().simple()),
+            request,
+            status: ParentOrderStatus::Active,
+            total_size,
+            filled_size: 0.0,
+            received: 0.0,
+            arrival_price: None,
+            average_price: None,
+            slippage_bps: None,
+            child_order_ids: Vec::new(),
+            slices_sent: 0,
+            next_slice_at: now,
+            trail_extreme: None,
+            created_at: now,
+            updated_at: now,
+            error: None,
+        };
+        let (base, quote) = parent.base_quote();
+        parent.arrival_price = self.market_price(base, quote).await;
+
+        let id = parent.id.clone();
+        log::info!("🧾 Accepted advanced order {} ({} {} -> {})",
+                   id, parent.total_size, parent.request.token_in, parent.request.token_out);
+        self.orders.write().await.insert(id.clone(), parent);
+        self.persist().await;
+        Ok(id)
+    }
+
+    pub async fn pause(&self, id: &str) -> std::result::Result<(), String> {
+        self.transition(id, ParentOrderStatus::Active, ParentOrderStatus::Paused).await
+    }
+
+    pub async fn resume(&self, id: &str) -> std::result::Result<(), String> {
+        self.transition(id, ParentOrderStatus::Paused, ParentOrderStatus::Active).await
+    }
+
+    pub async fn cancel(&self, id: &str) -> std::result::Result<(), String> {
+        {
+            let mut orders = self.orders.write().await;
+            let parent = orders.get_mut(id).ok_or("Advanced order not found")?;
+            if !matches!(parent.status, ParentOrderStatus::Active | ParentOrderStatus::Paused) {
+                return Err(format!("Advanced order is already {:?}", parent.status));
+            }
+            parent.status = ParentOrderStatus::Cancelled;
+            parent.updated_at = now_ms();
+        }
+        self.persist().await;
+        Ok(())
+    }
+
+    pub async fn get_order(&self, id: &str) -> Option<ParentOrder> {
+        self.orders.read().await.get(id).cloned()
+    }
+
+    pub async fn get_orders(&self) -> Vec<ParentOrder> {
+        self.orders.read().await.values().cloned().collect()
+    }
+
+    async fn transition(&self, id: &str, from: ParentOrderStatus, to: ParentOrderStatus) -> std::result::Result<(), String> {
+        {
+            let mut orders = self.orders.write().await;
+            let parent = orders.get_mut(id).ok_or("Advanced order not found")?;
+            if parent.status != from {
+                return Err(format!("Advanced order is {:?}, expected {:?}", parent.status, from));
+            }
+            parent.status = to;
+            parent.updated_at = now_ms();
+            // A resumed TWAP continues from now rather than bursting the missed slices
+            if to == ParentOrderStatus::Active {
+                parent.next_slice_at = parent.next_slice_at.max(now_ms());
+            }
+        }
+        self.persist().await;
+        Ok(())
+    }
+
+    async fn market_price(&self, base: &str, quote: &str) -> Option<f64> {
+        self.executor.quote(base, quote, 1.0).await.ok().map(|(_, out)| out).filter(|p| *p > 0.0)
+    }
+
+    async fn conditions_met(&self, conditions: &[MarketCondition]) -> bool {
+        for condition in conditions {
+            let met = match condition {
+                MarketCondition::PriceAbove { base, quote, price } => {
+                    self.market_price(base, quote).await.map(|p| p > *price).unwrap_or(false)
+                }
+                MarketCondition::PriceBelow { base, quote, price } => {
+                    self.market_price(base, quote).await.map(|p| p < *price).unwrap_or(false)
+                }
+                MarketCondition::After { timestamp } => now_ms() / 1000 >= *timestamp,
+            };
+            if !met {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Decide the next child for a parent order, updating trailing state in place
+    async fn schedule(&self, parent: &mut ParentOrder) -> Option<ScheduledAction> {
+        let remaining = parent.remaining();
+        let now = now_ms();
+        let (base, quote) = {
+            let (b, q) = parent.base_quote();
+            (b.to_string(), q.to_string())
+        };
+        let is_buy = parent.request.side == OrderSide::Buy;
+
+        match parent.request.order.clone() {
+            AdvancedOrder::Iceberg { visible_size, price, .. } => {
+                let market = self.market_price(&base, &quote).await?;
+                let marketable = if is_buy { market <= price } else { market >= price };
+                marketable.then(|| ScheduledAction::Slice(visible_size.min(remaining)))
+            }
+            AdvancedOrder::TWAP { total_size, duration, intervals } => {
+                if now < parent.next_slice_at {
+                    return None;
+                }
+                let interval_ms = duration.as_millis() as u64 / intervals as u64;
+                parent.next_slice_at = now + interval_ms;
+                // The last interval sweeps whatever earlier slices left unfilled
+                let slice = if parent.slices_sent + 1 >= intervals {
+                    remaining
+                } else {
+                    (total_size / intervals as f64).min(remaining)
+                };
+                Some(ScheduledAction::Slice(slice))
+            }
+            AdvancedOrder::StopLossWithTrailing { trigger_price, trailing_percent, .. } => {
+                let market = self.market_price(&base, &quote).await?;
+                // Sells trail below the high-water mark; buys trail above the low
+                let extreme = match parent.trail_extreme {
+                    Some(e) if is_buy => e.min(market),
+                    Some(e) => e.max(market),
+                    None => market,
+                };
+                parent.trail_extreme = Some(extreme);
+                let trail = trailing_percent / 100.0;
+                let triggered = if is_buy {
+                    market >= trigger_price.min(extreme * (1.0 + trail))
+                } else {
+                    market <= trigger_price.max(extreme * (1.0 - trail))
+                };
+                triggered.then(|| ScheduledAction::Slice(remaining))
+            }
+            AdvancedOrder::ConditionalBundle { conditions, orders } => {
+                self.conditions_met(&conditions).await.then(|| ScheduledAction::Bundle(orders))
+            }
+        }
+    }
+
+    /// Send a child order through the executor; returns (input spent, output received)
+    async fn send_child(&self, parent: &ParentOrder, template: Option<&TradeOrder>, amount: f64) -> std::result::Result<(String, f64, f64), String> {
+        let (token_in, token_out, side) = match template {
+            Some(t) => (t.token_in.clone(), t.token_out.clone(), t.side),
+            None => (parent.request.token_in.clone(), parent.request.token_out.clone(), parent.request.side),
+        };
+        let (dex, quote) = self.executor.quote(&token_in, &token_out, amount).await?;
+        if let Some(breaker) = &self.circuit_breaker {
+            breaker.check("AdvancedOrders", &[dex.as_str()]).await
+                .map_err(|trip| format!("Circuit breaker {} open: {}", trip.scope, trip.detail))?;
+        }
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        // Deterministic child id doubles as the client order id, so a retried tick cannot send a slice twice
+        let child_id = format!("{}_{}", parent.id, parent.child_order_ids.len() + 1);
+        let child = TradeOrder {
+            id: child_id.clone(),
+            wallet: parent.request.wallet.clone(),
+            order_type: OrderType::Market,
+            side,
+            token_in,
+            token_out,
+            amount_in: amount,
+            amount_out_min: quote,
+            slippage: parent.request.slippage,
+            status: OrderStatus::New,
+            dex: Some(dex),
+            route: None,
+            gas_price: None,
+            deadline: now + 60,
+            created_at: now,
+            executed_at: None,
+            tx_hash: None,
+            error: None,
+            client_order_id: child_id,
+            venue_order_id: None,
+            filled_amount: 0.0,
+            status_history: Vec::new(),
+        };
+        let child_id = self.executor.create_order(child).await?;
+        let result = self.executor.execute_order(&child_id).await?;
+        Ok((child_id, result.amount_in, result.amount_out))
+    }
+
+    async fn tick(&self) {
+        let active: Vec<ParentOrder> = self.orders.read().await.values()
+            .filter(|o| o.status == ParentOrderStatus::Active)
+            .cloned()
+            .collect();
+        if active.is_empty() {
+            return;
+        }
+
+        for mut parent in active {
+            let Some(action) = self.schedule(&mut parent).await else {
+                self.store(parent, false).await;
+                continue;
+            };
+
+            match action {
+                ScheduledAction::Slice(amount) if amount > 0.0 => {
+                    match self.send_child(&parent, None, amount).await {
+                        Ok((child_id, spent, received)) => {
+                            parent.child_order_ids.push(child_id);
+                            parent.slices_sent += 1;
+                            parent.record_fill(spent, received);
+                            parent.error = None;
+                        }
+                        Err(e) => {
+                            log::warn!("⚠️ Child order for {} failed: {}", parent.id, e);
+                            parent.error = Some(e);
+                        }
+                    }
+                }
+                ScheduledAction::Slice(_) => {}
+                ScheduledAction::Bundle(orders) => {
+                    for template in &orders {
+                        match self.send_child(&parent, Some(template), template.amount_in).await {
+                            Ok((child_id, spent, received)) => {
+                                parent.child_order_ids.push(child_id);
+                                parent.record_fill(spent, received);
+                            }
+                            Err(e) => {
+                                // Later legs depend on earlier ones; stop the bundle here
+                                parent.status = ParentOrderStatus::Failed;
+                                parent.error = Some(format!("Bundle order {} failed: {}", template.id, e));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if parent.status == ParentOrderStatus::Active && parent.remaining() <= 1e-9 {
+                parent.status = ParentOrderStatus::Completed;
+                log::info!("✅ Advanced order {} completed: avg price {:.6}, slippage {:.1} bps",
+                           parent.id, parent.average_price.unwrap_or(0.0), parent.slippage_bps.unwrap_or(0.0));
+            }
+            parent.updated_at = now_ms();
+            self.store(parent, true).await;
+        }
+    }
+
+    /// Write back a ticked order unless it was paused or cancelled meanwhile
+    async fn store(&self, parent: ParentOrder, changed: bool) {
+        {
+            let mut orders = self.orders.write().await;
+            match orders.get(&parent.id) {
+                Some(current) if current.status != ParentOrderStatus::Active && parent.child_order_ids.len() == current.child_order_ids.len() => return,
+                Some(current) if current.status != ParentOrderStatus::Active => {
+                    // Keep the pause/cancel but account for the child that was in flight
+                    let status = current.status;
+                    let mut merged = parent;
+                    merged.status = status;
+                    orders.insert(merged.id.clone(), merged);
+                }
+                _ => {
+                    orders.insert(parent.id.clone(), parent);
+                }
+            }
+        }
+        if changed {
+            self.persist().await;
+        }
+    }
+
+    fn load_state(path: &Path) -> HashMap<String, ParentOrder> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str::<Vec<ParentOrder>>(&json)
+                .map(|orders| orders.into_iter().map(|o| (o.id.clone(), o)).collect())
+                .unwrap_or_else(|e| {
+                    log::warn!("⚠️ Ignoring unreadable advanced order state {}: {}", path.display(), e);
+                    HashMap::new()
+                }),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Atomically rewrite the state file (write to a temp file, then rename)
+    async fn persist(&self) {
+        let orders: Vec<ParentOrder> = self.orders.read().await.values().cloned().collect();
+        let json = match serde_json::to_string_pretty(&orders) {
+            Ok(json) => json,
+            Err(e) => {
+                log::error!("❌ Failed to serialize advanced orders: {}", e);
+                return;
+            }
+        };
+        if let Some(dir) = self.state_path.parent() {
+            let _ = tokio::fs::create_dir_all(dir).await;
+        }
+        let tmp = self.state_path.with_extension("json.tmp");
+        if let Err(e) = async {
+            tokio::fs::write(&tmp, json).await?;
+            tokio::fs::rename(&tmp, &self.state_path).await
+        }.await {
+            log::error!("❌ Failed to persist advanced orders to {}: {}", self.state_path.display(), e);
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    Utc::now().timestamp_millis() as u64
+}
+
 // ============================================================================
 // 7. Market Making with Inventory Management
 // ============================================================================
@@ -511,21 +970,6 @@ impl WormholeBridge {
     pub fn new() -> Self { Self }
 }
 
-pub struct PrivateMempool;
-impl PrivateMempool {
-    pub fn new() -> Self { Self }
-}
-
-pub struct FlashbotsClient;
-impl FlashbotsClient {
-    pub fn new() -> Self { Self }
-}
-
-pub struct BundleBuilder;
-impl BundleBuilder {
-    pub fn new() -> Self { Self }
-}
-
 pub struct NewPoolMonitor;
 impl NewPoolMonitor {
     pub fn new() -> Self { Self }
@@ -636,8 +1080,6 @@ pub enum RiskProfile {
 
 pub struct OptionOpportunity;
 pub struct Market;
-pub struct Order;
-pub struct MarketCondition;
 
 #[derive(Clone)]
 pub enum Chain {
@@ -9,6 +9,8 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use log::{info, debug};
 
+use crate::fee_model::{FeeModel, PricedLeg, ProfitBreakdown};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageOpportunity {
     pub id: String,
@@ -21,6 +23,7 @@ pub struct ArbitrageOpportunity {
     pub estimated_profit: Decimal,
     pub timestamp: u64,
     pub risk_score: f64,
+    pub profit_breakdown: ProfitBreakdown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,14 +47,16 @@ pub struct ArbitrageEngine {
     opportunities: Arc<RwLock<Vec<ArbitrageOpportunity>>>,
     min_profit_threshold: Decimal,
     max_risk_score: f64,
+    fee_model: Arc<FeeModel>,
 }
 
 impl ArbitrageEngine {
-    pub fn new() -> Self {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
         Self {
             opportunities: Arc::new(RwLock::new(Vec::new())),
             min_profit_threshold: Decimal::from(100), // $100 minimum profit
             max_risk_score: 0.7, // 70% max risk
+            fee_model,
         }
     }
 
@@ -62,17 +67,22 @@ impl ArbitrageEngine {
 
     pub async fn scan_opportunities(&self) -> Result<Vec<ArbitrageOpportunity>> {
         // Simulate finding arbitrage opportunities
+        let buy_price = Decimal::from(100);
+        let sell_price = Decimal::from(102);
+        let breakdown = self.calculate_profit_breakdown("Jupiter", "Raydium", buy_price, sell_price, Decimal::from(100), "SOL").await;
+        
         let opportunity = ArbitrageOpportunity {
             id: format!("arb_{}", chrono::Utc::now().timestamp()),
             pair: "SOL/USDC".to_string(),
             buy_exchange: "Jupiter".to_string(),
             sell_exchange: "Raydium".to_string(),
-            buy_price: Decimal::from(100),
-            sell_price: Decimal::from(102),
-            profit_percentage: Decimal::from(2),
-            estimated_profit: Decimal::from(200),
+            buy_price,
+            sell_price,
+            profit_percentage: breakdown.net_profit_percentage,
+            estimated_profit: breakdown.net_profit_usd,
             timestamp: chrono::Utc::now().timestamp() as u64,
             risk_score: 0.3,
+            profit_breakdown: breakdown,
         };
 
         let mut opportunities = self.opportunities.write().await;
@@ -92,8 +102,38 @@ impl ArbitrageEngine {
         Ok(format!("tx_{}", chrono::Utc::now().timestamp()))
     }
 
-    pub async fn calculate_profit(&self, buy_price: Decimal, sell_price: Decimal, amount: Decimal) -> Decimal {
-        (sell_price - buy_price) * amount
+    /// Net profit after fees, gas and transfers for buying on `buy_exchange` and selling on `sell_exchange`
+    pub async fn calculate_profit(
+        &self,
+        buy_exchange: &str,
+        sell_exchange: &str,
+        buy_price: Decimal,
+        sell_price: Decimal,
+        amount: Decimal,
+        base_asset: &str,
+    ) -> Decimal {
+        self.calculate_profit_breakdown(buy_exchange, sell_exchange, buy_price, sell_price, amount, base_asset)
+            .await
+            .net_profit_usd
+    }
+
+    pub async fn calculate_profit_breakdown(
+        &self,
+        buy_exchange: &str,
+        sell_exchange: &str,
+        buy_price: Decimal,
+        sell_price: Decimal,
+        amount: Decimal,
+        base_asset: &str,
+    ) -> ProfitBreakdown {
+        let requires_transfer = self.fee_model.requires_transfer(buy_exchange, sell_exchange).await;
+        self.fee_model.evaluate_two_leg(
+            PricedLeg { venue: buy_exchange, price: buy_price },
+            PricedLeg { venue: sell_exchange, price: sell_price },
+            amount,
+            base_asset,
+            requires_transfer,
+        ).await
     }
 
     pub async fn assess_risk(&self, opportunity: &ArbitrageOpportunity) -> f64 {
@@ -111,7 +151,7 @@ impl ArbitrageEngine {
 
 impl Default for ArbitrageEngine {
     fn default() -> Self {
-        Self::new()
+        Self::new(Arc::new(FeeModel::new()))
     }
 }
 
@@ -0,0 +1,514 @@
+// Authentication & Authorization - Session tokens for the dashboard API and WebSocket feeds
+// Issued after wallet sign-in (SIWE/SIWS) or API-key login; roles gate every route and connection
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use rand::RngCore;
+use log::{error, info, warn};
+
+use crate::wallet_manager::{self, WalletSecurity};
+
+// ============================================================================
+// ROLES & SESSIONS
+// ============================================================================
+
+/// Ordered: every role can do everything the roles below it can
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Viewer,  // Read-only market data, portfolio and risk state
+    Trader,  // Simulations and order placement
+    Admin,   // Kill switch, trading mode and configuration
+}
+
+impl Role {
+    pub fn allows(&self, required: Role) -> bool {
+        *self >= required
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "viewer" => Some(Role::Viewer),
+            "trader" => Some(Role::Trader),
+            "admin" => Some(Role::Admin),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    Wallet,
+    ApiKey,
+    Disabled, // Authentication turned off for local development
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub subject: String, // Wallet address or API key name
+    pub role: Role,
+    pub method: AuthMethod,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+/// Returned once at login; only a hash of the token is kept server-side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedSession {
+    pub token: String,
+    pub session: Session,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    InvalidCredentials(String),
+    Forbidden { required: Role, actual: Role },
+    RateLimited,
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingToken => write!(f, "Missing bearer token"),
+            AuthError::InvalidToken => write!(f, "Invalid or expired session"),
+            AuthError::InvalidCredentials(reason) => write!(f, "Invalid credentials: {}", reason),
+            AuthError::Forbidden { required, actual } => write!(f, "Requires {:?} role, session has {:?}", required, actual),
+            AuthError::RateLimited => write!(f, "Too many pending sign-in requests"),
+        }
+    }
+}
+
+impl AuthError {
+    /// 401 for anything identity-related, 403 when the identity is known but not allowed
+    pub fn status_code(&self) -> u16 {
+        match self {
+            AuthError::Forbidden { .. } => 403,
+            AuthError::RateLimited => 429,
+            _ => 401,
+        }
+    }
+}
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    pub session_ttl_secs: u64,
+    pub sign_in_domain: String,           // Domain wallets must name in their sign-in message
+    pub allowed_origins: Vec<String>,     // Empty allows any origin
+    pub default_wallet_role: Role,
+    pub wallet_roles: HashMap<String, Role>,
+    pub max_sessions: usize,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            session_ttl_secs: 8 * 3600,
+            sign_in_domain: "localhost:3000".to_string(),
+            allowed_origins: vec!["http://localhost:3000".to_string()],
+            default_wallet_role: Role::Viewer,
+            wallet_roles: HashMap::new(),
+            max_sessions: 10_000,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// DEXTER_AUTH_DISABLED, DEXTER_SESSION_TTL_SECS, DEXTER_AUTH_DOMAIN, DEXTER_ALLOWED_ORIGINS (comma
+    /// separated, `*` for any) and DEXTER_WALLET_ROLES (`address:role,...`)
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(disabled) = std::env::var("DEXTER_AUTH_DISABLED") {
+            config.enabled = !(disabled == "true" || disabled == "1");
+        }
+        if let Some(ttl) = std::env::var("DEXTER_SESSION_TTL_SECS").ok().and_then(|v| v.parse().ok()) {
+            config.session_ttl_secs = ttl;
+        }
+        if let Ok(domain) = std::env::var("DEXTER_AUTH_DOMAIN") {
+            config.sign_in_domain = domain;
+        }
+        if let Ok(origins) = std::env::var("DEXTER_ALLOWED_ORIGINS") {
+            config.allowed_origins = origins.split(',')
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty() && o != "*")
+                .collect();
+        }
+        if let Ok(roles) = std::env::var("DEXTER_WALLET_ROLES") {
+            for entry in roles.split(',').filter(|e| !e.trim().is_empty()) {
+                match entry.rsplit_once(':').and_then(|(wallet, role)| Role::parse(role).map(|r| (wallet, r))) {
+                    Some((wallet, role)) => {
+                        config.wallet_roles.insert(normalize_subject(wallet.trim()), role);
+                    }
+                    None => warn!("⚠️ Ignoring malformed DEXTER_WALLET_ROLES entry '{}'", entry),
+                }
+            }
+        }
+        config
+    }
+
+    pub fn origin_allowed(&self, origin: Option<&str>) -> bool {
+        match origin {
+            _ if self.allowed_origins.is_empty() => true,
+            // Non-browser clients send no Origin header
+            None => true,
+            Some(origin) => self.allowed_origins.iter().any(|o| o == origin.trim_end_matches('/')),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ApiKey {
+    name: String,
+    role: Role,
+    key_hash: [u8; 32],
+}
+
+// ============================================================================
+// AUTH MANAGER
+// ============================================================================
+
+pub struct AuthManager {
+    wallet_security: Arc<WalletSecurity>,
+    config: Arc<RwLock<AuthConfig>>,
+    api_keys: Arc<RwLock<Vec<ApiKey>>>,
+    sessions: Arc<RwLock<HashMap<[u8; 32], Session>>>,
+    revocations: broadcast::Sender<String>, // Subject of each session ended before its expiry
+}
+
+impl AuthManager {
+    pub fn new(wallet_security: Arc<WalletSecurity>, config: AuthConfig) -> Self {
+        let (revocations, _) = broadcast::channel(256);
+        Self {
+            wallet_security,
+            config: Arc::new(RwLock::new(config)),
+            api_keys: Arc::new(RwLock::new(Vec::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
+            revocations,
+        }
+    }
+
+    /// Config from the environment plus API keys from DEXTER_API_KEYS (`name:role:key,...`)
+    pub async fn from_env(wallet_security: Arc<WalletSecurity>) -> Self {
+        let manager = Self::new(wallet_security, AuthConfig::from_env());
+        if let Ok(keys) = std::env::var("DEXTER_API_KEYS") {
+            for entry in keys.split(',').filter(|e| !e.trim().is_empty()) {
+                let parts: Vec<&str> = entry.trim().splitn(3, ':').collect();
+                match (parts.as_slice(), parts.get(1).and_then(|r| Role::parse(r))) {
+                    ([name, _, key], Some(role)) if !key.is_empty() => manager.add_api_key(name, role, key).await,
+                    _ => warn!("⚠️ Ignoring malformed DEXTER_API_KEYS entry"),
+                }
+            }
+        }
+        if !manager.config.read().await.enabled {
+            log_disabled();
+        }
+        manager
+    }
+
+    pub async fn get_config(&self) -> AuthConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: AuthConfig) {
+        if !config.enabled {
+            log_disabled();
+        }
+        *self.config.write().await = config;
+    }
+
+    /// Subjects whose session was revoked or evicted; holders of a session re-check it on each event
+    pub fn subscribe_revocations(&self) -> broadcast::Receiver<String> {
+        self.revocations.subscribe()
+    }
+
+    pub async fn add_api_key(&self, name: &str, role: Role, key: &str) {
+        info!("🔑 API key '{}' registered with {:?} role", name, role);
+        self.api_keys.write().await.push(ApiKey {
+            name: name.to_string(),
+            role,
+            key_hash: sha256(key.as_bytes()),
+        });
+    }
+
+    /// Nonce to embed in the wallet's sign-in message
+    pub async fn wallet_nonce(&self, wallet: &str) -> Result<String, AuthError> {
+        if !wallet_manager::is_wallet_address(wallet) {
+            return Err(AuthError::InvalidCredentials(format!("{} is not a wallet address", wallet)));
+        }
+        self.wallet_security.generate_nonce(wallet).await
+            .map_err(|_| AuthError::RateLimited)
+    }
+
+    pub async fn login_wallet(&self, message: &str, signature: &str) -> Result<IssuedSession, AuthError> {
+        let config = self.config.read().await.clone();
+        let sign_in = self.wallet_security.sign_in(message, signature, &config.sign_in_domain).await
+            .map_err(AuthError::InvalidCredentials)?;
+        let subject = normalize_subject(&sign_in.address);
+        let role = config.wallet_roles.get(&subject).copied().unwrap_or(config.default_wallet_role);
+
+        // A sign-in message that expires sooner than our TTL caps the session
+        let ttl_expiry = chrono::Utc::now().timestamp() as u64 + config.session_ttl_secs;
+        let expires_at = sign_in.expires_at.map(|e| e.min(ttl_expiry)).unwrap_or(ttl_expiry);
+        Ok(self.issue(sign_in.address, role, AuthMethod::Wallet, expires_at).await)
+    }
+
+    pub async fn login_api_key(&self, key: &str) -> Result<IssuedSession, AuthError> {
+        let presented = sha256(key.as_bytes());
+        let matched = self.api_keys.read().await.iter()
+            .find(|k| constant_time_eq(&k.key_hash, &presented))
+            .cloned();
+        let api_key = matched.ok_or_else(|| AuthError::InvalidCredentials("unknown API key".to_string()))?;
+        let expires_at = chrono::Utc::now().timestamp() as u64 + self.config.read().await.session_ttl_secs;
+        Ok(self.issue(api_key.name, api_key.role, AuthMethod::ApiKey, expires_at).await)
+    }
+
+    pub async fn authenticate(&self, token: Option<&str>) -> Result<Session, AuthError> {
+        if !self.config.read().await.enabled {
+            return Ok(Session {
+                subject: "anonymous".to_string(),
+                role: Role::Admin,
+                method: AuthMethod::Disabled,
+                issued_at: 0,
+                expires_at: u64::MAX,
+            });
+        }
+
+        let token = token.filter(|t| !t.is_empty()).ok_or(AuthError::MissingToken)?;
+        let key = sha256(token.as_bytes());
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut sessions = self.sessions.write().await;
+        match sessions.get(&key) {
+            Some(session) if session.expires_at > now => Ok(session.clone()),
+            Some(_) => {
+                sessions.remove(&key);
+                Err(AuthError::InvalidToken)
+            }
+            None => Err(AuthError::InvalidToken),
+        }
+    }
+
+    pub async fn authorize(&self, token: Option<&str>, required: Role) -> Result<Session, AuthError> {
+        let session = self.authenticate(token).await?;
+        if !session.role.allows(required) {
+            return Err(AuthError::Forbidden { required, actual: session.role });
+        }
+        Ok(session)
+    }
+
+    pub async fn logout(&self, token: &str) -> bool {
+        let removed = self.sessions.write().await.remove(&sha256(token.as_bytes()));
+        match removed {
+            Some(session) => {
+                let _ = self.revocations.send(session.subject);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub async fn allowed_origins(&self) -> Vec<String> {
+        self.config.read().await.allowed_origins.clone()
+    }
+
+    pub async fn origin_allowed(&self, origin: Option<&str>) -> bool {
+        self.config.read().await.origin_allowed(origin)
+    }
+
+    async fn issue(&self, subject: String, role: Role, method: AuthMethod, expires_at: u64) -> IssuedSession {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let now = chrono::Utc::now().timestamp() as u64;
+        let session = Session { subject, role, method, issued_at: now, expires_at };
+
+        let max_sessions = self.config.read().await.max_sessions;
+        let mut sessions = self.sessions.write().await;
+        sessions.retain(|_, s| s.expires_at > now);
+        if sessions.len() >= max_sessions {
+            if let Some(oldest) = sessions.iter().min_by_key(|(_, s)| s.issued_at).map(|(k, _)| *k) {
+                if let Some(evicted) = sessions.remove(&oldest) {
+                    let _ = self.revocations.send(evicted.subject);
+                }
+            }
+        }
+        sessions.insert(sha256(token.as_bytes()), session.clone());
+        drop(sessions);
+
+        info!("🔓 {:?} session issued to {} ({:?})", role, session.subject, method);
+        IssuedSession { token, session }
+    }
+}
+
+impl Clone for AuthManager {
+    fn clone(&self) -> Self {
+        Self {
+            wallet_security: self.wallet_security.clone(),
+            config: self.config.clone(),
+            api_keys: self.api_keys.clone(),
+            sessions: self.sessions.clone(),
+            revocations: self.revocations.clone(),
+        }
+    }
+}
+
+fn log_disabled() {
+    error!("🛑 Dashboard authentication is DISABLED: every client, on every route and WebSocket, has admin access");
+}
+
+/// `Authorization: Bearer <token>` header value to the bare token
+pub fn bearer_token(header: Option<&str>) -> Option<&str> {
+    header.and_then(|value| value.strip_prefix("Bearer ")).map(str::trim)
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+fn normalize_subject(subject: &str) -> String {
+    if subject.starts_with("0x") { subject.to_lowercase() } else { subject.to_string() }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(config: AuthConfig) -> AuthManager {
+        AuthManager::new(Arc::new(WalletSecurity::new()), config)
+    }
+
+    #[test]
+    fn roles_allow_everything_below_them() {
+        assert!(Role::Viewer < Role::Trader && Role::Trader < Role::Admin);
+        assert!(Role::Admin.allows(Role::Viewer));
+        assert!(Role::Trader.allows(Role::Trader));
+        assert!(!Role::Trader.allows(Role::Admin));
+        assert!(!Role::Viewer.allows(Role::Trader));
+        assert_eq!(Role::parse(" Trader "), Some(Role::Trader));
+        assert_eq!(Role::parse("root"), None);
+    }
+
+    #[tokio::test]
+    async fn only_token_hashes_are_stored() {
+        let auth = manager(AuthConfig::default());
+        auth.add_api_key("bot", Role::Trader, "secret-key").await;
+        assert!(auth.api_keys.read().await.iter().all(|k| k.key_hash == sha256(b"secret-key")));
+
+        let issued = auth.login_api_key("secret-key").await.unwrap();
+        assert_eq!(issued.token.len(), 64);
+        let sessions = auth.sessions.read().await;
+        assert!(sessions.contains_key(&sha256(issued.token.as_bytes())));
+        assert!(!sessions.keys().any(|k| k.as_slice() == issued.token.as_bytes()));
+        drop(sessions);
+
+        assert_eq!(auth.authenticate(Some(&issued.token)).await.unwrap().subject, "bot");
+        assert_eq!(auth.authenticate(Some("forged")).await.unwrap_err(), AuthError::InvalidToken);
+        assert_eq!(auth.authenticate(None).await.unwrap_err(), AuthError::MissingToken);
+        assert!(matches!(auth.login_api_key("wrong").await, Err(AuthError::InvalidCredentials(_))));
+    }
+
+    #[tokio::test]
+    async fn roles_gate_authorization() {
+        let auth = manager(AuthConfig::default());
+        auth.add_api_key("viewer", Role::Viewer, "view-key").await;
+        let token = auth.login_api_key("view-key").await.unwrap().token;
+
+        assert!(auth.authorize(Some(&token), Role::Viewer).await.is_ok());
+        let error = auth.authorize(Some(&token), Role::Admin).await.unwrap_err();
+        assert_eq!(error, AuthError::Forbidden { required: Role::Admin, actual: Role::Viewer });
+        assert_eq!(error.status_code(), 403);
+        assert_eq!(AuthError::InvalidToken.status_code(), 401);
+    }
+
+    #[tokio::test]
+    async fn expired_sessions_are_rejected_and_dropped() {
+        let auth = manager(AuthConfig { session_ttl_secs: 0, ..AuthConfig::default() });
+        auth.add_api_key("bot", Role::Trader, "secret-key").await;
+        let token = auth.login_api_key("secret-key").await.unwrap().token;
+
+        assert_eq!(auth.authenticate(Some(&token)).await.unwrap_err(), AuthError::InvalidToken);
+        assert!(auth.sessions.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn logout_revokes_the_session_and_notifies_holders() {
+        let auth = manager(AuthConfig::default());
+        auth.add_api_key("bot", Role::Trader, "secret-key").await;
+        let token = auth.login_api_key("secret-key").await.unwrap().token;
+        let other = auth.login_api_key("secret-key").await.unwrap().token;
+        let mut revocations = auth.subscribe_revocations();
+
+        assert!(auth.logout(&token).await);
+        assert_eq!(revocations.try_recv().unwrap(), "bot");
+        assert_eq!(auth.authenticate(Some(&token)).await.unwrap_err(), AuthError::InvalidToken);
+        assert!(!auth.logout(&token).await);
+        assert!(revocations.try_recv().is_err());
+        // Other sessions of the same subject stay valid
+        assert!(auth.authenticate(Some(&other)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn evicted_sessions_are_announced_as_revoked() {
+        let auth = manager(AuthConfig { max_sessions: 1, ..AuthConfig::default() });
+        auth.add_api_key("bot", Role::Trader, "secret-key").await;
+        let first = auth.login_api_key("secret-key").await.unwrap().token;
+        let mut revocations = auth.subscribe_revocations();
+
+        auth.login_api_key("secret-key").await.unwrap();
+        assert_eq!(revocations.try_recv().unwrap(), "bot");
+        assert!(auth.authenticate(Some(&first)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn wallet_nonces_need_a_real_address_and_are_rate_limited() {
+        let auth = manager(AuthConfig::default());
+        auth.wallet_security.set_config(crate::wallet_manager::WalletSecurityConfig {
+            max_pending_nonces: 1,
+            ..Default::default()
+        }).await;
+
+        let error = auth.wallet_nonce("<script>").await.unwrap_err();
+        assert!(matches!(error, AuthError::InvalidCredentials(_)));
+        assert!(auth.wallet_nonce("0x52908400098527886E0F7030069857D2E4169EE7").await.is_ok());
+        let error = auth.wallet_nonce("0x8617E340B3D01FA5F11F306F4090FD50E238070D").await.unwrap_err();
+        assert_eq!(error, AuthError::RateLimited);
+        assert_eq!(error.status_code(), 429);
+    }
+
+    #[tokio::test]
+    async fn disabled_auth_grants_admin_without_a_token() {
+        let auth = manager(AuthConfig { enabled: false, ..AuthConfig::default() });
+        let session = auth.authorize(None, Role::Admin).await.unwrap();
+        assert_eq!(session.method, AuthMethod::Disabled);
+        assert!(AuthConfig::default().enabled, "authentication must be on unless explicitly disabled");
+    }
+
+    #[test]
+    fn bearer_tokens_and_origins_are_parsed() {
+        assert_eq!(bearer_token(Some("Bearer abc ")), Some("abc"));
+        assert_eq!(bearer_token(Some("Basic abc")), None);
+
+        let config = AuthConfig::default();
+        assert!(config.origin_allowed(Some("http://localhost:3000/")));
+        assert!(!config.origin_allowed(Some("https://evil.example")));
+        assert!(config.origin_allowed(None));
+    }
+}
@@ -0,0 +1,421 @@
+// Balance Providers - On-chain wallet balances for Solana (SOL, SPL, Token-2022) and EVM chains
+// EVM native and ERC-20 balances are read in one Multicall3 round trip; USD prices come from the platform price feeds
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use log::{info, warn, debug};
+
+use crate::evm_tx::{self, AbiToken, Address, EvmRpcClient};
+use crate::external_apis::ExternalApiClient;
+use crate::solana_tx::RpcSubmitter;
+use crate::wallet_auth::ChainFamily;
+
+pub const SPL_TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+pub const TOKEN_2022_PROGRAM: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+// Deployed at the same address on every major EVM chain
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+const SOL_DECIMALS: u8 = 9;
+const EVM_NATIVE_DECIMALS: u8 = 18;
+
+// (chain id used for `<CHAIN>_RPC_URL`, native symbol)
+const EVM_CHAINS: [(&str, &str); 7] = [
+    ("ethereum", "ETH"),
+    ("bsc", "BNB"),
+    ("polygon", "MATIC"),
+    ("avalanche", "AVAX"),
+    ("arbitrum", "ETH"),
+    ("optimism", "ETH"),
+    ("base", "ETH"),
+];
+
+// ============================================================================
+// TYPES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedToken {
+    pub chain: String,
+    pub symbol: String,
+    pub address: String, // SPL mint or ERC-20 contract
+    pub decimals: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawTokenBalance {
+    pub symbol: String,
+    pub address: String,
+    pub amount: f64,
+    pub decimals: u8,
+    pub program: Option<String>, // Owning token program on Solana
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainBalances {
+    pub chain: String,
+    pub native_symbol: String,
+    pub native_decimals: u8,
+    pub native: f64,
+    pub tokens: Vec<RawTokenBalance>,
+    pub fetched_at: u64,
+}
+
+#[async_trait]
+pub trait BalanceProvider: Send + Sync {
+    fn chain(&self) -> &str;
+    fn family(&self) -> ChainFamily;
+    async fn fetch(&self, owner: &str) -> Result<ChainBalances>;
+}
+
+/// USD price per unit keyed by upper-case symbol
+#[async_trait]
+pub trait UsdPriceSource: Send + Sync {
+    async fn usd_prices(&self) -> Result<HashMap<String, f64>>;
+}
+
+#[async_trait]
+impl UsdPriceSource for ExternalApiClient {
+    async fn usd_prices(&self) -> Result<HashMap<String, f64>> {
+        let quotes = self.get_real_time_prices().await?;
+        Ok(quotes.into_iter()
+            .filter_map(|(pair, price)| pair.strip_suffix("/USDC").map(|symbol| (symbol.to_uppercase(), price)))
+            .collect())
+    }
+}
+
+/// Wrapped assets price as their underlying; stablecoins fall back to $1 without a quote
+pub fn usd_price(prices: &HashMap<String, f64>, symbol: &str) -> Option<f64> {
+    let symbol = symbol.to_uppercase();
+    let underlying = match symbol.as_str() {
+        "WSOL" => "SOL",
+        "WETH" => "ETH",
+        "WBTC" => "BTC",
+        "WBNB" => "BNB",
+        "WMATIC" => "MATIC",
+        "WAVAX" => "AVAX",
+        other => other,
+    };
+    prices.get(underlying).copied().or(match underlying {
+        "USDC" | "USDT" | "DAI" | "USDC.E" => Some(1.0),
+        _ => None,
+    })
+}
+
+fn scale(raw: u128, decimals: u8) -> f64 {
+    raw as f64 / 10f64.powi(decimals as i32)
+}
+
+// ============================================================================
+// SOLANA
+// ============================================================================
+
+pub struct SolanaBalanceProvider {
+    rpc: Arc<RpcSubmitter>,
+    known_mints: HashMap<String, String>, // mint -> symbol
+}
+
+impl SolanaBalanceProvider {
+    pub fn new(rpc: Arc<RpcSubmitter>) -> Self {
+        let known_mints = default_solana_tokens().into_iter()
+            .map(|token| (token.address, token.symbol))
+            .collect();
+        Self { rpc, known_mints }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(Arc::new(RpcSubmitter::from_env()))
+    }
+
+    pub fn with_mint(mut self, mint: &str, symbol: &str) -> Self {
+        self.known_mints.insert(mint.to_string(), symbol.to_string());
+        self
+    }
+
+    async fn token_accounts(&self, owner: &str, program: &str) -> Result<Vec<RawTokenBalance>> {
+        let result = self.rpc.call("getTokenAccountsByOwner", serde_json::json!([
+            owner,
+            {"programId": program},
+            {"encoding": "jsonParsed", "commitment": "confirmed"}
+        ])).await?;
+
+        let mut balances = Vec::new();
+        for account in result["value"].as_array().into_iter().flatten() {
+            let info = &account["account"]["data"]["parsed"]["info"];
+            let Some(mint) = info["mint"].as_str() else {
+                continue;
+            };
+            let decimals = info["tokenAmount"]["decimals"].as_u64().unwrap_or(0) as u8;
+            let raw: u128 = info["tokenAmount"]["amount"].as_str().and_then(|a| a.parse().ok()).unwrap_or(0);
+            if raw == 0 {
+                continue;
+            }
+            balances.push(RawTokenBalance {
+                symbol: self.known_mints.get(mint).cloned().unwrap_or_else(|| mint.to_string()),
+                address: mint.to_string(),
+                amount: scale(raw, decimals),
+                decimals,
+                program: Some(program.to_string()),
+            });
+        }
+        Ok(balances)
+    }
+}
+
+#[async_trait]
+impl BalanceProvider for SolanaBalanceProvider {
+    fn chain(&self) -> &str {
+        "solana"
+    }
+
+    fn family(&self) -> ChainFamily {
+        ChainFamily::Solana
+    }
+
+    async fn fetch(&self, owner: &str) -> Result<ChainBalances> {
+        let (lamports, spl, token_2022) = tokio::join!(
+            self.rpc.call("getBalance", serde_json::json!([owner, {"commitment": "confirmed"}])),
+            self.token_accounts(owner, SPL_TOKEN_PROGRAM),
+            self.token_accounts(owner, TOKEN_2022_PROGRAM),
+        );
+        let lamports = lamports?["value"].as_u64().ok_or_else(|| anyhow!("Invalid getBalance response"))?;
+
+        // A wallet can hold several accounts for one mint; report the total
+        let mut by_mint: HashMap<String, RawTokenBalance> = HashMap::new();
+        for balance in spl?.into_iter().chain(token_2022?) {
+            by_mint.entry(balance.address.clone())
+                .and_modify(|existing| existing.amount += balance.amount)
+                .or_insert(balance);
+        }
+
+        Ok(ChainBalances {
+            chain: "solana".to_string(),
+            native_symbol: "SOL".to_string(),
+            native_decimals: SOL_DECIMALS,
+            native: scale(lamports as u128, SOL_DECIMALS),
+            tokens: by_mint.into_values().collect(),
+            fetched_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+}
+
+pub fn default_solana_tokens() -> Vec<TrackedToken> {
+    [
+        ("USDC", "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", 6),
+        ("USDT", "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", 6),
+        ("WSOL", "So11111111111111111111111111111111111111112", 9),
+        ("RAY", "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R", 6),
+        ("ORCA", "orcaEKTdK7LKz57vaAYr9QeNsVEPfiu6QeMU1kektZE", 6),
+        ("BONK", "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263", 5),
+        ("JUP", "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN", 6),
+        ("MSOL", "mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So", 9),
+    ].into_iter().map(|(symbol, mint, decimals)| TrackedToken {
+        chain: "solana".to_string(),
+        symbol: symbol.to_string(),
+        address: mint.to_string(),
+        decimals,
+    }).collect()
+}
+
+// ============================================================================
+// EVM
+// ============================================================================
+
+pub struct EvmBalanceProvider {
+    chain: String,
+    native_symbol: String,
+    rpc: Arc<EvmRpcClient>,
+    tokens: Vec<(TrackedToken, Address)>,
+    multicall: Address,
+}
+
+impl EvmBalanceProvider {
+    pub fn new(chain: &str, native_symbol: &str, rpc: Arc<EvmRpcClient>, tokens: Vec<TrackedToken>) -> Self {
+        let tokens = tokens.into_iter()
+            .filter(|token| token.chain == chain)
+            .filter_map(|token| match evm_tx::parse_address(&token.address) {
+                Ok(address) => Some((token, address)),
+                Err(e) => {
+                    warn!("⚠️ Skipping {} token {}: {}", chain, token.symbol, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            chain: chain.to_string(),
+            native_symbol: native_symbol.to_string(),
+            rpc,
+            tokens,
+            multicall: evm_tx::parse_address(MULTICALL3_ADDRESS).expect("valid multicall address"),
+        }
+    }
+
+    /// Native balance plus every tracked token in a single eth_call
+    async fn fetch_multicall(&self, owner: Address) -> Result<(u128, Vec<Option<u128>>)> {
+        let mut calls = vec![AbiToken::Tuple(vec![
+            AbiToken::Address(self.multicall),
+            AbiToken::Bool(true),
+            AbiToken::Bytes(evm_tx::encode_call("getEthBalance(address)", &[AbiToken::Address(owner)])),
+        ])];
+        for (_, contract) in &self.tokens {
+            calls.push(AbiToken::Tuple(vec![
+                AbiToken::Address(*contract),
+                AbiToken::Bool(true), // One broken token must not fail the batch
+                AbiToken::Bytes(evm_tx::erc20_balance_of(owner)),
+            ]));
+        }
+
+        let data = evm_tx::encode_call("aggregate3((address,bool,bytes)[])", &[AbiToken::Array(calls)]);
+        let results = decode_aggregate3(&self.rpc.eth_call(&self.multicall, &data).await?)?;
+        if results.len() != self.tokens.len() + 1 {
+            return Err(anyhow!("Multicall returned {} results for {} calls", results.len(), self.tokens.len() + 1));
+        }
+
+        let native = results[0].as_deref()
+            .ok_or_else(|| anyhow!("getEthBalance failed"))
+            .and_then(|data| evm_tx::decode_uint(data, 0))?;
+        let tokens = results[1..].iter()
+            .map(|result| result.as_deref().and_then(|data| evm_tx::decode_uint(data, 0).ok()))
+            .collect();
+        Ok((native, tokens))
+    }
+
+    /// For chains without Multicall3 (local devnets)
+    async fn fetch_individually(&self, owner: Address) -> Result<(u128, Vec<Option<u128>>)> {
+        let native = self.rpc.balance(&owner).await?;
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        for (_, contract) in &self.tokens {
+            let balance = self.rpc.eth_call(contract, &evm_tx::erc20_balance_of(owner)).await
+                .and_then(|data| evm_tx::decode_uint(&data, 0));
+            tokens.push(balance.ok());
+        }
+        Ok((native, tokens))
+    }
+}
+
+#[async_trait]
+impl BalanceProvider for EvmBalanceProvider {
+    fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    fn family(&self) -> ChainFamily {
+        ChainFamily::Ethereum
+    }
+
+    async fn fetch(&self, owner: &str) -> Result<ChainBalances> {
+        let owner_address = evm_tx::parse_address(owner)?;
+        let (native, raw_tokens) = match self.fetch_multicall(owner_address).await {
+            Ok(result) => result,
+            Err(e) => {
+                debug!("Multicall unavailable on {}, querying balances one by one: {}", self.chain, e);
+                self.fetch_individually(owner_address).await?
+            }
+        };
+
+        let tokens = self.tokens.iter().zip(raw_tokens)
+            .filter_map(|((token, _), raw)| match raw {
+                Some(raw) if raw > 0 => Some(RawTokenBalance {
+                    symbol: token.symbol.clone(),
+                    address: token.address.clone(),
+                    amount: scale(raw, token.decimals),
+                    decimals: token.decimals,
+                    program: None,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        Ok(ChainBalances {
+            chain: self.chain.clone(),
+            native_symbol: self.native_symbol.clone(),
+            native_decimals: EVM_NATIVE_DECIMALS,
+            native: scale(native, EVM_NATIVE_DECIMALS),
+            tokens,
+            fetched_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+}
+
+/// `(bool success, bytes returnData)[]`; None for calls that reverted
+fn decode_aggregate3(data: &[u8]) -> Result<Vec<Option<Vec<u8>>>> {
+    let array_offset = evm_tx::decode_uint(data, 0)? as usize;
+    let array = data.get(array_offset..).ok_or_else(|| anyhow!("Multicall array offset out of range"))?;
+    let len = evm_tx::decode_uint(array, 0)? as usize;
+    let elements = &array[32..];
+
+    (0..len).map(|i| {
+        let tuple_offset = evm_tx::decode_uint(elements, i)? as usize;
+        let tuple = elements.get(tuple_offset..).ok_or_else(|| anyhow!("Multicall result offset out of range"))?;
+        let success = evm_tx::decode_uint(tuple, 0)? == 1;
+        let bytes_offset = evm_tx::decode_uint(tuple, 1)? as usize;
+        let bytes = tuple.get(bytes_offset..).ok_or_else(|| anyhow!("Multicall bytes offset out of range"))?;
+        let bytes_len = evm_tx::decode_uint(bytes, 0)? as usize;
+        let body = bytes.get(32..32 + bytes_len).ok_or_else(|| anyhow!("Multicall return data truncated"))?;
+        Ok(if success { Some(body.to_vec()) } else { None })
+    }).collect()
+}
+
+pub fn default_erc20_tokens() -> Vec<TrackedToken> {
+    [
+        ("ethereum", "USDC", "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", 6),
+        ("ethereum", "USDT", "0xdAC17F958D2ee523a2206206994597C13D831ec7", 6),
+        ("ethereum", "DAI", "0x6B175474E89094C44Da98b954EedeAC495271d0F", 18),
+        ("ethereum", "WETH", "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", 18),
+        ("ethereum", "WBTC", "0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599", 8),
+    ].into_iter().map(|(chain, symbol, address, decimals)| TrackedToken {
+        chain: chain.to_string(),
+        symbol: symbol.to_string(),
+        address: address.to_string(),
+        decimals,
+    }).collect()
+}
+
+/// Defaults plus DEXTER_ERC20_TOKENS entries (`chain:SYMBOL:0xcontract:decimals`, comma separated)
+pub fn erc20_tokens_from_env() -> Vec<TrackedToken> {
+    let mut tokens = default_erc20_tokens();
+    if let Ok(configured) = std::env::var("DEXTER_ERC20_TOKENS") {
+        for entry in configured.split(',').filter(|e| !e.trim().is_empty()) {
+            let parts: Vec<&str> = entry.trim().split(':').collect();
+            match parts.as_slice() {
+                [chain, symbol, address, decimals] => match decimals.parse() {
+                    Ok(decimals) => tokens.push(TrackedToken {
+                        chain: chain.to_lowercase(),
+                        symbol: symbol.to_uppercase(),
+                        address: address.to_string(),
+                        decimals,
+                    }),
+                    Err(_) => warn!("⚠️ Invalid decimals in DEXTER_ERC20_TOKENS entry '{}'", entry),
+                },
+                _ => warn!("⚠️ Ignoring malformed DEXTER_ERC20_TOKENS entry '{}'", entry),
+            }
+        }
+    }
+    tokens
+}
+
+/// Solana always; each EVM chain whose `<CHAIN>_RPC_URL` is set to a real endpoint
+pub fn providers_from_env() -> Vec<Arc<dyn BalanceProvider>> {
+    let mut providers: Vec<Arc<dyn BalanceProvider>> = vec![Arc::new(SolanaBalanceProvider::from_env())];
+    let tokens = erc20_tokens_from_env();
+
+    for (chain, native_symbol) in EVM_CHAINS {
+        let Ok(rpc_url) = std::env::var(format!("{}_RPC_URL", chain.to_uppercase())) else {
+            continue;
+        };
+        if rpc_url.is_empty() || rpc_url.contains("YOUR_API_KEY") {
+            continue;
+        }
+        providers.push(Arc::new(EvmBalanceProvider::new(
+            chain,
+            native_symbol,
+            Arc::new(EvmRpcClient::new(&rpc_url)),
+            tokens.clone(),
+        )));
+    }
+
+    info!("💼 Balance providers: {}", providers.iter().map(|p| p.chain()).collect::<Vec<_>>().join(", "));
+    providers
+}
@@ -0,0 +1,1000 @@
+// Private Bundles - Ordered transaction bundles with tips for Jito (Solana) and eth_sendBundle relays (EVM)
+// Builds tip transactions, simulates through the relay, submits and tracks inclusion until landed or expired
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use reqwest::Client;
+use anyhow::{Result, anyhow, Context};
+use async_trait::async_trait;
+use log::{info, warn, debug};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use rand::seq::SliceRandom;
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::VersionedTransaction,
+};
+
+use crate::evm_tx::{self, Address, EvmFees, EvmRpcClient, EvmSigner, EvmTransaction, SignedEvmTransaction};
+use crate::solana_tx::{self, SolanaTxBuilder};
+
+/// Jito block engine limit, tip transaction included
+pub const MAX_JITO_BUNDLE_SIZE: usize = 5;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Mainnet tip accounts; one is picked at random per bundle to spread write-lock contention
+const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+// ============================================================================
+// BUNDLES
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub enum BundlePayload {
+    Solana(Vec<VersionedTransaction>),
+    Evm {
+        transactions: Vec<SignedEvmTransaction>,
+        target_block: u64,
+    },
+}
+
+/// Transactions execute in order and atomically: all land in the same slot/block or none do.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub id: String,
+    pub payload: BundlePayload,
+    pub tip: u128, // Lamports or wei paid to the block builder
+    pub created_at: u64,
+}
+
+impl Bundle {
+    pub fn chain(&self) -> &'static str {
+        match self.payload {
+            BundlePayload::Solana(_) => "solana",
+            BundlePayload::Evm { .. } => "evm",
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.payload {
+            BundlePayload::Solana(transactions) => transactions.len(),
+            BundlePayload::Evm { transactions, .. } => transactions.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Base64 wire encoding for Solana, 0x-prefixed raw transactions for EVM
+    fn encoded_transactions(&self) -> Result<Vec<String>> {
+        match &self.payload {
+            BundlePayload::Solana(transactions) => transactions.iter()
+                .map(|tx| bincode::serialize(tx).map(|bytes| BASE64.encode(bytes)).context("Failed to serialize transaction"))
+                .collect(),
+            BundlePayload::Evm { transactions, .. } => Ok(transactions.iter().map(|tx| tx.raw_hex()).collect()),
+        }
+    }
+}
+
+pub struct BundleBuilder;
+
+impl BundleBuilder {
+    /// Append a tip transfer to a Jito tip account as the last transaction
+    pub fn jito(
+        transactions: Vec<VersionedTransaction>,
+        payer: &Keypair,
+        tip_lamports: u64,
+        recent_blockhash: Hash,
+    ) -> Result<Bundle> {
+        if transactions.is_empty() || transactions.len() >= MAX_JITO_BUNDLE_SIZE {
+            return Err(anyhow!("Jito bundles take 1-{} transactions plus the tip", MAX_JITO_BUNDLE_SIZE - 1));
+        }
+        let tip_account: Pubkey = JITO_TIP_ACCOUNTS
+            .choose(&mut rand::thread_rng())
+            .expect("non-empty tip account list")
+            .parse()
+            .map_err(|e| anyhow!("Invalid tip account: {}", e))?;
+
+        let tip = SolanaTxBuilder::new(payer.pubkey())
+            .instruction(solana_tx::transfer_instruction(&payer.pubkey(), &tip_account, tip_lamports))
+            .build_and_sign(recent_blockhash, &[payer])?;
+
+        let mut transactions = transactions;
+        transactions.push(tip);
+        Ok(Bundle {
+            id: uuid::Uuid::new_v4().to_string(),
+            payload: BundlePayload::Solana(transactions),
+            tip: tip_lamports as u128,
+            created_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// EVM bundle for one target block; the optional tip transaction is placed last
+    pub fn evm(
+        transactions: Vec<SignedEvmTransaction>,
+        target_block: u64,
+        tip: Option<(SignedEvmTransaction, u128)>,
+    ) -> Result<Bundle> {
+        if transactions.is_empty() {
+            return Err(anyhow!("Bundle has no transactions"));
+        }
+        let mut transactions = transactions;
+        let tip_amount = match tip {
+            Some((tip_tx, amount)) => {
+                transactions.push(tip_tx);
+                amount
+            }
+            None => 0,
+        };
+        Ok(Bundle {
+            id: uuid::Uuid::new_v4().to_string(),
+            payload: BundlePayload::Evm { transactions, target_block },
+            tip: tip_amount,
+            created_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// Plain value transfer to the builder's fee recipient
+    pub fn evm_tip_transaction(
+        signer: &EvmSigner,
+        chain_id: u64,
+        nonce: u64,
+        recipient: Address,
+        tip_wei: u128,
+        fees: EvmFees,
+    ) -> Result<SignedEvmTransaction> {
+        EvmTransaction {
+            chain_id,
+            nonce,
+            to: Some(recipient),
+            value: tip_wei,
+            data: Vec::new(),
+            gas_limit: 21_000,
+            fees,
+        }.sign(signer)
+    }
+}
+
+// ============================================================================
+// RELAYS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSimulation {
+    pub success: bool,
+    pub error: Option<String>,
+    pub units_consumed: Option<u64>, // Compute units (Solana) or gas (EVM)
+    pub builder_payment: Option<u128>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BundleStatus {
+    Pending,
+    Landed { slot: u64 },
+    Failed { reason: String },
+    Expired,
+}
+
+impl BundleStatus {
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, BundleStatus::Pending)
+    }
+}
+
+#[async_trait]
+pub trait BundleRelay: Send + Sync {
+    fn name(&self) -> &str;
+    fn supports(&self, bundle: &Bundle) -> bool;
+    fn can_simulate(&self) -> bool {
+        true
+    }
+    async fn simulate(&self, bundle: &Bundle) -> Result<BundleSimulation>;
+    /// Returns the relay's bundle id
+    async fn submit(&self, bundle: &Bundle) -> Result<String>;
+    async fn status(&self, bundle: &Bundle, relay_bundle_id: &str) -> Result<BundleStatus>;
+}
+
+/// Jito block engine; simulation needs a Jito-Solana RPC exposing `simulateBundle`
+pub struct JitoRelay {
+    client: Client,
+    block_engine_url: String,
+    simulate_url: Option<String>,
+}
+
+impl JitoRelay {
+    pub fn new(block_engine_url: &str, simulate_url: Option<String>) -> Self {
+        Self {
+            client: http_client(),
+            block_engine_url: block_engine_url.trim_end_matches('/').to_string(),
+            simulate_url,
+        }
+    }
+
+    async fn call(&self, url: &str, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        json_rpc(&self.client, url, method, params, None).await
+    }
+
+    fn bundles_url(&self) -> String {
+        format!("{}/api/v1/bundles", self.block_engine_url)
+    }
+}
+
+#[async_trait]
+impl BundleRelay for JitoRelay {
+    fn name(&self) -> &str {
+        "jito"
+    }
+
+    fn supports(&self, bundle: &Bundle) -> bool {
+        matches!(bundle.payload, BundlePayload::Solana(_))
+    }
+
+    fn can_simulate(&self) -> bool {
+        self.simulate_url.is_some()
+    }
+
+    async fn simulate(&self, bundle: &Bundle) -> Result<BundleSimulation> {
+        let url = self.simulate_url.as_deref().ok_or_else(|| anyhow!("No Jito simulation endpoint configured"))?;
+        let transactions = bundle.encoded_transactions()?;
+        let result = self.call(url, "simulateBundle", serde_json::json!([
+            {"encodedTransactions": transactions},
+            {"skipSigVerify": true, "replaceRecentBlockhash": false},
+        ])).await?;
+        let value = &result["value"];
+        let failure = &value["summary"]["failed"];
+        let units = value["transactionResults"].as_array()
+            .map(|results| results.iter().filter_map(|r| r["unitsConsumed"].as_u64()).sum());
+        Ok(BundleSimulation {
+            success: failure.is_null(),
+            error: (!failure.is_null()).then(|| failure["error"].to_string()),
+            units_consumed: units,
+            builder_payment: Some(bundle.tip),
+        })
+    }
+
+    async fn submit(&self, bundle: &Bundle) -> Result<String> {
+        let transactions = bundle.encoded_transactions()?;
+        let result = self.call(&self.bundles_url(), "sendBundle", serde_json::json!([
+            transactions, {"encoding": "base64"}
+        ])).await?;
+        result.as_str().map(str::to_string).ok_or_else(|| anyhow!("Invalid sendBundle response: {}", result))
+    }
+
+    async fn status(&self, _bundle: &Bundle, relay_bundle_id: &str) -> Result<BundleStatus> {
+        let result = self.call(&self.bundles_url(), "getInflightBundleStatuses", serde_json::json!([[relay_bundle_id]])).await?;
+        let entry = &result["value"][0];
+        Ok(match entry["status"].as_str().unwrap_or("Pending") {
+            "Landed" => BundleStatus::Landed { slot: entry["landed_slot"].as_u64().unwrap_or(0) },
+            "Failed" => BundleStatus::Failed { reason: "Rejected by block engine".to_string() },
+            // Unknown bundle ids age out after five minutes
+            "Invalid" => BundleStatus::Expired,
+            _ => BundleStatus::Pending,
+        })
+    }
+}
+
+/// Flashbots-compatible relay: `eth_callBundle` / `eth_sendBundle` with an X-Flashbots-Signature auth header
+pub struct FlashbotsRelay {
+    client: Client,
+    relay_url: String,
+    auth_signer: EvmSigner,
+    rpc: Arc<EvmRpcClient>, // Inclusion is checked against the chain, relays do not report it reliably
+}
+
+impl FlashbotsRelay {
+    pub fn new(relay_url: &str, auth_signer: EvmSigner, rpc: Arc<EvmRpcClient>) -> Self {
+        Self {
+            client: http_client(),
+            relay_url: relay_url.to_string(),
+            auth_signer,
+            rpc,
+        }
+    }
+
+    async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        json_rpc(&self.client, &self.relay_url, method, params, Some(&self.auth_signer)).await
+    }
+
+    fn target_block(bundle: &Bundle) -> Result<u64> {
+        match bundle.payload {
+            BundlePayload::Evm { target_block, .. } => Ok(target_block),
+            BundlePayload::Solana(_) => Err(anyhow!("Not an EVM bundle")),
+        }
+    }
+}
+
+#[async_trait]
+impl BundleRelay for FlashbotsRelay {
+    fn name(&self) -> &str {
+        "flashbots"
+    }
+
+    fn supports(&self, bundle: &Bundle) -> bool {
+        matches!(bundle.payload, BundlePayload::Evm { .. })
+    }
+
+    async fn simulate(&self, bundle: &Bundle) -> Result<BundleSimulation> {
+        let result = self.call("eth_callBundle", serde_json::json!([{
+            "txs": bundle.encoded_transactions()?,
+            "blockNumber": evm_tx::quantity(Self::target_block(bundle)? as u128),
+            "stateBlockNumber": "latest",
+        }])).await?;
+        let error = result["results"].as_array()
+            .and_then(|results| results.iter().find_map(|r| {
+                r.get("error").or_else(|| r.get("revert")).filter(|e| !e.is_null()).map(|e| e.to_string())
+            }));
+        Ok(BundleSimulation {
+            success: error.is_none(),
+            error,
+            units_consumed: result["totalGasUsed"].as_u64(),
+            builder_payment: result["coinbaseDiff"].as_str().and_then(|v| v.parse().ok()),
+        })
+    }
+
+    async fn submit(&self, bundle: &Bundle) -> Result<String> {
+        let result = self.call("eth_sendBundle", serde_json::json!([{
+            "txs": bundle.encoded_transactions()?,
+            "blockNumber": evm_tx::quantity(Self::target_block(bundle)? as u128),
+        }])).await?;
+        result["bundleHash"].as_str().map(str::to_string)
+            .ok_or_else(|| anyhow!("Invalid eth_sendBundle response: {}", result))
+    }
+
+    async fn status(&self, bundle: &Bundle, _relay_bundle_id: &str) -> Result<BundleStatus> {
+        let BundlePayload::Evm { transactions, target_block } = &bundle.payload else {
+            return Err(anyhow!("Not an EVM bundle"));
+        };
+        let first = transactions.first().ok_or_else(|| anyhow!("Bundle has no transactions"))?;
+        if let Some(receipt) = self.rpc.transaction_receipt(&first.hash_hex()).await? {
+            return Ok(if receipt.block_number == *target_block {
+                BundleStatus::Landed { slot: receipt.block_number }
+            } else {
+                // Same nonce mined outside the bundle
+                BundleStatus::Failed { reason: format!("Mined outside the bundle in block {}", receipt.block_number) }
+            });
+        }
+        if self.rpc.block_number().await? > *target_block {
+            return Ok(BundleStatus::Expired);
+        }
+        Ok(BundleStatus::Pending)
+    }
+}
+
+/// In-process relay that accepts everything and lands after a few polls; for local runs without a block engine
+pub struct MockRelay {
+    land_after_polls: u32,
+    fail_simulation: AtomicBool,
+    polls: RwLock<HashMap<String, u32>>,
+}
+
+impl MockRelay {
+    pub fn new(land_after_polls: u32) -> Self {
+        Self {
+            land_after_polls,
+            fail_simulation: AtomicBool::new(false),
+            polls: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_fail_simulation(&self, fail: bool) {
+        self.fail_simulation.store(fail, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl BundleRelay for MockRelay {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn supports(&self, _bundle: &Bundle) -> bool {
+        true
+    }
+
+    async fn simulate(&self, bundle: &Bundle) -> Result<BundleSimulation> {
+        let fail = self.fail_simulation.load(Ordering::Relaxed);
+        Ok(BundleSimulation {
+            success: !fail,
+            error: fail.then(|| "Simulated revert".to_string()),
+            units_consumed: Some(bundle.len() as u64 * 100_000),
+            builder_payment: Some(bundle.tip),
+        })
+    }
+
+    async fn submit(&self, bundle: &Bundle) -> Result<String> {
+        let relay_id = format!("mock-{}", bundle.id);
+        self.polls.write().await.insert(relay_id.clone(), 0);
+        Ok(relay_id)
+    }
+
+    async fn status(&self, _bundle: &Bundle, relay_bundle_id: &str) -> Result<BundleStatus> {
+        let mut polls = self.polls.write().await;
+        let count = polls.get_mut(relay_bundle_id).ok_or_else(|| anyhow!("Unknown bundle {}", relay_bundle_id))?;
+        *count += 1;
+        Ok(if *count >= self.land_after_polls {
+            BundleStatus::Landed { slot: chrono::Utc::now().timestamp() as u64 }
+        } else {
+            BundleStatus::Pending
+        })
+    }
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build()
+        .unwrap_or_default()
+}
+
+async fn json_rpc(
+    client: &Client,
+    url: &str,
+    method: &str,
+    params: serde_json::Value,
+    auth_signer: Option<&EvmSigner>,
+) -> Result<serde_json::Value> {
+    let body = serde_json::to_string(&serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    }))?;
+
+    let mut request = client.post(url).header("Content-Type", "application/json");
+    if let Some(signer) = auth_signer {
+        // Flashbots auth: EIP-191 signature over the hex keccak256 of the body
+        let digest = format!("0x{}", hex::encode(evm_tx::keccak256(body.as_bytes())));
+        let signature = signer.sign_message(digest.as_bytes())?;
+        request = request.header(
+            "X-Flashbots-Signature",
+            format!("{}:0x{}", evm_tx::format_address(&signer.address()), hex::encode(signature)),
+        );
+    }
+
+    let response = request.body(body).send().await.with_context(|| format!("{} request failed", method))?;
+    let mut data: serde_json::Value = response.json().await.with_context(|| format!("{} returned invalid JSON", method))?;
+    if let Some(error) = data.get("error") {
+        return Err(anyhow!("{} failed: {}", method, error));
+    }
+    Ok(data["result"].take())
+}
+
+// ============================================================================
+// BUNDLE MANAGER
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManagerConfig {
+    pub simulate_before_submit: bool,
+    pub poll_interval_ms: u64,
+    pub max_tracking_secs: u64, // Give up and mark expired after this long
+    pub max_history: usize,
+}
+
+impl Default for BundleManagerConfig {
+    fn default() -> Self {
+        Self {
+            simulate_before_submit: true,
+            poll_interval_ms: 1000,
+            max_tracking_secs: 120,
+            max_history: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedBundle {
+    pub bundle_id: String,
+    pub relay: String,
+    pub relay_bundle_id: String,
+    pub chain: String,
+    pub transactions: usize,
+    pub tip: u128,
+    pub simulation: Option<BundleSimulation>,
+    pub status: BundleStatus,
+    pub submitted_at: u64,
+    pub updated_at: u64,
+}
+
+struct InFlight {
+    relay: Arc<dyn BundleRelay>,
+    bundle: Bundle,
+}
+
+pub struct BundleManager {
+    relays: Arc<RwLock<Vec<Arc<dyn BundleRelay>>>>,
+    config: Arc<RwLock<BundleManagerConfig>>,
+    in_flight: Arc<RwLock<HashMap<String, InFlight>>>,
+    tracked: Arc<RwLock<Vec<TrackedBundle>>>,
+    events: broadcast::Sender<TrackedBundle>,
+}
+
+impl BundleManager {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(100);
+        Self {
+            relays: Arc::new(RwLock::new(Vec::new())),
+            config: Arc::new(RwLock::new(BundleManagerConfig::default())),
+            in_flight: Arc::new(RwLock::new(HashMap::new())),
+            tracked: Arc::new(RwLock::new(Vec::new())),
+            events,
+        }
+    }
+
+    /// Relays configured through JITO_* / FLASHBOTS_* variables; BUNDLE_MOCK_RELAY=true adds the in-process relay
+    pub async fn from_env() -> Self {
+        let manager = Self::new();
+        if let Ok(url) = std::env::var("JITO_BLOCK_ENGINE_URL") {
+            let simulate_url = std::env::var("JITO_SIMULATE_URL").ok().filter(|u| !u.is_empty());
+            manager.add_relay(Arc::new(JitoRelay::new(&url, simulate_url))).await;
+        }
+        if let Ok(key) = std::env::var("FLASHBOTS_AUTH_KEY") {
+            match EvmSigner::from_hex(&key) {
+                Ok(signer) => {
+                    let relay_url = std::env::var("FLASHBOTS_RELAY_URL")
+                        .unwrap_or_else(|_| "https://relay.flashbots.net".to_string());
+                    let rpc_url = std::env::var("ETHEREUM_RPC_URL")
+                        .unwrap_or_else(|_| "http://127.0.0.1:8545".to_string());
+                    manager.add_relay(Arc::new(FlashbotsRelay::new(&relay_url, signer, Arc::new(EvmRpcClient::new(&rpc_url))))).await;
+                }
+                Err(e) => warn!("⚠️ Ignoring FLASHBOTS_AUTH_KEY: {}", e),
+            }
+        }
+        if std::env::var("BUNDLE_MOCK_RELAY").map(|v| v == "true" || v == "1").unwrap_or(false) {
+            manager.add_relay(Arc::new(MockRelay::new(2))).await;
+        }
+        manager
+    }
+
+    pub async fn add_relay(&self, relay: Arc<dyn BundleRelay>) {
+        info!("📦 Registered bundle relay {}", relay.name());
+        self.relays.write().await.push(relay);
+    }
+
+    pub async fn get_config(&self) -> BundleManagerConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: BundleManagerConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TrackedBundle> {
+        self.events.subscribe()
+    }
+
+    /// Simulate once, then submit to every relay that accepts the bundle
+    pub async fn send(&self, bundle: Bundle) -> Result<Vec<TrackedBundle>> {
+        let config = self.config.read().await.clone();
+        let relays: Vec<_> = self.relays.read().await.iter()
+            .filter(|r| r.supports(&bundle))
+            .cloned()
+            .collect();
+        if relays.is_empty() {
+            return Err(anyhow!("No relay configured for {} bundles", bundle.chain()));
+        }
+
+        let mut simulation = None;
+        if config.simulate_before_submit {
+            if let Some(relay) = relays.iter().find(|r| r.can_simulate()) {
+                let result = relay.simulate(&bundle).await?;
+                if !result.success {
+                    return Err(anyhow!("Bundle {} failed simulation on {}: {}",
+                                       bundle.id, relay.name(), result.error.clone().unwrap_or_default()));
+                }
+                simulation = Some(result);
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut submitted = Vec::new();
+        for relay in relays {
+            match relay.submit(&bundle).await {
+                Ok(relay_bundle_id) => {
+                    info!("📦 Bundle {} ({} txs, tip {}) submitted to {} as {}",
+                          bundle.id, bundle.len(), bundle.tip, relay.name(), relay_bundle_id);
+                    let record = TrackedBundle {
+                        bundle_id: bundle.id.clone(),
+                        relay: relay.name().to_string(),
+                        relay_bundle_id: relay_bundle_id.clone(),
+                        chain: bundle.chain().to_string(),
+                        transactions: bundle.len(),
+                        tip: bundle.tip,
+                        simulation: simulation.clone(),
+                        status: BundleStatus::Pending,
+                        submitted_at: now,
+                        updated_at: now,
+                    };
+                    self.in_flight.write().await.insert(relay_bundle_id, InFlight { relay, bundle: bundle.clone() });
+                    self.record(record.clone(), config.max_history).await;
+                    submitted.push(record);
+                }
+                Err(e) => warn!("⚠️ {} rejected bundle {}: {}", relay.name(), bundle.id, e),
+            }
+        }
+
+        if submitted.is_empty() {
+            return Err(anyhow!("Every relay rejected bundle {}", bundle.id));
+        }
+        Ok(submitted)
+    }
+
+    pub async fn start(&self) {
+        loop {
+            let interval = self.config.read().await.poll_interval_ms.max(100);
+            tokio::time::sleep(tokio::time::Duration::from_millis(interval)).await;
+            self.poll_once().await;
+        }
+    }
+
+    /// Refresh the status of every in-flight bundle
+    pub async fn poll_once(&self) {
+        let config = self.config.read().await.clone();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let pending: Vec<(String, Arc<dyn BundleRelay>, Bundle)> = self.in_flight.read().await.iter()
+            .map(|(id, f)| (id.clone(), f.relay.clone(), f.bundle.clone()))
+            .collect();
+
+        for (relay_bundle_id, relay, bundle) in pending {
+            let status = match relay.status(&bundle, &relay_bundle_id).await {
+                Ok(status) => status,
+                Err(e) => {
+                    debug!("Bundle status for {} unavailable: {}", relay_bundle_id, e);
+                    BundleStatus::Pending
+                }
+            };
+            let status = if !status.is_terminal() && now.saturating_sub(bundle.created_at) > config.max_tracking_secs {
+                BundleStatus::Expired
+            } else {
+                status
+            };
+            if !status.is_terminal() {
+                continue;
+            }
+
+            self.in_flight.write().await.remove(&relay_bundle_id);
+            match &status {
+                BundleStatus::Landed { slot } => info!("✅ Bundle {} landed via {} at {}", bundle.id, relay.name(), slot),
+                other => warn!("⚠️ Bundle {} via {} ended {:?}", bundle.id, relay.name(), other),
+            }
+
+            let mut tracked = self.tracked.write().await;
+            if let Some(record) = tracked.iter_mut().find(|t| t.relay_bundle_id == relay_bundle_id) {
+                record.status = status;
+                record.updated_at = now;
+                let _ = self.events.send(record.clone());
+            }
+        }
+    }
+
+    pub async fn get_bundles(&self, limit: usize) -> Vec<TrackedBundle> {
+        self.tracked.read().await.iter().rev().take(limit).cloned().collect()
+    }
+
+    async fn record(&self, record: TrackedBundle, max_history: usize) {
+        let _ = self.events.send(record.clone());
+        let mut tracked = self.tracked.write().await;
+        tracked.push(record);
+        let excess = tracked.len().saturating_sub(max_history);
+        tracked.drain(..excess);
+    }
+}
+
+impl Clone for BundleManager {
+    fn clone(&self) -> Self {
+        Self {
+            relays: self.relays.clone(),
+            config: self.config.clone(),
+            in_flight: self.in_flight.clone(),
+            tracked: self.tracked.clone(),
+            events: self.events.clone(),
+        }
+    }
+}
+
+impl Default for BundleManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+    use warp::Filter;
+
+    #[derive(Debug, Clone)]
+    struct RelayRequest {
+        path: String,
+        method: String,
+        params: serde_json::Value,
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// JSON-RPC relay on localhost: replies per method in order, the last one repeating
+    struct MockRelayServer {
+        url: String,
+        requests: Arc<StdMutex<Vec<RelayRequest>>>,
+    }
+
+    impl MockRelayServer {
+        async fn start(routes: Vec<(&str, Vec<serde_json::Value>)>) -> Self {
+            let replies: HashMap<String, Vec<serde_json::Value>> = routes.into_iter()
+                .map(|(method, replies)| (method.to_string(), replies))
+                .collect();
+            let replies = Arc::new(StdMutex::new(replies));
+            let requests = Arc::new(StdMutex::new(Vec::new()));
+
+            let recorded = requests.clone();
+            let route = warp::post()
+                .and(warp::path::full())
+                .and(warp::header::headers_cloned())
+                .and(warp::body::bytes())
+                .map(move |path: warp::path::FullPath, headers: warp::http::HeaderMap, body: warp::hyper::body::Bytes| {
+                    let body = String::from_utf8_lossy(&body).to_string();
+                    let request: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+                    let method = request["method"].as_str().unwrap_or_default().to_string();
+                    recorded.lock().unwrap().push(RelayRequest {
+                        path: path.as_str().to_string(),
+                        method: method.clone(),
+                        params: request["params"].clone(),
+                        headers: headers.iter()
+                            .map(|(k, v)| (k.as_str().to_lowercase(), v.to_str().unwrap_or_default().to_string()))
+                            .collect(),
+                        body,
+                    });
+                    let mut replies = replies.lock().unwrap();
+                    let reply = match replies.get_mut(&method) {
+                        Some(queue) if queue.len() > 1 => queue.remove(0),
+                        Some(queue) if !queue.is_empty() => queue[0].clone(),
+                        _ => serde_json::json!({ "error": { "code": -32601, "message": format!("{} not mocked", method) } }),
+                    };
+                    warp::reply::json(&reply)
+                });
+
+            let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(server);
+            Self { url: format!("http://{}", addr), requests }
+        }
+
+        fn requests(&self, method: &str) -> Vec<RelayRequest> {
+            self.requests.lock().unwrap().iter().filter(|r| r.method == method).cloned().collect()
+        }
+    }
+
+    fn result(value: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": value })
+    }
+
+    fn rpc_error(message: &str) -> serde_json::Value {
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": message } })
+    }
+
+    fn jito_bundle(payer: &Keypair) -> Bundle {
+        let blockhash = Hash::new_from_array([9; 32]);
+        let swap = SolanaTxBuilder::new(payer.pubkey())
+            .instruction(solana_tx::transfer_instruction(&payer.pubkey(), &Pubkey::new_unique(), 1_000))
+            .build_and_sign(blockhash, &[payer])
+            .unwrap();
+        BundleBuilder::jito(vec![swap], payer, 10_000, blockhash).unwrap()
+    }
+
+    async fn manager_with(relay: Arc<dyn BundleRelay>) -> BundleManager {
+        let manager = BundleManager::new();
+        manager.add_relay(relay).await;
+        manager
+    }
+
+    #[tokio::test]
+    async fn jito_bundle_is_simulated_submitted_and_tracked_until_it_lands() {
+        let relay = MockRelayServer::start(vec![
+            ("simulateBundle", vec![result(serde_json::json!({ "value": {
+                "summary": "succeeded",
+                "transactionResults": [{ "unitsConsumed": 450 }, { "unitsConsumed": 150 }],
+            }}))]),
+            ("sendBundle", vec![result(serde_json::json!("jito-bundle-1"))]),
+            ("getInflightBundleStatuses", vec![
+                result(serde_json::json!({ "value": [{ "bundle_id": "jito-bundle-1", "status": "Pending" }] })),
+                result(serde_json::json!({ "value": [{ "bundle_id": "jito-bundle-1", "status": "Landed", "landed_slot": 321 }] })),
+            ]),
+        ]).await;
+        let manager = manager_with(Arc::new(JitoRelay::new(&relay.url, Some(format!("{}/simulate", relay.url))))).await;
+        let mut events = manager.subscribe();
+        let payer = Keypair::new();
+        let bundle = jito_bundle(&payer);
+
+        let submitted = manager.send(bundle.clone()).await.unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!((submitted[0].relay.as_str(), submitted[0].relay_bundle_id.as_str()), ("jito", "jito-bundle-1"));
+        assert_eq!(submitted[0].status, BundleStatus::Pending);
+        assert_eq!(submitted[0].simulation.as_ref().unwrap().units_consumed, Some(600));
+        assert_eq!(relay.requests("simulateBundle")[0].path, "/simulate");
+
+        // Both transactions go out base64-encoded, the tip transfer last
+        let sent = &relay.requests("sendBundle")[0];
+        assert_eq!(sent.path, "/api/v1/bundles");
+        assert_eq!(sent.params[1]["encoding"], "base64");
+        let encoded: Vec<String> = serde_json::from_value(sent.params[0].clone()).unwrap();
+        assert_eq!(encoded, bundle.encoded_transactions().unwrap());
+        let tip: VersionedTransaction = bincode::deserialize(&BASE64.decode(&encoded[1]).unwrap()).unwrap();
+        let tip_accounts: Vec<Pubkey> = JITO_TIP_ACCOUNTS.iter().map(|a| a.parse().unwrap()).collect();
+        assert!(tip.message.static_account_keys().iter().any(|key| tip_accounts.contains(key)));
+        assert_eq!(events.recv().await.unwrap().status, BundleStatus::Pending);
+
+        manager.poll_once().await;
+        assert_eq!(manager.get_bundles(10).await[0].status, BundleStatus::Pending);
+        manager.poll_once().await;
+        assert_eq!(manager.get_bundles(10).await[0].status, BundleStatus::Landed { slot: 321 });
+        assert_eq!(events.recv().await.unwrap().status, BundleStatus::Landed { slot: 321 });
+        assert_eq!(relay.requests("getInflightBundleStatuses")[0].params, serde_json::json!([["jito-bundle-1"]]));
+
+        // Landed bundles are no longer polled
+        manager.poll_once().await;
+        assert_eq!(relay.requests("getInflightBundleStatuses").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_simulation_stops_the_bundle_before_submission() {
+        let relay = MockRelayServer::start(vec![
+            ("simulateBundle", vec![result(serde_json::json!({ "value": {
+                "summary": { "failed": { "error": { "TransactionFailure": "InsufficientFundsForFee" } } },
+                "transactionResults": [],
+            }}))]),
+            ("sendBundle", vec![result(serde_json::json!("never"))]),
+        ]).await;
+        let manager = manager_with(Arc::new(JitoRelay::new(&relay.url, Some(relay.url.clone())))).await;
+
+        let error = manager.send(jito_bundle(&Keypair::new())).await.unwrap_err();
+        assert!(error.to_string().contains("failed simulation"), "{}", error);
+        assert!(error.to_string().contains("InsufficientFundsForFee"), "{}", error);
+        assert!(relay.requests("sendBundle").is_empty());
+        assert!(manager.get_bundles(10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejected_bundles_are_reported_and_never_tracked() {
+        let relay = MockRelayServer::start(vec![
+            ("sendBundle", vec![rpc_error("bundle contains an already processed transaction")]),
+        ]).await;
+        let manager = manager_with(Arc::new(JitoRelay::new(&relay.url, None))).await;
+
+        let error = manager.send(jito_bundle(&Keypair::new())).await.unwrap_err();
+        assert!(error.to_string().contains("Every relay rejected"), "{}", error);
+        assert_eq!(relay.requests("sendBundle").len(), 1);
+        assert!(relay.requests("simulateBundle").is_empty());
+        assert!(manager.get_bundles(10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn block_engine_failed_and_invalid_statuses_are_terminal() {
+        let relay = MockRelayServer::start(vec![
+            ("sendBundle", vec![result(serde_json::json!("jito-a")), result(serde_json::json!("jito-b"))]),
+            ("getInflightBundleStatuses", vec![
+                result(serde_json::json!({ "value": [{ "status": "Failed" }] })),
+                result(serde_json::json!({ "value": [{ "status": "Invalid" }] })),
+            ]),
+        ]).await;
+        let jito = JitoRelay::new(&relay.url, None);
+        let bundle = jito_bundle(&Keypair::new());
+
+        assert_eq!(jito.submit(&bundle).await.unwrap(), "jito-a");
+        assert!(matches!(jito.status(&bundle, "jito-a").await.unwrap(), BundleStatus::Failed { .. }));
+        assert_eq!(jito.status(&bundle, "jito-b").await.unwrap(), BundleStatus::Expired);
+    }
+
+    fn evm_bundle(signer: &EvmSigner, target_block: u64) -> Bundle {
+        let fees = EvmFees::Eip1559 { max_fee_per_gas: 30_000_000_000, max_priority_fee_per_gas: 0 };
+        let swap = EvmTransaction {
+            chain_id: 1,
+            nonce: 4,
+            to: Some([0x11; 20]),
+            value: 0,
+            data: evm_tx::erc20_transfer([0x22; 20], 1_000),
+            gas_limit: 60_000,
+            fees,
+        }.sign(signer).unwrap();
+        let tip = BundleBuilder::evm_tip_transaction(signer, 1, 5, [0x33; 20], 5_000, fees).unwrap();
+        BundleBuilder::evm(vec![swap], target_block, Some((tip, 5_000))).unwrap()
+    }
+
+    const AUTH_KEY: &str = "0x8d5366123cb560bb606379f90a0bfd4769eecc0557f1b362dcae9012b548b1e5";
+    const SEARCHER_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[tokio::test]
+    async fn flashbots_bundle_is_signed_submitted_and_confirmed_on_chain() {
+        let relay = MockRelayServer::start(vec![
+            ("eth_callBundle", vec![result(serde_json::json!({
+                "results": [{ "gasUsed": 51_000 }, { "gasUsed": 21_000 }],
+                "totalGasUsed": 72_000,
+                "coinbaseDiff": "5000",
+            }))]),
+            ("eth_sendBundle", vec![result(serde_json::json!({ "bundleHash": "0xb0b" }))]),
+            ("eth_getTransactionReceipt", vec![
+                result(serde_json::Value::Null),
+                result(serde_json::json!({ "blockNumber": "0x64", "status": "0x1", "gasUsed": "0xc738", "effectiveGasPrice": "0x1" })),
+            ]),
+            ("eth_blockNumber", vec![result(serde_json::json!("0x63"))]),
+        ]).await;
+        let searcher = EvmSigner::from_hex(SEARCHER_KEY).unwrap();
+        let rpc = Arc::new(EvmRpcClient::new(&format!("{}/rpc", relay.url)));
+        let flashbots = FlashbotsRelay::new(&format!("{}/relay", relay.url), EvmSigner::from_hex(AUTH_KEY).unwrap(), rpc);
+        let manager = manager_with(Arc::new(flashbots)).await;
+        let bundle = evm_bundle(&searcher, 100);
+
+        let submitted = manager.send(bundle.clone()).await.unwrap();
+        assert_eq!(submitted[0].relay_bundle_id, "0xb0b");
+        let simulation = submitted[0].simulation.clone().unwrap();
+        assert_eq!((simulation.units_consumed, simulation.builder_payment), (Some(72_000), Some(5_000)));
+
+        let sent = &relay.requests("eth_sendBundle")[0];
+        assert_eq!(sent.path, "/relay");
+        assert_eq!(sent.params[0]["blockNumber"], "0x64");
+        assert_eq!(sent.params[0]["txs"], serde_json::json!(bundle.encoded_transactions().unwrap()));
+
+        // X-Flashbots-Signature is the auth key's EIP-191 signature over the hex body hash
+        let auth = EvmSigner::from_hex(AUTH_KEY).unwrap();
+        let digest = format!("0x{}", hex::encode(evm_tx::keccak256(sent.body.as_bytes())));
+        assert_eq!(
+            sent.headers["x-flashbots-signature"],
+            format!("{}:0x{}", evm_tx::format_address(&auth.address()), hex::encode(auth.sign_message(digest.as_bytes()).unwrap())),
+        );
+
+        manager.poll_once().await;
+        assert_eq!(manager.get_bundles(10).await[0].status, BundleStatus::Pending);
+        manager.poll_once().await;
+        assert_eq!(manager.get_bundles(10).await[0].status, BundleStatus::Landed { slot: 100 });
+        let receipt_lookup = &relay.requests("eth_getTransactionReceipt")[0];
+        assert_eq!(receipt_lookup.path, "/rpc");
+        assert_eq!(receipt_lookup.params[0], match &bundle.payload {
+            BundlePayload::Evm { transactions, .. } => transactions[0].hash_hex(),
+            BundlePayload::Solana(_) => unreachable!(),
+        });
+    }
+
+    #[tokio::test]
+    async fn flashbots_bundle_expires_once_the_target_block_passes() {
+        let relay = MockRelayServer::start(vec![
+            ("eth_sendBundle", vec![result(serde_json::json!({ "bundleHash": "0xb0b" }))]),
+            ("eth_getTransactionReceipt", vec![result(serde_json::Value::Null)]),
+            ("eth_blockNumber", vec![result(serde_json::json!("0x65"))]),
+        ]).await;
+        let rpc = Arc::new(EvmRpcClient::new(&relay.url));
+        let manager = manager_with(Arc::new(FlashbotsRelay::new(&relay.url, EvmSigner::from_hex(AUTH_KEY).unwrap(), rpc))).await;
+        manager.set_config(BundleManagerConfig { simulate_before_submit: false, ..BundleManagerConfig::default() }).await;
+
+        manager.send(evm_bundle(&EvmSigner::from_hex(SEARCHER_KEY).unwrap(), 100)).await.unwrap();
+        manager.poll_once().await;
+        assert_eq!(manager.get_bundles(10).await[0].status, BundleStatus::Expired);
+        assert!(relay.requests("eth_callBundle").is_empty());
+    }
+
+    #[tokio::test]
+    async fn flashbots_relay_errors_reject_the_bundle() {
+        let relay = MockRelayServer::start(vec![
+            ("eth_callBundle", vec![result(serde_json::json!({ "results": [{ "error": "execution reverted", "revert": "TooLittleReceived" }] }))]),
+        ]).await;
+        let rpc = Arc::new(EvmRpcClient::new(&relay.url));
+        let manager = manager_with(Arc::new(FlashbotsRelay::new(&relay.url, EvmSigner::from_hex(AUTH_KEY).unwrap(), rpc))).await;
+
+        let error = manager.send(evm_bundle(&EvmSigner::from_hex(SEARCHER_KEY).unwrap(), 100)).await.unwrap_err();
+        assert!(error.to_string().contains("execution reverted"), "{}", error);
+        assert!(relay.requests("eth_sendBundle").is_empty());
+    }
+}
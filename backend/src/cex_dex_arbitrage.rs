@@ -0,0 +1,418 @@
+// CEX-DEX Arbitrage - Solana DEX swaps hedged against centralized order books
+// Sizes both legs for a concrete notional and prices on-chain fees, taker fees and inclusion latency
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromStr, ToPrimitive, FromPrimitive};
+use anyhow::{Result, anyhow};
+use log::{info, warn, debug};
+
+use crate::external_apis::{CexOrderBook, ExternalApiClient, SolanaTokens};
+use crate::fee_model::{self, CostKind, FeeModel, GasOperation, Liquidity, ProfitBreakdown};
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexVenue {
+    pub name: String,
+    /// Jupiter AMM labels to restrict routing to; empty routes through every AMM
+    pub amm_labels: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolanaAsset {
+    pub symbol: String,
+    pub mint: String,
+    pub decimals: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexDexConfig {
+    pub cex_venues: Vec<String>,
+    pub dex_venues: Vec<DexVenue>,
+    pub assets: Vec<SolanaAsset>,
+    pub trade_sizes_usd: Vec<Decimal>,
+    pub slippage_bps: u32,
+    pub book_depth: u32,
+    pub rpc_url: String,
+    pub priority_fee_percentile: f64,
+    pub default_priority_fee_micro_lamports: u64,
+    pub slot_time_ms: u64,
+    /// Slots between submission and inclusion at the chosen priority fee
+    pub expected_slots_to_land: u64,
+    pub landing_probability: f64,
+    pub cex_fill_ms: u64,
+    /// Per-second volatility used for the exposure while one leg is unhedged
+    pub volatility_per_sqrt_sec: f64,
+    /// Standard deviations of adverse move to charge for that exposure
+    pub adverse_move_sigmas: f64,
+    pub min_net_profit_usd: Decimal,
+}
+
+impl Default for CexDexConfig {
+    fn default() -> Self {
+        Self {
+            cex_venues: vec!["Binance".to_string(), "Coinbase".to_string(), "Kraken".to_string(), "OKX".to_string(), "Bybit".to_string()],
+            dex_venues: vec![
+                DexVenue { name: "Jupiter".to_string(), amm_labels: vec![] },
+                DexVenue { name: "Raydium".to_string(), amm_labels: vec!["Raydium".to_string(), "Raydium CLMM".to_string()] },
+                DexVenue { name: "Orca".to_string(), amm_labels: vec!["Whirlpool".to_string()] },
+            ],
+            assets: vec![
+                SolanaAsset { symbol: "SOL".to_string(), mint: SolanaTokens::SOL.to_string(), decimals: 9 },
+                SolanaAsset { symbol: "RAY".to_string(), mint: SolanaTokens::RAY.to_string(), decimals: 6 },
+            ],
+            trade_sizes_usd: vec![Decimal::from(1000), Decimal::from(10000)],
+            slippage_bps: 50,
+            book_depth: 100,
+            rpc_url: std::env::var("SOLANA_RPC_URL")
+                .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string()),
+            priority_fee_percentile: 0.75,
+            default_priority_fee_micro_lamports: 10_000,
+            slot_time_ms: 400,
+            expected_slots_to_land: 2,
+            landing_probability: 0.9,
+            cex_fill_ms: 50,
+            volatility_per_sqrt_sec: 0.00015, // ~4.4% daily
+            adverse_move_sigmas: 1.0,
+            min_net_profit_usd: Decimal::ONE,
+        }
+    }
+}
+
+// ============================================================================
+// OPPORTUNITY DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CexDexDirection {
+    BuyDexSellCex,
+    BuyCexSellDex,
+}
+
+/// How the two legs are sequenced given that the DEX leg waits for block inclusion.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HedgeMode {
+    /// Hedge on the CEX only after the swap confirms; carries price risk during inclusion
+    DexFirst,
+    /// Fire both legs together; carries unwind risk if the swap fails to land
+    Simultaneous,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexDexOpportunity {
+    pub id: String,
+    pub asset: String,
+    pub direction: CexDexDirection,
+    pub dex_venue: String,
+    pub cex_venue: String,
+    pub size_usd: Decimal,
+    pub base_amount: Decimal,
+    pub dex_price: Decimal,
+    pub cex_avg_price: Decimal,
+    pub dex_price_impact_pct: f64,
+    pub priority_fee_micro_lamports: u64,
+    pub hedge_mode: HedgeMode,
+    pub dex_latency_ms: u64,
+    pub cex_latency_ms: u64,
+    pub profit_breakdown: ProfitBreakdown,
+    pub timestamp: u64,
+}
+
+impl CexDexOpportunity {
+    pub fn buy_exchange(&self) -> &str {
+        match self.direction {
+            CexDexDirection::BuyDexSellCex => &self.dex_venue,
+            CexDexDirection::BuyCexSellDex => &self.cex_venue,
+        }
+    }
+
+    pub fn sell_exchange(&self) -> &str {
+        match self.direction {
+            CexDexDirection::BuyDexSellCex => &self.cex_venue,
+            CexDexDirection::BuyCexSellDex => &self.dex_venue,
+        }
+    }
+
+    pub fn buy_price(&self) -> Decimal {
+        match self.direction {
+            CexDexDirection::BuyDexSellCex => self.dex_price,
+            CexDexDirection::BuyCexSellDex => self.cex_avg_price,
+        }
+    }
+
+    pub fn sell_price(&self) -> Decimal {
+        match self.direction {
+            CexDexDirection::BuyDexSellCex => self.cex_avg_price,
+            CexDexDirection::BuyCexSellDex => self.dex_price,
+        }
+    }
+}
+
+// ============================================================================
+// CEX-DEX DETECTOR
+// ============================================================================
+
+pub struct CexDexArbitrageDetector {
+    api_client: Arc<ExternalApiClient>,
+    fee_model: Arc<FeeModel>,
+    config: Arc<RwLock<CexDexConfig>>,
+    opportunities: Arc<RwLock<Vec<CexDexOpportunity>>>,
+}
+
+impl CexDexArbitrageDetector {
+    pub fn new(api_client: Arc<ExternalApiClient>, fee_model: Arc<FeeModel>) -> Self {
+        Self {
+            api_client,
+            fee_model,
+            config: Arc::new(RwLock::new(CexDexConfig::default())),
+            opportunities: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn get_config(&self) -> CexDexConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: CexDexConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub async fn get_opportunities(&self) -> Vec<CexDexOpportunity> {
+        self.opportunities.read().await.clone()
+    }
+
+    /// Quote every asset, size, DEX venue and CEX book in both directions.
+    pub async fn detect(&self) -> Result<Vec<CexDexOpportunity>> {
+        let config = self.config.read().await.clone();
+        let usdc = SolanaTokens::USDC;
+
+        let priority_fee = match self.api_client
+            .get_solana_priority_fee(&config.rpc_url, &[], config.priority_fee_percentile)
+            .await
+        {
+            Ok(fee) => fee.max(config.default_priority_fee_micro_lamports),
+            Err(e) => {
+                warn!("⚠️ Priority fee lookup failed, using default: {}", e);
+                config.default_priority_fee_micro_lamports
+            }
+        };
+        // micro-lamports per CU -> SOL per CU
+        let unit_price_sol = Decimal::from(priority_fee) / Decimal::from(1_000_000_000_000_000u64);
+        self.fee_model.update_gas_price("solana", unit_price_sol).await;
+
+        let mut opportunities = Vec::new();
+
+        for asset in &config.assets {
+            let mut books: HashMap<String, CexOrderBook> = HashMap::new();
+            for cex in &config.cex_venues {
+                match self.api_client.get_cex_order_book(cex, &asset.symbol, config.book_depth).await {
+                    Ok(book) => {
+                        books.insert(cex.clone(), book);
+                    }
+                    Err(e) => debug!("No {} book on {}: {}", asset.symbol, cex, e),
+                }
+            }
+            if books.is_empty() {
+                continue;
+            }
+
+            if asset.symbol == "SOL" {
+                if let Some(mid) = books.values().filter_map(|b| b.mid()).next() {
+                    self.fee_model.update_native_price("solana", mid).await;
+                }
+            }
+            let scale = Decimal::from(10u64.pow(asset.decimals));
+            let usdc_scale = Decimal::from(1_000_000u64);
+
+            for size_usd in &config.trade_sizes_usd {
+                for dex in &config.dex_venues {
+                    let labels: Vec<&str> = dex.amm_labels.iter().map(|l| l.as_str()).collect();
+
+                    // Buy on the DEX with USDC, sell the received asset into the CEX bids
+                    let usdc_in = (*size_usd * usdc_scale).to_u64().unwrap_or(0);
+                    match self.api_client
+                        .get_jupiter_quote_on_dexes(usdc, &asset.mint, usdc_in, Some(config.slippage_bps), Some(&labels))
+                        .await
+                    {
+                        Ok(quote) => {
+                            let base_out = Decimal::from_str(&quote.out_amount).unwrap_or_default() / scale;
+                            let impact = quote.price_impact_pct.parse::<f64>().unwrap_or(0.0) * 100.0;
+                            for (cex, book) in &books {
+                                let Some((proceeds, avg_price)) = book.proceeds_from_sell(base_out) else {
+                                    continue;
+                                };
+                                let opp = self.evaluate(
+                                    &config, CexDexDirection::BuyDexSellCex, &asset.symbol, &dex.name, cex,
+                                    *size_usd, base_out, *size_usd / base_out, avg_price,
+                                    proceeds - *size_usd, proceeds, impact, priority_fee,
+                                ).await;
+                                opportunities.extend(opp);
+                            }
+                        }
+                        Err(e) => debug!("Jupiter {} quote failed on {}: {}", asset.symbol, dex.name, e),
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+                    // Buy on each CEX against the asks, sell the asset on the DEX for USDC
+                    for (cex, book) in &books {
+                        let Some(best_ask) = book.asks.first().map(|l| l.price) else { continue };
+                        let base_amount = (*size_usd / best_ask).round_dp(asset.decimals.min(8));
+                        let Some((cost, avg_price)) = book.cost_to_buy(base_amount) else { continue };
+
+                        let base_in = (base_amount * scale).to_u64().unwrap_or(0);
+                        match self.api_client
+                            .get_jupiter_quote_on_dexes(&asset.mint, usdc, base_in, Some(config.slippage_bps), Some(&labels))
+                            .await
+                        {
+                            Ok(quote) => {
+                                let usdc_out = Decimal::from_str(&quote.out_amount).unwrap_or_default() / usdc_scale;
+                                let impact = quote.price_impact_pct.parse::<f64>().unwrap_or(0.0) * 100.0;
+                                let opp = self.evaluate(
+                                    &config, CexDexDirection::BuyCexSellDex, &asset.symbol, &dex.name, cex,
+                                    cost, base_amount, usdc_out / base_amount, avg_price,
+                                    usdc_out - cost, cost, impact, priority_fee,
+                                ).await;
+                                opportunities.extend(opp);
+                            }
+                            Err(e) => debug!("Jupiter {} quote failed on {}: {}", asset.symbol, dex.name, e),
+                        }
+                        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        }
+
+        opportunities.sort_by(|a, b| b.profit_breakdown.net_profit_usd.cmp(&a.profit_breakdown.net_profit_usd));
+        info!("🔀 CEX-DEX scan found {} opportunities", opportunities.len());
+
+        *self.opportunities.write().await = opportunities.clone();
+        Ok(opportunities)
+    }
+
+    /// Itemize costs for one sized CEX-DEX trade. DEX pool fees are already netted in the
+    /// Jupiter out amount, so only the CEX taker fee, network fees and latency are added.
+    #[allow(clippy::too_many_arguments)]
+    async fn evaluate(
+        &self,
+        config: &CexDexConfig,
+        direction: CexDexDirection,
+        asset: &str,
+        dex_venue: &str,
+        cex_venue: &str,
+        size_usd: Decimal,
+        base_amount: Decimal,
+        dex_price: Decimal,
+        cex_avg_price: Decimal,
+        gross_profit_usd: Decimal,
+        cex_notional_usd: Decimal,
+        dex_price_impact_pct: f64,
+        priority_fee_micro_lamports: u64,
+    ) -> Option<CexDexOpportunity> {
+        if gross_profit_usd <= Decimal::ZERO || base_amount <= Decimal::ZERO {
+            return None;
+        }
+
+        let mut breakdown = ProfitBreakdown::new(size_usd, gross_profit_usd);
+
+        let taker_rate = self.fee_model.fee_rate(cex_venue, Liquidity::Taker).await;
+        let taker_fee = cex_notional_usd * taker_rate;
+        breakdown.add_cost(CostKind::TradingFee, cex_venue, taker_fee, "CEX taker fee");
+
+        let network_fee = self.fee_model.gas_cost_usd("solana", GasOperation::Swap).await;
+        breakdown.add_cost(
+            CostKind::NetworkGas,
+            dex_venue,
+            network_fee,
+            format!("Solana base + priority fee ({} µlamports/CU)", priority_fee_micro_lamports),
+        );
+
+        let dex_latency_ms = config.expected_slots_to_land * config.slot_time_ms;
+        let (hedge_mode, latency_cost) = self.latency_cost(config, size_usd, taker_rate, dex_latency_ms);
+        breakdown.add_cost(
+            CostKind::Slippage,
+            cex_venue,
+            latency_cost,
+            format!("{:?} latency exposure ({}ms inclusion vs {}ms fill)", hedge_mode, dex_latency_ms, config.cex_fill_ms),
+        );
+
+        if breakdown.net_profit_usd < config.min_net_profit_usd {
+            return None;
+        }
+
+        Some(CexDexOpportunity {
+            id: format!("cexdex_{}_{}_{}_{}", asset, dex_venue, cex_venue, chrono::Utc::now().timestamp_millis()),
+            asset: asset.to_string(),
+            direction,
+            dex_venue: dex_venue.to_string(),
+            cex_venue: cex_venue.to_string(),
+            size_usd,
+            base_amount,
+            dex_price,
+            cex_avg_price,
+            dex_price_impact_pct,
+            priority_fee_micro_lamports,
+            hedge_mode,
+            dex_latency_ms,
+            cex_latency_ms: config.cex_fill_ms,
+            profit_breakdown: breakdown,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// Cheaper of the two sequencing choices for the inclusion/fill latency gap.
+    fn latency_cost(
+        &self,
+        config: &CexDexConfig,
+        notional_usd: Decimal,
+        taker_rate: Decimal,
+        dex_latency_ms: u64,
+    ) -> (HedgeMode, Decimal) {
+        let notional = fee_model::to_f64(notional_usd);
+        let sigma = config.volatility_per_sqrt_sec;
+
+        // DEX first: the CEX price can drift for the whole inclusion time plus the fill
+        let exposure_secs = (dex_latency_ms + config.cex_fill_ms) as f64 / 1000.0;
+        let dex_first = config.adverse_move_sigmas * sigma * exposure_secs.sqrt() * notional;
+
+        // Simultaneous: if the swap does not land, the filled CEX leg is unwound at another taker fee
+        let unwind = notional * (fee_model::to_f64(taker_rate) + config.adverse_move_sigmas * sigma * exposure_secs.sqrt());
+        let simultaneous = (1.0 - config.landing_probability) * unwind;
+
+        if simultaneous < dex_first {
+            (HedgeMode::Simultaneous, Decimal::from_f64(simultaneous).unwrap_or_default())
+        } else {
+            (HedgeMode::DexFirst, Decimal::from_f64(dex_first).unwrap_or_default())
+        }
+    }
+
+    pub async fn get_opportunity(&self, id: &str) -> Result<CexDexOpportunity> {
+        self.opportunities.read().await
+            .iter()
+            .find(|o| o.id == id)
+            .cloned()
+            .ok_or_else(|| anyhow!("CEX-DEX opportunity {} not found", id))
+    }
+}
+
+impl Clone for CexDexArbitrageDetector {
+    fn clone(&self) -> Self {
+        Self {
+            api_client: self.api_client.clone(),
+            fee_model: self.fee_model.clone(),
+            config: self.config.clone(),
+            opportunities: self.opportunities.clone(),
+        }
+    }
+}
+
+impl Default for CexDexArbitrageDetector {
+    fn default() -> Self {
+        Self::new(Arc::new(ExternalApiClient::new()), Arc::new(FeeModel::new()))
+    }
+}
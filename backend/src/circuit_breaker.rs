@@ -0,0 +1,528 @@
+// Circuit Breakers - Global kill switch and automatic trading halts per strategy and venue
+// Trips on failure streaks, windowed losses, abnormal slippage, stale data and venue error rates; cooldown or manual reset
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use log::{info, warn, error};
+
+use crate::PriceInfo;
+
+// ============================================================================
+// BREAKER DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "name", rename_all = "snake_case")]
+pub enum BreakerScope {
+    Global,
+    Strategy(String),
+    Venue(String),
+}
+
+impl std::fmt::Display for BreakerScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BreakerScope::Global => write!(f, "global"),
+            BreakerScope::Strategy(name) => write!(f, "strategy:{}", name),
+            BreakerScope::Venue(name) => write!(f, "venue:{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BreakerReason {
+    KillSwitch,
+    ConsecutiveFailures,
+    RealizedLoss,
+    AbnormalSlippage,
+    StaleData,
+    VenueErrorRate,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerTrip {
+    pub scope: BreakerScope,
+    pub reason: BreakerReason,
+    pub detail: String,
+    pub tripped_at: u64,
+    pub cooldown_until: Option<u64>, // None: stays open until reset manually
+    pub tripped_by: String,          // "auto" or the operator that pulled the kill switch
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreakerAction {
+    Tripped,
+    Reset,
+}
+
+/// Broadcast to the dashboard whenever a breaker opens or closes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerEvent {
+    pub action: BreakerAction,
+    pub scope: BreakerScope,
+    pub reason: BreakerReason,
+    pub detail: String,
+    pub actor: String,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerConfig {
+    pub max_consecutive_failures: u32,
+    pub loss_window_secs: u64,
+    pub max_window_loss_usd: Decimal,
+    pub max_slippage_bps: f64,
+    pub max_data_age_secs: u64,
+    pub venue_error_window_secs: u64,
+    pub max_venue_error_rate: f64,
+    pub min_venue_samples: usize,
+    pub cooldown_secs: u64,
+    pub manual_reset_reasons: Vec<BreakerReason>, // Never cool down automatically
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_consecutive_failures: 5,
+            loss_window_secs: 3600,
+            max_window_loss_usd: Decimal::from(1000),
+            max_slippage_bps: 100.0,
+            max_data_age_secs: 30,
+            venue_error_window_secs: 300,
+            max_venue_error_rate: 0.5,
+            min_venue_samples: 10,
+            cooldown_secs: 300,
+            manual_reset_reasons: vec![BreakerReason::KillSwitch, BreakerReason::RealizedLoss],
+        }
+    }
+}
+
+/// Result of one trade as seen by the breakers
+#[derive(Debug, Clone)]
+pub struct TradeOutcome {
+    pub success: bool,
+    pub pnl_usd: Decimal,
+    pub slippage_bps: f64,
+}
+
+#[derive(Debug, Default)]
+struct ScopeStats {
+    consecutive_failures: u32,
+    pnl: VecDeque<(u64, Decimal)>,
+}
+
+type VenueResults = HashMap<String, VecDeque<(u64, bool)>>; // venue -> (timestamp, ok)
+
+// ============================================================================
+// CIRCUIT BREAKER
+// ============================================================================
+
+pub struct CircuitBreaker {
+    trips: Arc<RwLock<HashMap<BreakerScope, BreakerTrip>>>,
+    stats: Arc<RwLock<HashMap<BreakerScope, ScopeStats>>>,
+    venue_results: Arc<RwLock<VenueResults>>,
+    last_data: Arc<RwLock<HashMap<String, u64>>>, // venue -> last quote timestamp
+    half_open: Arc<RwLock<HashSet<BreakerScope>>>, // Cooled down; the next failure re-trips
+    config: Arc<RwLock<BreakerConfig>>,
+    events: broadcast::Sender<BreakerEvent>,
+    history: Arc<RwLock<Vec<BreakerEvent>>>,
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            trips: Arc::new(RwLock::new(HashMap::new())),
+            stats: Arc::new(RwLock::new(HashMap::new())),
+            venue_results: Arc::new(RwLock::new(HashMap::new())),
+            last_data: Arc::new(RwLock::new(HashMap::new())),
+            half_open: Arc::new(RwLock::new(HashSet::new())),
+            config: Arc::new(RwLock::new(BreakerConfig::default())),
+            events,
+            history: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn get_config(&self) -> BreakerConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: BreakerConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<BreakerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Allow a new order only if the global, strategy and every venue breaker are closed.
+    /// A breaker whose cooldown has elapsed goes half-open: orders pass, but the next failure re-trips it.
+    pub async fn check(&self, strategy: &str, venues: &[&str]) -> Result<(), BreakerTrip> {
+        let mut scopes = vec![BreakerScope::Global, BreakerScope::Strategy(strategy.to_string())];
+        scopes.extend(venues.iter().map(|v| BreakerScope::Venue(v.to_string())));
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        for scope in scopes {
+            let trip = self.trips.read().await.get(&scope).cloned();
+            match trip {
+                Some(trip) if trip.cooldown_until.map(|t| now >= t).unwrap_or(false) => {
+                    self.close(&scope, "cooldown elapsed, half-open", "auto").await;
+                    self.half_open.write().await.insert(scope);
+                }
+                Some(trip) => return Err(trip),
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Manually open a breaker; tripping the global scope is the kill switch
+    pub async fn kill(&self, scope: BreakerScope, detail: &str, actor: &str) {
+        self.open(scope, BreakerReason::KillSwitch, detail.to_string(), actor).await;
+    }
+
+    /// Manually close a breaker and clear the statistics that tripped it
+    pub async fn reset(&self, scope: &BreakerScope, actor: &str) -> bool {
+        self.stats.write().await.remove(scope);
+        self.half_open.write().await.remove(scope);
+        if let BreakerScope::Venue(venue) = scope {
+            self.venue_results.write().await.remove(venue);
+        }
+        self.close(scope, "manual reset", actor).await
+    }
+
+    pub async fn get_trips(&self) -> Vec<BreakerTrip> {
+        self.trips.read().await.values().cloned().collect()
+    }
+
+    pub async fn get_events(&self, limit: usize) -> Vec<BreakerEvent> {
+        self.history.read().await.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Open a breaker on behalf of another risk component
+    pub async fn trip(&self, scope: BreakerScope, reason: BreakerReason, detail: &str) {
+        self.open(scope, reason, detail.to_string(), "auto").await;
+    }
+
+    /// Feed one finished trade into the strategy and global failure/loss/slippage breakers
+    pub async fn record_trade(&self, strategy: &str, outcome: &TradeOutcome) {
+        let config = self.config.read().await.clone();
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        if outcome.slippage_bps.abs() > config.max_slippage_bps {
+            let detail = format!("{:.1} bps slippage exceeds {:.1} bps", outcome.slippage_bps, config.max_slippage_bps);
+            self.open(BreakerScope::Strategy(strategy.to_string()), BreakerReason::AbnormalSlippage, detail, "auto").await;
+        }
+
+        let mut to_open = Vec::new();
+        {
+            let mut stats = self.stats.write().await;
+            let mut half_open = self.half_open.write().await;
+            for scope in [BreakerScope::Strategy(strategy.to_string()), BreakerScope::Global] {
+                let entry = stats.entry(scope.clone()).or_default();
+                if outcome.success {
+                    entry.consecutive_failures = 0;
+                } else {
+                    entry.consecutive_failures += 1;
+                }
+                entry.pnl.push_back((now, outcome.pnl_usd));
+                while entry.pnl.front().map(|(t, _)| now.saturating_sub(*t) > config.loss_window_secs).unwrap_or(false) {
+                    entry.pnl.pop_front();
+                }
+
+                let probing = half_open.remove(&scope);
+                if probing && !outcome.success {
+                    to_open.push((scope.clone(), BreakerReason::ConsecutiveFailures,
+                                  "Trade failed while half-open".to_string()));
+                } else if entry.consecutive_failures >= config.max_consecutive_failures {
+                    to_open.push((scope.clone(), BreakerReason::ConsecutiveFailures,
+                                  format!("{} consecutive failed trades", entry.consecutive_failures)));
+                }
+                let window_pnl: Decimal = entry.pnl.iter().map(|(_, p)| *p).sum();
+                if -window_pnl > config.max_window_loss_usd {
+                    to_open.push((scope, BreakerReason::RealizedLoss,
+                                  format!("Realized loss ${} over {}s exceeds ${}", -window_pnl, config.loss_window_secs, config.max_window_loss_usd)));
+                }
+            }
+        }
+        for (scope, reason, detail) in to_open {
+            self.open(scope, reason, detail, "auto").await;
+        }
+    }
+
+    /// Feed one venue interaction (order, leg or request) into that venue's error-rate breaker
+    pub async fn record_venue_result(&self, venue: &str, ok: bool) {
+        let config = self.config.read().await.clone();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let scope = BreakerScope::Venue(venue.to_string());
+        if self.half_open.write().await.remove(&scope) && !ok {
+            self.open(scope, BreakerReason::VenueErrorRate, "Request failed while half-open".to_string(), "auto").await;
+            return;
+        }
+        let rate = {
+            let mut results = self.venue_results.write().await;
+            let window = results.entry(venue.to_string()).or_default();
+            window.push_back((now, ok));
+            while window.front().map(|(t, _)| now.saturating_sub(*t) > config.venue_error_window_secs).unwrap_or(false) {
+                window.pop_front();
+            }
+            if window.len() < config.min_venue_samples {
+                return;
+            }
+            window.iter().filter(|(_, ok)| !ok).count() as f64 / window.len() as f64
+        };
+        if rate > config.max_venue_error_rate {
+            let detail = format!("{:.0}% of requests failed over {}s", rate * 100.0, config.venue_error_window_secs);
+            self.open(scope, BreakerReason::VenueErrorRate, detail, "auto").await;
+        }
+    }
+
+    /// Track data freshness from the price stream and trip venues whose feed goes quiet
+    pub async fn start(&self, mut prices: broadcast::Receiver<PriceInfo>) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        loop {
+            tokio::select! {
+                quote = prices.recv() => match quote {
+                    Ok(quote) => {
+                        self.last_data.write().await.insert(quote.exchange.clone(), quote.timestamp);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = interval.tick() => self.check_staleness().await,
+            }
+        }
+    }
+
+    async fn check_staleness(&self) {
+        let max_age = self.config.read().await.max_data_age_secs;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let stale: Vec<(String, u64)> = self.last_data.read().await
+            .iter()
+            .map(|(venue, ts)| (venue.clone(), now.saturating_sub(*ts)))
+            .filter(|(_, age)| *age > max_age)
+            .collect();
+
+        for (venue, age) in stale {
+            let scope = BreakerScope::Venue(venue);
+            let already_open = self.trips.read().await.contains_key(&scope);
+            if !already_open {
+                self.open(scope, BreakerReason::StaleData, format!("No market data for {}s", age), "auto").await;
+            }
+        }
+    }
+
+    async fn open(&self, scope: BreakerScope, reason: BreakerReason, detail: String, actor: &str) {
+        let config = self.config.read().await.clone();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let cooldown_until = if config.manual_reset_reasons.contains(&reason) {
+            None
+        } else {
+            Some(now + config.cooldown_secs)
+        };
+
+        {
+            let mut trips = self.trips.write().await;
+            // A manual-reset trip stays as it is until an operator clears it
+            if trips.get(&scope).map(|t| t.cooldown_until.is_none()).unwrap_or(false) {
+                return;
+            }
+            trips.insert(scope.clone(), BreakerTrip {
+                scope: scope.clone(),
+                reason,
+                detail: detail.clone(),
+                tripped_at: now,
+                cooldown_until,
+                tripped_by: actor.to_string(),
+            });
+        }
+
+        if reason == BreakerReason::KillSwitch && scope == BreakerScope::Global {
+            error!("🛑 KILL SWITCH engaged by {}: {}", actor, detail);
+        } else {
+            warn!("🚨 Circuit breaker {} tripped ({:?}): {}", scope, reason, detail);
+        }
+        self.emit(BreakerEvent {
+            action: BreakerAction::Tripped,
+            scope,
+            reason,
+            detail,
+            actor: actor.to_string(),
+            timestamp: now,
+        }).await;
+    }
+
+    async fn close(&self, scope: &BreakerScope, detail: &str, actor: &str) -> bool {
+        let Some(trip) = self.trips.write().await.remove(scope) else {
+            return false;
+        };
+        info!("✅ Circuit breaker {} reset by {} ({})", scope, actor, detail);
+        self.emit(BreakerEvent {
+            action: BreakerAction::Reset,
+            scope: scope.clone(),
+            reason: trip.reason,
+            detail: detail.to_string(),
+            actor: actor.to_string(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }).await;
+        true
+    }
+
+    async fn emit(&self, event: BreakerEvent) {
+        let mut history = self.history.write().await;
+        history.push(event.clone());
+        if history.len() > 500 {
+            let drain_count = history.len() - 500;
+            history.drain(0..drain_count);
+        }
+        let _ = self.events.send(event);
+    }
+}
+
+impl Clone for CircuitBreaker {
+    fn clone(&self) -> Self {
+        Self {
+            trips: self.trips.clone(),
+            stats: self.stats.clone(),
+            venue_results: self.venue_results.clone(),
+            last_data: self.last_data.clone(),
+            half_open: self.half_open.clone(),
+            config: self.config.clone(),
+            events: self.events.clone(),
+            history: self.history.clone(),
+        }
+    }
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn breaker(cooldown_secs: u64) -> CircuitBreaker {
+        let breaker = CircuitBreaker::new();
+        breaker.set_config(BreakerConfig {
+            max_consecutive_failures: 3,
+            cooldown_secs,
+            ..BreakerConfig::default()
+        }).await;
+        breaker
+    }
+
+    fn outcome(success: bool, pnl_usd: i64) -> TradeOutcome {
+        TradeOutcome { success, pnl_usd: Decimal::from(pnl_usd), slippage_bps: 0.0 }
+    }
+
+    #[tokio::test]
+    async fn consecutive_failures_trip_the_strategy_and_global_scopes() {
+        let breaker = breaker(300).await;
+        for _ in 0..2 {
+            breaker.record_trade("spread", &outcome(false, 0)).await;
+        }
+        assert!(breaker.check("spread", &[]).await.is_ok());
+
+        breaker.record_trade("spread", &outcome(false, 0)).await;
+        let trip = breaker.check("spread", &[]).await.unwrap_err();
+        assert_eq!(trip.reason, BreakerReason::ConsecutiveFailures);
+        assert!(trip.cooldown_until.is_some());
+        // The global breaker halts every other strategy too
+        assert_eq!(breaker.check("other", &[]).await.unwrap_err().scope, BreakerScope::Global);
+    }
+
+    #[tokio::test]
+    async fn a_success_resets_the_failure_streak() {
+        let breaker = breaker(300).await;
+        for success in [false, false, true, false, false] {
+            breaker.record_trade("spread", &outcome(success, 0)).await;
+        }
+        assert!(breaker.check("spread", &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn windowed_losses_need_a_manual_reset() {
+        let breaker = breaker(0).await;
+        breaker.record_trade("spread", &outcome(true, -600)).await;
+        breaker.record_trade("spread", &outcome(true, -600)).await;
+
+        let trip = breaker.check("spread", &[]).await.unwrap_err();
+        assert_eq!(trip.reason, BreakerReason::RealizedLoss);
+        assert_eq!(trip.cooldown_until, None);
+        assert!(breaker.check("spread", &[]).await.is_err(), "loss trips never cool down");
+
+        assert!(breaker.reset(&BreakerScope::Global, "operator").await);
+        assert!(breaker.reset(&BreakerScope::Strategy("spread".to_string()), "operator").await);
+        assert!(breaker.check("spread", &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cooldown_closes_the_breaker_half_open() {
+        let breaker = breaker(0).await;
+        for _ in 0..3 {
+            breaker.record_trade("spread", &outcome(false, 0)).await;
+        }
+        assert_eq!(breaker.get_trips().await.len(), 2);
+
+        // The cooldown has elapsed, so the next check lets orders through
+        assert!(breaker.check("spread", &[]).await.is_ok());
+        assert!(breaker.get_trips().await.is_empty());
+
+        // ...but the first failed probe re-trips without waiting for a new streak
+        breaker.record_trade("spread", &outcome(false, 0)).await;
+        let trip = breaker.get_trips().await.into_iter()
+            .find(|t| t.scope == BreakerScope::Strategy("spread".to_string()))
+            .unwrap();
+        assert_eq!(trip.detail, "Trade failed while half-open");
+    }
+
+    #[tokio::test]
+    async fn a_successful_probe_closes_the_breaker_fully() {
+        let breaker = breaker(0).await;
+        for _ in 0..3 {
+            breaker.record_trade("spread", &outcome(false, 0)).await;
+        }
+        assert!(breaker.check("spread", &[]).await.is_ok());
+
+        breaker.record_trade("spread", &outcome(true, 0)).await;
+        breaker.record_trade("spread", &outcome(false, 0)).await;
+        assert!(breaker.get_trips().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn half_open_venues_re_trip_on_the_next_error() {
+        let breaker = breaker(0).await;
+        breaker.trip(BreakerScope::Venue("binance".to_string()), BreakerReason::StaleData, "quiet feed").await;
+        assert!(breaker.check("spread", &["binance"]).await.is_ok());
+
+        // One error is far below the sample minimum, but a half-open venue gets no second chance
+        breaker.set_config(BreakerConfig { cooldown_secs: 300, ..breaker.get_config().await }).await;
+        breaker.record_venue_result("binance", false).await;
+        let trip = breaker.check("spread", &["binance"]).await.unwrap_err();
+        assert_eq!(trip.reason, BreakerReason::VenueErrorRate);
+    }
+
+    #[tokio::test]
+    async fn the_kill_switch_latches_until_reset() {
+        let breaker = breaker(0).await;
+        breaker.kill(BreakerScope::Global, "halt", "operator").await;
+
+        let trip = breaker.check("spread", &[]).await.unwrap_err();
+        assert_eq!(trip.reason, BreakerReason::KillSwitch);
+        assert_eq!(trip.tripped_by, "operator");
+
+        // Automatic trips cannot overwrite a latched manual-reset trip
+        breaker.trip(BreakerScope::Global, BreakerReason::StaleData, "quiet feed").await;
+        assert_eq!(breaker.check("spread", &[]).await.unwrap_err().reason, BreakerReason::KillSwitch);
+
+        assert!(breaker.reset(&BreakerScope::Global, "operator").await);
+        assert!(breaker.check("spread", &[]).await.is_ok());
+        assert!(!breaker.reset(&BreakerScope::Global, "operator").await);
+    }
+}
@@ -0,0 +1,110 @@
+// Client Arbitrage - Cross-venue spreads between quotes from connected DEX/CEX clients
+// Prices every buy/sell venue combination through the fee model and keeps the net-profitable ones
+
+use rust_decimal::Decimal;
+
+use crate::{ArbitrageOpportunity, PriceInfo, TradeStep};
+use crate::fee_model::{FeeModel, PricedLeg, ProfitBreakdown};
+use crate::opportunity_scoring::OpportunityScore;
+
+/// Gross spread less every venue, gas and transfer cost for buying `notional_usd` of `base_asset`
+/// on `buy_exchange` and selling it on `sell_exchange`.
+pub async fn price_opportunity(
+    fee_model: &FeeModel,
+    buy_exchange: &str,
+    sell_exchange: &str,
+    buy_price: Decimal,
+    sell_price: Decimal,
+    notional_usd: Decimal,
+    base_asset: &str,
+) -> ProfitBreakdown {
+    if buy_price <= Decimal::ZERO {
+        return ProfitBreakdown::new(notional_usd, Decimal::ZERO);
+    }
+
+    let amount = notional_usd / buy_price;
+    let requires_transfer = fee_model.requires_transfer(buy_exchange, sell_exchange).await;
+
+    fee_model.evaluate_two_leg(
+        PricedLeg { venue: buy_exchange, price: buy_price },
+        PricedLeg { venue: sell_exchange, price: sell_price },
+        amount,
+        base_asset,
+        requires_transfer,
+    ).await
+}
+
+/// Compare every pair of quotes for `pair` and return the opportunities that are profitable after fees.
+/// Buys lift the ask and sells hit the bid, falling back to the mid when a venue has no two-sided quote.
+pub async fn find_opportunities(
+    fee_model: &FeeModel,
+    pair: &str,
+    quotes: &[PriceInfo],
+    trade_size_usd: Decimal,
+) -> Vec<ArbitrageOpportunity> {
+    let (base_asset, quote_asset) = pair.split_once('/').unwrap_or(("SOL", "USDC"));
+    let mut opportunities = Vec::new();
+
+    for buy in quotes {
+        for sell in quotes {
+            if buy.exchange == sell.exchange {
+                continue;
+            }
+            let buy_price = buy.ask.unwrap_or(buy.price);
+            let sell_price = sell.bid.unwrap_or(sell.price);
+            if sell_price <= buy_price {
+                continue;
+            }
+
+            let breakdown = price_opportunity(
+                fee_model, &buy.exchange, &sell.exchange, buy_price, sell_price, trade_size_usd, base_asset,
+            ).await;
+            if !breakdown.is_profitable() {
+                continue;
+            }
+            let amount = trade_size_usd / buy_price;
+            let now = chrono::Utc::now().timestamp() as u64;
+
+            opportunities.push(ArbitrageOpportunity {
+                id: format!("client_arb_{}_{}_{}", buy.exchange, sell.exchange, chrono::Utc::now().timestamp_millis()),
+                token_pair: pair.to_string(),
+                buy_exchange: buy.exchange.clone(),
+                sell_exchange: sell.exchange.clone(),
+                buy_price,
+                sell_price,
+                profit_percentage: breakdown.net_profit_percentage,
+                estimated_profit_usd: breakdown.net_profit_usd,
+                max_trade_size: trade_size_usd,
+                liquidity_score: 0.8,
+                risk_score: 0.3,
+                confidence: 0.8,
+                timestamp: now,
+                expires_at: now + 30,
+                trade_route: vec![
+                    TradeStep {
+                        exchange: buy.exchange.clone(),
+                        action: "buy".to_string(),
+                        from_token: quote_asset.to_string(),
+                        to_token: base_asset.to_string(),
+                        amount,
+                        price: buy_price,
+                        fees: breakdown.costs_for_venue(&buy.exchange),
+                    },
+                    TradeStep {
+                        exchange: sell.exchange.clone(),
+                        action: "sell".to_string(),
+                        from_token: base_asset.to_string(),
+                        to_token: quote_asset.to_string(),
+                        amount,
+                        price: sell_price,
+                        fees: breakdown.costs_for_venue(&sell.exchange),
+                    },
+                ],
+                profit_breakdown: breakdown,
+                score: OpportunityScore::default(),
+            });
+        }
+    }
+
+    opportunities
+}
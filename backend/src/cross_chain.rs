@@ -3,6 +3,10 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::fee_model::{self, FeeModel, ProfitBreakdown, TradeLeg, Liquidity};
+use crate::evm_tx::{self, EvmCallRequest, EvmFees, EvmRpcClient, EvmSigner, EvmTxSender};
+use crate::risk_gate::{OrderOrigin, PreTradeRequest, PreTradeRiskGate};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chain {
     pub id: String,
@@ -41,6 +45,7 @@ pub struct CrossChainRoute {
     pub estimated_time: u64,
     pub profit: f64,
     pub profit_percentage: f64,
+    pub profit_breakdown: ProfitBreakdown,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,10 +74,12 @@ pub struct CrossChainAggregator {
     bridges: Arc<RwLock<Vec<Bridge>>>,
     token_prices: Arc<RwLock<HashMap<(String, String), TokenPrice>>>, // (token, chain) -> price
     routes_cache: Arc<RwLock<HashMap<String, Vec<CrossChainRoute>>>>,
+    fee_model: Arc<FeeModel>,
+    risk_gate: Arc<PreTradeRiskGate>,
 }
 
 impl CrossChainAggregator {
-    pub fn new() -> Self {
+    pub fn new(fee_model: Arc<FeeModel>, risk_gate: Arc<PreTradeRiskGate>) -> Self {
         let mut chains = HashMap::new();
         
         // Initialize supported chains
@@ -184,6 +191,8 @@ impl CrossChainAggregator {
             bridges: Arc::new(RwLock::new(bridges)),
             token_prices: Arc::new(RwLock::new(HashMap::new())),
             routes_cache: Arc::new(RwLock::new(HashMap::new())),
+            fee_model,
+            risk_gate,
         }
     }
 
@@ -195,7 +204,6 @@ impl CrossChainAggregator {
     ) -> Vec<CrossChainRoute> {
         let mut opportunities = Vec::new();
         let prices = self.token_prices.read().await;
-        let chains = self.chains.read().await;
         let bridges = self.bridges.read().await;
         
         // Find price discrepancies across chains
@@ -220,18 +228,19 @@ impl CrossChainAggregator {
                 let (low_chain, low_price) = chain_prices[i];
                 let (high_chain, high_price) = chain_prices[j];
                 
-                let price_diff_percentage = ((high_price - low_price) / low_price) * 100.0;
-                
                 // Find bridge route
                 if let Some(bridge_route) = self.find_bridge_route(low_chain, high_chain, token, &bridges).await {
-                    let total_fee_percentage = bridge_route.iter()
-                        .map(|b| b.fee_percentage)
-                        .sum::<f64>();
-                    
-                    let gas_cost = self.estimate_gas_cost(low_chain, high_chain, &chains).await;
-                    let gas_cost_percentage = (gas_cost / (amount * low_price)) * 100.0;
-                    
-                    let net_profit_percentage = price_diff_percentage - total_fee_percentage - gas_cost_percentage;
+                    let buy_dex = Self::default_dex(low_chain);
+                    let sell_dex = Self::default_dex(high_chain);
+                    let breakdown = self.price_route(
+                        &bridge_route,
+                        buy_dex,
+                        sell_dex,
+                        amount,
+                        low_price,
+                        high_price,
+                    ).await;
+                    let net_profit_percentage = fee_model::to_f64(breakdown.net_profit_percentage);
                     
                     if net_profit_percentage > min_profit_percentage {
                         let estimated_time: u64 = bridge_route.iter()
@@ -249,27 +258,28 @@ impl CrossChainAggregator {
                             dex_swaps: vec![
                                 DexSwap {
                                     chain: low_chain.to_string(),
-                                    dex: "Jupiter".to_string(),
+                                    dex: buy_dex.to_string(),
                                     token_in: "USDC".to_string(),
                                     token_out: token.to_string(),
                                     amount_in: amount * low_price,
                                     amount_out: amount,
-                                    fee: 0.003,
+                                    fee: fee_model::to_f64(breakdown.costs_for_venue(buy_dex)),
                                 },
                                 DexSwap {
                                     chain: high_chain.to_string(),
-                                    dex: "Uniswap".to_string(),
+                                    dex: sell_dex.to_string(),
                                     token_in: token.to_string(),
                                     token_out: "USDC".to_string(),
                                     amount_in: amount,
-                                    amount_out: amount * high_price * (1.0 - total_fee_percentage),
-                                    fee: 0.003,
+                                    amount_out: amount * high_price,
+                                    fee: fee_model::to_f64(breakdown.costs_for_venue(sell_dex)),
                                 },
                             ],
-                            total_fee: total_fee_percentage * amount * low_price,
+                            total_fee: fee_model::to_f64(breakdown.total_costs_usd),
                             estimated_time,
-                            profit: amount * (high_price - low_price) * (1.0 - total_fee_percentage) - gas_cost,
+                            profit: fee_model::to_f64(breakdown.net_profit_usd),
                             profit_percentage: net_profit_percentage,
+                            profit_breakdown: breakdown,
                         });
                     }
                 }
@@ -315,30 +325,53 @@ impl CrossChainAggregator {
         None
     }
 
-    async fn estimate_gas_cost(&self, source: &str, destination: &str, chains: &HashMap<String, Chain>) -> f64 {
-        let mut total_cost = 0.0;
+    /// Itemize swap fees, bridge fees and gas on both chains for one route
+    async fn price_route(
+        &self,
+        bridge_route: &[Bridge],
+        buy_dex: &str,
+        sell_dex: &str,
+        amount: f64,
+        low_price: f64,
+        high_price: f64,
+    ) -> ProfitBreakdown {
+        let notional = fee_model::dec(amount * low_price);
+        let mut breakdown = ProfitBreakdown::new(notional, fee_model::dec(amount * (high_price - low_price)));
         
-        // Source chain gas cost
-        if let Some(source_chain) = chains.get(source) {
-            let gas_units = match source {
-                "ethereum" => 150000.0, // ETH transfer + bridge interaction
-                "solana" => 0.000005,   // SOL transaction fee
-                "bsc" => 100000.0,      // BSC gas units
-                "polygon" => 100000.0,   // Polygon gas units
-                "avalanche" => 100000.0, // Avalanche gas units
-                _ => 100000.0,
-            };
-            
-            total_cost += gas_units * source_chain.avg_gas_price * source_chain.gas_token_price / 1e9;
-        }
+        self.fee_model.apply_leg(&mut breakdown, &TradeLeg {
+            venue: buy_dex.to_string(),
+            notional_usd: notional,
+            liquidity: Liquidity::Taker,
+        }).await;
         
-        // Destination chain gas cost
-        if let Some(dest_chain) = chains.get(destination) {
-            let gas_units = 50000.0; // Claiming bridged tokens
-            total_cost += gas_units * dest_chain.avg_gas_price * dest_chain.gas_token_price / 1e9;
+        for bridge in bridge_route {
+            self.fee_model.apply_bridge(
+                &mut breakdown,
+                &bridge.id,
+                fee_model::dec(bridge.fee_percentage),
+                notional,
+                &bridge.source_chain,
+                &bridge.destination_chain,
+            ).await;
         }
         
-        total_cost
+        self.fee_model.apply_leg(&mut breakdown, &TradeLeg {
+            venue: sell_dex.to_string(),
+            notional_usd: fee_model::dec(amount * high_price),
+            liquidity: Liquidity::Taker,
+        }).await;
+        
+        breakdown
+    }
+
+    fn default_dex(chain: &str) -> &'static str {
+        match chain {
+            "solana" => "Jupiter",
+            "bsc" => "PancakeSwap",
+            "polygon" => "QuickSwap",
+            "avalanche" => "TraderJoe",
+            _ => "Uniswap",
+        }
     }
 
     pub async fn update_token_price(
@@ -349,6 +382,15 @@ impl CrossChainAggregator {
         liquidity: f64,
         volume_24h: f64,
     ) {
+        // Keep the fee model's gas pricing in step with the chain's native token
+        let is_native = self.chains.read().await
+            .get(chain)
+            .map(|c| c.native_token == token)
+            .unwrap_or(false);
+        if is_native {
+            self.fee_model.update_native_price(chain, fee_model::dec(price)).await;
+        }
+        
         let mut prices = self.token_prices.write().await;
         
         prices.insert(
@@ -368,14 +410,29 @@ impl CrossChainAggregator {
         &self,
         route: &CrossChainRoute,
     ) -> Result<CrossChainExecutionResult, String> {
+        let execution_id = uuid::Uuid::new_v4().to_string();
+        
+        // Pre-trade risk gate, valued at the source chain price
+        let notional_usd = self.token_prices.read().await
+            .get(&(route.token.clone(), route.source_chain.clone()))
+            .map(|p| p.price * route.amount);
+        let mut venues: Vec<String> = route.bridges.iter().map(|b| b.name.clone()).collect();
+        venues.extend(route.dex_swaps.iter().map(|s| s.dex.clone()));
+        let request = PreTradeRequest::new(&execution_id, OrderOrigin::CrossChain, &route.token, notional_usd)
+            .with_venues(venues)
+            .with_expected_profit(route.profit_percentage, 0.0);
+        if let Err(decision) = self.risk_gate.check(request).await {
+            return Err(format!("Risk management rejection: {}", decision.summary()));
+        }
+        
         // Placeholder for actual cross-chain execution
         // This would involve:
         // 1. Execute buy on source chain
         // 2. Bridge tokens to destination chain
         // 3. Wait for bridge confirmation
         // 4. Execute sell on destination chain
-        
-        let execution_id = uuid::Uuid::new_v4().to_string();
+        // The reservation should then be held until the destination leg settles
+        self.risk_gate.release(&execution_id).await;
         
         Ok(CrossChainExecutionResult {
             execution_id,
@@ -483,30 +540,118 @@ pub enum BridgeTransactionStatus {
 // Chain-specific connectors
 pub struct ChainConnector {
     chain: Chain,
-    rpc_client: reqwest::Client,
+    evm_rpc: Option<Arc<EvmRpcClient>>,
+    evm_sender: Option<EvmTxSender>,
 }
 
 impl ChainConnector {
+    /// EVM chains use `<ID>_RPC_URL` when set and sign with `EVM_PRIVATE_KEY` when present
     pub fn new(chain: Chain) -> Self {
+        if chain.id == "solana" {
+            return Self { chain, evm_rpc: None, evm_sender: None };
+        }
+
+        let rpc_url = std::env::var(format!("{}_RPC_URL", chain.id.to_uppercase()))
+            .unwrap_or_else(|_| chain.rpc_url.clone());
+        let rpc = Arc::new(EvmRpcClient::new(&rpc_url));
+        let evm_sender = EvmSigner::from_env()
+            .ok()
+            .map(|signer| EvmTxSender::new(chain.chain_id, rpc.clone(), Arc::new(signer)));
+
         Self {
             chain,
-            rpc_client: reqwest::Client::new(),
+            evm_rpc: Some(rpc),
+            evm_sender,
+        }
+    }
+
+    /// Sign with a keystore account instead of the plaintext EVM_PRIVATE_KEY
+    pub fn with_evm_signer(mut self, signer: Arc<EvmSigner>) -> Self {
+        if let Some(rpc) = &self.evm_rpc {
+            self.evm_sender = Some(EvmTxSender::new(self.chain.chain_id, rpc.clone(), signer));
         }
+        self
+    }
+
+    pub fn evm_sender(&self) -> Option<&EvmTxSender> {
+        self.evm_sender.as_ref()
+    }
+
+    fn rpc(&self) -> Result<&Arc<EvmRpcClient>, String> {
+        self.evm_rpc.as_ref().ok_or_else(|| format!("{} is not an EVM chain", self.chain.name))
     }
 
+    /// Balance in whole units; `token` is the native symbol or an ERC-20 contract address
     pub async fn get_token_balance(&self, address: &str, token: &str) -> Result<f64, String> {
-        // Placeholder - would implement chain-specific balance queries
-        Ok(1000.0)
+        let rpc = self.rpc()?;
+        let owner = evm_tx::parse_address(address).map_err(|e| e.to_string())?;
+
+        if token.eq_ignore_ascii_case(&self.chain.native_token) {
+            let wei = rpc.balance(&owner).await.map_err(|e| e.to_string())?;
+            return Ok(evm_tx::from_wei(wei, 18));
+        }
+
+        let contract = evm_tx::parse_address(token).map_err(|e| e.to_string())?;
+        let balance = rpc.eth_call(&contract, &evm_tx::erc20_balance_of(owner)).await
+            .and_then(|data| evm_tx::decode_uint(&data, 0))
+            .map_err(|e| e.to_string())?;
+        let decimals = rpc.eth_call(&contract, &evm_tx::erc20_decimals()).await
+            .and_then(|data| evm_tx::decode_uint(&data, 0))
+            .map_err(|e| e.to_string())?;
+        Ok(evm_tx::from_wei(balance, decimals as u32))
     }
 
+    /// Expected fee in the native token
     pub async fn estimate_gas(&self, tx_data: &TransactionData) -> Result<f64, String> {
-        // Placeholder - would implement chain-specific gas estimation
-        Ok(0.001)
+        let rpc = self.rpc()?;
+        let request = Self::call_request(tx_data)?;
+        let from = evm_tx::parse_address(&tx_data.from).map_err(|e| e.to_string())?;
+
+        let gas = match tx_data.gas_limit {
+            Some(limit) => limit,
+            None => rpc.estimate_gas(&from, request.to.as_ref(), request.value, &request.data).await
+                .map_err(|e| e.to_string())?,
+        };
+        let gas_price = match request.fees {
+            Some(fees) => fees.max_gas_price(),
+            None => match &self.evm_sender {
+                Some(sender) => sender.suggest_fees().await.map_err(|e| e.to_string())?.max_gas_price(),
+                None => rpc.gas_price().await.map_err(|e| e.to_string())?,
+            },
+        };
+        Ok(evm_tx::from_wei(gas as u128 * gas_price, 18))
     }
 
     pub async fn send_transaction(&self, tx_data: &TransactionData) -> Result<String, String> {
-        // Placeholder - would implement chain-specific transaction sending
-        Ok(format!("0x{}", uuid::Uuid::new_v4().to_string().replace("-", "")))
+        self.rpc()?;
+        let sender = self.evm_sender.as_ref()
+            .ok_or_else(|| "EVM_PRIVATE_KEY is not configured".to_string())?;
+        let from = evm_tx::parse_address(&tx_data.from).map_err(|e| e.to_string())?;
+        if from != sender.address() {
+            return Err(format!("Signer {} does not match sender {}", evm_tx::format_address(&sender.address()), tx_data.from));
+        }
+
+        let signed = sender.send(Self::call_request(tx_data)?).await.map_err(|e| e.to_string())?;
+        Ok(signed.hash_hex())
+    }
+
+    /// `value` is in the native token and `gas_price` in gwei (forces a legacy transaction)
+    fn call_request(tx_data: &TransactionData) -> Result<EvmCallRequest, String> {
+        let to = if tx_data.to.is_empty() {
+            None
+        } else {
+            Some(evm_tx::parse_address(&tx_data.to).map_err(|e| e.to_string())?)
+        };
+        let data = hex::decode(tx_data.data.trim_start_matches("0x"))
+            .map_err(|e| format!("Invalid calldata: {}", e))?;
+        Ok(EvmCallRequest {
+            to,
+            value: evm_tx::to_wei(tx_data.value, 18),
+            data,
+            gas_limit: tx_data.gas_limit,
+            fees: tx_data.gas_price.map(|gwei| EvmFees::Legacy { gas_price: evm_tx::to_wei(gwei, 9) }),
+            nonce: tx_data.nonce,
+        })
     }
 }
 
@@ -11,6 +11,11 @@ use warp::http::StatusCode;
 use anyhow::Result;
 use log::{info, warn};
 use crate::external_apis::ExternalApiClient;
+use crate::circuit_breaker::{BreakerScope, CircuitBreaker};
+use crate::auth::{self, AuthError, AuthManager, Role, Session};
+use crate::tx_policy::TxPolicyEngine;
+use crate::ledger::{CostBasisMethod, ReportFormat, ReportGrouping, TaxLotLedger};
+use crate::risk_gate::PreTradeRiskGate;
 use chrono;
 use rand;
 
@@ -162,6 +167,72 @@ pub struct ExecutionMetricsResponse {
     pub sharpe_ratio: f64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillSwitchAction {
+    Trip,
+    Reset,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitchRequest {
+    pub action: KillSwitchAction,
+    #[serde(default = "default_kill_scope")]
+    pub scope: BreakerScope,
+    #[serde(default)]
+    pub detail: Option<String>,
+    #[serde(default)]
+    pub actor: Option<String>,
+}
+
+fn default_kill_scope() -> BreakerScope {
+    BreakerScope::Global
+}
+
+// Static bearer token accepted by the kill-switch endpoint in addition to admin sessions
+const KILL_SWITCH_TOKEN_ENV: &str = "DEXTER_KILL_SWITCH_TOKEN";
+
+// Query for /pnl-report; `from`/`to` are unix seconds, defaulting to the last 30 days
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlReportQuery {
+    pub method: Option<CostBasisMethod>,
+    pub group_by: Option<ReportGrouping>,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    pub format: Option<ReportFormat>,
+}
+
+// Query for /risk-decisions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskDecisionsQuery {
+    pub limit: Option<usize>,
+    pub rejected: Option<bool>, // Only rejections
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecisionRequest {
+    pub approve: bool,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletLoginRequest {
+    pub message: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyLoginRequest {
+    pub api_key: String,
+}
+
+// Rejection carried through warp so the recover handler can answer 401/403
+#[derive(Debug)]
+struct AuthRejection(AuthError);
+
+impl warp::reject::Reject for AuthRejection {}
+
 // Dashboard API Server
 pub struct DashboardApiServer {
     arbitrage_opportunities: Arc<RwLock<Vec<DashboardArbitrageOpportunity>>>,
@@ -171,6 +242,11 @@ pub struct DashboardApiServer {
     technical_indicators: Arc<RwLock<HashMap<String, Vec<TechnicalIndicator>>>>,
     platform_stats: Arc<Mutex<PlatformStats>>,
     external_api_client: Arc<ExternalApiClient>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    auth: Option<Arc<AuthManager>>,
+    policy_engine: Option<Arc<TxPolicyEngine>>,
+    ledger: Option<Arc<TaxLotLedger>>,
+    risk_gate: Option<Arc<PreTradeRiskGate>>,
     port: u16,
 }
 
@@ -192,10 +268,41 @@ impl DashboardApiServer {
                 active_strategies: 0,
             })),
             external_api_client,
+            circuit_breaker: None,
+            auth: None,
+            policy_engine: None,
+            ledger: None,
+            risk_gate: None,
             port,
         }
     }
 
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreaker>) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Without an auth manager every protected route answers 401
+    pub fn with_auth(mut self, auth: Arc<AuthManager>) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_policy_engine(mut self, policy_engine: Arc<TxPolicyEngine>) -> Self {
+        self.policy_engine = Some(policy_engine);
+        self
+    }
+
+    pub fn with_ledger(mut self, ledger: Arc<TaxLotLedger>) -> Self {
+        self.ledger = Some(ledger);
+        self
+    }
+
+    pub fn with_risk_gate(mut self, risk_gate: Arc<PreTradeRiskGate>) -> Self {
+        self.risk_gate = Some(risk_gate);
+        self
+    }
+
     pub async fn start(self: Arc<Self>) -> Result<()> {
         info!("🌐 Starting Dashboard API server on port {}", self.port);
 
@@ -207,12 +314,34 @@ impl DashboardApiServer {
         let threats = self.mev_threats.clone();
         let indicators = self.technical_indicators.clone();
         let stats = self.platform_stats.clone();
+        let breakers = self.circuit_breaker.clone();
+        let kill_switch = self.circuit_breaker.clone();
+        let kill_switch_auth = self.auth.clone();
+        let nonce_auth = self.auth.clone();
+        let wallet_auth = self.auth.clone();
+        let api_key_auth = self.auth.clone();
+        let logout_auth = self.auth.clone();
+        let approvals_policy = self.policy_engine.clone();
+        let decide_policy = self.policy_engine.clone();
+        let audit_policy = self.policy_engine.clone();
+        let report_ledger = self.ledger.clone();
+        let decisions_gate = self.risk_gate.clone();
 
-        // CORS headers
+        // CORS headers - restricted to the configured dashboard origins
+        let origins = match &self.auth {
+            Some(auth) => auth.allowed_origins().await,
+            None => Vec::new(),
+        };
         let cors = warp::cors()
-            .allow_any_origin()
-            .allow_headers(vec!["content-type"])
+            .allow_headers(vec!["content-type", "authorization"])
             .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]);
+        let cors = if origins.is_empty() {
+            warn!("⚠️ Dashboard API accepting requests from any origin");
+            cors.allow_any_origin()
+        } else {
+            info!("🔒 Dashboard API CORS origins: {:?}", origins);
+            cors.allow_origins(origins.iter().map(String::as_str))
+        };
 
         // API Routes
         let api = warp::path("api").and(warp::path("v1"));
@@ -221,6 +350,7 @@ impl DashboardApiServer {
         let opportunities_route = api
             .and(warp::path("opportunities"))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || opportunities.clone()))
             .and_then(get_arbitrage_opportunities);
 
@@ -228,6 +358,7 @@ impl DashboardApiServer {
         let simulate_flashloan_route = api
             .and(warp::path("simulate-flashloan"))
             .and(warp::post())
+            .and(require_role(self.auth.clone(), Role::Trader))
             .and(warp::body::json())
             .and(warp::any().map(move || simulations.clone()))
             .and_then(simulate_flash_loan);
@@ -236,6 +367,7 @@ impl DashboardApiServer {
         let market_depth_route = api
             .and(warp::path!("market-depth" / String / String))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || depth_market.clone()))
             .and_then(get_market_depth_by_parts);
 
@@ -243,6 +375,7 @@ impl DashboardApiServer {
         let market_depth_pairs_route = api
             .and(warp::path("market-depth"))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || depth_pairs.clone()))
             .and_then(list_market_depth_pairs);
 
@@ -250,6 +383,7 @@ impl DashboardApiServer {
         let mev_threats_route = api
             .and(warp::path("mev-threats"))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || threats.clone()))
             .and_then(get_mev_threats);
 
@@ -257,6 +391,7 @@ impl DashboardApiServer {
         let indicators_route = api
             .and(warp::path!("technical-indicators" / String))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || indicators.clone()))
             .and_then(get_technical_indicators);
 
@@ -264,9 +399,110 @@ impl DashboardApiServer {
         let stats_route = api
             .and(warp::path("platform-stats"))
             .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
             .and(warp::any().map(move || stats.clone()))
             .and_then(get_platform_stats);
 
+        // GET /api/v1/circuit-breakers - Open breakers and recent breaker events
+        let breakers_route = api
+            .and(warp::path("circuit-breakers"))
+            .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
+            .and(warp::any().map(move || breakers.clone()))
+            .and_then(get_circuit_breakers);
+
+        // POST /api/v1/kill-switch - Trip or reset a breaker (admin session or kill-switch token)
+        let kill_switch_route = api
+            .and(warp::path("kill-switch"))
+            .and(warp::post())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::body::json())
+            .and(warp::any().map(move || kill_switch.clone()))
+            .and(warp::any().map(move || kill_switch_auth.clone()))
+            .and_then(handle_kill_switch);
+
+        // GET /api/v1/approvals - Transactions held for manual approval
+        let approvals_route = api
+            .and(warp::path("approvals"))
+            .and(warp::path::end())
+            .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Trader))
+            .and(warp::any().map(move || approvals_policy.clone()))
+            .and_then(get_approvals);
+
+        // POST /api/v1/approvals/{id} - Approve or reject a held transaction
+        let approval_decision_route = api
+            .and(warp::path!("approvals" / String))
+            .and(warp::post())
+            .and(require_session(self.auth.clone(), Role::Admin))
+            .and(warp::body::json())
+            .and(warp::any().map(move || decide_policy.clone()))
+            .and_then(decide_approval);
+
+        // GET /api/v1/policy-audit - Recent transaction policy decisions
+        let policy_audit_route = api
+            .and(warp::path("policy-audit"))
+            .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Admin))
+            .and(warp::any().map(move || audit_policy.clone()))
+            .and_then(get_policy_audit);
+
+        // GET /api/v1/pnl-report - Realized/unrealized P&L by wallet, venue or strategy (JSON or CSV)
+        let pnl_report_route = api
+            .and(warp::path("pnl-report"))
+            .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
+            .and(warp::query::<PnlReportQuery>())
+            .and(warp::any().map(move || report_ledger.clone()))
+            .and_then(get_pnl_report);
+
+        // GET /api/v1/risk-decisions - Pre-trade risk gate decisions with structured rejections
+        let risk_decisions_route = api
+            .and(warp::path("risk-decisions"))
+            .and(warp::get())
+            .and(require_role(self.auth.clone(), Role::Viewer))
+            .and(warp::query::<RiskDecisionsQuery>())
+            .and(warp::any().map(move || decisions_gate.clone()))
+            .and_then(get_risk_decisions);
+
+        // GET /api/v1/auth/nonce/{address} - Nonce for the wallet's sign-in message
+        let nonce_route = api
+            .and(warp::path!("auth" / "nonce" / String))
+            .and(warp::get())
+            .and(warp::any().map(move || nonce_auth.clone()))
+            .and_then(get_auth_nonce);
+
+        // POST /api/v1/auth/wallet - Exchange a signed SIWE/SIWS message for a session
+        let wallet_login_route = api
+            .and(warp::path!("auth" / "wallet"))
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(warp::any().map(move || wallet_auth.clone()))
+            .and_then(login_wallet);
+
+        // POST /api/v1/auth/api-key - Exchange an API key for a session
+        let api_key_login_route = api
+            .and(warp::path!("auth" / "api-key"))
+            .and(warp::post())
+            .and(warp::body::json())
+            .and(warp::any().map(move || api_key_auth.clone()))
+            .and_then(login_api_key);
+
+        // GET /api/v1/auth/session - Describe the caller's session
+        let session_route = api
+            .and(warp::path!("auth" / "session"))
+            .and(warp::get())
+            .and(require_session(self.auth.clone(), Role::Viewer))
+            .map(|session: Session| warp::reply::json(&session));
+
+        // POST /api/v1/auth/logout - Revoke the caller's session
+        let logout_route = api
+            .and(warp::path!("auth" / "logout"))
+            .and(warp::post())
+            .and(warp::header::optional::<String>("authorization"))
+            .and(warp::any().map(move || logout_auth.clone()))
+            .and_then(logout);
+
         // Health check
         let health_route = warp::path("health")
             .and(warp::get())
@@ -280,7 +516,20 @@ impl DashboardApiServer {
             .or(mev_threats_route)
             .or(indicators_route)
             .or(stats_route)
+            .or(breakers_route)
+            .or(kill_switch_route)
+            .or(approvals_route)
+            .or(approval_decision_route)
+            .or(policy_audit_route)
+            .or(pnl_report_route)
+            .or(risk_decisions_route)
+            .or(nonce_route)
+            .or(wallet_login_route)
+            .or(api_key_login_route)
+            .or(session_route)
+            .or(logout_route)
             .or(health_route)
+            .recover(handle_rejection)
             .with(cors);
 
         // Start data generation tasks
@@ -576,11 +825,67 @@ impl Clone for DashboardApiServer {
             technical_indicators: self.technical_indicators.clone(),
             platform_stats: self.platform_stats.clone(),
             external_api_client: self.external_api_client.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            auth: self.auth.clone(),
+            policy_engine: self.policy_engine.clone(),
+            ledger: self.ledger.clone(),
+            risk_gate: self.risk_gate.clone(),
             port: self.port,
         }
     }
 }
 
+// Authorization filters
+fn require_session(
+    auth: Option<Arc<AuthManager>>,
+    role: Role,
+) -> impl Filter<Extract = (Session,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("authorization")
+        .and(warp::any().map(move || auth.clone()))
+        .and_then(move |authorization: Option<String>, auth: Option<Arc<AuthManager>>| async move {
+            let Some(auth) = auth else {
+                return Err(warp::reject::custom(AuthRejection(AuthError::MissingToken)));
+            };
+            auth.authorize(auth::bearer_token(authorization.as_deref()), role).await
+                .map_err(|e| warp::reject::custom(AuthRejection(e)))
+        })
+}
+
+fn require_role(
+    auth: Option<Arc<AuthManager>>,
+    role: Role,
+) -> impl Filter<Extract = (), Error = warp::Rejection> + Clone {
+    require_session(auth, role).map(|_| ()).untuple_one()
+}
+
+async fn handle_rejection(err: warp::Rejection) -> Result<impl Reply, std::convert::Infallible> {
+    let (status, message) = if let Some(AuthRejection(error)) = err.find::<AuthRejection>() {
+        let status = StatusCode::from_u16(error.status_code()).unwrap_or(StatusCode::UNAUTHORIZED);
+        (status, error.to_string())
+    } else if err.is_not_found() {
+        (StatusCode::NOT_FOUND, "Not found".to_string())
+    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+        (StatusCode::METHOD_NOT_ALLOWED, "Method not allowed".to_string())
+    } else if err.find::<warp::body::BodyDeserializeError>().is_some() {
+        (StatusCode::BAD_REQUEST, "Invalid request body".to_string())
+    } else {
+        warn!("⚠️ Unhandled dashboard API rejection: {:?}", err);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Internal error".to_string())
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"error": message})),
+        status,
+    ))
+}
+
+fn auth_unavailable() -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"error": "Authentication is not configured"})),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
 // Handler functions
 async fn get_arbitrage_opportunities(
     opportunities: Arc<RwLock<Vec<DashboardArbitrageOpportunity>>>,
@@ -670,4 +975,273 @@ async fn get_platform_stats(
         total_profit: stats.total_profit.to_f64().unwrap(),
         active_strategies: stats.active_strategies,
     }))
-}
\ No newline at end of file
+}
+
+async fn get_circuit_breakers(
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+) -> Result<impl Reply, warp::Rejection> {
+    match circuit_breaker {
+        Some(breaker) => Ok(warp::reply::json(&serde_json::json!({
+            "trips": breaker.get_trips().await,
+            "events": breaker.get_events(100).await,
+        }))),
+        None => Ok(warp::reply::json(&serde_json::json!({"trips": [], "events": []}))),
+    }
+}
+
+async fn handle_kill_switch(
+    authorization: Option<String>,
+    request: KillSwitchRequest,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    auth: Option<Arc<AuthManager>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let provided = auth::bearer_token(authorization.as_deref());
+
+    // Admin sessions first, then the static token for scripts and runbooks
+    let session = match &auth {
+        Some(auth) => Some(auth.authorize(provided, Role::Admin).await),
+        None => None,
+    };
+    let session_actor = match session {
+        Some(Ok(session)) => Some(session.subject),
+        Some(Err(AuthError::Forbidden { .. })) => {
+            warn!("🚫 Rejected kill switch request from non-admin session");
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error": "Forbidden"})),
+                StatusCode::FORBIDDEN,
+            ));
+        }
+        _ => None,
+    };
+
+    if session_actor.is_none() {
+        let token_valid = match std::env::var(KILL_SWITCH_TOKEN_ENV) {
+            Ok(expected) if !expected.is_empty() => {
+                auth::constant_time_eq(provided.unwrap_or("").as_bytes(), expected.as_bytes())
+            }
+            _ => false,
+        };
+        if !token_valid {
+            warn!("🚫 Rejected kill switch request with invalid credentials");
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error": "Unauthorized"})),
+                StatusCode::UNAUTHORIZED,
+            ));
+        }
+    }
+
+    let Some(breaker) = circuit_breaker else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error": "Circuit breaker not attached"})),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    };
+
+    // A session names its own actor; only token callers may self-report
+    let actor = session_actor
+        .or(request.actor)
+        .unwrap_or_else(|| "dashboard".to_string());
+    let changed = match request.action {
+        KillSwitchAction::Trip => {
+            let detail = request.detail.unwrap_or_else(|| "Kill switch engaged from dashboard".to_string());
+            breaker.kill(request.scope.clone(), &detail, &actor).await;
+            true
+        }
+        KillSwitchAction::Reset => breaker.reset(&request.scope, &actor).await,
+    };
+
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "action": request.action,
+            "scope": request.scope,
+            "changed": changed,
+            "trips": breaker.get_trips().await,
+        })),
+        StatusCode::OK,
+    ))
+}
+
+fn policy_unavailable() -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"error": "Transaction policy engine not attached"})),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
+async fn get_approvals(
+    policy_engine: Option<Arc<TxPolicyEngine>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(engine) = policy_engine else {
+        return Ok(policy_unavailable());
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "pending": engine.get_approvals(true).await,
+            "recent": engine.get_approvals(false).await,
+        })),
+        StatusCode::OK,
+    ))
+}
+
+async fn decide_approval(
+    approval_id: String,
+    session: Session,
+    request: ApprovalDecisionRequest,
+    policy_engine: Option<Arc<TxPolicyEngine>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(engine) = policy_engine else {
+        return Ok(policy_unavailable());
+    };
+    let decided = if request.approve {
+        engine.approve(&approval_id, &session.subject).await
+    } else {
+        engine.reject(&approval_id, &session.subject, request.note).await
+    };
+    Ok(match decided {
+        Ok(approval) => warp::reply::with_status(warp::reply::json(&approval), StatusCode::OK),
+        Err(e) => warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error": e})),
+            StatusCode::CONFLICT,
+        ),
+    })
+}
+
+async fn get_policy_audit(
+    policy_engine: Option<Arc<TxPolicyEngine>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(engine) = policy_engine else {
+        return Ok(policy_unavailable());
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"entries": engine.get_audit_log(500).await})),
+        StatusCode::OK,
+    ))
+}
+
+async fn get_risk_decisions(
+    query: RiskDecisionsQuery,
+    risk_gate: Option<Arc<PreTradeRiskGate>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(gate) = risk_gate else {
+        return Ok(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error": "Risk gate not attached"})),
+            StatusCode::SERVICE_UNAVAILABLE,
+        ));
+    };
+    let decisions = gate.get_decisions(query.limit.unwrap_or(100).min(1000), query.rejected.unwrap_or(false)).await;
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({
+            "decisions": decisions,
+            "in_flight": gate.get_reservations().await,
+            "config": gate.get_config().await,
+        })),
+        StatusCode::OK,
+    ))
+}
+
+async fn get_pnl_report(
+    query: PnlReportQuery,
+    ledger: Option<Arc<TaxLotLedger>>,
+) -> Result<Box<dyn Reply>, warp::Rejection> {
+    let Some(ledger) = ledger else {
+        return Ok(Box::new(warp::reply::with_status(
+            warp::reply::json(&serde_json::json!({"error": "Ledger not attached"})),
+            StatusCode::SERVICE_UNAVAILABLE,
+        )));
+    };
+    let to = query.to.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64);
+    let from = query.from.unwrap_or_else(|| to.saturating_sub(30 * 86_400));
+    let method = match query.method {
+        Some(method) => method,
+        None => ledger.get_config().await.method,
+    };
+    let report = ledger.report(method, query.group_by.unwrap_or(ReportGrouping::Strategy), from, to).await;
+
+    match query.format.unwrap_or(ReportFormat::Json) {
+        ReportFormat::Json => Ok(Box::new(warp::reply::json(&report))),
+        ReportFormat::Csv => Ok(Box::new(warp::reply::with_header(
+            report.to_csv(),
+            "content-type",
+            "text/csv; charset=utf-8",
+        ))),
+    }
+}
+
+async fn get_auth_nonce(
+    address: String,
+    auth: Option<Arc<AuthManager>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(auth) = auth else {
+        return Ok(auth_unavailable());
+    };
+    let nonce = match auth.wallet_nonce(&address).await {
+        Ok(nonce) => nonce,
+        Err(e) => {
+            let status = StatusCode::from_u16(e.status_code()).unwrap_or(StatusCode::BAD_REQUEST);
+            return Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error": e.to_string()})),
+                status,
+            ));
+        }
+    };
+    let domain = auth.get_config().await.sign_in_domain;
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"nonce": nonce, "domain": domain})),
+        StatusCode::OK,
+    ))
+}
+
+async fn login_wallet(
+    request: WalletLoginRequest,
+    auth: Option<Arc<AuthManager>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(auth) = auth else {
+        return Ok(auth_unavailable());
+    };
+    match auth.login_wallet(&request.message, &request.signature).await {
+        Ok(issued) => Ok(warp::reply::with_status(warp::reply::json(&issued), StatusCode::OK)),
+        Err(e) => {
+            warn!("🚫 Wallet sign-in rejected: {}", e);
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error": e.to_string()})),
+                StatusCode::UNAUTHORIZED,
+            ))
+        }
+    }
+}
+
+async fn login_api_key(
+    request: ApiKeyLoginRequest,
+    auth: Option<Arc<AuthManager>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(auth) = auth else {
+        return Ok(auth_unavailable());
+    };
+    match auth.login_api_key(&request.api_key).await {
+        Ok(issued) => Ok(warp::reply::with_status(warp::reply::json(&issued), StatusCode::OK)),
+        Err(e) => {
+            warn!("🚫 API key login rejected");
+            Ok(warp::reply::with_status(
+                warp::reply::json(&serde_json::json!({"error": e.to_string()})),
+                StatusCode::UNAUTHORIZED,
+            ))
+        }
+    }
+}
+
+async fn logout(
+    authorization: Option<String>,
+    auth: Option<Arc<AuthManager>>,
+) -> Result<impl Reply, warp::Rejection> {
+    let Some(auth) = auth else {
+        return Ok(auth_unavailable());
+    };
+    let revoked = match auth::bearer_token(authorization.as_deref()) {
+        Some(token) => auth.logout(token).await,
+        None => false,
+    };
+    Ok(warp::reply::with_status(
+        warp::reply::json(&serde_json::json!({"revoked": revoked})),
+        StatusCode::OK,
+    ))
+}
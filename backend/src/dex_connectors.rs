@@ -215,7 +215,7 @@ impl DexConnector for OrcaConnector {
 }
 
 pub struct DexAggregator {
-    connectors: HashMap<String, Box<dyn DexConnector>>,
+    pub(crate) connectors: HashMap<String, Box<dyn DexConnector>>,
 }
 
 impl DexAggregator {
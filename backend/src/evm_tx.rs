@@ -0,0 +1,1271 @@
+// EVM Transactions - ABI encoding, EIP-1559/legacy signing, nonce management and JSON-RPC for EVM chains
+// Router calls for Uniswap V2/V3 and ERC-20, works against any node including local anvil/hardhat instances
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use reqwest::Client;
+use anyhow::{Result, anyhow, Context};
+use log::{info, warn, debug};
+use sha3::{Digest, Keccak256};
+use k256::ecdsa::{SigningKey, VerifyingKey};
+
+pub type Address = [u8; 20];
+
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+const WEI_PER_GWEI: u128 = 1_000_000_000;
+
+// ============================================================================
+// HASHING & ADDRESSES
+// ============================================================================
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// First four bytes of keccak256 of the canonical signature, e.g. `approve(address,uint256)`
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// EIP-191 `personal_sign` digest: keccak256("\x19Ethereum Signed Message:\n" + len + message)
+pub fn hash_personal_message(message: &[u8]) -> [u8; 32] {
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend_from_slice(message);
+    keccak256(&data)
+}
+
+pub fn parse_address(value: &str) -> Result<Address> {
+    let bytes = hex::decode(value.trim().trim_start_matches("0x"))
+        .with_context(|| format!("Invalid address {}", value))?;
+    bytes.try_into().map_err(|_| anyhow!("Address {} is not 20 bytes", value))
+}
+
+/// EIP-55 mixed-case checksum encoding
+pub fn format_address(address: &Address) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+    let checksummed: String = lower.chars().enumerate().map(|(i, c)| {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        if c.is_ascii_alphabetic() && nibble >= 8 { c.to_ascii_uppercase() } else { c }
+    }).collect();
+    format!("0x{}", checksummed)
+}
+
+pub fn address_from_public_key(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    let mut address = [0u8; 20];
+    address.copy_from_slice(&hash[12..]);
+    address
+}
+
+pub fn to_wei(amount: f64, decimals: u32) -> u128 {
+    (amount * 10f64.powi(decimals as i32)).round().max(0.0) as u128
+}
+
+pub fn from_wei(amount: u128, decimals: u32) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+// ============================================================================
+// ABI ENCODING
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AbiToken {
+    Address(Address),
+    Uint([u8; 32]),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    AddressArray(Vec<Address>),
+    Tuple(Vec<AbiToken>),
+    Array(Vec<AbiToken>), // Dynamic-length array of any element type, e.g. Multicall3 call structs
+}
+
+impl AbiToken {
+    pub fn uint(value: u128) -> Self {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&value.to_be_bytes());
+        AbiToken::Uint(word)
+    }
+
+    /// type(uint256).max, the conventional unlimited approval
+    pub fn uint_max() -> Self {
+        AbiToken::Uint([0xff; 32])
+    }
+
+    fn is_dynamic(&self) -> bool {
+        match self {
+            AbiToken::Bytes(_) | AbiToken::AddressArray(_) | AbiToken::Array(_) => true,
+            AbiToken::Tuple(items) => items.iter().any(|t| t.is_dynamic()),
+            _ => false,
+        }
+    }
+
+    fn head_len(&self) -> usize {
+        match self {
+            AbiToken::Tuple(items) if !self.is_dynamic() => items.iter().map(|t| t.head_len()).sum(),
+            _ => 32,
+        }
+    }
+
+    fn encode_static(&self) -> Vec<u8> {
+        match self {
+            AbiToken::Address(address) => {
+                let mut word = vec![0u8; 12];
+                word.extend_from_slice(address);
+                word
+            }
+            AbiToken::Uint(word) => word.to_vec(),
+            AbiToken::Bool(value) => AbiToken::uint(*value as u128).encode_static(),
+            AbiToken::Tuple(items) => encode(items),
+            AbiToken::Bytes(_) | AbiToken::AddressArray(_) | AbiToken::Array(_) => {
+                unreachable!("dynamic token in static position")
+            }
+        }
+    }
+
+    fn encode_tail(&self) -> Vec<u8> {
+        match self {
+            AbiToken::Bytes(bytes) => {
+                let mut out = AbiToken::uint(bytes.len() as u128).encode_static();
+                out.extend_from_slice(bytes);
+                out.resize(out.len() + (32 - bytes.len() % 32) % 32, 0);
+                out
+            }
+            AbiToken::AddressArray(addresses) => {
+                let mut out = AbiToken::uint(addresses.len() as u128).encode_static();
+                for address in addresses {
+                    out.extend(AbiToken::Address(*address).encode_static());
+                }
+                out
+            }
+            AbiToken::Array(items) => {
+                let mut out = AbiToken::uint(items.len() as u128).encode_static();
+                out.extend(encode(items));
+                out
+            }
+            AbiToken::Tuple(items) => encode(items),
+            _ => self.encode_static(),
+        }
+    }
+}
+
+/// Standard head/tail encoding of a parameter list
+pub fn encode(tokens: &[AbiToken]) -> Vec<u8> {
+    let head_len: usize = tokens.iter().map(|t| t.head_len()).sum();
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+    for token in tokens {
+        if token.is_dynamic() {
+            head.extend(AbiToken::uint((head_len + tail.len()) as u128).encode_static());
+            tail.extend(token.encode_tail());
+        } else {
+            head.extend(token.encode_static());
+        }
+    }
+    head.extend(tail);
+    head
+}
+
+pub fn encode_call(signature: &str, tokens: &[AbiToken]) -> Vec<u8> {
+    let mut data = selector(signature).to_vec();
+    data.extend(encode(tokens));
+    data
+}
+
+/// Word `index` of the return data as an integer, saturating above u128
+pub fn decode_uint(data: &[u8], index: usize) -> Result<u128> {
+    let word = data.get(index * 32..index * 32 + 32)
+        .ok_or_else(|| anyhow!("Return data too short for word {}", index))?;
+    if word[..16].iter().any(|b| *b != 0) {
+        return Ok(u128::MAX);
+    }
+    Ok(u128::from_be_bytes(word[16..].try_into().expect("16-byte slice")))
+}
+
+/// A single returned `uint256[]`
+pub fn decode_uint_array(data: &[u8]) -> Result<Vec<u128>> {
+    let offset = decode_uint(data, 0)? as usize;
+    let body = data.get(offset..).ok_or_else(|| anyhow!("Array offset out of range"))?;
+    let len = decode_uint(body, 0)? as usize;
+    (1..=len).map(|i| decode_uint(body, i)).collect()
+}
+
+// ============================================================================
+// ROUTER & TOKEN CALLS
+// ============================================================================
+
+pub fn erc20_approve(spender: Address, amount: Option<u128>) -> Vec<u8> {
+    let amount = amount.map(AbiToken::uint).unwrap_or_else(AbiToken::uint_max);
+    encode_call("approve(address,uint256)", &[AbiToken::Address(spender), amount])
+}
+
+pub fn erc20_transfer(to: Address, amount: u128) -> Vec<u8> {
+    encode_call("transfer(address,uint256)", &[AbiToken::Address(to), AbiToken::uint(amount)])
+}
+
+pub fn erc20_balance_of(owner: Address) -> Vec<u8> {
+    encode_call("balanceOf(address)", &[AbiToken::Address(owner)])
+}
+
+pub fn erc20_decimals() -> Vec<u8> {
+    encode_call("decimals()", &[])
+}
+
+pub fn uniswap_v2_swap_exact_tokens_for_tokens(
+    amount_in: u128,
+    amount_out_min: u128,
+    path: Vec<Address>,
+    to: Address,
+    deadline: u64,
+) -> Vec<u8> {
+    encode_call("swapExactTokensForTokens(uint256,uint256,address[],address,uint256)", &[
+        AbiToken::uint(amount_in),
+        AbiToken::uint(amount_out_min),
+        AbiToken::AddressArray(path),
+        AbiToken::Address(to),
+        AbiToken::uint(deadline as u128),
+    ])
+}
+
+/// Native value is sent as the transaction value; path[0] must be the wrapped native token
+pub fn uniswap_v2_swap_exact_eth_for_tokens(amount_out_min: u128, path: Vec<Address>, to: Address, deadline: u64) -> Vec<u8> {
+    encode_call("swapExactETHForTokens(uint256,address[],address,uint256)", &[
+        AbiToken::uint(amount_out_min),
+        AbiToken::AddressArray(path),
+        AbiToken::Address(to),
+        AbiToken::uint(deadline as u128),
+    ])
+}
+
+pub fn uniswap_v2_get_amounts_out(amount_in: u128, path: Vec<Address>) -> Vec<u8> {
+    encode_call("getAmountsOut(uint256,address[])", &[AbiToken::uint(amount_in), AbiToken::AddressArray(path)])
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct V3ExactInputSingle {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub fee: u32, // Pool fee in hundredths of a bip: 500, 3000, 10000
+    pub recipient: Address,
+    pub deadline: u64,
+    pub amount_in: u128,
+    pub amount_out_minimum: u128,
+}
+
+/// SwapRouter.exactInputSingle without a price limit
+pub fn uniswap_v3_exact_input_single(params: &V3ExactInputSingle) -> Vec<u8> {
+    encode_call(
+        "exactInputSingle((address,address,uint24,address,uint256,uint256,uint256,uint160))",
+        &[AbiToken::Tuple(vec![
+            AbiToken::Address(params.token_in),
+            AbiToken::Address(params.token_out),
+            AbiToken::uint(params.fee as u128),
+            AbiToken::Address(params.recipient),
+            AbiToken::uint(params.deadline as u128),
+            AbiToken::uint(params.amount_in),
+            AbiToken::uint(params.amount_out_minimum),
+            AbiToken::uint(0),
+        ])],
+    )
+}
+
+/// Packed multi-hop path: token (20) | fee (3) | token (20) | ...
+pub fn uniswap_v3_path(tokens: &[Address], fees: &[u32]) -> Result<Vec<u8>> {
+    if tokens.len() < 2 || fees.len() != tokens.len() - 1 {
+        return Err(anyhow!("V3 path needs n tokens and n-1 fees"));
+    }
+    let mut path = tokens[0].to_vec();
+    for (fee, token) in fees.iter().zip(&tokens[1..]) {
+        path.extend_from_slice(&fee.to_be_bytes()[1..]);
+        path.extend_from_slice(token);
+    }
+    Ok(path)
+}
+
+pub fn uniswap_v3_exact_input(path: Vec<u8>, recipient: Address, deadline: u64, amount_in: u128, amount_out_minimum: u128) -> Vec<u8> {
+    encode_call("exactInput((bytes,address,uint256,uint256,uint256))", &[AbiToken::Tuple(vec![
+        AbiToken::Bytes(path),
+        AbiToken::Address(recipient),
+        AbiToken::uint(deadline as u128),
+        AbiToken::uint(amount_in),
+        AbiToken::uint(amount_out_minimum),
+    ])])
+}
+
+// ============================================================================
+// RLP
+// ============================================================================
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut out = rlp_header(0x80, bytes.len());
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn rlp_uint(value: u128) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    rlp_bytes(&bytes[first..])
+}
+
+fn rlp_word(word: &[u8; 32]) -> Vec<u8> {
+    let first = word.iter().position(|b| *b != 0).unwrap_or(word.len());
+    rlp_bytes(&word[first..])
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload: Vec<u8> = items.concat();
+    let mut out = rlp_header(0xc0, payload.len());
+    out.extend(payload);
+    out
+}
+
+fn rlp_header(offset: u8, len: usize) -> Vec<u8> {
+    if len <= 55 {
+        return vec![offset + len as u8];
+    }
+    let len_bytes = (len as u64).to_be_bytes();
+    let first = len_bytes.iter().position(|b| *b != 0).unwrap_or(7);
+    let mut out = vec![offset + 55 + (8 - first) as u8];
+    out.extend_from_slice(&len_bytes[first..]);
+    out
+}
+
+// ============================================================================
+// TRANSACTIONS & SIGNING
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvmFees {
+    Eip1559 { max_fee_per_gas: u128, max_priority_fee_per_gas: u128 },
+    Legacy { gas_price: u128 },
+}
+
+impl EvmFees {
+    /// Upper bound on the per-gas price actually paid
+    pub fn max_gas_price(&self) -> u128 {
+        match self {
+            EvmFees::Eip1559 { max_fee_per_gas, .. } => *max_fee_per_gas,
+            EvmFees::Legacy { gas_price } => *gas_price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvmTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub to: Option<Address>, // None deploys a contract
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub gas_limit: u64,
+    pub fees: EvmFees,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedEvmTransaction {
+    pub raw: Vec<u8>,
+    pub hash: [u8; 32],
+    pub from: Address,
+    pub nonce: u64,
+}
+
+impl SignedEvmTransaction {
+    pub fn raw_hex(&self) -> String {
+        format!("0x{}", hex::encode(&self.raw))
+    }
+
+    pub fn hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.hash))
+    }
+}
+
+impl EvmTransaction {
+    fn to_field(&self) -> Vec<u8> {
+        rlp_bytes(self.to.as_ref().map(|a| a.as_slice()).unwrap_or(&[]))
+    }
+
+    fn payload(&self, signature: Option<(u8, [u8; 32], [u8; 32])>) -> Vec<u8> {
+        match self.fees {
+            EvmFees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => {
+                let mut fields = vec![
+                    rlp_uint(self.chain_id as u128),
+                    rlp_uint(self.nonce as u128),
+                    rlp_uint(max_priority_fee_per_gas),
+                    rlp_uint(max_fee_per_gas),
+                    rlp_uint(self.gas_limit as u128),
+                    self.to_field(),
+                    rlp_uint(self.value),
+                    rlp_bytes(&self.data),
+                    rlp_list(&[]), // access list
+                ];
+                if let Some((y_parity, r, s)) = signature {
+                    fields.extend([rlp_uint(y_parity as u128), rlp_word(&r), rlp_word(&s)]);
+                }
+                let mut out = vec![0x02];
+                out.extend(rlp_list(&fields));
+                out
+            }
+            EvmFees::Legacy { gas_price } => {
+                let mut fields = vec![
+                    rlp_uint(self.nonce as u128),
+                    rlp_uint(gas_price),
+                    rlp_uint(self.gas_limit as u128),
+                    self.to_field(),
+                    rlp_uint(self.value),
+                    rlp_bytes(&self.data),
+                ];
+                match signature {
+                    // EIP-155 replay protection
+                    Some((recovery_id, r, s)) => fields.extend([
+                        rlp_uint(recovery_id as u128 + self.chain_id as u128 * 2 + 35),
+                        rlp_word(&r),
+                        rlp_word(&s),
+                    ]),
+                    None => fields.extend([rlp_uint(self.chain_id as u128), rlp_uint(0), rlp_uint(0)]),
+                }
+                rlp_list(&fields)
+            }
+        }
+    }
+
+    pub fn signing_hash(&self) -> [u8; 32] {
+        keccak256(&self.payload(None))
+    }
+
+    pub fn sign(&self, signer: &EvmSigner) -> Result<SignedEvmTransaction> {
+        let signature = signer.sign_hash(&self.signing_hash())?;
+        let raw = self.payload(Some(signature));
+        Ok(SignedEvmTransaction {
+            hash: keccak256(&raw),
+            raw,
+            from: signer.address(),
+            nonce: self.nonce,
+        })
+    }
+}
+
+/// secp256k1 key held in memory
+pub struct EvmSigner {
+    key: SigningKey,
+    address: Address,
+}
+
+impl EvmSigner {
+    pub fn from_hex(secret: &str) -> Result<Self> {
+        let bytes = zeroize::Zeroizing::new(
+            hex::decode(secret.trim().trim_start_matches("0x")).context("Private key is not valid hex")?,
+        );
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(secret: &[u8]) -> Result<Self> {
+        let key = SigningKey::from_slice(secret).map_err(|e| anyhow!("Invalid private key: {}", e))?;
+        let address = address_from_public_key(key.verifying_key());
+        Ok(Self { key, address })
+    }
+
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("EVM_PRIVATE_KEY") {
+            Ok(secret) if !secret.is_empty() => Self::from_hex(&secret),
+            _ => Err(anyhow!("EVM_PRIVATE_KEY is not set")),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Low-s signature over a 32-byte digest as (recovery id, r, s)
+    pub fn sign_hash(&self, hash: &[u8; 32]) -> Result<(u8, [u8; 32], [u8; 32])> {
+        let (signature, recovery_id) = self.key.sign_prehash_recoverable(hash)
+            .map_err(|e| anyhow!("Signing failed: {}", e))?;
+        let bytes = signature.to_bytes();
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        s.copy_from_slice(&bytes[32..]);
+        Ok((recovery_id.to_byte(), r, s))
+    }
+
+    /// EIP-191 signature as r | s | v with v in {27, 28}
+    pub fn sign_message(&self, message: &[u8]) -> Result<[u8; 65]> {
+        let (recovery_id, r, s) = self.sign_hash(&hash_personal_message(message))?;
+        let mut signature = [0u8; 65];
+        signature[..32].copy_from_slice(&r);
+        signature[32..64].copy_from_slice(&s);
+        signature[64] = 27 + recovery_id;
+        Ok(signature)
+    }
+}
+
+// ============================================================================
+// JSON-RPC CLIENT
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvmReceipt {
+    pub transaction_hash: String,
+    pub block_number: u64,
+    pub success: bool,
+    pub gas_used: u64,
+    pub effective_gas_price: u128,
+}
+
+/// Error object in a JSON-RPC response: the node received the request and refused it
+#[derive(Debug, Clone)]
+pub struct RpcError {
+    pub method: String,
+    pub code: i64,
+    pub message: String,
+}
+
+impl std::fmt::Display for RpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed: {}", self.method, self.message)
+    }
+}
+
+impl std::error::Error for RpcError {}
+
+pub struct EvmRpcClient {
+    client: Client,
+    rpc_url: String,
+}
+
+impl EvmRpcClient {
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+            rpc_url: rpc_url.to_string(),
+        }
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response = self.client.post(&self.rpc_url).json(&body).send().await
+            .with_context(|| format!("{} request failed", method))?;
+        let mut data: serde_json::Value = response.json().await
+            .with_context(|| format!("{} returned invalid JSON", method))?;
+        if let Some(error) = data.get("error") {
+            return Err(RpcError {
+                method: method.to_string(),
+                code: error["code"].as_i64().unwrap_or(0),
+                message: error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string()),
+            }.into());
+        }
+        Ok(data["result"].take())
+    }
+
+    pub async fn chain_id(&self) -> Result<u64> {
+        Ok(parse_quantity(&self.call("eth_chainId", serde_json::json!([])).await?)? as u64)
+    }
+
+    pub async fn block_number(&self) -> Result<u64> {
+        Ok(parse_quantity(&self.call("eth_blockNumber", serde_json::json!([])).await?)? as u64)
+    }
+
+    pub async fn transaction_count(&self, address: &Address, block: &str) -> Result<u64> {
+        let result = self.call("eth_getTransactionCount", serde_json::json!([format_address(address), block])).await?;
+        Ok(parse_quantity(&result)? as u64)
+    }
+
+    pub async fn balance(&self, address: &Address) -> Result<u128> {
+        parse_quantity(&self.call("eth_getBalance", serde_json::json!([format_address(address), "latest"])).await?)
+    }
+
+    pub async fn gas_price(&self) -> Result<u128> {
+        parse_quantity(&self.call("eth_gasPrice", serde_json::json!([])).await?)
+    }
+
+    pub async fn max_priority_fee(&self) -> Result<u128> {
+        parse_quantity(&self.call("eth_maxPriorityFeePerGas", serde_json::json!([])).await?)
+    }
+
+    /// None on chains without EIP-1559
+    pub async fn base_fee(&self) -> Result<Option<u128>> {
+        let block = self.call("eth_getBlockByNumber", serde_json::json!(["latest", false])).await?;
+        match block.get("baseFeePerGas") {
+            Some(value) if !value.is_null() => Ok(Some(parse_quantity(value)?)),
+            _ => Ok(None),
+        }
+    }
+
+    pub async fn estimate_gas(&self, from: &Address, to: Option<&Address>, value: u128, data: &[u8]) -> Result<u64> {
+        let mut tx = serde_json::json!({
+            "from": format_address(from),
+            "value": quantity(value),
+            "data": format!("0x{}", hex::encode(data)),
+        });
+        if let Some(to) = to {
+            tx["to"] = serde_json::json!(format_address(to));
+        }
+        Ok(parse_quantity(&self.call("eth_estimateGas", serde_json::json!([tx])).await?)? as u64)
+    }
+
+    pub async fn eth_call(&self, to: &Address, data: &[u8]) -> Result<Vec<u8>> {
+        let result = self.call("eth_call", serde_json::json!([
+            {"to": format_address(to), "data": format!("0x{}", hex::encode(data))},
+            "latest"
+        ])).await?;
+        let encoded = result.as_str().ok_or_else(|| anyhow!("Invalid eth_call response: {}", result))?;
+        hex::decode(encoded.trim_start_matches("0x")).context("eth_call returned invalid hex")
+    }
+
+    pub async fn send_raw_transaction(&self, transaction: &SignedEvmTransaction) -> Result<String> {
+        let result = self.call("eth_sendRawTransaction", serde_json::json!([transaction.raw_hex()])).await?;
+        result.as_str().map(str::to_string).ok_or_else(|| anyhow!("Invalid eth_sendRawTransaction response: {}", result))
+    }
+
+    pub async fn transaction_receipt(&self, hash: &str) -> Result<Option<EvmReceipt>> {
+        let receipt = self.call("eth_getTransactionReceipt", serde_json::json!([hash])).await?;
+        if receipt.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(EvmReceipt {
+            transaction_hash: hash.to_string(),
+            block_number: parse_quantity(&receipt["blockNumber"]).unwrap_or(0) as u64,
+            success: receipt["status"].as_str() == Some("0x1"),
+            gas_used: parse_quantity(&receipt["gasUsed"]).unwrap_or(0) as u64,
+            effective_gas_price: parse_quantity(&receipt["effectiveGasPrice"]).unwrap_or(0),
+        }))
+    }
+}
+
+fn parse_quantity(value: &serde_json::Value) -> Result<u128> {
+    let text = value.as_str().ok_or_else(|| anyhow!("Expected hex quantity, got {}", value))?;
+    u128::from_str_radix(text.trim_start_matches("0x"), 16)
+        .map_err(|e| anyhow!("Invalid quantity {}: {}", text, e))
+}
+
+pub fn quantity(value: u128) -> String {
+    format!("0x{:x}", value)
+}
+
+// ============================================================================
+// NONCE MANAGEMENT
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct NonceState {
+    next: u64,
+    gaps: BTreeSet<u64>,     // Reserved but never broadcast, reused before advancing
+    in_doubt: BTreeSet<u64>, // Sent without an answer from the node; settled by the next reconcile
+}
+
+impl NonceState {
+    /// Collapse trailing gaps so the next reservation does not skip ahead
+    fn trim(&mut self) {
+        while self.next > 0 && self.gaps.remove(&(self.next - 1)) {
+            self.next -= 1;
+        }
+    }
+}
+
+/// Hands out nonces locally so concurrent sends do not race the node's pending count.
+pub struct NonceManager {
+    rpc: Arc<EvmRpcClient>,
+    accounts: Arc<RwLock<HashMap<Address, NonceState>>>,
+}
+
+impl NonceManager {
+    pub fn new(rpc: Arc<EvmRpcClient>) -> Self {
+        Self {
+            rpc,
+            accounts: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Lowest free nonce; fills gaps left by failed sends first, settling in-doubt nonces before handing out more
+    pub async fn reserve(&self, address: &Address) -> Result<u64> {
+        let in_doubt = self.accounts.read().await.get(address).is_some_and(|s| !s.in_doubt.is_empty());
+        if in_doubt {
+            self.reconcile(address).await?;
+        }
+        let mut accounts = self.accounts.write().await;
+        if !accounts.contains_key(address) {
+            let next = self.rpc.transaction_count(address, "pending").await?;
+            accounts.insert(*address, NonceState { next, ..NonceState::default() });
+        }
+        let state = accounts.get_mut(address).expect("initialized above");
+        if let Some(nonce) = state.gaps.pop_first() {
+            return Ok(nonce);
+        }
+        let nonce = state.next;
+        state.next += 1;
+        Ok(nonce)
+    }
+
+    /// Return a nonce the node definitely refused
+    pub async fn release(&self, address: &Address, nonce: u64) {
+        let mut accounts = self.accounts.write().await;
+        let Some(state) = accounts.get_mut(address) else { return };
+        if nonce >= state.next {
+            return;
+        }
+        state.in_doubt.remove(&nonce);
+        state.gaps.insert(nonce);
+        state.trim();
+    }
+
+    /// Keep a nonce reserved whose send got no answer; it may already be in the mempool
+    pub async fn mark_in_doubt(&self, address: &Address, nonce: u64) {
+        let mut accounts = self.accounts.write().await;
+        if let Some(state) = accounts.get_mut(address).filter(|s| nonce < s.next) {
+            state.in_doubt.insert(nonce);
+        }
+    }
+
+    /// Settle in-doubt nonces against the node's pending count: those below it were broadcast,
+    /// the rest never arrived and become gaps to hand out again
+    pub async fn reconcile(&self, address: &Address) -> Result<u64> {
+        let pending = self.rpc.transaction_count(address, "pending").await?;
+        let mut accounts = self.accounts.write().await;
+        let state = accounts.entry(*address).or_insert_with(|| NonceState { next: pending, ..NonceState::default() });
+        for nonce in std::mem::take(&mut state.in_doubt) {
+            if nonce >= pending {
+                warn!("🔢 Nonce {} for {} never reached the node, reusing it", nonce, format_address(address));
+                state.gaps.insert(nonce);
+            }
+        }
+        state.gaps.retain(|nonce| *nonce >= pending);
+        state.next = state.next.max(pending);
+        state.trim();
+        Ok(pending)
+    }
+
+    /// Adopt the node's pending count, e.g. after "nonce too low" or transactions dropped from the mempool
+    pub async fn resync(&self, address: &Address) -> Result<u64> {
+        let next = self.rpc.transaction_count(address, "pending").await?;
+        let mut accounts = self.accounts.write().await;
+        let previous = accounts.insert(*address, NonceState { next, ..NonceState::default() });
+        if let Some(previous) = previous {
+            if previous.next != next {
+                warn!("🔢 Nonce for {} resynced {} -> {}", format_address(address), previous.next, next);
+            }
+        }
+        Ok(next)
+    }
+
+    pub async fn peek(&self, address: &Address) -> Option<u64> {
+        self.accounts.read().await.get(address).map(|s| s.next)
+    }
+}
+
+// ============================================================================
+// SENDER
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvmTxConfig {
+    pub use_eip1559: bool,            // Falls back to legacy when the chain reports no base fee
+    pub base_fee_multiplier: f64,     // max_fee = base_fee * multiplier + tip
+    pub min_priority_fee_gwei: f64,
+    pub gas_limit_multiplier: f64,    // Headroom over eth_estimateGas
+    pub receipt_timeout_secs: u64,
+    pub max_nonce_retries: u32,
+}
+
+impl Default for EvmTxConfig {
+    fn default() -> Self {
+        Self {
+            use_eip1559: true,
+            base_fee_multiplier: 2.0,
+            min_priority_fee_gwei: 0.1,
+            gas_limit_multiplier: 1.2,
+            receipt_timeout_secs: 120,
+            max_nonce_retries: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvmCallRequest {
+    pub to: Option<Address>,
+    pub value: u128,
+    pub data: Vec<u8>,
+    pub gas_limit: Option<u64>,
+    pub fees: Option<EvmFees>,
+    pub nonce: Option<u64>, // Explicit nonce bypasses the manager, e.g. to replace a stuck transaction
+}
+
+pub struct EvmTxSender {
+    chain_id: u64,
+    rpc: Arc<EvmRpcClient>,
+    signer: Arc<EvmSigner>,
+    nonces: Arc<NonceManager>,
+    config: Arc<RwLock<EvmTxConfig>>,
+}
+
+impl EvmTxSender {
+    pub fn new(chain_id: u64, rpc: Arc<EvmRpcClient>, signer: Arc<EvmSigner>) -> Self {
+        Self {
+            chain_id,
+            nonces: Arc::new(NonceManager::new(rpc.clone())),
+            rpc,
+            signer,
+            config: Arc::new(RwLock::new(EvmTxConfig::default())),
+        }
+    }
+
+    pub async fn get_config(&self) -> EvmTxConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: EvmTxConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    pub fn nonces(&self) -> Arc<NonceManager> {
+        self.nonces.clone()
+    }
+
+    pub async fn suggest_fees(&self) -> Result<EvmFees> {
+        let config = self.config.read().await.clone();
+        if config.use_eip1559 {
+            if let Some(base_fee) = self.rpc.base_fee().await? {
+                let floor = (config.min_priority_fee_gwei * WEI_PER_GWEI as f64) as u128;
+                let tip = self.rpc.max_priority_fee().await.unwrap_or(floor).max(floor);
+                return Ok(EvmFees::Eip1559 {
+                    max_fee_per_gas: (base_fee as f64 * config.base_fee_multiplier) as u128 + tip,
+                    max_priority_fee_per_gas: tip,
+                });
+            }
+        }
+        Ok(EvmFees::Legacy { gas_price: self.rpc.gas_price().await? })
+    }
+
+    /// Sign and broadcast, resyncing the nonce and retrying when the node rejects it.
+    /// A managed nonce is only released when the node refused the transaction; unanswered sends are reconciled.
+    pub async fn send(&self, request: EvmCallRequest) -> Result<SignedEvmTransaction> {
+        let config = self.config.read().await.clone();
+        let from = self.signer.address();
+        let fees = match request.fees {
+            Some(fees) => fees,
+            None => self.suggest_fees().await?,
+        };
+        let gas_limit = match request.gas_limit {
+            Some(limit) => limit,
+            None => {
+                let estimate = self.rpc.estimate_gas(&from, request.to.as_ref(), request.value, &request.data).await?;
+                (estimate as f64 * config.gas_limit_multiplier.max(1.0)).ceil() as u64
+            }
+        };
+
+        let mut attempt = 0;
+        loop {
+            let managed = request.nonce.is_none();
+            let nonce = match request.nonce {
+                Some(nonce) => nonce,
+                None => self.nonces.reserve(&from).await?,
+            };
+            let transaction = EvmTransaction {
+                chain_id: self.chain_id,
+                nonce,
+                to: request.to,
+                value: request.value,
+                data: request.data.clone(),
+                gas_limit,
+                fees,
+            };
+            let signed = transaction.sign(&self.signer)?;
+
+            let error = match self.rpc.send_raw_transaction(&signed).await {
+                Ok(hash) => {
+                    info!("📨 Sent EVM transaction {} (chain {}, nonce {})", hash, self.chain_id, nonce);
+                    return Ok(signed);
+                }
+                Err(e) => e,
+            };
+            match classify_send_error(&error) {
+                SendFailure::AlreadyKnown => {
+                    info!("📨 EVM transaction {} already in the mempool (chain {}, nonce {})", signed.hash_hex(), self.chain_id, nonce);
+                    return Ok(signed);
+                }
+                SendFailure::NonceConflict if managed && attempt < config.max_nonce_retries => {
+                    attempt += 1;
+                    warn!("⚠️ Nonce {} rejected ({}), resyncing", nonce, error);
+                    self.nonces.resync(&from).await?;
+                }
+                SendFailure::NonceConflict | SendFailure::Rejected => {
+                    if managed {
+                        self.nonces.release(&from, nonce).await;
+                    }
+                    return Err(error);
+                }
+                SendFailure::Unknown if managed => {
+                    self.nonces.mark_in_doubt(&from, nonce).await;
+                    match self.nonces.reconcile(&from).await {
+                        Ok(pending) if pending > nonce => {
+                            warn!("⚠️ No answer sending nonce {} ({}), but the node has it pending", nonce, error);
+                            return Ok(signed);
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!("⚠️ Could not reconcile nonce {} after failed send: {}", nonce, e),
+                    }
+                    return Err(error);
+                }
+                SendFailure::Unknown => return Err(error),
+            }
+        }
+    }
+
+    pub async fn wait_for_receipt(&self, hash: &str) -> Result<EvmReceipt> {
+        let timeout = self.config.read().await.receipt_timeout_secs;
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout);
+        while std::time::Instant::now() < deadline {
+            if let Some(receipt) = self.rpc.transaction_receipt(hash).await? {
+                debug!("🧾 {} mined in block {} (success: {})", hash, receipt.block_number, receipt.success);
+                return Ok(receipt);
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+        }
+        Err(anyhow!("Timed out waiting for receipt of {}", hash))
+    }
+}
+
+impl Clone for EvmTxSender {
+    fn clone(&self) -> Self {
+        Self {
+            chain_id: self.chain_id,
+            rpc: self.rpc.clone(),
+            signer: self.signer.clone(),
+            nonces: self.nonces.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// What a failed eth_sendRawTransaction says about the transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SendFailure {
+    AlreadyKnown,  // The node already holds this exact transaction
+    NonceConflict, // Refused for its nonce; a resync can fix it
+    Rejected,      // Refused for any other reason; nothing was broadcast
+    Unknown,       // No answer from the node; it may or may not have the transaction
+}
+
+fn classify_send_error(error: &anyhow::Error) -> SendFailure {
+    let Some(rpc_error) = error.downcast_ref::<RpcError>() else {
+        return SendFailure::Unknown;
+    };
+    let message = rpc_error.message.to_lowercase();
+    if message.contains("already known") || message.contains("known transaction") || message.contains("already imported") {
+        SendFailure::AlreadyKnown
+    } else if message.contains("nonce too low") || message.contains("nonce too high") {
+        SendFailure::NonceConflict
+    } else {
+        SendFailure::Rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+    use k256::ecdsa::{RecoveryId, Signature};
+    use warp::Filter;
+
+    const EIP155_KEY: &str = "4646464646464646464646464646464646464646464646464646464646464646";
+
+    fn unhex(value: &str) -> Vec<u8> {
+        hex::decode(value.trim_start_matches("0x")).unwrap()
+    }
+
+    #[test]
+    fn hashing_and_address_vectors() {
+        assert_eq!(hex::encode(keccak256(b"")), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(hex::encode(selector("transfer(address,uint256)")), "a9059cbb");
+        let address = parse_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+        assert_eq!(format_address(&address), "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert!(parse_address("0x1234").is_err());
+    }
+
+    #[test]
+    fn rlp_matches_the_spec_examples() {
+        assert_eq!(rlp_bytes(b"dog"), unhex("83646f67"));
+        assert_eq!(rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]), unhex("c88363617483646f67"));
+        assert_eq!(rlp_bytes(b""), unhex("80"));
+        assert_eq!(rlp_list(&[]), unhex("c0"));
+        assert_eq!(rlp_uint(0), unhex("80"));
+        assert_eq!(rlp_uint(15), unhex("0f"));
+        assert_eq!(rlp_uint(1024), unhex("820400"));
+        assert_eq!(rlp_bytes(&[0x80]), unhex("8180"));
+
+        let lorem = b"Lorem ipsum dolor sit amet, consectetur adipisicing elit";
+        let mut expected = unhex("b838");
+        expected.extend_from_slice(lorem);
+        assert_eq!(rlp_bytes(lorem), expected);
+
+        let mut word = [0u8; 32];
+        word[31] = 0x7f;
+        assert_eq!(rlp_word(&word), unhex("7f"));
+    }
+
+    #[test]
+    fn legacy_signing_matches_the_eip155_example() {
+        let signer = EvmSigner::from_hex(EIP155_KEY).unwrap();
+        assert_eq!(format_address(&signer.address()), "0x9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+
+        let transaction = EvmTransaction {
+            chain_id: 1,
+            nonce: 9,
+            to: Some([0x35; 20]),
+            value: 1_000_000_000_000_000_000,
+            data: Vec::new(),
+            gas_limit: 21_000,
+            fees: EvmFees::Legacy { gas_price: 20 * WEI_PER_GWEI },
+        };
+        assert_eq!(
+            transaction.payload(None),
+            unhex("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080"),
+        );
+        assert_eq!(
+            hex::encode(transaction.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53",
+        );
+
+        let signed = transaction.sign(&signer).unwrap();
+        assert_eq!(
+            signed.raw_hex(),
+            "0xf86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a0\
+             28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a0\
+             67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83",
+        );
+        assert_eq!(signed.hash, keccak256(&signed.raw));
+        assert_eq!((signed.from, signed.nonce), (signer.address(), 9));
+    }
+
+    #[test]
+    fn eip1559_signing_is_typed_and_recovers_the_sender() {
+        let signer = EvmSigner::from_hex(EIP155_KEY).unwrap();
+        let transaction = EvmTransaction {
+            chain_id: 1,
+            nonce: 9,
+            to: Some([0x35; 20]),
+            value: 1_000_000_000_000_000_000,
+            data: Vec::new(),
+            gas_limit: 21_000,
+            fees: EvmFees::Eip1559 { max_fee_per_gas: 30 * WEI_PER_GWEI, max_priority_fee_per_gas: 2 * WEI_PER_GWEI },
+        };
+
+        // 0x02 || rlp([chainId, nonce, tip, maxFee, gas, to, value, data, accessList])
+        let unsigned = unhex(
+            "02f0010984773594008506fc23ac00825208943535353535353535353535353535353535353535880de0b6b3a764000080c0",
+        );
+        assert_eq!(transaction.payload(None), unsigned);
+        assert_eq!(transaction.signing_hash(), keccak256(&unsigned));
+
+        let signed = transaction.sign(&signer).unwrap();
+        assert_eq!(signed.raw[0], 0x02);
+        // Same fields, then y_parity, r and s in a longer list
+        assert_eq!(signed.raw[1], 0xf8);
+        assert_eq!(signed.raw[2] as usize, signed.raw.len() - 3);
+        assert_eq!(&signed.raw[3..3 + 48], &unsigned[2..]);
+
+        let y_parity = signed.raw[51];
+        assert!(y_parity == 0x80 || y_parity == 0x01);
+        let r = &signed.raw[53..85];
+        let s = &signed.raw[86..118];
+        assert_eq!((signed.raw[52], signed.raw[85]), (0xa0, 0xa0));
+        let signature = Signature::from_scalars(<[u8; 32]>::try_from(r).unwrap(), <[u8; 32]>::try_from(s).unwrap()).unwrap();
+        let recovery_id = RecoveryId::from_byte(if y_parity == 0x01 { 1 } else { 0 }).unwrap();
+        let recovered = VerifyingKey::recover_from_prehash(&transaction.signing_hash(), &signature, recovery_id).unwrap();
+        assert_eq!(address_from_public_key(&recovered), signer.address());
+    }
+
+    // ------------------------------------------------------------------------
+    // Nonce handling against a scripted JSON-RPC node
+    // ------------------------------------------------------------------------
+
+    type MockReply = (u16, String);
+
+    struct MockNode {
+        url: String,
+        calls: Arc<StdMutex<Vec<String>>>,
+    }
+
+    impl MockNode {
+        /// Replies per method in order, the last one repeating
+        async fn start(routes: Vec<(&str, Vec<MockReply>)>) -> Self {
+            let replies: HashMap<String, Vec<MockReply>> = routes.into_iter()
+                .map(|(method, replies)| (method.to_string(), replies))
+                .collect();
+            let replies = Arc::new(StdMutex::new(replies));
+            let calls = Arc::new(StdMutex::new(Vec::new()));
+
+            let recorded = calls.clone();
+            let route = warp::post().and(warp::body::json()).map(move |body: serde_json::Value| {
+                let method = body["method"].as_str().unwrap_or_default().to_string();
+                recorded.lock().unwrap().push(method.clone());
+                let mut replies = replies.lock().unwrap();
+                let (status, reply) = match replies.get_mut(&method) {
+                    Some(queue) if queue.len() > 1 => queue.remove(0),
+                    Some(queue) if !queue.is_empty() => queue[0].clone(),
+                    _ => rpc_error(&format!("method {} not mocked", method)),
+                };
+                warp::reply::with_status(reply, warp::http::StatusCode::from_u16(status).unwrap())
+            });
+
+            let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+            tokio::spawn(server);
+            Self { url: format!("http://{}", addr), calls }
+        }
+
+        fn count(&self, method: &str) -> usize {
+            self.calls.lock().unwrap().iter().filter(|m| *m == method).count()
+        }
+    }
+
+    fn result(value: serde_json::Value) -> MockReply {
+        (200, serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": value }).to_string())
+    }
+
+    fn rpc_error(message: &str) -> MockReply {
+        (200, serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32000, "message": message } }).to_string())
+    }
+
+    // A proxy that gave up waiting: the node may or may not have seen the request
+    fn bad_gateway() -> MockReply {
+        (502, "<html>502 Bad Gateway</html>".to_string())
+    }
+
+    fn sender(node: &MockNode) -> EvmTxSender {
+        EvmTxSender::new(1, Arc::new(EvmRpcClient::new(&node.url)), Arc::new(EvmSigner::from_hex(EIP155_KEY).unwrap()))
+    }
+
+    fn transfer() -> EvmCallRequest {
+        EvmCallRequest {
+            to: Some([0x35; 20]),
+            value: 1,
+            gas_limit: Some(21_000),
+            fees: Some(EvmFees::Legacy { gas_price: WEI_PER_GWEI }),
+            ..EvmCallRequest::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn already_known_returns_the_signed_transaction_without_resubmitting() {
+        let node = MockNode::start(vec![
+            ("eth_getTransactionCount", vec![result(serde_json::json!("0x5"))]),
+            ("eth_sendRawTransaction", vec![rpc_error("already known")]),
+        ]).await;
+        let sender = sender(&node);
+
+        let signed = sender.send(transfer()).await.unwrap();
+        assert_eq!(signed.nonce, 5);
+        assert_eq!(node.count("eth_sendRawTransaction"), 1);
+        assert_eq!(node.count("eth_getTransactionCount"), 1);
+        assert_eq!(sender.nonces().peek(&sender.address()).await, Some(6));
+    }
+
+    #[tokio::test]
+    async fn nonce_too_low_resyncs_and_resubmits() {
+        let node = MockNode::start(vec![
+            ("eth_getTransactionCount", vec![result(serde_json::json!("0x5")), result(serde_json::json!("0x7"))]),
+            ("eth_sendRawTransaction", vec![rpc_error("nonce too low"), result(serde_json::json!("0xabc"))]),
+        ]).await;
+        let sender = sender(&node);
+
+        let signed = sender.send(transfer()).await.unwrap();
+        assert_eq!(signed.nonce, 7);
+        assert_eq!(node.count("eth_sendRawTransaction"), 2);
+        assert_eq!(sender.nonces().peek(&sender.address()).await, Some(8));
+    }
+
+    #[tokio::test]
+    async fn definite_rejection_releases_the_nonce() {
+        let node = MockNode::start(vec![
+            ("eth_getTransactionCount", vec![result(serde_json::json!("0x5"))]),
+            ("eth_sendRawTransaction", vec![
+                rpc_error("insufficient funds for gas * price + value"),
+                result(serde_json::json!("0xabc")),
+            ]),
+        ]).await;
+        let sender = sender(&node);
+
+        let error = sender.send(transfer()).await.unwrap_err();
+        assert!(error.to_string().contains("insufficient funds"));
+        assert_eq!(sender.nonces().peek(&sender.address()).await, Some(5));
+        assert_eq!(sender.send(transfer()).await.unwrap().nonce, 5);
+    }
+
+    #[tokio::test]
+    async fn unanswered_send_the_node_picked_up_keeps_its_nonce() {
+        let node = MockNode::start(vec![
+            ("eth_getTransactionCount", vec![result(serde_json::json!("0x5")), result(serde_json::json!("0x6"))]),
+            ("eth_sendRawTransaction", vec![bad_gateway(), result(serde_json::json!("0xabc"))]),
+        ]).await;
+        let sender = sender(&node);
+
+        let signed = sender.send(transfer()).await.unwrap();
+        assert_eq!(signed.nonce, 5);
+        assert_eq!(node.count("eth_sendRawTransaction"), 1);
+        assert_eq!(sender.send(transfer()).await.unwrap().nonce, 6);
+    }
+
+    #[tokio::test]
+    async fn nonce_gap_from_an_unanswered_send_is_refilled() {
+        // The send and the follow-up pending count both go unanswered, so nonce 5 stays in doubt
+        let node = MockNode::start(vec![
+            ("eth_getTransactionCount", vec![
+                result(serde_json::json!("0x5")),
+                bad_gateway(),
+                result(serde_json::json!("0x5")),
+            ]),
+            ("eth_sendRawTransaction", vec![bad_gateway(), result(serde_json::json!("0xabc"))]),
+        ]).await;
+        let sender = sender(&node);
+        let address = sender.address();
+
+        assert!(sender.send(transfer()).await.is_err());
+        assert_eq!(sender.nonces().peek(&address).await, Some(6));
+
+        // The next reservation reconciles first: the node never saw 5, so it is reused instead of leaving a gap
+        let signed = sender.send(transfer()).await.unwrap();
+        assert_eq!(signed.nonce, 5);
+        assert_eq!(node.count("eth_getTransactionCount"), 3);
+        assert_eq!(sender.send(transfer()).await.unwrap().nonce, 6);
+    }
+
+    /// Needs anvil on its default port with the default mnemonic: `cargo test local_dev_node -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn local_dev_node_transfer_is_mined() {
+        let rpc = Arc::new(EvmRpcClient::new("http://127.0.0.1:8545"));
+        let chain_id = rpc.chain_id().await.unwrap();
+        let signer = EvmSigner::from_hex("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap();
+        let sender = EvmTxSender::new(chain_id, rpc.clone(), Arc::new(signer));
+        let recipient = [0x42; 20];
+        let before = rpc.balance(&recipient).await.unwrap();
+
+        let signed = sender.send(EvmCallRequest { to: Some(recipient), value: 1_000_000, ..EvmCallRequest::default() }).await.unwrap();
+        let receipt = sender.wait_for_receipt(&signed.hash_hex()).await.unwrap();
+        assert!(receipt.success);
+        assert_eq!(rpc.balance(&recipient).await.unwrap(), before + 1_000_000);
+    }
+}
@@ -0,0 +1,901 @@
+// Exchange Simulator - In-process venues implementing DexClient and CexClient
+// Price-time-priority matching, constant-product AMM pools, latency, partial fills and scenario scripts
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::{RwLock, Mutex};
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use log::{info, warn, debug};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::{CexClient, DexClient, ExchangeType, Order, OrderBook, OrderBookEntry, PriceInfo, TradeStep};
+use crate::fee_model::{FeeModel, FeeTier, VenueFeeSchedule, VenueKind};
+
+// ============================================================================
+// SCENARIO FILES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub seed: u64,
+    pub venues: Vec<SimVenueConfig>,
+    #[serde(default)]
+    pub events: Vec<ScenarioEvent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SimVenueKind {
+    Cex,
+    Dex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimVenueConfig {
+    pub name: String,
+    pub kind: SimVenueKind,
+    #[serde(default)]
+    pub chain: Option<String>,
+    #[serde(default)]
+    pub latency_ms: u64,
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+    /// Probability an order or swap is rejected outright
+    #[serde(default)]
+    pub reject_probability: f64,
+    /// Probability a marketable order only fills part of its size
+    #[serde(default)]
+    pub partial_fill_probability: f64,
+    #[serde(default)]
+    pub maker_fee: Decimal,
+    #[serde(default)]
+    pub taker_fee: Decimal,
+    /// Swaps executing worse than the requested price by more than this are reverted
+    #[serde(default = "default_max_slippage_bps")]
+    pub max_slippage_bps: u32,
+    #[serde(default)]
+    pub balances: HashMap<String, Decimal>,
+    #[serde(default)]
+    pub books: HashMap<String, SimBookConfig>,
+    #[serde(default)]
+    pub pools: Vec<SimPoolConfig>,
+}
+
+fn default_max_slippage_bps() -> u32 {
+    100
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimBookConfig {
+    pub bids: Vec<(Decimal, Decimal)>, // (price, quantity)
+    pub asks: Vec<(Decimal, Decimal)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimPoolConfig {
+    pub pair: String,
+    pub reserve_base: Decimal,
+    pub reserve_quote: Decimal,
+    pub fee: Decimal,
+}
+
+/// Scripted change applied `at_ms` after the scenario starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioEvent {
+    pub at_ms: u64,
+    pub venue: String,
+    pub action: ScenarioAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScenarioAction {
+    /// Rest a liquidity order on the book (from the venue's market makers)
+    PlaceLimit { pair: String, side: String, price: Decimal, quantity: Decimal },
+    ClearBook { pair: String },
+    /// Move an AMM pool to a new price keeping its liquidity
+    SetPoolPrice { pair: String, price: Decimal },
+    SetLatency { latency_ms: u64, latency_jitter_ms: u64 },
+    SetRejectProbability { probability: f64 },
+    Halt,
+    Resume,
+}
+
+// ============================================================================
+// MATCHING ENGINE
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SimSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RestingOrder {
+    id: String,
+    owner: Option<String>, // None for scenario liquidity
+    quantity: Decimal,
+    timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimFill {
+    pub order_id: String,
+    pub maker_order_id: String,
+    pub pair: String,
+    pub side: SimSide,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub fee: Decimal,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SimOrderStatus {
+    Filled,
+    PartiallyFilled,
+    Resting,
+    Rejected,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimOrderResult {
+    pub order_id: String,
+    pub status: SimOrderStatus,
+    pub filled_quantity: Decimal,
+    pub average_price: Option<Decimal>,
+    pub fills: Vec<SimFill>,
+    pub reason: Option<String>,
+}
+
+/// Price-time-priority limit order book for one pair.
+#[derive(Debug, Clone, Default)]
+pub struct MatchingEngine {
+    bids: BTreeMap<Decimal, VecDeque<RestingOrder>>,
+    asks: BTreeMap<Decimal, VecDeque<RestingOrder>>,
+    sequence: u64,
+}
+
+impl MatchingEngine {
+    pub fn from_config(config: &SimBookConfig) -> Self {
+        let mut engine = Self::default();
+        for (price, quantity) in &config.bids {
+            engine.rest(SimSide::Buy, *price, *quantity, None);
+        }
+        for (price, quantity) in &config.asks {
+            engine.rest(SimSide::Sell, *price, *quantity, None);
+        }
+        engine
+    }
+
+    fn next_id(&mut self) -> String {
+        self.sequence += 1;
+        format!("sim_{}", self.sequence)
+    }
+
+    fn rest(&mut self, side: SimSide, price: Decimal, quantity: Decimal, owner: Option<String>) -> String {
+        let id = self.next_id();
+        let book = match side {
+            SimSide::Buy => &mut self.bids,
+            SimSide::Sell => &mut self.asks,
+        };
+        book.entry(price).or_default().push_back(RestingOrder {
+            id: id.clone(),
+            owner,
+            quantity,
+            timestamp: self.sequence,
+        });
+        id
+    }
+
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.keys().next_back().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.keys().next().copied()
+    }
+
+    /// Match an incoming order; `limit` None sweeps as a market order. Unfilled limit
+    /// quantity rests on the book, unfilled market quantity is cancelled.
+    #[allow(clippy::too_many_arguments)]
+    pub fn submit(
+        &mut self,
+        pair: &str,
+        side: SimSide,
+        quantity: Decimal,
+        limit: Option<Decimal>,
+        owner: &str,
+        taker_fee: Decimal,
+        max_fill: Decimal,
+    ) -> SimOrderResult {
+        let order_id = self.next_id();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut remaining = quantity.min(max_fill);
+        let mut fills = Vec::new();
+
+        loop {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let best = match side {
+                SimSide::Buy => self.best_ask(),
+                SimSide::Sell => self.best_bid(),
+            };
+            let Some(level_price) = best else { break };
+            let crosses = match (side, limit) {
+                (_, None) => true,
+                (SimSide::Buy, Some(limit)) => level_price <= limit,
+                (SimSide::Sell, Some(limit)) => level_price >= limit,
+            };
+            if !crosses {
+                break;
+            }
+
+            let book = match side {
+                SimSide::Buy => &mut self.asks,
+                SimSide::Sell => &mut self.bids,
+            };
+            let level = book.get_mut(&level_price).expect("best level exists");
+            while remaining > Decimal::ZERO {
+                let Some(maker) = level.front_mut() else { break };
+                let take = remaining.min(maker.quantity);
+                maker.quantity -= take;
+                remaining -= take;
+                fills.push(SimFill {
+                    order_id: order_id.clone(),
+                    maker_order_id: maker.id.clone(),
+                    pair: pair.to_string(),
+                    side,
+                    price: level_price,
+                    quantity: take,
+                    fee: take * level_price * taker_fee,
+                    timestamp: now,
+                });
+                if maker.quantity <= Decimal::ZERO {
+                    level.pop_front();
+                }
+            }
+            if level.is_empty() {
+                book.remove(&level_price);
+            }
+        }
+
+        let filled: Decimal = fills.iter().map(|f| f.quantity).sum();
+        let notional: Decimal = fills.iter().map(|f| f.quantity * f.price).sum();
+        let average_price = if filled > Decimal::ZERO { Some(notional / filled) } else { None };
+
+        let unfilled = quantity - filled;
+        let status = if unfilled <= Decimal::ZERO {
+            SimOrderStatus::Filled
+        } else if let (Some(limit), true) = (limit, max_fill >= quantity) {
+            self.rest_with_id(side, limit, unfilled, owner, &order_id);
+            if filled > Decimal::ZERO { SimOrderStatus::PartiallyFilled } else { SimOrderStatus::Resting }
+        } else {
+            SimOrderStatus::PartiallyFilled
+        };
+
+        SimOrderResult {
+            order_id,
+            status,
+            filled_quantity: filled,
+            average_price,
+            fills,
+            reason: None,
+        }
+    }
+
+    fn rest_with_id(&mut self, side: SimSide, price: Decimal, quantity: Decimal, owner: &str, id: &str) {
+        let book = match side {
+            SimSide::Buy => &mut self.bids,
+            SimSide::Sell => &mut self.asks,
+        };
+        book.entry(price).or_default().push_back(RestingOrder {
+            id: id.to_string(),
+            owner: Some(owner.to_string()),
+            quantity,
+            timestamp: self.sequence,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+
+    pub fn snapshot(&self, depth: usize) -> OrderBook {
+        let level = |(price, orders): (&Decimal, &VecDeque<RestingOrder>)| OrderBookEntry {
+            price: *price,
+            amount: orders.iter().map(|o| o.quantity).sum(),
+        };
+        OrderBook {
+            bids: self.bids.iter().rev().take(depth).map(level).collect(),
+            asks: self.asks.iter().take(depth).map(level).collect(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+// ============================================================================
+// AMM POOLS
+// ============================================================================
+
+/// Constant-product pool (x * y = k) with the fee taken from the input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmmPool {
+    pub pair: String,
+    pub reserve_base: Decimal,
+    pub reserve_quote: Decimal,
+    pub fee: Decimal,
+}
+
+impl AmmPool {
+    pub fn price(&self) -> Decimal {
+        if self.reserve_base.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.reserve_quote / self.reserve_base
+    }
+
+    /// Output for `amount_in`; `base_in` true swaps base for quote.
+    pub fn quote_out(&self, amount_in: Decimal, base_in: bool) -> Decimal {
+        let effective_in = amount_in * (Decimal::ONE - self.fee);
+        let (reserve_in, reserve_out) = if base_in {
+            (self.reserve_base, self.reserve_quote)
+        } else {
+            (self.reserve_quote, self.reserve_base)
+        };
+        if reserve_in + effective_in <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        reserve_out * effective_in / (reserve_in + effective_in)
+    }
+
+    pub fn swap(&mut self, amount_in: Decimal, base_in: bool) -> Decimal {
+        let out = self.quote_out(amount_in, base_in);
+        if base_in {
+            self.reserve_base += amount_in;
+            self.reserve_quote -= out;
+        } else {
+            self.reserve_quote += amount_in;
+            self.reserve_base -= out;
+        }
+        out
+    }
+
+    /// Re-center the pool on `price` keeping k constant.
+    pub fn set_price(&mut self, price: Decimal) {
+        if price <= Decimal::ZERO {
+            return;
+        }
+        let k = self.reserve_base * self.reserve_quote;
+        let base = (k / price).to_f64().unwrap_or(0.0).sqrt();
+        if let Ok(base) = Decimal::try_from(base) {
+            self.reserve_base = base;
+            self.reserve_quote = base * price;
+        }
+    }
+}
+
+// ============================================================================
+// SIMULATED EXCHANGE
+// ============================================================================
+
+struct SimState {
+    books: HashMap<String, MatchingEngine>,
+    pools: HashMap<String, AmmPool>,
+    balances: HashMap<String, Decimal>,
+    orders: HashMap<String, SimOrderResult>,
+    fills: Vec<SimFill>,
+    latency_ms: u64,
+    latency_jitter_ms: u64,
+    reject_probability: f64,
+    halted: bool,
+}
+
+pub struct SimulatedExchange {
+    config: SimVenueConfig,
+    state: Arc<RwLock<SimState>>,
+    rng: Arc<Mutex<StdRng>>,
+}
+
+impl SimulatedExchange {
+    pub fn new(config: SimVenueConfig, seed: u64) -> Self {
+        let books = config.books.iter()
+            .map(|(pair, book)| (pair.clone(), MatchingEngine::from_config(book)))
+            .collect();
+        let pools = config.pools.iter()
+            .map(|p| (p.pair.clone(), AmmPool {
+                pair: p.pair.clone(),
+                reserve_base: p.reserve_base,
+                reserve_quote: p.reserve_quote,
+                fee: p.fee,
+            }))
+            .collect();
+
+        let state = SimState {
+            books,
+            pools,
+            balances: config.balances.iter().map(|(k, v)| (k.to_uppercase(), *v)).collect(),
+            orders: HashMap::new(),
+            fills: Vec::new(),
+            latency_ms: config.latency_ms,
+            latency_jitter_ms: config.latency_jitter_ms,
+            reject_probability: config.reject_probability,
+            halted: false,
+        };
+
+        Self {
+            config,
+            state: Arc::new(RwLock::new(state)),
+            rng: Arc::new(Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    pub fn kind(&self) -> SimVenueKind {
+        self.config.kind
+    }
+
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// Current mean latency (base plus half the jitter)
+    pub async fn latency_ms(&self) -> u64 {
+        let state = self.state.read().await;
+        state.latency_ms + state.latency_jitter_ms / 2
+    }
+
+    /// Fee schedule matching this venue, for registering with the fee model
+    pub fn fee_schedule(&self) -> VenueFeeSchedule {
+        let is_dex = self.config.kind == SimVenueKind::Dex;
+        VenueFeeSchedule {
+            venue: self.config.name.clone(),
+            kind: if is_dex { VenueKind::Dex } else { VenueKind::Cex },
+            tiers: if is_dex {
+                Vec::new()
+            } else {
+                vec![FeeTier {
+                    min_volume_30d_usd: Decimal::ZERO,
+                    maker_fee: self.config.maker_fee,
+                    taker_fee: self.config.taker_fee,
+                }]
+            },
+            volume_30d_usd: Decimal::ZERO,
+            pool_fee: if is_dex { self.config.pools.first().map(|p| p.fee) } else { None },
+            chain: self.config.chain.clone(),
+            withdrawal_fees: HashMap::new(),
+            // Fills walk the simulated book and pools, so impact is already in the fill price
+            expected_slippage: Decimal::ZERO,
+        }
+    }
+
+    async fn simulate_latency(&self) -> u64 {
+        let (base, jitter) = {
+            let state = self.state.read().await;
+            (state.latency_ms, state.latency_jitter_ms)
+        };
+        let extra = if jitter > 0 { self.rng.lock().await.gen_range(0..=jitter) } else { 0 };
+        let latency = base + extra;
+        if latency > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_millis(latency)).await;
+        }
+        latency
+    }
+
+    async fn roll(&self, probability: f64) -> bool {
+        probability > 0.0 && self.rng.lock().await.gen::<f64>() < probability
+    }
+
+    pub async fn apply_event(&self, action: &ScenarioAction) {
+        let mut state = self.state.write().await;
+        match action {
+            ScenarioAction::PlaceLimit { pair, side, price, quantity } => {
+                let side = if side.eq_ignore_ascii_case("buy") { SimSide::Buy } else { SimSide::Sell };
+                state.books.entry(pair.clone()).or_default().rest(side, *price, *quantity, None);
+            }
+            ScenarioAction::ClearBook { pair } => {
+                if let Some(book) = state.books.get_mut(pair) {
+                    book.clear();
+                }
+            }
+            ScenarioAction::SetPoolPrice { pair, price } => {
+                if let Some(pool) = state.pools.get_mut(pair) {
+                    pool.set_price(*price);
+                }
+            }
+            ScenarioAction::SetLatency { latency_ms, latency_jitter_ms } => {
+                state.latency_ms = *latency_ms;
+                state.latency_jitter_ms = *latency_jitter_ms;
+            }
+            ScenarioAction::SetRejectProbability { probability } => {
+                state.reject_probability = *probability;
+            }
+            ScenarioAction::Halt => state.halted = true,
+            ScenarioAction::Resume => state.halted = false,
+        }
+        debug!("🧪 {} applied scenario event {:?}", self.config.name, action);
+    }
+
+    /// Place an order and return the full result (fills, status, rejection reason).
+    pub async fn submit_order(&self, order: &Order) -> Result<SimOrderResult> {
+        self.simulate_latency().await;
+
+        let reject_probability = self.state.read().await.reject_probability;
+        let rejected = self.roll(reject_probability).await;
+        let partial = self.roll(self.config.partial_fill_probability).await;
+        let partial_ratio = if partial {
+            Decimal::try_from(self.rng.lock().await.gen_range(0.1..0.9)).unwrap_or(Decimal::ONE)
+        } else {
+            Decimal::ONE
+        };
+
+        let mut state = self.state.write().await;
+        if state.halted {
+            return Err(anyhow!("{} is halted", self.config.name));
+        }
+        if rejected {
+            return Ok(rejection(&order.pair, "Venue rejected the order"));
+        }
+
+        let (base, quote) = split_pair(&order.pair)?;
+        let side = if order.side.eq_ignore_ascii_case("buy") { SimSide::Buy } else { SimSide::Sell };
+        let limit = if order.order_type.eq_ignore_ascii_case("limit") { order.price } else { None };
+
+        // Balance check against the worst price we could pay
+        let book = state.books.get(&order.pair)
+            .ok_or_else(|| anyhow!("{} has no book for {}", self.config.name, order.pair))?;
+        let reference = match side {
+            SimSide::Buy => limit.or(book.best_ask()),
+            SimSide::Sell => limit.or(book.best_bid()),
+        }.unwrap_or(Decimal::ZERO);
+        let (need_asset, need_amount) = match side {
+            SimSide::Buy => (quote.clone(), order.amount * reference * (Decimal::ONE + self.config.taker_fee)),
+            SimSide::Sell => (base.clone(), order.amount),
+        };
+        if state.balances.get(&need_asset).copied().unwrap_or(Decimal::ZERO) < need_amount {
+            return Ok(rejection(&order.pair, &format!("Insufficient {} balance", need_asset)));
+        }
+
+        let taker_fee = self.config.taker_fee;
+        let max_fill = order.amount * partial_ratio;
+        let result = state.books.get_mut(&order.pair).expect("book checked above")
+            .submit(&order.pair, side, order.amount, limit, "self", taker_fee, max_fill);
+
+        for fill in &result.fills {
+            let notional = fill.quantity * fill.price;
+            match side {
+                SimSide::Buy => {
+                    *state.balances.entry(quote.clone()).or_default() -= notional + fill.fee;
+                    *state.balances.entry(base.clone()).or_default() += fill.quantity;
+                }
+                SimSide::Sell => {
+                    *state.balances.entry(base.clone()).or_default() -= fill.quantity;
+                    *state.balances.entry(quote.clone()).or_default() += notional - fill.fee;
+                }
+            }
+        }
+        state.fills.extend(result.fills.clone());
+        state.orders.insert(result.order_id.clone(), result.clone());
+
+        Ok(result)
+    }
+
+    /// Swap through the venue's AMM pool; reverts if the price is beyond `max_slippage_bps`.
+    pub async fn submit_swap(&self, trade: &TradeStep) -> Result<SimOrderResult> {
+        self.simulate_latency().await;
+
+        let reject_probability = self.state.read().await.reject_probability;
+        if self.roll(reject_probability).await {
+            return Err(anyhow!("{}: transaction dropped", self.config.name));
+        }
+
+        let mut state = self.state.write().await;
+        if state.halted {
+            return Err(anyhow!("{} is halted", self.config.name));
+        }
+
+        let from = trade.from_token.to_uppercase();
+        let to = trade.to_token.to_uppercase();
+        let (pair, base_in) = if state.pools.contains_key(&format!("{}/{}", from, to)) {
+            (format!("{}/{}", from, to), true)
+        } else if state.pools.contains_key(&format!("{}/{}", to, from)) {
+            (format!("{}/{}", to, from), false)
+        } else {
+            return Err(anyhow!("{} has no pool for {}/{}", self.config.name, from, to));
+        };
+
+        if state.balances.get(&from).copied().unwrap_or(Decimal::ZERO) < trade.amount {
+            return Err(anyhow!("Insufficient {} balance", from));
+        }
+
+        let pool = state.pools.get_mut(&pair).expect("pool checked above");
+        let out = pool.quote_out(trade.amount, base_in);
+        if out <= Decimal::ZERO {
+            return Err(anyhow!("Swap would return nothing"));
+        }
+        // Execution price in quote per base
+        let exec_price = if base_in { out / trade.amount } else { trade.amount / out };
+        if trade.price > Decimal::ZERO {
+            let deviation = ((exec_price - trade.price) / trade.price).abs() * Decimal::from(10_000);
+            if deviation > Decimal::from(self.config.max_slippage_bps) {
+                return Err(anyhow!("Slippage {} bps exceeds {} bps", deviation.round_dp(1), self.config.max_slippage_bps));
+            }
+        }
+        let fee = trade.amount * pool.fee;
+        pool.swap(trade.amount, base_in);
+
+        *state.balances.entry(from).or_default() -= trade.amount;
+        *state.balances.entry(to).or_default() += out;
+
+        let tx_id = format!("simtx_{}_{}", self.config.name.to_lowercase(), state.fills.len() + 1);
+        let fill = SimFill {
+            order_id: tx_id.clone(),
+            maker_order_id: pair.clone(),
+            pair: pair.clone(),
+            side: if base_in { SimSide::Sell } else { SimSide::Buy },
+            price: exec_price,
+            quantity: if base_in { trade.amount } else { out },
+            fee,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        };
+        state.fills.push(fill.clone());
+
+        let result = SimOrderResult {
+            order_id: tx_id,
+            status: SimOrderStatus::Filled,
+            filled_quantity: fill.quantity,
+            average_price: Some(exec_price),
+            fills: vec![fill],
+            reason: None,
+        };
+        state.orders.insert(result.order_id.clone(), result.clone());
+        Ok(result)
+    }
+
+    pub async fn get_order(&self, order_id: &str) -> Option<SimOrderResult> {
+        self.state.read().await.orders.get(order_id).cloned()
+    }
+
+    pub async fn get_fills(&self) -> Vec<SimFill> {
+        self.state.read().await.fills.clone()
+    }
+
+    pub async fn set_balance(&self, token: &str, amount: Decimal) {
+        self.state.write().await.balances.insert(token.to_uppercase(), amount);
+    }
+
+    async fn price_info(&self, pair: &str) -> Result<PriceInfo> {
+        let latency = self.simulate_latency().await;
+        let state = self.state.read().await;
+
+        let (price, bid, ask, liquidity, exchange_type) = match self.config.kind {
+            SimVenueKind::Cex => {
+                let book = state.books.get(pair)
+                    .ok_or_else(|| anyhow!("{} has no book for {}", self.config.name, pair))?;
+                let bid = book.best_bid();
+                let ask = book.best_ask();
+                let mid = match (bid, ask) {
+                    (Some(b), Some(a)) => (b + a) / Decimal::from(2),
+                    (Some(p), None) | (None, Some(p)) => p,
+                    _ => return Err(anyhow!("{} book for {} is empty", self.config.name, pair)),
+                };
+                let snapshot = book.snapshot(usize::MAX);
+                let depth: Decimal = snapshot.bids.iter().chain(snapshot.asks.iter())
+                    .map(|l| l.amount * l.price)
+                    .sum();
+                (mid, bid, ask, depth, ExchangeType::Cex)
+            }
+            SimVenueKind::Dex => {
+                let pool = state.pools.get(pair)
+                    .ok_or_else(|| anyhow!("{} has no pool for {}", self.config.name, pair))?;
+                let price = pool.price();
+                let bid = price * (Decimal::ONE - pool.fee);
+                let ask = price * (Decimal::ONE + pool.fee);
+                (price, Some(bid), Some(ask), pool.reserve_quote * Decimal::from(2), ExchangeType::Dex)
+            }
+        };
+
+        Ok(PriceInfo {
+            exchange: self.config.name.clone(),
+            exchange_type,
+            pair: pair.to_string(),
+            price,
+            bid,
+            ask,
+            volume_24h: None,
+            liquidity: Some(liquidity),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            latency_ms: latency,
+        })
+    }
+
+    pub async fn pairs(&self) -> Vec<String> {
+        let state = self.state.read().await;
+        state.books.keys().chain(state.pools.keys()).cloned().collect()
+    }
+}
+
+impl Clone for SimulatedExchange {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            state: self.state.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl CexClient for SimulatedExchange {
+    async fn get_price(&self, pair: &str) -> Result<PriceInfo> {
+        self.price_info(pair).await
+    }
+
+    async fn get_order_book(&self, pair: &str) -> Result<OrderBook> {
+        self.simulate_latency().await;
+        self.state.read().await.books.get(pair)
+            .map(|book| book.snapshot(50))
+            .ok_or_else(|| anyhow!("{} has no book for {}", self.config.name, pair))
+    }
+
+    async fn place_order(&self, order: &Order) -> Result<String> {
+        let result = self.submit_order(order).await?;
+        match result.status {
+            SimOrderStatus::Rejected => Err(anyhow!(
+                "{} rejected order: {}", self.config.name, result.reason.unwrap_or_default()
+            )),
+            _ => Ok(result.order_id),
+        }
+    }
+
+    async fn get_balance(&self, token: &str) -> Result<Decimal> {
+        Ok(self.state.read().await.balances.get(&token.to_uppercase()).copied().unwrap_or(Decimal::ZERO))
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+#[async_trait]
+impl DexClient for SimulatedExchange {
+    async fn get_price(&self, pair: &str) -> Result<PriceInfo> {
+        self.price_info(pair).await
+    }
+
+    async fn get_liquidity(&self, pool: &str) -> Result<Decimal> {
+        self.state.read().await.pools.get(pool)
+            .map(|p| p.reserve_quote * Decimal::from(2))
+            .ok_or_else(|| anyhow!("{} has no pool {}", self.config.name, pool))
+    }
+
+    async fn execute_swap(&self, trade: &TradeStep) -> Result<String> {
+        Ok(self.submit_swap(trade).await?.order_id)
+    }
+
+    async fn get_pools(&self) -> Result<Vec<String>> {
+        Ok(self.state.read().await.pools.keys().cloned().collect())
+    }
+
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+}
+
+// ============================================================================
+// EXCHANGE SIMULATOR (SCENARIO RUNNER)
+// ============================================================================
+
+pub struct ExchangeSimulator {
+    scenario: Scenario,
+    venues: HashMap<String, SimulatedExchange>,
+}
+
+impl ExchangeSimulator {
+    pub fn new(scenario: Scenario) -> Self {
+        let venues = scenario.venues.iter()
+            .enumerate()
+            .map(|(i, config)| {
+                let seed = scenario.seed.wrapping_add(i as u64);
+                (config.name.clone(), SimulatedExchange::new(config.clone(), seed))
+            })
+            .collect();
+        Self { scenario, venues }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let scenario: Scenario = serde_json::from_str(json)?;
+        Ok(Self::new(scenario))
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read scenario {}: {}", path, e))?;
+        Self::from_json(&json)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.scenario.name
+    }
+
+    pub fn venue(&self, name: &str) -> Option<SimulatedExchange> {
+        self.venues.get(name).cloned()
+    }
+
+    pub fn venues(&self) -> Vec<SimulatedExchange> {
+        self.venues.values().cloned().collect()
+    }
+
+    pub fn dex_clients(&self) -> HashMap<String, Box<dyn DexClient + Send + Sync>> {
+        self.venues.iter()
+            .filter(|(_, v)| v.kind() == SimVenueKind::Dex)
+            .map(|(name, v)| (name.clone(), Box::new(v.clone()) as Box<dyn DexClient + Send + Sync>))
+            .collect()
+    }
+
+    pub fn cex_clients(&self) -> HashMap<String, Box<dyn CexClient + Send + Sync>> {
+        self.venues.iter()
+            .filter(|(_, v)| v.kind() == SimVenueKind::Cex)
+            .map(|(name, v)| (name.clone(), Box::new(v.clone()) as Box<dyn CexClient + Send + Sync>))
+            .collect()
+    }
+
+    /// Make the fee model price simulated venues with their scenario fees.
+    pub async fn register_fee_schedules(&self, fee_model: &FeeModel) {
+        for venue in self.venues.values() {
+            fee_model.set_schedule(venue.fee_schedule()).await;
+        }
+    }
+
+    /// Replay scripted events relative to now.
+    pub async fn run_events(&self) {
+        let mut events = self.scenario.events.clone();
+        events.sort_by_key(|e| e.at_ms);
+        let started = tokio::time::Instant::now();
+
+        info!("🧪 Running scenario '{}' with {} events", self.scenario.name, events.len());
+        for event in events {
+            tokio::time::sleep_until(started + tokio::time::Duration::from_millis(event.at_ms)).await;
+            match self.venues.get(&event.venue) {
+                Some(venue) => venue.apply_event(&event.action).await,
+                None => warn!("⚠️ Scenario event for unknown venue {}", event.venue),
+            }
+        }
+        info!("🧪 Scenario '{}' events complete", self.scenario.name);
+    }
+}
+
+impl Clone for ExchangeSimulator {
+    fn clone(&self) -> Self {
+        Self {
+            scenario: self.scenario.clone(),
+            venues: self.venues.clone(),
+        }
+    }
+}
+
+fn split_pair(pair: &str) -> Result<(String, String)> {
+    pair.split_once('/')
+        .map(|(b, q)| (b.to_uppercase(), q.to_uppercase()))
+        .ok_or_else(|| anyhow!("Invalid pair {}", pair))
+}
+
+fn rejection(pair: &str, reason: &str) -> SimOrderResult {
+    SimOrderResult {
+        order_id: format!("rejected_{}_{}", pair.replace('/', "_"), chrono::Utc::now().timestamp_millis()),
+        status: SimOrderStatus::Rejected,
+        filled_quantity: Decimal::ZERO,
+        average_price: None,
+        fills: Vec::new(),
+        reason: Some(reason.to_string()),
+    }
+}
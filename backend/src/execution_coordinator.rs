@@ -0,0 +1,760 @@
+// Execution Coordinator - Two-leg arbitrage execution with leg-risk management
+// Concurrent or sequential leg dispatch, partial-fill detection, automatic hedge/unwind and leg timelines
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use log::{info, warn, error};
+
+use crate::{CexClient, Order, TradeStep};
+use crate::cex_trading::TradingCexClient;
+use crate::exchange_simulator::{SimOrderStatus, SimVenueKind, SimulatedExchange};
+use crate::fee_model::{FeeModel, Liquidity};
+use crate::trade_execution::{ExecutionStatus, ExecutionStep, LegEvent, LegEventKind, LegRole};
+
+// ============================================================================
+// LEG VENUES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegRequest {
+    pub role: LegRole,
+    pub venue: String,
+    pub pair: String,
+    pub side: String, // "buy" | "sell"
+    pub amount: Decimal, // Base units
+    pub expected_price: Decimal,
+    pub limit_price: Option<Decimal>, // Worst acceptable price, None for market
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegFill {
+    pub order_id: Option<String>,
+    pub filled_amount: Decimal,
+    pub average_price: Option<Decimal>,
+}
+
+/// A venue the coordinator can send a single leg to and learn how much of it filled.
+#[async_trait]
+pub trait LegVenue: Send + Sync {
+    fn name(&self) -> &str;
+    /// All-or-nothing venues (on-chain swaps) never leave a partial fill.
+    fn is_atomic(&self) -> bool;
+    fn is_simulated(&self) -> bool;
+    async fn expected_latency_ms(&self) -> u64;
+    async fn execute(&self, leg: &LegRequest) -> Result<LegFill>;
+}
+
+/// Authenticated exchange account: place, poll until terminal, cancel any remainder on timeout.
+pub struct CexLegVenue {
+    client: Arc<dyn TradingCexClient>,
+    fill_timeout_ms: u64,
+    poll_interval_ms: u64,
+    latency_ms: AtomicU64,
+}
+
+impl CexLegVenue {
+    pub fn new(client: Arc<dyn TradingCexClient>) -> Self {
+        Self {
+            client,
+            fill_timeout_ms: 3000,
+            poll_interval_ms: 150,
+            latency_ms: AtomicU64::new(100),
+        }
+    }
+}
+
+#[async_trait]
+impl LegVenue for CexLegVenue {
+    fn name(&self) -> &str {
+        self.client.name()
+    }
+
+    fn is_atomic(&self) -> bool {
+        false
+    }
+
+    fn is_simulated(&self) -> bool {
+        false
+    }
+
+    async fn expected_latency_ms(&self) -> u64 {
+        self.latency_ms.load(Ordering::Relaxed)
+    }
+
+    async fn execute(&self, leg: &LegRequest) -> Result<LegFill> {
+        let order = Order {
+            pair: leg.pair.clone(),
+            side: leg.side.clone(),
+            amount: leg.amount,
+            price: leg.limit_price,
+            order_type: if leg.limit_price.is_some() { "limit" } else { "market" }.to_string(),
+        };
+        let client_order_id = format!("dexter-{}", uuid::Uuid::new_v4().simple());
+
+        let started = std::time::Instant::now();
+        let order_id = self.client.place_order_with_id(&order, &client_order_id).await?;
+        // EWMA of the acknowledgement latency drives the dispatch decision
+        let ack_ms = started.elapsed().as_millis() as u64;
+        let previous = self.latency_ms.load(Ordering::Relaxed);
+        self.latency_ms.store((previous * 4 + ack_ms) / 5, Ordering::Relaxed);
+
+        let mut status = self.client.get_order(&leg.pair, &order_id).await?;
+        while !status.state.is_terminal() {
+            if started.elapsed().as_millis() as u64 > self.fill_timeout_ms {
+                if let Err(e) = self.client.cancel_order(&leg.pair, &order_id).await {
+                    warn!("⚠️ Failed to cancel unfilled remainder of {} on {}: {}", order_id, self.name(), e);
+                }
+                status = self.client.get_order(&leg.pair, &order_id).await?;
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(self.poll_interval_ms)).await;
+            status = self.client.get_order(&leg.pair, &order_id).await?;
+        }
+
+        Ok(LegFill {
+            order_id: Some(order_id),
+            filled_amount: status.filled_amount,
+            average_price: status.avg_fill_price.or(leg.limit_price),
+        })
+    }
+}
+
+/// Scenario venue from the exchange simulator: market orders on CEX books, swaps on DEX pools.
+pub struct SimulatedLegVenue {
+    exchange: SimulatedExchange,
+}
+
+impl SimulatedLegVenue {
+    pub fn new(exchange: SimulatedExchange) -> Self {
+        Self { exchange }
+    }
+
+    /// Average price for `amount` against the current book, used to enforce limits before a market order.
+    async fn book_price(&self, leg: &LegRequest) -> Result<Decimal> {
+        let book = CexClient::get_order_book(&self.exchange, &leg.pair).await?;
+        let levels = if leg.side == "buy" { &book.asks } else { &book.bids };
+        let mut remaining = leg.amount;
+        let mut cost = Decimal::ZERO;
+        for level in levels {
+            let take = remaining.min(level.amount);
+            cost += take * level.price;
+            remaining -= take;
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+        }
+        let filled = leg.amount - remaining.max(Decimal::ZERO);
+        if filled <= Decimal::ZERO {
+            return Err(anyhow!("{} has no {} liquidity for {}", self.exchange.name(), leg.side, leg.pair));
+        }
+        Ok(cost / filled)
+    }
+}
+
+#[async_trait]
+impl LegVenue for SimulatedLegVenue {
+    fn name(&self) -> &str {
+        self.exchange.name()
+    }
+
+    fn is_atomic(&self) -> bool {
+        self.exchange.kind() == SimVenueKind::Dex
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+
+    async fn expected_latency_ms(&self) -> u64 {
+        self.exchange.latency_ms().await
+    }
+
+    async fn execute(&self, leg: &LegRequest) -> Result<LegFill> {
+        if self.exchange.kind() == SimVenueKind::Dex {
+            let (base, quote) = leg.pair.split_once('/')
+                .ok_or_else(|| anyhow!("Invalid pair {}", leg.pair))?;
+            let price = leg.limit_price.unwrap_or(leg.expected_price);
+            let step = if leg.side == "buy" {
+                TradeStep {
+                    exchange: leg.venue.clone(),
+                    action: "swap".to_string(),
+                    from_token: quote.to_string(),
+                    to_token: base.to_string(),
+                    amount: leg.amount * price,
+                    price,
+                    fees: Decimal::ZERO,
+                }
+            } else {
+                TradeStep {
+                    exchange: leg.venue.clone(),
+                    action: "swap".to_string(),
+                    from_token: base.to_string(),
+                    to_token: quote.to_string(),
+                    amount: leg.amount,
+                    price,
+                    fees: Decimal::ZERO,
+                }
+            };
+            let result = self.exchange.submit_swap(&step).await?;
+            return Ok(LegFill {
+                order_id: Some(result.order_id),
+                filled_amount: result.filled_quantity,
+                average_price: result.average_price,
+            });
+        }
+
+        if let Some(limit) = leg.limit_price {
+            let price = self.book_price(leg).await?;
+            let beyond = if leg.side == "buy" { price > limit } else { price < limit };
+            if beyond {
+                return Err(anyhow!("{} book price {} is beyond limit {}", self.exchange.name(), price, limit));
+            }
+        }
+
+        let order = Order {
+            pair: leg.pair.clone(),
+            side: leg.side.clone(),
+            amount: leg.amount,
+            price: None,
+            order_type: "market".to_string(),
+        };
+        let result = self.exchange.submit_order(&order).await?;
+        if result.status == SimOrderStatus::Rejected {
+            return Err(anyhow!("{}", result.reason.unwrap_or_else(|| "Order rejected".to_string())));
+        }
+        Ok(LegFill {
+            order_id: Some(result.order_id),
+            filled_amount: result.filled_quantity,
+            average_price: result.average_price,
+        })
+    }
+}
+
+// ============================================================================
+// COORDINATOR CONFIGURATION
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DispatchMode {
+    Concurrent,
+    BuyFirst,
+    SellFirst,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResidualPolicy {
+    HedgeThenUnwind, // Complete the missing leg, reverse the filled one if that fails
+    HedgeOnly,
+    UnwindOnly,
+    Hold, // Leave the residual open and report it
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinatorConfig {
+    pub dispatch_override: Option<DispatchMode>,
+    pub max_concurrent_latency_gap_ms: u64, // Legs run together when their latencies are this close
+    pub residual_policy: ResidualPolicy,
+    pub residual_tolerance_usd: Decimal, // Residuals below this are treated as flat
+    pub max_hedge_attempts: u32,
+    pub max_hedge_slippage_bps: u32, // Hedge limit price versus the opportunity price
+    pub max_unwind_loss_usd: Decimal, // Unwind limit price is set so the loss cannot exceed this
+    pub max_open_exposure_usd: Decimal, // New trades are refused while unresolved residuals exceed this
+}
+
+impl Default for CoordinatorConfig {
+    fn default() -> Self {
+        Self {
+            dispatch_override: None,
+            max_concurrent_latency_gap_ms: 150,
+            residual_policy: ResidualPolicy::HedgeThenUnwind,
+            residual_tolerance_usd: Decimal::from(5),
+            max_hedge_attempts: 2,
+            max_hedge_slippage_bps: 50,
+            max_unwind_loss_usd: Decimal::from(250),
+            max_open_exposure_usd: Decimal::from(1000),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoLegPlan {
+    pub trade_id: String,
+    pub pair: String, // BASE/QUOTE
+    pub buy_venue: String,
+    pub sell_venue: String,
+    pub amount: Decimal, // Base units
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoLegOutcome {
+    pub mode: DispatchMode,
+    pub status: ExecutionStatus,
+    pub timeline: Vec<LegEvent>,
+    pub steps: Vec<ExecutionStep>,
+    pub bought: Decimal,
+    pub sold: Decimal,
+    pub avg_buy_price: Option<Decimal>,
+    pub avg_sell_price: Option<Decimal>,
+    pub fees_usd: Decimal,
+    pub residual: Decimal, // +long / -short base units
+    pub pnl_usd: Decimal, // Cash flow net of fees, residual marked at the opportunity mid
+    pub error: Option<String>,
+}
+
+/// Running position across every leg of one trade.
+#[derive(Debug, Clone, Default)]
+struct LegLedger {
+    bought: Decimal,
+    bought_quote: Decimal,
+    sold: Decimal,
+    sold_quote: Decimal,
+    fees_usd: Decimal,
+}
+
+impl LegLedger {
+    fn residual(&self) -> Decimal {
+        self.bought - self.sold
+    }
+
+    fn avg_buy(&self) -> Option<Decimal> {
+        (self.bought > Decimal::ZERO).then(|| self.bought_quote / self.bought)
+    }
+
+    fn avg_sell(&self) -> Option<Decimal> {
+        (self.sold > Decimal::ZERO).then(|| self.sold_quote / self.sold)
+    }
+}
+
+// ============================================================================
+// EXECUTION COORDINATOR
+// ============================================================================
+
+pub struct ExecutionCoordinator {
+    venues: Arc<RwLock<HashMap<String, Arc<dyn LegVenue>>>>,
+    open_exposure: Arc<RwLock<HashMap<String, Decimal>>>, // trade id -> unresolved residual (USD)
+    config: Arc<RwLock<CoordinatorConfig>>,
+    fee_model: Arc<FeeModel>,
+}
+
+impl ExecutionCoordinator {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
+        Self {
+            venues: Arc::new(RwLock::new(HashMap::new())),
+            open_exposure: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(CoordinatorConfig::default())),
+            fee_model,
+        }
+    }
+
+    pub async fn register_venue(&self, venue: Arc<dyn LegVenue>) {
+        info!("🔗 Execution coordinator registered venue {}", venue.name());
+        self.venues.write().await.insert(venue.name().to_string(), venue);
+    }
+
+    /// Both venues are routable; in simulation mode they must also be simulated.
+    pub async fn can_execute(&self, buy_venue: &str, sell_venue: &str, simulation_only: bool) -> bool {
+        let venues = self.venues.read().await;
+        [buy_venue, sell_venue].iter().all(|name| {
+            venues.get(*name).map(|v| !simulation_only || v.is_simulated()).unwrap_or(false)
+        })
+    }
+
+    pub async fn get_config(&self) -> CoordinatorConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: CoordinatorConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub async fn open_exposure_usd(&self) -> Decimal {
+        self.open_exposure.read().await.values().sum()
+    }
+
+    /// Mark a residual as closed out externally (manual flatten or later reconciliation).
+    pub async fn resolve_exposure(&self, trade_id: &str) {
+        self.open_exposure.write().await.remove(trade_id);
+    }
+
+    async fn venue(&self, name: &str) -> Result<Arc<dyn LegVenue>> {
+        self.venues.read().await.get(name).cloned()
+            .ok_or_else(|| anyhow!("No execution venue registered for {}", name))
+    }
+
+    /// Similar latencies run concurrently; otherwise the slower leg goes first so the
+    /// fast leg can be sized to its actual fill while the quote is still fresh.
+    async fn dispatch_mode(&self, buy: &dyn LegVenue, sell: &dyn LegVenue) -> DispatchMode {
+        let config = self.config.read().await;
+        if let Some(mode) = config.dispatch_override {
+            return mode;
+        }
+        let buy_latency = buy.expected_latency_ms().await;
+        let sell_latency = sell.expected_latency_ms().await;
+        if buy_latency.abs_diff(sell_latency) <= config.max_concurrent_latency_gap_ms {
+            DispatchMode::Concurrent
+        } else if buy_latency > sell_latency {
+            DispatchMode::BuyFirst
+        } else {
+            DispatchMode::SellFirst
+        }
+    }
+
+    /// Execute both legs of an arbitrage and leave the position flat, hedged or explicitly flagged.
+    pub async fn execute_two_leg(&self, plan: &TwoLegPlan) -> Result<TwoLegOutcome> {
+        let config = self.get_config().await;
+        let open_exposure = self.open_exposure_usd().await;
+        if open_exposure > config.max_open_exposure_usd {
+            return Err(anyhow!(
+                "Unresolved leg exposure ${} exceeds limit ${}", open_exposure.round_dp(2), config.max_open_exposure_usd
+            ));
+        }
+
+        let buy_venue = self.venue(&plan.buy_venue).await?;
+        let sell_venue = self.venue(&plan.sell_venue).await?;
+        let mode = self.dispatch_mode(buy_venue.as_ref(), sell_venue.as_ref()).await;
+        info!("🧭 Executing {}: buy {} {} on {}, sell on {} ({:?})",
+              plan.trade_id, plan.amount, plan.pair, plan.buy_venue, plan.sell_venue, mode);
+
+        let mut ledger = LegLedger::default();
+        let mut timeline = Vec::new();
+        let mut steps = Vec::new();
+
+        let buy_leg = LegRequest {
+            role: LegRole::Buy,
+            venue: plan.buy_venue.clone(),
+            pair: plan.pair.clone(),
+            side: "buy".to_string(),
+            amount: plan.amount,
+            expected_price: plan.buy_price,
+            limit_price: None,
+        };
+        let sell_leg = LegRequest {
+            role: LegRole::Sell,
+            venue: plan.sell_venue.clone(),
+            pair: plan.pair.clone(),
+            side: "sell".to_string(),
+            amount: plan.amount,
+            expected_price: plan.sell_price,
+            limit_price: None,
+        };
+
+        match mode {
+            DispatchMode::Concurrent => {
+                let (buy_result, sell_result) = tokio::join!(
+                    self.run_leg(buy_venue.clone(), buy_leg),
+                    self.run_leg(sell_venue.clone(), sell_leg)
+                );
+                for result in [buy_result, sell_result] {
+                    self.record(&plan.trade_id, result, &mut ledger, &mut timeline, &mut steps).await;
+                }
+            }
+            DispatchMode::BuyFirst | DispatchMode::SellFirst => {
+                let (first_venue, first_leg, second_venue, mut second_leg) = if mode == DispatchMode::BuyFirst {
+                    (buy_venue.clone(), buy_leg, sell_venue.clone(), sell_leg)
+                } else {
+                    (sell_venue.clone(), sell_leg, buy_venue.clone(), buy_leg)
+                };
+
+                let first = self.run_leg(first_venue, first_leg).await;
+                let first_filled = first.2.as_ref().map(|f| f.filled_amount).unwrap_or(Decimal::ZERO);
+                self.record(&plan.trade_id, first, &mut ledger, &mut timeline, &mut steps).await;
+
+                if first_filled > Decimal::ZERO {
+                    // Size the second leg to what actually filled on the first
+                    second_leg.amount = first_filled;
+                    let second = self.run_leg(second_venue, second_leg).await;
+                    self.record(&plan.trade_id, second, &mut ledger, &mut timeline, &mut steps).await;
+                } else {
+                    timeline.push(LegEvent {
+                        timestamp_ms: now_ms(),
+                        role: second_leg.role,
+                        kind: LegEventKind::Skipped,
+                        venue: second_leg.venue.clone(),
+                        side: second_leg.side.clone(),
+                        amount: Decimal::ZERO,
+                        price: None,
+                        order_id: None,
+                        detail: Some("First leg did not fill".to_string()),
+                    });
+                }
+            }
+        }
+
+        // Resolve any residual left by partial fills or a failed leg
+        let mark = (plan.buy_price + plan.sell_price) / Decimal::from(2);
+        let mut unwound = false;
+        if (ledger.residual() * mark).abs() > config.residual_tolerance_usd {
+            warn!("⚠️ {} leg imbalance of {} {} after primary legs", plan.trade_id, ledger.residual(), plan.pair);
+
+            let hedge = matches!(config.residual_policy, ResidualPolicy::HedgeThenUnwind | ResidualPolicy::HedgeOnly);
+            let unwind = matches!(config.residual_policy, ResidualPolicy::HedgeThenUnwind | ResidualPolicy::UnwindOnly);
+
+            if hedge {
+                self.hedge(plan, &config, mark, &mut ledger, &mut timeline, &mut steps).await;
+            }
+            if unwind && (ledger.residual() * mark).abs() > config.residual_tolerance_usd {
+                unwound = self.unwind(plan, &config, mark, &mut ledger, &mut timeline, &mut steps).await;
+            }
+        }
+
+        timeline.sort_by_key(|e| e.timestamp_ms);
+        let residual = ledger.residual();
+        let residual_usd = (residual * mark).abs();
+        let pnl_usd = ledger.sold_quote - ledger.bought_quote + residual * mark - ledger.fees_usd;
+
+        let (status, error) = if residual_usd > config.residual_tolerance_usd {
+            self.open_exposure.write().await.insert(plan.trade_id.clone(), residual_usd);
+            error!("🚨 {} left {} {} (${}) of unhedged exposure", plan.trade_id, residual, plan.pair, residual_usd.round_dp(2));
+            (ExecutionStatus::PartiallyFilled,
+             Some(format!("Residual exposure of {} {} left open", residual.round_dp(8), plan.pair)))
+        } else if ledger.bought.is_zero() && ledger.sold.is_zero() {
+            (ExecutionStatus::Failed, Some("No leg filled".to_string()))
+        } else if unwound {
+            (ExecutionStatus::Unwound, Some("Legs could not be completed; filled leg unwound".to_string()))
+        } else {
+            (ExecutionStatus::Completed, None)
+        };
+
+        info!("✅ {} finished {:?}: bought {} sold {} pnl ${}",
+              plan.trade_id, status, ledger.bought, ledger.sold, pnl_usd.round_dp(4));
+
+        Ok(TwoLegOutcome {
+            mode,
+            status,
+            timeline,
+            steps,
+            bought: ledger.bought,
+            sold: ledger.sold,
+            avg_buy_price: ledger.avg_buy(),
+            avg_sell_price: ledger.avg_sell(),
+            fees_usd: ledger.fees_usd,
+            residual,
+            pnl_usd,
+            error,
+        })
+    }
+
+    /// Complete the missing side on the original venue at a bounded price.
+    async fn hedge(
+        &self,
+        plan: &TwoLegPlan,
+        config: &CoordinatorConfig,
+        mark: Decimal,
+        ledger: &mut LegLedger,
+        timeline: &mut Vec<LegEvent>,
+        steps: &mut Vec<ExecutionStep>,
+    ) {
+        let slippage = Decimal::from(config.max_hedge_slippage_bps) / Decimal::from(10_000);
+        for attempt in 1..=config.max_hedge_attempts {
+            let residual = ledger.residual();
+            if (residual * mark).abs() <= config.residual_tolerance_usd {
+                return;
+            }
+            let (venue_name, side, expected, limit) = if residual > Decimal::ZERO {
+                (&plan.sell_venue, "sell", plan.sell_price, plan.sell_price * (Decimal::ONE - slippage))
+            } else {
+                (&plan.buy_venue, "buy", plan.buy_price, plan.buy_price * (Decimal::ONE + slippage))
+            };
+            let venue = match self.venue(venue_name).await {
+                Ok(venue) => venue,
+                Err(_) => return,
+            };
+
+            info!("🛡️ Hedge attempt {} for {}: {} {} on {}", attempt, plan.trade_id, side, residual.abs(), venue_name);
+            let leg = LegRequest {
+                role: LegRole::Hedge,
+                venue: venue_name.clone(),
+                pair: plan.pair.clone(),
+                side: side.to_string(),
+                amount: residual.abs(),
+                expected_price: expected,
+                limit_price: Some(limit),
+            };
+            let result = self.run_leg(venue, leg).await;
+            self.record(&plan.trade_id, result, ledger, timeline, steps).await;
+        }
+    }
+
+    /// Reverse the filled side on the venue it filled on, with a limit that caps the loss.
+    async fn unwind(
+        &self,
+        plan: &TwoLegPlan,
+        config: &CoordinatorConfig,
+        mark: Decimal,
+        ledger: &mut LegLedger,
+        timeline: &mut Vec<LegEvent>,
+        steps: &mut Vec<ExecutionStep>,
+    ) -> bool {
+        let residual = ledger.residual();
+        let loss_per_unit = config.max_unwind_loss_usd / residual.abs();
+        let (venue_name, side, entry) = if residual > Decimal::ZERO {
+            (&plan.buy_venue, "sell", ledger.avg_buy().unwrap_or(plan.buy_price))
+        } else {
+            (&plan.sell_venue, "buy", ledger.avg_sell().unwrap_or(plan.sell_price))
+        };
+        let limit = if side == "sell" {
+            (entry - loss_per_unit).max(Decimal::ZERO)
+        } else {
+            entry + loss_per_unit
+        };
+        let venue = match self.venue(venue_name).await {
+            Ok(venue) => venue,
+            Err(_) => return false,
+        };
+
+        warn!("↩️ Unwinding {} {} of {} on {} (limit {})", side, residual.abs(), plan.pair, venue_name, limit.round_dp(6));
+        let leg = LegRequest {
+            role: LegRole::Unwind,
+            venue: venue_name.clone(),
+            pair: plan.pair.clone(),
+            side: side.to_string(),
+            amount: residual.abs(),
+            expected_price: mark,
+            limit_price: Some(limit),
+        };
+        let result = self.run_leg(venue, leg).await;
+        self.record(&plan.trade_id, result, ledger, timeline, steps).await;
+
+        (ledger.residual() * mark).abs() <= config.residual_tolerance_usd
+    }
+
+    async fn run_leg(&self, venue: Arc<dyn LegVenue>, leg: LegRequest) -> (LegRequest, Vec<LegEvent>, Option<LegFill>) {
+        let mut events = vec![LegEvent {
+            timestamp_ms: now_ms(),
+            role: leg.role,
+            kind: LegEventKind::Submitted,
+            venue: leg.venue.clone(),
+            side: leg.side.clone(),
+            amount: leg.amount,
+            price: leg.limit_price.or(Some(leg.expected_price)),
+            order_id: None,
+            detail: None,
+        }];
+
+        let fill = match venue.execute(&leg).await {
+            Ok(fill) => {
+                let kind = if fill.filled_amount >= leg.amount {
+                    LegEventKind::Filled
+                } else if fill.filled_amount > Decimal::ZERO {
+                    LegEventKind::PartiallyFilled
+                } else {
+                    LegEventKind::Failed
+                };
+                events.push(LegEvent {
+                    timestamp_ms: now_ms(),
+                    role: leg.role,
+                    kind,
+                    venue: leg.venue.clone(),
+                    side: leg.side.clone(),
+                    amount: fill.filled_amount,
+                    price: fill.average_price,
+                    order_id: fill.order_id.clone(),
+                    detail: (kind == LegEventKind::Failed).then(|| "Nothing filled".to_string()),
+                });
+                Some(fill)
+            }
+            Err(e) => {
+                warn!("❌ {:?} leg on {} failed: {}", leg.role, leg.venue, e);
+                events.push(LegEvent {
+                    timestamp_ms: now_ms(),
+                    role: leg.role,
+                    kind: LegEventKind::Failed,
+                    venue: leg.venue.clone(),
+                    side: leg.side.clone(),
+                    amount: Decimal::ZERO,
+                    price: None,
+                    order_id: None,
+                    detail: Some(e.to_string()),
+                });
+                None
+            }
+        };
+
+        (leg, events, fill)
+    }
+
+    /// Fold a leg result into the ledger, timeline and execution steps.
+    async fn record(
+        &self,
+        trade_id: &str,
+        (leg, events, fill): (LegRequest, Vec<LegEvent>, Option<LegFill>),
+        ledger: &mut LegLedger,
+        timeline: &mut Vec<LegEvent>,
+        steps: &mut Vec<ExecutionStep>,
+    ) {
+        timeline.extend(events);
+
+        let (filled, price, order_id) = match &fill {
+            Some(f) if f.filled_amount > Decimal::ZERO => {
+                (f.filled_amount, f.average_price.unwrap_or(leg.expected_price), f.order_id.clone())
+            }
+            _ => (Decimal::ZERO, leg.expected_price, None),
+        };
+        let (base, quote) = leg.pair.split_once('/').unwrap_or((leg.pair.as_str(), "USDC"));
+
+        let mut fee_usd = Decimal::ZERO;
+        if filled > Decimal::ZERO {
+            let notional = filled * price;
+            fee_usd = self.fee_model.trading_fee_usd(&leg.venue, notional, Liquidity::Taker).await;
+            ledger.fees_usd += fee_usd;
+            if leg.side == "buy" {
+                ledger.bought += filled;
+                ledger.bought_quote += notional;
+            } else {
+                ledger.sold += filled;
+                ledger.sold_quote += notional;
+            }
+        }
+
+        let role = format!("{:?}", leg.role).to_lowercase();
+        steps.push(ExecutionStep {
+            step_id: format!("{}_{}_{}", trade_id, role, steps.len()),
+            exchange: leg.venue.clone(),
+            action: leg.side.clone(),
+            from_token: if leg.side == "buy" { quote } else { base }.to_string(),
+            to_token: if leg.side == "buy" { base } else { quote }.to_string(),
+            amount: filled,
+            price,
+            fee_usd,
+            status: if filled.is_zero() {
+                ExecutionStatus::Failed
+            } else if filled < leg.amount {
+                ExecutionStatus::PartiallyFilled
+            } else {
+                ExecutionStatus::Completed
+            },
+            transaction_hash: order_id,
+            gas_used: None,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        });
+    }
+}
+
+impl Clone for ExecutionCoordinator {
+    fn clone(&self) -> Self {
+        Self {
+            venues: self.venues.clone(),
+            open_exposure: self.open_exposure.clone(),
+            config: self.config.clone(),
+            fee_model: self.fee_model.clone(),
+        }
+    }
+}
+
+impl Default for ExecutionCoordinator {
+    fn default() -> Self {
+        Self::new(Arc::new(FeeModel::new()))
+    }
+}
+
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis() as u64
+}
@@ -1673,3 +1673,203 @@ impl ExternalApiClient {
         Ok(opportunities)
     }
 }
+
+// ============================================================================
+// CEX ORDER BOOK DEPTH AND SOLANA FEE MARKET
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexBookLevel {
+    pub price: Decimal,
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexOrderBook {
+    pub exchange: String,
+    pub symbol: String,
+    pub bids: Vec<CexBookLevel>, // Best (highest) first
+    pub asks: Vec<CexBookLevel>, // Best (lowest) first
+    pub timestamp: u64,
+}
+
+impl CexOrderBook {
+    /// Quote spent and average price to buy `base_amount` against the asks
+    pub fn cost_to_buy(&self, base_amount: Decimal) -> Option<(Decimal, Decimal)> {
+        walk_levels(&self.asks, base_amount)
+    }
+
+    /// Quote received and average price to sell `base_amount` into the bids
+    pub fn proceeds_from_sell(&self, base_amount: Decimal) -> Option<(Decimal, Decimal)> {
+        walk_levels(&self.bids, base_amount)
+    }
+
+    pub fn mid(&self) -> Option<Decimal> {
+        match (self.bids.first(), self.asks.first()) {
+            (Some(bid), Some(ask)) => Some((bid.price + ask.price) / Decimal::from(2)),
+            _ => None,
+        }
+    }
+}
+
+/// Walk book levels until `base_amount` is filled; None if the visible book is too thin.
+fn walk_levels(levels: &[CexBookLevel], base_amount: Decimal) -> Option<(Decimal, Decimal)> {
+    if base_amount <= Decimal::ZERO {
+        return None;
+    }
+    let mut remaining = base_amount;
+    let mut quote_total = Decimal::ZERO;
+    for level in levels {
+        let take = remaining.min(level.quantity);
+        quote_total += take * level.price;
+        remaining -= take;
+        if remaining <= Decimal::ZERO {
+            return Some((quote_total, quote_total / base_amount));
+        }
+    }
+    None
+}
+
+fn parse_levels(value: &serde_json::Value) -> Vec<CexBookLevel> {
+    value.as_array()
+        .map(|levels| levels.iter()
+            .filter_map(|level| {
+                let price = level[0].as_str().and_then(|p| Decimal::from_str(p).ok())
+                    .or_else(|| level[0].as_f64().and_then(Decimal::from_f64))?;
+                let quantity = level[1].as_str().and_then(|q| Decimal::from_str(q).ok())
+                    .or_else(|| level[1].as_f64().and_then(Decimal::from_f64))?;
+                Some(CexBookLevel { price, quantity })
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+impl ExternalApiClient {
+    /// Jupiter quote restricted to specific AMMs (e.g. "Raydium", "Whirlpool"); None routes through all
+    pub async fn get_jupiter_quote_on_dexes(
+        &self,
+        input_mint: &str,
+        output_mint: &str,
+        amount: u64,
+        slippage_bps: Option<u32>,
+        dexes: Option<&[&str]>,
+    ) -> Result<JupiterQuoteResponse> {
+        let dexes = match dexes {
+            Some(dexes) if !dexes.is_empty() => dexes,
+            _ => return self.get_jupiter_quote(input_mint, output_mint, amount, slippage_bps).await,
+        };
+
+        let url = format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}&dexes={}",
+            self.jupiter_base_url, input_mint, output_mint, amount,
+            slippage_bps.unwrap_or(50), dexes.join(",")
+        );
+
+        let response = self.client
+            .get(&url)
+            .header("User-Agent", "DEXTER-v3.0-Arbitrage-Bot")
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Jupiter API error {}: {}", status, text));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Level-2 order book snapshot from a CEX public endpoint
+    pub async fn get_cex_order_book(&self, exchange: &str, base: &str, depth: u32) -> Result<CexOrderBook> {
+        let (symbol, url) = match exchange {
+            "Binance" => {
+                let symbol = format!("{}USDT", base);
+                let url = format!("https://api.binance.com/api/v3/depth?symbol={}&limit={}", symbol, depth);
+                (symbol, url)
+            }
+            "Coinbase" => {
+                let symbol = format!("{}-USD", base);
+                let url = format!("https://api.exchange.coinbase.com/products/{}/book?level=2", symbol);
+                (symbol, url)
+            }
+            "Kraken" => {
+                let symbol = format!("{}USD", if base == "BTC" { "XBT" } else { base });
+                let url = format!("https://api.kraken.com/0/public/Depth?pair={}&count={}", symbol, depth);
+                (symbol, url)
+            }
+            "OKX" => {
+                let symbol = format!("{}-USDT", base);
+                let url = format!("https://www.okx.com/api/v5/market/books?instId={}&sz={}", symbol, depth);
+                (symbol, url)
+            }
+            "Bybit" => {
+                let symbol = format!("{}USDT", base);
+                let url = format!("https://api.bybit.com/v5/market/orderbook?category=spot&symbol={}&limit={}", symbol, depth);
+                (symbol, url)
+            }
+            other => return Err(anyhow!("Order book not supported for {}", other)),
+        };
+
+        let response = tokio::time::timeout(
+            tokio::time::Duration::from_secs(5),
+            self.client.get(&url).send()
+        ).await
+            .map_err(|_| anyhow!("{} order book timeout", exchange))??;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("{} order book error {}", exchange, response.status()));
+        }
+        let data: serde_json::Value = response.json().await?;
+
+        let (bids, asks) = match exchange {
+            "Kraken" => {
+                let book = data["result"].as_object()
+                    .and_then(|result| result.values().next())
+                    .ok_or_else(|| anyhow!("Kraken returned empty book"))?;
+                (parse_levels(&book["bids"]), parse_levels(&book["asks"]))
+            }
+            "OKX" => (parse_levels(&data["data"][0]["bids"]), parse_levels(&data["data"][0]["asks"])),
+            "Bybit" => (parse_levels(&data["result"]["b"]), parse_levels(&data["result"]["a"])),
+            _ => (parse_levels(&data["bids"]), parse_levels(&data["asks"])),
+        };
+
+        if bids.is_empty() || asks.is_empty() {
+            return Err(anyhow!("{} returned an empty {} book", exchange, symbol));
+        }
+
+        Ok(CexOrderBook {
+            exchange: exchange.to_string(),
+            symbol,
+            bids,
+            asks,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    /// Recent prioritization fees (micro-lamports per compute unit) at the given percentile
+    pub async fn get_solana_priority_fee(&self, rpc_url: &str, accounts: &[&str], percentile: f64) -> Result<u64> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getRecentPrioritizationFees",
+            "params": [accounts],
+        });
+
+        let response = self.client.post(rpc_url).json(&body).send().await?;
+        let data: serde_json::Value = response.json().await?;
+
+        let mut fees: Vec<u64> = data["result"].as_array()
+            .ok_or_else(|| anyhow!("Invalid getRecentPrioritizationFees response"))?
+            .iter()
+            .filter_map(|entry| entry["prioritizationFee"].as_u64())
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
+        fees.sort_unstable();
+
+        let index = ((fees.len() - 1) as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+        Ok(fees[index])
+    }
+}
@@ -0,0 +1,811 @@
+// Fee Model - Per-venue trading, network, withdrawal and bridge cost schedules
+// Turns gross spreads into itemized net profit for every opportunity type
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromStr, FromPrimitive, ToPrimitive};
+use log::debug;
+
+// ============================================================================
+// FEE SCHEDULE DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum VenueKind {
+    Cex,
+    Dex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GasOperation {
+    Swap,
+    Transfer,
+    Approve,
+    FlashLoan,
+    BridgeDeposit,
+    BridgeClaim,
+}
+
+/// Maker/taker rates that apply once 30-day volume reaches `min_volume_30d_usd`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub min_volume_30d_usd: Decimal,
+    pub maker_fee: Decimal,
+    pub taker_fee: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueFeeSchedule {
+    pub venue: String,
+    pub kind: VenueKind,
+    pub tiers: Vec<FeeTier>,
+    pub volume_30d_usd: Decimal,
+    pub pool_fee: Option<Decimal>, // DEX LP fee, charged on input amount
+    pub chain: Option<String>,     // Chain whose gas the venue consumes (DEX only)
+    pub withdrawal_fees: HashMap<String, Decimal>, // asset -> flat fee in asset units
+    #[serde(default)]
+    pub expected_slippage: Decimal, // Fraction of notional lost to price impact per fill
+}
+
+impl VenueFeeSchedule {
+    /// Tier matching the current 30-day volume (tiers are kept sorted ascending).
+    pub fn active_tier(&self) -> Option<&FeeTier> {
+        self.tiers
+            .iter()
+            .rfind(|tier| self.volume_30d_usd >= tier.min_volume_30d_usd)
+            .or_else(|| self.tiers.first())
+    }
+
+    /// Fractional fee rate for a fill with the given liquidity flag.
+    pub fn fee_rate(&self, liquidity: Liquidity) -> Decimal {
+        let tier_fee = self.active_tier()
+            .map(|tier| match liquidity {
+                Liquidity::Maker => tier.maker_fee,
+                Liquidity::Taker => tier.taker_fee,
+            })
+            .unwrap_or(Decimal::ZERO);
+
+        tier_fee + self.pool_fee.unwrap_or(Decimal::ZERO)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainGasModel {
+    pub chain: String,
+    pub native_token: String,
+    pub native_price_usd: Decimal,
+    pub base_fee_native: Decimal,   // Fixed per-transaction fee (e.g. Solana signature fee)
+    pub unit_price_native: Decimal, // Price per gas/compute unit incl. priority fee
+    pub operation_units: HashMap<GasOperation, u64>,
+}
+
+impl ChainGasModel {
+    pub fn cost_native(&self, operation: GasOperation) -> Decimal {
+        let units = self.operation_units.get(&operation).copied().unwrap_or(0);
+        self.base_fee_native + self.unit_price_native * Decimal::from(units)
+    }
+
+    pub fn cost_usd(&self, operation: GasOperation) -> Decimal {
+        self.cost_native(operation) * self.native_price_usd
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeFeeSchedule {
+    pub bridge: String,
+    pub fee_percentage: Decimal, // Fraction of bridged notional
+    pub fixed_fee_usd: Decimal,  // Relayer / messaging fee
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlashLoanFeeSchedule {
+    pub provider: String,
+    pub fee_percentage: Decimal, // Fraction of borrowed notional
+}
+
+// ============================================================================
+// PROFIT BREAKDOWN
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CostKind {
+    TradingFee,
+    PoolFee,
+    NetworkGas,
+    Withdrawal,
+    Bridge,
+    FlashLoanFee,
+    Slippage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostItem {
+    pub kind: CostKind,
+    pub venue: String,
+    pub amount_usd: Decimal,
+    pub description: String,
+}
+
+/// Gross profit, itemized costs and net profit for a single opportunity.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfitBreakdown {
+    pub notional_usd: Decimal,
+    pub gross_profit_usd: Decimal,
+    pub costs: Vec<CostItem>,
+    pub total_costs_usd: Decimal,
+    pub net_profit_usd: Decimal,
+    pub net_profit_percentage: Decimal,
+}
+
+impl ProfitBreakdown {
+    pub fn new(notional_usd: Decimal, gross_profit_usd: Decimal) -> Self {
+        let mut breakdown = Self {
+            notional_usd,
+            gross_profit_usd,
+            ..Default::default()
+        };
+        breakdown.recompute();
+        breakdown
+    }
+
+    pub fn add_cost(&mut self, kind: CostKind, venue: &str, amount_usd: Decimal, description: impl Into<String>) {
+        if amount_usd.is_zero() {
+            return;
+        }
+        self.costs.push(CostItem {
+            kind,
+            venue: venue.to_string(),
+            amount_usd,
+            description: description.into(),
+        });
+        self.recompute();
+    }
+
+    pub fn cost_of(&self, kind: CostKind) -> Decimal {
+        self.costs.iter().filter(|c| c.kind == kind).map(|c| c.amount_usd).sum()
+    }
+
+    pub fn costs_for_venue(&self, venue: &str) -> Decimal {
+        self.costs.iter().filter(|c| c.venue == venue).map(|c| c.amount_usd).sum()
+    }
+
+    pub fn is_profitable(&self) -> bool {
+        self.net_profit_usd > Decimal::ZERO
+    }
+
+    fn recompute(&mut self) {
+        self.total_costs_usd = self.costs.iter().map(|c| c.amount_usd).sum();
+        self.net_profit_usd = self.gross_profit_usd - self.total_costs_usd;
+        self.net_profit_percentage = if self.notional_usd > Decimal::ZERO {
+            self.net_profit_usd / self.notional_usd * Decimal::from(100)
+        } else {
+            Decimal::ZERO
+        };
+    }
+}
+
+/// One side of a trade evaluated by the fee model.
+#[derive(Debug, Clone)]
+pub struct TradeLeg {
+    pub venue: String,
+    pub notional_usd: Decimal,
+    pub liquidity: Liquidity,
+}
+
+/// Venue and fill price of one side of a two-leg trade.
+#[derive(Debug, Clone, Copy)]
+pub struct PricedLeg<'a> {
+    pub venue: &'a str,
+    pub price: Decimal,
+}
+
+// ============================================================================
+// FEE MODEL
+// ============================================================================
+
+#[derive(Clone)]
+pub struct FeeModel {
+    venues: Arc<RwLock<HashMap<String, VenueFeeSchedule>>>,
+    chains: Arc<RwLock<HashMap<String, ChainGasModel>>>,
+    bridges: Arc<RwLock<HashMap<String, BridgeFeeSchedule>>>,
+    flash_loans: Arc<RwLock<HashMap<String, FlashLoanFeeSchedule>>>,
+    fallback_venue: VenueFeeSchedule,
+}
+
+impl FeeModel {
+    pub fn new() -> Self {
+        let venues = default_venue_schedules()
+            .into_iter()
+            .map(|schedule| (normalize(&schedule.venue), schedule))
+            .collect();
+        let chains = default_chain_gas_models()
+            .into_iter()
+            .map(|model| (normalize(&model.chain), model))
+            .collect();
+        let bridges = default_bridge_schedules()
+            .into_iter()
+            .map(|schedule| (normalize(&schedule.bridge), schedule))
+            .collect();
+        let flash_loans = default_flash_loan_schedules()
+            .into_iter()
+            .map(|schedule| (normalize(&schedule.provider), schedule))
+            .collect();
+
+        Self {
+            venues: Arc::new(RwLock::new(venues)),
+            chains: Arc::new(RwLock::new(chains)),
+            bridges: Arc::new(RwLock::new(bridges)),
+            flash_loans: Arc::new(RwLock::new(flash_loans)),
+            fallback_venue: VenueFeeSchedule {
+                venue: "unknown".to_string(),
+                kind: VenueKind::Cex,
+                tiers: vec![tier("0", "0.003", "0.003")],
+                volume_30d_usd: Decimal::ZERO,
+                pool_fee: None,
+                chain: None,
+                withdrawal_fees: HashMap::new(),
+                expected_slippage: Decimal::from_str("0.001").unwrap(),
+            },
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Schedule management
+    // ------------------------------------------------------------------------
+
+    pub async fn schedule(&self, venue: &str) -> VenueFeeSchedule {
+        self.venues.read().await
+            .get(&normalize(venue))
+            .cloned()
+            .unwrap_or_else(|| VenueFeeSchedule {
+                venue: venue.to_string(),
+                ..self.fallback_venue.clone()
+            })
+    }
+
+    pub async fn set_schedule(&self, schedule: VenueFeeSchedule) {
+        self.venues.write().await.insert(normalize(&schedule.venue), schedule);
+    }
+
+    pub async fn set_volume_30d(&self, venue: &str, volume_usd: Decimal) {
+        if let Some(schedule) = self.venues.write().await.get_mut(&normalize(venue)) {
+            schedule.volume_30d_usd = volume_usd;
+        }
+    }
+
+    /// Refresh the USD price of a chain's native token used for gas pricing.
+    pub async fn update_native_price(&self, chain: &str, price_usd: Decimal) {
+        if let Some(model) = self.chains.write().await.get_mut(&normalize(chain)) {
+            model.native_price_usd = price_usd;
+            debug!("⛽ Updated {} gas token price to ${}", chain, price_usd);
+        }
+    }
+
+    pub async fn update_gas_price(&self, chain: &str, unit_price_native: Decimal) {
+        if let Some(model) = self.chains.write().await.get_mut(&normalize(chain)) {
+            model.unit_price_native = unit_price_native;
+        }
+    }
+
+    pub async fn chain_model(&self, chain: &str) -> Option<ChainGasModel> {
+        self.chains.read().await.get(&normalize(chain)).cloned()
+    }
+
+    // ------------------------------------------------------------------------
+    // Individual cost components
+    // ------------------------------------------------------------------------
+
+    pub async fn fee_rate(&self, venue: &str, liquidity: Liquidity) -> Decimal {
+        self.schedule(venue).await.fee_rate(liquidity)
+    }
+
+    pub async fn trading_fee_usd(&self, venue: &str, notional_usd: Decimal, liquidity: Liquidity) -> Decimal {
+        notional_usd * self.fee_rate(venue, liquidity).await
+    }
+
+    pub async fn gas_cost_usd(&self, chain: &str, operation: GasOperation) -> Decimal {
+        self.chains.read().await
+            .get(&normalize(chain))
+            .map(|model| model.cost_usd(operation))
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub async fn withdrawal_fee_usd(&self, venue: &str, asset: &str, asset_price_usd: Decimal) -> Decimal {
+        let schedule = self.schedule(venue).await;
+        schedule.withdrawal_fees
+            .get(&asset.to_uppercase())
+            .map(|fee| *fee * asset_price_usd)
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub async fn bridge_fee_usd(&self, bridge: &str, notional_usd: Decimal) -> Option<Decimal> {
+        self.bridges.read().await
+            .get(&normalize(bridge))
+            .map(|schedule| notional_usd * schedule.fee_percentage + schedule.fixed_fee_usd)
+    }
+
+    /// Fee rate charged by a flash loan provider; unknown providers are priced at the most expensive schedule.
+    pub async fn flash_loan_fee_rate(&self, provider: &str) -> Decimal {
+        let schedules = self.flash_loans.read().await;
+        schedules.get(&normalize(provider))
+            .or_else(|| schedules.values().max_by_key(|schedule| schedule.fee_percentage))
+            .map(|schedule| schedule.fee_percentage)
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub async fn set_flash_loan_schedule(&self, schedule: FlashLoanFeeSchedule) {
+        self.flash_loans.write().await.insert(normalize(&schedule.provider), schedule);
+    }
+
+    /// Settling across venues needs a transfer unless both legs are DEXs on the same chain.
+    pub async fn requires_transfer(&self, buy_venue: &str, sell_venue: &str) -> bool {
+        let buy = self.schedule(buy_venue).await;
+        let sell = self.schedule(sell_venue).await;
+        if normalize(&buy.venue) == normalize(&sell.venue) {
+            return false;
+        }
+        !(buy.kind == VenueKind::Dex && sell.kind == VenueKind::Dex && buy.chain == sell.chain)
+    }
+
+    /// Half-spread a venue's quotes carry around mid when no book is available.
+    pub async fn quoted_half_spread(&self, venue: &str) -> Decimal {
+        let schedule = self.schedule(venue).await;
+        match schedule.kind {
+            VenueKind::Dex => schedule.fee_rate(Liquidity::Taker),
+            VenueKind::Cex => schedule.fee_rate(Liquidity::Maker).max(Decimal::from_str("0.0005").unwrap()),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Opportunity evaluation
+    // ------------------------------------------------------------------------
+
+    /// Add trading and network costs for one executed leg.
+    pub async fn apply_leg(&self, breakdown: &mut ProfitBreakdown, leg: &TradeLeg) {
+        let schedule = self.schedule(&leg.venue).await;
+
+        let tier_rate = schedule.active_tier()
+            .map(|tier| match leg.liquidity {
+                Liquidity::Maker => tier.maker_fee,
+                Liquidity::Taker => tier.taker_fee,
+            })
+            .unwrap_or(Decimal::ZERO);
+        breakdown.add_cost(
+            CostKind::TradingFee,
+            &leg.venue,
+            leg.notional_usd * tier_rate,
+            format!("{:?} fee {}%", leg.liquidity, (tier_rate * Decimal::from(100)).round_dp(4)),
+        );
+
+        if let Some(pool_fee) = schedule.pool_fee {
+            breakdown.add_cost(
+                CostKind::PoolFee,
+                &leg.venue,
+                leg.notional_usd * pool_fee,
+                format!("Pool fee {}%", (pool_fee * Decimal::from(100)).round_dp(4)),
+            );
+        }
+
+        if let Some(chain) = schedule.chain.as_deref() {
+            let gas = self.gas_cost_usd(chain, GasOperation::Swap).await;
+            breakdown.add_cost(CostKind::NetworkGas, &leg.venue, gas, format!("{} swap gas", chain));
+        }
+    }
+
+    /// Expected price impact of one fill at the venue's slippage rate.
+    pub async fn apply_slippage(&self, breakdown: &mut ProfitBreakdown, leg: &TradeLeg) {
+        let rate = self.schedule(&leg.venue).await.expected_slippage;
+        breakdown.add_cost(
+            CostKind::Slippage,
+            &leg.venue,
+            leg.notional_usd * rate,
+            format!("Expected slippage {}%", (rate * Decimal::from(100)).round_dp(4)),
+        );
+    }
+
+    /// Buy on one venue and sell on another, optionally moving the base asset between them.
+    pub async fn evaluate_two_leg(
+        &self,
+        buy: PricedLeg<'_>,
+        sell: PricedLeg<'_>,
+        amount: Decimal,
+        base_asset: &str,
+        requires_transfer: bool,
+    ) -> ProfitBreakdown {
+        let buy_notional = buy.price * amount;
+        let sell_notional = sell.price * amount;
+        let mut breakdown = ProfitBreakdown::new(buy_notional, sell_notional - buy_notional);
+
+        self.apply_leg(&mut breakdown, &TradeLeg {
+            venue: buy.venue.to_string(),
+            notional_usd: buy_notional,
+            liquidity: Liquidity::Taker,
+        }).await;
+        self.apply_leg(&mut breakdown, &TradeLeg {
+            venue: sell.venue.to_string(),
+            notional_usd: sell_notional,
+            liquidity: Liquidity::Taker,
+        }).await;
+
+        if requires_transfer {
+            self.apply_transfer(&mut breakdown, buy.venue, sell.venue, base_asset, buy.price).await;
+        }
+
+        breakdown
+    }
+
+    /// Cost of moving `asset` from one venue to another (CEX withdrawal or on-chain transfer).
+    pub async fn apply_transfer(
+        &self,
+        breakdown: &mut ProfitBreakdown,
+        from_venue: &str,
+        to_venue: &str,
+        asset: &str,
+        asset_price_usd: Decimal,
+    ) {
+        let from = self.schedule(from_venue).await;
+        match from.kind {
+            VenueKind::Cex => {
+                let fee = self.withdrawal_fee_usd(from_venue, asset, asset_price_usd).await;
+                breakdown.add_cost(
+                    CostKind::Withdrawal,
+                    from_venue,
+                    fee,
+                    format!("Withdraw {} to {}", asset, to_venue),
+                );
+            }
+            VenueKind::Dex => {
+                if let Some(chain) = from.chain.as_deref() {
+                    let gas = self.gas_cost_usd(chain, GasOperation::Transfer).await;
+                    breakdown.add_cost(
+                        CostKind::NetworkGas,
+                        from_venue,
+                        gas,
+                        format!("Transfer {} to {}", asset, to_venue),
+                    );
+                }
+            }
+        }
+    }
+
+    pub async fn apply_bridge(
+        &self,
+        breakdown: &mut ProfitBreakdown,
+        bridge: &str,
+        fallback_percentage: Decimal,
+        notional_usd: Decimal,
+        source_chain: &str,
+        destination_chain: &str,
+    ) {
+        let fee = self.bridge_fee_usd(bridge, notional_usd).await
+            .unwrap_or(notional_usd * fallback_percentage);
+        breakdown.add_cost(CostKind::Bridge, bridge, fee, format!("{} -> {}", source_chain, destination_chain));
+
+        let deposit_gas = self.gas_cost_usd(source_chain, GasOperation::BridgeDeposit).await;
+        breakdown.add_cost(CostKind::NetworkGas, source_chain, deposit_gas, format!("{} bridge deposit gas", bridge));
+
+        let claim_gas = self.gas_cost_usd(destination_chain, GasOperation::BridgeClaim).await;
+        breakdown.add_cost(CostKind::NetworkGas, destination_chain, claim_gas, format!("{} bridge claim gas", bridge));
+    }
+
+    /// Flash loan fee plus the gas of the wrapping transaction.
+    pub async fn apply_flash_loan(
+        &self,
+        breakdown: &mut ProfitBreakdown,
+        provider: &str,
+        loan_notional_usd: Decimal,
+        chain: &str,
+    ) {
+        let fee_percentage = self.flash_loan_fee_rate(provider).await;
+        breakdown.add_cost(
+            CostKind::FlashLoanFee,
+            provider,
+            loan_notional_usd * fee_percentage,
+            format!("Flash loan fee {}%", (fee_percentage * Decimal::from(100)).round_dp(4)),
+        );
+        let gas = self.gas_cost_usd(chain, GasOperation::FlashLoan).await;
+        breakdown.add_cost(CostKind::NetworkGas, chain, gas, "Flash loan wrapper gas");
+    }
+
+    /// Itemize a multi-step route; each step's notional is `amount * price` in USD.
+    pub async fn evaluate_route(
+        &self,
+        steps: &[(String, Decimal)], // (venue, notional_usd)
+        notional_usd: Decimal,
+        gross_profit_usd: Decimal,
+    ) -> ProfitBreakdown {
+        let mut breakdown = ProfitBreakdown::new(notional_usd, gross_profit_usd);
+        for (venue, step_notional) in steps {
+            self.apply_leg(&mut breakdown, &TradeLeg {
+                venue: venue.clone(),
+                notional_usd: *step_notional,
+                liquidity: Liquidity::Taker,
+            }).await;
+        }
+        breakdown
+    }
+}
+
+impl Default for FeeModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert an f64 amount into a Decimal, treating NaN/inf as zero.
+pub fn dec(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+pub fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace([' ', '-'], "_")
+}
+
+fn tier(min_volume: &str, maker: &str, taker: &str) -> FeeTier {
+    FeeTier {
+        min_volume_30d_usd: Decimal::from_str(min_volume).unwrap(),
+        maker_fee: Decimal::from_str(maker).unwrap(),
+        taker_fee: Decimal::from_str(taker).unwrap(),
+    }
+}
+
+fn withdrawals(entries: &[(&str, &str)]) -> HashMap<String, Decimal> {
+    entries.iter()
+        .map(|(asset, fee)| (asset.to_string(), Decimal::from_str(fee).unwrap()))
+        .collect()
+}
+
+fn cex(venue: &str, tiers: Vec<FeeTier>, withdrawal_fees: HashMap<String, Decimal>) -> VenueFeeSchedule {
+    VenueFeeSchedule {
+        venue: venue.to_string(),
+        kind: VenueKind::Cex,
+        tiers,
+        volume_30d_usd: Decimal::ZERO,
+        pool_fee: None,
+        chain: None,
+        withdrawal_fees,
+        expected_slippage: Decimal::from_str("0.0005").unwrap(),
+    }
+}
+
+fn dex(venue: &str, chain: &str, pool_fee: &str) -> VenueFeeSchedule {
+    VenueFeeSchedule {
+        venue: venue.to_string(),
+        kind: VenueKind::Dex,
+        tiers: Vec::new(),
+        volume_30d_usd: Decimal::ZERO,
+        pool_fee: Some(Decimal::from_str(pool_fee).unwrap()),
+        chain: Some(chain.to_string()),
+        withdrawal_fees: HashMap::new(),
+        // Pool price impact is larger than book slippage at comparable sizes
+        expected_slippage: Decimal::from_str("0.001").unwrap(),
+    }
+}
+
+// Published schedules at the time of writing; override at runtime with `set_schedule`.
+fn default_venue_schedules() -> Vec<VenueFeeSchedule> {
+    vec![
+        cex("Binance", vec![
+            tier("0", "0.001", "0.001"),
+            tier("1000000", "0.0009", "0.001"),
+            tier("5000000", "0.0008", "0.001"),
+            tier("20000000", "0.0004", "0.0006"),
+        ], withdrawals(&[("BTC", "0.0002"), ("ETH", "0.0012"), ("SOL", "0.008"), ("USDT", "1"), ("USDC", "1"), ("BNB", "0.0005")])),
+        cex("Coinbase", vec![
+            tier("0", "0.004", "0.006"),
+            tier("10000", "0.0025", "0.004"),
+            tier("50000", "0.0015", "0.0025"),
+            tier("100000", "0.001", "0.002"),
+            tier("1000000", "0.0006", "0.0016"),
+        ], withdrawals(&[("BTC", "0.0001"), ("ETH", "0.001"), ("SOL", "0.000005"), ("USDC", "0")])),
+        cex("Kraken", vec![
+            tier("0", "0.0025", "0.004"),
+            tier("10000", "0.002", "0.0035"),
+            tier("50000", "0.0014", "0.0024"),
+            tier("100000", "0.0012", "0.0022"),
+        ], withdrawals(&[("BTC", "0.00015"), ("ETH", "0.0025"), ("SOL", "0.01"), ("USDT", "2.5"), ("USDC", "2.5")])),
+        cex("OKX", vec![
+            tier("0", "0.0008", "0.001"),
+            tier("5000000", "0.00045", "0.0005"),
+        ], withdrawals(&[("BTC", "0.0001"), ("ETH", "0.0008"), ("SOL", "0.008"), ("USDT", "1")])),
+        cex("Bybit", vec![tier("0", "0.001", "0.001")], withdrawals(&[("BTC", "0.0002"), ("ETH", "0.0012"), ("SOL", "0.01"), ("USDT", "1")])),
+        cex("KuCoin", vec![tier("0", "0.001", "0.001")], withdrawals(&[("BTC", "0.0005"), ("ETH", "0.002"), ("USDT", "1")])),
+        cex("Gate.io", vec![tier("0", "0.002", "0.002")], withdrawals(&[("BTC", "0.001"), ("ETH", "0.003"), ("USDT", "1")])),
+        cex("Huobi", vec![tier("0", "0.002", "0.002")], withdrawals(&[("BTC", "0.0004"), ("ETH", "0.002"), ("USDT", "1")])),
+        cex("Bitfinex", vec![tier("0", "0.001", "0.002")], withdrawals(&[("BTC", "0.0004"), ("ETH", "0.0025")])),
+        cex("Gemini", vec![tier("0", "0.002", "0.004")], withdrawals(&[("BTC", "0"), ("ETH", "0")])),
+        // Jupiter routes through underlying pools; use a blended pool fee
+        dex("Jupiter", "solana", "0.0025"),
+        dex("Raydium", "solana", "0.0025"),
+        dex("Orca", "solana", "0.003"),
+        dex("Uniswap_V3", "ethereum", "0.003"),
+        dex("Uniswap", "ethereum", "0.003"),
+        dex("SushiSwap", "ethereum", "0.003"),
+        dex("Curve", "ethereum", "0.0004"),
+        dex("Balancer", "ethereum", "0.003"),
+        dex("PancakeSwap", "bsc", "0.0025"),
+        dex("QuickSwap", "polygon", "0.003"),
+        dex("TraderJoe", "avalanche", "0.003"),
+    ]
+}
+
+fn gas_model(chain: &str, native: &str, price: &str, base_fee: &str, unit_price: &str, units: &[(GasOperation, u64)]) -> ChainGasModel {
+    ChainGasModel {
+        chain: chain.to_string(),
+        native_token: native.to_string(),
+        native_price_usd: Decimal::from_str(price).unwrap(),
+        base_fee_native: Decimal::from_str(base_fee).unwrap(),
+        unit_price_native: Decimal::from_str(unit_price).unwrap(),
+        operation_units: units.iter().copied().collect(),
+    }
+}
+
+fn default_chain_gas_models() -> Vec<ChainGasModel> {
+    let evm_units = [
+        (GasOperation::Swap, 150_000),
+        (GasOperation::Transfer, 65_000),
+        (GasOperation::Approve, 46_000),
+        (GasOperation::FlashLoan, 300_000),
+        (GasOperation::BridgeDeposit, 150_000),
+        (GasOperation::BridgeClaim, 50_000),
+    ];
+
+    vec![
+        // Solana: 5000 lamports per signature plus priority fee per compute unit
+        // (10,000 micro-lamports/CU = 1e-11 SOL/CU)
+        gas_model("solana", "SOL", "171.12", "0.000005", "0.00000000001", &[
+            (GasOperation::Swap, 300_000),
+            (GasOperation::Transfer, 50_000),
+            (GasOperation::Approve, 10_000),
+            (GasOperation::FlashLoan, 600_000),
+            (GasOperation::BridgeDeposit, 400_000),
+            (GasOperation::BridgeClaim, 400_000),
+        ]),
+        gas_model("ethereum", "ETH", "3400", "0", "0.00000003", &evm_units),   // 30 gwei
+        gas_model("bsc", "BNB", "600", "0", "0.000000005", &evm_units),        // 5 gwei
+        gas_model("polygon", "MATIC", "0.8", "0", "0.00000003", &evm_units),   // 30 gwei
+        gas_model("avalanche", "AVAX", "35", "0", "0.000000025", &evm_units),  // 25 nAVAX
+    ]
+}
+
+fn default_bridge_schedules() -> Vec<BridgeFeeSchedule> {
+    let bridge = |name: &str, pct: &str, fixed: &str| BridgeFeeSchedule {
+        bridge: name.to_string(),
+        fee_percentage: Decimal::from_str(pct).unwrap(),
+        fixed_fee_usd: Decimal::from_str(fixed).unwrap(),
+    };
+
+    vec![
+        bridge("wormhole", "0.001", "0.50"),
+        bridge("portal", "0.001", "0.50"),
+        bridge("allbridge", "0.0015", "1.00"),
+        bridge("synapse", "0.0005", "2.00"),
+    ]
+}
+
+fn default_flash_loan_schedules() -> Vec<FlashLoanFeeSchedule> {
+    let provider = |name: &str, pct: &str| FlashLoanFeeSchedule {
+        provider: name.to_string(),
+        fee_percentage: Decimal::from_str(pct).unwrap(),
+    };
+
+    vec![
+        provider("Aave V3", "0.0009"),
+        provider("dYdX", "0.0005"),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn active_tier_follows_30_day_volume() {
+        let model = FeeModel::new();
+        assert_eq!(model.fee_rate("Binance", Liquidity::Maker).await, d("0.001"));
+
+        model.set_volume_30d("Binance", d("6000000")).await;
+        assert_eq!(model.fee_rate("Binance", Liquidity::Maker).await, d("0.0008"));
+        assert_eq!(model.fee_rate("Binance", Liquidity::Taker).await, d("0.001"));
+
+        model.set_volume_30d("Binance", d("20000000")).await;
+        assert_eq!(model.fee_rate("Binance", Liquidity::Maker).await, d("0.0004"));
+    }
+
+    #[test]
+    fn volume_below_every_tier_uses_the_first() {
+        let schedule = cex("Test", vec![tier("1000", "0.002", "0.003"), tier("5000", "0.001", "0.002")], HashMap::new());
+        assert_eq!(schedule.active_tier().unwrap().maker_fee, d("0.002"));
+        assert_eq!(schedule.fee_rate(Liquidity::Taker), d("0.003"));
+    }
+
+    #[test]
+    fn dex_rates_include_the_pool_fee() {
+        let schedule = dex("Pool", "solana", "0.0025");
+        assert_eq!(schedule.fee_rate(Liquidity::Taker), d("0.0025"));
+    }
+
+    #[tokio::test]
+    async fn two_leg_net_profit_subtracts_each_venue_fee() {
+        let model = FeeModel::new();
+        let breakdown = model.evaluate_two_leg(
+            PricedLeg { venue: "Binance", price: d("100") },
+            PricedLeg { venue: "Coinbase", price: d("102") },
+            d("10"),
+            "BTC",
+            false,
+        ).await;
+
+        // 1000 * 0.1% on Binance, 1020 * 0.6% on Coinbase
+        assert_eq!(breakdown.notional_usd, d("1000"));
+        assert_eq!(breakdown.gross_profit_usd, d("20"));
+        assert_eq!(breakdown.cost_of(CostKind::TradingFee), d("7.12"));
+        assert_eq!(breakdown.net_profit_usd, d("12.88"));
+        assert_eq!(breakdown.net_profit_percentage, d("1.288"));
+        assert!(breakdown.is_profitable());
+    }
+
+    #[tokio::test]
+    async fn transfers_charge_withdrawals_between_venues_only() {
+        let model = FeeModel::new();
+        assert!(model.requires_transfer("Binance", "Coinbase").await);
+        assert!(!model.requires_transfer("Jupiter", "Raydium").await);
+        assert!(!model.requires_transfer("Binance", "binance").await);
+
+        let breakdown = model.evaluate_two_leg(
+            PricedLeg { venue: "Binance", price: d("150") },
+            PricedLeg { venue: "Coinbase", price: d("151") },
+            d("1"),
+            "SOL",
+            true,
+        ).await;
+        assert_eq!(breakdown.cost_of(CostKind::Withdrawal), d("1.2")); // 0.008 SOL at $150
+    }
+
+    #[tokio::test]
+    async fn unknown_flash_loan_providers_pay_the_highest_schedule() {
+        let model = FeeModel::new();
+        assert_eq!(model.flash_loan_fee_rate("dYdX").await, d("0.0005"));
+        assert_eq!(model.flash_loan_fee_rate("unknown").await, d("0.0009"));
+
+        let mut breakdown = ProfitBreakdown::new(d("10000"), d("50"));
+        model.apply_flash_loan(&mut breakdown, "dYdX", d("10000"), "ethereum").await;
+        assert_eq!(breakdown.cost_of(CostKind::FlashLoanFee), d("5"));
+    }
+
+    #[tokio::test]
+    async fn slippage_is_priced_from_the_venue_schedule() {
+        let model = FeeModel::new();
+        let mut breakdown = ProfitBreakdown::new(d("1000"), d("10"));
+        model.apply_slippage(&mut breakdown, &TradeLeg {
+            venue: "Binance".to_string(),
+            notional_usd: d("1000"),
+            liquidity: Liquidity::Taker,
+        }).await;
+        model.apply_slippage(&mut breakdown, &TradeLeg {
+            venue: "Raydium".to_string(),
+            notional_usd: d("1000"),
+            liquidity: Liquidity::Taker,
+        }).await;
+        assert_eq!(breakdown.cost_of(CostKind::Slippage), d("1.5"));
+        assert_eq!(breakdown.net_profit_usd, d("8.5"));
+    }
+}
@@ -9,6 +9,9 @@ use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use log::{info, warn, error, debug};
 
+use crate::fee_model::{CostKind, FeeModel, Liquidity, ProfitBreakdown, TradeLeg};
+use crate::risk_gate::{OrderOrigin, PreTradeRequest, PreTradeRiskGate, RiskDecision};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlashLoanProvider {
     pub name: String,
@@ -84,6 +87,9 @@ pub struct FlashLoanSimulationResult {
     pub timing_analysis: TimingAnalysis,
     pub alternative_strategies: Vec<AlternativeStrategy>,
     pub simulation_timestamp: u64,
+    pub provider: Option<String>,
+    pub profit_breakdown: ProfitBreakdown,
+    pub risk_gate: Option<RiskDecision>, // Pre-trade gate verdict; a rejected plan is not executable
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,6 +135,9 @@ pub struct AlternativeStrategy {
     pub description: String,
 }
 
+/// Flash loan providers configured here (Aave, dYdX) settle on Ethereum mainnet
+const FLASH_LOAN_CHAIN: &str = "ethereum";
+
 pub struct FlashLoanSimulator {
     // Providers and strategies
     providers: Arc<RwLock<HashMap<String, FlashLoanProvider>>>,
@@ -142,6 +151,12 @@ pub struct FlashLoanSimulator {
     
     // Statistics
     stats: Arc<Mutex<SimulatorStats>>,
+    
+    // Shared venue/gas fee model
+    fee_model: Arc<FeeModel>,
+    
+    // Pre-trade risk gate shared with the other order paths
+    risk_gate: Arc<PreTradeRiskGate>,
 }
 
 #[derive(Debug, Default)]
@@ -155,13 +170,15 @@ pub struct SimulatorStats {
 }
 
 impl FlashLoanSimulator {
-    pub fn new() -> Self {
+    pub fn new(fee_model: Arc<FeeModel>, risk_gate: Arc<PreTradeRiskGate>) -> Self {
         Self {
             providers: Arc::new(RwLock::new(HashMap::new())),
             strategies: Arc::new(RwLock::new(HashMap::new())),
             simulation_results: Arc::new(RwLock::new(Vec::new())),
             token_prices: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(Mutex::new(SimulatorStats::default())),
+            fee_model,
+            risk_gate,
         }
     }
 
@@ -181,7 +198,7 @@ impl FlashLoanSimulator {
             FlashLoanProvider {
                 name: "Aave V3".to_string(),
                 protocol: "aave-v3".to_string(),
-                fee_percentage: Decimal::from_str("0.0009").unwrap(), // 0.09%
+                fee_percentage: self.fee_model.flash_loan_fee_rate("Aave V3").await,
                 max_amount: Decimal::from(10000000),
                 available_tokens: vec!["USDC".to_string(), "ETH".to_string(), "DAI".to_string()],
                 min_amount: Decimal::from(1000),
@@ -192,7 +209,7 @@ impl FlashLoanSimulator {
             FlashLoanProvider {
                 name: "dYdX".to_string(),
                 protocol: "dydx".to_string(),
-                fee_percentage: Decimal::from_str("0.0005").unwrap(), // 0.05%
+                fee_percentage: self.fee_model.flash_loan_fee_rate("dYdX").await,
                 max_amount: Decimal::from(5000000),
                 available_tokens: vec!["USDC".to_string(), "ETH".to_string()],
                 min_amount: Decimal::from(500),
@@ -273,30 +290,78 @@ impl FlashLoanSimulator {
             .map(|step| step.output_amount)
             .unwrap_or(Decimal::ZERO);
         
-        let loan_fee = request.amount * Decimal::from_str("0.0009").unwrap(); // 0.09% fee
-        let total_fees = loan_fee + execution_steps.iter()
-            .map(|step| step.gas_used)
-            .sum::<Decimal>() * Decimal::from(30); // Gas price
+        // Price everything in USD through the fee model, then report in loan token units
+        let token_price = self.token_prices.read().await
+            .get(&request.token)
+            .copied()
+            .unwrap_or(Decimal::ONE);
+        let provider = self.select_provider(&request.token, request.amount).await;
+        
+        let gross_profit = total_output - request.amount;
+        let mut breakdown = ProfitBreakdown::new(request.amount * token_price, gross_profit * token_price);
+        
+        // Unknown providers are charged the most expensive schedule the fee model knows
+        let provider_name = match &provider {
+            Some(p) => p.name.clone(),
+            None => {
+                warn!("⚠️ No flash loan provider offers {} {}", request.amount, request.token);
+                "unknown".to_string()
+            }
+        };
+        self.fee_model.apply_flash_loan(
+            &mut breakdown,
+            &provider_name,
+            request.amount * token_price,
+            FLASH_LOAN_CHAIN,
+        ).await;
+        
+        for (step, executed) in request.strategy.steps.iter().zip(execution_steps.iter()) {
+            self.fee_model.apply_leg(&mut breakdown, &TradeLeg {
+                venue: step.protocol.clone(),
+                notional_usd: executed.input_amount * token_price,
+                liquidity: Liquidity::Taker,
+            }).await;
+        }
         
-        let profit_loss = total_output - request.amount - loan_fee;
-        let net_profit = profit_loss - total_fees;
+        let loan_fee = breakdown.cost_of(CostKind::FlashLoanFee) / token_price;
+        let total_fees = breakdown.total_costs_usd / token_price;
+        let gas_cost = breakdown.cost_of(CostKind::NetworkGas) / token_price;
+        let profit_loss = gross_profit - loan_fee;
+        let net_profit = breakdown.net_profit_usd / token_price;
+        
+        // Checked like any other order and held in flight until the result is stored
+        let notional_usd = request.amount * token_price;
+        let profit_pct = if notional_usd > Decimal::ZERO {
+            (breakdown.net_profit_usd / notional_usd * Decimal::from(100)).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        let overall_risk_score = 0.3;
+        let mut risk_factors = vec!["Market volatility".to_string()];
+        let gate_request = PreTradeRequest::new(&request.id, OrderOrigin::FlashLoan, &request.token, notional_usd.to_f64())
+            .with_strategy(request.strategy.name.clone())
+            .with_venues(vec![provider_name.clone()])
+            .with_expected_profit(profit_pct, overall_risk_score);
+        let decision = self.risk_gate.check(gate_request).await.unwrap_or_else(|rejected| rejected);
+        let reserved = decision.approved;
+        risk_factors.extend(decision.rejections.iter().map(|r| r.reason.clone()));
         
         let result = FlashLoanSimulationResult {
             request_id: request.id.clone(),
-            success: net_profit > Decimal::ZERO,
+            success: breakdown.is_profitable() && decision.approved,
             profit_loss,
             net_profit,
             total_fees,
-            gas_cost: total_fees,
+            gas_cost,
             execution_path: execution_steps,
             risk_assessment: RiskAssessment {
-                overall_risk_score: 0.3,
+                overall_risk_score,
                 liquidity_risk: 0.2,
                 price_impact_risk: 0.25,
                 execution_risk: 0.3,
                 smart_contract_risk: 0.1,
                 market_risk: 0.15,
-                risk_factors: vec!["Market volatility".to_string()],
+                risk_factors,
                 mitigation_suggestions: vec!["Use private mempool".to_string()],
             },
             timing_analysis: TimingAnalysis {
@@ -308,15 +373,30 @@ impl FlashLoanSimulator {
             },
             alternative_strategies: vec![],
             simulation_timestamp: chrono::Utc::now().timestamp() as u64,
+            provider: provider.map(|p| p.name),
+            profit_breakdown: breakdown,
+            risk_gate: Some(decision),
         };
 
         // Store result
-        let mut results = self.simulation_results.write().await;
-        results.push(result.clone());
+        self.simulation_results.write().await.push(result.clone());
+        if reserved {
+            self.risk_gate.release(&request.id).await;
+        }
         
         Ok(result)
     }
 
+    /// Cheapest provider that lends `token` in the requested size
+    async fn select_provider(&self, token: &str, amount: Decimal) -> Option<FlashLoanProvider> {
+        let providers = self.providers.read().await;
+        providers.values()
+            .filter(|p| p.available_tokens.iter().any(|t| t == token))
+            .filter(|p| amount >= p.min_amount && amount <= p.max_amount)
+            .min_by(|a, b| a.fee_percentage.cmp(&b.fee_percentage))
+            .cloned()
+    }
+
     async fn simulate_strategy_execution(&self, request: &FlashLoanSimulationRequest) -> Result<Vec<ExecutionStep>> {
         let mut execution_steps = Vec::new();
         let mut current_amount = request.amount;
@@ -380,15 +460,11 @@ impl Clone for FlashLoanSimulator {
             simulation_results: self.simulation_results.clone(),
             token_prices: self.token_prices.clone(),
             stats: self.stats.clone(),
+            fee_model: self.fee_model.clone(),
+            risk_gate: self.risk_gate.clone(),
         }
     }
 }
 
-impl Default for FlashLoanSimulator {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 use rust_decimal::prelude::{FromStr, ToPrimitive, FromPrimitive};
 use chrono;
\ No newline at end of file
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
 use chrono::{DateTime, Utc};
+use log::warn;
+use rust_decimal::prelude::ToPrimitive;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OHLCV {
@@ -47,7 +49,7 @@ pub struct TimeSeriesData {
     pub max_candles: usize,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum TimeFrame {
     M1,  // 1 minute
     M5,  // 5 minutes
@@ -196,6 +198,69 @@ impl HistoricalDataStore {
         let snapshots = self.snapshots.read().await;
         snapshots.get(symbol)?.back().cloned()
     }
+
+    pub async fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.ohlcv_data.read().await.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Log returns between consecutive closes, as (candle timestamp, return); gaps in the
+    /// series are skipped so every return spans exactly one period
+    pub async fn get_log_returns(&self, symbol: &str, timeframe: TimeFrame, limit: usize) -> Vec<(i64, f64)> {
+        let candles = self.get_candles(symbol, timeframe, limit + 1).await;
+        candles.windows(2)
+            .filter(|pair| pair[1].timestamp - pair[0].timestamp == timeframe.to_seconds())
+            .filter(|pair| pair[0].close > 0.0 && pair[1].close > 0.0)
+            .map(|pair| (pair[1].timestamp, (pair[1].close / pair[0].close).ln()))
+            .collect()
+    }
+
+    /// Returns for several symbols on the timestamps they all share, oldest first
+    pub async fn get_aligned_returns(&self, symbols: &[String], timeframe: TimeFrame, limit: usize) -> (Vec<i64>, Vec<Vec<f64>>) {
+        let mut series = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let returns: HashMap<i64, f64> = self.get_log_returns(symbol, timeframe, limit).await.into_iter().collect();
+            series.push(returns);
+        }
+        let Some(first) = series.first() else {
+            return (Vec::new(), Vec::new());
+        };
+
+        let mut timestamps: Vec<i64> = first.keys()
+            .filter(|ts| series.iter().all(|s| s.contains_key(ts)))
+            .copied()
+            .collect();
+        timestamps.sort_unstable();
+        let aligned = series.iter()
+            .map(|s| timestamps.iter().map(|ts| s[ts]).collect())
+            .collect();
+        (timestamps, aligned)
+    }
+
+    /// Records every broadcast quote so candles (and return series) build up from live prices
+    pub async fn start(&self, mut prices: broadcast::Receiver<crate::PriceInfo>) {
+        loop {
+            match prices.recv().await {
+                Ok(quote) => {
+                    let Some(price) = quote.price.to_f64().filter(|p| *p > 0.0) else {
+                        continue;
+                    };
+                    self.add_trade(&quote.pair, TradeTick {
+                        price,
+                        amount: 0.0, // Quotes, not prints
+                        side: TradeSide::Buy,
+                        timestamp: quote.timestamp as i64,
+                        exchange: quote.exchange.clone(),
+                    }).await;
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ Historical data store lagged, skipped {} price updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
 }
 
 // Backtesting Engine
@@ -0,0 +1,469 @@
+// Inventory Manager - Pre-funded balances per venue for transfer-free CEX arbitrage
+// Sizes opportunities to current inventory and plans rebalancing transfers when venues skew
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromStr;
+use anyhow::{Result, anyhow};
+use log::{info, warn, debug};
+
+use crate::fee_model::{FeeModel, PricedLeg, ProfitBreakdown};
+use crate::universal_price_aggregator::LiveArbitrageOpportunity;
+
+// ============================================================================
+// INVENTORY DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenueBalance {
+    pub venue: String,
+    pub asset: String,
+    pub available: Decimal,
+    pub reserved: Decimal,
+    pub in_transit: Decimal, // Inbound transfers not yet credited
+    pub last_updated: u64,
+}
+
+/// A withdrawal route for an asset between venues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferNetwork {
+    pub network: String,
+    pub withdrawal_fee: Decimal, // In asset units
+    pub transfer_time_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceConfig {
+    /// Relative deviation from target share before a venue is rebalanced
+    pub skew_threshold: Decimal,
+    pub min_transfer_usd: Decimal,
+    /// Skip transfers whose fee exceeds this fraction of the amount moved
+    pub max_fee_ratio: Decimal,
+    /// Target share of each asset per venue; venues not listed split the remainder evenly
+    pub target_weights: HashMap<String, Decimal>,
+    pub networks: HashMap<String, Vec<TransferNetwork>>,
+}
+
+impl Default for RebalanceConfig {
+    fn default() -> Self {
+        let mut networks = HashMap::new();
+        networks.insert("USDT".to_string(), vec![
+            network("tron", "1", 120),
+            network("solana", "1", 60),
+            network("ethereum", "5", 300),
+        ]);
+        networks.insert("USDC".to_string(), vec![
+            network("solana", "1", 60),
+            network("polygon", "0.5", 300),
+            network("ethereum", "5", 300),
+        ]);
+        networks.insert("SOL".to_string(), vec![network("solana", "0.008", 60)]);
+        networks.insert("ETH".to_string(), vec![
+            network("arbitrum", "0.0001", 600),
+            network("ethereum", "0.0012", 300),
+        ]);
+        networks.insert("BTC".to_string(), vec![network("bitcoin", "0.0002", 3600)]);
+
+        Self {
+            skew_threshold: Decimal::from_str("0.3").unwrap(), // 30% off target
+            min_transfer_usd: Decimal::from(500),
+            max_fee_ratio: Decimal::from_str("0.005").unwrap(), // 0.5%
+            target_weights: HashMap::new(),
+            networks,
+        }
+    }
+}
+
+/// Cross-venue opportunity sized to what can be bought and sold from existing balances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventoryOpportunity {
+    pub opportunity: LiveArbitrageOpportunity,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub executable_amount: Decimal,
+    pub limiting_balance: String,
+    pub profit_breakdown: ProfitBreakdown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebalanceTransfer {
+    pub asset: String,
+    pub from_venue: String,
+    pub to_venue: String,
+    pub amount: Decimal,
+    pub network: String,
+    pub withdrawal_fee: Decimal,
+    pub fee_usd: Decimal,
+    pub estimated_time_secs: u64,
+}
+
+// ============================================================================
+// INVENTORY MANAGER
+// ============================================================================
+
+pub struct InventoryManager {
+    balances: Arc<RwLock<HashMap<(String, String), VenueBalance>>>, // (venue, asset)
+    asset_prices: Arc<RwLock<HashMap<String, Decimal>>>,
+    config: Arc<RwLock<RebalanceConfig>>,
+    fee_model: Arc<FeeModel>,
+}
+
+impl InventoryManager {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
+        Self {
+            balances: Arc::new(RwLock::new(HashMap::new())),
+            asset_prices: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(RebalanceConfig::default())),
+            fee_model,
+        }
+    }
+
+    pub async fn set_config(&self, config: RebalanceConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub async fn get_config(&self) -> RebalanceConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_balance(&self, venue: &str, asset: &str, available: Decimal) {
+        let mut balances = self.balances.write().await;
+        let balance = balances.entry((venue.to_string(), asset.to_uppercase()))
+            .or_insert_with(|| empty_balance(venue, asset));
+        balance.available = available;
+        balance.last_updated = chrono::Utc::now().timestamp() as u64;
+    }
+
+    pub async fn get_balance(&self, venue: &str, asset: &str) -> Decimal {
+        self.balances.read().await
+            .get(&(venue.to_string(), asset.to_uppercase()))
+            .map(|b| b.available)
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub async fn get_balances(&self) -> Vec<VenueBalance> {
+        self.balances.read().await.values().cloned().collect()
+    }
+
+    pub async fn update_price(&self, asset: &str, price_usd: Decimal) {
+        self.asset_prices.write().await.insert(asset.to_uppercase(), price_usd);
+    }
+
+    async fn price_of(&self, asset: &str) -> Option<Decimal> {
+        let asset = asset.to_uppercase();
+        if is_stablecoin(&asset) {
+            return Some(Decimal::ONE);
+        }
+        self.asset_prices.read().await.get(&asset).copied()
+    }
+
+    // ------------------------------------------------------------------------
+    // Detection
+    // ------------------------------------------------------------------------
+
+    /// Keep only opportunities that can be filled from balances already on both venues.
+    pub async fn executable_opportunities(
+        &self,
+        opportunities: &[LiveArbitrageOpportunity],
+    ) -> Vec<InventoryOpportunity> {
+        let mut executable = Vec::new();
+
+        for opp in opportunities {
+            let (base, quote) = match opp.token_pair.split_once('/') {
+                Some((base, quote)) => (base.to_uppercase(), quote.to_uppercase()),
+                None => continue,
+            };
+            let buy_venue = &opp.buy_exchange.exchange;
+            let sell_venue = &opp.sell_exchange.exchange;
+            let buy_price = opp.buy_exchange.ask;
+            let sell_price = opp.sell_exchange.bid;
+            if buy_price <= Decimal::ZERO {
+                continue;
+            }
+
+            // Buy base with quote held on the buy venue, sell base held on the sell venue
+            let quote_on_buy = self.get_balance(buy_venue, &quote).await;
+            let base_on_sell = self.get_balance(sell_venue, &base).await;
+            let affordable = quote_on_buy / buy_price;
+            let (amount, limiting) = if affordable <= base_on_sell {
+                (affordable, format!("{} {}", buy_venue, quote))
+            } else {
+                (base_on_sell, format!("{} {}", sell_venue, base))
+            };
+            if amount <= Decimal::ZERO || amount < opp.buy_exchange.min_order_size {
+                continue;
+            }
+
+            self.update_price(&base, buy_price).await;
+            let breakdown = self.fee_model.evaluate_two_leg(
+                PricedLeg { venue: buy_venue, price: buy_price },
+                PricedLeg { venue: sell_venue, price: sell_price },
+                amount,
+                &base,
+                false, // Inventory trades settle in place
+            ).await;
+            if !breakdown.is_profitable() {
+                continue;
+            }
+
+            executable.push(InventoryOpportunity {
+                opportunity: opp.clone(),
+                base_asset: base,
+                quote_asset: quote,
+                executable_amount: amount,
+                limiting_balance: limiting,
+                profit_breakdown: breakdown,
+            });
+        }
+
+        executable.sort_by(|a, b| b.profit_breakdown.net_profit_usd.cmp(&a.profit_breakdown.net_profit_usd));
+        debug!("📦 {} of {} opportunities executable from inventory", executable.len(), opportunities.len());
+        executable
+    }
+
+    /// Apply both fills of an inventory trade to venue balances.
+    pub async fn apply_fill(&self, opportunity: &InventoryOpportunity, amount: Decimal) -> Result<()> {
+        let buy_venue = &opportunity.opportunity.buy_exchange.exchange;
+        let sell_venue = &opportunity.opportunity.sell_exchange.exchange;
+        let cost = amount * opportunity.opportunity.buy_exchange.ask;
+        let proceeds = amount * opportunity.opportunity.sell_exchange.bid;
+
+        let mut balances = self.balances.write().await;
+        let quote_key = (buy_venue.clone(), opportunity.quote_asset.clone());
+        let base_key = (sell_venue.clone(), opportunity.base_asset.clone());
+
+        let quote_available = balances.get(&quote_key).map(|b| b.available).unwrap_or(Decimal::ZERO);
+        let base_available = balances.get(&base_key).map(|b| b.available).unwrap_or(Decimal::ZERO);
+        if quote_available < cost || base_available < amount {
+            return Err(anyhow!("Insufficient inventory for {} {}", amount, opportunity.base_asset));
+        }
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut adjust = |venue: &str, asset: &str, delta: Decimal| {
+            let balance = balances.entry((venue.to_string(), asset.to_string()))
+                .or_insert_with(|| empty_balance(venue, asset));
+            balance.available += delta;
+            balance.last_updated = now;
+        };
+        adjust(buy_venue, &opportunity.quote_asset, -cost);
+        adjust(buy_venue, &opportunity.base_asset, amount);
+        adjust(sell_venue, &opportunity.base_asset, -amount);
+        adjust(sell_venue, &opportunity.quote_asset, proceeds);
+
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Rebalancing
+    // ------------------------------------------------------------------------
+
+    /// Propose transfers that bring each asset back towards its target share per venue.
+    pub async fn plan_rebalance(&self) -> Vec<RebalanceTransfer> {
+        let config = self.config.read().await.clone();
+        let balances = self.balances.read().await.clone();
+
+        let mut by_asset: HashMap<String, Vec<VenueBalance>> = HashMap::new();
+        for balance in balances.into_values() {
+            by_asset.entry(balance.asset.clone()).or_default().push(balance);
+        }
+
+        let mut transfers = Vec::new();
+        for (asset, venues) in by_asset {
+            if venues.len() < 2 {
+                continue;
+            }
+            let price = match self.price_of(&asset).await {
+                Some(price) => price,
+                None => {
+                    debug!("No price for {}, skipping rebalance", asset);
+                    continue;
+                }
+            };
+
+            let total: Decimal = venues.iter().map(|b| b.available + b.in_transit).sum();
+            if total <= Decimal::ZERO {
+                continue;
+            }
+            let targets = target_amounts(&venues, total, &config.target_weights);
+
+            // Positive = surplus to send, negative = deficit to fill
+            let mut surpluses = Vec::new();
+            let mut deficits = Vec::new();
+            for balance in &venues {
+                let target = targets[&balance.venue];
+                let held = balance.available + balance.in_transit;
+                let deviation = held - target;
+                if target > Decimal::ZERO && (deviation.abs() / target) < config.skew_threshold {
+                    continue;
+                }
+                if deviation > Decimal::ZERO {
+                    // Never plan to send more than is actually available
+                    surpluses.push((balance.venue.clone(), deviation.min(balance.available)));
+                } else if deviation < Decimal::ZERO {
+                    deficits.push((balance.venue.clone(), -deviation));
+                }
+            }
+            surpluses.sort_by(|a, b| b.1.cmp(&a.1));
+            deficits.sort_by(|a, b| b.1.cmp(&a.1));
+
+            for (to_venue, mut needed) in deficits {
+                for (from_venue, surplus) in surpluses.iter_mut() {
+                    if needed <= Decimal::ZERO {
+                        break;
+                    }
+                    let amount = needed.min(*surplus);
+                    if amount * price < config.min_transfer_usd {
+                        continue;
+                    }
+                    let Some((network, fee)) = self.cheapest_network(from_venue, &asset, &config).await else {
+                        warn!("⚠️ No transfer network configured for {}", asset);
+                        break;
+                    };
+                    if fee > amount * config.max_fee_ratio {
+                        continue;
+                    }
+
+                    transfers.push(RebalanceTransfer {
+                        asset: asset.clone(),
+                        from_venue: from_venue.clone(),
+                        to_venue: to_venue.clone(),
+                        amount,
+                        network: network.network.clone(),
+                        withdrawal_fee: fee,
+                        fee_usd: fee * price,
+                        estimated_time_secs: network.transfer_time_secs,
+                    });
+                    *surplus -= amount;
+                    needed -= amount;
+                }
+            }
+        }
+
+        if !transfers.is_empty() {
+            info!("🔁 Rebalance plan: {} transfers", transfers.len());
+        }
+        transfers
+    }
+
+    /// Lowest-fee network for withdrawing `asset` from a venue; the venue's own schedule
+    /// overrides the fee of the asset's primary network.
+    async fn cheapest_network(
+        &self,
+        venue: &str,
+        asset: &str,
+        config: &RebalanceConfig,
+    ) -> Option<(TransferNetwork, Decimal)> {
+        let networks = config.networks.get(asset)?;
+        let venue_fee = self.fee_model.schedule(venue).await.withdrawal_fees.get(asset).copied();
+
+        networks.iter()
+            .enumerate()
+            .map(|(i, network)| {
+                let fee = match (i, venue_fee) {
+                    (0, Some(fee)) => fee,
+                    _ => network.withdrawal_fee,
+                };
+                (network.clone(), fee)
+            })
+            .min_by(|a, b| a.1.cmp(&b.1).then(a.0.transfer_time_secs.cmp(&b.0.transfer_time_secs)))
+    }
+
+    /// Debit the source and mark the amount as in transit to the destination.
+    pub async fn start_transfer(&self, transfer: &RebalanceTransfer) -> Result<()> {
+        let mut balances = self.balances.write().await;
+        let from_key = (transfer.from_venue.clone(), transfer.asset.clone());
+        let from = balances.get_mut(&from_key)
+            .ok_or_else(|| anyhow!("No {} balance on {}", transfer.asset, transfer.from_venue))?;
+        if from.available < transfer.amount {
+            return Err(anyhow!("Insufficient {} on {}", transfer.asset, transfer.from_venue));
+        }
+        from.available -= transfer.amount;
+
+        let to = balances.entry((transfer.to_venue.clone(), transfer.asset.clone()))
+            .or_insert_with(|| empty_balance(&transfer.to_venue, &transfer.asset));
+        to.in_transit += transfer.amount - transfer.withdrawal_fee;
+
+        info!("🚚 Transfer started: {} {} {} -> {} via {}",
+              transfer.amount, transfer.asset, transfer.from_venue, transfer.to_venue, transfer.network);
+        Ok(())
+    }
+
+    /// Credit an arrived transfer at its destination.
+    pub async fn complete_transfer(&self, transfer: &RebalanceTransfer) {
+        let mut balances = self.balances.write().await;
+        let to = balances.entry((transfer.to_venue.clone(), transfer.asset.clone()))
+            .or_insert_with(|| empty_balance(&transfer.to_venue, &transfer.asset));
+        let received = transfer.amount - transfer.withdrawal_fee;
+        to.in_transit = (to.in_transit - received).max(Decimal::ZERO);
+        to.available += received;
+        to.last_updated = chrono::Utc::now().timestamp() as u64;
+    }
+}
+
+impl Clone for InventoryManager {
+    fn clone(&self) -> Self {
+        Self {
+            balances: self.balances.clone(),
+            asset_prices: self.asset_prices.clone(),
+            config: self.config.clone(),
+            fee_model: self.fee_model.clone(),
+        }
+    }
+}
+
+impl Default for InventoryManager {
+    fn default() -> Self {
+        Self::new(Arc::new(FeeModel::new()))
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn network(name: &str, fee: &str, transfer_time_secs: u64) -> TransferNetwork {
+    TransferNetwork {
+        network: name.to_string(),
+        withdrawal_fee: Decimal::from_str(fee).unwrap(),
+        transfer_time_secs,
+    }
+}
+
+fn empty_balance(venue: &str, asset: &str) -> VenueBalance {
+    VenueBalance {
+        venue: venue.to_string(),
+        asset: asset.to_uppercase(),
+        available: Decimal::ZERO,
+        reserved: Decimal::ZERO,
+        in_transit: Decimal::ZERO,
+        last_updated: chrono::Utc::now().timestamp() as u64,
+    }
+}
+
+pub fn is_stablecoin(asset: &str) -> bool {
+    matches!(asset, "USDT" | "USDC" | "DAI" | "BUSD" | "USD")
+}
+
+fn target_amounts(
+    venues: &[VenueBalance],
+    total: Decimal,
+    weights: &HashMap<String, Decimal>,
+) -> HashMap<String, Decimal> {
+    let assigned: Decimal = venues.iter().filter_map(|b| weights.get(&b.venue)).sum();
+    let unweighted = venues.iter().filter(|b| !weights.contains_key(&b.venue)).count();
+    let remainder_share = if unweighted > 0 {
+        (Decimal::ONE - assigned).max(Decimal::ZERO) / Decimal::from(unweighted as u64)
+    } else {
+        Decimal::ZERO
+    };
+
+    venues.iter()
+        .map(|b| {
+            let share = weights.get(&b.venue).copied().unwrap_or(remainder_share);
+            (b.venue.clone(), total * share)
+        })
+        .collect()
+}
@@ -0,0 +1,756 @@
+// Encrypted Keystore - Password-protected storage for hot-wallet signing keys
+// Ethereum keystore v3 JSON (scrypt/pbkdf2/argon2id + AES) with unlocked keys held in locked, zeroized memory
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, anyhow, Context};
+use log::{info, warn, error, debug};
+use rand::RngCore;
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+use aes::cipher::{KeyIvInit, StreamCipher};
+use aes_gcm::{Aes256Gcm, Nonce, aead::{Aead, KeyInit}};
+use solana_sdk::signature::{Keypair, Signer, keypair_from_seed};
+
+use crate::auth::constant_time_eq;
+use crate::evm_tx::{self, EvmSigner};
+use crate::wallet_auth::ChainFamily;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+// ============================================================================
+// LOCKED SECRETS
+// ============================================================================
+
+/// Key bytes pinned in RAM where the OS allows it and wiped on drop; never printed
+pub struct LockedSecret {
+    _lock: Option<region::LockGuard>, // Declared first so the pages unlock before the buffer is freed
+    bytes: Box<[u8]>,
+}
+
+impl LockedSecret {
+    pub fn new(bytes: &[u8]) -> Self {
+        let bytes: Box<[u8]> = bytes.to_vec().into_boxed_slice();
+        let lock = match region::lock(bytes.as_ptr(), bytes.len()) {
+            Ok(guard) => Some(guard),
+            Err(e) => {
+                debug!("Key memory could not be locked (RLIMIT_MEMLOCK?): {}", e);
+                None
+            }
+        };
+        Self { _lock: lock, bytes }
+    }
+
+    pub fn expose(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl Drop for LockedSecret {
+    fn drop(&mut self) {
+        self.bytes.zeroize();
+    }
+}
+
+impl std::fmt::Debug for LockedSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LockedSecret([REDACTED; {}])", self.bytes.len())
+    }
+}
+
+// ============================================================================
+// KEYSTORE V3 FORMAT
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreFile {
+    pub version: u32,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(alias = "Crypto")]
+    pub crypto: CryptoSection,
+    // Extensions; files written by other wallets lack them and are treated as Ethereum keys
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<ChainFamily>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CryptoSection {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+/// Union of the scrypt (n, r, p), pbkdf2 (c, prf) and argon2id (m, t, p) parameter sets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub dklen: usize,
+    pub salt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prf: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub t: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystoreKdf {
+    Scrypt,
+    Argon2id,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeystoreCipher {
+    Aes128Ctr, // Standard v3 cipher, readable by geth and other wallets
+    Aes256Gcm, // Authenticated; written by default
+}
+
+impl KeystoreCipher {
+    fn label(&self) -> &'static str {
+        match self {
+            KeystoreCipher::Aes128Ctr => "aes-128-ctr",
+            KeystoreCipher::Aes256Gcm => "aes-256-gcm",
+        }
+    }
+
+    fn from_label(label: &str) -> Result<Self> {
+        match label {
+            "aes-128-ctr" => Ok(KeystoreCipher::Aes128Ctr),
+            "aes-256-gcm" => Ok(KeystoreCipher::Aes256Gcm),
+            other => Err(anyhow!("Unsupported keystore cipher '{}'", other)),
+        }
+    }
+
+    fn key_len(&self) -> usize {
+        match self {
+            KeystoreCipher::Aes128Ctr => 16,
+            KeystoreCipher::Aes256Gcm => 32,
+        }
+    }
+
+    fn iv_len(&self) -> usize {
+        match self {
+            KeystoreCipher::Aes128Ctr => 16,
+            KeystoreCipher::Aes256Gcm => 12,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreConfig {
+    pub directory: String,
+    pub kdf: KeystoreKdf,
+    pub cipher: KeystoreCipher,
+    pub scrypt_log_n: u8,
+    pub scrypt_r: u32,
+    pub scrypt_p: u32,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+}
+
+impl Default for KeystoreConfig {
+    fn default() -> Self {
+        Self {
+            directory: "keystore".to_string(),
+            kdf: KeystoreKdf::Scrypt,
+            cipher: KeystoreCipher::Aes256Gcm,
+            scrypt_log_n: 18, // n = 262144, the geth "standard" cost
+            scrypt_r: 8,
+            scrypt_p: 1,
+            argon2_memory_kib: 64 * 1024,
+            argon2_iterations: 3,
+            argon2_parallelism: 1,
+        }
+    }
+}
+
+impl KeystoreConfig {
+    /// DEXTER_KEYSTORE_DIR and DEXTER_KEYSTORE_KDF (`scrypt` or `argon2id`)
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(directory) = std::env::var("DEXTER_KEYSTORE_DIR") {
+            if !directory.is_empty() {
+                config.directory = directory;
+            }
+        }
+        if let Ok(kdf) = std::env::var("DEXTER_KEYSTORE_KDF") {
+            match kdf.to_lowercase().as_str() {
+                "scrypt" => config.kdf = KeystoreKdf::Scrypt,
+                "argon2id" | "argon2" => config.kdf = KeystoreKdf::Argon2id,
+                other => warn!("⚠️ Unknown DEXTER_KEYSTORE_KDF '{}', using scrypt", other),
+            }
+        }
+        config
+    }
+}
+
+// ============================================================================
+// ENCRYPTION
+// ============================================================================
+
+fn derive_key(crypto: &CryptoSection, password: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    let params = &crypto.kdfparams;
+    if params.dklen < 32 || params.dklen > 64 {
+        return Err(anyhow!("Unsupported derived key length {}", params.dklen));
+    }
+    let salt = hex::decode(&params.salt).context("Keystore salt is not valid hex")?;
+    let mut derived = Zeroizing::new(vec![0u8; params.dklen]);
+
+    match crypto.kdf.as_str() {
+        "scrypt" => {
+            let n = params.n.ok_or_else(|| anyhow!("scrypt keystore is missing n"))?;
+            if n < 2 || !n.is_power_of_two() {
+                return Err(anyhow!("scrypt n must be a power of two"));
+            }
+            let scrypt_params = scrypt::Params::new(
+                n.trailing_zeros() as u8,
+                params.r.unwrap_or(8),
+                params.p.unwrap_or(1),
+                params.dklen,
+            ).map_err(|e| anyhow!("Invalid scrypt parameters: {}", e))?;
+            scrypt::scrypt(password, &salt, &scrypt_params, derived.as_mut_slice())
+                .map_err(|e| anyhow!("scrypt failed: {}", e))?;
+        }
+        "pbkdf2" => {
+            if params.prf.as_deref().unwrap_or("hmac-sha256") != "hmac-sha256" {
+                return Err(anyhow!("Unsupported pbkdf2 prf {:?}", params.prf));
+            }
+            let rounds = params.c.ok_or_else(|| anyhow!("pbkdf2 keystore is missing c"))?;
+            pbkdf2::pbkdf2_hmac::<Sha256>(password, &salt, rounds, derived.as_mut_slice());
+        }
+        "argon2id" => {
+            let argon_params = argon2::Params::new(
+                params.m.ok_or_else(|| anyhow!("argon2id keystore is missing m"))?,
+                params.t.ok_or_else(|| anyhow!("argon2id keystore is missing t"))?,
+                params.p.unwrap_or(1),
+                Some(params.dklen),
+            ).map_err(|e| anyhow!("Invalid argon2 parameters: {}", e))?;
+            argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon_params)
+                .hash_password_into(password, &salt, derived.as_mut_slice())
+                .map_err(|e| anyhow!("argon2id failed: {}", e))?;
+        }
+        other => return Err(anyhow!("Unsupported keystore kdf '{}'", other)),
+    }
+
+    Ok(derived)
+}
+
+/// keccak256(last 16 bytes of the derived key | ciphertext); dk[16..32] for standard v3 files
+fn compute_mac(derived: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(16 + ciphertext.len());
+    data.extend_from_slice(&derived[derived.len() - 16..]);
+    data.extend_from_slice(ciphertext);
+    let mac = evm_tx::keccak256(&data);
+    data.zeroize();
+    mac
+}
+
+fn apply_cipher(cipher: KeystoreCipher, derived: &[u8], iv: &[u8], data: &[u8], encrypt: bool) -> Result<Vec<u8>> {
+    if derived.len() < cipher.key_len() + 16 {
+        return Err(anyhow!("Derived key too short for {}", cipher.label()));
+    }
+    if iv.len() != cipher.iv_len() {
+        return Err(anyhow!("Invalid IV length for {}", cipher.label()));
+    }
+    let key = &derived[..cipher.key_len()];
+
+    match cipher {
+        KeystoreCipher::Aes128Ctr => {
+            let mut buffer = data.to_vec();
+            let mut stream = Aes128Ctr::new_from_slices(key, iv).map_err(|e| anyhow!("Invalid AES key: {}", e))?;
+            stream.apply_keystream(&mut buffer);
+            Ok(buffer)
+        }
+        KeystoreCipher::Aes256Gcm => {
+            let aead = Aes256Gcm::new_from_slice(key).map_err(|e| anyhow!("Invalid AES key: {}", e))?;
+            let nonce = Nonce::from_slice(iv);
+            if encrypt {
+                aead.encrypt(nonce, data).map_err(|_| anyhow!("Keystore encryption failed"))
+            } else {
+                aead.decrypt(nonce, data).map_err(|_| anyhow!("Keystore ciphertext failed authentication"))
+            }
+        }
+    }
+}
+
+pub fn encrypt_secret(secret: &[u8], password: &str, config: &KeystoreConfig) -> Result<CryptoSection> {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; 32];
+    rng.fill_bytes(&mut salt);
+    let mut iv = vec![0u8; config.cipher.iv_len()];
+    rng.fill_bytes(&mut iv);
+
+    let dklen = config.cipher.key_len() + 16;
+    let (kdf, kdfparams) = match config.kdf {
+        KeystoreKdf::Scrypt => ("scrypt", KdfParams {
+            dklen,
+            salt: hex::encode(salt),
+            n: Some(1u64 << config.scrypt_log_n),
+            r: Some(config.scrypt_r),
+            p: Some(config.scrypt_p),
+            ..Default::default()
+        }),
+        KeystoreKdf::Argon2id => ("argon2id", KdfParams {
+            dklen,
+            salt: hex::encode(salt),
+            m: Some(config.argon2_memory_kib),
+            t: Some(config.argon2_iterations),
+            p: Some(config.argon2_parallelism),
+            ..Default::default()
+        }),
+    };
+
+    let mut crypto = CryptoSection {
+        cipher: config.cipher.label().to_string(),
+        cipherparams: CipherParams { iv: hex::encode(&iv) },
+        ciphertext: String::new(),
+        kdf: kdf.to_string(),
+        kdfparams,
+        mac: String::new(),
+    };
+    let derived = derive_key(&crypto, password.as_bytes())?;
+    let ciphertext = apply_cipher(config.cipher, &derived, &iv, secret, true)?;
+    crypto.mac = hex::encode(compute_mac(&derived, &ciphertext));
+    crypto.ciphertext = hex::encode(ciphertext);
+    Ok(crypto)
+}
+
+/// The MAC is checked before decrypting so a wrong password never yields garbage key bytes
+pub fn decrypt_secret(crypto: &CryptoSection, password: &str) -> Result<LockedSecret> {
+    let cipher = KeystoreCipher::from_label(&crypto.cipher)?;
+    let ciphertext = hex::decode(&crypto.ciphertext).context("Keystore ciphertext is not valid hex")?;
+    let iv = hex::decode(&crypto.cipherparams.iv).context("Keystore IV is not valid hex")?;
+    let expected_mac = hex::decode(&crypto.mac).context("Keystore MAC is not valid hex")?;
+
+    let derived = derive_key(crypto, password.as_bytes())?;
+    if !constant_time_eq(&compute_mac(&derived, &ciphertext), &expected_mac) {
+        return Err(anyhow!("Incorrect keystore password or corrupted keystore"));
+    }
+    let plaintext = Zeroizing::new(apply_cipher(cipher, &derived, &iv, &ciphertext, false)?);
+    Ok(LockedSecret::new(&plaintext))
+}
+
+fn solana_keypair_from_seed(seed: &[u8]) -> Result<Keypair> {
+    if seed.len() != 32 {
+        return Err(anyhow!("Solana keystore secret must be a 32-byte seed"));
+    }
+    keypair_from_seed(seed).map_err(|e| anyhow!("Invalid Solana seed: {}", e))
+}
+
+fn address_for(chain: ChainFamily, secret: &[u8]) -> Result<String> {
+    match chain {
+        ChainFamily::Ethereum => Ok(evm_tx::format_address(&EvmSigner::from_bytes(secret)?.address())),
+        ChainFamily::Solana => Ok(solana_keypair_from_seed(secret)?.pubkey().to_string()),
+    }
+}
+
+// ============================================================================
+// KEYSTORE
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreAccount {
+    pub name: String,
+    pub chain: ChainFamily,
+    pub address: String,
+    pub unlocked: bool,
+}
+
+struct UnlockedKey {
+    account: KeystoreAccount,
+    secret: LockedSecret,
+}
+
+pub struct Keystore {
+    config: Arc<RwLock<KeystoreConfig>>,
+    unlocked: Arc<RwLock<HashMap<String, UnlockedKey>>>,
+}
+
+impl Keystore {
+    pub fn new(config: KeystoreConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            unlocked: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(KeystoreConfig::from_env())
+    }
+
+    pub async fn get_config(&self) -> KeystoreConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: KeystoreConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Every keystore file in the directory, without decrypting anything
+    pub async fn list_accounts(&self) -> Result<Vec<KeystoreAccount>> {
+        let directory = PathBuf::from(&self.config.read().await.directory);
+        let unlocked = self.unlocked.read().await;
+        let mut accounts = Vec::new();
+        if !directory.exists() {
+            return Ok(accounts);
+        }
+
+        for entry in std::fs::read_dir(&directory).with_context(|| format!("Failed to read {}", directory.display()))? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match read_keystore_file(&path) {
+                Ok((name, file)) => {
+                    let chain = file.chain.unwrap_or(ChainFamily::Ethereum);
+                    accounts.push(KeystoreAccount {
+                        unlocked: unlocked.contains_key(&name),
+                        address: display_address(chain, file.address.as_deref().unwrap_or_default()),
+                        name,
+                        chain,
+                    });
+                }
+                Err(e) => warn!("⚠️ Skipping unreadable keystore {}: {}", path.display(), e),
+            }
+        }
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(accounts)
+    }
+
+    /// Generate a fresh key and store it encrypted under `name`
+    pub async fn create_account(&self, name: &str, chain: ChainFamily, password: &str) -> Result<KeystoreAccount> {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        loop {
+            rand::thread_rng().fill_bytes(secret.as_mut_slice());
+            // secp256k1 rejects zero and values above the curve order
+            if chain == ChainFamily::Solana || EvmSigner::from_bytes(secret.as_slice()).is_ok() {
+                break;
+            }
+        }
+        self.import_secret(name, chain, secret.as_slice(), password).await
+    }
+
+    /// Encrypt an existing key: 32-byte secp256k1 scalar, or a Solana 32-byte seed / 64-byte keypair
+    pub async fn import_secret(&self, name: &str, chain: ChainFamily, secret: &[u8], password: &str) -> Result<KeystoreAccount> {
+        let seed = Zeroizing::new(match (chain, secret.len()) {
+            (ChainFamily::Solana, 64) => {
+                let keypair = solana_keypair_from_seed(&secret[..32])?;
+                if keypair.pubkey().to_bytes()[..] != secret[32..] {
+                    return Err(anyhow!("Solana keypair bytes do not match their public key"));
+                }
+                secret[..32].to_vec()
+            }
+            _ => secret.to_vec(),
+        });
+        let address = address_for(chain, &seed)?;
+        let config = self.config.read().await.clone();
+        let path = keystore_path(&config.directory, name)?;
+        if path.exists() {
+            return Err(anyhow!("Keystore account '{}' already exists", name));
+        }
+
+        let password = Zeroizing::new(password.to_string());
+        let crypto = tokio::task::spawn_blocking(move || encrypt_secret(&seed, &password, &config)).await??;
+        let file = KeystoreFile {
+            version: 3,
+            id: uuid::Uuid::new_v4().to_string(),
+            address: Some(match chain {
+                ChainFamily::Ethereum => address.trim_start_matches("0x").to_lowercase(),
+                ChainFamily::Solana => address.clone(),
+            }),
+            crypto,
+            name: Some(name.to_string()),
+            chain: Some(chain),
+        };
+        write_keystore_file(&path, &file)?;
+
+        info!("🔐 Stored {:?} account '{}' ({})", chain, name, address);
+        Ok(KeystoreAccount { name: name.to_string(), chain, address, unlocked: false })
+    }
+
+    /// Copy a v3 keystore exported by another wallet (geth, MetaMask, ...) into the directory
+    pub async fn import_keystore_json(&self, name: &str, json: &str, chain: ChainFamily) -> Result<KeystoreAccount> {
+        let mut file: KeystoreFile = serde_json::from_str(json).context("Not a keystore v3 file")?;
+        if file.version != 3 {
+            return Err(anyhow!("Unsupported keystore version {}", file.version));
+        }
+        file.name = Some(name.to_string());
+        file.chain = Some(chain);
+        let path = keystore_path(&self.config.read().await.directory, name)?;
+        if path.exists() {
+            return Err(anyhow!("Keystore account '{}' already exists", name));
+        }
+        write_keystore_file(&path, &file)?;
+
+        let address = display_address(chain, file.address.as_deref().unwrap_or_default());
+        info!("🔐 Imported {:?} keystore '{}' ({})", chain, name, address);
+        Ok(KeystoreAccount { name: name.to_string(), chain, address, unlocked: false })
+    }
+
+    pub async fn unlock(&self, name: &str, password: &str) -> Result<KeystoreAccount> {
+        let path = keystore_path(&self.config.read().await.directory, name)?;
+        let (_, file) = read_keystore_file(&path)?;
+        let chain = file.chain.unwrap_or(ChainFamily::Ethereum);
+
+        let password = Zeroizing::new(password.to_string());
+        let crypto = file.crypto.clone();
+        let secret = tokio::task::spawn_blocking(move || decrypt_secret(&crypto, &password)).await??;
+
+        // The stored address is informational; trust only what the key derives to
+        let address = address_for(chain, secret.expose())?;
+        if let Some(stored) = file.address.as_deref().filter(|a| !a.is_empty()) {
+            if display_address(chain, stored).to_lowercase() != address.to_lowercase() {
+                return Err(anyhow!("Keystore '{}' address does not match its key", name));
+            }
+        }
+
+        let account = KeystoreAccount { name: name.to_string(), chain, address, unlocked: true };
+        self.unlocked.write().await.insert(name.to_string(), UnlockedKey { account: account.clone(), secret });
+        info!("🔓 Unlocked {:?} account '{}' ({})", chain, name, account.address);
+        Ok(account)
+    }
+
+    /// Unlock every account the password opens; failures are reported by name only
+    pub async fn unlock_all(&self, password: &str) -> Result<Vec<KeystoreAccount>> {
+        let mut unlocked = Vec::new();
+        for account in self.list_accounts().await? {
+            match self.unlock(&account.name, password).await {
+                Ok(account) => unlocked.push(account),
+                Err(e) => error!("❌ Could not unlock keystore account '{}': {}", account.name, e),
+            }
+        }
+        Ok(unlocked)
+    }
+
+    pub async fn lock(&self, name: &str) -> bool {
+        self.unlocked.write().await.remove(name).is_some()
+    }
+
+    pub async fn lock_all(&self) {
+        self.unlocked.write().await.clear();
+        info!("🔒 All keystore accounts locked");
+    }
+
+    pub async fn unlocked_accounts(&self) -> Vec<KeystoreAccount> {
+        let mut accounts: Vec<KeystoreAccount> = self.unlocked.read().await.values().map(|k| k.account.clone()).collect();
+        accounts.sort_by(|a, b| a.name.cmp(&b.name));
+        accounts
+    }
+
+    /// Signer for the named account, or the first unlocked Ethereum account by name
+    pub async fn evm_signer(&self, name: Option<&str>) -> Result<EvmSigner> {
+        let unlocked = self.unlocked.read().await;
+        let key = select_account(&unlocked, name, ChainFamily::Ethereum)?;
+        EvmSigner::from_bytes(key.secret.expose())
+    }
+
+    /// Keypair for the named account, or the first unlocked Solana account by name
+    pub async fn solana_keypair(&self, name: Option<&str>) -> Result<Keypair> {
+        let unlocked = self.unlocked.read().await;
+        let key = select_account(&unlocked, name, ChainFamily::Solana)?;
+        solana_keypair_from_seed(key.secret.expose())
+    }
+
+    /// One-time move of the plaintext TRADING_WALLET_PRIVATE_KEY / EVM_PRIVATE_KEY into the keystore
+    pub async fn migrate_env_keys(&self, password: &str) -> Result<usize> {
+        let existing = self.list_accounts().await?;
+        let mut migrated = 0;
+
+        if !existing.iter().any(|a| a.chain == ChainFamily::Solana) {
+            if let Ok(secret) = std::env::var("TRADING_WALLET_PRIVATE_KEY") {
+                if !secret.is_empty() {
+                    let bytes = Zeroizing::new(bs58::decode(secret.trim()).into_vec().context("TRADING_WALLET_PRIVATE_KEY is not valid base58")?);
+                    self.import_secret("solana-trading", ChainFamily::Solana, &bytes, password).await?;
+                    migrated += 1;
+                }
+            }
+        }
+        if !existing.iter().any(|a| a.chain == ChainFamily::Ethereum) {
+            if let Ok(secret) = std::env::var("EVM_PRIVATE_KEY") {
+                if !secret.is_empty() {
+                    let bytes = Zeroizing::new(hex::decode(secret.trim().trim_start_matches("0x")).context("EVM_PRIVATE_KEY is not valid hex")?);
+                    self.import_secret("evm-trading", ChainFamily::Ethereum, &bytes, password).await?;
+                    migrated += 1;
+                }
+            }
+        }
+
+        if migrated > 0 {
+            warn!("⚠️ Migrated {} plaintext key(s) into the keystore; remove them from the environment", migrated);
+        }
+        Ok(migrated)
+    }
+}
+
+impl Clone for Keystore {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            unlocked: self.unlocked.clone(),
+        }
+    }
+}
+
+impl Default for Keystore {
+    fn default() -> Self {
+        Self::new(KeystoreConfig::default())
+    }
+}
+
+/// DEXTER_KEYSTORE_PASSWORD_FILE, falling back to DEXTER_KEYSTORE_PASSWORD; the variable is cleared once read
+pub fn password_from_env() -> Option<Zeroizing<String>> {
+    if let Ok(path) = std::env::var("DEXTER_KEYSTORE_PASSWORD_FILE") {
+        if !path.is_empty() {
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => {
+                    let contents = Zeroizing::new(contents);
+                    return Some(Zeroizing::new(contents.trim_end_matches(['\r', '\n']).to_string()));
+                }
+                Err(e) => warn!("⚠️ Could not read keystore password file {}: {}", path, e),
+            }
+        }
+    }
+    let password = std::env::var("DEXTER_KEYSTORE_PASSWORD").ok().filter(|p| !p.is_empty())?;
+    std::env::remove_var("DEXTER_KEYSTORE_PASSWORD");
+    Some(Zeroizing::new(password))
+}
+
+fn select_account<'a>(
+    unlocked: &'a HashMap<String, UnlockedKey>,
+    name: Option<&str>,
+    chain: ChainFamily,
+) -> Result<&'a UnlockedKey> {
+    let key = match name {
+        Some(name) => unlocked.get(name).ok_or_else(|| anyhow!("Keystore account '{}' is not unlocked", name))?,
+        None => unlocked.values()
+            .filter(|k| k.account.chain == chain)
+            .min_by(|a, b| a.account.name.cmp(&b.account.name))
+            .ok_or_else(|| anyhow!("No unlocked {:?} account in the keystore", chain))?,
+    };
+    if key.account.chain != chain {
+        return Err(anyhow!("Keystore account '{}' holds a {:?} key", key.account.name, key.account.chain));
+    }
+    Ok(key)
+}
+
+fn display_address(chain: ChainFamily, stored: &str) -> String {
+    match chain {
+        ChainFamily::Ethereum if !stored.is_empty() => evm_tx::parse_address(stored)
+            .map(|a| evm_tx::format_address(&a))
+            .unwrap_or_else(|_| stored.to_string()),
+        _ => stored.to_string(),
+    }
+}
+
+/// Account names double as file names, so keep them to a safe character set
+fn keystore_path(directory: &str, name: &str) -> Result<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(anyhow!("Keystore account names may only contain letters, digits, '-' and '_'"));
+    }
+    Ok(Path::new(directory).join(format!("{}.json", name)))
+}
+
+fn read_keystore_file(path: &Path) -> Result<(String, KeystoreFile)> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file: KeystoreFile = serde_json::from_str(&contents).context("Not a keystore v3 file")?;
+    if file.version != 3 {
+        return Err(anyhow!("Unsupported keystore version {}", file.version));
+    }
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+    Ok((file.name.clone().unwrap_or(stem), file))
+}
+
+fn write_keystore_file(path: &Path, file: &KeystoreFile) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(file)?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut handle = options.open(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut handle, json.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Web3 Secret Storage definition, pbkdf2 test vector
+    fn pbkdf2_vector() -> CryptoSection {
+        CryptoSection {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: CipherParams { iv: "6087dab2f9fdbbfaddc31a909735c1e6".to_string() },
+            ciphertext: "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46".to_string(),
+            kdf: "pbkdf2".to_string(),
+            kdfparams: KdfParams {
+                dklen: 32,
+                salt: "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd".to_string(),
+                c: Some(262144),
+                prf: Some("hmac-sha256".to_string()),
+                ..Default::default()
+            },
+            mac: "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2".to_string(),
+        }
+    }
+
+    #[test]
+    fn pbkdf2_vector_decrypts() {
+        let secret = decrypt_secret(&pbkdf2_vector(), "testpassword").unwrap();
+        assert_eq!(hex::encode(secret.expose()), "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d");
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        assert!(decrypt_secret(&pbkdf2_vector(), "wrongpassword").is_err());
+    }
+
+    #[test]
+    fn scrypt_and_argon2id_round_trip() {
+        for kdf in [KeystoreKdf::Scrypt, KeystoreKdf::Argon2id] {
+            // Cheap parameters; only the plumbing is under test here
+            let config = KeystoreConfig {
+                kdf,
+                scrypt_log_n: 10,
+                argon2_memory_kib: 1024,
+                argon2_iterations: 1,
+                ..Default::default()
+            };
+            let crypto = encrypt_secret(&[0x42; 32], "dexter", &config).unwrap();
+            assert_eq!(decrypt_secret(&crypto, "dexter").unwrap().expose(), [0x42u8; 32], "{:?}", kdf);
+            assert!(decrypt_secret(&crypto, "hunter2").is_err(), "{:?}", kdf);
+        }
+    }
+}
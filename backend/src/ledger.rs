@@ -0,0 +1,827 @@
+// Tax-Lot Ledger - Every fill and fee recorded as cost-basis lots per asset
+// Realized P&L under FIFO, LIFO and average cost, unrealized P&L at mark prices, period reports
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{BufRead, Write};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use anyhow::{Result, anyhow};
+use log::{info, warn, error};
+
+use crate::balances::{self, UsdPriceSource};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// ============================================================================
+// CONFIGURATION
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    Fifo,
+    Lifo,
+    AverageCost,
+}
+
+impl CostBasisMethod {
+    pub const ALL: [CostBasisMethod; 3] = [CostBasisMethod::Fifo, CostBasisMethod::Lifo, CostBasisMethod::AverageCost];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "fifo" => Some(CostBasisMethod::Fifo),
+            "lifo" => Some(CostBasisMethod::Lifo),
+            "avg" | "average" | "average_cost" => Some(CostBasisMethod::AverageCost),
+            _ => None,
+        }
+    }
+}
+
+/// Universal pools lots per asset across every wallet (arbitrage legs on different
+/// venues close against each other); per-wallet keeps each wallet's lots apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LotPooling {
+    Universal,
+    PerWallet,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerConfig {
+    pub method: CostBasisMethod, // Method behind portfolio P&L; reports can ask for any
+    pub pooling: LotPooling,
+    pub venue_wallets: HashMap<String, String>, // Venue -> wallet/account label for fills
+    pub journal_path: Option<String>,           // JSON lines of fills, replayed on startup
+}
+
+impl Default for LedgerConfig {
+    fn default() -> Self {
+        Self {
+            method: CostBasisMethod::Fifo,
+            pooling: LotPooling::Universal,
+            venue_wallets: HashMap::new(),
+            journal_path: None,
+        }
+    }
+}
+
+impl LedgerConfig {
+    pub fn from_env() -> Self {
+        let mut config = LedgerConfig::default();
+        if let Ok(method) = std::env::var("DEXTER_COST_BASIS_METHOD") {
+            match CostBasisMethod::parse(&method) {
+                Some(method) => config.method = method,
+                None => warn!("⚠️ Unknown DEXTER_COST_BASIS_METHOD {}; using FIFO", method),
+            }
+        }
+        if let Ok(pooling) = std::env::var("DEXTER_LOT_POOLING") {
+            config.pooling = match pooling.to_lowercase().as_str() {
+                "wallet" | "per_wallet" => LotPooling::PerWallet,
+                _ => LotPooling::Universal,
+            };
+        }
+        // venue=wallet pairs, comma separated
+        if let Ok(mapping) = std::env::var("DEXTER_VENUE_WALLETS") {
+            config.venue_wallets = mapping.split(',')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(venue, wallet)| (venue.trim().to_string(), wallet.trim().to_string()))
+                .filter(|(venue, wallet)| !venue.is_empty() && !wallet.is_empty())
+                .collect();
+        }
+        if let Ok(path) = std::env::var("DEXTER_LEDGER_JOURNAL") {
+            if !path.is_empty() {
+                config.journal_path = Some(path);
+            }
+        }
+        config
+    }
+
+    pub fn wallet_for_venue(&self, venue: &str) -> String {
+        self.venue_wallets.get(venue).cloned().unwrap_or_else(|| venue.to_string())
+    }
+}
+
+// ============================================================================
+// FILLS, LOTS & DISPOSALS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FillSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerFill {
+    pub id: String,
+    pub trade_id: Option<String>,
+    pub timestamp: u64,
+    pub wallet: String,
+    pub venue: String,
+    pub strategy: String,
+    pub asset: String,
+    pub side: FillSide,
+    pub quantity: Decimal,  // Base units
+    pub price_usd: Decimal, // Per unit
+    pub fee_usd: Decimal,   // Trading fees plus any gas attributed to this fill
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LotDirection {
+    Long,
+    Short, // Sold before it was held; closed by later buys
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLot {
+    pub id: String,
+    pub asset: String,
+    pub direction: LotDirection,
+    pub wallet: String,
+    pub venue: String,
+    pub strategy: String,
+    pub open_fill_id: String,
+    pub opened_at: u64,
+    pub quantity: Decimal,
+    pub remaining: Decimal,
+    pub unit_basis_usd: Decimal, // Cost per unit incl. fees (long) or proceeds per unit net of fees (short)
+}
+
+/// One lot (or part of one) closed by a fill
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Disposal {
+    pub method: CostBasisMethod,
+    pub asset: String,
+    pub direction: LotDirection,
+    pub lot_id: String,
+    pub open_fill_id: String,
+    pub close_fill_id: String,
+    pub trade_id: Option<String>,
+    pub wallet: String,   // Of the closing fill
+    pub venue: String,    // Of the closing fill
+    pub strategy: String, // Of the closing fill
+    pub opened_at: u64,
+    pub closed_at: u64,
+    pub quantity: Decimal,
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub realized_pnl_usd: Decimal,
+}
+
+impl Disposal {
+    pub fn holding_period_secs(&self) -> u64 {
+        self.closed_at.saturating_sub(self.opened_at)
+    }
+}
+
+// Open lots and closed disposals under one cost-basis method
+#[derive(Debug, Clone, Default)]
+struct LotBook {
+    lots: HashMap<(String, String), VecDeque<TaxLot>>, // (pool, asset) -> lots, all one direction
+    disposals: Vec<Disposal>,
+}
+
+impl LotBook {
+    fn apply(&mut self, method: CostBasisMethod, pooling: LotPooling, fill: &LedgerFill) {
+        let pool = match pooling {
+            LotPooling::Universal => String::new(),
+            LotPooling::PerWallet => fill.wallet.clone(),
+        };
+        let lots = self.lots.entry((pool, fill.asset.clone())).or_default();
+        let closes = match fill.side {
+            FillSide::Buy => LotDirection::Short,
+            FillSide::Sell => LotDirection::Long,
+        };
+        let fee_per_unit = fill.fee_usd / fill.quantity;
+        let mut remaining = fill.quantity;
+
+        while remaining > Decimal::ZERO && lots.front().map(|lot| lot.direction == closes).unwrap_or(false) {
+            let index = match method {
+                CostBasisMethod::Lifo => lots.len() - 1,
+                // Average cost keeps a single pooled lot per asset
+                CostBasisMethod::Fifo | CostBasisMethod::AverageCost => 0,
+            };
+            let lot = &mut lots[index];
+            let quantity = remaining.min(lot.remaining);
+            let (proceeds, cost) = match lot.direction {
+                LotDirection::Long => (quantity * (fill.price_usd - fee_per_unit), quantity * lot.unit_basis_usd),
+                LotDirection::Short => (quantity * lot.unit_basis_usd, quantity * (fill.price_usd + fee_per_unit)),
+            };
+            self.disposals.push(Disposal {
+                method,
+                asset: fill.asset.clone(),
+                direction: lot.direction,
+                lot_id: lot.id.clone(),
+                open_fill_id: lot.open_fill_id.clone(),
+                close_fill_id: fill.id.clone(),
+                trade_id: fill.trade_id.clone(),
+                wallet: fill.wallet.clone(),
+                venue: fill.venue.clone(),
+                strategy: fill.strategy.clone(),
+                opened_at: lot.opened_at,
+                closed_at: fill.timestamp,
+                quantity,
+                proceeds_usd: proceeds,
+                cost_basis_usd: cost,
+                realized_pnl_usd: proceeds - cost,
+            });
+
+            lot.remaining -= quantity;
+            remaining -= quantity;
+            if lot.remaining.is_zero() {
+                lots.remove(index);
+            }
+        }
+
+        if remaining > Decimal::ZERO {
+            let (direction, unit_basis) = match fill.side {
+                FillSide::Buy => (LotDirection::Long, fill.price_usd + fee_per_unit),
+                FillSide::Sell => (LotDirection::Short, fill.price_usd - fee_per_unit),
+            };
+            match lots.front_mut() {
+                Some(pooled) if method == CostBasisMethod::AverageCost => {
+                    let total = pooled.remaining + remaining;
+                    pooled.unit_basis_usd = (pooled.unit_basis_usd * pooled.remaining + unit_basis * remaining) / total;
+                    pooled.remaining = total;
+                    pooled.quantity += remaining;
+                }
+                _ => lots.push_back(TaxLot {
+                    id: format!("lot_{}", fill.id),
+                    asset: fill.asset.clone(),
+                    direction,
+                    wallet: fill.wallet.clone(),
+                    venue: fill.venue.clone(),
+                    strategy: fill.strategy.clone(),
+                    open_fill_id: fill.id.clone(),
+                    opened_at: fill.timestamp,
+                    quantity: remaining,
+                    remaining,
+                    unit_basis_usd: unit_basis,
+                }),
+            }
+        }
+    }
+
+    fn open_lots(&self) -> impl Iterator<Item = &TaxLot> {
+        self.lots.values().flatten()
+    }
+}
+
+#[derive(Debug, Default)]
+struct LedgerState {
+    fills: Vec<LedgerFill>,
+    books: HashMap<CostBasisMethod, LotBook>,
+    marks: HashMap<String, Decimal>, // Asset -> USD mark price
+}
+
+impl LedgerState {
+    fn apply(&mut self, pooling: LotPooling, fill: &LedgerFill) {
+        for method in CostBasisMethod::ALL {
+            self.books.entry(method).or_default().apply(method, pooling, fill);
+        }
+        self.marks.insert(fill.asset.clone(), fill.price_usd);
+    }
+
+    fn book(&self, method: CostBasisMethod) -> Option<&LotBook> {
+        self.books.get(&method)
+    }
+}
+
+// ============================================================================
+// REPORTS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    Wallet,
+    Venue,
+    Strategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PnlReportRow {
+    pub key: String,
+    pub realized_pnl_usd: Decimal,
+    pub unrealized_pnl_usd: Decimal,
+    pub proceeds_usd: Decimal,
+    pub cost_basis_usd: Decimal,
+    pub fees_usd: Decimal,
+    pub volume_usd: Decimal,
+    pub fills: u64,
+    pub disposals: u64,
+    pub winning_disposals: u64,
+    pub losing_disposals: u64,
+}
+
+impl PnlReportRow {
+    fn add_disposal(&mut self, disposal: &Disposal) {
+        self.realized_pnl_usd += disposal.realized_pnl_usd;
+        self.proceeds_usd += disposal.proceeds_usd;
+        self.cost_basis_usd += disposal.cost_basis_usd;
+        self.disposals += 1;
+        if disposal.realized_pnl_usd > Decimal::ZERO {
+            self.winning_disposals += 1;
+        } else if disposal.realized_pnl_usd < Decimal::ZERO {
+            self.losing_disposals += 1;
+        }
+    }
+
+    fn add_fill(&mut self, fill: &LedgerFill) {
+        self.fees_usd += fill.fee_usd;
+        self.volume_usd += fill.quantity * fill.price_usd;
+        self.fills += 1;
+    }
+
+    fn merge(&mut self, other: &PnlReportRow) {
+        self.realized_pnl_usd += other.realized_pnl_usd;
+        self.unrealized_pnl_usd += other.unrealized_pnl_usd;
+        self.proceeds_usd += other.proceeds_usd;
+        self.cost_basis_usd += other.cost_basis_usd;
+        self.fees_usd += other.fees_usd;
+        self.volume_usd += other.volume_usd;
+        self.fills += other.fills;
+        self.disposals += other.disposals;
+        self.winning_disposals += other.winning_disposals;
+        self.losing_disposals += other.losing_disposals;
+    }
+}
+
+/// Realized figures cover [from, to); unrealized is as of `generated_at` at current marks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlReport {
+    pub method: CostBasisMethod,
+    pub grouping: ReportGrouping,
+    pub from: u64,
+    pub to: u64,
+    pub generated_at: u64,
+    pub rows: Vec<PnlReportRow>,
+    pub totals: PnlReportRow,
+    pub unpriced_assets: Vec<String>, // Open lots without a mark, left out of unrealized P&L
+}
+
+impl PnlReport {
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Csv => Ok(self.to_csv()),
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "group,realized_pnl_usd,unrealized_pnl_usd,proceeds_usd,cost_basis_usd,fees_usd,volume_usd,fills,disposals,winning_disposals,losing_disposals\n",
+        );
+        for row in self.rows.iter().chain(std::iter::once(&self.totals)) {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&row.key),
+                row.realized_pnl_usd.round_dp(8),
+                row.unrealized_pnl_usd.round_dp(8),
+                row.proceeds_usd.round_dp(8),
+                row.cost_basis_usd.round_dp(8),
+                row.fees_usd.round_dp(8),
+                row.volume_usd.round_dp(8),
+                row.fills,
+                row.disposals,
+                row.winning_disposals,
+                row.losing_disposals,
+            ));
+        }
+        csv
+    }
+}
+
+/// What the portfolio view needs from the ledger under one method
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerSummary {
+    pub method: CostBasisMethod,
+    pub realized_pnl_usd: Decimal,
+    pub realized_today_usd: Decimal, // Since 00:00 UTC
+    pub unrealized_pnl_usd: Decimal,
+    pub fees_usd: Decimal,
+    pub open_lots: usize,
+    pub closed_trades: u64,
+    pub win_rate: f64, // Share of closed trades with positive realized P&L
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn group_key(grouping: ReportGrouping, wallet: &str, venue: &str, strategy: &str) -> String {
+    match grouping {
+        ReportGrouping::Wallet => wallet,
+        ReportGrouping::Venue => venue,
+        ReportGrouping::Strategy => strategy,
+    }.to_string()
+}
+
+fn report_row(rows: &mut HashMap<String, PnlReportRow>, key: String) -> &mut PnlReportRow {
+    rows.entry(key.clone()).or_insert_with(|| PnlReportRow { key, ..Default::default() })
+}
+
+fn unrealized_pnl(lot: &TaxLot, mark: Decimal) -> Decimal {
+    match lot.direction {
+        LotDirection::Long => (mark - lot.unit_basis_usd) * lot.remaining,
+        LotDirection::Short => (lot.unit_basis_usd - mark) * lot.remaining,
+    }
+}
+
+// ============================================================================
+// LEDGER
+// ============================================================================
+
+pub struct TaxLotLedger {
+    config: Arc<RwLock<LedgerConfig>>,
+    state: Arc<RwLock<LedgerState>>,
+}
+
+impl TaxLotLedger {
+    pub fn new(config: LedgerConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            state: Arc::new(RwLock::new(LedgerState::default())),
+        }
+    }
+
+    /// Replays the journal (if configured) so lots survive restarts
+    pub fn from_env() -> Self {
+        let config = LedgerConfig::from_env();
+        let mut state = LedgerState::default();
+        if let Some(path) = &config.journal_path {
+            match std::fs::File::open(path) {
+                Ok(file) => {
+                    for (line_no, line) in std::io::BufReader::new(file).lines().enumerate() {
+                        let Ok(line) = line else { break };
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        match serde_json::from_str::<LedgerFill>(&line) {
+                            Ok(fill) => {
+                                state.apply(config.pooling, &fill);
+                                state.fills.push(fill);
+                            }
+                            Err(e) => error!("❌ Skipping ledger journal line {} in {}: {}", line_no + 1, path, e),
+                        }
+                    }
+                    info!("📒 Replayed {} fills from ledger journal {}", state.fills.len(), path);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => error!("❌ Cannot read ledger journal {}: {}", path, e),
+            }
+        }
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            state: Arc::new(RwLock::new(state)),
+        }
+    }
+
+    pub async fn get_config(&self) -> LedgerConfig {
+        self.config.read().await.clone()
+    }
+
+    /// Changing the pooling rebuilds every book from the recorded fills
+    pub async fn set_config(&self, config: LedgerConfig) {
+        let mut state = self.state.write().await;
+        let marks = std::mem::take(&mut state.marks);
+        let fills = std::mem::take(&mut state.fills);
+        *state = LedgerState::default();
+        for fill in &fills {
+            state.apply(config.pooling, fill);
+        }
+        state.fills = fills;
+        state.marks.extend(marks);
+        *self.config.write().await = config;
+    }
+
+    pub async fn wallet_for_venue(&self, venue: &str) -> String {
+        self.config.read().await.wallet_for_venue(venue)
+    }
+
+    /// Returns the disposals this fill closed under the configured method
+    pub async fn record_fill(&self, fill: LedgerFill) -> Result<Vec<Disposal>> {
+        if fill.quantity <= Decimal::ZERO {
+            return Err(anyhow!("Fill {} has non-positive quantity {}", fill.id, fill.quantity));
+        }
+        if fill.price_usd < Decimal::ZERO || fill.fee_usd < Decimal::ZERO {
+            return Err(anyhow!("Fill {} has a negative price or fee", fill.id));
+        }
+
+        let config = self.config.read().await.clone();
+        let mut state = self.state.write().await;
+        if state.fills.iter().any(|f| f.id == fill.id) {
+            return Err(anyhow!("Fill {} already recorded", fill.id));
+        }
+
+        if let Some(path) = &config.journal_path {
+            let appended = serde_json::to_string(&fill).map_err(|e| e.to_string()).and_then(|line| {
+                std::fs::OpenOptions::new().create(true).append(true).open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+                    .map_err(|e| e.to_string())
+            });
+            if let Err(e) = appended {
+                error!("❌ Failed to append ledger journal {}: {}", path, e);
+            }
+        }
+
+        let before = state.book(config.method).map(|b| b.disposals.len()).unwrap_or(0);
+        state.apply(config.pooling, &fill);
+        state.fills.push(fill);
+        Ok(state.book(config.method).map(|b| b.disposals[before..].to_vec()).unwrap_or_default())
+    }
+
+    /// Marks open assets from a USD price map (symbols as `balances::usd_price` resolves them)
+    pub async fn mark_prices(&self, prices: &HashMap<String, f64>) {
+        let mut state = self.state.write().await;
+        let assets: HashSet<String> = state.books.values()
+            .flat_map(|book| book.open_lots().map(|lot| lot.asset.clone()))
+            .collect();
+        for asset in assets {
+            if let Some(price) = balances::usd_price(prices, &asset).and_then(Decimal::from_f64) {
+                state.marks.insert(asset, price);
+            }
+        }
+    }
+
+    /// Re-marks open lots from the price source until the task is dropped
+    pub async fn start_mark_refresh(self: Arc<Self>, source: Arc<dyn UsdPriceSource>, interval_secs: u64) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs.max(1)));
+        loop {
+            interval.tick().await;
+            match source.usd_prices().await {
+                Ok(prices) => self.mark_prices(&prices).await,
+                Err(e) => warn!("⚠️ Ledger mark price refresh failed: {}", e),
+            }
+        }
+    }
+
+    pub async fn set_mark(&self, asset: &str, price_usd: Decimal) {
+        self.state.write().await.marks.insert(asset.to_string(), price_usd);
+    }
+
+    pub async fn get_fills(&self, from: u64, to: u64) -> Vec<LedgerFill> {
+        self.state.read().await.fills.iter()
+            .filter(|f| f.timestamp >= from && f.timestamp < to)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get_open_lots(&self, method: CostBasisMethod) -> Vec<TaxLot> {
+        let state = self.state.read().await;
+        let mut lots: Vec<TaxLot> = state.book(method)
+            .map(|book| book.open_lots().cloned().collect())
+            .unwrap_or_default();
+        lots.sort_by(|a, b| a.asset.cmp(&b.asset).then(a.opened_at.cmp(&b.opened_at)));
+        lots
+    }
+
+    pub async fn get_disposals(&self, method: CostBasisMethod, from: u64, to: u64) -> Vec<Disposal> {
+        self.state.read().await.book(method)
+            .map(|book| book.disposals.iter()
+                .filter(|d| d.closed_at >= from && d.closed_at < to)
+                .cloned()
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Gross open exposure per asset in USD: remaining quantity at the mark, or at basis before the first mark
+    pub async fn open_exposure_usd(&self) -> HashMap<String, f64> {
+        let method = self.config.read().await.method;
+        let state = self.state.read().await;
+        let mut exposure: HashMap<String, f64> = HashMap::new();
+        if let Some(book) = state.book(method) {
+            for lot in book.open_lots() {
+                let price = state.marks.get(&lot.asset).copied().unwrap_or(lot.unit_basis_usd);
+                let value = (lot.remaining * price).abs().to_f64().unwrap_or(0.0);
+                *exposure.entry(lot.asset.clone()).or_insert(0.0) += value;
+            }
+        }
+        exposure
+    }
+
+    pub async fn summary(&self, method: Option<CostBasisMethod>) -> LedgerSummary {
+        let method = match method {
+            Some(method) => method,
+            None => self.config.read().await.method,
+        };
+        let state = self.state.read().await;
+        let today = chrono::Utc::now().timestamp() as u64 / SECONDS_PER_DAY * SECONDS_PER_DAY;
+
+        let mut summary = LedgerSummary {
+            method,
+            realized_pnl_usd: Decimal::ZERO,
+            realized_today_usd: Decimal::ZERO,
+            unrealized_pnl_usd: Decimal::ZERO,
+            fees_usd: state.fills.iter().map(|f| f.fee_usd).sum(),
+            open_lots: 0,
+            closed_trades: 0,
+            win_rate: 0.0,
+        };
+        let Some(book) = state.book(method) else {
+            return summary;
+        };
+
+        // A trade (or an untagged closing fill) wins when its disposals net positive
+        let mut by_trade: HashMap<&str, Decimal> = HashMap::new();
+        for disposal in &book.disposals {
+            summary.realized_pnl_usd += disposal.realized_pnl_usd;
+            if disposal.closed_at >= today {
+                summary.realized_today_usd += disposal.realized_pnl_usd;
+            }
+            let key = disposal.trade_id.as_deref().unwrap_or(&disposal.close_fill_id);
+            *by_trade.entry(key).or_insert(Decimal::ZERO) += disposal.realized_pnl_usd;
+        }
+        summary.closed_trades = by_trade.len() as u64;
+        if !by_trade.is_empty() {
+            let wins = by_trade.values().filter(|pnl| **pnl > Decimal::ZERO).count();
+            summary.win_rate = wins as f64 / by_trade.len() as f64;
+        }
+
+        for lot in book.open_lots() {
+            summary.open_lots += 1;
+            if let Some(mark) = state.marks.get(&lot.asset) {
+                summary.unrealized_pnl_usd += unrealized_pnl(lot, *mark);
+            }
+        }
+        summary
+    }
+
+    pub async fn report(&self, method: CostBasisMethod, grouping: ReportGrouping, from: u64, to: u64) -> PnlReport {
+        let state = self.state.read().await;
+        let mut rows: HashMap<String, PnlReportRow> = HashMap::new();
+        let mut unpriced = HashSet::new();
+
+        for fill in state.fills.iter().filter(|f| f.timestamp >= from && f.timestamp < to) {
+            report_row(&mut rows, group_key(grouping, &fill.wallet, &fill.venue, &fill.strategy)).add_fill(fill);
+        }
+        if let Some(book) = state.book(method) {
+            for disposal in book.disposals.iter().filter(|d| d.closed_at >= from && d.closed_at < to) {
+                report_row(&mut rows, group_key(grouping, &disposal.wallet, &disposal.venue, &disposal.strategy)).add_disposal(disposal);
+            }
+            // Open positions belong to whoever opened them
+            for lot in book.open_lots() {
+                match state.marks.get(&lot.asset) {
+                    Some(mark) => {
+                        report_row(&mut rows, group_key(grouping, &lot.wallet, &lot.venue, &lot.strategy)).unrealized_pnl_usd += unrealized_pnl(lot, *mark);
+                    }
+                    None => {
+                        unpriced.insert(lot.asset.clone());
+                    }
+                }
+            }
+        }
+
+        let mut rows: Vec<PnlReportRow> = rows.into_values().collect();
+        rows.sort_by(|a, b| a.key.cmp(&b.key));
+        let mut totals = PnlReportRow { key: "TOTAL".to_string(), ..Default::default() };
+        for row in &rows {
+            totals.merge(row);
+        }
+        let mut unpriced_assets: Vec<String> = unpriced.into_iter().collect();
+        unpriced_assets.sort();
+
+        PnlReport {
+            method,
+            grouping,
+            from,
+            to,
+            generated_at: chrono::Utc::now().timestamp() as u64,
+            rows,
+            totals,
+            unpriced_assets,
+        }
+    }
+
+    /// One row per disposal, the shape tax forms ask for
+    pub async fn disposals_csv(&self, method: CostBasisMethod, from: u64, to: u64) -> String {
+        let mut csv = String::from(
+            "asset,direction,quantity,opened_at,closed_at,holding_period_secs,proceeds_usd,cost_basis_usd,realized_pnl_usd,wallet,venue,strategy,trade_id,lot_id,close_fill_id\n",
+        );
+        for disposal in self.get_disposals(method, from, to).await {
+            let opened = chrono::DateTime::from_timestamp(disposal.opened_at as i64, 0).map(|t| t.to_rfc3339()).unwrap_or_default();
+            let closed = chrono::DateTime::from_timestamp(disposal.closed_at as i64, 0).map(|t| t.to_rfc3339()).unwrap_or_default();
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                csv_field(&disposal.asset),
+                if disposal.direction == LotDirection::Long { "long" } else { "short" },
+                disposal.quantity,
+                opened,
+                closed,
+                disposal.holding_period_secs(),
+                disposal.proceeds_usd.round_dp(8),
+                disposal.cost_basis_usd.round_dp(8),
+                disposal.realized_pnl_usd.round_dp(8),
+                csv_field(&disposal.wallet),
+                csv_field(&disposal.venue),
+                csv_field(&disposal.strategy),
+                csv_field(disposal.trade_id.as_deref().unwrap_or("")),
+                csv_field(&disposal.lot_id),
+                csv_field(&disposal.close_fill_id),
+            ));
+        }
+        csv
+    }
+
+    pub async fn export_report(
+        &self,
+        path: &str,
+        method: CostBasisMethod,
+        grouping: ReportGrouping,
+        from: u64,
+        to: u64,
+        format: ReportFormat,
+    ) -> Result<()> {
+        let report = self.report(method, grouping, from, to).await;
+        std::fs::write(path, report.render(format)?)?;
+        info!("📒 Exported {:?} P&L report by {:?} ({} rows) to {}", method, grouping, report.rows.len(), path);
+        Ok(())
+    }
+}
+
+impl Clone for TaxLotLedger {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Default for TaxLotLedger {
+    fn default() -> Self {
+        Self::new(LedgerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(id: &str, side: FillSide, quantity: i64, price: i64, fee: i64, timestamp: u64) -> LedgerFill {
+        LedgerFill {
+            id: id.to_string(),
+            trade_id: None,
+            timestamp,
+            wallet: "vector".to_string(),
+            venue: "vector".to_string(),
+            strategy: "vector".to_string(),
+            asset: "TEST".to_string(),
+            side,
+            quantity: Decimal::from(quantity),
+            price_usd: Decimal::from(price),
+            fee_usd: Decimal::from(fee),
+        }
+    }
+
+    /// 10 @ 100 (+$10 fee), 10 @ 120, sell 15 @ 130 (+$15 fee)
+    async fn buy_buy_sell() -> TaxLotLedger {
+        let ledger = TaxLotLedger::new(LedgerConfig::default());
+        ledger.record_fill(fill("a", FillSide::Buy, 10, 100, 10, 1)).await.unwrap();
+        ledger.record_fill(fill("b", FillSide::Buy, 10, 120, 0, 2)).await.unwrap();
+        ledger.record_fill(fill("c", FillSide::Sell, 15, 130, 15, 3)).await.unwrap();
+        ledger
+    }
+
+    async fn assert_realized(method: CostBasisMethod, expected: Decimal) {
+        let ledger = buy_buy_sell().await;
+        let realized: Decimal = ledger.get_disposals(method, 0, u64::MAX).await.iter().map(|d| d.realized_pnl_usd).sum();
+        assert_eq!(realized, expected, "{:?}", method);
+        let open: Decimal = ledger.get_open_lots(method).await.iter().map(|l| l.remaining).sum();
+        assert_eq!(open, Decimal::from(5), "{:?}", method);
+    }
+
+    // Proceeds are 15 * 129 = 1935 under every method
+
+    #[tokio::test]
+    async fn fifo_closes_the_oldest_lot_first() {
+        assert_realized(CostBasisMethod::Fifo, Decimal::from(1935 - (1010 + 600))).await;
+    }
+
+    #[tokio::test]
+    async fn lifo_closes_the_newest_lot_first() {
+        assert_realized(CostBasisMethod::Lifo, Decimal::from(1935 - (1200 + 505))).await;
+    }
+
+    #[tokio::test]
+    async fn average_cost_pools_every_lot() {
+        // 2210 / 20 = 110.5 per unit, 1935 - 1657.5
+        assert_realized(CostBasisMethod::AverageCost, Decimal::new(2775, 1)).await;
+    }
+}
@@ -6,16 +6,15 @@ use std::sync::Arc;
 use std::time::Duration;
 use anyhow::Result;
 use rust_decimal::{Decimal, prelude::FromStr};
-use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, RwLock, Mutex};
 use log::{info, error, debug, warn};
 use chrono;
 use rust_decimal::prelude::{ToPrimitive, FromPrimitive};
-use async_trait::async_trait;
 use futures_util;
 use rand;
 
 // Import all modules
+mod types;
 mod market_data;
 mod arbitrage_engine;
 mod smart_contracts;
@@ -38,6 +37,32 @@ mod ml_models;
 mod risk_management;
 mod cross_chain;
 mod alpha_strategies;
+mod fee_model;
+mod opportunity_scoring;
+mod inventory;
+mod cex_dex_arbitrage;
+mod exchange_simulator;
+mod cex_trading;
+mod execution_coordinator;
+mod reconciliation;
+mod trigger_orders;
+mod paper_trading;
+mod circuit_breaker;
+mod solana_tx;
+mod evm_tx;
+mod bundles;
+mod wallet_auth;
+mod auth;
+mod keystore;
+mod balances;
+mod tx_policy;
+mod ledger;
+mod risk_stats;
+mod risk_gate;
+mod client_arbitrage;
+
+// Core data structures and exchange client traits
+pub use types::*;
 
 // Import specific items we need
 use dashboard_api::DashboardApiServer;
@@ -46,7 +71,7 @@ use flash_loan_simulator::{FlashLoanSimulator, FlashLoanSimulationRequest, Flash
 use ws_server::{WebSocketServer, AlphaStrategyUpdate};
 use mev_protection::MevDetection;
 use external_apis::{ExternalApiClient, ArbitrageOpportunity as ExternalArbitrageOpportunity};
-use trade_execution::{TradeExecutionEngine, TradeExecution, Portfolio, ExecutionMetrics};
+use trade_execution::{TradeExecutionEngine, TradeExecution, Portfolio, ExecutionMetrics, ExecutionStrategy};
 use universal_price_aggregator::{UniversalPriceAggregator, PriceBroadcaster, LiveArbitrageOpportunity};
 
 // New module imports
@@ -56,106 +81,32 @@ use trade_executor::{TradeExecutor, SmartOrderRouter};
 use websocket_feeds::{WebSocketFeedManager, DexWebSocketClient};
 use historical_data::{HistoricalDataStore, BacktestEngine};
 use ml_models::{PricePredictionModel, MEVDetectionModel, TradingSignalGenerator};
-use risk_management::{RiskManager, RiskProfile, PositionSizer, ExitStrategyManager};
+use risk_management::{CorrelationSnapshot, RiskManager, RiskProfile, PortfolioRisk, PositionSizer, ExitStrategyManager};
+use risk_gate::{PreTradeRiskGate, RiskDecision};
 use cross_chain::{CrossChainAggregator};
+use fee_model::{FeeModel, ProfitBreakdown};
+use opportunity_scoring::{OpportunityScorer, OpportunityScore};
+use inventory::InventoryManager;
+use cex_dex_arbitrage::CexDexArbitrageDetector;
+use exchange_simulator::{ExchangeSimulator, SimVenueKind};
+use cex_trading::TradingCexClient;
+use execution_coordinator::{ExecutionCoordinator, CexLegVenue, SimulatedLegVenue};
+use reconciliation::Reconciler;
+use trigger_orders::TriggerEngine;
+use paper_trading::{PaperAccountSummary, PaperTradingEngine, TradingMode};
+use circuit_breaker::{BreakerScope, BreakerTrip, CircuitBreaker};
+use bundles::{BundleManager, TrackedBundle};
+use auth::AuthManager;
+use keystore::{Keystore, KeystoreAccount};
+use solana_tx::{RpcSubmitter, SolanaTxSender};
+use tx_policy::{ApprovalRequest, TxPolicyEngine};
+use ledger::{CostBasisMethod, PnlReport, ReportGrouping, TaxLotLedger};
 use alpha_strategies::{
     JITLiquidityProvider, StatisticalArbitrageEngine, CrossChainArbitrageBot,
     MEVProtectionExtractor, LiquiditySniperBot, AdvancedOrderEngine,
     MarketMakingBot, SandwichProtector, YieldAggregator, OptionsTrader
 };
 
-// Core Data Structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PriceInfo {
-    pub exchange: String,
-    pub exchange_type: ExchangeType,
-    pub pair: String,
-    pub price: Decimal,
-    pub bid: Option<Decimal>,
-    pub ask: Option<Decimal>,
-    pub volume_24h: Option<Decimal>,
-    pub liquidity: Option<Decimal>,
-    pub timestamp: u64,
-    pub latency_ms: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum ExchangeType {
-    DEX,
-    CEX,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ArbitrageOpportunity {
-    pub id: String,
-    pub token_pair: String,
-    pub buy_exchange: String,
-    pub sell_exchange: String,
-    pub buy_price: Decimal,
-    pub sell_price: Decimal,
-    pub profit_percentage: Decimal,
-    pub estimated_profit_usd: Decimal,
-    pub max_trade_size: Decimal,
-    pub liquidity_score: f64,
-    pub risk_score: f64,
-    pub confidence: f64,
-    pub timestamp: u64,
-    pub expires_at: u64,
-    pub trade_route: Vec<TradeStep>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TradeStep {
-    pub exchange: String,
-    pub action: String, // "buy" | "sell" | "swap"
-    pub from_token: String,
-    pub to_token: String,
-    pub amount: Decimal,
-    pub price: Decimal,
-    pub fees: Decimal,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Order {
-    pub pair: String,
-    pub side: String, // "buy" | "sell"
-    pub amount: Decimal,
-    pub price: Option<Decimal>, // None for market orders
-    pub order_type: String, // "market" | "limit"
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderBook {
-    pub bids: Vec<OrderBookEntry>,
-    pub asks: Vec<OrderBookEntry>,
-    pub timestamp: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OrderBookEntry {
-    pub price: Decimal,
-    pub amount: Decimal,
-}
-
-// Traits for exchange clients
-#[async_trait]
-pub trait DexClient {
-    async fn get_price(&self, pair: &str) -> Result<PriceInfo>;
-    async fn get_liquidity(&self, pool: &str) -> Result<Decimal>;
-    async fn execute_swap(&self, trade: &TradeStep) -> Result<String>;
-    async fn get_pools(&self) -> Result<Vec<String>>;
-    fn name(&self) -> &str;
-}
-
-#[async_trait]
-pub trait CexClient {
-    async fn get_price(&self, pair: &str) -> Result<PriceInfo>;
-    async fn get_order_book(&self, pair: &str) -> Result<OrderBook>;
-    async fn place_order(&self, order: &Order) -> Result<String>;
-    async fn get_balance(&self, token: &str) -> Result<Decimal>;
-    fn name(&self) -> &str;
-}
-
 // Main DEXTER Platform with Real-time WebSocket Streaming
 pub struct DexterPlatform {
     // Core components
@@ -165,6 +116,9 @@ pub struct DexterPlatform {
     // Exchange clients
     dex_clients: HashMap<String, Box<dyn DexClient + Send + Sync>>,
     cex_clients: HashMap<String, Box<dyn CexClient + Send + Sync>>,
+    trading_clients: HashMap<String, Arc<dyn TradingCexClient>>,
+    execution_coordinator: Arc<ExecutionCoordinator>,
+    exchange_simulator: Option<Arc<ExchangeSimulator>>,
     
     // External API integration
     external_api_client: Arc<ExternalApiClient>,
@@ -176,12 +130,17 @@ pub struct DexterPlatform {
     mev_protection: Arc<MevProtectionEngine>,
     flash_loan_simulator: Arc<FlashLoanSimulator>,
     trade_execution_engine: Arc<TradeExecutionEngine>,
+    paper_trading: Arc<PaperTradingEngine>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    bundle_manager: Arc<BundleManager>,
     ws_server: Arc<RwLock<Option<Arc<WebSocketServer>>>>,
     
     // New advanced components
     dex_aggregator: Arc<DexAggregator>,
     wallet_manager: Arc<WalletManager>,
     wallet_security: Arc<WalletSecurity>,
+    auth_manager: Arc<AuthManager>,
+    keystore: Arc<Keystore>,
     trade_executor: Arc<TradeExecutor>,
     ws_feed_manager: Arc<WebSocketFeedManager>,
     historical_store: Arc<HistoricalDataStore>,
@@ -190,9 +149,15 @@ pub struct DexterPlatform {
     mev_detector: Arc<MEVDetectionModel>,
     signal_generator: Arc<TradingSignalGenerator>,
     risk_manager: Arc<RiskManager>,
+    risk_gate: Arc<PreTradeRiskGate>,
     position_sizer: Arc<PositionSizer>,
     exit_manager: Arc<ExitStrategyManager>,
     cross_chain: Arc<CrossChainAggregator>,
+    fee_model: Arc<FeeModel>,
+    opportunity_scorer: Arc<OpportunityScorer>,
+    inventory_manager: Arc<InventoryManager>,
+    reconciler: Arc<Reconciler>,
+    cex_dex_detector: Arc<CexDexArbitrageDetector>,
     
     // Alpha extraction strategies
     jit_liquidity: Arc<JITLiquidityProvider>,
@@ -201,6 +166,7 @@ pub struct DexterPlatform {
     mev_extractor: Arc<MEVProtectionExtractor>,
     liquidity_sniper: Arc<LiquiditySniperBot>,
     advanced_orders: Arc<AdvancedOrderEngine>,
+    trigger_engine: Arc<TriggerEngine>,
     market_maker: Arc<MarketMakingBot>,
     sandwich_protector: Arc<SandwichProtector>,
     yield_aggregator: Arc<YieldAggregator>,
@@ -226,6 +192,8 @@ pub struct PlatformConfig {
     pub supported_exchanges: Vec<String>,
     pub api_keys: HashMap<String, String>,
     pub websocket_port: u16,
+    /// Scenario file for simulated venues; when set, dex/cex clients are in-process simulators
+    pub simulation_scenario: Option<String>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -246,53 +214,95 @@ impl DexterPlatform {
         let (price_tx, _) = broadcast::channel(1000);
         let (opp_tx, _) = broadcast::channel(1000);
         
-        let universal_aggregator = Arc::new(UniversalPriceAggregator::new());
+        let fee_model = Arc::new(FeeModel::new());
+        let execution_coordinator = Arc::new(ExecutionCoordinator::new(fee_model.clone()));
+        let paper_trading = Arc::new(PaperTradingEngine::new(fee_model.clone()));
+        let circuit_breaker = Arc::new(CircuitBreaker::new());
+        let bundle_manager = Arc::new(BundleManager::from_env().await);
+        let wallet_security = Arc::new(WalletSecurity::new());
+        let auth_manager = Arc::new(AuthManager::from_env(wallet_security.clone()).await);
+        let external_api_client = Arc::new(ExternalApiClient::new());
+        let keystore = Arc::new(Keystore::from_env());
+        let wallet_manager = Arc::new(
+            WalletManager::new()
+                .with_policy_engine(Arc::new(TxPolicyEngine::from_env()))
+                .with_solana_signer(SolanaTxSender::new(Arc::new(RpcSubmitter::from_env())), keystore.clone()),
+        );
+        for provider in balances::providers_from_env() {
+            wallet_manager.add_balance_provider(provider).await;
+        }
+        wallet_manager.set_price_source(external_api_client.clone()).await;
+        let historical_store = Arc::new(HistoricalDataStore::new());
+        let risk_manager = Arc::new(
+            RiskManager::new(RiskProfile::default()).with_data_store(historical_store.clone()),
+        );
+        // One pre-trade gate in front of every order path
+        let risk_gate = Arc::new(PreTradeRiskGate::from_env(risk_manager.clone()));
+        let trade_executor = Arc::new(TradeExecutor::new(Arc::new(DexAggregator::new()), wallet_manager.clone(), risk_gate.clone()));
+        let inventory_manager = Arc::new(InventoryManager::new(fee_model.clone()));
+        let exit_manager = Arc::new(ExitStrategyManager::new(risk_manager.clone()));
+        let universal_aggregator = Arc::new(UniversalPriceAggregator::new(fee_model.clone()));
         let price_broadcaster_universal = Arc::new(PriceBroadcaster::new(universal_aggregator.clone()));
         
-        let platform = Self {
+        let mut platform = Self {
             price_feeds: Arc::new(RwLock::new(HashMap::new())),
             opportunities: Arc::new(RwLock::new(Vec::new())),
             dex_clients: HashMap::new(),
             cex_clients: HashMap::new(),
+            trading_clients: HashMap::new(),
+            execution_coordinator: execution_coordinator.clone(),
+            exchange_simulator: None,
             
             // External API integration
-            external_api_client: Arc::new(ExternalApiClient::new()),
+            external_api_client: external_api_client.clone(),
             universal_price_aggregator: universal_aggregator,
             price_broadcaster_universal,
             
             // Advanced Features
             dashboard_api: Arc::new(RwLock::new(None)),
-            mev_protection: Arc::new(MevProtectionEngine::new()),
-            flash_loan_simulator: Arc::new(FlashLoanSimulator::new()),
-            trade_execution_engine: Arc::new(TradeExecutionEngine::new()),
+            mev_protection: Arc::new(MevProtectionEngine::new().with_bundle_manager(bundle_manager.clone())),
+            flash_loan_simulator: Arc::new(FlashLoanSimulator::new(fee_model.clone(), risk_gate.clone())),
+            trade_execution_engine: Arc::new(
+                TradeExecutionEngine::new(fee_model.clone(), execution_coordinator.clone(), paper_trading.clone(), circuit_breaker.clone(), risk_gate.clone())
+                    .with_ledger(Arc::new(TaxLotLedger::from_env())),
+            ),
+            paper_trading,
+            circuit_breaker: circuit_breaker.clone(),
+            bundle_manager: bundle_manager.clone(),
             ws_server: Arc::new(RwLock::new(None)),
             
             // Initialize new components
             dex_aggregator: Arc::new(DexAggregator::new()),
-            wallet_manager: Arc::new(WalletManager::new()),
-            wallet_security: Arc::new(WalletSecurity::new()),
-            trade_executor: Arc::new(TradeExecutor::new(
-                Arc::new(DexAggregator::new()),
-                Arc::new(WalletManager::new()),
-            )),
+            wallet_manager,
+            wallet_security,
+            auth_manager,
+            keystore,
+            trade_executor: trade_executor.clone(),
             ws_feed_manager: Arc::new(WebSocketFeedManager::new()),
-            historical_store: Arc::new(HistoricalDataStore::new()),
-            backtest_engine: Arc::new(BacktestEngine::new(Arc::new(HistoricalDataStore::new()))),
+            historical_store: historical_store.clone(),
+            backtest_engine: Arc::new(BacktestEngine::new(historical_store)),
             price_predictor: Arc::new(PricePredictionModel::new()),
             mev_detector: Arc::new(MEVDetectionModel::new()),
             signal_generator: Arc::new(TradingSignalGenerator::new()),
-            risk_manager: Arc::new(RiskManager::new(RiskProfile::default())),
-            position_sizer: Arc::new(PositionSizer::new(Arc::new(RiskManager::new(RiskProfile::default())))),
-            exit_manager: Arc::new(ExitStrategyManager::new(Arc::new(RiskManager::new(RiskProfile::default())))),
-            cross_chain: Arc::new(CrossChainAggregator::new()),
+            risk_manager: risk_manager.clone(),
+            risk_gate: risk_gate.clone(),
+            position_sizer: Arc::new(PositionSizer::new(risk_manager)),
+            exit_manager: exit_manager.clone(),
+            cross_chain: Arc::new(CrossChainAggregator::new(fee_model.clone(), risk_gate)),
+            opportunity_scorer: Arc::new(OpportunityScorer::new(fee_model.clone())),
+            inventory_manager: inventory_manager.clone(),
+            reconciler: Arc::new(Reconciler::new(trade_executor.clone(), inventory_manager)),
+            cex_dex_detector: Arc::new(CexDexArbitrageDetector::new(external_api_client, fee_model.clone())),
+            fee_model,
             
             // Initialize alpha extraction strategies
             jit_liquidity: Arc::new(JITLiquidityProvider::new()),
             stat_arb_engine: Arc::new(StatisticalArbitrageEngine::new()),
             cross_chain_arb: Arc::new(CrossChainArbitrageBot::new()),
-            mev_extractor: Arc::new(MEVProtectionExtractor::new()),
+            mev_extractor: Arc::new(MEVProtectionExtractor::with_bundle_manager(bundle_manager)),
             liquidity_sniper: Arc::new(LiquiditySniperBot::new()),
-            advanced_orders: Arc::new(AdvancedOrderEngine::new()),
+            advanced_orders: Arc::new(AdvancedOrderEngine::new(trade_executor.clone()).with_circuit_breaker(circuit_breaker)),
+            trigger_engine: Arc::new(TriggerEngine::new(trade_executor, exit_manager)),
             market_maker: Arc::new(MarketMakingBot::new()),
             sandwich_protector: Arc::new(SandwichProtector::new()),
             yield_aggregator: Arc::new(YieldAggregator::new()),
@@ -305,9 +315,50 @@ impl DexterPlatform {
             metrics: Arc::new(Mutex::new(PlatformMetrics::default())),
         };
         
+        if let Some(path) = platform.config.simulation_scenario.clone() {
+            let simulator = ExchangeSimulator::from_file(&path)?;
+            simulator.register_fee_schedules(&platform.fee_model).await;
+            platform.dex_clients = simulator.dex_clients();
+            platform.cex_clients = simulator.cex_clients();
+            for venue in simulator.venues() {
+                if venue.kind() == SimVenueKind::Cex {
+                    platform.paper_trading.register_book_source(venue.name(), Arc::new(venue.clone())).await;
+                }
+                platform.execution_coordinator.register_venue(Arc::new(SimulatedLegVenue::new(venue))).await;
+            }
+            info!("🧪 Loaded simulation scenario '{}' ({} DEX, {} CEX venues)",
+                  simulator.name(), platform.dex_clients.len(), platform.cex_clients.len());
+            platform.exchange_simulator = Some(Arc::new(simulator));
+        } else {
+            platform.register_trading_clients().await;
+        }
+        
+        if let Some(password) = keystore::password_from_env() {
+            if let Err(e) = platform.keystore.migrate_env_keys(&password).await {
+                error!("❌ Failed to migrate plaintext keys into the keystore: {}", e);
+            }
+            match platform.keystore.unlock_all(&password).await {
+                Ok(accounts) => info!("🔐 Unlocked {} keystore account(s)", accounts.len()),
+                Err(e) => error!("❌ Failed to unlock keystore: {}", e),
+            }
+        }
+        
         Ok(platform)
     }
     
+    /// Register signed CEX clients for every exchange with credentials configured.
+    async fn register_trading_clients(&mut self) {
+        for client in cex_trading::clients_from_env() {
+            let name = client.name().to_string();
+            info!("🔐 Authenticated {} trading client enabled", name);
+            self.cex_clients.insert(name.clone(), Box::new(client.clone()));
+            self.execution_coordinator.register_venue(Arc::new(CexLegVenue::new(client.clone()))).await;
+            self.trade_executor.register_cex_client(client.clone()).await;
+            self.paper_trading.register_book_source(&name, Arc::new(client.clone())).await;
+            self.trading_clients.insert(name, client);
+        }
+    }
+    
     pub async fn start(self: Arc<Self>) -> Result<()> {
         info!("🚀 DEXTER v3.0 - Advanced Multi-Platform Trading System Starting!");
         info!("📡 Initializing real-time WebSocket streaming architecture...");
@@ -321,6 +372,81 @@ impl DexterPlatform {
             }
         });
         
+        info!("📦 Starting private bundle tracker...");
+        let bundle_manager = self.bundle_manager.clone();
+        tokio::spawn(async move {
+            bundle_manager.start().await;
+        });
+        
+        if let Some(simulator) = self.exchange_simulator.clone() {
+            info!("🧪 Starting exchange simulator scenario events...");
+            tokio::spawn(async move {
+                simulator.run_events().await;
+            });
+        }
+        
+        for client in self.trading_clients.values().cloned() {
+            tokio::spawn(async move {
+                match client.sync_clock().await {
+                    Ok(offset) => info!("⏱️ {} clock offset {}ms", client.name(), offset),
+                    Err(e) => warn!("⚠️ {} clock sync failed: {}", client.name(), e),
+                }
+            });
+        }
+        
+        info!("🧾 Starting advanced order scheduler...");
+        let advanced_orders = self.advanced_orders.clone();
+        tokio::spawn(async move {
+            advanced_orders.start().await;
+        });
+        
+        info!("🧻 Starting paper trading market data...");
+        let paper_trading = self.paper_trading.clone();
+        let paper_prices = self.price_broadcaster.subscribe();
+        tokio::spawn(async move {
+            paper_trading.start(paper_prices).await;
+        });
+        
+        info!("🛑 Starting circuit breaker monitor...");
+        let circuit_breaker = self.circuit_breaker.clone();
+        let breaker_prices = self.price_broadcaster.subscribe();
+        tokio::spawn(async move {
+            circuit_breaker.start(breaker_prices).await;
+        });
+        
+        info!("📈 Starting historical price recording for risk models...");
+        let historical_store = self.historical_store.clone();
+        let history_prices = self.price_broadcaster.subscribe();
+        tokio::spawn(async move {
+            historical_store.start(history_prices).await;
+        });
+        
+        info!("📉 Starting rolling correlation estimator...");
+        let risk_manager = self.risk_manager.clone();
+        tokio::spawn(async move {
+            risk_manager.start_correlation_refresh().await;
+        });
+        
+        info!("🎯 Starting stop-loss / take-profit trigger monitor...");
+        let trigger_engine = self.trigger_engine.clone();
+        let trigger_prices = self.price_broadcaster.subscribe();
+        tokio::spawn(async move {
+            trigger_engine.start(trigger_prices).await;
+        });
+        
+        info!("💼 Starting on-chain wallet balance refresh...");
+        tokio::spawn(self.wallet_manager.clone().start_balance_refresh());
+        
+        info!("📒 Starting tax-lot ledger mark price refresh...");
+        tokio::spawn(self.trade_execution_engine.ledger().start_mark_refresh(self.external_api_client.clone(), 30));
+        tokio::spawn(self.trade_execution_engine.paper_ledger().start_mark_refresh(self.external_api_client.clone(), 30));
+        
+        info!("🔎 Starting order and balance reconciliation...");
+        let reconciler = self.reconciler.clone();
+        tokio::spawn(async move {
+            reconciler.start().await;
+        });
+        
         info!("⚡ Starting Flash Loan Simulator...");
         let flash_simulator = self.flash_loan_simulator.clone();
         tokio::spawn(async move {
@@ -338,7 +464,14 @@ impl DexterPlatform {
         });
         
         info!("🌐 Starting Dashboard API Server (REST)...");
-        let dashboard_api = Arc::new(DashboardApiServer::new(3001, self.external_api_client.clone()));
+        let dashboard_api = Arc::new(
+            DashboardApiServer::new(3001, self.external_api_client.clone())
+                .with_circuit_breaker(self.circuit_breaker.clone())
+                .with_auth(self.auth_manager.clone())
+                .with_policy_engine(self.wallet_manager.policy_engine())
+                .with_ledger(self.trade_execution_engine.ledger())
+                .with_risk_gate(self.risk_gate.clone()),
+        );
         self.dashboard_api.write().await.replace(dashboard_api.clone());
         tokio::spawn(async move {
             if let Err(e) = dashboard_api.start().await {
@@ -362,6 +495,9 @@ impl DexterPlatform {
             self.universal_price_aggregator.clone(),
             self.price_broadcaster_universal.clone(),
         );
+        ws_server.set_circuit_breaker(self.circuit_breaker.clone());
+        ws_server.set_auth_manager(self.auth_manager.clone());
+        ws_server.set_wallet_manager(self.wallet_manager.clone());
         
         let ws_server_arc = Arc::new(ws_server);
         
@@ -401,6 +537,38 @@ impl DexterPlatform {
             }
         });
         
+        // Inventory arbitrage: only spreads fillable from pre-funded balances, plus rebalancing
+        info!("📦 Starting Inventory Arbitrage Monitor...");
+        let inventory = self.inventory_manager.clone();
+        let inventory_aggregator = self.universal_price_aggregator.clone();
+        tokio::spawn(async move {
+            let mut cycles: u64 = 0;
+            loop {
+                let opportunities = inventory_aggregator.get_top_opportunities(50).await;
+                let executable = inventory.executable_opportunities(&opportunities).await;
+                for opp in executable.iter().take(5) {
+                    info!("📦 INVENTORY ARB: {} {} buy {} / sell {} = ${:.2} net (limited by {})",
+                          opp.executable_amount,
+                          opp.opportunity.token_pair,
+                          opp.opportunity.buy_exchange.exchange,
+                          opp.opportunity.sell_exchange.exchange,
+                          opp.profit_breakdown.net_profit_usd,
+                          opp.limiting_balance);
+                }
+                
+                // Re-plan transfers once a minute
+                if cycles % 12 == 0 {
+                    for transfer in inventory.plan_rebalance().await {
+                        info!("🔁 Rebalance {} {} {} -> {} via {} (fee ${:.2}, ~{}s)",
+                              transfer.amount, transfer.asset, transfer.from_venue, transfer.to_venue,
+                              transfer.network, transfer.fee_usd, transfer.estimated_time_secs);
+                    }
+                }
+                cycles += 1;
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+        
         // 🎯 Start Alpha Extraction Strategies
         info!("🎯 Starting Alpha Extraction Strategies...");
         
@@ -523,6 +691,7 @@ impl DexterPlatform {
             interval.tick().await;
             
             // 🔥 GET REAL-TIME PRICES FROM EXTERNAL APIs 🔥
+            let fetch_started = std::time::Instant::now();
             let real_time_prices = match self.external_api_client.get_real_time_prices().await {
                 Ok(prices) => {
                     info!("✅ Successfully fetched real-time prices from external APIs");
@@ -540,6 +709,7 @@ impl DexterPlatform {
                     fallback_prices
                 }
             };
+            let fetch_latency_ms = fetch_started.elapsed().as_millis() as u64;
             
             // Generate price data for each pair and exchange using real-time data
             for pair in &pairs {
@@ -569,12 +739,8 @@ impl DexterPlatform {
                     let fluctuation = (rand::random::<f64>() - 0.5) * 0.005; // ±0.25% for real data
                     let current_price = base_price * (1.0 + fluctuation);
                     
-                    // Add exchange-specific spreads
-                    let spread = match *exchange {
-                        "Jupiter" | "Raydium" | "Orca" => 0.003, // 0.3% DEX spread
-                        "Binance" | "Coinbase" => 0.001,        // 0.1% CEX spread
-                        _ => 0.002,
-                    };
+                    // Quote spread follows the venue's fee schedule (pool fee for DEXs, maker fee for CEXs)
+                    let spread = fee_model::to_f64(self.fee_model.quoted_half_spread(exchange).await);
                     
                     let bid_price = current_price * (1.0 - spread);
                     let ask_price = current_price * (1.0 + spread);
@@ -582,9 +748,9 @@ impl DexterPlatform {
                     let price_info = PriceInfo {
                         exchange: exchange.to_string(),
                         exchange_type: if exchange.contains("Binance") || exchange.contains("Coinbase") { 
-                            ExchangeType::CEX 
+                            ExchangeType::Cex 
                         } else { 
-                            ExchangeType::DEX 
+                            ExchangeType::Dex 
                         },
                         pair: pair.to_string(),
                         price: Decimal::from_f64(current_price).unwrap_or_default(),
@@ -593,8 +759,9 @@ impl DexterPlatform {
                         volume_24h: Some(Decimal::from(rand::random::<u32>() % 1000000 + 100000)),
                         liquidity: Some(Decimal::from(rand::random::<u32>() % 5000000 + 1000000)),
                         timestamp: chrono::Utc::now().timestamp() as u64,
-                        latency_ms: rand::random::<u64>() % 100 + 10, // 10-110ms latency
+                        latency_ms: fetch_latency_ms,
                     };
+                    self.opportunity_scorer.record_quote(&price_info).await;
                     
                     // Update price feeds storage
                     let mut feeds = self.price_feeds.write().await;
@@ -627,6 +794,11 @@ impl DexterPlatform {
             let mock_opportunities = self.scan_cross_exchange_arbitrage().await?;
             all_opportunities.extend(mock_opportunities);
             
+            // Scan connected venue clients (real or simulated)
+            if !self.dex_clients.is_empty() || !self.cex_clients.is_empty() {
+                all_opportunities.extend(self.scan_client_arbitrage().await);
+            }
+            
             // Scan external APIs every 20th iteration (every 10 seconds) to respect rate limits
             external_api_counter += 1;
             if external_api_counter >= 20 {
@@ -644,6 +816,9 @@ impl DexterPlatform {
                 }
             }
             
+            // Rank by latency-adjusted expected value and drop stale or negative-EV spreads
+            let all_opportunities = self.opportunity_scorer.rank(all_opportunities).await;
+            
             if !all_opportunities.is_empty() {
                 info!("🚨 Found {} arbitrage opportunities - streaming to clients", all_opportunities.len());
                 
@@ -671,6 +846,43 @@ impl DexterPlatform {
         }
     }
     
+    /// Compare quotes from every connected DEX and CEX client for the same pair
+    async fn scan_client_arbitrage(&self) -> Vec<ArbitrageOpportunity> {
+        let mut pairs: Vec<String> = vec!["SOL/USDC".to_string(), "ETH/USDC".to_string(), "BTC/USDC".to_string()];
+        for client in self.dex_clients.values() {
+            if let Ok(pools) = client.get_pools().await {
+                pairs.extend(pools);
+            }
+        }
+        pairs.sort();
+        pairs.dedup();
+        
+        let mut opportunities = Vec::new();
+        for pair in &pairs {
+            let mut quotes = Vec::new();
+            for client in self.dex_clients.values() {
+                if let Ok(quote) = client.get_price(pair).await {
+                    quotes.push(quote);
+                }
+            }
+            for client in self.cex_clients.values() {
+                if let Ok(quote) = client.get_price(pair).await {
+                    quotes.push(quote);
+                }
+            }
+            for quote in &quotes {
+                self.opportunity_scorer.record_quote(quote).await;
+                let _ = self.price_broadcaster.send(quote.clone());
+            }
+            
+            opportunities.extend(
+                client_arbitrage::find_opportunities(&self.fee_model, pair, &quotes, Decimal::from(10000)).await,
+            );
+        }
+        
+        opportunities
+    }
+    
     async fn scan_cross_exchange_arbitrage(&self) -> Result<Vec<ArbitrageOpportunity>> {
         let mut opportunities = Vec::new();
         
@@ -692,13 +904,25 @@ impl DexterPlatform {
         };
         
         info!("💰 Using SOL price: ${:.2} for arbitrage detection", sol_price);
+        self.fee_model.update_native_price("solana", fee_model::dec(sol_price)).await;
         
         // Generate realistic mock arbitrage opportunities using real-time market prices
         let price_variation = (rand::random::<f64>() - 0.5) * 0.01; // ±0.5% variation
         
         let buy_price = sol_price * (1.0 + price_variation - 0.002); // Slightly lower for buy
         let sell_price = sol_price * (1.0 + price_variation + 0.002); // Slightly higher for sell
-        let profit_percentage = ((sell_price - buy_price) / buy_price) * 100.0;
+        
+        let trade_size_usd = Decimal::from(10000);
+        let breakdown = self.price_opportunity(
+            "Raydium",
+            "Jupiter",
+            Decimal::from_f64(buy_price).unwrap_or_default(),
+            Decimal::from_f64(sell_price).unwrap_or_default(),
+            trade_size_usd,
+            "SOL",
+        ).await;
+        let buy_fees = breakdown.costs_for_venue("Raydium");
+        let sell_fees = breakdown.costs_for_venue("Jupiter");
         
         let mock_opportunity = ArbitrageOpportunity {
             id: format!("arb_{}", chrono::Utc::now().timestamp_millis()),
@@ -707,9 +931,9 @@ impl DexterPlatform {
             sell_exchange: "Jupiter".to_string(),
             buy_price: Decimal::from_f64(buy_price).unwrap_or_default(),
             sell_price: Decimal::from_f64(sell_price).unwrap_or_default(),
-            profit_percentage: Decimal::from_f64(profit_percentage).unwrap_or_default(),
-            estimated_profit_usd: Decimal::from_f64(profit_percentage * 100.0).unwrap_or_default(), // Profit on $10k
-            max_trade_size: Decimal::from_str("10000").unwrap(),
+            profit_percentage: breakdown.net_profit_percentage,
+            estimated_profit_usd: breakdown.net_profit_usd,
+            max_trade_size: trade_size_usd,
             liquidity_score: 0.85,
             risk_score: 0.25,
             confidence: 0.92,
@@ -721,20 +945,22 @@ impl DexterPlatform {
                     action: "buy".to_string(),
                     from_token: "USDC".to_string(),
                     to_token: "SOL".to_string(),
-                    amount: Decimal::from_str("1000").unwrap(),
+                    amount: trade_size_usd,
                     price: Decimal::from_f64(buy_price).unwrap_or_default(),
-                    fees: Decimal::from_str("0.25").unwrap(),
+                    fees: buy_fees,
                 },
                 TradeStep {
                     exchange: "Jupiter".to_string(),
                     action: "sell".to_string(),
                     from_token: "SOL".to_string(),
                     to_token: "USDC".to_string(),
-                    amount: Decimal::from_f64(1000.0 / buy_price).unwrap_or_default(), // SOL amount
+                    amount: trade_size_usd / Decimal::from_f64(buy_price).unwrap_or(Decimal::ONE), // SOL amount
                     price: Decimal::from_f64(sell_price).unwrap_or_default(),
-                    fees: Decimal::from_str("0.30").unwrap(),
+                    fees: sell_fees,
                 },
             ],
+            profit_breakdown: breakdown,
+            score: OpportunityScore::default(),
         };
         
         opportunities.push(mock_opportunity);
@@ -742,6 +968,21 @@ impl DexterPlatform {
         Ok(opportunities)
     }
     
+    /// Price both legs of an opportunity through the fee model for a given notional size
+    async fn price_opportunity(
+        &self,
+        buy_exchange: &str,
+        sell_exchange: &str,
+        buy_price: Decimal,
+        sell_price: Decimal,
+        notional_usd: Decimal,
+        base_asset: &str,
+    ) -> ProfitBreakdown {
+        client_arbitrage::price_opportunity(
+            &self.fee_model, buy_exchange, sell_exchange, buy_price, sell_price, notional_usd, base_asset,
+        ).await
+    }
+    
     /// Scan external APIs for real arbitrage opportunities
     async fn scan_external_api_opportunities(&self) -> Result<Vec<ArbitrageOpportunity>> {
         let mut opportunities = Vec::new();
@@ -784,6 +1025,17 @@ impl DexterPlatform {
             Ok(jupiter_opportunities) => {
                 for ext_opp in jupiter_opportunities {
                     // Convert external opportunity to internal format
+                    let base_asset = ext_opp.pair.split('/').next().unwrap_or("SOL").to_string();
+                    let breakdown = self.price_opportunity(
+                        &ext_opp.buy_exchange,
+                        &ext_opp.sell_exchange,
+                        ext_opp.buy_price,
+                        ext_opp.sell_price,
+                        ext_opp.required_capital,
+                        &base_asset,
+                    ).await;
+                    let buy_fees = breakdown.costs_for_venue(&ext_opp.buy_exchange);
+                    
                     let internal_opp = ArbitrageOpportunity {
                         id: ext_opp.id.clone(),
                         token_pair: ext_opp.pair.clone(),
@@ -791,8 +1043,8 @@ impl DexterPlatform {
                         sell_exchange: ext_opp.sell_exchange.clone(),
                         buy_price: ext_opp.buy_price,
                         sell_price: ext_opp.sell_price,
-                        profit_percentage: Decimal::from_f64(ext_opp.profit_percentage).unwrap_or_default(),
-                        estimated_profit_usd: ext_opp.estimated_profit,
+                        profit_percentage: breakdown.net_profit_percentage,
+                        estimated_profit_usd: breakdown.net_profit_usd,
                         max_trade_size: ext_opp.required_capital,
                         liquidity_score: 0.8, // High for Jupiter
                         risk_score: 0.2, // Low risk for Jupiter
@@ -807,9 +1059,11 @@ impl DexterPlatform {
                                 to_token: "Output".to_string(),
                                 amount: ext_opp.required_capital,
                                 price: ext_opp.buy_price,
-                                fees: Decimal::from_str("0.25").unwrap_or_default(),
+                                fees: buy_fees,
                             },
                         ],
+                        profit_breakdown: breakdown,
+                        score: OpportunityScore::default(),
                     };
                     
                     opportunities.push(internal_opp);
@@ -832,6 +1086,18 @@ impl DexterPlatform {
         ).await {
             Ok(cross_dex_opportunities) => {
                 for ext_opp in cross_dex_opportunities {
+                    let base_asset = ext_opp.pair.split('/').next().unwrap_or("SOL").to_string();
+                    let breakdown = self.price_opportunity(
+                        &ext_opp.buy_exchange,
+                        &ext_opp.sell_exchange,
+                        ext_opp.buy_price,
+                        ext_opp.sell_price,
+                        ext_opp.required_capital,
+                        &base_asset,
+                    ).await;
+                    let buy_fees = breakdown.costs_for_venue(&ext_opp.buy_exchange);
+                    let sell_fees = breakdown.costs_for_venue(&ext_opp.sell_exchange);
+                    
                     let internal_opp = ArbitrageOpportunity {
                         id: ext_opp.id.clone(),
                         token_pair: ext_opp.pair.clone(),
@@ -839,8 +1105,8 @@ impl DexterPlatform {
                         sell_exchange: ext_opp.sell_exchange.clone(),
                         buy_price: ext_opp.buy_price,
                         sell_price: ext_opp.sell_price,
-                        profit_percentage: Decimal::from_f64(ext_opp.profit_percentage).unwrap_or_default(),
-                        estimated_profit_usd: ext_opp.estimated_profit,
+                        profit_percentage: breakdown.net_profit_percentage,
+                        estimated_profit_usd: breakdown.net_profit_usd,
                         max_trade_size: ext_opp.required_capital,
                         liquidity_score: 0.7, // Medium for cross-DEX
                         risk_score: 0.4, // Higher risk for cross-DEX
@@ -855,7 +1121,7 @@ impl DexterPlatform {
                                 to_token: "SOL".to_string(),
                                 amount: ext_opp.required_capital,
                                 price: ext_opp.buy_price,
-                                fees: Decimal::from_str("0.30").unwrap_or_default(),
+                                fees: buy_fees,
                             },
                             TradeStep {
                                 exchange: ext_opp.sell_exchange.clone(),
@@ -864,9 +1130,11 @@ impl DexterPlatform {
                                 to_token: "USDC".to_string(),
                                 amount: ext_opp.required_capital,
                                 price: ext_opp.sell_price,
-                                fees: Decimal::from_str("0.30").unwrap_or_default(),
+                                fees: sell_fees,
                             },
                         ],
+                        profit_breakdown: breakdown,
+                        score: OpportunityScore::default(),
                     };
                     
                     opportunities.push(internal_opp);
@@ -887,6 +1155,17 @@ impl DexterPlatform {
         ).await {
             Ok(dexscreener_opportunities) => {
                 for ext_opp in dexscreener_opportunities {
+                    let base_asset = ext_opp.pair.split('/').next().unwrap_or("SOL").to_string();
+                    let breakdown = self.price_opportunity(
+                        &ext_opp.buy_exchange,
+                        &ext_opp.sell_exchange,
+                        ext_opp.buy_price,
+                        ext_opp.sell_price,
+                        ext_opp.required_capital,
+                        &base_asset,
+                    ).await;
+                    let buy_fees = breakdown.costs_for_venue(&ext_opp.buy_exchange);
+                    
                     let internal_opp = ArbitrageOpportunity {
                         id: ext_opp.id.clone(),
                         token_pair: ext_opp.pair.clone(),
@@ -894,8 +1173,8 @@ impl DexterPlatform {
                         sell_exchange: ext_opp.sell_exchange.clone(),
                         buy_price: ext_opp.buy_price,
                         sell_price: ext_opp.sell_price,
-                        profit_percentage: Decimal::from_f64(ext_opp.profit_percentage).unwrap_or_default(),
-                        estimated_profit_usd: ext_opp.estimated_profit,
+                        profit_percentage: breakdown.net_profit_percentage,
+                        estimated_profit_usd: breakdown.net_profit_usd,
                         max_trade_size: ext_opp.required_capital,
                         liquidity_score: 0.75, // Good for DEX Screener
                         risk_score: 0.3, // Medium risk
@@ -910,9 +1189,11 @@ impl DexterPlatform {
                                 to_token: "Token".to_string(),
                                 amount: ext_opp.required_capital,
                                 price: ext_opp.buy_price,
-                                fees: Decimal::from_str("0.25").unwrap_or_default(),
+                                fees: buy_fees,
                             },
                         ],
+                        profit_breakdown: breakdown,
+                        score: OpportunityScore::default(),
                     };
                     
                     opportunities.push(internal_opp);
@@ -938,6 +1219,17 @@ impl DexterPlatform {
                 ).await {
                     Ok(bitquery_opportunities) => {
                         for ext_opp in bitquery_opportunities {
+                            let base_asset = ext_opp.pair.split('/').next().unwrap_or("SOL").to_string();
+                            let breakdown = self.price_opportunity(
+                                &ext_opp.buy_exchange,
+                                &ext_opp.sell_exchange,
+                                ext_opp.buy_price,
+                                ext_opp.sell_price,
+                                ext_opp.required_capital,
+                                &base_asset,
+                            ).await;
+                            let buy_fees = breakdown.costs_for_venue(&ext_opp.buy_exchange);
+                            
                             let internal_opp = ArbitrageOpportunity {
                                 id: ext_opp.id.clone(),
                                 token_pair: ext_opp.pair.clone(),
@@ -945,8 +1237,8 @@ impl DexterPlatform {
                                 sell_exchange: ext_opp.sell_exchange.clone(),
                                 buy_price: ext_opp.buy_price,
                                 sell_price: ext_opp.sell_price,
-                                profit_percentage: Decimal::from_f64(ext_opp.profit_percentage).unwrap_or_default(),
-                                estimated_profit_usd: ext_opp.estimated_profit,
+                                profit_percentage: breakdown.net_profit_percentage,
+                                estimated_profit_usd: breakdown.net_profit_usd,
                                 max_trade_size: ext_opp.required_capital,
                                 liquidity_score: 0.85, // High for Bitquery historical data
                                 risk_score: 0.25, // Lower risk with historical data
@@ -961,9 +1253,11 @@ impl DexterPlatform {
                                         to_token: "Token".to_string(),
                                         amount: ext_opp.required_capital,
                                         price: ext_opp.buy_price,
-                                        fees: Decimal::from_str("0.30").unwrap_or_default(),
+                                        fees: buy_fees,
                                     },
                                 ],
+                                profit_breakdown: breakdown,
+                                score: OpportunityScore::default(),
                             };
                             
                             opportunities.push(internal_opp);
@@ -979,6 +1273,63 @@ impl DexterPlatform {
             }
         }
         
+        // Scan CEX-DEX: sized Jupiter/Raydium/Orca swaps hedged against CEX book depth
+        match self.cex_dex_detector.detect().await {
+            Ok(cex_dex_opportunities) => {
+                for cex_dex in cex_dex_opportunities {
+                    let breakdown = cex_dex.profit_breakdown.clone();
+                    let buy_fees = breakdown.costs_for_venue(cex_dex.buy_exchange());
+                    let sell_fees = breakdown.costs_for_venue(cex_dex.sell_exchange());
+                    
+                    let internal_opp = ArbitrageOpportunity {
+                        id: cex_dex.id.clone(),
+                        token_pair: format!("{}/USDC", cex_dex.asset),
+                        buy_exchange: cex_dex.buy_exchange().to_string(),
+                        sell_exchange: cex_dex.sell_exchange().to_string(),
+                        buy_price: cex_dex.buy_price(),
+                        sell_price: cex_dex.sell_price(),
+                        profit_percentage: breakdown.net_profit_percentage,
+                        estimated_profit_usd: breakdown.net_profit_usd,
+                        max_trade_size: cex_dex.size_usd,
+                        liquidity_score: 0.8, // Sized against live book depth and routed quotes
+                        risk_score: 0.3,
+                        confidence: 0.8,
+                        timestamp: cex_dex.timestamp,
+                        expires_at: cex_dex.timestamp + 10, // Quotes are only good for a few slots
+                        trade_route: vec![
+                            TradeStep {
+                                exchange: cex_dex.buy_exchange().to_string(),
+                                action: "buy".to_string(),
+                                from_token: "USDC".to_string(),
+                                to_token: cex_dex.asset.clone(),
+                                amount: cex_dex.base_amount,
+                                price: cex_dex.buy_price(),
+                                fees: buy_fees,
+                            },
+                            TradeStep {
+                                exchange: cex_dex.sell_exchange().to_string(),
+                                action: "sell".to_string(),
+                                from_token: cex_dex.asset.clone(),
+                                to_token: "USDC".to_string(),
+                                amount: cex_dex.base_amount,
+                                price: cex_dex.sell_price(),
+                                fees: sell_fees,
+                            },
+                        ],
+                        profit_breakdown: breakdown,
+                        score: OpportunityScore::default(),
+                    };
+                    
+                    info!("🔀 CEX-DEX opportunity integrated: {} {:?} via {:?} = ${:.2} net",
+                          cex_dex.asset, cex_dex.direction, cex_dex.hedge_mode, internal_opp.estimated_profit_usd);
+                    opportunities.push(internal_opp);
+                }
+            }
+            Err(e) => {
+                error!("❌ Failed to scan CEX-DEX opportunities: {}", e);
+            }
+        }
+        
         Ok(opportunities)
     }
     
@@ -1109,6 +1460,61 @@ impl DexterPlatform {
         self.trade_execution_engine.set_simulation_mode(enabled).await
     }
     
+    pub async fn set_strategy_mode(&self, strategy: ExecutionStrategy, mode: Option<TradingMode>) {
+        self.trade_execution_engine.set_strategy_mode(strategy, mode).await
+    }
+    
+    pub async fn get_paper_account(&self) -> PaperAccountSummary {
+        self.paper_trading.account_summary().await
+    }
+    
+    pub async fn get_paper_portfolio(&self) -> Portfolio {
+        self.trade_execution_engine.get_paper_portfolio().await
+    }
+    
+    pub async fn kill_switch(&self, scope: BreakerScope, detail: &str, actor: &str) {
+        self.circuit_breaker.kill(scope, detail, actor).await
+    }
+    
+    pub async fn reset_breaker(&self, scope: &BreakerScope, actor: &str) -> bool {
+        self.circuit_breaker.reset(scope, actor).await
+    }
+    
+    pub async fn get_circuit_breakers(&self) -> Vec<BreakerTrip> {
+        self.circuit_breaker.get_trips().await
+    }
+    
+    pub async fn get_bundles(&self, limit: usize) -> Vec<TrackedBundle> {
+        self.bundle_manager.get_bundles(limit).await
+    }
+    
+    /// Names and addresses only; key material never leaves the keystore
+    pub async fn get_keystore_accounts(&self) -> Vec<KeystoreAccount> {
+        self.keystore.unlocked_accounts().await
+    }
+    
+    /// VaR/CVaR (historical, parametric, Monte Carlo) and the Kupiec backtest for open positions
+    pub async fn get_portfolio_risk(&self, portfolio_value: f64) -> PortfolioRisk {
+        self.risk_manager.calculate_portfolio_risk(portfolio_value).await
+    }
+
+    pub async fn get_correlation_snapshot(&self) -> Option<CorrelationSnapshot> {
+        self.risk_manager.get_correlation_snapshot().await
+    }
+
+    pub async fn get_risk_decisions(&self, limit: usize, rejected_only: bool) -> Vec<RiskDecision> {
+        self.risk_gate.get_decisions(limit, rejected_only).await
+    }
+    
+    /// Realized P&L over [from, to) plus current unrealized, grouped by wallet, venue or strategy
+    pub async fn get_pnl_report(&self, method: CostBasisMethod, grouping: ReportGrouping, from: u64, to: u64) -> PnlReport {
+        self.trade_execution_engine.ledger().report(method, grouping, from, to).await
+    }
+    
+    pub async fn get_pending_approvals(&self) -> Vec<ApprovalRequest> {
+        self.wallet_manager.policy_engine().get_approvals(true).await
+    }
+    
     pub async fn get_platform_metrics(&self) -> PlatformMetrics {
         self.metrics.lock().await.clone()
     }
@@ -1134,6 +1540,7 @@ async fn main() -> Result<()> {
         supported_exchanges: vec!["binance".to_string(), "coinbase".to_string(), "jupiter".to_string()],
         api_keys: HashMap::new(),
         websocket_port: 3002, // WebSocket on port 3002, REST on 3001, Next.js on 3000
+        simulation_scenario: std::env::var("DEXTER_SIMULATION_SCENARIO").ok(),
     };
     
     let platform = Arc::new(DexterPlatform::new(config).await?);
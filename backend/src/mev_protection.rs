@@ -9,6 +9,8 @@ use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use log::{info, warn, error, debug};
 
+use crate::bundles::{Bundle, BundleManager};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MevTransaction {
     pub hash: String,
@@ -101,6 +103,9 @@ pub struct MevProtectionEngine {
     
     // Statistics
     stats: Arc<Mutex<MevProtectionStats>>,
+    
+    // Private relay routing for the PrivateMempool rule
+    bundle_manager: Option<Arc<BundleManager>>,
 }
 
 #[derive(Debug, Default)]
@@ -127,9 +132,15 @@ impl MevProtectionEngine {
             detection_sensitivity: Arc::new(RwLock::new(0.8)),
             protection_enabled: Arc::new(Mutex::new(true)),
             stats: Arc::new(Mutex::new(MevProtectionStats::default())),
+            bundle_manager: None,
         }
     }
 
+    pub fn with_bundle_manager(mut self, bundle_manager: Arc<BundleManager>) -> Self {
+        self.bundle_manager = Some(bundle_manager);
+        self
+    }
+
     pub async fn start(&self) -> Result<()> {
         info!("🛡️ Starting MEV Protection Engine...");
         
@@ -252,6 +263,52 @@ impl MevProtectionEngine {
     }
 }
 
+impl MevProtectionEngine {
+    /// Apply the PrivateMempool rule: bundles worth at least `min_value` go to private relays.
+    /// An unsuccessful result tells the caller to fall back to public submission.
+    pub async fn route_private(&self, bundle: Bundle, value_usd: Decimal) -> Result<ProtectionResult> {
+        let rule = self.protection_rules.read().await.values()
+            .find(|r| r.enabled && matches!(r.rule_type, ProtectionRuleType::PrivateMempool))
+            .cloned();
+        let min_value = rule.as_ref()
+            .and_then(|r| r.parameters.get("min_value"))
+            .and_then(|v| Decimal::from_str(v).ok())
+            .unwrap_or(Decimal::ZERO);
+
+        let mut result = ProtectionResult {
+            transaction_hash: bundle.id.clone(),
+            protection_applied: Vec::new(),
+            mev_risk_reduced: 0.0,
+            additional_cost: Decimal::ZERO,
+            success: false,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        };
+
+        let manager = match (&self.bundle_manager, rule) {
+            (Some(manager), Some(_)) if self.is_protection_enabled().await && value_usd >= min_value => manager,
+            _ => {
+                result.protection_applied.push("Public mempool".to_string());
+                return Ok(result);
+            }
+        };
+
+        let submitted = manager.send(bundle).await?;
+        result.protection_applied = submitted.iter()
+            .map(|b| format!("Private relay: {}", b.relay))
+            .collect();
+        result.mev_risk_reduced = 0.9;
+        result.success = true;
+
+        let mut stats = self.stats.lock().await;
+        stats.attacks_prevented += 1;
+        stats.total_value_protected += value_usd;
+        drop(stats);
+
+        self.protection_results.write().await.push(result.clone());
+        Ok(result)
+    }
+}
+
 impl Clone for MevProtectionEngine {
     fn clone(&self) -> Self {
         Self {
@@ -266,6 +323,7 @@ impl Clone for MevProtectionEngine {
             detection_sensitivity: self.detection_sensitivity.clone(),
             protection_enabled: self.protection_enabled.clone(),
             stats: self.stats.clone(),
+            bundle_manager: self.bundle_manager.clone(),
         }
     }
 }
@@ -0,0 +1,402 @@
+// Opportunity Scoring - Latency-aware expected value and time-to-live estimation
+// Combines venue fetch latency, quote age, spread half-life and volatility into a fill probability
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use log::debug;
+
+use crate::fee_model::{self, FeeModel, VenueKind};
+
+// ============================================================================
+// SCORING DATA STRUCTURES
+// ============================================================================
+
+/// Probability that an opportunity is still there when our orders land, and what it is worth.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpportunityScore {
+    pub fill_probability: f64,
+    pub persistence_probability: f64,
+    pub volatility_probability: f64,
+    pub horizon_ms: u64,
+    pub quote_age_ms: u64,
+    pub fetch_latency_ms: u64,
+    pub execution_latency_ms: u64,
+    pub spread_half_life_ms: u64,
+    pub volatility_per_sqrt_sec: f64,
+    pub expected_value_usd: Decimal,
+    pub ttl_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    /// Smoothing factor for per-venue fetch latency
+    pub latency_alpha: f64,
+    /// Smoothing factor for per-venue return variance
+    pub volatility_alpha: f64,
+    /// Spread samples kept per (pair, venue, venue) for the half-life fit
+    pub spread_window: usize,
+    pub min_spread_samples: usize,
+    pub default_half_life_ms: u64,
+    pub max_half_life_ms: u64,
+    pub default_fetch_latency_ms: u64,
+    /// Order placement to fill on a centralized venue
+    pub cex_execution_ms: u64,
+    /// Confirmation time per chain for DEX legs
+    pub chain_execution_ms: HashMap<String, u64>,
+    /// Opportunity expires once persistence probability drops below this
+    pub min_persistence_probability: f64,
+    pub min_expected_value_usd: Decimal,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        let mut chain_execution_ms = HashMap::new();
+        chain_execution_ms.insert("solana".to_string(), 400);
+        chain_execution_ms.insert("ethereum".to_string(), 12_000);
+        chain_execution_ms.insert("bsc".to_string(), 3_000);
+        chain_execution_ms.insert("polygon".to_string(), 2_000);
+        chain_execution_ms.insert("avalanche".to_string(), 2_000);
+
+        Self {
+            latency_alpha: 0.2,
+            volatility_alpha: 0.1,
+            spread_window: 120,
+            min_spread_samples: 10,
+            default_half_life_ms: 2_000,
+            max_half_life_ms: 60_000,
+            default_fetch_latency_ms: 100,
+            cex_execution_ms: 50,
+            chain_execution_ms,
+            min_persistence_probability: 0.5,
+            min_expected_value_usd: Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct VenueStats {
+    latency_ewma_ms: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct QuoteState {
+    mid: f64,
+    timestamp_ms: u64,
+    /// EWMA of squared log returns per second
+    variance_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct SpreadSample {
+    spread: f64,
+    timestamp_ms: u64,
+}
+
+type SpreadHistory = HashMap<(String, String, String), VecDeque<SpreadSample>>; // (pair, venue_a, venue_b)
+
+// ============================================================================
+// OPPORTUNITY SCORER
+// ============================================================================
+
+pub struct OpportunityScorer {
+    config: Arc<RwLock<ScoringConfig>>,
+    venues: Arc<RwLock<HashMap<String, VenueStats>>>,
+    quotes: Arc<RwLock<HashMap<(String, String), QuoteState>>>, // (pair, venue)
+    spreads: Arc<RwLock<SpreadHistory>>,
+    fee_model: Arc<FeeModel>,
+}
+
+impl OpportunityScorer {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(ScoringConfig::default())),
+            venues: Arc::new(RwLock::new(HashMap::new())),
+            quotes: Arc::new(RwLock::new(HashMap::new())),
+            spreads: Arc::new(RwLock::new(HashMap::new())),
+            fee_model,
+        }
+    }
+
+    pub async fn get_config(&self) -> ScoringConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: ScoringConfig) {
+        *self.config.write().await = config;
+    }
+
+    // ------------------------------------------------------------------------
+    // Observations
+    // ------------------------------------------------------------------------
+
+    /// Record a quote as fetched: updates venue latency, venue volatility and cross-venue spreads.
+    pub async fn record_quote(&self, quote: &crate::PriceInfo) {
+        let config = self.config.read().await.clone();
+        let mid = match (quote.bid, quote.ask) {
+            (Some(bid), Some(ask)) if bid > Decimal::ZERO && ask > Decimal::ZERO => {
+                fee_model::to_f64((bid + ask) / Decimal::from(2))
+            }
+            _ => fee_model::to_f64(quote.price),
+        };
+        if mid <= 0.0 {
+            return;
+        }
+        let timestamp_ms = quote.timestamp * 1000;
+
+        {
+            let mut venues = self.venues.write().await;
+            let stats = venues.entry(quote.exchange.clone()).or_default();
+            stats.latency_ewma_ms = Some(match stats.latency_ewma_ms {
+                Some(prev) => prev + config.latency_alpha * (quote.latency_ms as f64 - prev),
+                None => quote.latency_ms as f64,
+            });
+        }
+
+        let mut quotes = self.quotes.write().await;
+        let key = (quote.pair.clone(), quote.exchange.clone());
+        let variance_rate = match quotes.get(&key) {
+            Some(prev) if timestamp_ms > prev.timestamp_ms => {
+                let dt_sec = (timestamp_ms - prev.timestamp_ms) as f64 / 1000.0;
+                let r = (mid / prev.mid).ln();
+                let sample = r * r / dt_sec;
+                Some(match prev.variance_rate {
+                    Some(v) => v + config.volatility_alpha * (sample - v),
+                    None => sample,
+                })
+            }
+            Some(prev) => prev.variance_rate,
+            None => None,
+        };
+        quotes.insert(key, QuoteState { mid, timestamp_ms, variance_rate });
+
+        // Relative mid difference against every other venue quoting this pair
+        let others: Vec<(String, f64)> = quotes.iter()
+            .filter(|((pair, venue), _)| pair == &quote.pair && venue != &quote.exchange)
+            .map(|((_, venue), state)| (venue.clone(), state.mid))
+            .collect();
+        drop(quotes);
+
+        let mut spreads = self.spreads.write().await;
+        for (other, other_mid) in others {
+            let (key, spread) = if quote.exchange < other {
+                ((quote.pair.clone(), quote.exchange.clone(), other), (mid - other_mid) / other_mid)
+            } else {
+                ((quote.pair.clone(), other, quote.exchange.clone()), (other_mid - mid) / mid)
+            };
+            let series = spreads.entry(key).or_insert_with(VecDeque::new);
+            if series.back().map(|s| s.timestamp_ms >= timestamp_ms).unwrap_or(false) {
+                continue;
+            }
+            series.push_back(SpreadSample { spread, timestamp_ms });
+            while series.len() > config.spread_window {
+                series.pop_front();
+            }
+        }
+    }
+
+    /// Smoothed fetch latency for a venue
+    pub async fn venue_latency_ms(&self, venue: &str) -> u64 {
+        let default = self.config.read().await.default_fetch_latency_ms;
+        self.venues.read().await
+            .get(venue)
+            .and_then(|s| s.latency_ewma_ms)
+            .map(|l| l.round() as u64)
+            .unwrap_or(default)
+    }
+
+    /// Half-life of the spread between two venues from an AR(1) fit of the spread series.
+    pub async fn spread_half_life_ms(&self, pair: &str, venue_a: &str, venue_b: &str) -> u64 {
+        let config = self.config.read().await.clone();
+        let key = if venue_a < venue_b {
+            (pair.to_string(), venue_a.to_string(), venue_b.to_string())
+        } else {
+            (pair.to_string(), venue_b.to_string(), venue_a.to_string())
+        };
+
+        let spreads = self.spreads.read().await;
+        let series = match spreads.get(&key) {
+            Some(series) if series.len() >= config.min_spread_samples => series,
+            _ => return config.default_half_life_ms,
+        };
+
+        let mut num = 0.0;
+        let mut den = 0.0;
+        let mut dt_total = 0u64;
+        for (prev, next) in series.iter().zip(series.iter().skip(1)) {
+            num += next.spread * prev.spread;
+            den += prev.spread * prev.spread;
+            dt_total += next.timestamp_ms - prev.timestamp_ms;
+        }
+        let dt_ms = dt_total as f64 / (series.len() - 1) as f64;
+        if den <= 0.0 || dt_ms <= 0.0 {
+            return config.default_half_life_ms;
+        }
+
+        let phi = num / den;
+        let half_life = if phi <= 0.0 {
+            dt_ms / 2.0 // Spread flips sign between samples
+        } else if phi >= 1.0 {
+            config.max_half_life_ms as f64
+        } else {
+            -std::f64::consts::LN_2 / phi.ln() * dt_ms
+        };
+
+        (half_life.round() as u64).clamp(1, config.max_half_life_ms)
+    }
+
+    /// Volatility of a venue's mid price, per square-root second
+    pub async fn venue_volatility(&self, pair: &str, venue: &str) -> f64 {
+        self.quotes.read().await
+            .get(&(pair.to_string(), venue.to_string()))
+            .and_then(|q| q.variance_rate)
+            .map(f64::sqrt)
+            .unwrap_or(0.0)
+    }
+
+    async fn execution_latency_ms(&self, venue: &str, config: &ScoringConfig) -> u64 {
+        let schedule = self.fee_model.schedule(venue).await;
+        match schedule.kind {
+            VenueKind::Cex => config.cex_execution_ms,
+            VenueKind::Dex => schedule.chain
+                .and_then(|chain| config.chain_execution_ms.get(&chain).copied())
+                .unwrap_or(config.cex_execution_ms),
+        }
+    }
+
+    async fn quote_age_ms(&self, pair: &str, venue: &str, fallback_timestamp: u64, now_ms: u64) -> u64 {
+        let timestamp_ms = self.quotes.read().await
+            .get(&(pair.to_string(), venue.to_string()))
+            .map(|q| q.timestamp_ms)
+            .unwrap_or(fallback_timestamp * 1000);
+        now_ms.saturating_sub(timestamp_ms)
+    }
+
+    // ------------------------------------------------------------------------
+    // Scoring
+    // ------------------------------------------------------------------------
+
+    pub async fn score(&self, opportunity: &crate::ArbitrageOpportunity) -> OpportunityScore {
+        let config = self.config.read().await.clone();
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        let pair = &opportunity.token_pair;
+        let buy = &opportunity.buy_exchange;
+        let sell = &opportunity.sell_exchange;
+
+        let quote_age_ms = self.quote_age_ms(pair, buy, opportunity.timestamp, now_ms).await
+            .max(self.quote_age_ms(pair, sell, opportunity.timestamp, now_ms).await);
+        let fetch_latency_ms = self.venue_latency_ms(buy).await
+            .max(self.venue_latency_ms(sell).await);
+        let execution_latency_ms = self.execution_latency_ms(buy, &config).await
+            .max(self.execution_latency_ms(sell, &config).await);
+        let horizon_ms = quote_age_ms + fetch_latency_ms + execution_latency_ms;
+
+        let half_life_ms = self.spread_half_life_ms(pair, buy, sell).await;
+        let persistence_probability = 0.5f64.powf(horizon_ms as f64 / half_life_ms as f64);
+
+        // Chance that adverse price moves over the horizon do not eat the net edge
+        let vol_buy = self.venue_volatility(pair, buy).await;
+        let vol_sell = self.venue_volatility(pair, sell).await;
+        let volatility = (vol_buy * vol_buy + vol_sell * vol_sell).sqrt();
+        let breakdown = &opportunity.profit_breakdown;
+        let edge = if breakdown.notional_usd > Decimal::ZERO {
+            fee_model::to_f64(breakdown.net_profit_usd / breakdown.notional_usd)
+        } else {
+            0.0
+        };
+        let sigma = volatility * (horizon_ms as f64 / 1000.0).sqrt();
+        let volatility_probability = if sigma > 0.0 {
+            normal_cdf(edge / sigma)
+        } else if edge > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        let fill_probability = persistence_probability * volatility_probability;
+
+        // A missed spread still costs the fees and gas of the leg that filled plus its unwind
+        let failure_cost = breakdown.total_costs_usd;
+        let p = fee_model::dec(fill_probability);
+        let expected_value_usd = p * breakdown.net_profit_usd - (Decimal::ONE - p) * failure_cost;
+
+        let ttl_ms = (half_life_ms as f64 * (1.0 / config.min_persistence_probability).log2()
+            - horizon_ms as f64).max(0.0) as u64;
+
+        OpportunityScore {
+            fill_probability,
+            persistence_probability,
+            volatility_probability,
+            horizon_ms,
+            quote_age_ms,
+            fetch_latency_ms,
+            execution_latency_ms,
+            spread_half_life_ms: half_life_ms,
+            volatility_per_sqrt_sec: volatility,
+            expected_value_usd,
+            ttl_ms,
+        }
+    }
+
+    /// Score an opportunity in place, replacing its confidence, risk score and expiry.
+    pub async fn apply(&self, opportunity: &mut crate::ArbitrageOpportunity) {
+        let score = self.score(opportunity).await;
+        let now = chrono::Utc::now().timestamp() as u64;
+
+        opportunity.confidence = score.fill_probability;
+        opportunity.risk_score = 1.0 - score.fill_probability;
+        opportunity.expires_at = now + score.ttl_ms / 1000;
+        opportunity.score = score;
+    }
+
+    /// Score, drop negative expected value, and rank best first.
+    pub async fn rank(&self, opportunities: Vec<crate::ArbitrageOpportunity>) -> Vec<crate::ArbitrageOpportunity> {
+        let min_expected_value = self.config.read().await.min_expected_value_usd;
+        let total = opportunities.len();
+
+        let mut ranked = Vec::with_capacity(total);
+        for mut opportunity in opportunities {
+            self.apply(&mut opportunity).await;
+            if opportunity.score.expected_value_usd > min_expected_value && opportunity.score.ttl_ms > 0 {
+                ranked.push(opportunity);
+            }
+        }
+        ranked.sort_by_key(|opportunity| Reverse(opportunity.score.expected_value_usd));
+
+        debug!("📐 Scored {} opportunities, {} with positive expected value", total, ranked.len());
+        ranked
+    }
+}
+
+impl Clone for OpportunityScorer {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            venues: self.venues.clone(),
+            quotes: self.quotes.clone(),
+            spreads: self.spreads.clone(),
+            fee_model: self.fee_model.clone(),
+        }
+    }
+}
+
+impl Default for OpportunityScorer {
+    fn default() -> Self {
+        Self::new(Arc::new(FeeModel::new()))
+    }
+}
+
+/// Standard normal CDF (Abramowitz & Stegun 7.1.26)
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
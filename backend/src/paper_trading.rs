@@ -0,0 +1,505 @@
+// Paper Trading - Virtual per-venue accounts filled against live or replayed market data
+// Walks order books and constant-product pool state for realistic slippage, charges venue fees and gas, reports paper P&L
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use log::{info, warn, debug};
+
+use crate::{CexClient, ExchangeType, OrderBook, PriceInfo};
+use crate::execution_coordinator::{ExecutionCoordinator, LegFill, LegRequest, LegVenue};
+use crate::fee_model::{FeeModel, GasOperation, Liquidity};
+
+// ============================================================================
+// PAPER ACCOUNT DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TradingMode {
+    Live,
+    Paper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperConfig {
+    pub initial_balances: HashMap<String, Decimal>, // Seeded on every venue the first time it is used
+    pub max_quote_age_secs: u64,
+    pub default_liquidity_usd: Decimal, // Pool depth assumed when a quote reports none
+    pub simulate_latency: bool,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        let initial_balances = [
+            ("USDC", Decimal::from(50_000)),
+            ("USDT", Decimal::from(50_000)),
+            ("USD", Decimal::from(50_000)),
+            ("SOL", Decimal::from(250)),
+            ("ETH", Decimal::from(10)),
+            ("BTC", Decimal::new(5, 1)),
+        ]
+        .into_iter()
+        .map(|(asset, amount)| (asset.to_string(), amount))
+        .collect();
+
+        Self {
+            initial_balances,
+            max_quote_age_secs: 30,
+            default_liquidity_usd: Decimal::from(1_000_000),
+            simulate_latency: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperFill {
+    pub order_id: String,
+    pub venue: String,
+    pub pair: String,
+    pub side: String,
+    pub amount: Decimal,
+    pub average_price: Decimal,
+    pub reference_price: Decimal, // Top of book / pool spot before the fill
+    pub fee_usd: Decimal,
+    pub gas_usd: Decimal,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperBalance {
+    pub venue: String,
+    pub asset: String,
+    pub amount: Decimal,
+    pub initial: Decimal,
+    pub value_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountSummary {
+    pub balances: Vec<PaperBalance>,
+    pub equity_usd: Decimal,
+    pub initial_equity_usd: Decimal, // Seeded balances marked at current prices
+    pub pnl_usd: Decimal,
+    pub fees_usd: Decimal,
+    pub gas_usd: Decimal,
+    pub slippage_usd: Decimal,
+    pub fills: usize,
+    pub unpriced_assets: Vec<String>,
+}
+
+// ============================================================================
+// PAPER TRADING ENGINE
+// ============================================================================
+
+pub struct PaperTradingEngine {
+    balances: Arc<RwLock<HashMap<(String, String), Decimal>>>, // (venue, asset)
+    initial: Arc<RwLock<HashMap<(String, String), Decimal>>>,
+    fills: Arc<RwLock<Vec<PaperFill>>>,
+    quotes: Arc<RwLock<HashMap<(String, String), PriceInfo>>>, // (venue, pair)
+    books: Arc<RwLock<HashMap<String, Arc<dyn CexClient + Send + Sync>>>>,
+    config: Arc<RwLock<PaperConfig>>,
+    fee_model: Arc<FeeModel>,
+    coordinator: Arc<ExecutionCoordinator>,
+}
+
+impl PaperTradingEngine {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
+        Self {
+            balances: Arc::new(RwLock::new(HashMap::new())),
+            initial: Arc::new(RwLock::new(HashMap::new())),
+            fills: Arc::new(RwLock::new(Vec::new())),
+            quotes: Arc::new(RwLock::new(HashMap::new())),
+            books: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(PaperConfig::default())),
+            coordinator: Arc::new(ExecutionCoordinator::new(fee_model.clone())),
+            fee_model,
+        }
+    }
+
+    pub async fn get_config(&self) -> PaperConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: PaperConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Coordinator whose venues all fill against the paper account
+    pub fn coordinator(&self) -> Arc<ExecutionCoordinator> {
+        self.coordinator.clone()
+    }
+
+    /// Fill this venue's paper orders against a real (or replayed) order book instead of its last quote
+    pub async fn register_book_source(&self, venue: &str, client: Arc<dyn CexClient + Send + Sync>) {
+        self.books.write().await.insert(venue.to_string(), client);
+        self.ensure_venue(venue, false).await;
+    }
+
+    /// Track quotes from the platform price stream, registering a paper venue for each new exchange
+    pub async fn start(&self, mut prices: broadcast::Receiver<PriceInfo>) {
+        loop {
+            match prices.recv().await {
+                Ok(quote) => self.record_quote(quote).await,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Paper trading skipped {} price updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub async fn record_quote(&self, quote: PriceInfo) {
+        let atomic = matches!(quote.exchange_type, ExchangeType::Dex);
+        let key = (quote.exchange.clone(), quote.pair.clone());
+        let is_new_venue = !self.quotes.read().await.keys().any(|(venue, _)| venue == &key.0);
+        self.quotes.write().await.insert(key.clone(), quote);
+        if is_new_venue {
+            self.ensure_venue(&key.0, atomic).await;
+        }
+    }
+
+    async fn ensure_venue(&self, venue: &str, atomic: bool) {
+        if self.coordinator.can_execute(venue, venue, true).await {
+            return;
+        }
+        let paper_venue = PaperLegVenue {
+            name: venue.to_string(),
+            atomic,
+            engine: self.clone(),
+        };
+        self.coordinator.register_venue(Arc::new(paper_venue)).await;
+    }
+
+    pub async fn set_balance(&self, venue: &str, asset: &str, amount: Decimal) {
+        let key = (venue.to_string(), asset.to_uppercase());
+        self.initial.write().await.insert(key.clone(), amount);
+        self.balances.write().await.insert(key, amount);
+    }
+
+    pub async fn get_balance(&self, venue: &str, asset: &str) -> Decimal {
+        self.seed_venue(venue).await;
+        self.balances.read().await
+            .get(&(venue.to_string(), asset.to_uppercase()))
+            .copied()
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Drop all paper balances and fills; venues are re-seeded on next use
+    pub async fn reset(&self) {
+        self.balances.write().await.clear();
+        self.initial.write().await.clear();
+        self.fills.write().await.clear();
+        info!("🧻 Paper trading account reset");
+    }
+
+    pub async fn get_fills(&self, limit: usize) -> Vec<PaperFill> {
+        self.fills.read().await.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Mark every paper balance to USD; P&L is measured against the seeded balances at the same prices
+    pub async fn account_summary(&self) -> PaperAccountSummary {
+        let balances = self.balances.read().await.clone();
+        let initial = self.initial.read().await.clone();
+        let fills = self.fills.read().await;
+
+        let mut summary = PaperAccountSummary {
+            balances: Vec::new(),
+            equity_usd: Decimal::ZERO,
+            initial_equity_usd: Decimal::ZERO,
+            pnl_usd: Decimal::ZERO,
+            fees_usd: fills.iter().map(|f| f.fee_usd).sum(),
+            gas_usd: fills.iter().map(|f| f.gas_usd).sum(),
+            slippage_usd: fills.iter().map(|f| ((f.average_price - f.reference_price) * f.amount).abs()).sum(),
+            fills: fills.len(),
+            unpriced_assets: Vec::new(),
+        };
+        drop(fills);
+
+        let mut keys: Vec<_> = balances.keys().chain(initial.keys()).cloned().collect();
+        keys.sort();
+        keys.dedup();
+        for (venue, asset) in keys {
+            let amount = balances.get(&(venue.clone(), asset.clone())).copied().unwrap_or(Decimal::ZERO);
+            let seeded = initial.get(&(venue.clone(), asset.clone())).copied().unwrap_or(Decimal::ZERO);
+            let price = self.asset_price_usd(&asset).await;
+            match price {
+                Some(price) => {
+                    summary.equity_usd += amount * price;
+                    summary.initial_equity_usd += seeded * price;
+                }
+                None if !summary.unpriced_assets.contains(&asset) => summary.unpriced_assets.push(asset.clone()),
+                None => {}
+            }
+            summary.balances.push(PaperBalance {
+                venue,
+                asset,
+                amount,
+                initial: seeded,
+                value_usd: price.map(|p| amount * p),
+            });
+        }
+        summary.pnl_usd = summary.equity_usd - summary.initial_equity_usd;
+        summary
+    }
+
+    async fn asset_price_usd(&self, asset: &str) -> Option<Decimal> {
+        if is_usd_stable(asset) {
+            return Some(Decimal::ONE);
+        }
+        let quotes = self.quotes.read().await;
+        let prices: Vec<Decimal> = quotes.values()
+            .filter(|q| {
+                let (base, quote) = split_pair(&q.pair);
+                base.eq_ignore_ascii_case(asset) && is_usd_stable(quote)
+            })
+            .map(|q| q.price)
+            .filter(|p| *p > Decimal::ZERO)
+            .collect();
+        if prices.is_empty() {
+            return None;
+        }
+        Some(prices.iter().copied().sum::<Decimal>() / Decimal::from(prices.len()))
+    }
+
+    async fn seed_venue(&self, venue: &str) {
+        let seeded = self.initial.read().await.keys().any(|(v, _)| v == venue);
+        if seeded {
+            return;
+        }
+        let config = self.config.read().await.clone();
+        let mut balances = self.balances.write().await;
+        let mut initial = self.initial.write().await;
+        for (asset, amount) in config.initial_balances {
+            let key = (venue.to_string(), asset.to_uppercase());
+            initial.entry(key.clone()).or_insert(amount);
+            balances.entry(key).or_insert(amount);
+        }
+    }
+
+    /// Price the leg against the venue's book (or pool implied by its last quote) and settle it in the paper account
+    async fn fill(&self, venue: &str, atomic: bool, leg: &LegRequest) -> Result<LegFill> {
+        let config = self.config.read().await.clone();
+        let book_source = self.books.read().await.get(venue).cloned();
+        let quote = self.quotes.read().await.get(&(venue.to_string(), leg.pair.clone())).cloned();
+
+        if config.simulate_latency {
+            if let Some(q) = &quote {
+                tokio::time::sleep(tokio::time::Duration::from_millis(q.latency_ms.min(2_000))).await;
+            }
+        }
+
+        let (filled, cost, reference) = match book_source {
+            Some(client) => {
+                let book = client.get_order_book(&leg.pair).await?;
+                walk_book(&book, leg)?
+            }
+            None => {
+                let quote = quote.ok_or_else(|| anyhow!("No paper market data for {} on {}", leg.pair, venue))?;
+                let age = (chrono::Utc::now().timestamp() as u64).saturating_sub(quote.timestamp);
+                if age > config.max_quote_age_secs {
+                    return Err(anyhow!("Paper quote for {} on {} is {}s old", leg.pair, venue, age));
+                }
+                constant_product_fill(&quote, leg, config.default_liquidity_usd)?
+            }
+        };
+        if filled <= Decimal::ZERO {
+            return Err(anyhow!("{} has no paper liquidity for {} {} within limit", venue, leg.side, leg.pair));
+        }
+        if atomic && filled < leg.amount {
+            return Err(anyhow!("Paper swap on {} would only fill {} of {}", venue, filled, leg.amount));
+        }
+
+        let average_price = cost / filled;
+        let fee_usd = self.fee_model.trading_fee_usd(venue, cost, Liquidity::Taker).await;
+        let gas_usd = match self.fee_model.schedule(venue).await.chain {
+            Some(chain) => self.fee_model.gas_cost_usd(&chain, GasOperation::Swap).await,
+            None => Decimal::ZERO,
+        };
+
+        self.seed_venue(venue).await;
+        let (base, quote_asset) = split_pair(&leg.pair);
+        let base_key = (venue.to_string(), base.to_uppercase());
+        let quote_key = (venue.to_string(), quote_asset.to_uppercase());
+        {
+            let mut balances = self.balances.write().await;
+            let base_balance = balances.get(&base_key).copied().unwrap_or(Decimal::ZERO);
+            let quote_balance = balances.get(&quote_key).copied().unwrap_or(Decimal::ZERO);
+            // Fees and gas are charged in the quote asset
+            if leg.side == "buy" {
+                let debit = cost + fee_usd + gas_usd;
+                if quote_balance < debit {
+                    return Err(anyhow!("Insufficient paper {} on {}: have {}, need {}", quote_asset, venue, quote_balance, debit));
+                }
+                balances.insert(quote_key, quote_balance - debit);
+                balances.insert(base_key, base_balance + filled);
+            } else {
+                if base_balance < filled {
+                    return Err(anyhow!("Insufficient paper {} on {}: have {}, need {}", base, venue, base_balance, filled));
+                }
+                balances.insert(base_key, base_balance - filled);
+                balances.insert(quote_key, quote_balance + cost - fee_usd - gas_usd);
+            }
+        }
+
+        let order_id = format!("paper_{}", uuid::Uuid::new_v4().simple());
+        debug!("📝 Paper {} {} {} on {} @ {} (ref {})", leg.side, filled, leg.pair, venue, average_price, reference);
+        self.fills.write().await.push(PaperFill {
+            order_id: order_id.clone(),
+            venue: venue.to_string(),
+            pair: leg.pair.clone(),
+            side: leg.side.clone(),
+            amount: filled,
+            average_price,
+            reference_price: reference,
+            fee_usd,
+            gas_usd,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        });
+
+        Ok(LegFill {
+            order_id: Some(order_id),
+            filled_amount: filled,
+            average_price: Some(average_price),
+        })
+    }
+}
+
+impl Clone for PaperTradingEngine {
+    fn clone(&self) -> Self {
+        Self {
+            balances: self.balances.clone(),
+            initial: self.initial.clone(),
+            fills: self.fills.clone(),
+            quotes: self.quotes.clone(),
+            books: self.books.clone(),
+            config: self.config.clone(),
+            fee_model: self.fee_model.clone(),
+            coordinator: self.coordinator.clone(),
+        }
+    }
+}
+
+impl Default for PaperTradingEngine {
+    fn default() -> Self {
+        Self::new(Arc::new(FeeModel::new()))
+    }
+}
+
+// ============================================================================
+// PAPER LEG VENUE
+// ============================================================================
+
+/// Coordinator venue backed by the paper account; shares the real venue's name so plans route unchanged
+pub struct PaperLegVenue {
+    name: String,
+    atomic: bool,
+    engine: PaperTradingEngine,
+}
+
+#[async_trait]
+impl LegVenue for PaperLegVenue {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn is_atomic(&self) -> bool {
+        self.atomic
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+
+    async fn expected_latency_ms(&self) -> u64 {
+        self.engine.quotes.read().await
+            .iter()
+            .filter(|((venue, _), _)| venue == &self.name)
+            .map(|(_, q)| q.latency_ms)
+            .max()
+            .unwrap_or(50)
+    }
+
+    async fn execute(&self, leg: &LegRequest) -> Result<LegFill> {
+        match self.engine.fill(&self.name, self.atomic, leg).await {
+            Ok(fill) => Ok(fill),
+            Err(e) => {
+                warn!("⚠️ Paper {} leg on {} rejected: {}", leg.side, self.name, e);
+                Err(e)
+            }
+        }
+    }
+}
+
+// ============================================================================
+// FILL SIMULATION
+// ============================================================================
+
+/// Take levels up to the limit price; returns (base filled, quote cost, top-of-book price)
+fn walk_book(book: &OrderBook, leg: &LegRequest) -> Result<(Decimal, Decimal, Decimal)> {
+    let levels = if leg.side == "buy" { &book.asks } else { &book.bids };
+    let reference = levels.first()
+        .map(|l| l.price)
+        .ok_or_else(|| anyhow!("Empty {} side for {}", leg.side, leg.pair))?;
+
+    let mut remaining = leg.amount;
+    let mut cost = Decimal::ZERO;
+    for level in levels {
+        let within_limit = match leg.limit_price {
+            Some(limit) if leg.side == "buy" => level.price <= limit,
+            Some(limit) => level.price >= limit,
+            None => true,
+        };
+        if !within_limit || remaining <= Decimal::ZERO {
+            break;
+        }
+        let take = remaining.min(level.amount);
+        cost += take * level.price;
+        remaining -= take;
+    }
+    Ok((leg.amount - remaining, cost, reference))
+}
+
+/// Constant-product pool sized from the quote's reported liquidity, starting from its bid/ask
+fn constant_product_fill(quote: &PriceInfo, leg: &LegRequest, default_liquidity: Decimal) -> Result<(Decimal, Decimal, Decimal)> {
+    let reference = if leg.side == "buy" { quote.ask } else { quote.bid }.unwrap_or(quote.price);
+    if reference <= Decimal::ZERO {
+        return Err(anyhow!("No usable price for {} on {}", quote.pair, quote.exchange));
+    }
+    let liquidity = quote.liquidity.filter(|l| *l > Decimal::ZERO).unwrap_or(default_liquidity);
+    let reserve_quote = liquidity / Decimal::from(2);
+    let reserve_base = reserve_quote / reference;
+    let k = reserve_base * reserve_quote;
+
+    let amount = leg.amount;
+    let cost = if leg.side == "buy" {
+        if amount >= reserve_base {
+            return Err(anyhow!("Paper buy of {} exceeds {} pool depth", amount, quote.pair));
+        }
+        k / (reserve_base - amount) - reserve_quote
+    } else {
+        reserve_quote - k / (reserve_base + amount)
+    };
+
+    let average = cost / amount;
+    let within_limit = match leg.limit_price {
+        Some(limit) if leg.side == "buy" => average <= limit,
+        Some(limit) => average >= limit,
+        None => true,
+    };
+    if !within_limit {
+        return Ok((Decimal::ZERO, Decimal::ZERO, reference));
+    }
+    Ok((amount, cost, reference))
+}
+
+fn split_pair(pair: &str) -> (&str, &str) {
+    pair.split_once('/').unwrap_or((pair, "USDC"))
+}
+
+fn is_usd_stable(asset: &str) -> bool {
+    matches!(asset.to_uppercase().as_str(), "USD" | "USDC" | "USDT" | "DAI" | "BUSD")
+}
@@ -0,0 +1,329 @@
+// Reconciliation - Periodic comparison of local orders and inventory against venue state
+// Flags drift between what we think happened and what the exchange reports, and optionally repairs it
+
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use anyhow::Result;
+use log::{info, warn, debug};
+
+use crate::cex_trading::{CexOrderState, CexOrderStatus};
+use crate::inventory::InventoryManager;
+use crate::trade_executor::{OrderSide, OrderStatus, TradeExecutor, TradeOrder};
+
+// ============================================================================
+// CONFIGURATION & REPORTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationConfig {
+    pub interval_secs: u64,
+    pub balance_tolerance_pct: Decimal, // Relative drift ignored, percent of the venue balance
+    pub balance_tolerance_abs: Decimal, // Absolute drift ignored, units of the asset
+    pub expiry_grace_secs: u64,         // Past the deadline before an unacknowledged order is expired
+    pub auto_repair: bool,
+    pub max_reports: usize,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 30,
+            balance_tolerance_pct: Decimal::new(5, 1), // 0.5%
+            balance_tolerance_abs: Decimal::new(1, 6),
+            expiry_grace_secs: 30,
+            auto_repair: true,
+            max_reports: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DriftKind {
+    OrderStatus,   // Venue state differs from the local state
+    OrderFill,     // Same state, different filled amount
+    OrderExpired,  // Deadline passed without a venue acknowledgement
+    OrderMissing,  // Venue no longer knows the order
+    Balance,       // Inventory differs from the venue account
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftRecord {
+    pub kind: DriftKind,
+    pub venue: String,
+    pub reference: String, // Order id or asset
+    pub local: String,
+    pub remote: String,
+    pub repaired: bool,
+    pub error: Option<String>,
+    pub detected_at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub started_at: u64,
+    pub orders_checked: usize,
+    pub balances_checked: usize,
+    pub drifts: Vec<DriftRecord>,
+    pub errors: Vec<String>,
+}
+
+// ============================================================================
+// RECONCILER
+// ============================================================================
+
+pub struct Reconciler {
+    trade_executor: Arc<TradeExecutor>,
+    inventory: Arc<InventoryManager>,
+    config: Arc<RwLock<ReconciliationConfig>>,
+    reports: Arc<RwLock<Vec<DriftRecord>>>,
+    last_report: Arc<RwLock<Option<ReconciliationReport>>>,
+}
+
+impl Reconciler {
+    pub fn new(trade_executor: Arc<TradeExecutor>, inventory: Arc<InventoryManager>) -> Self {
+        Self {
+            trade_executor,
+            inventory,
+            config: Arc::new(RwLock::new(ReconciliationConfig::default())),
+            reports: Arc::new(RwLock::new(Vec::new())),
+            last_report: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub async fn get_config(&self) -> ReconciliationConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: ReconciliationConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub async fn start(&self) {
+        loop {
+            let interval = self.config.read().await.interval_secs.max(1);
+            tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+            if let Err(e) = self.reconcile_once().await {
+                warn!("⚠️ Reconciliation pass failed: {}", e);
+            }
+        }
+    }
+
+    /// One full pass over open orders and venue balances.
+    pub async fn reconcile_once(&self) -> Result<ReconciliationReport> {
+        let config = self.config.read().await.clone();
+        let mut report = ReconciliationReport {
+            started_at: chrono::Utc::now().timestamp() as u64,
+            ..Default::default()
+        };
+
+        for order in self.trade_executor.get_open_orders().await {
+            report.orders_checked += 1;
+            if let Err(e) = self.reconcile_order(&order, &config, &mut report).await {
+                report.errors.push(format!("{}: {}", order.id, e));
+            }
+        }
+
+        for client in self.trade_executor.cex_clients().await {
+            match client.get_balances().await {
+                Ok(balances) => {
+                    for balance in balances {
+                        report.balances_checked += 1;
+                        self.reconcile_balance(client.name(), &balance.asset, balance.available, &config, &mut report).await;
+                    }
+                }
+                Err(e) => report.errors.push(format!("{} balances: {}", client.name(), e)),
+            }
+        }
+
+        if report.drifts.is_empty() {
+            debug!("🔎 Reconciled {} orders and {} balances, no drift", report.orders_checked, report.balances_checked);
+        } else {
+            warn!("⚠️ Reconciliation found {} drifts ({} repaired)",
+                  report.drifts.len(), report.drifts.iter().filter(|d| d.repaired).count());
+        }
+
+        let mut reports = self.reports.write().await;
+        reports.extend(report.drifts.iter().cloned());
+        let excess = reports.len().saturating_sub(config.max_reports);
+        reports.drain(..excess);
+        drop(reports);
+
+        *self.last_report.write().await = Some(report.clone());
+        Ok(report)
+    }
+
+    pub async fn get_reports(&self, limit: usize) -> Vec<DriftRecord> {
+        let reports = self.reports.read().await;
+        reports.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub async fn get_last_report(&self) -> Option<ReconciliationReport> {
+        self.last_report.read().await.clone()
+    }
+
+    async fn reconcile_order(
+        &self,
+        order: &TradeOrder,
+        config: &ReconciliationConfig,
+        report: &mut ReconciliationReport,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let client = match &order.dex {
+            Some(venue) => self.trade_executor.cex_client(venue).await,
+            None => None,
+        };
+
+        let (client, venue_order_id) = match (client, &order.venue_order_id) {
+            (Some(client), Some(id)) => (client, id.clone()),
+            // Never acknowledged by a venue we can query: only the deadline can settle it
+            _ => {
+                if now > order.deadline + config.expiry_grace_secs {
+                    let venue = order.dex.clone().unwrap_or_default();
+                    self.record_order_drift(
+                        order, DriftKind::OrderExpired, &venue, "deadline passed".to_string(),
+                        OrderStatus::Expired, None, config, report,
+                    ).await;
+                }
+                return Ok(());
+            }
+        };
+
+        let (pair, _) = order.cex_pair_and_size();
+        let remote = match client.get_order(&pair, &venue_order_id).await {
+            Ok(remote) => remote,
+            Err(e) if e.to_string().to_lowercase().contains("not found") || e.to_string().contains("-2013") => {
+                self.record_order_drift(
+                    order, DriftKind::OrderMissing, client.name(), "unknown to venue".to_string(),
+                    OrderStatus::Expired, None, config, report,
+                ).await;
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let remote_status = local_status(remote.state);
+        let remote_filled = filled_input(order, &remote);
+        let fill_drift = (remote_filled - order.filled_amount).abs() > 1e-9;
+        if remote_status == order.status && !fill_drift {
+            return Ok(());
+        }
+
+        let kind = if remote_status == order.status { DriftKind::OrderFill } else { DriftKind::OrderStatus };
+        let remote_desc = format!("{:?} filled {}", remote.state, remote.filled_amount);
+        self.record_order_drift(order, kind, client.name(), remote_desc, remote_status, Some(remote_filled), config, report).await;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_order_drift(
+        &self,
+        order: &TradeOrder,
+        kind: DriftKind,
+        venue: &str,
+        remote: String,
+        target: OrderStatus,
+        filled: Option<f64>,
+        config: &ReconciliationConfig,
+        report: &mut ReconciliationReport,
+    ) {
+        let mut record = DriftRecord {
+            kind,
+            venue: venue.to_string(),
+            reference: order.id.clone(),
+            local: format!("{:?} filled {}", order.status, order.filled_amount),
+            remote,
+            repaired: false,
+            error: None,
+            detected_at: chrono::Utc::now().timestamp() as u64,
+        };
+        if config.auto_repair {
+            let reason = Some(format!("Reconciled: {:?}", kind));
+            match self.trade_executor.apply_venue_status(&order.id, target, filled, reason).await {
+                Ok(()) => {
+                    record.repaired = true;
+                    info!("🔧 Order {} reconciled {:?} -> {:?}", order.id, order.status, target);
+                }
+                Err(e) => record.error = Some(e),
+            }
+        }
+        report.drifts.push(record);
+    }
+
+    async fn reconcile_balance(
+        &self,
+        venue: &str,
+        asset: &str,
+        remote: Decimal,
+        config: &ReconciliationConfig,
+        report: &mut ReconciliationReport,
+    ) {
+        let local = self.inventory.get_balance(venue, asset).await;
+        let tolerance = (remote.abs() * config.balance_tolerance_pct / Decimal::from(100))
+            .max(config.balance_tolerance_abs);
+        if (remote - local).abs() <= tolerance {
+            return;
+        }
+
+        let mut record = DriftRecord {
+            kind: DriftKind::Balance,
+            venue: venue.to_string(),
+            reference: asset.to_uppercase(),
+            local: local.to_string(),
+            remote: remote.to_string(),
+            repaired: false,
+            error: None,
+            detected_at: chrono::Utc::now().timestamp() as u64,
+        };
+        if config.auto_repair {
+            self.inventory.set_balance(venue, asset, remote).await;
+            record.repaired = true;
+            info!("🔧 {} {} balance reconciled {} -> {}", venue, asset, local, remote);
+        }
+        report.drifts.push(record);
+    }
+}
+
+impl Clone for Reconciler {
+    fn clone(&self) -> Self {
+        Self {
+            trade_executor: self.trade_executor.clone(),
+            inventory: self.inventory.clone(),
+            config: self.config.clone(),
+            reports: self.reports.clone(),
+            last_report: self.last_report.clone(),
+        }
+    }
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+fn local_status(state: CexOrderState) -> OrderStatus {
+    match state {
+        CexOrderState::New => OrderStatus::Submitted,
+        CexOrderState::PartiallyFilled => OrderStatus::PartiallyFilled,
+        CexOrderState::Filled => OrderStatus::Filled,
+        CexOrderState::Canceled => OrderStatus::Cancelled,
+        CexOrderState::Rejected => OrderStatus::Rejected,
+        CexOrderState::Expired => OrderStatus::Expired,
+    }
+}
+
+/// Venue fills are in the base asset; local orders track the input token
+fn filled_input(order: &TradeOrder, remote: &CexOrderStatus) -> f64 {
+    let base = remote.filled_amount.to_f64().unwrap_or(0.0);
+    match order.side {
+        OrderSide::Buy => {
+            let price = remote.avg_fill_price
+                .or(remote.price)
+                .and_then(|p| p.to_f64())
+                .unwrap_or(order.amount_in / order.amount_out_min.max(1e-18));
+            base * price
+        }
+        OrderSide::Sell | OrderSide::Swap => base,
+    }
+}
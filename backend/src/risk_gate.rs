@@ -0,0 +1,599 @@
+// Pre-Trade Risk Gate - The one risk check every order path passes before anything reaches a venue
+// Order size, exposure, loss limits, concentration and correlation, with structured rejections
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use log::{debug, warn};
+
+use crate::inventory::is_stablecoin;
+use crate::risk_management::{RiskCheck, RiskManager, RiskRejection};
+
+const MAX_DECISIONS: usize = 1000;
+
+// ============================================================================
+// CONFIG
+// ============================================================================
+
+/// Gate-level limits; portfolio-relative limits (position size, portfolio risk, leverage,
+/// daily loss, correlated positions) come from the shared RiskManager's RiskProfile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskGateConfig {
+    pub max_order_usd: f64,
+    pub max_concurrent_orders: usize,    // In flight per book (live / paper)
+    pub max_concentration: f64,          // One asset's share of equity once the order fills
+    pub max_venue_concentration: f64,    // In-flight notional on one venue as a share of equity
+    pub max_drawdown: f64,               // From the book's peak equity
+    pub max_daily_loss_usd: Option<f64>, // Absolute cap on top of the profile's percentage
+    pub min_profit_pct: f64,             // Only for orders that carry an expected profit
+    pub max_risk_score: f64,
+}
+
+impl Default for RiskGateConfig {
+    fn default() -> Self {
+        Self {
+            max_order_usd: 10_000.0,
+            max_concurrent_orders: 5,
+            max_concentration: 0.25,
+            max_venue_concentration: 0.5,
+            max_drawdown: 0.10,
+            max_daily_loss_usd: None,
+            min_profit_pct: 0.1,
+            max_risk_score: 0.7,
+        }
+    }
+}
+
+impl RiskGateConfig {
+    /// MAX_POSITION_SIZE and MAX_DAILY_LOSS (USD) and MIN_PROFIT_THRESHOLD (percent) override the defaults
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        let parse = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        if let Some(max_order) = parse("MAX_POSITION_SIZE") {
+            config.max_order_usd = max_order;
+        }
+        if let Some(max_loss) = parse("MAX_DAILY_LOSS") {
+            config.max_daily_loss_usd = Some(max_loss);
+        }
+        if let Some(min_profit) = parse("MIN_PROFIT_THRESHOLD") {
+            config.min_profit_pct = min_profit;
+        }
+        config
+    }
+}
+
+// ============================================================================
+// REQUESTS AND DECISIONS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderOrigin {
+    ExecutionEngine,
+    TradeExecutor,
+    SmartOrderRouter,
+    FlashLoan,
+    CrossChain,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreTradeRequest {
+    pub id: String,                // Order or trade id; also the reservation key
+    pub origin: OrderOrigin,
+    pub strategy: Option<String>,
+    pub asset: String,             // Asset whose exposure the order changes (never the stablecoin leg)
+    pub venues: Vec<String>,
+    pub notional_usd: Option<f64>, // None when the order could not be priced
+    pub reduces_exposure: bool,    // Sells into stablecoins; only the part the book holds skips the exposure checks
+    pub expected_profit_pct: Option<f64>,
+    pub risk_score: Option<f64>,
+    pub paper: bool,
+}
+
+impl PreTradeRequest {
+    pub fn new(id: impl Into<String>, origin: OrderOrigin, asset: &str, notional_usd: Option<f64>) -> Self {
+        Self {
+            id: id.into(),
+            origin,
+            strategy: None,
+            asset: asset.to_uppercase(),
+            venues: Vec::new(),
+            notional_usd,
+            reduces_exposure: false,
+            expected_profit_pct: None,
+            risk_score: None,
+            paper: false,
+        }
+    }
+
+    pub fn with_strategy(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy = Some(strategy.into());
+        self
+    }
+
+    pub fn with_venues(mut self, venues: Vec<String>) -> Self {
+        self.venues = venues;
+        self
+    }
+
+    pub fn with_expected_profit(mut self, profit_pct: f64, risk_score: f64) -> Self {
+        self.expected_profit_pct = Some(profit_pct);
+        self.risk_score = Some(risk_score);
+        self
+    }
+
+    pub fn reducing(mut self, reduces_exposure: bool) -> Self {
+        self.reduces_exposure = reduces_exposure;
+        self
+    }
+
+    pub fn paper(mut self, paper: bool) -> Self {
+        self.paper = paper;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskDecision {
+    pub request: PreTradeRequest,
+    pub approved: bool,
+    pub rejections: Vec<RiskRejection>, // Every failed check, not just the first
+    pub decided_at: u64,
+}
+
+impl RiskDecision {
+    pub fn summary(&self) -> String {
+        self.rejections.iter().map(|r| r.reason.as_str()).collect::<Vec<_>>().join("; ")
+    }
+}
+
+/// Portfolio state of one book, pushed by the execution engine after fills and on its refresh loop
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BookState {
+    pub equity_usd: f64,
+    pub peak_equity_usd: f64,
+    pub available_usd: f64,
+    pub daily_pnl_usd: f64,
+    pub exposure_usd: HashMap<String, f64>, // Open exposure per asset
+    pub updated_at: u64,
+}
+
+/// Asset an order changes exposure to, and whether the order sells it (into a stablecoin, or one stablecoin
+/// for another). The gate caps the reducing part at what the book holds in that asset.
+pub fn exposure_asset(token_in: &str, token_out: &str) -> (String, bool) {
+    let (token_in, token_out) = (token_in.to_uppercase(), token_out.to_uppercase());
+    match (is_stablecoin(&token_in), is_stablecoin(&token_out)) {
+        (true, stable_out) => (token_out, stable_out),
+        (false, true) => (token_in, true),
+        (false, false) => (token_out, false), // Token for token: the acquired leg is the new risk
+    }
+}
+
+// ============================================================================
+// GATE
+// ============================================================================
+
+pub struct PreTradeRiskGate {
+    config: Arc<RwLock<RiskGateConfig>>,
+    risk_manager: Arc<RiskManager>,
+    books: Arc<RwLock<HashMap<bool, BookState>>>, // Keyed by `paper`
+    reservations: Arc<RwLock<HashMap<String, PreTradeRequest>>>, // Approved and still in flight
+    decisions: Arc<RwLock<VecDeque<RiskDecision>>>,
+}
+
+impl PreTradeRiskGate {
+    pub fn new(config: RiskGateConfig, risk_manager: Arc<RiskManager>) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            risk_manager,
+            books: Arc::new(RwLock::new(HashMap::new())),
+            reservations: Arc::new(RwLock::new(HashMap::new())),
+            decisions: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    pub fn from_env(risk_manager: Arc<RiskManager>) -> Self {
+        Self::new(RiskGateConfig::from_env(), risk_manager)
+    }
+
+    pub async fn get_config(&self) -> RiskGateConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: RiskGateConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub async fn update_book(
+        &self,
+        paper: bool,
+        equity_usd: f64,
+        available_usd: f64,
+        daily_pnl_usd: f64,
+        exposure_usd: HashMap<String, f64>,
+    ) {
+        let mut books = self.books.write().await;
+        let book = books.entry(paper).or_default();
+        book.equity_usd = equity_usd;
+        book.peak_equity_usd = book.peak_equity_usd.max(equity_usd);
+        book.available_usd = available_usd;
+        book.daily_pnl_usd = daily_pnl_usd;
+        book.exposure_usd = exposure_usd.into_iter().map(|(asset, value)| (asset.to_uppercase(), value)).collect();
+        book.updated_at = chrono::Utc::now().timestamp() as u64;
+    }
+
+    pub async fn get_book(&self, paper: bool) -> Option<BookState> {
+        self.books.read().await.get(&paper).cloned()
+    }
+
+    /// Decision without reserving anything (previews, and parents of orders that are checked again when split)
+    pub async fn evaluate(&self, request: &PreTradeRequest) -> RiskDecision {
+        let reservations = self.reservations.read().await;
+        self.decide(request, &reservations).await
+    }
+
+    /// Check and, when approved, reserve the order's exposure until `release`. An id that is already
+    /// reserved is rejected so a duplicate can neither replace nor later release the live reservation.
+    pub async fn check(&self, request: PreTradeRequest) -> Result<RiskDecision, RiskDecision> {
+        let decision = {
+            let mut reservations = self.reservations.write().await;
+            let decision = if reservations.contains_key(&request.id) {
+                RiskDecision {
+                    rejections: vec![RiskRejection {
+                        check: RiskCheck::ConcurrentOrders,
+                        reason: format!("{} is already in flight", request.id),
+                        limit: 0.0,
+                        actual: 0.0,
+                    }],
+                    request,
+                    approved: false,
+                    decided_at: chrono::Utc::now().timestamp() as u64,
+                }
+            } else {
+                self.decide(&request, &reservations).await
+            };
+            if decision.approved {
+                reservations.insert(decision.request.id.clone(), decision.request.clone());
+            }
+            decision
+        };
+
+        if decision.approved {
+            debug!("✅ Pre-trade risk check passed for {} ({:?})", decision.request.id, decision.request.origin);
+        } else {
+            warn!(
+                "🛑 Pre-trade risk gate rejected {} {} ({:?}): {}",
+                decision.request.id, decision.request.asset, decision.request.origin, decision.summary()
+            );
+        }
+
+        let result = if decision.approved { Ok(decision.clone()) } else { Err(decision.clone()) };
+        let mut decisions = self.decisions.write().await;
+        decisions.push_back(decision);
+        while decisions.len() > MAX_DECISIONS {
+            decisions.pop_front();
+        }
+        result
+    }
+
+    /// The order finished (filled, failed or cancelled); its exposure now lives in the book, if anywhere
+    pub async fn release(&self, id: &str) {
+        self.reservations.write().await.remove(id);
+    }
+
+    pub async fn get_reservations(&self) -> Vec<PreTradeRequest> {
+        self.reservations.read().await.values().cloned().collect()
+    }
+
+    /// Newest first
+    pub async fn get_decisions(&self, limit: usize, rejected_only: bool) -> Vec<RiskDecision> {
+        self.decisions.read().await.iter()
+            .rev()
+            .filter(|d| !rejected_only || !d.approved)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    async fn decide(&self, request: &PreTradeRequest, reservations: &HashMap<String, PreTradeRequest>) -> RiskDecision {
+        let rejections = self.rejections(request, reservations).await;
+        RiskDecision {
+            request: request.clone(),
+            approved: rejections.is_empty(),
+            rejections,
+            decided_at: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+
+    async fn rejections(&self, request: &PreTradeRequest, reservations: &HashMap<String, PreTradeRequest>) -> Vec<RiskRejection> {
+        let config = self.config.read().await.clone();
+        let book_name = if request.paper { "paper" } else { "live" };
+
+        let notional = match request.notional_usd {
+            Some(value) if value.is_finite() && value >= 0.0 => value,
+            _ => return vec![RiskRejection {
+                check: RiskCheck::Valuation,
+                reason: format!("No USD value for {} order {}", request.asset, request.id),
+                limit: 0.0,
+                actual: 0.0,
+            }],
+        };
+        let Some(book) = self.books.read().await.get(&request.paper).cloned() else {
+            return vec![RiskRejection {
+                check: RiskCheck::Valuation,
+                reason: format!("No {} portfolio state yet", book_name),
+                limit: 0.0,
+                actual: 0.0,
+            }];
+        };
+
+        // Other approved orders of this book that have not finished yet count as open exposure
+        let in_flight: Vec<&PreTradeRequest> = reservations.values()
+            .filter(|r| r.paper == request.paper && r.id != request.id)
+            .collect();
+        let mut exposure = book.exposure_usd.clone();
+        for reserved in in_flight.iter().filter(|r| !r.reduces_exposure) {
+            *exposure.entry(reserved.asset.clone()).or_insert(0.0) += reserved.notional_usd.unwrap_or(0.0);
+        }
+
+        let mut rejections = Vec::new();
+
+        // Hard limits: order size and the book's loss halts hold for every order, exits included
+        if notional > config.max_order_usd {
+            rejections.push(RiskRejection {
+                check: RiskCheck::OrderSize,
+                reason: format!("Order ${:.2} exceeds maximum ${:.2}", notional, config.max_order_usd),
+                limit: config.max_order_usd,
+                actual: notional,
+            });
+        }
+
+        if let Some(max_loss) = config.max_daily_loss_usd {
+            if book.daily_pnl_usd < -max_loss {
+                rejections.push(RiskRejection {
+                    check: RiskCheck::DailyLoss,
+                    reason: format!("Daily loss ${:.2} exceeds ${:.2} limit", -book.daily_pnl_usd, max_loss),
+                    limit: -max_loss,
+                    actual: book.daily_pnl_usd,
+                });
+            }
+        }
+
+        if book.peak_equity_usd > 0.0 {
+            let drawdown = (book.peak_equity_usd - book.equity_usd) / book.peak_equity_usd;
+            if drawdown > config.max_drawdown {
+                rejections.push(RiskRejection {
+                    check: RiskCheck::Drawdown,
+                    reason: format!("Drawdown {:.2}% exceeds {:.2}% limit", drawdown * 100.0, config.max_drawdown * 100.0),
+                    limit: config.max_drawdown,
+                    actual: drawdown,
+                });
+            }
+        }
+
+        // A sell reduces exposure only up to what the book holds, less what sells already in flight claim;
+        // anything beyond that opens new exposure and is gated like any other order
+        let notional = if request.reduces_exposure {
+            let claimed: f64 = in_flight.iter()
+                .filter(|r| r.reduces_exposure && r.asset == request.asset)
+                .map(|r| r.notional_usd.unwrap_or(0.0))
+                .sum();
+            let held = (book.exposure_usd.get(&request.asset).copied().unwrap_or(0.0) - claimed).max(0.0);
+            let excess = (notional - held).max(0.0);
+            if excess <= 0.0 {
+                return rejections;
+            }
+            excess
+        } else {
+            notional
+        };
+
+        if in_flight.len() >= config.max_concurrent_orders {
+            rejections.push(RiskRejection {
+                check: RiskCheck::ConcurrentOrders,
+                reason: format!("{} {} orders already in flight (max {})", in_flight.len(), book_name, config.max_concurrent_orders),
+                limit: config.max_concurrent_orders as f64,
+                actual: in_flight.len() as f64,
+            });
+        }
+
+        // Flash loans borrow their notional instead of drawing on the book
+        if request.origin != OrderOrigin::FlashLoan && notional > book.available_usd {
+            rejections.push(RiskRejection {
+                check: RiskCheck::AvailableBalance,
+                reason: format!("Order ${:.2} exceeds available ${:.2}", notional, book.available_usd),
+                limit: book.available_usd,
+                actual: notional,
+            });
+        }
+
+        // Position size, portfolio risk, leverage, daily loss and correlated positions
+        let daily_loss_checked = rejections.iter().any(|r| r.check == RiskCheck::DailyLoss);
+        rejections.extend(
+            self.risk_manager
+                .pre_trade_checks(&request.asset, notional, book.equity_usd, book.daily_pnl_usd, &exposure)
+                .await
+                .into_iter()
+                .filter(|r| !(daily_loss_checked && r.check == RiskCheck::DailyLoss)),
+        );
+
+        if book.equity_usd > 0.0 {
+            let concentration = (exposure.get(&request.asset).copied().unwrap_or(0.0) + notional) / book.equity_usd;
+            if concentration > config.max_concentration {
+                rejections.push(RiskRejection {
+                    check: RiskCheck::Concentration,
+                    reason: format!(
+                        "{} would be {:.2}% of equity (max {:.2}%)",
+                        request.asset, concentration * 100.0, config.max_concentration * 100.0
+                    ),
+                    limit: config.max_concentration,
+                    actual: concentration,
+                });
+            }
+
+            for venue in &request.venues {
+                let on_venue: f64 = in_flight.iter()
+                    .filter(|r| r.venues.contains(venue))
+                    .map(|r| r.notional_usd.unwrap_or(0.0))
+                    .sum::<f64>() + notional;
+                let share = on_venue / book.equity_usd;
+                if share > config.max_venue_concentration {
+                    rejections.push(RiskRejection {
+                        check: RiskCheck::VenueConcentration,
+                        reason: format!(
+                            "{:.2}% of equity in flight on {} (max {:.2}%)",
+                            share * 100.0, venue, config.max_venue_concentration * 100.0
+                        ),
+                        limit: config.max_venue_concentration,
+                        actual: share,
+                    });
+                }
+            }
+        }
+
+        if let Some(profit) = request.expected_profit_pct {
+            if profit < config.min_profit_pct {
+                rejections.push(RiskRejection {
+                    check: RiskCheck::MinProfit,
+                    reason: format!("Expected profit {:.3}% below minimum {:.3}%", profit, config.min_profit_pct),
+                    limit: config.min_profit_pct,
+                    actual: profit,
+                });
+            }
+        }
+
+        if let Some(score) = request.risk_score {
+            if score > config.max_risk_score {
+                rejections.push(RiskRejection {
+                    check: RiskCheck::RiskScore,
+                    reason: format!("Risk score {:.2} above maximum {:.2}", score, config.max_risk_score),
+                    limit: config.max_risk_score,
+                    actual: score,
+                });
+            }
+        }
+
+        rejections
+    }
+}
+
+impl Clone for PreTradeRiskGate {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            risk_manager: self.risk_manager.clone(),
+            books: self.books.clone(),
+            reservations: self.reservations.clone(),
+            decisions: self.decisions.clone(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk_management::RiskProfile;
+
+    async fn gate(config: RiskGateConfig, exposure: &[(&str, f64)], daily_pnl_usd: f64) -> PreTradeRiskGate {
+        let gate = PreTradeRiskGate::new(config, Arc::new(RiskManager::new(RiskProfile::default())));
+        let exposure = exposure.iter().map(|(asset, value)| (asset.to_string(), *value)).collect();
+        gate.update_book(false, 100_000.0, 50_000.0, daily_pnl_usd, exposure).await;
+        gate
+    }
+
+    fn order(id: &str, asset: &str, notional: f64) -> PreTradeRequest {
+        PreTradeRequest::new(id, OrderOrigin::TradeExecutor, asset, Some(notional))
+    }
+
+    fn checks(result: Result<RiskDecision, RiskDecision>) -> Vec<RiskCheck> {
+        result.err().map(|d| d.rejections.iter().map(|r| r.check).collect()).unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn approved_orders_are_reserved_until_released() {
+        let gate = gate(RiskGateConfig::default(), &[], 0.0).await;
+
+        assert!(gate.evaluate(&order("preview", "SOL", 1_000.0)).await.approved);
+        assert!(gate.get_reservations().await.is_empty());
+
+        gate.check(order("a", "SOL", 1_000.0)).await.unwrap();
+        assert_eq!(checks(gate.check(order("big", "SOL", 20_000.0)).await), vec![RiskCheck::OrderSize, RiskCheck::PositionSize]);
+        assert_eq!(gate.get_reservations().await.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec!["a"]);
+
+        // A second order under a live id neither replaces nor releases the first reservation
+        assert_eq!(checks(gate.check(order("a", "SOL", 2_000.0)).await), vec![RiskCheck::ConcurrentOrders]);
+        assert_eq!(gate.get_reservations().await[0].notional_usd, Some(1_000.0));
+
+        gate.release("a").await;
+        assert!(gate.get_reservations().await.is_empty());
+        assert_eq!(gate.get_decisions(10, true).await.len(), 2);
+
+        // Unpriced orders are never approved
+        assert_eq!(
+            checks(gate.check(PreTradeRequest::new("unpriced", OrderOrigin::TradeExecutor, "SOL", None)).await),
+            vec![RiskCheck::Valuation],
+        );
+    }
+
+    #[tokio::test]
+    async fn in_flight_orders_count_toward_exposure_venues_and_concurrency() {
+        let config = RiskGateConfig { max_concurrent_orders: 3, max_venue_concentration: 0.15, ..RiskGateConfig::default() };
+        let gate = gate(config, &[("ETH", 10_000.0)], 0.0).await;
+        let on = |id: &str, notional: f64| order(id, "ETH", notional).with_venues(vec!["raydium".to_string()]);
+
+        gate.check(on("a", 5_000.0)).await.unwrap();
+        gate.check(on("b", 5_000.0)).await.unwrap();
+
+        // 10k held + 10k in flight + 6k is 26% of equity; 16k on one venue is 16%
+        let rejected = gate.check(on("c", 6_000.0)).await.unwrap_err();
+        let concentration = rejected.rejections.iter().find(|r| r.check == RiskCheck::Concentration).unwrap();
+        assert!((concentration.actual - 0.26).abs() < 1e-9);
+        assert!(rejected.rejections.iter().any(|r| r.check == RiskCheck::VenueConcentration));
+
+        gate.release("a").await;
+        gate.check(on("c", 5_000.0)).await.unwrap();
+        gate.check(order("d", "BONK", 1_000.0)).await.unwrap();
+        assert_eq!(checks(gate.check(order("e", "JUP", 1_000.0)).await), vec![RiskCheck::ConcurrentOrders]);
+    }
+
+    #[tokio::test]
+    async fn reducing_orders_skip_exposure_checks_only_up_to_what_the_book_holds() {
+        // SOL is already 30% of equity, so any buy breaches the 5% concentration limit
+        let config = RiskGateConfig { max_concentration: 0.05, ..RiskGateConfig::default() };
+        let gate = gate(config, &[("SOL", 30_000.0)], 0.0).await;
+        let sell = |id: &str, asset: &str, notional: f64| order(id, asset, notional).reducing(true);
+
+        assert_eq!(checks(gate.check(order("buy", "SOL", 1_000.0)).await), vec![RiskCheck::Concentration]);
+        gate.check(sell("exit-1", "SOL", 9_000.0)).await.unwrap();
+        gate.check(sell("exit-2", "SOL", 9_000.0)).await.unwrap();
+
+        // 12k of SOL left once the in-flight sells land; the 3k beyond that is gated as new exposure
+        assert_eq!(checks(gate.check(sell("exit-3", "SOL", 15_000.0)).await), vec![RiskCheck::OrderSize, RiskCheck::Concentration]);
+        assert_eq!(checks(gate.check(sell("short", "BONK", 9_000.0)).await), vec![RiskCheck::Concentration]);
+        gate.release("exit-1").await;
+        gate.check(sell("exit-3", "SOL", 9_000.0)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn order_size_and_loss_halts_apply_to_reducing_orders() {
+        let config = RiskGateConfig { max_daily_loss_usd: Some(1_000.0), ..RiskGateConfig::default() };
+        let gate = gate(config, &[("SOL", 50_000.0)], -2_000.0).await;
+
+        assert_eq!(checks(gate.check(order("exit", "SOL", 5_000.0).reducing(true)).await), vec![RiskCheck::DailyLoss]);
+        assert_eq!(
+            checks(gate.check(order("dump", "SOL", 20_000.0).reducing(true)).await),
+            vec![RiskCheck::OrderSize, RiskCheck::DailyLoss],
+        );
+    }
+
+    #[test]
+    fn sells_into_stablecoins_are_reducing() {
+        assert_eq!(exposure_asset("sol", "USDC"), ("SOL".to_string(), true));
+        assert_eq!(exposure_asset("USDC", "sol"), ("SOL".to_string(), false));
+        assert_eq!(exposure_asset("SOL", "BONK"), ("BONK".to_string(), false));
+        assert_eq!(exposure_asset("USDT", "USDC"), ("USDC".to_string(), true));
+    }
+}
@@ -2,6 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use rand::{rngs::StdRng, SeedableRng};
+use log::{debug, info, warn};
+
+use crate::historical_data::{HistoricalDataStore, TimeFrame};
+use crate::risk_stats;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskProfile {
@@ -30,6 +35,243 @@ impl Default for RiskProfile {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VarMethod {
+    Historical,  // Empirical quantile of replayed returns
+    Parametric,  // Normal, from the fitted mean and covariance
+    MonteCarlo,  // Simulated from the fitted covariance (Cholesky)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarConfig {
+    pub timeframe: TimeFrame,       // Return frequency taken from the historical store
+    pub lookback: usize,            // Returns per estimate
+    pub confidence_levels: Vec<f64>,
+    pub horizons: Vec<u32>,         // In periods of `timeframe`
+    pub primary_method: VarMethod,  // Behind var_95/cvar_95 and position VaR
+    pub primary_horizon: u32,
+    pub monte_carlo_paths: usize,
+    pub monte_carlo_seed: Option<u64>, // Fixed seed for reproducible runs
+    pub backtest_window: usize,     // Trailing returns behind each rolling forecast
+    pub backtest_confidence: f64,
+    pub min_observations: usize,
+    pub fallback_volatility: f64,   // Daily; used until enough returns exist
+    pub fallback_correlation: f64,  // For pairs without fitted or supplied correlation
+    pub shrink_covariance: bool,    // Ledoit-Wolf instead of the raw sample covariance
+}
+
+impl Default for VarConfig {
+    fn default() -> Self {
+        Self {
+            timeframe: TimeFrame::H1,
+            lookback: 720,              // 30 days of hourly returns
+            confidence_levels: vec![0.95, 0.99],
+            horizons: vec![1, 24],      // 1 hour, 1 day
+            primary_method: VarMethod::Historical,
+            primary_horizon: 24,
+            monte_carlo_paths: 10_000,
+            monte_carlo_seed: None,
+            backtest_window: 168,       // 1 week
+            backtest_confidence: 0.95,
+            min_observations: 30,
+            fallback_volatility: 0.02,
+            fallback_correlation: 0.3,
+            shrink_covariance: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationConfig {
+    pub timeframe: TimeFrame,
+    pub window: usize,               // Trailing returns per estimate
+    pub min_observations: usize,
+    pub refresh_interval_secs: u64,
+    pub shrinkage: bool,             // Ledoit-Wolf shrinkage toward a scaled identity
+    pub group_min_correlation: f64,  // Average-linkage cut: clusters correlate at least this much
+    pub symbols: Vec<String>,        // Always included; the store's symbols are added when they have data
+}
+
+impl Default for CorrelationConfig {
+    fn default() -> Self {
+        Self {
+            timeframe: TimeFrame::H1,
+            window: 336,                 // 2 weeks of hourly returns
+            min_observations: 48,
+            refresh_interval_secs: 900,
+            shrinkage: true,
+            group_min_correlation: 0.6,
+            symbols: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationGroup {
+    pub id: String,
+    pub members: Vec<String>,
+    pub average_correlation: f64, // Mean pairwise correlation inside the group (1.0 for singletons)
+}
+
+/// Latest rolling estimate, as used by order validation and portfolio VaR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationSnapshot {
+    pub symbols: Vec<String>,
+    pub matrix: Vec<Vec<f64>>,
+    pub shrinkage: f64,
+    pub observations: usize,
+    pub groups: Vec<CorrelationGroup>,
+    pub updated_at: u64,
+}
+
+// Positions may be keyed by base asset ("SOL") while candles are keyed by pair ("SOL/USDC")
+fn symbol_aliases(symbol: &str) -> Vec<String> {
+    let mut aliases = vec![symbol.to_string()];
+    match symbol.split_once('/') {
+        Some((base, "USDC")) | Some((base, "USDT")) => aliases.push(base.to_string()),
+        Some(_) => {}
+        None => {
+            aliases.push(format!("{}/USDC", symbol));
+            aliases.push(format!("{}/USDT", symbol));
+        }
+    }
+    aliases
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarEstimate {
+    pub method: VarMethod,
+    pub confidence: f64,
+    pub horizon: u32,
+    pub var: f64,
+    pub cvar: f64,
+}
+
+/// Rolling one-period historical VaR forecasts against the P&L the current positions would have made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarBacktest {
+    pub confidence: f64,
+    pub observations: usize,
+    pub breaches: usize,
+    pub expected_breaches: f64,
+    pub breach_rate: f64,
+    pub kupiec_lr: f64,
+    pub p_value: f64,
+    pub rejected: bool, // Breach frequency inconsistent with the confidence level at 5%
+}
+
+// Aligned per-period log returns for a set of symbols with their fitted moments
+struct ReturnModel {
+    returns: Vec<Vec<f64>>, // returns[asset][period]
+    means: Vec<f64>,
+    covariance: Vec<Vec<f64>>,
+}
+
+impl ReturnModel {
+    fn fit(returns: Vec<Vec<f64>>, shrink: bool) -> Self {
+        let means = returns.iter().map(|r| risk_stats::mean(r)).collect();
+        let covariance = if shrink {
+            risk_stats::ledoit_wolf_covariance(&returns).0
+        } else {
+            risk_stats::covariance_matrix(&returns)
+        };
+        Self { returns, means, covariance }
+    }
+
+    fn periods(&self) -> usize {
+        self.returns.first().map(|r| r.len()).unwrap_or(0)
+    }
+
+    /// Portfolio P&L per window of `horizon` periods (overlapping), revaluing exposures exactly
+    fn pnl_series(&self, exposures: &[f64], horizon: usize) -> Vec<f64> {
+        let periods = self.periods();
+        if horizon == 0 || periods < horizon {
+            return Vec::new();
+        }
+        (0..=periods - horizon)
+            .map(|start| {
+                exposures.iter().zip(&self.returns)
+                    .map(|(exposure, r)| exposure * (r[start..start + horizon].iter().sum::<f64>().exp() - 1.0))
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn historical(&self, exposures: &[f64], confidence: f64, horizon: u32, min_observations: usize) -> Option<(f64, f64)> {
+        let series = self.pnl_series(exposures, horizon as usize);
+        if series.len() >= min_observations {
+            return risk_stats::empirical_var_cvar(&series, confidence);
+        }
+        // Too few overlapping windows: scale the one-period figures by √h
+        let (var, cvar) = risk_stats::empirical_var_cvar(&self.pnl_series(exposures, 1), confidence)?;
+        let scale = (horizon as f64).sqrt();
+        Some((var * scale, cvar * scale))
+    }
+
+    fn parametric(&self, exposures: &[f64], confidence: f64, horizon: u32) -> Option<(f64, f64)> {
+        let h = horizon as f64;
+        let mu = exposures.iter().zip(&self.means).map(|(e, m)| e * m).sum::<f64>() * h;
+        let sigma = (risk_stats::quadratic_form(exposures, &self.covariance) * h).max(0.0).sqrt();
+        let z = risk_stats::normal_quantile(confidence);
+        if !z.is_finite() || !sigma.is_finite() {
+            return None;
+        }
+        let var = -mu + z * sigma;
+        let cvar = -mu + sigma * risk_stats::normal_pdf(z) / (1.0 - confidence);
+        Some((var.max(0.0), cvar.max(0.0)))
+    }
+
+    fn monte_carlo(&self, exposures: &[f64], confidence: f64, horizon: u32, paths: usize, rng: &mut StdRng) -> Option<(f64, f64)> {
+        let h = horizon as f64;
+        let scaled: Vec<Vec<f64>> = self.covariance.iter()
+            .map(|row| row.iter().map(|c| c * h).collect())
+            .collect();
+        let lower = risk_stats::cholesky_with_jitter(&scaled)?;
+        let n = exposures.len();
+        let mut shocks = vec![0.0; n];
+        let pnl: Vec<f64> = (0..paths.max(1))
+            .map(|_| {
+                for shock in shocks.iter_mut() {
+                    *shock = risk_stats::standard_normal(rng);
+                }
+                (0..n)
+                    .map(|i| {
+                        let r = self.means[i] * h + (0..=i).map(|k| lower[i][k] * shocks[k]).sum::<f64>();
+                        exposures[i] * (r.exp() - 1.0)
+                    })
+                    .sum()
+            })
+            .collect();
+        risk_stats::empirical_var_cvar(&pnl, confidence)
+    }
+
+    fn backtest(&self, exposures: &[f64], confidence: f64, window: usize, min_observations: usize) -> Option<VarBacktest> {
+        let pnl = self.pnl_series(exposures, 1);
+        if window == 0 || pnl.len() < window + min_observations {
+            return None;
+        }
+        let observations = pnl.len() - window;
+        let breaches = (window..pnl.len())
+            .filter(|&t| {
+                risk_stats::empirical_var_cvar(&pnl[t - window..t], confidence)
+                    .map(|(var, _)| -pnl[t] > var)
+                    .unwrap_or(false)
+            })
+            .count();
+        let (kupiec_lr, p_value) = risk_stats::kupiec_pof(observations, breaches, confidence);
+        Some(VarBacktest {
+            confidence,
+            observations,
+            breaches,
+            expected_breaches: observations as f64 * (1.0 - confidence),
+            breach_rate: breaches as f64 / observations as f64,
+            kupiec_lr,
+            p_value,
+            rejected: p_value < 0.05,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionRisk {
     pub symbol: String,
@@ -44,12 +286,44 @@ pub struct PositionRisk {
     pub correlation_group: Option<String>,
 }
 
+/// Pre-trade check behind a rejection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskCheck {
+    OrderSize,
+    PositionSize,
+    PortfolioRisk,
+    GrossExposure,
+    DailyLoss,
+    Drawdown,
+    Concentration,
+    VenueConcentration,
+    CorrelatedPositions,
+    ConcurrentOrders,
+    AvailableBalance,
+    MinProfit,
+    RiskScore,
+    Valuation, // Order or portfolio could not be valued in USD
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskRejection {
+    pub check: RiskCheck,
+    pub reason: String,
+    pub limit: f64,
+    pub actual: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortfolioRisk {
     pub total_value: f64,
     pub total_risk: f64,
-    pub var_95: f64,
-    pub cvar_95: f64, // Conditional VaR
+    pub var_95: f64,  // Primary method at the primary horizon
+    pub cvar_95: f64, // Conditional VaR (expected shortfall beyond var_95)
+    pub var_estimates: Vec<VarEstimate>, // Every method x confidence x horizon
+    pub var_backtest: Option<VarBacktest>,
+    pub return_observations: usize, // Aligned periods behind the estimates
+    pub var_fallback: bool, // Too little history: fixed volatility and correlation assumed
     pub sharpe_ratio: f64,
     pub sortino_ratio: f64,
     pub max_drawdown: f64,
@@ -65,6 +339,11 @@ pub struct RiskManager {
     portfolio_history: Arc<RwLock<Vec<(u64, f64)>>>, // (timestamp, value)
     daily_pnl_history: Arc<RwLock<Vec<(u64, f64)>>>,
     correlation_matrix: Arc<RwLock<HashMap<(String, String), f64>>>,
+    var_config: Arc<RwLock<VarConfig>>,
+    correlation_config: Arc<RwLock<CorrelationConfig>>,
+    correlation_groups: Arc<RwLock<HashMap<String, String>>>, // Symbol (and aliases) -> group id
+    correlation_snapshot: Arc<RwLock<Option<CorrelationSnapshot>>>,
+    data_store: Option<Arc<HistoricalDataStore>>,
 }
 
 impl RiskManager {
@@ -75,9 +354,186 @@ impl RiskManager {
             portfolio_history: Arc::new(RwLock::new(Vec::new())),
             daily_pnl_history: Arc::new(RwLock::new(Vec::new())),
             correlation_matrix: Arc::new(RwLock::new(HashMap::new())),
+            var_config: Arc::new(RwLock::new(VarConfig::default())),
+            correlation_config: Arc::new(RwLock::new(CorrelationConfig::default())),
+            correlation_groups: Arc::new(RwLock::new(HashMap::new())),
+            correlation_snapshot: Arc::new(RwLock::new(None)),
+            data_store: None,
         }
     }
 
+    /// Return series for VaR come from this store; without one every estimate is the fallback
+    pub fn with_data_store(mut self, data_store: Arc<HistoricalDataStore>) -> Self {
+        self.data_store = Some(data_store);
+        self
+    }
+
+    pub async fn get_var_config(&self) -> VarConfig {
+        self.var_config.read().await.clone()
+    }
+
+    pub async fn set_var_config(&self, config: VarConfig) {
+        *self.var_config.write().await = config;
+    }
+
+    pub async fn get_correlation_config(&self) -> CorrelationConfig {
+        self.correlation_config.read().await.clone()
+    }
+
+    pub async fn set_correlation_config(&self, config: CorrelationConfig) {
+        *self.correlation_config.write().await = config;
+    }
+
+    pub async fn get_correlation_snapshot(&self) -> Option<CorrelationSnapshot> {
+        self.correlation_snapshot.read().await.clone()
+    }
+
+    /// Periodically re-estimates correlations and correlation groups from candle returns
+    pub async fn start_correlation_refresh(self: Arc<Self>) {
+        loop {
+            if let Err(e) = self.refresh_correlations().await {
+                debug!("📉 Correlation refresh skipped: {}", e);
+            }
+            let interval = self.correlation_config.read().await.refresh_interval_secs.max(1);
+            tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    /// Rolling (optionally Ledoit-Wolf shrunk) correlation matrix plus average-linkage groups
+    pub async fn refresh_correlations(&self) -> Result<CorrelationSnapshot, String> {
+        let store = self.data_store.as_ref().ok_or("No historical data store attached")?;
+        let config = self.correlation_config.read().await.clone();
+
+        // Universe: configured symbols, open positions and everything the store has candles for
+        let mut candidates: Vec<String> = config.symbols.clone();
+        let position_symbols: Vec<String> = self.position_risks.read().await.keys().cloned().collect();
+        for symbol in position_symbols.iter().chain(store.symbols().await.iter()) {
+            let key = {
+                let mut found = None;
+                for alias in symbol_aliases(symbol) {
+                    if store.get_log_returns(&alias, config.timeframe, config.window).await.len() >= config.min_observations {
+                        found = Some(alias);
+                        break;
+                    }
+                }
+                found
+            };
+            if let Some(key) = key {
+                if !candidates.contains(&key) {
+                    candidates.push(key);
+                }
+            }
+        }
+
+        // Drop the shortest series until the rest share enough timestamps
+        let mut lengths = HashMap::new();
+        for symbol in &candidates {
+            lengths.insert(symbol.clone(), store.get_log_returns(symbol, config.timeframe, config.window).await.len());
+        }
+        candidates.retain(|s| lengths.get(s).copied().unwrap_or(0) >= config.min_observations);
+        candidates.sort_by(|a, b| lengths[b].cmp(&lengths[a]).then(a.cmp(b)));
+        let (symbols, returns) = loop {
+            if candidates.len() < 2 {
+                return Err("Fewer than two symbols with enough returns".to_string());
+            }
+            let (_, returns) = store.get_aligned_returns(&candidates, config.timeframe, config.window).await;
+            if returns.first().map(|r| r.len()).unwrap_or(0) >= config.min_observations {
+                break (candidates, returns);
+            }
+            candidates.pop();
+        };
+
+        let (covariance, shrinkage) = if config.shrinkage {
+            risk_stats::ledoit_wolf_covariance(&returns)
+        } else {
+            (risk_stats::covariance_matrix(&returns), 0.0)
+        };
+        let n = symbols.len();
+        let matrix: Vec<Vec<f64>> = (0..n)
+            .map(|i| (0..n).map(|j| {
+                if i == j { 1.0 } else { risk_stats::correlation_from_covariance(&covariance, i, j).unwrap_or(0.0) }
+            }).collect())
+            .collect();
+
+        let distances: Vec<Vec<f64>> = matrix.iter()
+            .map(|row| row.iter().map(|rho| risk_stats::correlation_distance(*rho)).collect())
+            .collect();
+        let clusters = risk_stats::average_linkage_clusters(&distances, risk_stats::correlation_distance(config.group_min_correlation));
+        let groups: Vec<CorrelationGroup> = clusters.iter().enumerate()
+            .map(|(index, members)| {
+                let pairs: Vec<f64> = members.iter()
+                    .flat_map(|&i| members.iter().filter(move |&&j| j > i).map(move |&j| (i, j)))
+                    .map(|(i, j)| matrix[i][j])
+                    .collect();
+                CorrelationGroup {
+                    id: format!("CORR_{}", index + 1),
+                    members: members.iter().map(|&i| symbols[i].clone()).collect(),
+                    average_correlation: if pairs.is_empty() { 1.0 } else { risk_stats::mean(&pairs) },
+                }
+            })
+            .collect();
+
+        {
+            let mut correlations = self.correlation_matrix.write().await;
+            correlations.clear();
+            for i in 0..n {
+                for j in 0..n {
+                    if i == j {
+                        continue;
+                    }
+                    for a in symbol_aliases(&symbols[i]) {
+                        for b in symbol_aliases(&symbols[j]) {
+                            correlations.insert((a.clone(), b), matrix[i][j]);
+                        }
+                    }
+                }
+            }
+        }
+        {
+            let mut group_map = self.correlation_groups.write().await;
+            group_map.clear();
+            for group in &groups {
+                for member in &group.members {
+                    for alias in symbol_aliases(member) {
+                        group_map.insert(alias, group.id.clone());
+                    }
+                }
+            }
+        }
+        // Open positions pick up their new group
+        {
+            let group_map = self.correlation_groups.read().await;
+            for (symbol, position) in self.position_risks.write().await.iter_mut() {
+                if let Some(group) = group_map.get(symbol) {
+                    position.correlation_group = Some(group.clone());
+                }
+            }
+        }
+
+        let snapshot = CorrelationSnapshot {
+            symbols,
+            matrix,
+            shrinkage,
+            observations: returns.first().map(|r| r.len()).unwrap_or(0),
+            groups,
+            updated_at: chrono::Utc::now().timestamp() as u64,
+        };
+        info!(
+            "📉 Correlations refreshed: {} symbols, {} observations, shrinkage {:.2}, {} groups",
+            snapshot.symbols.len(), snapshot.observations, snapshot.shrinkage, snapshot.groups.len()
+        );
+        *self.correlation_snapshot.write().await = Some(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    pub async fn get_risk_profile(&self) -> RiskProfile {
+        self.risk_profile.read().await.clone()
+    }
+
+    pub async fn set_risk_profile(&self, profile: RiskProfile) {
+        *self.risk_profile.write().await = profile;
+    }
+
     pub async fn validate_order(
         &self,
         symbol: &str,
@@ -85,54 +541,116 @@ impl RiskManager {
         order_value: f64,
         portfolio_value: f64,
     ) -> Result<(), String> {
-        let profile = self.risk_profile.read().await;
-        let positions = self.position_risks.read().await;
+        let open_exposure: HashMap<String, f64> = self.position_risks.read().await.values()
+            .map(|p| (p.symbol.clone(), (p.position_size * p.current_price).abs()))
+            .collect();
+        let daily_pnl = self.calculate_daily_pnl().await;
+        
+        match self.pre_trade_checks(symbol, order_value, portfolio_value, daily_pnl, &open_exposure).await.into_iter().next() {
+            Some(rejection) => Err(rejection.reason),
+            None => Ok(()),
+        }
+    }
+
+    /// Profile limits for an order adding `order_value` of `symbol` on top of `open_exposure` (symbol -> USD).
+    /// Every failed check is returned, not just the first.
+    pub async fn pre_trade_checks(
+        &self,
+        symbol: &str,
+        order_value: f64,
+        portfolio_value: f64,
+        daily_pnl: f64,
+        open_exposure: &HashMap<String, f64>,
+    ) -> Vec<RiskRejection> {
+        let profile = self.risk_profile.read().await.clone();
+        let mut rejections = Vec::new();
+        
+        if portfolio_value <= 0.0 {
+            rejections.push(RiskRejection {
+                check: RiskCheck::Valuation,
+                reason: "Portfolio value unknown".to_string(),
+                limit: 0.0,
+                actual: portfolio_value,
+            });
+            return rejections;
+        }
         
         // Check position size limit
         let position_percentage = order_value / portfolio_value;
         if position_percentage > profile.max_position_size {
-            return Err(format!(
-                "Position size {:.2}% exceeds maximum {:.2}%",
-                position_percentage * 100.0,
-                profile.max_position_size * 100.0
-            ));
+            rejections.push(RiskRejection {
+                check: RiskCheck::PositionSize,
+                reason: format!(
+                    "Position size {:.2}% exceeds maximum {:.2}%",
+                    position_percentage * 100.0,
+                    profile.max_position_size * 100.0
+                ),
+                limit: profile.max_position_size,
+                actual: position_percentage,
+            });
         }
         
-        // Check total portfolio risk
-        let current_risk: f64 = positions.values().map(|p| p.risk_amount).sum();
-        let new_risk = order_value * profile.stop_loss_percentage;
-        let total_risk = (current_risk + new_risk) / portfolio_value;
-        
+        // Check total portfolio risk (stop-loss distance on everything open plus this order)
+        let open_value: f64 = open_exposure.values().sum();
+        let total_risk = (open_value + order_value) * profile.stop_loss_percentage / portfolio_value;
         if total_risk > profile.max_portfolio_risk {
-            return Err(format!(
-                "Total portfolio risk {:.2}% would exceed maximum {:.2}%",
-                total_risk * 100.0,
-                profile.max_portfolio_risk * 100.0
-            ));
+            rejections.push(RiskRejection {
+                check: RiskCheck::PortfolioRisk,
+                reason: format!(
+                    "Total portfolio risk {:.2}% would exceed maximum {:.2}%",
+                    total_risk * 100.0,
+                    profile.max_portfolio_risk * 100.0
+                ),
+                limit: profile.max_portfolio_risk,
+                actual: total_risk,
+            });
+        }
+        
+        // Check gross exposure against leverage
+        let leverage = (open_value + order_value) / portfolio_value;
+        if leverage > profile.max_leverage {
+            rejections.push(RiskRejection {
+                check: RiskCheck::GrossExposure,
+                reason: format!("Gross exposure {:.2}x would exceed maximum {:.2}x", leverage, profile.max_leverage),
+                limit: profile.max_leverage,
+                actual: leverage,
+            });
         }
         
         // Check daily loss limit
-        let daily_pnl = self.calculate_daily_pnl().await;
-        if daily_pnl < -profile.max_daily_loss * portfolio_value {
-            return Err("Daily loss limit reached".to_string());
+        let max_daily_loss = profile.max_daily_loss * portfolio_value;
+        if daily_pnl < -max_daily_loss {
+            rejections.push(RiskRejection {
+                check: RiskCheck::DailyLoss,
+                reason: "Daily loss limit reached".to_string(),
+                limit: -max_daily_loss,
+                actual: daily_pnl,
+            });
         }
         
-        // Check correlated positions
-        let correlation_group = self.get_correlation_group(symbol).await;
-        if let Some(group) = correlation_group {
-            let correlated_count = positions.values()
-                .filter(|p| p.correlation_group.as_ref() == Some(&group))
-                .count();
+        // Check correlated positions against the latest estimated groups
+        if let Some(group) = self.get_correlation_group(symbol).await {
+            let mut correlated_count = 0;
+            for (other, value) in open_exposure {
+                if other != symbol && *value > 0.0 && self.get_correlation_group(other).await.as_ref() == Some(&group) {
+                    correlated_count += 1;
+                }
+            }
             
             if correlated_count >= profile.max_correlated_positions {
-                return Err(format!(
-                    "Maximum {} correlated positions already open",
-                    profile.max_correlated_positions
-                ));
+                rejections.push(RiskRejection {
+                    check: RiskCheck::CorrelatedPositions,
+                    reason: format!(
+                        "Maximum {} correlated positions already open in {}",
+                        profile.max_correlated_positions, group
+                    ),
+                    limit: profile.max_correlated_positions as f64,
+                    actual: correlated_count as f64,
+                });
             }
         }
         
-        Ok(())
+        rejections
     }
 
     pub async fn calculate_position_size(
@@ -218,12 +736,46 @@ impl RiskManager {
     }
 
     pub async fn calculate_portfolio_risk(&self, portfolio_value: f64) -> PortfolioRisk {
-        let positions = self.position_risks.read().await;
-        let position_list: Vec<PositionRisk> = positions.values().cloned().collect();
+        let position_list: Vec<PositionRisk> = self.position_risks.read().await.values().cloned().collect();
+        let config = self.var_config.read().await.clone();
         
         let total_risk: f64 = position_list.iter().map(|p| p.risk_amount).sum();
-        let var_95 = self.calculate_portfolio_var(&position_list).await;
-        let cvar_95 = var_95 * 1.2; // Simplified CVaR calculation
+        let symbols: Vec<String> = position_list.iter().map(|p| p.symbol.clone()).collect();
+        let exposures: Vec<f64> = position_list.iter().map(|p| p.position_size * p.current_price).collect();
+        
+        let model = self.fit_return_model(&symbols, &config).await;
+        let (var_95, cvar_95, var_estimates, var_backtest) = match &model {
+            Some(model) => {
+                let mut rng = match config.monte_carlo_seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                };
+                let mut estimates = Vec::new();
+                for method in [VarMethod::Historical, VarMethod::Parametric, VarMethod::MonteCarlo] {
+                    for &confidence in &config.confidence_levels {
+                        for &horizon in &config.horizons {
+                            if let Some(estimate) = Self::estimate(model, &exposures, method, confidence, horizon, &config, &mut rng) {
+                                estimates.push(estimate);
+                            }
+                        }
+                    }
+                }
+                let primary = estimates.iter()
+                    .find(|e| e.method == config.primary_method && e.confidence == 0.95 && e.horizon == config.primary_horizon)
+                    .cloned()
+                    .or_else(|| Self::estimate(model, &exposures, config.primary_method, 0.95, config.primary_horizon, &config, &mut rng));
+                let (var, cvar) = primary.map(|e| (e.var, e.cvar)).unwrap_or((0.0, 0.0));
+                let backtest = model.backtest(&exposures, config.backtest_confidence, config.backtest_window, config.min_observations);
+                (var, cvar, estimates, backtest)
+            }
+            None => {
+                // Not enough history yet: normal approximation with the fallback volatility
+                let var = self.calculate_portfolio_var(&position_list, &config).await;
+                let z = risk_stats::normal_quantile(0.95);
+                let cvar = var * risk_stats::normal_pdf(z) / (0.05 * z);
+                (var, cvar, Vec::new(), None)
+            }
+        };
         
         let (sharpe_ratio, sortino_ratio) = self.calculate_risk_ratios().await;
         let (max_drawdown, current_drawdown) = self.calculate_drawdowns(portfolio_value).await;
@@ -235,6 +787,10 @@ impl RiskManager {
             total_risk,
             var_95,
             cvar_95,
+            var_estimates,
+            var_backtest,
+            return_observations: model.as_ref().map(|m| m.periods()).unwrap_or(0),
+            var_fallback: model.is_none() && !position_list.is_empty(),
             sharpe_ratio,
             sortino_ratio,
             max_drawdown,
@@ -245,16 +801,75 @@ impl RiskManager {
         }
     }
 
+    fn estimate(
+        model: &ReturnModel,
+        exposures: &[f64],
+        method: VarMethod,
+        confidence: f64,
+        horizon: u32,
+        config: &VarConfig,
+        rng: &mut StdRng,
+    ) -> Option<VarEstimate> {
+        let (var, cvar) = match method {
+            VarMethod::Historical => model.historical(exposures, confidence, horizon, config.min_observations),
+            VarMethod::Parametric => model.parametric(exposures, confidence, horizon),
+            VarMethod::MonteCarlo => model.monte_carlo(exposures, confidence, horizon, config.monte_carlo_paths, rng),
+        }?;
+        Some(VarEstimate { method, confidence, horizon, var, cvar })
+    }
+
+    /// Aligned returns for the symbols, or None while the store holds too little history
+    async fn fit_return_model(&self, symbols: &[String], config: &VarConfig) -> Option<ReturnModel> {
+        let store = self.data_store.as_ref()?;
+        if symbols.is_empty() {
+            return None;
+        }
+        
+        let mut series_keys = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            let mut found = None;
+            for candidate in symbol_aliases(symbol) {
+                if store.get_log_returns(&candidate, config.timeframe, config.lookback).await.len() >= config.min_observations {
+                    found = Some(candidate);
+                    break;
+                }
+            }
+            series_keys.push(found?);
+        }
+        
+        let (_, returns) = store.get_aligned_returns(&series_keys, config.timeframe, config.lookback).await;
+        if returns.first().map(|r| r.len()).unwrap_or(0) < config.min_observations {
+            return None;
+        }
+        Some(ReturnModel::fit(returns, config.shrink_covariance))
+    }
+
     async fn calculate_var(&self, symbol: String, position_size: f64, current_price: f64) -> f64 {
-        // Simplified VaR calculation using historical volatility
-        let volatility = 0.02; // 2% daily volatility placeholder
-        let confidence_level = 1.645; // 95% confidence
+        let config = self.var_config.read().await.clone();
+        let exposure = position_size * current_price;
+        
+        if let Some(model) = self.fit_return_model(std::slice::from_ref(&symbol), &config).await {
+            let mut rng = match config.monte_carlo_seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            if let Some(estimate) = Self::estimate(&model, &[exposure], config.primary_method, 0.95, config.primary_horizon, &config, &mut rng) {
+                return estimate.var;
+            }
+            warn!("⚠️ {:?} VaR failed for {}; using fallback volatility", config.primary_method, symbol);
+        }
         
-        position_size * current_price * volatility * confidence_level
+        exposure.abs() * Self::fallback_volatility(&config) * risk_stats::normal_quantile(0.95)
     }
 
-    async fn calculate_portfolio_var(&self, positions: &[PositionRisk]) -> f64 {
-        // Simplified portfolio VaR considering correlations
+    /// Fallback daily volatility rescaled to the primary horizon
+    fn fallback_volatility(config: &VarConfig) -> f64 {
+        let horizon_secs = config.timeframe.to_seconds() as f64 * config.primary_horizon.max(1) as f64;
+        config.fallback_volatility * (horizon_secs / 86_400.0).sqrt()
+    }
+
+    async fn calculate_portfolio_var(&self, positions: &[PositionRisk], config: &VarConfig) -> f64 {
+        // Position VaRs combined through known correlations
         let correlations = self.correlation_matrix.read().await;
         let mut portfolio_var = 0.0;
         
@@ -265,7 +880,7 @@ impl RiskManager {
                 } else {
                     correlations.get(&(positions[i].symbol.clone(), positions[j].symbol.clone()))
                         .copied()
-                        .unwrap_or(0.3) // Default correlation
+                        .unwrap_or(config.fallback_correlation)
                 };
                 
                 portfolio_var += positions[i].var_95 * positions[j].var_95 * correlation;
@@ -363,7 +978,11 @@ impl RiskManager {
     }
 
     async fn get_correlation_group(&self, symbol: &str) -> Option<String> {
-        // Group correlated assets
+        if let Some(group) = self.correlation_groups.read().await.get(symbol) {
+            return Some(group.clone());
+        }
+        
+        // No estimate covers this symbol yet: coarse static buckets
         if symbol.contains("BTC") || symbol.contains("ETH") {
             Some("CRYPTO_MAJOR".to_string())
         } else if symbol.contains("SOL") || symbol.contains("AVAX") {
@@ -484,17 +1103,18 @@ impl ExitStrategyManager {
         }
     }
 
+    /// `best_price` is the high-water mark for longs and the low-water mark for shorts
     pub fn trailing_stop_loss(
         &self,
         current_price: f64,
-        highest_price: f64,
+        best_price: f64,
         trail_percentage: f64,
         is_long: bool,
     ) -> f64 {
         if is_long {
-            highest_price * (1.0 - trail_percentage)
+            best_price.max(current_price) * (1.0 - trail_percentage)
         } else {
-            current_price * (1.0 + trail_percentage)
+            best_price.min(current_price) * (1.0 + trail_percentage)
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,341 @@
+// Risk Statistics - Numeric building blocks for portfolio risk
+// Empirical quantiles, covariance (sample and Ledoit-Wolf), Cholesky, clustering, distributions and VaR backtests
+
+use rand::Rng;
+
+// ============================================================================
+// DESCRIPTIVE STATISTICS
+// ============================================================================
+
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample covariance matrix of equally long series (`series[i]` is asset i)
+pub fn covariance_matrix(series: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = series.len();
+    let len = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    let mut cov = vec![vec![0.0; n]; n];
+    if len < 2 {
+        return cov;
+    }
+    let means: Vec<f64> = series.iter().map(|s| mean(&s[..len])).collect();
+    for i in 0..n {
+        for j in i..n {
+            let c = (0..len)
+                .map(|t| (series[i][t] - means[i]) * (series[j][t] - means[j]))
+                .sum::<f64>() / (len - 1) as f64;
+            cov[i][j] = c;
+            cov[j][i] = c;
+        }
+    }
+    cov
+}
+
+/// Ledoit-Wolf (2004) shrinkage of the sample covariance toward a scaled identity.
+/// Returns the shrunk matrix and the shrinkage intensity in [0, 1]
+pub fn ledoit_wolf_covariance(series: &[Vec<f64>]) -> (Vec<Vec<f64>>, f64) {
+    let n = series.len();
+    let len = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    if n == 0 || len < 2 {
+        return (vec![vec![0.0; n]; n], 0.0);
+    }
+    let t = len as f64;
+    let centered: Vec<Vec<f64>> = series.iter()
+        .map(|s| {
+            let m = mean(&s[..len]);
+            s[..len].iter().map(|x| x - m).collect()
+        })
+        .collect();
+
+    // Maximum-likelihood sample covariance (divides by T, as in the paper)
+    let mut sample = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let c = (0..len).map(|k| centered[i][k] * centered[j][k]).sum::<f64>() / t;
+            sample[i][j] = c;
+            sample[j][i] = c;
+        }
+    }
+
+    // Squared norms are scaled by 1/N throughout
+    let target = (0..n).map(|i| sample[i][i]).sum::<f64>() / n as f64;
+    let d2 = (0..n)
+        .flat_map(|i| (0..n).map(move |j| (i, j)))
+        .map(|(i, j)| {
+            let f = if i == j { target } else { 0.0 };
+            (sample[i][j] - f).powi(2)
+        })
+        .sum::<f64>() / n as f64;
+    let b2_bar = (0..len)
+        .map(|k| {
+            (0..n)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .map(|(i, j)| (centered[i][k] * centered[j][k] - sample[i][j]).powi(2))
+                .sum::<f64>() / n as f64
+        })
+        .sum::<f64>() / (t * t);
+    let b2 = b2_bar.min(d2);
+    let shrinkage = if d2 > 0.0 { (b2 / d2).clamp(0.0, 1.0) } else { 1.0 };
+
+    let mut shrunk = sample;
+    for (i, row) in shrunk.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            let f = if i == j { target } else { 0.0 };
+            *value = shrinkage * f + (1.0 - shrinkage) * *value;
+        }
+    }
+    (shrunk, shrinkage)
+}
+
+pub fn correlation_from_covariance(cov: &[Vec<f64>], i: usize, j: usize) -> Option<f64> {
+    let denom = (cov[i][i] * cov[j][j]).sqrt();
+    if denom > 0.0 && denom.is_finite() {
+        Some((cov[i][j] / denom).clamp(-1.0, 1.0))
+    } else {
+        None
+    }
+}
+
+/// (VaR, CVaR) as positive losses from a P&L sample; CVaR averages the losses at or beyond VaR
+pub fn empirical_var_cvar(pnl: &[f64], confidence: f64) -> Option<(f64, f64)> {
+    if pnl.is_empty() {
+        return None;
+    }
+    let mut losses: Vec<f64> = pnl.iter().map(|p| -p).filter(|l| l.is_finite()).collect();
+    if losses.is_empty() {
+        return None;
+    }
+    losses.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let index = ((confidence * losses.len() as f64).ceil() as usize).clamp(1, losses.len()) - 1;
+    let var = losses[index];
+    let tail = &losses[index..];
+    let cvar = mean(tail);
+    Some((var.max(0.0), cvar.max(0.0)))
+}
+
+// ============================================================================
+// CLUSTERING
+// ============================================================================
+
+/// Correlation distance sqrt((1 - ρ) / 2): 0 for perfectly correlated, 1 for perfectly anti-correlated
+pub fn correlation_distance(rho: f64) -> f64 {
+    ((1.0 - rho.clamp(-1.0, 1.0)) / 2.0).sqrt()
+}
+
+/// Agglomerative clustering with average linkage; merging stops once the closest pair of
+/// clusters is farther apart than `max_distance`. Clusters are returned largest first
+pub fn average_linkage_clusters(distances: &[Vec<f64>], max_distance: f64) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<Vec<usize>> = (0..distances.len()).map(|i| vec![i]).collect();
+    let linkage = |a: &[usize], b: &[usize]| -> f64 {
+        let total: f64 = a.iter().flat_map(|&i| b.iter().map(move |&j| distances[i][j])).sum();
+        total / (a.len() * b.len()) as f64
+    };
+
+    while clusters.len() > 1 {
+        let mut closest: Option<(usize, usize, f64)> = None;
+        for a in 0..clusters.len() {
+            for b in a + 1..clusters.len() {
+                let d = linkage(&clusters[a], &clusters[b]);
+                if closest.map(|(_, _, best)| d < best).unwrap_or(true) {
+                    closest = Some((a, b, d));
+                }
+            }
+        }
+        match closest {
+            Some((a, b, d)) if d <= max_distance => {
+                let merged = clusters.remove(b);
+                clusters[a].extend(merged);
+            }
+            _ => break,
+        }
+    }
+
+    for cluster in clusters.iter_mut() {
+        cluster.sort_unstable();
+    }
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    clusters
+}
+
+// ============================================================================
+// LINEAR ALGEBRA
+// ============================================================================
+
+/// Lower-triangular L with L·Lᵀ = matrix, or None if the matrix is not positive definite
+pub fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut lower = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| lower[i][k] * lower[j][k]).sum();
+            if i == j {
+                let diag = matrix[i][i] - sum;
+                if diag <= 0.0 || !diag.is_finite() {
+                    return None;
+                }
+                lower[i][j] = diag.sqrt();
+            } else {
+                lower[i][j] = (matrix[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// Cholesky with growing diagonal jitter for sample covariances that are only semi-definite
+pub fn cholesky_with_jitter(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    if let Some(lower) = cholesky(matrix) {
+        return Some(lower);
+    }
+    let scale = (0..matrix.len()).map(|i| matrix[i][i].abs()).fold(0.0, f64::max).max(1e-12);
+    let mut jitter = scale * 1e-10;
+    for _ in 0..8 {
+        let mut adjusted = matrix.to_vec();
+        for (i, row) in adjusted.iter_mut().enumerate() {
+            row[i] += jitter;
+        }
+        if let Some(lower) = cholesky(&adjusted) {
+            return Some(lower);
+        }
+        jitter *= 10.0;
+    }
+    None
+}
+
+pub fn quadratic_form(weights: &[f64], matrix: &[Vec<f64>]) -> f64 {
+    let mut total = 0.0;
+    for i in 0..weights.len() {
+        for j in 0..weights.len() {
+            total += weights[i] * weights[j] * matrix[i][j];
+        }
+    }
+    total
+}
+
+// ============================================================================
+// NORMAL & CHI-SQUARE DISTRIBUTIONS
+// ============================================================================
+
+pub fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Inverse standard normal CDF (Acklam's rational approximation, |error| < 1.2e-9)
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02, 1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    const B: [f64; 5] = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02, 6.680131188771972e+01, -1.328068155288572e+01];
+    const C: [f64; 6] = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00, -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    const D: [f64; 4] = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00, 3.754408661907416e+00];
+    const P_LOW: f64 = 0.02425;
+
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    if p < P_LOW {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -normal_quantile(1.0 - p)
+    }
+}
+
+/// Complementary error function (Numerical Recipes erfcc, relative error < 1.2e-7)
+pub fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t * (-z * z - 1.26551223
+        + t * (1.00002368 + t * (0.37409196 + t * (0.09678418 + t * (-0.18628806
+        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+/// P(X > x) for a chi-square variable with one degree of freedom
+pub fn chi_square_1_sf(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    erfc((x / 2.0).sqrt())
+}
+
+/// Standard normal draw (Box-Muller)
+pub fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.gen::<f64>().max(f64::MIN_POSITIVE);
+    let u2: f64 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// ============================================================================
+// VAR BACKTESTING
+// ============================================================================
+
+/// Kupiec proportion-of-failures likelihood ratio and its chi-square(1) p-value
+pub fn kupiec_pof(observations: usize, breaches: usize, confidence: f64) -> (f64, f64) {
+    if observations == 0 {
+        return (0.0, 1.0);
+    }
+    let t = observations as f64;
+    let x = breaches as f64;
+    let p = 1.0 - confidence;
+    let observed = x / t;
+
+    // a·ln(b), taking 0·ln(0) as 0
+    let xlogy = |a: f64, b: f64| if a == 0.0 { 0.0 } else { a * b.ln() };
+    let null = xlogy(t - x, 1.0 - p) + xlogy(x, p);
+    let alternative = xlogy(t - x, 1.0 - observed) + xlogy(x, observed);
+    let lr = (-2.0 * (null - alternative)).max(0.0);
+    (lr, chi_square_1_sf(lr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(name: &str, actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{} = {} (expected {})", name, actual, expected);
+    }
+
+    #[test]
+    fn normal_quantile_matches_known_values() {
+        assert_close("normal_quantile(0.95)", normal_quantile(0.95), 1.6448536269514722, 1e-8);
+        assert_close("normal_quantile(0.99)", normal_quantile(0.99), 2.3263478740408408, 1e-8);
+        assert_close("normal_quantile(0.001)", normal_quantile(0.001), -3.090232306167813, 1e-8);
+    }
+
+    #[test]
+    fn kupiec_rejects_too_many_breaches() {
+        assert_close("chi_square_1_sf(3.841459)", chi_square_1_sf(3.841459), 0.05, 1e-6);
+        // 250 days at 99% with 8 breaches rejects the model at 5%
+        let (lr, p_value) = kupiec_pof(250, 8, 0.99);
+        assert_close("kupiec_pof(250, 8, 0.99)", lr, 7.7335507, 1e-5);
+        assert!(p_value < 0.05, "p = {}", p_value);
+    }
+
+    #[test]
+    fn correlated_assets_share_a_cluster() {
+        // Two tightly correlated assets and one independent one split into two clusters
+        let rho = [[1.0, 0.9, 0.1], [0.9, 1.0, 0.0], [0.1, 0.0, 1.0]];
+        let distances: Vec<Vec<f64>> = rho.iter().map(|row| row.iter().map(|r| correlation_distance(*r)).collect()).collect();
+        assert_eq!(average_linkage_clusters(&distances, correlation_distance(0.6)), vec![vec![0, 1], vec![2]]);
+    }
+
+    #[test]
+    fn cholesky_factors_a_positive_definite_matrix() {
+        let lower = cholesky(&[vec![4.0, 2.0], vec![2.0, 3.0]]).expect("positive definite");
+        assert_close("l00", lower[0][0], 2.0, 1e-12);
+        assert_close("l10", lower[1][0], 1.0, 1e-12);
+        assert_close("l11", lower[1][1], 2.0_f64.sqrt(), 1e-12);
+    }
+}
@@ -11,8 +11,9 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, VersionedTransaction},
     instruction::{AccountMeta, Instruction},
+    hash::Hash,
 };
 use anchor_lang::prelude::*;
 use rayon::prelude::*;
@@ -22,6 +23,8 @@ use dashmap::DashMap;
 use crossbeam::channel::{bounded, Sender, Receiver};
 use parking_lot::RwLock as ParkingRwLock;
 
+use crate::solana_tx::SolanaTxBuilder;
+
 // Zero-copy structures for Solana programs
 #[account(zero_copy)]
 #[repr(C)]
@@ -318,6 +321,22 @@ impl SmartContractManager {
         }
     }
     
+    // Signed v0 swap transaction with a priority fee, ready for any TransactionSubmitter
+    pub fn build_swap_transaction(
+        &self,
+        pool: &Pubkey,
+        payer: &Keypair,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        recent_blockhash: Hash,
+        compute_unit_price_micro_lamports: u64,
+    ) -> Result<VersionedTransaction> {
+        SolanaTxBuilder::new(payer.pubkey())
+            .instruction(self.create_swap_instruction(pool, &payer.pubkey(), amount_in, minimum_amount_out))
+            .compute_unit_price(compute_unit_price_micro_lamports)
+            .build_and_sign(recent_blockhash, &[payer])
+    }
+    
     // High-performance liquidity provision
     pub async fn provide_liquidity(
         &self,
@@ -0,0 +1,973 @@
+// Solana Transaction Assembly - Offline builder for versioned (v0) transactions and pluggable submission
+// Address lookup tables, compute-unit limit/price, recent-blockhash handling, local keypair signing and RPC submitters
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use reqwest::Client;
+use anyhow::{Result, anyhow, Context};
+use async_trait::async_trait;
+use log::{info, warn, debug};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use solana_sdk::{
+    address_lookup_table::state::AddressLookupTable,
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::{v0, AddressLookupTableAccount, VersionedMessage},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+use solana_system_interface::instruction::SystemInstruction;
+
+/// Hard cap the runtime enforces on a single transaction.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+const REQUEST_TIMEOUT_SECS: u64 = 15;
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+/// Rent-exempt minimum of a 165-byte SPL token account, paid by whoever creates it
+const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280;
+
+// ============================================================================
+// CONFIGURATION & RESULTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolanaTxConfig {
+    pub compute_unit_limit: u32,             // Used as-is when simulation is off, as the simulation budget otherwise
+    pub compute_unit_price_micro_lamports: u64,
+    pub simulate_before_send: bool,          // Size the compute-unit limit from a simulation
+    pub compute_unit_margin: f64,            // Headroom over simulated units, 0.1 = +10%
+    pub blockhash_ttl_secs: u64,             // Reuse a fetched blockhash for at most this long
+    pub confirm_timeout_secs: u64,
+    pub max_resubmits: u32,                  // Rebuilds with a fresh blockhash after expiry
+    pub skip_preflight: bool,
+    pub commitment: String,                  // processed | confirmed | finalized
+}
+
+impl Default for SolanaTxConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_limit: 200_000,
+            compute_unit_price_micro_lamports: 0,
+            simulate_before_send: true,
+            compute_unit_margin: 0.1,
+            blockhash_ttl_secs: 30,
+            confirm_timeout_secs: 60,
+            max_resubmits: 2,
+            skip_preflight: true,
+            commitment: "confirmed".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecentBlockhash {
+    pub hash: Hash,
+    pub last_valid_block_height: u64,
+    pub fetched_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationOutcome {
+    pub units_consumed: Option<u64>,
+    pub err: Option<String>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureStatus {
+    pub slot: u64,
+    pub confirmation_status: Option<String>,
+    pub err: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmittedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    pub compute_unit_limit: u32,
+    pub compute_unit_price_micro_lamports: u64,
+    pub fee_lamports: u64,
+    pub attempts: u32,
+}
+
+// ============================================================================
+// OFFLINE BUILDER
+// ============================================================================
+
+/// Assembles a v0 message without touching the network; the caller supplies the blockhash.
+#[derive(Debug, Clone)]
+pub struct SolanaTxBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+}
+
+impl SolanaTxBuilder {
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            lookup_tables: Vec::new(),
+            compute_unit_limit: None,
+            compute_unit_price: None,
+        }
+    }
+
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    pub fn lookup_table(mut self, table: AddressLookupTableAccount) -> Self {
+        self.lookup_tables.push(table);
+        self
+    }
+
+    pub fn lookup_tables(mut self, tables: impl IntoIterator<Item = AddressLookupTableAccount>) -> Self {
+        self.lookup_tables.extend(tables);
+        self
+    }
+
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units.min(MAX_COMPUTE_UNIT_LIMIT));
+        self
+    }
+
+    /// Priority fee in micro-lamports per compute unit
+    pub fn compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer
+    }
+
+    /// Compute-budget instructions go first so the runtime applies them before any program runs
+    fn all_instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::with_capacity(self.instructions.len() + 2);
+        if let Some(units) = self.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.compute_unit_price.filter(|p| *p > 0) {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions.extend(self.instructions.iter().cloned());
+        instructions
+    }
+
+    pub fn build_message(&self, recent_blockhash: Hash) -> Result<VersionedMessage> {
+        if self.instructions.is_empty() {
+            return Err(anyhow!("Transaction has no instructions"));
+        }
+        let message = v0::Message::try_compile(
+            &self.payer,
+            &self.all_instructions(),
+            &self.lookup_tables,
+            recent_blockhash,
+        ).map_err(|e| anyhow!("Failed to compile v0 message: {}", e))?;
+        Ok(VersionedMessage::V0(message))
+    }
+
+    /// Sign with every required signer; the fee payer must be among them
+    pub fn build_and_sign(&self, recent_blockhash: Hash, signers: &[&Keypair]) -> Result<VersionedTransaction> {
+        if !signers.iter().any(|s| s.pubkey() == self.payer) {
+            return Err(anyhow!("Fee payer {} is not among the signers", self.payer));
+        }
+        let message = self.build_message(recent_blockhash)?;
+        VersionedTransaction::try_new(message, signers)
+            .map_err(|e| anyhow!("Failed to sign transaction: {}", e))
+    }
+
+    /// Base fee plus priority fee for the configured compute budget
+    pub fn estimated_fee_lamports(&self, num_signatures: usize) -> u64 {
+        let units = self.compute_unit_limit.unwrap_or(200_000) as u128;
+        let priority = (units * self.compute_unit_price.unwrap_or(0) as u128).div_ceil(1_000_000) as u64;
+        LAMPORTS_PER_SIGNATURE * num_signatures as u64 + priority
+    }
+}
+
+// ============================================================================
+// KEYPAIRS
+// ============================================================================
+
+/// Keypair from a Solana CLI JSON file (array of 64 bytes)
+pub fn load_keypair(path: &str) -> Result<Keypair> {
+    read_keypair_file(path).map_err(|e| anyhow!("Failed to read keypair {}: {}", path, e))
+}
+
+/// Keypair from a base58-encoded 64-byte secret
+pub fn keypair_from_base58(secret: &str) -> Result<Keypair> {
+    let bytes = bs58::decode(secret.trim()).into_vec().context("Keypair is not valid base58")?;
+    Keypair::try_from(bytes.as_slice()).map_err(|e| anyhow!("Invalid keypair bytes: {}", e))
+}
+
+/// SOLANA_KEYPAIR_PATH takes precedence over the inline TRADING_WALLET_PRIVATE_KEY.
+/// Both are plaintext; prefer `Keystore::solana_keypair` where a keystore is available.
+pub fn keypair_from_env() -> Result<Keypair> {
+    if let Ok(path) = std::env::var("SOLANA_KEYPAIR_PATH") {
+        if !path.is_empty() {
+            return load_keypair(&path);
+        }
+    }
+    match std::env::var("TRADING_WALLET_PRIVATE_KEY") {
+        Ok(secret) if !secret.is_empty() => keypair_from_base58(&secret),
+        _ => Err(anyhow!("No Solana keypair configured (SOLANA_KEYPAIR_PATH or TRADING_WALLET_PRIVATE_KEY)")),
+    }
+}
+
+// ============================================================================
+// INSTRUCTIONS
+// ============================================================================
+
+/// Instruction in the JSON shape swap APIs return: `{programId, accounts: [{pubkey, isSigner, isWritable}], data}`, data in base64
+pub fn instruction_from_json(value: &serde_json::Value) -> Result<Instruction> {
+    let pubkey = |field: &serde_json::Value, name: &str| -> Result<Pubkey> {
+        let key = field.as_str().ok_or_else(|| anyhow!("Instruction is missing {}", name))?;
+        Pubkey::from_str(key).map_err(|e| anyhow!("Invalid {} {}: {}", name, key, e))
+    };
+    let accounts = value["accounts"].as_array()
+        .ok_or_else(|| anyhow!("Instruction is missing accounts"))?
+        .iter()
+        .map(|account| Ok(AccountMeta {
+            pubkey: pubkey(&account["pubkey"], "account pubkey")?,
+            is_signer: account["isSigner"].as_bool().unwrap_or(false),
+            is_writable: account["isWritable"].as_bool().unwrap_or(false),
+        }))
+        .collect::<Result<Vec<_>>>()?;
+    let data = BASE64.decode(value["data"].as_str().unwrap_or_default())
+        .context("Instruction data is not valid base64")?;
+    Ok(Instruction { program_id: pubkey(&value["programId"], "programId")?, accounts, data })
+}
+
+/// Native SOL transfer through the system program
+pub fn transfer_instruction(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    solana_system_interface::instruction::transfer(from, to, lamports)
+}
+
+/// The sender sets its own compute budget; caller-supplied budget instructions would conflict with it
+pub fn is_compute_budget_instruction(instruction: &Instruction) -> bool {
+    instruction.program_id == solana_sdk::compute_budget::id()
+}
+
+/// Value an instruction moves out of the signer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outflow {
+    Lamports(u64),
+    Token { mint: Option<Pubkey>, amount: u64, decimals: Option<u8> }, // Plain SPL transfers name neither
+}
+
+/// What an instruction does to accounts the signer does not own, read from its data and account list alone
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    pub recipients: Vec<Pubkey>,
+    pub outflow: Option<Outflow>,
+}
+
+/// System, SPL Token / Token-2022 and associated-token-account instructions. Anything else, or an
+/// instruction whose effect on other wallets cannot be bounded from its data, is `None`.
+pub fn decode_instruction(instruction: &Instruction, signer: &Pubkey) -> Option<DecodedInstruction> {
+    let account = |index: usize| instruction.accounts.get(index).map(|meta| meta.pubkey);
+    let decoded = |recipients: Vec<Pubkey>, outflow: Option<Outflow>| Some(DecodedInstruction { recipients, outflow });
+
+    if instruction.program_id == solana_system_interface::program::ID {
+        return match bincode::deserialize::<SystemInstruction>(&instruction.data).ok()? {
+            SystemInstruction::Transfer { lamports } => decoded(vec![account(1)?], Some(Outflow::Lamports(lamports))),
+            SystemInstruction::TransferWithSeed { lamports, .. } => decoded(vec![account(2)?], Some(Outflow::Lamports(lamports))),
+            // The new account signs the transaction as well, so it is the signer's own
+            SystemInstruction::CreateAccount { lamports, .. } => decoded(Vec::new(), Some(Outflow::Lamports(lamports))),
+            _ => None,
+        };
+    }
+
+    if instruction.program_id == TOKEN_PROGRAM_ID || instruction.program_id == TOKEN_2022_PROGRAM_ID {
+        let (&tag, rest) = instruction.data.split_first()?;
+        let amount = || rest.get(..8).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes);
+        return match tag {
+            3 => decoded(vec![account(1)?], Some(Outflow::Token { mint: None, amount: amount()?, decimals: None })),
+            12 => decoded(vec![account(2)?], Some(Outflow::Token {
+                mint: Some(account(1)?),
+                amount: amount()?,
+                decimals: Some(*rest.get(8)?),
+            })),
+            // CloseAccount back into the signer's wallet; into anyone else's it would hand over an unknown balance
+            9 if account(1)? == *signer => decoded(Vec::new(), None),
+            // SyncNative only recounts lamports already in a wrapped SOL account
+            17 => decoded(Vec::new(), None),
+            _ => None,
+        };
+    }
+
+    if instruction.program_id == ASSOCIATED_TOKEN_PROGRAM_ID {
+        // Create (no data or 0) and CreateIdempotent (1); the payer funds the new account's rent
+        if instruction.data.len() > 1 || instruction.data.first().is_some_and(|tag| *tag > 1) {
+            return None;
+        }
+        let owner = account(2)?;
+        if owner == *signer {
+            return decoded(Vec::new(), None);
+        }
+        return decoded(vec![owner], Some(Outflow::Lamports(TOKEN_ACCOUNT_RENT_LAMPORTS)));
+    }
+
+    None
+}
+
+// ============================================================================
+// SUBMITTERS
+// ============================================================================
+
+/// Network side of transaction submission; swap implementations for RPC, a local validator or bundles.
+#[async_trait]
+pub trait TransactionSubmitter: Send + Sync {
+    async fn latest_blockhash(&self) -> Result<RecentBlockhash>;
+    async fn block_height(&self) -> Result<u64>;
+    async fn simulate(&self, transaction: &VersionedTransaction) -> Result<SimulationOutcome>;
+    async fn send(&self, transaction: &VersionedTransaction) -> Result<Signature>;
+    async fn signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>>;
+    async fn lookup_table(&self, key: &Pubkey) -> Result<AddressLookupTableAccount>;
+    fn name(&self) -> &str;
+}
+
+/// JSON-RPC submitter; works against mainnet, devnet and `solana-test-validator` alike.
+pub struct RpcSubmitter {
+    client: Client,
+    rpc_url: String,
+    commitment: String,
+    skip_preflight: bool,
+}
+
+impl RpcSubmitter {
+    pub fn new(rpc_url: &str, commitment: &str, skip_preflight: bool) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+            rpc_url: rpc_url.to_string(),
+            commitment: commitment.to_string(),
+            skip_preflight,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let rpc_url = std::env::var("SOLANA_RPC_URL")
+            .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+        Self::new(&rpc_url, "confirmed", true)
+    }
+
+    /// Fund an account on a local validator or devnet before exercising the send path
+    pub async fn request_airdrop(&self, pubkey: &Pubkey, lamports: u64) -> Result<Signature> {
+        let result = self.call("requestAirdrop", serde_json::json!([
+            pubkey.to_string(), lamports, {"commitment": self.commitment}
+        ])).await?;
+        let signature = result.as_str().ok_or_else(|| anyhow!("Invalid requestAirdrop response: {}", result))?;
+        Signature::from_str(signature).map_err(|e| anyhow!("Invalid airdrop signature: {}", e))
+    }
+
+    pub async fn call(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response = self.client.post(&self.rpc_url).json(&body).send().await
+            .with_context(|| format!("{} request failed", method))?;
+        let mut data: serde_json::Value = response.json().await
+            .with_context(|| format!("{} returned invalid JSON", method))?;
+        if let Some(error) = data.get("error") {
+            return Err(anyhow!("{} failed: {}", method, error));
+        }
+        Ok(data["result"].take())
+    }
+
+    fn encode(transaction: &VersionedTransaction) -> Result<String> {
+        let bytes = bincode::serialize(transaction).context("Failed to serialize transaction")?;
+        Ok(BASE64.encode(bytes))
+    }
+}
+
+#[async_trait]
+impl TransactionSubmitter for RpcSubmitter {
+    async fn latest_blockhash(&self) -> Result<RecentBlockhash> {
+        let result = self.call("getLatestBlockhash", serde_json::json!([{"commitment": self.commitment}])).await?;
+        let hash = result["value"]["blockhash"].as_str()
+            .ok_or_else(|| anyhow!("Invalid getLatestBlockhash response: {}", result))?;
+        Ok(RecentBlockhash {
+            hash: Hash::from_str(hash).map_err(|e| anyhow!("Invalid blockhash {}: {}", hash, e))?,
+            last_valid_block_height: result["value"]["lastValidBlockHeight"].as_u64().unwrap_or(0),
+            fetched_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    async fn block_height(&self) -> Result<u64> {
+        let result = self.call("getBlockHeight", serde_json::json!([{"commitment": self.commitment}])).await?;
+        result.as_u64().ok_or_else(|| anyhow!("Invalid getBlockHeight response: {}", result))
+    }
+
+    async fn simulate(&self, transaction: &VersionedTransaction) -> Result<SimulationOutcome> {
+        let result = self.call("simulateTransaction", serde_json::json!([
+            Self::encode(transaction)?,
+            {
+                "encoding": "base64",
+                "commitment": self.commitment,
+                "sigVerify": false,
+                "replaceRecentBlockhash": true,
+            }
+        ])).await?;
+        let value = &result["value"];
+        Ok(SimulationOutcome {
+            units_consumed: value["unitsConsumed"].as_u64(),
+            err: (!value["err"].is_null()).then(|| value["err"].to_string()),
+            logs: value["logs"].as_array()
+                .map(|logs| logs.iter().filter_map(|l| l.as_str().map(str::to_string)).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    async fn send(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+        let result = self.call("sendTransaction", serde_json::json!([
+            Self::encode(transaction)?,
+            {
+                "encoding": "base64",
+                "skipPreflight": self.skip_preflight,
+                "preflightCommitment": self.commitment,
+                "maxRetries": 0,
+            }
+        ])).await?;
+        let signature = result.as_str().ok_or_else(|| anyhow!("Invalid sendTransaction response: {}", result))?;
+        Signature::from_str(signature).map_err(|e| anyhow!("Invalid signature {}: {}", signature, e))
+    }
+
+    async fn signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+        let result = self.call("getSignatureStatuses", serde_json::json!([
+            [signature.to_string()], {"searchTransactionHistory": false}
+        ])).await?;
+        let status = &result["value"][0];
+        if status.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(SignatureStatus {
+            slot: status["slot"].as_u64().unwrap_or(0),
+            confirmation_status: status["confirmationStatus"].as_str().map(str::to_string),
+            err: (!status["err"].is_null()).then(|| status["err"].to_string()),
+        }))
+    }
+
+    async fn lookup_table(&self, key: &Pubkey) -> Result<AddressLookupTableAccount> {
+        let result = self.call("getAccountInfo", serde_json::json!([
+            key.to_string(), {"encoding": "base64", "commitment": self.commitment}
+        ])).await?;
+        let encoded = result["value"]["data"][0].as_str()
+            .ok_or_else(|| anyhow!("Lookup table {} not found", key))?;
+        let data = BASE64.decode(encoded).context("Lookup table data is not valid base64")?;
+        let table = AddressLookupTable::deserialize(&data)
+            .map_err(|e| anyhow!("Failed to decode lookup table {}: {}", key, e))?;
+        Ok(AddressLookupTableAccount {
+            key: *key,
+            addresses: table.addresses.to_vec(),
+        })
+    }
+
+    fn name(&self) -> &str {
+        "rpc"
+    }
+}
+
+// ============================================================================
+// SENDER
+// ============================================================================
+
+/// Fetches blockhash and lookup tables, sizes the compute budget, signs, sends and confirms.
+pub struct SolanaTxSender {
+    submitter: Arc<dyn TransactionSubmitter>,
+    config: Arc<RwLock<SolanaTxConfig>>,
+    blockhash: Arc<RwLock<Option<RecentBlockhash>>>,
+    lookup_tables: Arc<RwLock<HashMap<Pubkey, AddressLookupTableAccount>>>,
+}
+
+impl SolanaTxSender {
+    pub fn new(submitter: Arc<dyn TransactionSubmitter>) -> Self {
+        Self {
+            submitter,
+            config: Arc::new(RwLock::new(SolanaTxConfig::default())),
+            blockhash: Arc::new(RwLock::new(None)),
+            lookup_tables: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub async fn get_config(&self) -> SolanaTxConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: SolanaTxConfig) {
+        *self.config.write().await = config;
+    }
+
+    pub fn submitter(&self) -> Arc<dyn TransactionSubmitter> {
+        self.submitter.clone()
+    }
+
+    /// Cached blockhash while it is younger than the TTL; `refresh` forces a new one
+    pub async fn recent_blockhash(&self, refresh: bool) -> Result<RecentBlockhash> {
+        let ttl = self.config.read().await.blockhash_ttl_secs;
+        let now = chrono::Utc::now().timestamp() as u64;
+        if !refresh {
+            if let Some(cached) = *self.blockhash.read().await {
+                if now.saturating_sub(cached.fetched_at) < ttl {
+                    return Ok(cached);
+                }
+            }
+        }
+        let fresh = self.submitter.latest_blockhash().await?;
+        *self.blockhash.write().await = Some(fresh);
+        Ok(fresh)
+    }
+
+    /// Lookup tables are immutable once frozen and append-only otherwise, so cache until told to drop them
+    pub async fn resolve_lookup_tables(&self, keys: &[Pubkey]) -> Result<Vec<AddressLookupTableAccount>> {
+        let mut tables = Vec::with_capacity(keys.len());
+        for key in keys {
+            if let Some(table) = self.lookup_tables.read().await.get(key).cloned() {
+                tables.push(table);
+                continue;
+            }
+            let table = self.submitter.lookup_table(key).await?;
+            self.lookup_tables.write().await.insert(*key, table.clone());
+            tables.push(table);
+        }
+        Ok(tables)
+    }
+
+    pub async fn invalidate_lookup_table(&self, key: &Pubkey) {
+        self.lookup_tables.write().await.remove(key);
+    }
+
+    /// Sign and simulate without sending; a program failure comes back in `err`, not as an `Err`
+    pub async fn simulate(
+        &self,
+        instructions: Vec<Instruction>,
+        lookup_table_keys: &[Pubkey],
+        signers: &[&Keypair],
+    ) -> Result<SimulationOutcome> {
+        let config = self.config.read().await.clone();
+        let builder = self.builder(instructions, lookup_table_keys, signers, &config).await?;
+        let blockhash = self.recent_blockhash(false).await?;
+        self.probe(&builder, blockhash.hash, signers).await
+    }
+
+    /// Build, size, sign, send and confirm; the fee payer is `signers[0]`
+    pub async fn submit(
+        &self,
+        instructions: Vec<Instruction>,
+        lookup_table_keys: &[Pubkey],
+        signers: &[&Keypair],
+    ) -> Result<SubmittedTransaction> {
+        let config = self.config.read().await.clone();
+        let mut builder = self.builder(instructions, lookup_table_keys, signers, &config).await?;
+
+        let mut blockhash = self.recent_blockhash(false).await?;
+        if config.simulate_before_send {
+            let simulation = self.probe(&builder, blockhash.hash, signers).await?;
+            if let Some(err) = simulation.err {
+                debug!("Simulation logs: {:?}", simulation.logs);
+                return Err(anyhow!("Simulation failed: {}", err));
+            }
+            if let Some(units) = simulation.units_consumed {
+                let sized = (units as f64 * (1.0 + config.compute_unit_margin.max(0.0))).ceil() as u32;
+                builder = builder.compute_unit_limit(sized.max(1));
+            }
+        }
+
+        for attempt in 1..=config.max_resubmits + 1 {
+            let transaction = builder.build_and_sign(blockhash.hash, signers)?;
+            let signature = self.submitter.send(&transaction).await?;
+            info!("📨 Sent Solana transaction {} via {} (attempt {})", signature, self.submitter.name(), attempt);
+
+            match self.confirm(&signature, &blockhash, &config).await? {
+                Some(status) => {
+                    if let Some(err) = status.err {
+                        return Err(anyhow!("Transaction {} failed on-chain: {}", signature, err));
+                    }
+                    return Ok(SubmittedTransaction {
+                        signature: signature.to_string(),
+                        slot: status.slot,
+                        compute_unit_limit: builder.compute_unit_limit.unwrap_or(config.compute_unit_limit),
+                        compute_unit_price_micro_lamports: config.compute_unit_price_micro_lamports,
+                        fee_lamports: builder.estimated_fee_lamports(transaction.signatures.len()),
+                        attempts: attempt,
+                    });
+                }
+                None => {
+                    warn!("⌛ Blockhash expired before {} landed, rebuilding", signature);
+                    blockhash = self.recent_blockhash(true).await?;
+                }
+            }
+        }
+
+        Err(anyhow!("Transaction did not land after {} attempts", config.max_resubmits + 1))
+    }
+
+    async fn builder(
+        &self,
+        instructions: Vec<Instruction>,
+        lookup_table_keys: &[Pubkey],
+        signers: &[&Keypair],
+        config: &SolanaTxConfig,
+    ) -> Result<SolanaTxBuilder> {
+        let payer = signers.first().ok_or_else(|| anyhow!("At least one signer is required"))?.pubkey();
+        let tables = self.resolve_lookup_tables(lookup_table_keys).await?;
+        Ok(SolanaTxBuilder::new(payer)
+            .instructions(instructions)
+            .lookup_tables(tables)
+            .compute_unit_limit(config.compute_unit_limit)
+            .compute_unit_price(config.compute_unit_price_micro_lamports))
+    }
+
+    /// Simulated at the maximum compute budget so the result can size the real one
+    async fn probe(&self, builder: &SolanaTxBuilder, blockhash: Hash, signers: &[&Keypair]) -> Result<SimulationOutcome> {
+        let probe = builder.clone()
+            .compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT)
+            .build_and_sign(blockhash, signers)?;
+        self.submitter.simulate(&probe).await
+    }
+
+    /// Poll until the commitment is reached; `None` once the blockhash can no longer land
+    async fn confirm(
+        &self,
+        signature: &Signature,
+        blockhash: &RecentBlockhash,
+        config: &SolanaTxConfig,
+    ) -> Result<Option<SignatureStatus>> {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(config.confirm_timeout_secs);
+        while std::time::Instant::now() < deadline {
+            if let Some(status) = self.submitter.signature_status(signature).await? {
+                if status.err.is_some() || commitment_reached(status.confirmation_status.as_deref(), &config.commitment) {
+                    return Ok(Some(status));
+                }
+            } else if blockhash.last_valid_block_height > 0
+                && self.submitter.block_height().await? > blockhash.last_valid_block_height
+            {
+                return Ok(None);
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+        Err(anyhow!("Timed out confirming {}", signature))
+    }
+}
+
+impl Clone for SolanaTxSender {
+    fn clone(&self) -> Self {
+        Self {
+            submitter: self.submitter.clone(),
+            config: self.config.clone(),
+            blockhash: self.blockhash.clone(),
+            lookup_tables: self.lookup_tables.clone(),
+        }
+    }
+}
+
+fn commitment_level(commitment: &str) -> u8 {
+    match commitment {
+        "finalized" => 2,
+        "confirmed" => 1,
+        _ => 0,
+    }
+}
+
+fn commitment_reached(status: Option<&str>, target: &str) -> bool {
+    status.map(|s| commitment_level(s) >= commitment_level(target)).unwrap_or(false)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::Mutex as StdMutex;
+
+    /// In-memory chain: every send lands at `confirmed` unless the first `expire_sends` are set to drop
+    pub(crate) struct MockSubmitter {
+        pub sent: StdMutex<Vec<VersionedTransaction>>,
+        pub simulated: StdMutex<Vec<VersionedTransaction>>,
+        pub units_consumed: Option<u64>,
+        pub simulation_error: Option<String>,
+        pub expire_sends: StdMutex<usize>,
+        dropped: StdMutex<Vec<Signature>>,
+        blockhashes: StdMutex<u64>,
+    }
+
+    impl MockSubmitter {
+        pub(crate) fn new() -> Self {
+            Self {
+                sent: StdMutex::new(Vec::new()),
+                simulated: StdMutex::new(Vec::new()),
+                units_consumed: Some(12_000),
+                simulation_error: None,
+                expire_sends: StdMutex::new(0),
+                dropped: StdMutex::new(Vec::new()),
+                blockhashes: StdMutex::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TransactionSubmitter for MockSubmitter {
+        async fn latest_blockhash(&self) -> Result<RecentBlockhash> {
+            let mut issued = self.blockhashes.lock().unwrap();
+            *issued += 1;
+            Ok(RecentBlockhash {
+                hash: Hash::new_from_array([*issued as u8; 32]),
+                last_valid_block_height: 100 * *issued,
+                fetched_at: chrono::Utc::now().timestamp() as u64,
+            })
+        }
+
+        async fn block_height(&self) -> Result<u64> {
+            Ok(100 * *self.blockhashes.lock().unwrap() + 1)
+        }
+
+        async fn simulate(&self, transaction: &VersionedTransaction) -> Result<SimulationOutcome> {
+            self.simulated.lock().unwrap().push(transaction.clone());
+            Ok(SimulationOutcome { units_consumed: self.units_consumed, err: self.simulation_error.clone(), logs: Vec::new() })
+        }
+
+        async fn send(&self, transaction: &VersionedTransaction) -> Result<Signature> {
+            self.sent.lock().unwrap().push(transaction.clone());
+            let mut expire = self.expire_sends.lock().unwrap();
+            if *expire > 0 {
+                *expire -= 1;
+                self.dropped.lock().unwrap().push(transaction.signatures[0]);
+            }
+            Ok(transaction.signatures[0])
+        }
+
+        async fn signature_status(&self, signature: &Signature) -> Result<Option<SignatureStatus>> {
+            if self.dropped.lock().unwrap().contains(signature) {
+                return Ok(None);
+            }
+            Ok(Some(SignatureStatus { slot: 42, confirmation_status: Some("confirmed".to_string()), err: None }))
+        }
+
+        async fn lookup_table(&self, key: &Pubkey) -> Result<AddressLookupTableAccount> {
+            Err(anyhow!("Lookup table {} not found", key))
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    fn compute_budget_units(transaction: &VersionedTransaction) -> Option<u32> {
+        let message = &transaction.message;
+        message.instructions().iter().find_map(|ix| {
+            let program = message.static_account_keys()[ix.program_id_index as usize];
+            (program == solana_sdk::compute_budget::id() && ix.data[0] == 2)
+                .then(|| u32::from_le_bytes(ix.data[1..5].try_into().unwrap()))
+        })
+    }
+
+    #[test]
+    fn builder_puts_compute_budget_first_and_signs_every_signature() {
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+        let transaction = SolanaTxBuilder::new(payer.pubkey())
+            .instruction(transfer_instruction(&payer.pubkey(), &recipient, 5_000))
+            .compute_unit_limit(50_000)
+            .compute_unit_price(1_000)
+            .build_and_sign(Hash::new_from_array([7; 32]), &[&payer])
+            .unwrap();
+
+        let message = &transaction.message;
+        assert_eq!(*message.recent_blockhash(), Hash::new_from_array([7; 32]));
+        assert_eq!(message.static_account_keys()[0], payer.pubkey());
+        let programs: Vec<Pubkey> = message.instructions().iter()
+            .map(|ix| message.static_account_keys()[ix.program_id_index as usize])
+            .collect();
+        assert_eq!(programs, vec![solana_sdk::compute_budget::id(), solana_sdk::compute_budget::id(), solana_system_interface::program::ID]);
+        assert_eq!(compute_budget_units(&transaction), Some(50_000));
+        assert!(transaction.verify_with_results().iter().all(|ok| *ok));
+
+        // Base fee plus 50k units at 1000 micro-lamports
+        let builder = SolanaTxBuilder::new(payer.pubkey()).compute_unit_limit(50_000).compute_unit_price(1_000);
+        assert_eq!(builder.estimated_fee_lamports(1), 5_000 + 50);
+    }
+
+    #[test]
+    fn builder_rejects_a_payer_that_does_not_sign() {
+        let payer = Keypair::new();
+        let other = Keypair::new();
+        let result = SolanaTxBuilder::new(payer.pubkey())
+            .instruction(transfer_instruction(&payer.pubkey(), &other.pubkey(), 1))
+            .build_and_sign(Hash::default(), &[&other]);
+        assert!(result.unwrap_err().to_string().contains("not among the signers"));
+        assert!(SolanaTxBuilder::new(payer.pubkey()).build_message(Hash::default()).is_err());
+    }
+
+    #[test]
+    fn instruction_json_decodes_the_swap_api_shape() {
+        let program = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let instruction = instruction_from_json(&serde_json::json!({
+            "programId": program.to_string(),
+            "accounts": [{ "pubkey": account.to_string(), "isSigner": false, "isWritable": true }],
+            "data": BASE64.encode([1u8, 2, 3]),
+        })).unwrap();
+        assert_eq!(instruction.program_id, program);
+        assert_eq!(instruction.accounts, vec![AccountMeta::new(account, false)]);
+        assert_eq!(instruction.data, vec![1, 2, 3]);
+        assert!(!is_compute_budget_instruction(&instruction));
+        assert!(is_compute_budget_instruction(&ComputeBudgetInstruction::set_compute_unit_limit(1)));
+
+        assert!(instruction_from_json(&serde_json::json!({ "programId": "not-a-key", "accounts": [], "data": "" })).is_err());
+        assert!(instruction_from_json(&serde_json::json!({ "programId": program.to_string(), "data": "" })).is_err());
+    }
+
+    #[test]
+    fn decodes_system_and_token_transfers_but_not_unknown_programs() {
+        let signer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        assert_eq!(
+            decode_instruction(&transfer_instruction(&signer, &recipient, 7_000), &signer),
+            Some(DecodedInstruction { recipients: vec![recipient], outflow: Some(Outflow::Lamports(7_000)) }),
+        );
+
+        let (source, mint, destination) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![12];
+        data.extend_from_slice(&2_500_000u64.to_le_bytes());
+        data.push(6);
+        let transfer_checked = Instruction {
+            program_id: TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(source, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(signer, true),
+            ],
+            data,
+        };
+        assert_eq!(
+            decode_instruction(&transfer_checked, &signer),
+            Some(DecodedInstruction {
+                recipients: vec![destination],
+                outflow: Some(Outflow::Token { mint: Some(mint), amount: 2_500_000, decimals: Some(6) }),
+            }),
+        );
+
+        // Closing into someone else's wallet and unknown programs cannot be bounded
+        let close = |destination: Pubkey| Instruction {
+            program_id: TOKEN_2022_PROGRAM_ID,
+            accounts: vec![AccountMeta::new(source, false), AccountMeta::new(destination, false), AccountMeta::new_readonly(signer, true)],
+            data: vec![9],
+        };
+        assert_eq!(decode_instruction(&close(signer), &signer), Some(DecodedInstruction { recipients: Vec::new(), outflow: None }));
+        assert_eq!(decode_instruction(&close(recipient), &signer), None);
+        let swap = Instruction { program_id: Pubkey::new_unique(), accounts: vec![AccountMeta::new(recipient, false)], data: vec![1, 2, 3] };
+        assert_eq!(decode_instruction(&swap, &signer), None);
+    }
+
+    #[test]
+    fn base58_keypairs_round_trip() {
+        let keypair = Keypair::new();
+        let encoded = bs58::encode(keypair.to_bytes()).into_string();
+        assert_eq!(keypair_from_base58(&encoded).unwrap().pubkey(), keypair.pubkey());
+        assert!(keypair_from_base58("abc").is_err());
+    }
+
+    #[tokio::test]
+    async fn submit_sizes_the_compute_budget_from_simulation() {
+        let mock = Arc::new(MockSubmitter::new());
+        let sender = SolanaTxSender::new(mock.clone());
+        sender.set_config(SolanaTxConfig { compute_unit_margin: 0.25, ..SolanaTxConfig::default() }).await;
+        let payer = Keypair::new();
+
+        let submitted = sender.submit(
+            vec![transfer_instruction(&payer.pubkey(), &Pubkey::new_unique(), 1_000)], &[], &[&payer],
+        ).await.unwrap();
+
+        assert_eq!(submitted.attempts, 1);
+        assert_eq!(submitted.slot, 42);
+        // 12k simulated units plus the 25% margin
+        assert_eq!(submitted.compute_unit_limit, 15_000);
+        assert_eq!(compute_budget_units(&mock.simulated.lock().unwrap()[0]), Some(MAX_COMPUTE_UNIT_LIMIT));
+        let sent = mock.sent.lock().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(compute_budget_units(&sent[0]), Some(15_000));
+        assert_eq!(submitted.signature, sent[0].signatures[0].to_string());
+    }
+
+    #[tokio::test]
+    async fn submit_stops_when_simulation_fails() {
+        let mut mock = MockSubmitter::new();
+        mock.simulation_error = Some("InstructionError(0, Custom(1))".to_string());
+        let mock = Arc::new(mock);
+        let sender = SolanaTxSender::new(mock.clone());
+        let payer = Keypair::new();
+
+        let error = sender.submit(
+            vec![transfer_instruction(&payer.pubkey(), &Pubkey::new_unique(), 1_000)], &[], &[&payer],
+        ).await.unwrap_err();
+        assert!(error.to_string().contains("Simulation failed"));
+        assert!(mock.sent.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn submit_rebuilds_with_a_fresh_blockhash_after_expiry() {
+        let mock = Arc::new(MockSubmitter::new());
+        *mock.expire_sends.lock().unwrap() = 1;
+        let sender = SolanaTxSender::new(mock.clone());
+        let payer = Keypair::new();
+
+        let submitted = sender.submit(
+            vec![transfer_instruction(&payer.pubkey(), &Pubkey::new_unique(), 1_000)], &[], &[&payer],
+        ).await.unwrap();
+
+        assert_eq!(submitted.attempts, 2);
+        let sent = mock.sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_ne!(sent[0].message.recent_blockhash(), sent[1].message.recent_blockhash());
+        assert_eq!(submitted.signature, sent[1].signatures[0].to_string());
+    }
+
+    /// Needs `solana-test-validator` on its default port: `cargo test local_validator -- --ignored`
+    #[tokio::test]
+    #[ignore]
+    async fn local_validator_transfer_confirms() {
+        let submitter = Arc::new(RpcSubmitter::new("http://127.0.0.1:8899", "confirmed", false));
+        let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
+
+        let airdrop = submitter.request_airdrop(&payer.pubkey(), 1_000_000_000).await.unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+        while !commitment_reached(
+            submitter.signature_status(&airdrop).await.unwrap().and_then(|s| s.confirmation_status).as_deref(),
+            "confirmed",
+        ) {
+            assert!(std::time::Instant::now() < deadline, "airdrop did not confirm");
+            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        }
+
+        let sender = SolanaTxSender::new(submitter.clone());
+        let submitted = sender.submit(
+            vec![transfer_instruction(&payer.pubkey(), &recipient, 10_000_000)], &[], &[&payer],
+        ).await.unwrap();
+        assert!(submitted.slot > 0);
+
+        let balance = submitter.call("getBalance", serde_json::json!([recipient.to_string(), {"commitment": "confirmed"}])).await.unwrap();
+        assert_eq!(balance["value"].as_u64(), Some(10_000_000));
+    }
+}
@@ -11,6 +11,13 @@ use anyhow::{Result, anyhow};
 use log::{info, warn, error, debug};
 use chrono;
 
+use crate::fee_model::{CostKind, FeeModel, Liquidity, PricedLeg, ProfitBreakdown, TradeLeg};
+use crate::execution_coordinator::{ExecutionCoordinator, TwoLegPlan};
+use crate::paper_trading::{PaperTradingEngine, TradingMode};
+use crate::circuit_breaker::{BreakerReason, BreakerScope, CircuitBreaker, TradeOutcome};
+use crate::ledger::{FillSide, LedgerFill, TaxLotLedger};
+use crate::risk_gate::{OrderOrigin, PreTradeRequest, PreTradeRiskGate};
+
 // ============================================================================
 // TRADE EXECUTION DATA STRUCTURES
 // ============================================================================
@@ -34,9 +41,14 @@ pub struct TradeExecution {
     pub completed_at: Option<u64>,
     pub error_message: Option<String>,
     pub trade_steps: Vec<ExecutionStep>,
+    pub profit_breakdown: Option<ProfitBreakdown>,
+    pub leg_timeline: Vec<LegEvent>,
+    pub residual_exposure: Decimal, // Base units left open after hedging/unwinding (+long, -short)
+    #[serde(default)]
+    pub paper: bool, // Filled against the paper account; excluded from live P&L
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ExecutionStrategy {
     SimpleArbitrage,
     TriangularArbitrage,
@@ -53,6 +65,7 @@ pub enum ExecutionStatus {
     Completed,
     Failed,
     Cancelled,
+    Unwound,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,14 +75,47 @@ pub struct ExecutionStep {
     pub action: String, // "buy", "sell", "swap", "flashloan"
     pub from_token: String,
     pub to_token: String,
-    pub amount: Decimal,
+    pub amount: Decimal, // Base units
     pub price: Decimal,
+    #[serde(default)]
+    pub fee_usd: Decimal, // Venue fees for this step; gas is tracked on the trade
     pub status: ExecutionStatus,
     pub transaction_hash: Option<String>,
     pub gas_used: Option<u64>,
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegRole {
+    Buy,
+    Sell,
+    Hedge,
+    Unwind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LegEventKind {
+    Submitted,
+    Filled,
+    PartiallyFilled,
+    Failed,
+    Skipped,
+}
+
+/// One entry in a trade's leg timeline (submission, fill, failure, hedge or unwind).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegEvent {
+    pub timestamp_ms: u64,
+    pub role: LegRole,
+    pub kind: LegEventKind,
+    pub venue: String,
+    pub side: String,
+    pub amount: Decimal,
+    pub price: Option<Decimal>,
+    pub order_id: Option<String>,
+    pub detail: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Portfolio {
     pub total_value_usd: Decimal,
@@ -96,11 +142,8 @@ pub struct TokenBalance {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskParameters {
-    pub max_position_size: Decimal,
     pub max_slippage: f64,
     pub max_gas_price: u64,
-    pub min_profit_threshold: f64,
-    pub max_concurrent_trades: u32,
     pub stop_loss_percentage: f64,
     pub take_profit_percentage: f64,
     pub max_drawdown_limit: f64,
@@ -135,23 +178,45 @@ pub struct TradeExecutionEngine {
     
     // Risk management
     risk_parameters: Arc<RwLock<RiskParameters>>,
+    circuit_breaker: Arc<CircuitBreaker>,
+    risk_gate: Arc<PreTradeRiskGate>,
     
     // Performance tracking
     metrics: Arc<Mutex<ExecutionMetrics>>,
     
+    // Paper trading: virtual account, and P&L kept apart from live
+    paper: Arc<PaperTradingEngine>,
+    paper_portfolio: Arc<RwLock<Portfolio>>,
+    paper_metrics: Arc<Mutex<ExecutionMetrics>>,
+    
     // Configuration
     enabled: Arc<RwLock<bool>>,
-    simulation_mode: Arc<RwLock<bool>>,
+    simulation_mode: Arc<RwLock<bool>>, // Default mode for strategies without an override
+    strategy_modes: Arc<RwLock<HashMap<ExecutionStrategy, TradingMode>>>,
+    
+    // Shared venue/gas fee model
+    fee_model: Arc<FeeModel>,
+    
+    // Two-leg dispatch with hedge/unwind of residual exposure
+    coordinator: Arc<ExecutionCoordinator>,
+    
+    // Tax-lot ledgers behind portfolio P&L (live and paper kept apart)
+    ledger: Arc<TaxLotLedger>,
+    paper_ledger: Arc<TaxLotLedger>,
 }
 
 impl TradeExecutionEngine {
-    pub fn new() -> Self {
+    /// `risk_gate` is shared with the other order paths; this engine also feeds it book state
+    pub fn new(
+        fee_model: Arc<FeeModel>,
+        coordinator: Arc<ExecutionCoordinator>,
+        paper: Arc<PaperTradingEngine>,
+        circuit_breaker: Arc<CircuitBreaker>,
+        risk_gate: Arc<PreTradeRiskGate>,
+    ) -> Self {
         let default_risk_params = RiskParameters {
-            max_position_size: Decimal::from_str("10000").unwrap(), // $10,000 max position
             max_slippage: 0.5, // 0.5% max slippage
             max_gas_price: 100_000_000_000, // 100 gwei
-            min_profit_threshold: 0.1, // 0.1% minimum profit
-            max_concurrent_trades: 5,
             stop_loss_percentage: 2.0, // 2% stop loss
             take_profit_percentage: 5.0, // 5% take profit
             max_drawdown_limit: 10.0, // 10% max drawdown
@@ -190,14 +255,29 @@ impl TradeExecutionEngine {
         Self {
             active_trades: Arc::new(RwLock::new(HashMap::new())),
             trade_history: Arc::new(RwLock::new(Vec::new())),
+            paper_portfolio: Arc::new(RwLock::new(default_portfolio.clone())),
             portfolio: Arc::new(RwLock::new(default_portfolio)),
             risk_parameters: Arc::new(RwLock::new(default_risk_params)),
+            circuit_breaker,
+            paper_metrics: Arc::new(Mutex::new(default_metrics.clone())),
             metrics: Arc::new(Mutex::new(default_metrics)),
+            paper,
             enabled: Arc::new(RwLock::new(false)), // Start disabled for safety
             simulation_mode: Arc::new(RwLock::new(true)), // Start in simulation mode
+            strategy_modes: Arc::new(RwLock::new(HashMap::new())),
+            fee_model,
+            coordinator,
+            ledger: Arc::new(TaxLotLedger::default()),
+            paper_ledger: Arc::new(TaxLotLedger::default()),
+            risk_gate,
         }
     }
 
+    pub fn with_ledger(mut self, ledger: Arc<TaxLotLedger>) -> Self {
+        self.ledger = ledger;
+        self
+    }
+
     /// Start the trade execution engine
     pub async fn start(&self) -> Result<()> {
         info!("🚀 Starting Trade Execution Engine...");
@@ -226,35 +306,55 @@ impl TradeExecutionEngine {
     pub async fn execute_arbitrage(
         &self,
         opportunity: &crate::ArbitrageOpportunity,
+    ) -> Result<TradeExecution> {
+        self.execute_arbitrage_as(opportunity, ExecutionStrategy::SimpleArbitrage).await
+    }
+
+    /// Execute an opportunity on behalf of a strategy, in that strategy's live or paper mode
+    pub async fn execute_arbitrage_as(
+        &self,
+        opportunity: &crate::ArbitrageOpportunity,
+        strategy: ExecutionStrategy,
     ) -> Result<TradeExecution> {
         let enabled = *self.enabled.read().await;
-        let simulation_mode = *self.simulation_mode.read().await;
+        let mode = self.trading_mode(strategy).await;
 
         if !enabled {
             return Err(anyhow!("Trade execution is disabled"));
         }
 
+        let strategy_name = format!("{:?}", strategy);
+        let venues = [opportunity.buy_exchange.as_str(), opportunity.sell_exchange.as_str()];
+        if let Err(trip) = self.circuit_breaker.check(&strategy_name, &venues).await {
+            warn!("🚨 Trade blocked by circuit breaker {} ({:?}): {}", trip.scope, trip.reason, trip.detail);
+            return Err(anyhow!("Circuit breaker {} open: {}", trip.scope, trip.detail));
+        }
+
         info!("🎯 Executing arbitrage opportunity: {}", opportunity.id);
 
-        // Risk assessment
-        let risk_check = self.assess_trade_risk(opportunity).await?;
-        if !risk_check.approved {
-            warn!("❌ Trade rejected by risk management: {}", risk_check.reason);
-            return Err(anyhow!("Risk management rejection: {}", risk_check.reason));
+        // Pre-trade risk gate; the approval reserves this trade's exposure until it finishes
+        let trade_id = format!("trade_{}", chrono::Utc::now().timestamp_millis());
+        let asset = opportunity.token_pair.split('/').next().unwrap_or(&opportunity.token_pair);
+        let request = PreTradeRequest::new(&trade_id, OrderOrigin::ExecutionEngine, asset, opportunity.max_trade_size.to_f64())
+            .with_strategy(strategy_name.clone())
+            .with_venues(venues.iter().map(|v| v.to_string()).collect())
+            .with_expected_profit(opportunity.profit_percentage.to_f64().unwrap_or(0.0), opportunity.risk_score)
+            .paper(mode == TradingMode::Paper);
+        if let Err(decision) = self.risk_gate.check(request).await {
+            return Err(anyhow!("Risk management rejection: {}", decision.summary()));
         }
 
         // Create trade execution
-        let trade_id = format!("trade_{}", chrono::Utc::now().timestamp_millis());
         let mut trade = TradeExecution {
             id: trade_id.clone(),
             opportunity_id: opportunity.id.clone(),
-            strategy: ExecutionStrategy::SimpleArbitrage,
+            strategy,
             status: ExecutionStatus::Pending,
             entry_price: opportunity.buy_price,
             exit_price: None,
             amount: opportunity.max_trade_size,
             realized_profit: None,
-            gas_cost: Decimal::from_str("0.01").unwrap(), // Estimated gas cost
+            gas_cost: opportunity.profit_breakdown.cost_of(CostKind::NetworkGas), // Estimated gas cost
             slippage: Decimal::ZERO,
             execution_time_ms: 0,
             risk_score: opportunity.risk_score,
@@ -263,6 +363,10 @@ impl TradeExecutionEngine {
             completed_at: None,
             error_message: None,
             trade_steps: Vec::new(),
+            profit_breakdown: None,
+            leg_timeline: Vec::new(),
+            residual_exposure: Decimal::ZERO,
+            paper: mode == TradingMode::Paper,
         };
 
         // Add to active trades
@@ -274,20 +378,40 @@ impl TradeExecutionEngine {
         // Execute trade steps
         let execution_start = std::time::Instant::now();
         
-        if simulation_mode {
-            // Simulate execution
-            trade = self.simulate_trade_execution(trade, opportunity).await?;
-        } else {
-            // Real execution (placeholder for now)
-            trade = self.execute_real_trade(trade, opportunity).await?;
-        }
+        let (buy, sell) = (&opportunity.buy_exchange, &opportunity.sell_exchange);
+        let executed = match mode {
+            TradingMode::Live => self.execute_real_trade(trade, opportunity, &self.coordinator).await,
+            // Scenario venues replay their own books; otherwise fill against the paper account
+            TradingMode::Paper if self.coordinator.can_execute(buy, sell, true).await => {
+                self.execute_real_trade(trade, opportunity, &self.coordinator).await
+            }
+            TradingMode::Paper if self.paper.coordinator().can_execute(buy, sell, true).await => {
+                let paper_coordinator = self.paper.coordinator();
+                self.execute_real_trade(trade, opportunity, &paper_coordinator).await
+            }
+            // No market data for these venues yet: synthetic fills
+            TradingMode::Paper => self.simulate_trade_execution(trade, opportunity).await,
+        };
+        trade = match executed {
+            Ok(trade) => trade,
+            Err(e) => {
+                self.risk_gate.release(&trade_id).await;
+                return Err(e);
+            }
+        };
 
         trade.execution_time_ms = execution_start.elapsed().as_millis() as u64;
         trade.completed_at = Some(chrono::Utc::now().timestamp() as u64);
 
-        // Update portfolio and metrics
+        // Record fills as tax lots, then update portfolio and metrics
+        self.record_ledger_fills(&trade).await;
         self.update_portfolio_after_trade(&trade).await?;
         self.update_metrics_after_trade(&trade).await?;
+        // The book now carries whatever this trade left open
+        self.risk_gate.release(&trade_id).await;
+        if !trade.paper {
+            self.record_breaker_outcome(&strategy_name, &trade).await;
+        }
 
         // Move to history
         {
@@ -310,6 +434,25 @@ impl TradeExecutionEngine {
         Ok(trade)
     }
 
+    /// Live trades feed the strategy, global and venue circuit breakers
+    async fn record_breaker_outcome(&self, strategy: &str, trade: &TradeExecution) {
+        let slippage_bps = if trade.amount > Decimal::ZERO {
+            (trade.slippage / trade.amount * Decimal::from(10_000)).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        let outcome = TradeOutcome {
+            success: matches!(trade.status, ExecutionStatus::Completed),
+            pnl_usd: trade.realized_profit.unwrap_or(Decimal::ZERO),
+            slippage_bps,
+        };
+        self.circuit_breaker.record_trade(strategy, &outcome).await;
+        for step in &trade.trade_steps {
+            let ok = !matches!(step.status, ExecutionStatus::Failed);
+            self.circuit_breaker.record_venue_result(&step.exchange, ok).await;
+        }
+    }
+
     /// Simulate trade execution for testing
     async fn simulate_trade_execution(
         &self,
@@ -320,15 +463,23 @@ impl TradeExecutionEngine {
 
         trade.status = ExecutionStatus::Executing;
 
+        let (base, quote) = opportunity.token_pair.split_once('/').unwrap_or(("SOL", "USDC"));
+        let base_amount = if opportunity.buy_price > Decimal::ZERO {
+            trade.amount / opportunity.buy_price
+        } else {
+            trade.amount
+        };
+
         // Simulate buy step
         let buy_step = ExecutionStep {
             step_id: format!("{}_buy", trade.id),
             exchange: opportunity.buy_exchange.clone(),
             action: "buy".to_string(),
-            from_token: "USDC".to_string(),
-            to_token: "SOL".to_string(),
-            amount: trade.amount,
+            from_token: quote.to_string(),
+            to_token: base.to_string(),
+            amount: base_amount,
             price: opportunity.buy_price,
+            fee_usd: Decimal::ZERO,
             status: ExecutionStatus::Completed,
             transaction_hash: Some(format!("0x{:x}", rand::random::<u64>())),
             gas_used: Some(150_000),
@@ -340,10 +491,11 @@ impl TradeExecutionEngine {
             step_id: format!("{}_sell", trade.id),
             exchange: opportunity.sell_exchange.clone(),
             action: "sell".to_string(),
-            from_token: "SOL".to_string(),
-            to_token: "USDC".to_string(),
-            amount: trade.amount,
+            from_token: base.to_string(),
+            to_token: quote.to_string(),
+            amount: base_amount,
             price: opportunity.sell_price,
+            fee_usd: Decimal::ZERO,
             status: ExecutionStatus::Completed,
             transaction_hash: Some(format!("0x{:x}", rand::random::<u64>())),
             gas_used: Some(120_000),
@@ -351,15 +503,54 @@ impl TradeExecutionEngine {
         };
 
         trade.trade_steps = vec![buy_step, sell_step];
-
-        // Calculate simulated results
-        let gross_profit = opportunity.sell_price - opportunity.buy_price;
-        let slippage_cost = gross_profit * Decimal::from_str("0.001").unwrap(); // 0.1% slippage
-        let gas_cost = Decimal::from_str("0.015").unwrap(); // $0.015 gas cost
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        trade.leg_timeline = trade.trade_steps.iter()
+            .flat_map(|step| {
+                let role = if step.action == "buy" { LegRole::Buy } else { LegRole::Sell };
+                [LegEventKind::Submitted, LegEventKind::Filled].into_iter().map(move |kind| LegEvent {
+                    timestamp_ms: now_ms,
+                    role,
+                    kind,
+                    venue: step.exchange.clone(),
+                    side: step.action.clone(),
+                    amount: step.amount,
+                    price: Some(step.price),
+                    order_id: step.transaction_hash.clone(),
+                    detail: Some("Synthetic fill".to_string()),
+                })
+            })
+            .collect();
+
+        // Calculate simulated results through the fee model at the executed size
+        let mut breakdown = self.price_trade(&trade, opportunity).await;
+        let sell_notional = breakdown.notional_usd + breakdown.gross_profit_usd;
+        for (venue, notional_usd) in [
+            (&opportunity.buy_exchange, breakdown.notional_usd),
+            (&opportunity.sell_exchange, sell_notional),
+        ] {
+            self.fee_model.apply_slippage(&mut breakdown, &TradeLeg {
+                venue: venue.clone(),
+                notional_usd,
+                liquidity: Liquidity::Taker,
+            }).await;
+        }
+        let slippage_cost = breakdown.cost_of(CostKind::Slippage);
         
-        trade.realized_profit = Some(gross_profit - slippage_cost - gas_cost);
+        // Venue costs land on that venue's steps; gas stays on the trade
+        for index in 0..trade.trade_steps.len() {
+            let venue = trade.trade_steps[index].exchange.clone();
+            let steps_on_venue = trade.trade_steps.iter().filter(|s| s.exchange == venue).count();
+            let venue_costs: Decimal = breakdown.costs.iter()
+                .filter(|c| c.venue == venue && c.kind != CostKind::NetworkGas)
+                .map(|c| c.amount_usd)
+                .sum();
+            trade.trade_steps[index].fee_usd = venue_costs / Decimal::from(steps_on_venue as u64);
+        }
+        
+        trade.realized_profit = Some(breakdown.net_profit_usd);
         trade.slippage = slippage_cost;
-        trade.gas_cost = gas_cost;
+        trade.gas_cost = breakdown.cost_of(CostKind::NetworkGas);
+        trade.profit_breakdown = Some(breakdown);
         trade.exit_price = Some(opportunity.sell_price);
         trade.status = if trade.realized_profit.unwrap() > Decimal::ZERO {
             ExecutionStatus::Completed
@@ -376,103 +567,172 @@ impl TradeExecutionEngine {
         Ok(trade)
     }
 
-    /// Real trade execution (placeholder)
-    async fn execute_real_trade(
+    /// Itemized costs for the trade's notional; reuses the opportunity's breakdown when sizes match
+    async fn price_trade(
         &self,
-        mut trade: TradeExecution,
-        _opportunity: &crate::ArbitrageOpportunity,
-    ) -> Result<TradeExecution> {
-        warn!("🚧 Real trade execution not implemented yet - using simulation");
+        trade: &TradeExecution,
+        opportunity: &crate::ArbitrageOpportunity,
+    ) -> ProfitBreakdown {
+        if opportunity.profit_breakdown.notional_usd == trade.amount || opportunity.buy_price <= Decimal::ZERO {
+            return opportunity.profit_breakdown.clone();
+        }
         
-        // For now, redirect to simulation
-        trade.status = ExecutionStatus::Failed;
-        trade.error_message = Some("Real execution not implemented".to_string());
+        let base_amount = trade.amount / opportunity.buy_price;
+        let base_asset = opportunity.token_pair.split('/').next().unwrap_or("SOL");
+        let requires_transfer = self.fee_model
+            .requires_transfer(&opportunity.buy_exchange, &opportunity.sell_exchange)
+            .await;
         
-        Ok(trade)
+        self.fee_model.evaluate_two_leg(
+            PricedLeg { venue: &opportunity.buy_exchange, price: opportunity.buy_price },
+            PricedLeg { venue: &opportunity.sell_exchange, price: opportunity.sell_price },
+            base_amount,
+            base_asset,
+            requires_transfer,
+        ).await
     }
 
-    /// Assess trade risk before execution
-    async fn assess_trade_risk(
+    /// Execute both legs through a coordinator (live venues, scenario venues or the paper account)
+    async fn execute_real_trade(
         &self,
+        mut trade: TradeExecution,
         opportunity: &crate::ArbitrageOpportunity,
-    ) -> Result<RiskAssessment> {
-        let risk_params = self.risk_parameters.read().await;
-        let portfolio = self.portfolio.read().await;
-        let active_trades = self.active_trades.read().await;
-
-        // Check position size
-        if opportunity.max_trade_size > risk_params.max_position_size {
-            return Ok(RiskAssessment {
-                approved: false,
-                reason: "Position size exceeds maximum limit".to_string(),
-                risk_score: 1.0,
-            });
-        }
-
-        // Check available balance
-        if opportunity.max_trade_size > portfolio.available_balance {
-            return Ok(RiskAssessment {
-                approved: false,
-                reason: "Insufficient available balance".to_string(),
-                risk_score: 1.0,
-            });
-        }
-
-        // Check concurrent trades limit
-        if active_trades.len() >= risk_params.max_concurrent_trades as usize {
-            return Ok(RiskAssessment {
-                approved: false,
-                reason: "Maximum concurrent trades limit reached".to_string(),
-                risk_score: 0.8,
-            });
+        coordinator: &ExecutionCoordinator,
+    ) -> Result<TradeExecution> {
+        trade.status = ExecutionStatus::Executing;
+        
+        if opportunity.buy_price <= Decimal::ZERO {
+            trade.status = ExecutionStatus::Failed;
+            trade.error_message = Some("Opportunity has no buy price".to_string());
+            return Ok(trade);
         }
+        
+        let plan = TwoLegPlan {
+            trade_id: trade.id.clone(),
+            pair: opportunity.token_pair.clone(),
+            buy_venue: opportunity.buy_exchange.clone(),
+            sell_venue: opportunity.sell_exchange.clone(),
+            amount: trade.amount / opportunity.buy_price,
+            buy_price: opportunity.buy_price,
+            sell_price: opportunity.sell_price,
+        };
+        
+        let outcome = match coordinator.execute_two_leg(&plan).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                error!("❌ Coordinator refused {}: {}", trade.id, e);
+                trade.status = ExecutionStatus::Failed;
+                trade.error_message = Some(e.to_string());
+                return Ok(trade);
+            }
+        };
+        
+        // Slippage in USD against the opportunity prices, on the quantities that actually filled
+        let buy_slippage = outcome.avg_buy_price
+            .map(|p| (p - opportunity.buy_price) * outcome.bought)
+            .unwrap_or(Decimal::ZERO);
+        let sell_slippage = outcome.avg_sell_price
+            .map(|p| (opportunity.sell_price - p) * outcome.sold)
+            .unwrap_or(Decimal::ZERO);
+        
+        let breakdown = self.price_trade(&trade, opportunity).await;
+        trade.gas_cost = breakdown.cost_of(CostKind::NetworkGas);
+        trade.realized_profit = Some(outcome.pnl_usd - trade.gas_cost);
+        trade.profit_breakdown = Some(breakdown);
+        trade.slippage = buy_slippage + sell_slippage;
+        trade.entry_price = outcome.avg_buy_price.unwrap_or(opportunity.buy_price);
+        trade.exit_price = outcome.avg_sell_price;
+        trade.trade_steps = outcome.steps;
+        trade.leg_timeline = outcome.timeline;
+        trade.residual_exposure = outcome.residual;
+        trade.status = outcome.status;
+        trade.error_message = outcome.error;
+        
+        Ok(trade)
+    }
 
-        // Check minimum profit threshold
-        if opportunity.profit_percentage.to_f64().unwrap_or(0.0) < risk_params.min_profit_threshold {
-            return Ok(RiskAssessment {
-                approved: false,
-                reason: "Profit below minimum threshold".to_string(),
-                risk_score: 0.6,
-            });
+    /// Every filled buy/sell step becomes a ledger fill; the trade's gas is split across them
+    async fn record_ledger_fills(&self, trade: &TradeExecution) {
+        let ledger = if trade.paper { &self.paper_ledger } else { &self.ledger };
+        let filled: Vec<&ExecutionStep> = trade.trade_steps.iter()
+            .filter(|s| matches!(s.status, ExecutionStatus::Completed | ExecutionStatus::PartiallyFilled))
+            .filter(|s| s.amount > Decimal::ZERO && (s.action == "buy" || s.action == "sell"))
+            .collect();
+        if filled.is_empty() {
+            return;
         }
-
-        // Check risk score
-        if opportunity.risk_score > 0.7 {
-            return Ok(RiskAssessment {
-                approved: false,
-                reason: "Risk score too high".to_string(),
-                risk_score: opportunity.risk_score,
-            });
+        let gas_share = trade.gas_cost / Decimal::from(filled.len() as u64);
+        
+        for step in filled {
+            let (side, asset) = if step.action == "buy" {
+                (FillSide::Buy, step.to_token.clone())
+            } else {
+                (FillSide::Sell, step.from_token.clone())
+            };
+            let fill = LedgerFill {
+                id: step.step_id.clone(),
+                trade_id: Some(trade.id.clone()),
+                timestamp: step.timestamp,
+                wallet: ledger.wallet_for_venue(&step.exchange).await,
+                venue: step.exchange.clone(),
+                strategy: format!("{:?}", trade.strategy),
+                asset,
+                side,
+                quantity: step.amount,
+                price_usd: step.price,
+                fee_usd: step.fee_usd + gas_share,
+            };
+            if let Err(e) = ledger.record_fill(fill).await {
+                warn!("⚠️ Ledger rejected fill {} of {}: {}", step.step_id, trade.id, e);
+            }
         }
-
-        Ok(RiskAssessment {
-            approved: true,
-            reason: "Risk assessment passed".to_string(),
-            risk_score: opportunity.risk_score,
-        })
     }
 
     /// Update portfolio after trade completion
     async fn update_portfolio_after_trade(&self, trade: &TradeExecution) -> Result<()> {
-        let mut portfolio = self.portfolio.write().await;
-        
-        if let Some(profit) = trade.realized_profit {
-            portfolio.total_value_usd += profit;
-            portfolio.daily_pnl += profit;
-            portfolio.total_pnl += profit;
-            
-            // Update available balance
-            portfolio.available_balance = portfolio.total_value_usd - portfolio.locked_balance;
+        let target = if trade.paper { &self.paper_portfolio } else { &self.portfolio };
+        {
+            let mut portfolio = target.write().await;
+            if let Some(profit) = trade.realized_profit {
+                portfolio.total_value_usd += profit;
+                
+                // Update available balance
+                portfolio.available_balance = portfolio.total_value_usd - portfolio.locked_balance;
+            }
         }
-
-        portfolio.last_updated = chrono::Utc::now().timestamp() as u64;
         
+        self.refresh_portfolio_pnl(trade.paper).await;
         Ok(())
     }
 
+    /// P&L fields come from the tax-lot ledger under its configured cost-basis method
+    async fn refresh_portfolio_pnl(&self, paper: bool) {
+        let (ledger, target) = if paper {
+            (&self.paper_ledger, &self.paper_portfolio)
+        } else {
+            (&self.ledger, &self.portfolio)
+        };
+        let summary = ledger.summary(None).await;
+        let (equity, available, daily_pnl) = {
+            let mut portfolio = target.write().await;
+            portfolio.total_pnl = summary.realized_pnl_usd + summary.unrealized_pnl_usd;
+            portfolio.daily_pnl = summary.realized_today_usd;
+            portfolio.win_rate = summary.win_rate;
+            portfolio.last_updated = chrono::Utc::now().timestamp() as u64;
+            (
+                (portfolio.total_value_usd + summary.unrealized_pnl_usd).to_f64().unwrap_or(0.0),
+                portfolio.available_balance.to_f64().unwrap_or(0.0),
+                portfolio.daily_pnl.to_f64().unwrap_or(0.0),
+            )
+        };
+        let exposure = ledger.open_exposure_usd().await;
+        self.risk_gate.update_book(paper, equity, available, daily_pnl, exposure).await;
+    }
+
     /// Update metrics after trade completion
     async fn update_metrics_after_trade(&self, trade: &TradeExecution) -> Result<()> {
-        let mut metrics = self.metrics.lock().await;
+        let target = if trade.paper { &self.paper_metrics } else { &self.metrics };
+        let mut metrics = target.lock().await;
         
         metrics.total_trades += 1;
         
@@ -541,9 +801,11 @@ impl TradeExecutionEngine {
             // Check drawdown limits
             let current_drawdown = (portfolio.total_pnl / portfolio.total_value_usd * Decimal::from(100)).to_f64().unwrap_or(0.0);
             
-            if current_drawdown.abs() > risk_params.max_drawdown_limit {
-                warn!("🚨 Maximum drawdown limit exceeded: {:.2}%", current_drawdown);
-                // Could implement automatic trading halt here
+            if current_drawdown < 0.0 && current_drawdown.abs() > risk_params.max_drawdown_limit {
+                let detail = format!("Drawdown {:.2}% exceeds {:.2}% limit", current_drawdown, risk_params.max_drawdown_limit);
+                drop(portfolio);
+                drop(risk_params);
+                self.circuit_breaker.trip(BreakerScope::Global, BreakerReason::RealizedLoss, &detail).await;
             }
         }
     }
@@ -555,8 +817,10 @@ impl TradeExecutionEngine {
         loop {
             interval.tick().await;
             
-            // Update portfolio metrics, calculate performance ratios, etc.
+            // Unrealized P&L moves with the ledger's marks
             debug!("📊 Updating portfolio metrics...");
+            self.refresh_portfolio_pnl(false).await;
+            self.refresh_portfolio_pnl(true).await;
         }
     }
 
@@ -592,6 +856,55 @@ impl TradeExecutionEngine {
         *self.simulation_mode.write().await = enabled;
         info!("🎮 Simulation mode: {}", if enabled { "enabled" } else { "disabled" });
     }
+
+    /// Override the default mode for one strategy; `None` returns it to the default
+    pub async fn set_strategy_mode(&self, strategy: ExecutionStrategy, mode: Option<TradingMode>) {
+        let mut modes = self.strategy_modes.write().await;
+        match mode {
+            Some(mode) => {
+                modes.insert(strategy, mode);
+                info!("🎮 {:?} now trading in {:?} mode", strategy, mode);
+            }
+            None => {
+                modes.remove(&strategy);
+            }
+        }
+    }
+
+    pub async fn trading_mode(&self, strategy: ExecutionStrategy) -> TradingMode {
+        if let Some(mode) = self.strategy_modes.read().await.get(&strategy) {
+            return *mode;
+        }
+        if *self.simulation_mode.read().await { TradingMode::Paper } else { TradingMode::Live }
+    }
+
+    pub async fn get_strategy_modes(&self) -> HashMap<ExecutionStrategy, TradingMode> {
+        self.strategy_modes.read().await.clone()
+    }
+
+    pub async fn get_paper_portfolio(&self) -> Portfolio {
+        self.paper_portfolio.read().await.clone()
+    }
+
+    pub async fn get_paper_metrics(&self) -> ExecutionMetrics {
+        self.paper_metrics.lock().await.clone()
+    }
+
+    pub fn paper_account(&self) -> Arc<PaperTradingEngine> {
+        self.paper.clone()
+    }
+
+    pub fn ledger(&self) -> Arc<TaxLotLedger> {
+        self.ledger.clone()
+    }
+
+    pub fn paper_ledger(&self) -> Arc<TaxLotLedger> {
+        self.paper_ledger.clone()
+    }
+
+    pub fn risk_gate(&self) -> Arc<PreTradeRiskGate> {
+        self.risk_gate.clone()
+    }
 }
 
 impl Clone for TradeExecutionEngine {
@@ -601,18 +914,21 @@ impl Clone for TradeExecutionEngine {
             trade_history: self.trade_history.clone(),
             portfolio: self.portfolio.clone(),
             risk_parameters: self.risk_parameters.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
             metrics: self.metrics.clone(),
+            paper: self.paper.clone(),
+            paper_portfolio: self.paper_portfolio.clone(),
+            paper_metrics: self.paper_metrics.clone(),
             enabled: self.enabled.clone(),
             simulation_mode: self.simulation_mode.clone(),
+            strategy_modes: self.strategy_modes.clone(),
+            fee_model: self.fee_model.clone(),
+            coordinator: self.coordinator.clone(),
+            ledger: self.ledger.clone(),
+            paper_ledger: self.paper_ledger.clone(),
+            risk_gate: self.risk_gate.clone(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct RiskAssessment {
-    pub approved: bool,
-    pub reason: String,
-    pub risk_score: f64,
-}
-
 use rand; 
\ No newline at end of file
@@ -2,9 +2,14 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::collections::HashMap;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 
+use crate::Order;
+use crate::cex_trading::{CexOrderState, TradingCexClient};
 use crate::dex_connectors::{DexAggregator, ArbitrageRoute};
-use crate::wallet_manager::{WalletManager, TransactionType};
+use crate::wallet_manager::{WalletManager, TransactionStatus, TransactionType};
+use crate::risk_gate::{self, OrderOrigin, PreTradeRequest, PreTradeRiskGate};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeOrder {
@@ -26,9 +31,17 @@ pub struct TradeOrder {
     pub executed_at: Option<u64>,
     pub tx_hash: Option<String>,
     pub error: Option<String>,
+    #[serde(default)]
+    pub client_order_id: String, // Idempotency key sent to the venue; defaults to `id`
+    #[serde(default)]
+    pub venue_order_id: Option<String>,
+    #[serde(default)]
+    pub filled_amount: f64, // Input token filled so far
+    #[serde(default)]
+    pub status_history: Vec<OrderStatusChange>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderType {
     Market,
     Limit,
@@ -37,21 +50,84 @@ pub enum OrderType {
     TrailingStop,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderSide {
     Buy,
     Sell,
     Swap,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
-    Pending,
+    New,
     Submitted,
+    AwaitingApproval, // Held by the wallet's transaction policy; executing again resumes it
     PartiallyFilled,
     Filled,
     Cancelled,
-    Failed,
+    Rejected,
+    Expired,
+}
+
+impl OrderStatus {
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Cancelled | OrderStatus::Rejected | OrderStatus::Expired)
+    }
+
+    /// new → submitted → partially filled → filled/cancelled/rejected/expired; terminal states never change.
+    /// A submitted order held for manual approval goes back to submitted when it is executed again.
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        use OrderStatus::*;
+        matches!(
+            (*self, next),
+            (New, Submitted) | (New, Cancelled) | (New, Rejected) | (New, Expired)
+                | (Submitted, PartiallyFilled) | (Submitted, Filled) | (Submitted, Cancelled)
+                | (Submitted, Rejected) | (Submitted, Expired) | (Submitted, AwaitingApproval)
+                | (AwaitingApproval, Submitted) | (AwaitingApproval, Cancelled)
+                | (AwaitingApproval, Rejected) | (AwaitingApproval, Expired)
+                | (PartiallyFilled, PartiallyFilled) | (PartiallyFilled, Filled)
+                | (PartiallyFilled, Cancelled) | (PartiallyFilled, Expired)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderStatusChange {
+    pub from: OrderStatus,
+    pub to: OrderStatus,
+    pub timestamp: u64,
+    pub reason: Option<String>,
+}
+
+impl TradeOrder {
+    /// Apply a validated status change and record it in the order's history
+    pub fn transition(&mut self, next: OrderStatus, reason: Option<String>) -> Result<(), String> {
+        if !self.status.can_transition_to(next) {
+            return Err(format!("Invalid transition {:?} -> {:?} for order {}", self.status, next, self.id));
+        }
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.status_history.push(OrderStatusChange {
+            from: self.status,
+            to: next,
+            timestamp: now,
+            reason: reason.clone(),
+        });
+        self.status = next;
+        match next {
+            OrderStatus::Filled => self.executed_at = Some(now),
+            OrderStatus::Rejected | OrderStatus::Expired => self.error = reason,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Pair and base size as the CEX sees it: buys acquire `token_out`, sells and swaps dispose of `token_in`
+    pub fn cex_pair_and_size(&self) -> (String, f64) {
+        match self.side {
+            OrderSide::Buy => (format!("{}/{}", self.token_out, self.token_in), self.amount_out_min),
+            OrderSide::Sell | OrderSide::Swap => (format!("{}/{}", self.token_in, self.token_out), self.amount_in),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,37 +142,126 @@ pub struct ExecutionResult {
     pub profit_loss: f64,
 }
 
+/// Outcome of sending an order to a venue, applied to the stored order afterwards
+struct VenueFill {
+    result: ExecutionResult,
+    venue: String,
+    venue_order_id: Option<String>,
+    complete: bool,
+}
+
+enum VenueError {
+    Rejected(String), // Definitively not accepted by the venue
+    Held(String),     // Waiting on a manual policy approval; nothing was sent
+    Unknown(String),  // Transport failure after sending; the venue may hold the order
+}
+
+fn classify_venue_error(message: String) -> VenueError {
+    let lower = message.to_lowercase();
+    if lower.contains("timeout") || lower.contains("error sending request") || lower.contains("connection") {
+        VenueError::Unknown(message)
+    } else {
+        VenueError::Rejected(message)
+    }
+}
+
 pub struct TradeExecutor {
     orders: Arc<RwLock<HashMap<String, TradeOrder>>>,
+    client_ids: Arc<RwLock<HashMap<String, String>>>, // client_order_id -> order id
     dex_aggregator: Arc<DexAggregator>,
     wallet_manager: Arc<WalletManager>,
+    cex_clients: Arc<RwLock<HashMap<String, Arc<dyn TradingCexClient>>>>,
     execution_history: Arc<RwLock<Vec<ExecutionResult>>>,
+    risk_gate: Arc<PreTradeRiskGate>,
 }
 
 impl TradeExecutor {
+    /// Every order registered here passes `risk_gate`; an approval holds until the order is terminal
     pub fn new(
         dex_aggregator: Arc<DexAggregator>,
         wallet_manager: Arc<WalletManager>,
+        risk_gate: Arc<PreTradeRiskGate>,
     ) -> Self {
         Self {
             orders: Arc::new(RwLock::new(HashMap::new())),
+            client_ids: Arc::new(RwLock::new(HashMap::new())),
             dex_aggregator,
             wallet_manager,
+            cex_clients: Arc::new(RwLock::new(HashMap::new())),
             execution_history: Arc::new(RwLock::new(Vec::new())),
+            risk_gate,
         }
     }
 
-    pub async fn create_order(&self, order: TradeOrder) -> Result<String, String> {
-        // Validate order
+    pub fn risk_gate(&self) -> Arc<PreTradeRiskGate> {
+        self.risk_gate.clone()
+    }
+
+    /// Route orders whose `dex` names this venue through its signed trading client
+    pub async fn register_cex_client(&self, client: Arc<dyn TradingCexClient>) {
+        let name = client.name().to_string();
+        self.cex_clients.write().await.insert(name, client);
+    }
+
+    pub async fn cex_client(&self, venue: &str) -> Option<Arc<dyn TradingCexClient>> {
+        self.cex_clients.read().await.get(venue).cloned()
+    }
+
+    pub async fn cex_clients(&self) -> Vec<Arc<dyn TradingCexClient>> {
+        self.cex_clients.read().await.values().cloned().collect()
+    }
+
+    /// Register an order. Re-submitting a client_order_id returns the original order id instead of a duplicate.
+    pub async fn create_order(&self, mut order: TradeOrder) -> Result<String, String> {
+        if order.client_order_id.is_empty() {
+            order.client_order_id = order.id.clone();
+        }
+        if let Some(existing) = self.client_ids.read().await.get(&order.client_order_id) {
+            return Ok(existing.clone());
+        }
+        if order.status != OrderStatus::New {
+            return Err(format!("New orders must start in New state, got {:?}", order.status));
+        }
+
+        // Validate order, then clear it with the pre-trade risk gate
         self.validate_order(&order).await?;
-        
+        let request = self.risk_request(&order, OrderOrigin::TradeExecutor).await;
+
+        // Duplicates are turned away before the gate reserves under `order.id`, which would replace the live order's reservation
+        let mut client_ids = self.client_ids.write().await;
+        if let Some(existing) = client_ids.get(&order.client_order_id) {
+            return Ok(existing.clone());
+        }
         let order_id = order.id.clone();
         let mut orders = self.orders.write().await;
+        if orders.contains_key(&order_id) {
+            return Err(format!("Order {} already exists", order_id));
+        }
+        if let Err(decision) = self.risk_gate.check(request).await {
+            return Err(format!("Risk management rejection: {}", decision.summary()));
+        }
+        client_ids.insert(order.client_order_id.clone(), order_id.clone());
         orders.insert(order_id.clone(), order);
         
         Ok(order_id)
     }
 
+    /// Priced from the input side, or the minimum output when the input token has no quote
+    async fn risk_request(&self, order: &TradeOrder, origin: OrderOrigin) -> PreTradeRequest {
+        let (asset, reduces) = risk_gate::exposure_asset(&order.token_in, &order.token_out);
+        let notional = match self.wallet_manager.usd_value(&order.token_in, order.amount_in).await {
+            Some(value) => Some(value),
+            None => self.wallet_manager.usd_value(&order.token_out, order.amount_out_min).await,
+        };
+        PreTradeRequest::new(order.id.clone(), origin, &asset, notional)
+            .with_venues(order.dex.iter().cloned().collect())
+            .reducing(reduces)
+    }
+
+    pub async fn order_id_for_client_id(&self, client_order_id: &str) -> Option<String> {
+        self.client_ids.read().await.get(client_order_id).cloned()
+    }
+
     async fn validate_order(&self, order: &TradeOrder) -> Result<(), String> {
         // Check wallet connection
         if let Some(wallet) = self.wallet_manager.get_wallet(&order.wallet).await {
@@ -126,33 +291,127 @@ impl TradeExecutor {
         Ok(())
     }
 
+    /// Send an order to its venue. Retrying a finished order returns its recorded result rather than resubmitting;
+    /// a CEX order left Submitted without an acknowledgement is resent under the same client_order_id.
     pub async fn execute_order(&self, order_id: &str) -> Result<ExecutionResult, String> {
+        let order = {
+            let mut orders = self.orders.write().await;
+            let order = orders.get_mut(order_id)
+                .ok_or("Order not found")?;
+            let is_cex = match &order.dex {
+                Some(venue) => self.cex_clients.read().await.contains_key(venue),
+                None => false,
+            };
+            let unacknowledged = is_cex && order.status == OrderStatus::Submitted && order.venue_order_id.is_none();
+
+            if matches!(order.status, OrderStatus::New | OrderStatus::AwaitingApproval) {
+                order.transition(OrderStatus::Submitted, None)?;
+            } else if !unacknowledged {
+                let (status, error) = (order.status, order.error.clone());
+                drop(orders);
+                return self.recorded_result(order_id, status, error).await;
+            }
+            order.clone()
+        };
+
+        let client = match &order.dex {
+            Some(venue) => self.cex_client(venue).await,
+            None => None,
+        };
+        let outcome = match client {
+            Some(client) => self.execute_cex_order(&order, client).await,
+            None => self.execute_dex_order(&order).await,
+        };
+
         let mut orders = self.orders.write().await;
-        let order = orders.get_mut(order_id)
+        let stored = orders.get_mut(order_id)
             .ok_or("Order not found")?;
-        
-        // Update order status
-        order.status = OrderStatus::Submitted;
-        
+        match outcome {
+            Ok(fill) => {
+                stored.dex = Some(fill.venue);
+                let on_chain = fill.venue_order_id.is_none();
+                stored.venue_order_id = fill.venue_order_id;
+                stored.tx_hash = Some(fill.result.tx_hash.clone());
+                stored.filled_amount = fill.result.amount_in;
+                let next = if fill.complete {
+                    OrderStatus::Filled
+                } else if fill.result.amount_in > 0.0 {
+                    OrderStatus::PartiallyFilled
+                } else {
+                    stored.status
+                };
+                if next != stored.status {
+                    stored.transition(next, None)?;
+                }
+                let wallet = stored.wallet.clone();
+                drop(orders);
+                if next.is_terminal() {
+                    self.risk_gate.release(order_id).await;
+                }
+
+                if fill.result.amount_in > 0.0 {
+                    self.execution_history.write().await.push(fill.result.clone());
+                }
+                if on_chain {
+                    // The fill already happened; a failed balance refresh is retried by the refresh loop
+                    if let Err(e) = self.wallet_manager.update_wallet_balances(&wallet).await {
+                        log::warn!("⚠️ Balance refresh after fill failed for {}: {}", wallet, e);
+                    }
+                }
+                Ok(fill.result)
+            },
+            Err(VenueError::Rejected(e)) => {
+                stored.transition(OrderStatus::Rejected, Some(e.clone()))?;
+                drop(orders);
+                self.risk_gate.release(order_id).await;
+                Err(e)
+            },
+            Err(VenueError::Held(e)) => {
+                // The risk gate keeps the order's reservation while a human decides
+                stored.transition(OrderStatus::AwaitingApproval, Some(e.clone()))?;
+                Err(e)
+            },
+            Err(VenueError::Unknown(e)) => {
+                // The venue may have the order; leave it Submitted so a retry with the same client id is safe
+                stored.error = Some(e.clone());
+                Err(e)
+            },
+        }
+    }
+
+    async fn recorded_result(&self, order_id: &str, status: OrderStatus, error: Option<String>) -> Result<ExecutionResult, String> {
+        match status {
+            OrderStatus::Filled | OrderStatus::PartiallyFilled => self.execution_history.read().await
+                .iter()
+                .rev()
+                .find(|r| r.order_id == order_id)
+                .cloned()
+                .ok_or_else(|| format!("Order {} is {:?} but has no recorded execution", order_id, status)),
+            _ => Err(match error {
+                Some(e) => format!("Order {} already {:?}: {}", order_id, status, e),
+                None => format!("Order {} already {:?}", order_id, status),
+            }),
+        }
+    }
+
+    async fn execute_dex_order(&self, order: &TradeOrder) -> Result<VenueFill, VenueError> {
         // Get best execution route
         let (best_dex, quote) = self.get_best_execution_route(
             &order.token_in,
             &order.token_out,
             order.amount_in
-        ).await?;
+        ).await.map_err(VenueError::Rejected)?;
         
         // Check slippage
         let expected_out = order.amount_out_min;
         let actual_slippage = ((expected_out - quote) / expected_out).abs() * 100.0;
         
         if actual_slippage > order.slippage {
-            order.status = OrderStatus::Failed;
-            order.error = Some(format!("Slippage too high: {:.2}%", actual_slippage));
-            return Err("Slippage tolerance exceeded".to_string());
+            return Err(VenueError::Rejected(format!("Slippage too high: {:.2}%", actual_slippage)));
         }
         
-        // Create transaction
-        let tx = self.wallet_manager.create_transaction(
+        // Create transaction and clear it with the wallet's policy before anything is signed
+        let transaction = self.wallet_manager.create_transaction(
             order.wallet.clone(),
             TransactionType::Swap,
             order.amount_in,
@@ -161,52 +420,118 @@ impl TradeExecutor {
                 "token_out": order.token_out.clone(),
                 "amount_out": quote,
                 "dex": best_dex.clone(),
+                "client_order_id": order.client_order_id.clone(), // Each attempt is a new transaction; approvals follow the order
             })
-        ).await?;
+        ).await.map_err(VenueError::Rejected)?;
+        if let Err(e) = self.wallet_manager.authorize_transaction(&transaction).await {
+            let held = matches!(
+                self.wallet_manager.get_transaction(&transaction.id).await,
+                Some(tx) if matches!(tx.status, TransactionStatus::AwaitingApproval)
+            );
+            return Err(if held { VenueError::Held(e) } else { VenueError::Rejected(e) });
+        }
         
         // Execute swap on DEX
-        if let Some(connector) = self.dex_aggregator.connectors.get(&best_dex) {
-            match connector.execute_swap(
+        let swap = match self.dex_aggregator.connectors.get(&best_dex) {
+            Some(connector) => connector.execute_swap(
                 &order.token_in,
                 &order.token_out,
                 order.amount_in,
                 order.slippage
-            ).await {
-                Ok(tx_hash) => {
-                    order.status = OrderStatus::Filled;
-                    order.executed_at = Some(chrono::Utc::now().timestamp() as u64);
-                    order.tx_hash = Some(tx_hash.clone());
-                    order.dex = Some(best_dex);
-                    
-                    let result = ExecutionResult {
-                        order_id: order_id.to_string(),
-                        tx_hash,
-                        amount_in: order.amount_in,
-                        amount_out: quote,
-                        gas_used: 0.000005,
-                        execution_price: quote / order.amount_in,
-                        slippage: actual_slippage,
-                        profit_loss: quote - expected_out,
-                    };
-                    
-                    // Update wallet balances
-                    self.wallet_manager.update_wallet_balances(&order.wallet).await?;
-                    
-                    // Store execution history
-                    let mut history = self.execution_history.write().await;
-                    history.push(result.clone());
-                    
-                    Ok(result)
-                },
-                Err(e) => {
-                    order.status = OrderStatus::Failed;
-                    order.error = Some(e.to_string());
-                    Err(e.to_string())
+            ).await.map_err(|e| e.to_string()),
+            None => Err("DEX connector not found".to_string()),
+        };
+        let settled = self.wallet_manager.settle_transaction(&transaction.id, swap.is_ok(), swap.as_ref().ok().cloned()).await;
+        if let Err(e) = settled {
+            log::warn!("⚠️ Failed to record outcome of transaction {}: {}", transaction.id, e);
+        }
+        let tx_hash = swap.map_err(VenueError::Rejected)?;
+
+        Ok(VenueFill {
+            result: ExecutionResult {
+                order_id: order.id.clone(),
+                tx_hash,
+                amount_in: order.amount_in,
+                amount_out: quote,
+                gas_used: 0.000005,
+                execution_price: quote / order.amount_in,
+                slippage: actual_slippage,
+                profit_loss: quote - expected_out,
+            },
+            venue: best_dex,
+            venue_order_id: None,
+            complete: true,
+        })
+    }
+
+    async fn execute_cex_order(&self, order: &TradeOrder, client: Arc<dyn TradingCexClient>) -> Result<VenueFill, VenueError> {
+        let (pair, size) = order.cex_pair_and_size();
+        let limit = match (order.order_type, order.side) {
+            (OrderType::Limit, OrderSide::Buy) if order.amount_out_min > 0.0 => Decimal::from_f64(order.amount_in / order.amount_out_min),
+            (OrderType::Limit, _) if order.amount_in > 0.0 => Decimal::from_f64(order.amount_out_min / order.amount_in),
+            _ => None,
+        };
+        let request = Order {
+            pair: pair.clone(),
+            side: if order.side == OrderSide::Buy { "buy" } else { "sell" }.to_string(),
+            amount: Decimal::from_f64(size).ok_or_else(|| VenueError::Rejected(format!("Invalid order size {}", size)))?,
+            price: limit,
+            order_type: if limit.is_some() { "limit" } else { "market" }.to_string(),
+        };
+
+        let venue_order_id = client.place_order_with_id(&request, &order.client_order_id).await
+            .map_err(|e| classify_venue_error(e.to_string()))?;
+
+        // Market orders normally fill on acknowledgement; give them a moment before handing over to reconciliation
+        let mut status = None;
+        for _ in 0..5 {
+            match client.get_order(&pair, &venue_order_id).await {
+                Ok(s) if s.state.is_terminal() => {
+                    status = Some(s);
+                    break;
                 }
+                Ok(s) => status = Some(s),
+                Err(e) => log::warn!("⚠️ Failed to query {} on {}: {}", venue_order_id, client.name(), e),
             }
-        } else {
-            Err("DEX connector not found".to_string())
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
         }
+
+        let filled = status.as_ref().and_then(|s| s.filled_amount.to_f64()).unwrap_or(0.0);
+        let price = status.as_ref()
+            .and_then(|s| s.avg_fill_price)
+            .and_then(|p| p.to_f64())
+            .or_else(|| limit.and_then(|p| p.to_f64()))
+            .unwrap_or(0.0);
+        if let Some(s) = &status {
+            if matches!(s.state, CexOrderState::Rejected) {
+                return Err(VenueError::Rejected(format!("{} rejected order {}", client.name(), venue_order_id)));
+            }
+        }
+        let (amount_in, amount_out) = match order.side {
+            OrderSide::Buy => (filled * price, filled),
+            OrderSide::Sell | OrderSide::Swap => (filled, filled * price),
+        };
+
+        Ok(VenueFill {
+            result: ExecutionResult {
+                order_id: order.id.clone(),
+                tx_hash: venue_order_id.clone(),
+                amount_in,
+                amount_out,
+                gas_used: 0.0,
+                execution_price: if amount_in > 0.0 { amount_out / amount_in } else { 0.0 },
+                slippage: 0.0,
+                profit_loss: 0.0,
+            },
+            venue: client.name().to_string(),
+            venue_order_id: Some(venue_order_id),
+            complete: status.map(|s| s.state == CexOrderState::Filled).unwrap_or(false),
+        })
+    }
+
+    /// Best venue and expected output for swapping `amount` of `token_in`
+    pub async fn quote(&self, token_in: &str, token_out: &str, amount: f64) -> Result<(String, f64), String> {
+        self.get_best_execution_route(token_in, token_out, amount).await
     }
 
     async fn get_best_execution_route(
@@ -238,6 +563,12 @@ impl TradeExecutor {
         // Validate wallet has sufficient balance
         let wallet_data = self.wallet_manager.get_wallet(wallet).await
             .ok_or("Wallet not found")?;
+        let input_balance = wallet_data.tokens.get(&route.input_token)
+            .map(|t| t.balance)
+            .unwrap_or(0.0);
+        if input_balance < route.input_amount {
+            return Err(format!("Insufficient {} balance", route.input_token));
+        }
         
         // Execute each hop in the arbitrage route
         let mut current_amount = route.input_amount;
@@ -254,7 +585,7 @@ impl TradeExecutor {
                 amount_in: current_amount,
                 amount_out_min: current_amount * 0.95, // 5% slippage tolerance
                 slippage: 5.0,
-                status: OrderStatus::Pending,
+                status: OrderStatus::New,
                 dex: Some(pool.dex.clone()),
                 route: Some(route.path.iter().map(|p| p.dex.clone()).collect()),
                 gas_price: None,
@@ -263,10 +594,36 @@ impl TradeExecutor {
                 executed_at: None,
                 tx_hash: None,
                 error: None,
+                client_order_id: String::new(),
+                venue_order_id: None,
+                filled_amount: 0.0,
+                status_history: Vec::new(),
+            };
+            
+            let token_in = order.token_in.clone();
+            let hop_result = match self.create_order(order).await {
+                Ok(order_id) => self.execute_order(&order_id).await,
+                Err(e) => Err(e),
             };
             
-            let order_id = self.create_order(order).await?;
-            let result = self.execute_order(&order_id).await?;
+            let result = match hop_result {
+                Ok(result) => result,
+                Err(e) if i > 0 => {
+                    // Earlier hops filled: swap the intermediate token back instead of leaving it open
+                    let unwind = self.unwind_route(route, wallet, &token_in, current_amount).await;
+                    return Err(match unwind {
+                        Ok(recovered) => format!(
+                            "Hop {} failed ({}); unwound {} {} back to {} {}",
+                            i, e, current_amount, token_in, recovered, route.input_token
+                        ),
+                        Err(unwind_err) => format!(
+                            "Hop {} failed ({}); unwind failed ({}), holding {} {}",
+                            i, e, unwind_err, current_amount, token_in
+                        ),
+                    });
+                }
+                Err(e) => return Err(e),
+            };
             
             current_amount = result.amount_out;
             tx_hashes.push(result.tx_hash);
@@ -289,20 +646,101 @@ impl TradeExecutor {
         })
     }
 
-    pub async fn cancel_order(&self, order_id: &str) -> Result<(), String> {
-        let mut orders = self.orders.write().await;
+    /// Swap a stranded intermediate token back to the route's input token
+    async fn unwind_route(&self, route: &ArbitrageRoute, wallet: &str, token: &str, amount: f64) -> Result<f64, String> {
+        let (_, quote) = self.get_best_execution_route(token, &route.input_token, amount).await?;
+        let order = TradeOrder {
+            id: format!("{}_unwind", route.id),
+            wallet: wallet.to_string(),
+            order_type: OrderType::Market,
+            side: OrderSide::Swap,
+            token_in: token.to_string(),
+            token_out: route.input_token.clone(),
+            amount_in: amount,
+            amount_out_min: quote,
+            slippage: 10.0, // Wider than entry: recovering the position matters more than price
+            status: OrderStatus::New,
+            dex: None,
+            route: None,
+            gas_price: None,
+            deadline: chrono::Utc::now().timestamp() as u64 + 300,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            executed_at: None,
+            tx_hash: None,
+            error: None,
+            client_order_id: String::new(),
+            venue_order_id: None,
+            filled_amount: 0.0,
+            status_history: Vec::new(),
+        };
         
-        if let Some(order) = orders.get_mut(order_id) {
-            match order.status {
-                OrderStatus::Pending | OrderStatus::Submitted => {
-                    order.status = OrderStatus::Cancelled;
-                    Ok(())
-                },
-                _ => Err("Order cannot be cancelled".to_string())
+        let order_id = self.create_order(order).await?;
+        let result = self.execute_order(&order_id).await?;
+        Ok(result.amount_out)
+    }
+    
+    /// Cancel locally, and at the venue when the order has already been acknowledged there
+    pub async fn cancel_order(&self, order_id: &str) -> Result<(), String> {
+        let order = self.get_order(order_id).await.ok_or("Order not found")?;
+        if order.status.is_terminal() {
+            return Err("Order cannot be cancelled".to_string());
+        }
+
+        if let (Some(venue), Some(venue_order_id)) = (&order.dex, &order.venue_order_id) {
+            if let Some(client) = self.cex_client(venue).await {
+                let (pair, _) = order.cex_pair_and_size();
+                client.cancel_order(&pair, venue_order_id).await.map_err(|e| e.to_string())?;
             }
-        } else {
-            Err("Order not found".to_string())
         }
+
+        let mut orders = self.orders.write().await;
+        let order = orders.get_mut(order_id).ok_or("Order not found")?;
+        order.transition(OrderStatus::Cancelled, Some("Cancelled by user".to_string()))?;
+        drop(orders);
+        self.risk_gate.release(order_id).await;
+        Ok(())
+    }
+
+    /// Apply a status observed at the venue (or a local expiry) through the state machine
+    pub async fn apply_venue_status(
+        &self,
+        order_id: &str,
+        status: OrderStatus,
+        filled_amount: Option<f64>,
+        reason: Option<String>,
+    ) -> Result<(), String> {
+        let mut orders = self.orders.write().await;
+        let order = orders.get_mut(order_id).ok_or("Order not found")?;
+        let fill_changed = filled_amount
+            .map(|filled| (filled - order.filled_amount).abs() > 1e-12)
+            .unwrap_or(false);
+        if let Some(filled) = filled_amount {
+            order.filled_amount = filled;
+        }
+        if order.status == status && !(status == OrderStatus::PartiallyFilled && fill_changed) {
+            return Ok(());
+        }
+        // A venue can report fills for an order whose acknowledgement never reached us
+        if order.status == OrderStatus::New
+            && matches!(status, OrderStatus::PartiallyFilled | OrderStatus::Filled)
+        {
+            order.transition(OrderStatus::Submitted, reason.clone())?;
+        }
+        order.transition(status, reason)?;
+        if status.is_terminal() {
+            drop(orders);
+            self.risk_gate.release(order_id).await;
+        }
+        Ok(())
+    }
+
+    /// Orders that have not reached a terminal state
+    pub async fn get_open_orders(&self) -> Vec<TradeOrder> {
+        self.orders.read().await
+            .values()
+            .filter(|o| !o.status.is_terminal())
+            .cloned()
+            .collect()
     }
 
     pub async fn get_order(&self, order_id: &str) -> Option<TradeOrder> {
@@ -341,30 +779,201 @@ impl TradeExecutor {
 pub struct SmartOrderRouter {
     executor: Arc<TradeExecutor>,
     routing_config: RoutingConfig,
+    order_books: Arc<RwLock<HashMap<String, BookSnapshot>>>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RoutingConfig {
     pub max_splits: usize,
-    pub min_split_size: f64,
-    pub price_impact_threshold: f64,
+    pub min_split_size: f64, // In units of the input token
+    pub price_impact_threshold: f64, // Percent, versus the best spot rate across all venues
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            max_splits: 5,
+            min_split_size: 10.0,
+            price_impact_threshold: 1.0,
+        }
+    }
+}
+
+/// Order book snapshot for a CEX (or CLOB) venue, in f64 for the router
+#[derive(Debug, Clone)]
+struct BookSnapshot {
+    base: String,
+    quote: String,
+    bids: Vec<(f64, f64)>, // (price, base amount)
+    asks: Vec<(f64, f64)>,
+    fee: f64,
+}
+
+/// One place an order can be filled, seen from the input token's side
+#[derive(Debug, Clone)]
+enum LiquiditySource {
+    Pool { venue: String, pool: String, reserve_in: f64, reserve_out: f64, fee: f64 },
+    Book { venue: String, levels: Vec<(f64, f64)> }, // (output per unit input net of fee, input capacity), best first
+}
+
+impl LiquiditySource {
+    fn venue(&self) -> &str {
+        match self {
+            LiquiditySource::Pool { venue, .. } | LiquiditySource::Book { venue, .. } => venue,
+        }
+    }
+
+    fn pool(&self) -> Option<String> {
+        match self {
+            LiquiditySource::Pool { pool, .. } => Some(pool.clone()),
+            LiquiditySource::Book { .. } => None,
+        }
+    }
+
+    /// Marginal output per unit input at zero size
+    fn spot_rate(&self) -> f64 {
+        match self {
+            LiquiditySource::Pool { reserve_in, reserve_out, fee, .. } => reserve_out * (1.0 - fee) / reserve_in,
+            LiquiditySource::Book { levels, .. } => levels.first().map(|(rate, _)| *rate).unwrap_or(0.0),
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        match self {
+            LiquiditySource::Pool { .. } => f64::INFINITY,
+            LiquiditySource::Book { levels, .. } => levels.iter().map(|(_, cap)| cap).sum(),
+        }
+    }
+
+    fn output(&self, amount_in: f64) -> f64 {
+        match self {
+            LiquiditySource::Pool { reserve_in, reserve_out, fee, .. } => {
+                let effective = amount_in * (1.0 - fee);
+                reserve_out * effective / (reserve_in + effective)
+            }
+            LiquiditySource::Book { levels, .. } => {
+                let mut remaining = amount_in;
+                let mut out = 0.0;
+                for (rate, cap) in levels {
+                    let take = remaining.min(*cap);
+                    out += take * rate;
+                    remaining -= take;
+                    if remaining <= 0.0 {
+                        break;
+                    }
+                }
+                out
+            }
+        }
+    }
+
+    /// Input this source absorbs before its marginal rate falls below `rate`
+    fn amount_at_rate(&self, rate: f64) -> f64 {
+        match self {
+            LiquiditySource::Pool { reserve_in, reserve_out, fee, .. } => {
+                if rate <= 0.0 {
+                    return f64::INFINITY;
+                }
+                // Constant product: d(out)/d(in) = R_out * R_in * (1 - f) / (R_in + in * (1 - f))^2
+                let x = ((reserve_out * reserve_in * (1.0 - fee) / rate).sqrt() - reserve_in) / (1.0 - fee);
+                x.max(0.0)
+            }
+            LiquiditySource::Book { levels, .. } => {
+                levels.iter().filter(|(r, _)| *r >= rate).map(|(_, cap)| cap).sum()
+            }
+        }
+    }
+}
+
+/// Split `total` so every used source ends at the same marginal rate (water-filling on the rate)
+fn allocate_equal_marginal(sources: &[LiquiditySource], total: f64) -> Result<Vec<f64>, String> {
+    let capacity: f64 = sources.iter().map(|s| s.capacity()).sum();
+    if capacity < total {
+        return Err(format!("Insufficient liquidity: {:.4} available for {:.4} requested", capacity, total));
+    }
+
+    let absorbed = |rate: f64| -> f64 {
+        sources.iter().map(|s| s.amount_at_rate(rate).min(s.capacity())).sum()
+    };
+
+    let mut rate_hi = sources.iter().map(|s| s.spot_rate()).fold(0.0, f64::max);
+    let mut rate_lo = 0.0;
+    for _ in 0..200 {
+        let mid = (rate_hi + rate_lo) / 2.0;
+        if absorbed(mid) >= total {
+            rate_lo = mid;
+        } else {
+            rate_hi = mid;
+        }
+    }
+
+    // Everything above rate_hi is taken in full; the marginal band between the bounds covers the rest
+    let mut allocation: Vec<f64> = sources.iter()
+        .map(|s| s.amount_at_rate(rate_hi).min(s.capacity()))
+        .collect();
+    let remainder = total - allocation.iter().sum::<f64>();
+    if remainder > 0.0 {
+        let band: Vec<f64> = sources.iter().zip(&allocation)
+            .map(|(s, x)| (s.amount_at_rate(rate_lo).min(s.capacity()) - x).max(0.0))
+            .collect();
+        let band_total: f64 = band.iter().sum();
+        if band_total > 0.0 {
+            for (x, b) in allocation.iter_mut().zip(&band) {
+                *x += remainder * b / band_total;
+            }
+        }
+    }
+
+    Ok(allocation)
 }
 
 impl SmartOrderRouter {
     pub fn new(executor: Arc<TradeExecutor>) -> Self {
+        Self::with_config(executor, RoutingConfig::default())
+    }
+
+    pub fn with_config(executor: Arc<TradeExecutor>, routing_config: RoutingConfig) -> Self {
         Self {
             executor,
-            routing_config: RoutingConfig {
-                max_splits: 5,
-                min_split_size: 10.0,
-                price_impact_threshold: 1.0,
-            },
+            routing_config,
+            order_books: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Record a venue's current book so it competes with AMM pools for splits
+    pub async fn update_order_book(&self, venue: &str, pair: &str, book: &crate::OrderBook, taker_fee: f64) {
+        let Some((base, quote)) = pair.split_once('/') else {
+            return;
+        };
+        let levels = |entries: &[crate::OrderBookEntry]| -> Vec<(f64, f64)> {
+            entries.iter()
+                .filter_map(|e| Some((e.price.to_f64()?, e.amount.to_f64()?)))
+                .filter(|(price, amount)| *price > 0.0 && *amount > 0.0)
+                .collect()
+        };
+        
+        let snapshot = BookSnapshot {
+            base: base.to_uppercase(),
+            quote: quote.to_uppercase(),
+            bids: levels(&book.bids),
+            asks: levels(&book.asks),
+            fee: taker_fee,
+        };
+        self.order_books.write().await.insert(format!("{}:{}", venue, pair.to_uppercase()), snapshot);
+    }
+
     pub async fn route_order(&self, order: &TradeOrder) -> Result<Vec<TradeOrder>, String> {
+        // The whole order is reserved while it is split, so concurrent routes count each other as in flight;
+        // each child is checked and reserved again when created
+        let request = self.executor.risk_request(order, OrderOrigin::SmartOrderRouter).await;
+        if let Err(decision) = self.executor.risk_gate.check(request).await {
+            return Err(format!("Risk management rejection: {}", decision.summary()));
+        }
+        
         // Calculate optimal order splitting based on liquidity
-        let splits = self.calculate_order_splits(order).await?;
+        let splits = self.calculate_order_splits(order).await;
+        self.executor.risk_gate.release(&order.id).await;
+        let splits = splits?;
         
         // Create sub-orders for each split
         let mut sub_orders = Vec::new();
@@ -381,32 +990,286 @@ impl SmartOrderRouter {
         Ok(sub_orders)
     }
 
-    async fn calculate_order_splits(&self, order: &TradeOrder) -> Result<Vec<OrderSplit>, String> {
-        // Placeholder for order splitting logic
-        // Would analyze liquidity across DEXs and calculate optimal splits
-        Ok(vec![
-            OrderSplit {
-                dex: "Jupiter".to_string(),
-                amount: order.amount_in * 0.5,
-                expected_out: order.amount_out_min * 0.5,
-            },
-            OrderSplit {
-                dex: "Raydium".to_string(),
-                amount: order.amount_in * 0.3,
-                expected_out: order.amount_out_min * 0.3,
-            },
-            OrderSplit {
-                dex: "Orca".to_string(),
-                amount: order.amount_in * 0.2,
-                expected_out: order.amount_out_min * 0.2,
-            },
-        ])
+    /// Pools and books that can convert `token_in` into `token_out`
+    async fn liquidity_sources(&self, token_in: &str, token_out: &str) -> Vec<LiquiditySource> {
+        let token_in = token_in.to_uppercase();
+        let token_out = token_out.to_uppercase();
+        let mut sources = Vec::new();
+        
+        for pool in self.executor.dex_aggregator.get_all_pools().await {
+            let (a, b) = (pool.token_a.to_uppercase(), pool.token_b.to_uppercase());
+            let (reserve_in, reserve_out) = if a == token_in && b == token_out {
+                (pool.reserve_a, pool.reserve_b)
+            } else if b == token_in && a == token_out {
+                (pool.reserve_b, pool.reserve_a)
+            } else {
+                continue;
+            };
+            if reserve_in > 0.0 && reserve_out > 0.0 {
+                sources.push(LiquiditySource::Pool {
+                    venue: pool.dex.clone(),
+                    pool: pool.pool_address.clone(),
+                    reserve_in,
+                    reserve_out,
+                    fee: pool.fee,
+                });
+            }
+        }
+        
+        for (key, book) in self.order_books.read().await.iter() {
+            let venue = key.split(':').next().unwrap_or(key).to_string();
+            let mut levels: Vec<(f64, f64)> = if book.base == token_in && book.quote == token_out {
+                // Selling base into bids
+                book.bids.iter().map(|(price, amount)| (price * (1.0 - book.fee), *amount)).collect()
+            } else if book.quote == token_in && book.base == token_out {
+                // Buying base from asks, capacity in quote
+                book.asks.iter().map(|(price, amount)| ((1.0 - book.fee) / price, amount * price)).collect()
+            } else {
+                continue;
+            };
+            levels.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            if !levels.is_empty() {
+                sources.push(LiquiditySource::Book { venue, levels });
+            }
+        }
+        
+        sources
+    }
+
+    /// Split across venues at equal marginal price, within max_splits, min_split_size and price_impact_threshold
+    pub async fn calculate_order_splits(&self, order: &TradeOrder) -> Result<Vec<OrderSplit>, String> {
+        let config = &self.routing_config;
+        let total = order.amount_in;
+        if total <= 0.0 {
+            return Err("Order amount must be positive".to_string());
+        }
+        
+        let mut sources = self.liquidity_sources(&order.token_in, &order.token_out).await;
+        if sources.is_empty() {
+            return Err(format!("No liquidity for {} -> {}", order.token_in, order.token_out));
+        }
+        let best_spot = sources.iter().map(|s| s.spot_rate()).fold(0.0, f64::max);
+        
+        // Drop dust splits and cap the venue count, then re-optimize over the survivors
+        let allocation = loop {
+            let allocation = allocate_equal_marginal(&sources, total)?;
+            let mut ranked: Vec<(usize, f64)> = allocation.iter().copied().enumerate()
+                .filter(|(_, x)| *x > 0.0)
+                .collect();
+            ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            
+            let mut keep: Vec<usize> = ranked.iter()
+                .take(config.max_splits.max(1))
+                .filter(|(_, x)| *x >= config.min_split_size)
+                .map(|(i, _)| *i)
+                .collect();
+            if keep.is_empty() {
+                // Order smaller than a minimum split: send it all to the deepest allocation
+                keep.push(ranked.first().map(|(i, _)| *i).ok_or("No liquidity source absorbed the order")?);
+            }
+            
+            if keep.len() == ranked.len() {
+                break allocation;
+            }
+            sources = keep.into_iter().map(|i| sources[i].clone()).collect();
+        };
+        
+        let splits: Vec<OrderSplit> = sources.iter().zip(allocation)
+            .filter(|(_, amount)| *amount > 0.0)
+            .map(|(source, amount)| {
+                let expected_out = source.output(amount);
+                let rate = expected_out / amount;
+                OrderSplit {
+                    dex: source.venue().to_string(),
+                    pool: source.pool(),
+                    amount,
+                    expected_out,
+                    average_price: rate,
+                    price_impact: (best_spot - rate) / best_spot * 100.0,
+                }
+            })
+            .collect();
+        
+        let total_out: f64 = splits.iter().map(|s| s.expected_out).sum();
+        let price_impact = (best_spot - total_out / total) / best_spot * 100.0;
+        if price_impact > config.price_impact_threshold {
+            return Err(format!(
+                "Price impact {:.3}% exceeds threshold {:.3}% for {} {}",
+                price_impact, config.price_impact_threshold, total, order.token_in
+            ));
+        }
+        
+        Ok(splits)
     }
 }
 
-#[derive(Debug, Clone)]
-struct OrderSplit {
-    dex: String,
-    amount: f64,
-    expected_out: f64,
-}
\ No newline at end of file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderSplit {
+    pub dex: String,
+    pub pool: Option<String>,
+    pub amount: f64,
+    pub expected_out: f64,
+    pub average_price: f64, // Output per unit input, fees included
+    pub price_impact: f64, // Percent versus the best spot rate across venues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use crate::balances::UsdPriceSource;
+    use crate::dex_connectors::{DexConnector, DexPool, TokenPrice};
+    use crate::risk_gate::RiskGateConfig;
+    use crate::risk_management::{RiskManager, RiskProfile};
+    use crate::tx_policy::{ApprovalStatus, TxPolicyEngine, WalletPolicy};
+    use crate::wallet_manager::WalletType;
+
+    struct FixedPrices;
+
+    #[async_trait]
+    impl UsdPriceSource for FixedPrices {
+        async fn usd_prices(&self) -> anyhow::Result<HashMap<String, f64>> {
+            Ok(HashMap::from([("SOL".to_string(), 150.0), ("USDC".to_string(), 1.0)]))
+        }
+    }
+
+    /// Quotes SOL at 150 USDC and counts the swaps it was asked to execute
+    struct MockDex {
+        swaps: Arc<std::sync::Mutex<u32>>,
+    }
+
+    #[async_trait]
+    impl DexConnector for MockDex {
+        async fn get_pools(&self) -> Result<Vec<DexPool>, Box<dyn std::error::Error>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_price(&self, token_address: &str) -> Result<TokenPrice, Box<dyn std::error::Error>> {
+            Err(format!("no price for {}", token_address).into())
+        }
+
+        async fn get_swap_quote(&self, _from: &str, _to: &str, amount: f64) -> Result<f64, Box<dyn std::error::Error>> {
+            Ok(amount * 150.0)
+        }
+
+        async fn execute_swap(&self, _from: &str, _to: &str, _amount: f64, _slippage: f64) -> Result<String, Box<dyn std::error::Error>> {
+            let mut swaps = self.swaps.lock().unwrap();
+            *swaps += 1;
+            Ok(format!("swap-signature-{}", swaps))
+        }
+    }
+
+    fn sol_for_usdc(wallet: &str, amount: f64) -> TradeOrder {
+        let now = chrono::Utc::now().timestamp() as u64;
+        TradeOrder {
+            id: format!("order_{}", uuid::Uuid::new_v4().simple()),
+            wallet: wallet.to_string(),
+            order_type: OrderType::Market,
+            side: OrderSide::Buy,
+            token_in: "SOL".to_string(),
+            token_out: "USDC".to_string(),
+            amount_in: amount,
+            amount_out_min: amount * 150.0,
+            slippage: 1.0,
+            status: OrderStatus::New,
+            dex: None,
+            route: None,
+            gas_price: None,
+            deadline: now + 300,
+            created_at: now,
+            executed_at: None,
+            tx_hash: None,
+            error: None,
+            client_order_id: String::new(),
+            venue_order_id: None,
+            filled_amount: 0.0,
+            status_history: Vec::new(),
+        }
+    }
+
+    /// Live book holding $5,000 of SOL, so selling up to that much only reduces exposure
+    async fn holding_gate() -> Arc<PreTradeRiskGate> {
+        let gate = Arc::new(PreTradeRiskGate::new(RiskGateConfig::default(), Arc::new(RiskManager::new(RiskProfile::default()))));
+        gate.update_book(false, 100_000.0, 50_000.0, 0.0, HashMap::from([("SOL".to_string(), 5_000.0)])).await;
+        gate
+    }
+
+    #[tokio::test]
+    async fn duplicate_order_ids_leave_the_live_reservation_alone() {
+        let wallet_manager = Arc::new(WalletManager::new());
+        wallet_manager.set_price_source(Arc::new(FixedPrices)).await;
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        wallet_manager.connect_wallet(WalletType::Phantom, wallet.to_string()).await.unwrap();
+        let risk_gate = holding_gate().await;
+        let executor = TradeExecutor::new(Arc::new(DexAggregator { connectors: HashMap::new() }), wallet_manager, risk_gate.clone());
+
+        let order = sol_for_usdc(wallet, 1.0);
+        let order_id = executor.create_order(order.clone()).await.unwrap();
+        let duplicate = TradeOrder { client_order_id: "another-client-id".to_string(), amount_in: 2.0, ..order.clone() };
+        let error = executor.create_order(duplicate).await.unwrap_err();
+        assert!(error.contains("already exists"), "{}", error);
+
+        // Retrying under the same client id returns the live order
+        assert_eq!(executor.create_order(order).await.unwrap(), order_id);
+        let reservations = risk_gate.get_reservations().await;
+        assert_eq!(reservations.len(), 1);
+        assert_eq!((reservations[0].id.as_str(), reservations[0].notional_usd), (order_id.as_str(), Some(150.0)));
+    }
+
+    #[tokio::test]
+    async fn held_order_executes_once_its_approval_is_granted() {
+        let policy = Arc::new(TxPolicyEngine::default());
+        let wallet_manager = Arc::new(WalletManager::new().with_policy_engine(policy.clone()));
+        wallet_manager.set_price_source(Arc::new(FixedPrices)).await;
+        let wallet = "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU";
+        wallet_manager.connect_wallet(WalletType::Phantom, wallet.to_string()).await.unwrap();
+        policy.set_wallet_policy(wallet, WalletPolicy {
+            approval_threshold_usd: Some(1_000.0),
+            ..WalletPolicy::default()
+        }).await;
+
+        let swaps = Arc::new(std::sync::Mutex::new(0));
+        let aggregator = DexAggregator {
+            connectors: HashMap::from([("MockDex".to_string(), Box::new(MockDex { swaps: swaps.clone() }) as Box<dyn DexConnector>)]),
+        };
+        let executor = TradeExecutor::new(Arc::new(aggregator), wallet_manager.clone(), holding_gate().await);
+
+        // $1,500 of SOL is over the $1,000 approval threshold
+        let order_id = executor.create_order(sol_for_usdc(wallet, 10.0)).await.unwrap();
+        let error = executor.execute_order(&order_id).await.unwrap_err();
+        assert!(error.contains("awaiting manual approval"), "{}", error);
+        assert_eq!(executor.get_order(&order_id).await.unwrap().status, OrderStatus::AwaitingApproval);
+        assert_eq!(*swaps.lock().unwrap(), 0);
+
+        let pending = policy.get_approvals(true).await;
+        assert_eq!(pending.len(), 1);
+        let client_order_id = executor.get_order(&order_id).await.unwrap().client_order_id;
+        assert_eq!(pending[0].intent.client_id, client_order_id);
+        assert_eq!(pending[0].intent.value_usd, Some(1_500.0));
+
+        // Executing again before anyone decides keeps the same approval request
+        executor.execute_order(&order_id).await.unwrap_err();
+        assert_eq!(policy.get_approvals(true).await.len(), 1);
+
+        policy.approve(&pending[0].id, "admin").await.unwrap();
+        let result = executor.execute_order(&order_id).await.unwrap();
+        assert_eq!(result.tx_hash, "swap-signature-1");
+        assert_eq!(result.amount_out, 1_500.0);
+
+        let order = executor.get_order(&order_id).await.unwrap();
+        assert_eq!(order.status, OrderStatus::Filled);
+        let path: Vec<_> = order.status_history.iter().map(|c| c.to).collect();
+        assert_eq!(path, vec![
+            OrderStatus::Submitted, OrderStatus::AwaitingApproval,
+            OrderStatus::Submitted, OrderStatus::AwaitingApproval,
+            OrderStatus::Submitted, OrderStatus::Filled,
+        ]);
+        let approvals = policy.get_approvals(false).await;
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0].status, ApprovalStatus::Used);
+
+        // A finished order is never swapped twice
+        assert_eq!(executor.execute_order(&order_id).await.unwrap().tx_hash, "swap-signature-1");
+        assert_eq!(*swaps.lock().unwrap(), 1);
+    }
+}
@@ -0,0 +1,436 @@
+// Trigger Orders - Price-driven stop-loss, take-profit and trailing-stop exits
+// Watches the price stream, trails stop levels, fires exit orders through TradeExecutor and resolves OCO brackets
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use serde::{Deserialize, Serialize};
+use rust_decimal::prelude::ToPrimitive;
+use log::{info, warn, error};
+
+use crate::PriceInfo;
+use crate::risk_management::ExitStrategyManager;
+use crate::trade_executor::{OrderSide, OrderStatus, OrderType, TradeExecutor, TradeOrder};
+
+// ============================================================================
+// TRIGGER DEFINITIONS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TriggerKind {
+    StopLoss { stop_price: f64 },
+    TakeProfit { target_price: f64 },
+    TrailingStop { trail_percent: f64 }, // Percent below the high (longs) or above the low (shorts)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ExitExecution {
+    Market,
+    Limit { offset_bps: f64 }, // Limit placed this far through the trigger level
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerRequest {
+    pub position_id: String,
+    pub wallet: String,
+    pub base: String,
+    pub quote: String,
+    pub is_long: bool,
+    pub size: f64, // Base units to close
+    pub kind: TriggerKind,
+    pub execution: ExitExecution,
+    pub price_source: Option<String>, // Only react to quotes from this venue
+    pub dex: Option<String>,          // Exit venue; None lets the executor pick
+    pub slippage: f64,                // Percent, for market exits
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerStatus {
+    Armed,
+    Triggered, // Exit order in flight
+    Filled,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerOrder {
+    pub id: String,
+    pub request: TriggerRequest,
+    pub status: TriggerStatus,
+    pub level: f64,                 // Current trigger price; moves for trailing stops
+    pub extreme_price: Option<f64>, // Best price seen since arming
+    pub oco_group: Option<String>,
+    pub attempts: u32,
+    pub created_at: u64,
+    pub triggered_at: Option<u64>,
+    pub trigger_price: Option<f64>,
+    pub exit_order_ids: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl TriggerOrder {
+    fn pair(&self) -> String {
+        format!("{}/{}", self.request.base, self.request.quote)
+    }
+
+    /// Stops fire when price moves against the position, take-profits when it moves in favour
+    fn is_hit(&self, price: f64) -> bool {
+        let adverse = matches!(self.request.kind, TriggerKind::StopLoss { .. } | TriggerKind::TrailingStop { .. });
+        match (adverse, self.request.is_long) {
+            (true, true) | (false, false) => price <= self.level,
+            (true, false) | (false, true) => price >= self.level,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerEngineConfig {
+    pub max_fire_attempts: u32, // Re-arm after a failed exit this many times before giving up
+    pub max_price_age_secs: u64,
+}
+
+impl Default for TriggerEngineConfig {
+    fn default() -> Self {
+        Self {
+            max_fire_attempts: 3,
+            max_price_age_secs: 30,
+        }
+    }
+}
+
+// ============================================================================
+// TRIGGER ENGINE
+// ============================================================================
+
+pub struct TriggerEngine {
+    executor: Arc<TradeExecutor>,
+    exit_manager: Arc<ExitStrategyManager>,
+    triggers: Arc<RwLock<HashMap<String, TriggerOrder>>>,
+    config: Arc<RwLock<TriggerEngineConfig>>,
+}
+
+impl TriggerEngine {
+    pub fn new(executor: Arc<TradeExecutor>, exit_manager: Arc<ExitStrategyManager>) -> Self {
+        Self {
+            executor,
+            exit_manager,
+            triggers: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(TriggerEngineConfig::default())),
+        }
+    }
+
+    pub async fn get_config(&self) -> TriggerEngineConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: TriggerEngineConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Consume the platform price stream until it closes
+    pub async fn start(&self, mut prices: broadcast::Receiver<PriceInfo>) {
+        loop {
+            match prices.recv().await {
+                Ok(quote) => self.on_price(&quote).await,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("⚠️ Trigger engine lagged, skipped {} price updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    pub async fn arm(&self, request: TriggerRequest, reference_price: Option<f64>) -> Result<String, String> {
+        let trigger = self.build(request, reference_price, None)?;
+        let id = trigger.id.clone();
+        info!("🎯 Armed {:?} for {} at {:.6}", trigger.request.kind, trigger.pair(), trigger.level);
+        self.triggers.write().await.insert(id.clone(), trigger);
+        Ok(id)
+    }
+
+    /// Arm a stop and a take-profit on the same position; whichever fills first cancels the other
+    pub async fn arm_bracket(
+        &self,
+        stop: TriggerRequest,
+        take_profit: TriggerRequest,
+        reference_price: Option<f64>,
+    ) -> Result<(String, String), String> {
+        if stop.position_id != take_profit.position_id || stop.is_long != take_profit.is_long {
+            return Err("Bracket legs must protect the same position".to_string());
+        }
+        if matches!(take_profit.kind, TriggerKind::StopLoss { .. } | TriggerKind::TrailingStop { .. })
+            || matches!(stop.kind, TriggerKind::TakeProfit { .. })
+        {
+            return Err("Bracket needs one stop and one take-profit".to_string());
+        }
+
+        let group = format!("oco_{}", uuid::Uuid::new_v4().simple());
+        let stop = self.build(stop, reference_price, Some(group.clone()))?;
+        let take_profit = self.build(take_profit, reference_price, Some(group))?;
+        let ids = (stop.id.clone(), take_profit.id.clone());
+        info!("🎯 Armed OCO bracket on {}: stop {:.6} / target {:.6}", stop.pair(), stop.level, take_profit.level);
+
+        let mut triggers = self.triggers.write().await;
+        triggers.insert(ids.0.clone(), stop);
+        triggers.insert(ids.1.clone(), take_profit);
+        Ok(ids)
+    }
+
+    pub async fn cancel(&self, id: &str) -> Result<(), String> {
+        let mut triggers = self.triggers.write().await;
+        let trigger = triggers.get_mut(id).ok_or("Trigger not found")?;
+        if trigger.status != TriggerStatus::Armed {
+            return Err(format!("Trigger is {:?}", trigger.status));
+        }
+        trigger.status = TriggerStatus::Cancelled;
+        Ok(())
+    }
+
+    /// Cancel every armed trigger protecting a position, e.g. after it was closed manually
+    pub async fn cancel_position(&self, position_id: &str) -> usize {
+        let mut triggers = self.triggers.write().await;
+        let mut cancelled = 0;
+        for trigger in triggers.values_mut() {
+            if trigger.request.position_id == position_id && trigger.status == TriggerStatus::Armed {
+                trigger.status = TriggerStatus::Cancelled;
+                cancelled += 1;
+            }
+        }
+        cancelled
+    }
+
+    pub async fn get_trigger(&self, id: &str) -> Option<TriggerOrder> {
+        self.triggers.read().await.get(id).cloned()
+    }
+
+    pub async fn get_triggers(&self, position_id: Option<&str>) -> Vec<TriggerOrder> {
+        self.triggers.read().await
+            .values()
+            .filter(|t| position_id.map(|p| t.request.position_id == p).unwrap_or(true))
+            .cloned()
+            .collect()
+    }
+
+    fn build(&self, request: TriggerRequest, reference_price: Option<f64>, oco_group: Option<String>) -> Result<TriggerOrder, String> {
+        if request.size <= 0.0 {
+            return Err("Trigger size must be positive".to_string());
+        }
+        let level = match &request.kind {
+            TriggerKind::StopLoss { stop_price } => *stop_price,
+            TriggerKind::TakeProfit { target_price } => *target_price,
+            TriggerKind::TrailingStop { trail_percent } => {
+                if *trail_percent <= 0.0 || *trail_percent >= 100.0 {
+                    return Err("Trailing percent must be between 0 and 100".to_string());
+                }
+                match reference_price {
+                    Some(price) => self.exit_manager.trailing_stop_loss(price, price, trail_percent / 100.0, request.is_long),
+                    // Level is set from the first quote
+                    None => 0.0,
+                }
+            }
+        };
+        if level < 0.0 || (level == 0.0 && !matches!(request.kind, TriggerKind::TrailingStop { .. })) {
+            return Err("Trigger price must be positive".to_string());
+        }
+
+        Ok(TriggerOrder {
+            id: format!("trigger_{}", uuid::Uuid::new_v4().simple()),
+            request,
+            status: TriggerStatus::Armed,
+            level,
+            extreme_price: reference_price,
+            oco_group,
+            attempts: 0,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            triggered_at: None,
+            trigger_price: None,
+            exit_order_ids: Vec::new(),
+            error: None,
+        })
+    }
+
+    /// Update trailing levels for the quote's pair and fire any trigger it crosses
+    pub async fn on_price(&self, quote: &PriceInfo) {
+        let max_age = self.config.read().await.max_price_age_secs;
+        let now = chrono::Utc::now().timestamp() as u64;
+        if now.saturating_sub(quote.timestamp) > max_age {
+            return;
+        }
+
+        let mut fired = Vec::new();
+        {
+            let mut triggers = self.triggers.write().await;
+            // A bracket leg already in flight blocks its sibling until the exit resolves
+            let busy_groups: Vec<String> = triggers.values()
+                .filter(|t| t.status == TriggerStatus::Triggered)
+                .filter_map(|t| t.oco_group.clone())
+                .collect();
+
+            for trigger in triggers.values_mut() {
+                if trigger.status != TriggerStatus::Armed || trigger.pair() != quote.pair {
+                    continue;
+                }
+                if let Some(source) = &trigger.request.price_source {
+                    if source != &quote.exchange {
+                        continue;
+                    }
+                }
+                // Longs exit into the bid, shorts into the ask
+                let side_price = if trigger.request.is_long { quote.bid } else { quote.ask };
+                let price = match side_price.unwrap_or(quote.price).to_f64() {
+                    Some(p) if p > 0.0 => p,
+                    _ => continue,
+                };
+
+                if let TriggerKind::TrailingStop { trail_percent } = trigger.request.kind {
+                    let extreme = match trigger.extreme_price {
+                        Some(e) if trigger.request.is_long => e.max(price),
+                        Some(e) => e.min(price),
+                        None => price,
+                    };
+                    trigger.extreme_price = Some(extreme);
+                    trigger.level = self.exit_manager.trailing_stop_loss(price, extreme, trail_percent / 100.0, trigger.request.is_long);
+                }
+
+                let blocked = trigger.oco_group.as_ref().map(|g| busy_groups.contains(g)).unwrap_or(false);
+                if !blocked && trigger.is_hit(price) {
+                    trigger.status = TriggerStatus::Triggered;
+                    trigger.triggered_at = Some(now);
+                    trigger.trigger_price = Some(price);
+                    trigger.attempts += 1;
+                    fired.push(trigger.clone());
+                }
+            }
+
+            // Two legs of one bracket crossing on the same quote: keep the first, the other stays armed
+            let mut seen_groups = Vec::new();
+            fired.retain(|t| match &t.oco_group {
+                Some(group) if seen_groups.contains(group) => {
+                    if let Some(stored) = triggers.get_mut(&t.id) {
+                        stored.status = TriggerStatus::Armed;
+                        stored.attempts -= 1;
+                    }
+                    false
+                }
+                Some(group) => {
+                    seen_groups.push(group.clone());
+                    true
+                }
+                None => true,
+            });
+        }
+
+        for trigger in fired {
+            self.fire(trigger).await;
+        }
+    }
+
+    async fn fire(&self, trigger: TriggerOrder) {
+        let price = trigger.trigger_price.unwrap_or(trigger.level);
+        info!("🚨 {:?} triggered for {} at {:.6} (level {:.6})", trigger.request.kind, trigger.pair(), price, trigger.level);
+
+        let order = self.exit_order(&trigger, price);
+        let order_id = order.id.clone();
+        let result = match self.executor.create_order(order).await {
+            Ok(id) => self.executor.execute_order(&id).await,
+            Err(e) => Err(e),
+        };
+        // A CEX exit may still be working; treat acknowledgement as success and let reconciliation finish it
+        let outcome = match result {
+            Ok(_) => Ok(()),
+            Err(e) => match self.executor.get_order(&order_id).await {
+                Some(o) if matches!(o.status, OrderStatus::Submitted | OrderStatus::PartiallyFilled) => Ok(()),
+                _ => Err(e),
+            },
+        };
+
+        let max_attempts = self.config.read().await.max_fire_attempts;
+        let mut triggers = self.triggers.write().await;
+        if let Some(stored) = triggers.get_mut(&trigger.id) {
+            stored.exit_order_ids.push(order_id);
+            match outcome {
+                Ok(()) => stored.status = TriggerStatus::Filled,
+                Err(e) if stored.attempts < max_attempts => {
+                    warn!("⚠️ Exit for trigger {} failed ({}), re-arming", stored.id, e);
+                    stored.status = TriggerStatus::Armed;
+                    stored.error = Some(e);
+                }
+                Err(e) => {
+                    error!("❌ Exit for trigger {} failed after {} attempts: {}", stored.id, stored.attempts, e);
+                    stored.status = TriggerStatus::Failed;
+                    stored.error = Some(e);
+                }
+            }
+        }
+
+        if triggers.get(&trigger.id).map(|t| t.status) == Some(TriggerStatus::Filled) {
+            if let Some(group) = &trigger.oco_group {
+                for sibling in triggers.values_mut() {
+                    if sibling.oco_group.as_ref() == Some(group) && sibling.id != trigger.id && sibling.status == TriggerStatus::Armed {
+                        sibling.status = TriggerStatus::Cancelled;
+                        info!("🔗 OCO: cancelled {} after {} filled", sibling.id, trigger.id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Closing order for the position; the trigger id keys idempotency so one firing sends one order
+    fn exit_order(&self, trigger: &TriggerOrder, price: f64) -> TradeOrder {
+        let request = &trigger.request;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let (order_type, limit_price) = match request.execution {
+            ExitExecution::Market => {
+                let slip = request.slippage / 100.0;
+                (OrderType::Market, if request.is_long { price * (1.0 - slip) } else { price * (1.0 + slip) })
+            }
+            ExitExecution::Limit { offset_bps } => {
+                let offset = offset_bps / 10_000.0;
+                (OrderType::Limit, if request.is_long { trigger.level * (1.0 - offset) } else { trigger.level * (1.0 + offset) })
+            }
+        };
+        let (side, token_in, token_out, amount_in, amount_out_min) = if request.is_long {
+            (OrderSide::Sell, request.base.clone(), request.quote.clone(), request.size, request.size * limit_price)
+        } else {
+            (OrderSide::Buy, request.quote.clone(), request.base.clone(), request.size * limit_price, request.size)
+        };
+
+        let id = format!("{}_exit_{}", trigger.id, trigger.attempts);
+        TradeOrder {
+            id: id.clone(),
+            wallet: request.wallet.clone(),
+            order_type,
+            side,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out_min,
+            slippage: request.slippage,
+            status: OrderStatus::New,
+            dex: request.dex.clone(),
+            route: None,
+            gas_price: None,
+            deadline: now + 60,
+            created_at: now,
+            executed_at: None,
+            tx_hash: None,
+            error: None,
+            client_order_id: id,
+            venue_order_id: None,
+            filled_amount: 0.0,
+            status_history: Vec::new(),
+        }
+    }
+}
+
+impl Clone for TriggerEngine {
+    fn clone(&self) -> Self {
+        Self {
+            executor: self.executor.clone(),
+            exit_manager: self.exit_manager.clone(),
+            triggers: self.triggers.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
@@ -0,0 +1,830 @@
+// Transaction Policy Engine - Rules every wallet transaction must pass before it is signed
+// Per-tx and daily USD limits, program/counterparty allowlists, token denylists, simulation and manual approval
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use log::{info, warn, error};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+/// `None` limits and allowlists are unrestricted; an allowlist that is set but empty allows nothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WalletPolicy {
+    pub max_tx_usd: Option<f64>,
+    pub daily_limit_usd: Option<f64>,
+    pub allowed_programs: Option<HashSet<String>>,      // Solana program IDs / EVM contract addresses
+    pub allowed_counterparties: Option<HashSet<String>>,
+    pub denied_tokens: HashSet<String>,                 // Symbols or mint/contract addresses
+    pub require_simulation: bool,
+    pub approval_threshold_usd: Option<f64>,           // Above this a human must approve
+}
+
+impl Default for WalletPolicy {
+    fn default() -> Self {
+        Self {
+            max_tx_usd: Some(25_000.0),
+            daily_limit_usd: Some(100_000.0),
+            allowed_programs: None,
+            allowed_counterparties: None,
+            denied_tokens: HashSet::new(),
+            require_simulation: false,
+            approval_threshold_usd: Some(10_000.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyConfig {
+    pub default_policy: WalletPolicy,
+    pub wallet_policies: HashMap<String, WalletPolicy>,
+    pub approval_ttl_secs: u64,
+    pub audit_log_path: Option<String>, // JSON lines, appended per decision
+    pub max_audit_entries: usize,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            default_policy: WalletPolicy::default(),
+            wallet_policies: HashMap::new(),
+            approval_ttl_secs: 3600,
+            audit_log_path: None,
+            max_audit_entries: 10_000,
+        }
+    }
+}
+
+impl PolicyConfig {
+    /// JSON policy file from DEXTER_WALLET_POLICY_FILE; DEXTER_POLICY_AUDIT_LOG overrides the audit path
+    pub fn from_env() -> Self {
+        let mut config = match std::env::var("DEXTER_WALLET_POLICY_FILE") {
+            Ok(path) if !path.is_empty() => match std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|contents| serde_json::from_str::<PolicyConfig>(&contents).map_err(|e| e.to_string()))
+            {
+                Ok(config) => {
+                    info!("📜 Loaded wallet policies for {} wallet(s) from {}", config.wallet_policies.len(), path);
+                    config
+                }
+                Err(e) => {
+                    // Fall back to the defaults rather than running with no limits at all
+                    error!("❌ Invalid wallet policy file {}: {}; using default limits", path, e);
+                    PolicyConfig::default()
+                }
+            },
+            _ => PolicyConfig::default(),
+        };
+        if let Ok(path) = std::env::var("DEXTER_POLICY_AUDIT_LOG") {
+            if !path.is_empty() {
+                config.audit_log_path = Some(path);
+            }
+        }
+        config.normalized()
+    }
+
+    /// Re-keys wallet policies with `policy_key` so a checksummed EVM address in the file still matches
+    pub fn normalized(mut self) -> Self {
+        self.wallet_policies = self.wallet_policies.into_iter()
+            .map(|(wallet, policy)| (policy_key(&wallet), policy))
+            .collect();
+        self
+    }
+
+    pub fn policy_for(&self, wallet: &str) -> &WalletPolicy {
+        self.wallet_policies.get(&policy_key(wallet)).unwrap_or(&self.default_policy)
+    }
+}
+
+/// Policies, daily spend and approvals are keyed on this: EVM addresses lowercased, base58 keys unchanged
+pub fn policy_key(wallet: &str) -> String {
+    if wallet.starts_with("0x") { wallet.to_lowercase() } else { wallet.to_string() }
+}
+
+// ============================================================================
+// INTENTS & DECISIONS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationCheck {
+    pub success: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// What a transaction would do, as far as the policy is concerned. Value, programs, counterparties and simulation
+/// must come from the platform's own pricing, decoding and simulation, never from the request that asked for the
+/// transaction. Empty `programs` means nothing was decoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionIntent {
+    pub tx_id: String,
+    #[serde(default)]
+    pub client_id: String, // Stable across retries of one order; approvals are keyed on it (falls back to tx_id)
+    pub wallet: String,
+    pub token: String,
+    pub amount: f64,
+    pub value_usd: Option<f64>,
+    pub programs: Vec<String>,
+    pub counterparties: Vec<String>,
+    #[serde(default)]
+    pub undecoded_programs: Vec<String>, // Programs with instructions the decoder could not read
+    pub simulation: Option<SimulationCheck>,
+}
+
+impl TransactionIntent {
+    pub fn approval_key(&self) -> &str {
+        if self.client_id.is_empty() { &self.tx_id } else { &self.client_id }
+    }
+
+    /// Hash of what the transaction touches: wallet, token, programs and counterparties, in any order.
+    /// Value is bound separately as a ceiling since prices move between the request and the retry.
+    pub fn digest(&self) -> String {
+        let sorted = |items: &[String]| {
+            let mut keys: Vec<String> = items.iter().map(|item| policy_key(item)).collect();
+            keys.sort();
+            keys.dedup();
+            keys.join(",")
+        };
+        let mut hasher = Sha256::new();
+        for part in [policy_key(&self.wallet), self.token.to_uppercase(), sorted(&self.programs), sorted(&self.counterparties)] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Whether an approval granted for `self` also covers `other`: the same transaction for no more value
+    pub fn covers(&self, other: &TransactionIntent) -> bool {
+        self.digest() == other.digest()
+            && matches!((other.value_usd, self.value_usd), (Some(value), Some(approved)) if value <= approved)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PolicyViolation {
+    PerTxLimit { value_usd: f64, limit_usd: f64 },
+    DailyLimit { spent_usd: f64, value_usd: f64, limit_usd: f64 },
+    UnpricedTransaction { token: String },
+    ProgramNotAllowed { program: String },
+    ProgramsUndeclared,
+    InstructionUndecoded { program: String },
+    CounterpartiesUndeclared,
+    CounterpartyNotAllowed { counterparty: String },
+    TokenDenied { token: String },
+    SimulationMissing,
+    SimulationFailed { error: String },
+    ApprovalRejected { approval_id: String },
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::PerTxLimit { value_usd, limit_usd } => write!(f, "${:.2} exceeds per-transaction limit ${:.2}", value_usd, limit_usd),
+            PolicyViolation::DailyLimit { spent_usd, value_usd, limit_usd } => {
+                write!(f, "${:.2} on top of ${:.2} spent today exceeds daily limit ${:.2}", value_usd, spent_usd, limit_usd)
+            }
+            PolicyViolation::UnpricedTransaction { token } => write!(f, "no USD price for {} to check limits against", token),
+            PolicyViolation::ProgramNotAllowed { program } => write!(f, "program {} is not allowlisted", program),
+            PolicyViolation::ProgramsUndeclared => write!(f, "programs called by the transaction could not be decoded"),
+            PolicyViolation::InstructionUndecoded { program } => write!(f, "instruction for program {} could not be decoded", program),
+            PolicyViolation::CounterpartiesUndeclared => write!(f, "accounts the transaction pays or writes to could not be decoded"),
+            PolicyViolation::CounterpartyNotAllowed { counterparty } => write!(f, "counterparty {} is not allowlisted", counterparty),
+            PolicyViolation::TokenDenied { token } => write!(f, "token {} is denylisted", token),
+            PolicyViolation::SimulationMissing => write!(f, "simulation required but not run"),
+            PolicyViolation::SimulationFailed { error } => write!(f, "simulation failed: {}", error),
+            PolicyViolation::ApprovalRejected { approval_id } => write!(f, "approval {} was rejected", approval_id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "decision", rename_all = "snake_case")]
+pub enum PolicyDecision {
+    Allow,
+    Deny { violations: Vec<PolicyViolation> },
+    PendingApproval { approval_id: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Expired,
+    Used, // Approved and consumed by a signing attempt
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub intent: TransactionIntent,
+    pub status: ApprovalStatus,
+    pub requested_at: u64,
+    pub expires_at: u64,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<u64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyAuditEntry {
+    pub timestamp: u64,
+    pub wallet: String,
+    pub tx_id: String,
+    pub value_usd: Option<f64>,
+    pub outcome: String, // allow | deny | pending_approval | approved | rejected | released
+    pub reasons: Vec<String>,
+    pub actor: Option<String>,
+}
+
+// Spend held against the daily limit from approval until the transaction settles
+#[derive(Debug, Clone)]
+struct SpendReservation {
+    wallet: String,
+    day: u64,
+    value_usd: f64,
+}
+
+// Where an intent above the approval threshold stands
+enum ApprovalClaim {
+    Granted(String),
+    Pending(String),
+    Rejected(String),
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+pub struct TxPolicyEngine {
+    config: Arc<RwLock<PolicyConfig>>,
+    daily_spend: Arc<RwLock<HashMap<String, (u64, f64)>>>, // wallet -> (UTC day, USD spent)
+    reservations: Arc<RwLock<HashMap<String, SpendReservation>>>,
+    approvals: Arc<RwLock<HashMap<String, ApprovalRequest>>>,
+    audit_log: Arc<RwLock<VecDeque<PolicyAuditEntry>>>,
+}
+
+impl TxPolicyEngine {
+    pub fn new(config: PolicyConfig) -> Self {
+        Self {
+            config: Arc::new(RwLock::new(config)),
+            daily_spend: Arc::new(RwLock::new(HashMap::new())),
+            reservations: Arc::new(RwLock::new(HashMap::new())),
+            approvals: Arc::new(RwLock::new(HashMap::new())),
+            audit_log: Arc::new(RwLock::new(VecDeque::new())),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(PolicyConfig::from_env())
+    }
+
+    pub async fn get_config(&self) -> PolicyConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: PolicyConfig) {
+        *self.config.write().await = config.normalized();
+    }
+
+    pub async fn set_wallet_policy(&self, wallet: &str, policy: WalletPolicy) {
+        self.config.write().await.wallet_policies.insert(policy_key(wallet), policy);
+    }
+
+    /// Allowed intents reserve their USD value against the daily limit until `settle` or `release`
+    pub async fn evaluate(&self, intent: TransactionIntent) -> PolicyDecision {
+        let config = self.config.read().await.clone();
+        let policy = config.policy_for(&intent.wallet).clone();
+        let mut violations = static_violations(&policy, &intent);
+        let wallet = policy_key(&intent.wallet);
+
+        let day = chrono::Utc::now().timestamp() as u64 / SECONDS_PER_DAY;
+        if let Some(value) = intent.value_usd {
+            let spent = self.spent_today(&wallet, day).await;
+            if let Some(limit) = policy.daily_limit_usd {
+                if spent + value > limit {
+                    violations.push(PolicyViolation::DailyLimit { spent_usd: spent, value_usd: value, limit_usd: limit });
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            // The approval is looked up, the spend reserved and the approval used up under one lock, so two
+            // attempts cannot share an approval and an attempt denied by the daily limit does not spend it
+            let mut approvals = self.approvals.write().await;
+            let mut granted = None;
+            if let (Some(threshold), Some(value)) = (policy.approval_threshold_usd, intent.value_usd) {
+                if value > threshold {
+                    match claim_approval(&mut approvals, &intent, config.approval_ttl_secs) {
+                        ApprovalClaim::Granted(approval_id) => granted = Some(approval_id),
+                        ApprovalClaim::Pending(approval_id) => {
+                            drop(approvals);
+                            self.audit(&config, &intent, "pending_approval", vec![format!("${:.2} exceeds approval threshold ${:.2}", value, threshold)], None).await;
+                            return PolicyDecision::PendingApproval { approval_id };
+                        }
+                        ApprovalClaim::Rejected(approval_id) => violations.push(PolicyViolation::ApprovalRejected { approval_id }),
+                    }
+                }
+            }
+
+            if let Some(value) = intent.value_usd.filter(|_| violations.is_empty()) {
+                // Re-check under the write lock so concurrent transactions cannot overshoot the limit together
+                let mut daily = self.daily_spend.write().await;
+                let entry = daily.entry(wallet.clone()).or_insert((day, 0.0));
+                if entry.0 != day {
+                    *entry = (day, 0.0);
+                }
+                match policy.daily_limit_usd {
+                    Some(limit) if entry.1 + value > limit => {
+                        violations.push(PolicyViolation::DailyLimit { spent_usd: entry.1, value_usd: value, limit_usd: limit });
+                    }
+                    _ => {
+                        entry.1 += value;
+                        drop(daily);
+                        self.reservations.write().await.insert(intent.tx_id.clone(), SpendReservation {
+                            wallet: wallet.clone(),
+                            day,
+                            value_usd: value,
+                        });
+                    }
+                }
+            }
+
+            if violations.is_empty() {
+                if let Some(approval) = granted.and_then(|id| approvals.get_mut(&id)) {
+                    approval.status = ApprovalStatus::Used;
+                }
+            }
+        }
+
+        if violations.is_empty() {
+            self.audit(&config, &intent, "allow", Vec::new(), None).await;
+            PolicyDecision::Allow
+        } else {
+            let reasons = violations.iter().map(|v| v.to_string()).collect();
+            self.audit(&config, &intent, "deny", reasons, None).await;
+            PolicyDecision::Deny { violations }
+        }
+    }
+
+    /// The transaction went through; its spend stays counted
+    pub async fn settle(&self, tx_id: &str) {
+        self.reservations.write().await.remove(tx_id);
+    }
+
+    /// The transaction never reached the chain; give its spend back to today's limit
+    pub async fn release(&self, tx_id: &str) {
+        let Some(reservation) = self.reservations.write().await.remove(tx_id) else {
+            return;
+        };
+        {
+            let mut daily = self.daily_spend.write().await;
+            if let Some(entry) = daily.get_mut(&reservation.wallet) {
+                if entry.0 == reservation.day {
+                    entry.1 = (entry.1 - reservation.value_usd).max(0.0);
+                }
+            }
+        }
+        let config = self.config.read().await.clone();
+        self.record(&config, PolicyAuditEntry {
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            wallet: reservation.wallet,
+            tx_id: tx_id.to_string(),
+            value_usd: Some(reservation.value_usd),
+            outcome: "released".to_string(),
+            reasons: Vec::new(),
+            actor: None,
+        }).await;
+    }
+
+    pub async fn approve(&self, approval_id: &str, actor: &str) -> Result<ApprovalRequest, String> {
+        self.decide(approval_id, actor, ApprovalStatus::Approved, None).await
+    }
+
+    pub async fn reject(&self, approval_id: &str, actor: &str, reason: Option<String>) -> Result<ApprovalRequest, String> {
+        self.decide(approval_id, actor, ApprovalStatus::Rejected, reason).await
+    }
+
+    pub async fn get_approvals(&self, pending_only: bool) -> Vec<ApprovalRequest> {
+        self.expire_approvals().await;
+        let mut approvals: Vec<ApprovalRequest> = self.approvals.read().await
+            .values()
+            .filter(|a| !pending_only || a.status == ApprovalStatus::Pending)
+            .cloned()
+            .collect();
+        approvals.sort_by_key(|a| std::cmp::Reverse(a.requested_at));
+        approvals
+    }
+
+    pub async fn get_audit_log(&self, limit: usize) -> Vec<PolicyAuditEntry> {
+        self.audit_log.read().await.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub async fn spent_today(&self, wallet: &str, day: u64) -> f64 {
+        match self.daily_spend.read().await.get(&policy_key(wallet)) {
+            Some((spent_day, spent)) if *spent_day == day => *spent,
+            _ => 0.0,
+        }
+    }
+
+    async fn decide(&self, approval_id: &str, actor: &str, status: ApprovalStatus, note: Option<String>) -> Result<ApprovalRequest, String> {
+        self.expire_approvals().await;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let decided = {
+            let mut approvals = self.approvals.write().await;
+            let approval = approvals.get_mut(approval_id).ok_or_else(|| "Approval not found".to_string())?;
+            if approval.status != ApprovalStatus::Pending {
+                return Err(format!("Approval is already {:?}", approval.status));
+            }
+            approval.status = status;
+            approval.decided_by = Some(actor.to_string());
+            approval.decided_at = Some(now);
+            approval.note = note.clone();
+            approval.clone()
+        };
+
+        let config = self.config.read().await.clone();
+        let outcome = if status == ApprovalStatus::Approved { "approved" } else { "rejected" };
+        self.audit(&config, &decided.intent, outcome, note.into_iter().collect(), Some(actor.to_string())).await;
+        Ok(decided)
+    }
+
+    async fn expire_approvals(&self) {
+        expire(&mut *self.approvals.write().await);
+    }
+
+    async fn audit(&self, config: &PolicyConfig, intent: &TransactionIntent, outcome: &str, reasons: Vec<String>, actor: Option<String>) {
+        self.record(config, PolicyAuditEntry {
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            wallet: intent.wallet.clone(),
+            tx_id: intent.tx_id.clone(),
+            value_usd: intent.value_usd,
+            outcome: outcome.to_string(),
+            reasons,
+            actor,
+        }).await;
+    }
+
+    async fn record(&self, config: &PolicyConfig, entry: PolicyAuditEntry) {
+        match entry.outcome.as_str() {
+            "deny" | "rejected" => warn!("🚫 Policy {} for tx {} ({}): {}", entry.outcome, entry.tx_id, entry.wallet, entry.reasons.join("; ")),
+            _ => info!("📜 Policy {} for tx {} ({})", entry.outcome, entry.tx_id, entry.wallet),
+        }
+
+        if let Some(path) = &config.audit_log_path {
+            let appended = serde_json::to_string(&entry).map_err(|e| e.to_string()).and_then(|line| {
+                std::fs::OpenOptions::new().create(true).append(true).open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+                    .map_err(|e| e.to_string())
+            });
+            if let Err(e) = appended {
+                error!("❌ Failed to append policy audit log {}: {}", path, e);
+            }
+        }
+
+        let mut log = self.audit_log.write().await;
+        log.push_back(entry);
+        while log.len() > config.max_audit_entries {
+            log.pop_front();
+        }
+    }
+}
+
+impl Clone for TxPolicyEngine {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            daily_spend: self.daily_spend.clone(),
+            reservations: self.reservations.clone(),
+            approvals: self.approvals.clone(),
+            audit_log: self.audit_log.clone(),
+        }
+    }
+}
+
+impl Default for TxPolicyEngine {
+    fn default() -> Self {
+        Self::new(PolicyConfig::default())
+    }
+}
+
+fn expire(approvals: &mut HashMap<String, ApprovalRequest>) {
+    let now = chrono::Utc::now().timestamp() as u64;
+    for approval in approvals.values_mut() {
+        if matches!(approval.status, ApprovalStatus::Pending | ApprovalStatus::Approved) && approval.expires_at <= now {
+            approval.status = ApprovalStatus::Expired;
+        }
+    }
+    // Keep decided requests for a day so the dashboard can show them
+    approvals.retain(|_, a| a.status == ApprovalStatus::Pending || a.requested_at + SECONDS_PER_DAY > now);
+}
+
+/// Only requests for this order and this exact transaction (`TransactionIntent::digest`) count. A rejection
+/// wins, then an approval that covers the intent (the caller marks it used once the transaction is allowed),
+/// then an outstanding request; otherwise a new one is queued.
+fn claim_approval(approvals: &mut HashMap<String, ApprovalRequest>, intent: &TransactionIntent, ttl_secs: u64) -> ApprovalClaim {
+    expire(approvals);
+    let now = chrono::Utc::now().timestamp() as u64;
+    let wallet = policy_key(&intent.wallet);
+    let digest = intent.digest();
+
+    let requests: Vec<&ApprovalRequest> = approvals.values()
+        .filter(|a| policy_key(&a.intent.wallet) == wallet
+            && a.intent.approval_key() == intent.approval_key()
+            && a.intent.digest() == digest)
+        .collect();
+    if let Some(rejected) = requests.iter().find(|a| a.status == ApprovalStatus::Rejected) {
+        return ApprovalClaim::Rejected(rejected.id.clone());
+    }
+    // Approval covers what the approver saw; a bigger transaction needs a new one
+    if let Some(approved) = requests.iter().find(|a| a.status == ApprovalStatus::Approved && a.intent.covers(intent)) {
+        return ApprovalClaim::Granted(approved.id.clone());
+    }
+    if let Some(pending) = requests.iter().find(|a| a.status == ApprovalStatus::Pending) {
+        return ApprovalClaim::Pending(pending.id.clone());
+    }
+
+    let id = format!("approval_{}", uuid::Uuid::new_v4().simple());
+    approvals.insert(id.clone(), ApprovalRequest {
+        id: id.clone(),
+        intent: intent.clone(),
+        status: ApprovalStatus::Pending,
+        requested_at: now,
+        expires_at: now + ttl_secs,
+        decided_by: None,
+        decided_at: None,
+        note: None,
+    });
+    info!("✋ Transaction {} ({}) from {} queued for manual approval ({})", intent.tx_id, intent.approval_key(), intent.wallet, id);
+    ApprovalClaim::Pending(id)
+}
+
+/// Rules that depend only on the policy and the intent itself
+fn static_violations(policy: &WalletPolicy, intent: &TransactionIntent) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+
+    if policy.denied_tokens.iter().any(|t| t.eq_ignore_ascii_case(&intent.token)) {
+        violations.push(PolicyViolation::TokenDenied { token: intent.token.clone() });
+    }
+
+    for program in &intent.undecoded_programs {
+        violations.push(PolicyViolation::InstructionUndecoded { program: program.clone() });
+    }
+
+    if let Some(allowed) = &policy.allowed_programs {
+        if intent.programs.is_empty() {
+            violations.push(PolicyViolation::ProgramsUndeclared);
+        }
+        for program in &intent.programs {
+            if !contains_address(allowed, program) {
+                violations.push(PolicyViolation::ProgramNotAllowed { program: program.clone() });
+            }
+        }
+    }
+
+    if let Some(allowed) = &policy.allowed_counterparties {
+        if intent.programs.is_empty() {
+            violations.push(PolicyViolation::CounterpartiesUndeclared);
+        }
+        for counterparty in &intent.counterparties {
+            if !contains_address(allowed, counterparty) {
+                violations.push(PolicyViolation::CounterpartyNotAllowed { counterparty: counterparty.clone() });
+            }
+        }
+    }
+
+    if policy.require_simulation {
+        match &intent.simulation {
+            None => violations.push(PolicyViolation::SimulationMissing),
+            Some(simulation) if !simulation.success => violations.push(PolicyViolation::SimulationFailed {
+                error: simulation.error.clone().unwrap_or_else(|| "unknown error".to_string()),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    match intent.value_usd {
+        Some(value) => {
+            if let Some(limit) = policy.max_tx_usd {
+                if value > limit {
+                    violations.push(PolicyViolation::PerTxLimit { value_usd: value, limit_usd: limit });
+                }
+            }
+        }
+        // Limits that cannot be checked are treated as exceeded
+        None if policy.max_tx_usd.is_some() || policy.daily_limit_usd.is_some() || policy.approval_threshold_usd.is_some() => {
+            violations.push(PolicyViolation::UnpricedTransaction { token: intent.token.clone() });
+        }
+        None => {}
+    }
+
+    violations
+}
+
+/// EVM addresses compare case-insensitively; base58 keys are case-sensitive
+fn contains_address(set: &HashSet<String>, value: &str) -> bool {
+    if value.starts_with("0x") {
+        set.iter().any(|entry| entry.eq_ignore_ascii_case(value))
+    } else {
+        set.contains(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WALLET: &str = "0xAbCdEf0123456789aBcDeF0123456789AbCdEf01";
+
+    fn intent(tx_id: &str, value_usd: f64) -> TransactionIntent {
+        TransactionIntent {
+            tx_id: tx_id.to_string(),
+            client_id: "order-1".to_string(),
+            wallet: WALLET.to_string(),
+            token: "USDC".to_string(),
+            amount: value_usd,
+            value_usd: Some(value_usd),
+            programs: vec!["router".to_string()],
+            counterparties: vec!["0xPool".to_string()],
+            undecoded_programs: Vec::new(),
+            simulation: None,
+        }
+    }
+
+    fn engine(policy: WalletPolicy) -> TxPolicyEngine {
+        TxPolicyEngine::new(PolicyConfig { default_policy: policy, ..PolicyConfig::default() })
+    }
+
+    fn today() -> u64 {
+        chrono::Utc::now().timestamp() as u64 / SECONDS_PER_DAY
+    }
+
+    fn violations(decision: PolicyDecision) -> Vec<PolicyViolation> {
+        match decision {
+            PolicyDecision::Deny { violations } => violations,
+            other => panic!("expected a denial, got {:?}", other),
+        }
+    }
+
+    fn approval_id(decision: PolicyDecision) -> String {
+        match decision {
+            PolicyDecision::PendingApproval { approval_id } => approval_id,
+            other => panic!("expected a pending approval, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn per_transaction_and_daily_limits_hold_and_released_spend_returns() {
+        let engine = engine(WalletPolicy { max_tx_usd: Some(500.0), daily_limit_usd: Some(1_000.0), approval_threshold_usd: None, ..WalletPolicy::default() });
+
+        assert_eq!(violations(engine.evaluate(intent("big", 600.0)).await), vec![PolicyViolation::PerTxLimit { value_usd: 600.0, limit_usd: 500.0 }]);
+        assert!(matches!(engine.evaluate(intent("a", 400.0)).await, PolicyDecision::Allow));
+        assert!(matches!(engine.evaluate(intent("b", 400.0)).await, PolicyDecision::Allow));
+        assert_eq!(
+            violations(engine.evaluate(intent("c", 400.0)).await),
+            vec![PolicyViolation::DailyLimit { spent_usd: 800.0, value_usd: 400.0, limit_usd: 1_000.0 }],
+        );
+
+        engine.settle("a").await;
+        engine.release("b").await;
+        assert_eq!(engine.spent_today(WALLET, today()).await, 400.0);
+        assert!(matches!(engine.evaluate(intent("c", 400.0)).await, PolicyDecision::Allow));
+
+        // Limits that cannot be checked count as exceeded
+        let unpriced = TransactionIntent { value_usd: None, ..intent("d", 0.0) };
+        assert_eq!(violations(engine.evaluate(unpriced).await), vec![PolicyViolation::UnpricedTransaction { token: "USDC".to_string() }]);
+    }
+
+    #[tokio::test]
+    async fn allowlists_deny_unlisted_and_undecoded_programs_and_counterparties() {
+        let engine = engine(WalletPolicy {
+            allowed_programs: Some(HashSet::from(["router".to_string()])),
+            allowed_counterparties: Some(HashSet::from(["0xpool".to_string()])),
+            denied_tokens: HashSet::from(["SCAM".to_string()]),
+            ..WalletPolicy::default()
+        });
+
+        // EVM counterparties match in any case
+        assert!(matches!(engine.evaluate(intent("ok", 10.0)).await, PolicyDecision::Allow));
+
+        let unlisted = TransactionIntent {
+            programs: vec!["router".to_string(), "drainer".to_string()],
+            counterparties: vec!["0xThief".to_string()],
+            token: "scam".to_string(),
+            ..intent("unlisted", 10.0)
+        };
+        assert_eq!(violations(engine.evaluate(unlisted).await), vec![
+            PolicyViolation::TokenDenied { token: "scam".to_string() },
+            PolicyViolation::ProgramNotAllowed { program: "drainer".to_string() },
+            PolicyViolation::CounterpartyNotAllowed { counterparty: "0xThief".to_string() },
+        ]);
+
+        let undecoded = TransactionIntent { programs: Vec::new(), counterparties: Vec::new(), ..intent("undecoded", 10.0) };
+        assert_eq!(violations(engine.evaluate(undecoded).await), vec![PolicyViolation::ProgramsUndeclared, PolicyViolation::CounterpartiesUndeclared]);
+
+        // An instruction that did not decode is denied whatever the allowlists say
+        let open = self::engine(WalletPolicy::default());
+        let partial = TransactionIntent { undecoded_programs: vec!["mystery".to_string()], ..intent("partial", 10.0) };
+        assert_eq!(violations(open.evaluate(partial).await), vec![PolicyViolation::InstructionUndecoded { program: "mystery".to_string() }]);
+    }
+
+    #[tokio::test]
+    async fn evm_wallet_policies_match_in_any_case() {
+        let engine = engine(WalletPolicy::default());
+        let strict = WalletPolicy { max_tx_usd: Some(5.0), ..WalletPolicy::default() };
+        engine.set_config(PolicyConfig {
+            wallet_policies: HashMap::from([(WALLET.to_string(), strict.clone())]),
+            ..PolicyConfig::default()
+        }).await;
+
+        let config = engine.get_config().await;
+        assert!(config.wallet_policies.contains_key(&WALLET.to_lowercase()));
+        assert_eq!(config.policy_for(&WALLET.to_uppercase().replace("0X", "0x")).max_tx_usd, Some(5.0));
+        assert_eq!(config.policy_for("0x0000000000000000000000000000000000000001").max_tx_usd, Some(25_000.0));
+
+        engine.set_wallet_policy(&WALLET.to_lowercase(), strict).await;
+        assert_eq!(violations(engine.evaluate(intent("tx", 10.0)).await), vec![PolicyViolation::PerTxLimit { value_usd: 10.0, limit_usd: 5.0 }]);
+
+        // Daily spend is counted once per wallet whatever case the address arrives in
+        let engine = self::engine(WalletPolicy::default());
+        engine.evaluate(intent("mixed", 10.0)).await;
+        engine.evaluate(TransactionIntent { wallet: WALLET.to_lowercase(), ..intent("lower", 10.0) }).await;
+        assert_eq!(engine.spent_today(WALLET, today()).await, 20.0);
+    }
+
+    #[tokio::test]
+    async fn approvals_cover_one_attempt_at_the_approved_transaction() {
+        let engine = engine(WalletPolicy { approval_threshold_usd: Some(100.0), ..WalletPolicy::default() });
+
+        let id = approval_id(engine.evaluate(intent("tx-1", 200.0)).await);
+        assert_eq!(approval_id(engine.evaluate(intent("tx-2", 200.0)).await), id);
+        engine.approve(&id, "ops").await.unwrap();
+        assert!(engine.approve(&id, "ops").await.is_err());
+
+        // A retry to a different counterparty or for more value is a different transaction
+        let redirected = TransactionIntent { counterparties: vec!["0xElsewhere".to_string()], ..intent("tx-3", 200.0) };
+        let other = approval_id(engine.evaluate(redirected).await);
+        assert_ne!(other, id);
+        engine.reject(&other, "ops", Some("wrong pool".to_string())).await.unwrap();
+        let redirected = TransactionIntent { counterparties: vec!["0xElsewhere".to_string()], ..intent("tx-4", 200.0) };
+        assert_eq!(
+            violations(engine.evaluate(redirected).await),
+            vec![PolicyViolation::ApprovalRejected { approval_id: other }],
+        );
+
+        // The transaction that was approved still goes through, once
+        assert!(matches!(engine.evaluate(intent("tx-5", 150.0)).await, PolicyDecision::Allow));
+        assert_ne!(approval_id(engine.evaluate(intent("tx-6", 150.0)).await), id);
+    }
+
+    #[tokio::test]
+    async fn approvals_are_used_only_by_an_allowed_attempt() {
+        let policy = WalletPolicy { approval_threshold_usd: Some(100.0), daily_limit_usd: Some(1_000.0), ..WalletPolicy::default() };
+        let engine = engine(policy.clone());
+        let status = |approvals: Vec<ApprovalRequest>, id: &str| approvals.into_iter().find(|a| a.id == id).map(|a| a.status);
+
+        let id = approval_id(engine.evaluate(intent("tx-1", 300.0)).await);
+        engine.approve(&id, "ops").await.unwrap();
+
+        // Pricier than approved: a new approval is needed and the granted one stays unused
+        assert_ne!(approval_id(engine.evaluate(intent("tx-2", 301.0)).await), id);
+        assert_eq!(status(engine.get_approvals(false).await, &id), Some(ApprovalStatus::Approved));
+
+        let engine = self::engine(policy);
+        let id = approval_id(engine.evaluate(intent("tx-1", 300.0)).await);
+        engine.approve(&id, "ops").await.unwrap();
+
+        // Denied by the daily limit after approval: the approval survives for the next attempt
+        for i in 0..8 {
+            let filler = TransactionIntent { client_id: format!("filler-{}", i), ..intent(&format!("filler-{}", i), 100.0) };
+            assert!(matches!(engine.evaluate(filler).await, PolicyDecision::Allow));
+        }
+        assert!(matches!(engine.evaluate(intent("tx-2", 300.0)).await, PolicyDecision::Deny { .. }));
+        assert_eq!(status(engine.get_approvals(false).await, &id), Some(ApprovalStatus::Approved));
+
+        engine.release("filler-0").await;
+        assert!(matches!(engine.evaluate(intent("tx-3", 150.0)).await, PolicyDecision::Allow));
+        assert_eq!(status(engine.get_approvals(false).await, &id), Some(ApprovalStatus::Used));
+        assert_ne!(approval_id(engine.evaluate(intent("tx-4", 150.0)).await), id);
+    }
+
+    #[test]
+    fn an_unpriced_intent_is_never_covered() {
+        let approved = intent("tx-1", 300.0);
+        assert!(approved.covers(&intent("tx-2", 299.0)));
+        assert!(!approved.covers(&TransactionIntent { value_usd: None, ..intent("tx-2", 0.0) }));
+        assert!(!TransactionIntent { value_usd: None, ..intent("tx-1", 0.0) }.covers(&intent("tx-2", 1.0)));
+        assert!(!approved.covers(&TransactionIntent { token: "USDT".to_string(), ..intent("tx-2", 1.0) }));
+    }
+}
@@ -0,0 +1,104 @@
+// Core Types - Price quotes, opportunities, orders and exchange client traits
+// Shared by the platform and every venue connector, including the exchange simulator
+
+use anyhow::Result;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use async_trait::async_trait;
+
+use crate::fee_model::ProfitBreakdown;
+use crate::opportunity_scoring::OpportunityScore;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceInfo {
+    pub exchange: String,
+    pub exchange_type: ExchangeType,
+    pub pair: String,
+    pub price: Decimal,
+    pub bid: Option<Decimal>,
+    pub ask: Option<Decimal>,
+    pub volume_24h: Option<Decimal>,
+    pub liquidity: Option<Decimal>,
+    pub timestamp: u64,
+    pub latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ExchangeType {
+    Dex,
+    Cex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrageOpportunity {
+    pub id: String,
+    pub token_pair: String,
+    pub buy_exchange: String,
+    pub sell_exchange: String,
+    pub buy_price: Decimal,
+    pub sell_price: Decimal,
+    pub profit_percentage: Decimal,
+    pub estimated_profit_usd: Decimal,
+    pub max_trade_size: Decimal,
+    pub liquidity_score: f64,
+    pub risk_score: f64,
+    pub confidence: f64,
+    pub timestamp: u64,
+    pub expires_at: u64,
+    pub trade_route: Vec<TradeStep>,
+    pub profit_breakdown: ProfitBreakdown,
+    pub score: OpportunityScore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeStep {
+    pub exchange: String,
+    pub action: String, // "buy" | "sell" | "swap"
+    pub from_token: String,
+    pub to_token: String,
+    pub amount: Decimal,
+    pub price: Decimal,
+    pub fees: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Order {
+    pub pair: String,
+    pub side: String, // "buy" | "sell"
+    pub amount: Decimal,
+    pub price: Option<Decimal>, // None for market orders
+    pub order_type: String, // "market" | "limit"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub bids: Vec<OrderBookEntry>,
+    pub asks: Vec<OrderBookEntry>,
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookEntry {
+    pub price: Decimal,
+    pub amount: Decimal,
+}
+
+// Traits for exchange clients
+#[async_trait]
+pub trait DexClient {
+    async fn get_price(&self, pair: &str) -> Result<PriceInfo>;
+    async fn get_liquidity(&self, pool: &str) -> Result<Decimal>;
+    async fn execute_swap(&self, trade: &TradeStep) -> Result<String>;
+    async fn get_pools(&self) -> Result<Vec<String>>;
+    fn name(&self) -> &str;
+}
+
+#[async_trait]
+pub trait CexClient {
+    async fn get_price(&self, pair: &str) -> Result<PriceInfo>;
+    async fn get_order_book(&self, pair: &str) -> Result<OrderBook>;
+    async fn place_order(&self, order: &Order) -> Result<String>;
+    async fn get_balance(&self, token: &str) -> Result<Decimal>;
+    fn name(&self) -> &str;
+}
@@ -12,6 +12,8 @@ use chrono::{DateTime, Utc};
 use reqwest::Client;
 use tokio::time::{interval, Duration};
 
+use crate::fee_model::{FeeModel, Liquidity, PricedLeg, ProfitBreakdown};
+
 // Price data structure with full exchange details
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangePrice {
@@ -52,6 +54,7 @@ pub struct LiveArbitrageOpportunity {
     pub execution_path: Vec<String>,
     pub expires_at: DateTime<Utc>,
     pub confidence_score: f64,
+    pub profit_breakdown: ProfitBreakdown,
 }
 
 pub struct UniversalPriceAggregator {
@@ -69,10 +72,13 @@ pub struct UniversalPriceAggregator {
     
     // API keys for CEX access
     api_keys: Arc<RwLock<HashMap<String, String>>>,
+    
+    // Shared venue/gas fee model
+    fee_model: Arc<FeeModel>,
 }
 
 impl UniversalPriceAggregator {
-    pub fn new() -> Self {
+    pub fn new(fee_model: Arc<FeeModel>) -> Self {
         let mut dex_endpoints = HashMap::new();
         let mut cex_endpoints = HashMap::new();
         
@@ -109,6 +115,7 @@ impl UniversalPriceAggregator {
             dex_endpoints,
             cex_endpoints,
             api_keys: Arc::new(RwLock::new(HashMap::new())),
+            fee_model,
         }
     }
     
@@ -147,9 +154,11 @@ impl UniversalPriceAggregator {
         // Collect all results
         let results = futures::future::join_all(tasks).await;
         
-        // Process results and update price storage
+        // Process results and update price storage; fees come from the venue's schedule
         for result in results {
-            if let Ok(Ok(Some(price))) = result {
+            if let Ok(Ok(Some(mut price))) = result {
+                price.maker_fee = self.fee_model.fee_rate(&price.exchange, Liquidity::Maker).await;
+                price.taker_fee = self.fee_model.fee_rate(&price.exchange, Liquidity::Taker).await;
                 let pair = price.pair.clone();
                 self.prices.entry(pair).or_insert(Vec::new()).push(price);
             }
@@ -226,8 +235,8 @@ impl UniversalPriceAggregator {
                     last_update: Utc::now(),
                     tradeable: true,
                     min_order_size: Decimal::from(10),
-                    maker_fee: Decimal::from_f64(0.0025).unwrap(),
-                    taker_fee: Decimal::from_f64(0.0025).unwrap(),
+                    maker_fee: Decimal::ZERO, // Filled from the fee model
+                    taker_fee: Decimal::ZERO,
                 }));
             }
         }
@@ -278,8 +287,8 @@ impl UniversalPriceAggregator {
                 last_update: Utc::now(),
                 tradeable: true,
                 min_order_size: Decimal::from_f64(0.001).unwrap(),
-                maker_fee: Decimal::from_f64(0.001).unwrap(), // 0.1%
-                taker_fee: Decimal::from_f64(0.001).unwrap(), // 0.1%
+                maker_fee: Decimal::ZERO, // Filled from the fee model
+                taker_fee: Decimal::ZERO,
             }));
         }
         
@@ -332,8 +341,8 @@ impl UniversalPriceAggregator {
                 last_update: Utc::now(),
                 tradeable: true,
                 min_order_size: Decimal::from_f64(0.001).unwrap(),
-                maker_fee: Decimal::from_f64(0.005).unwrap(), // 0.5%
-                taker_fee: Decimal::from_f64(0.005).unwrap(), // 0.5%
+                maker_fee: Decimal::ZERO, // Filled from the fee model
+                taker_fee: Decimal::ZERO,
             }));
         }
         
@@ -425,14 +434,19 @@ impl UniversalPriceAggregator {
                         let gross_profit_pct = ((sell_price - buy_price) / buy_price * Decimal::from(100))
                             .round_dp(2);
                         
-                        // Calculate fees
-                        let buy_fee = buy_price * buy_exchange.taker_fee;
-                        let sell_fee = sell_price * sell_exchange.taker_fee;
-                        let total_fees = buy_fee + sell_fee;
-                        
-                        // Calculate net profit
-                        let net_profit_pct = gross_profit_pct - 
-                            (total_fees / buy_price * Decimal::from(100));
+                        // Trading fees, pool fees, gas and transfer costs for a 1000-unit trade
+                        let base_asset = pair.split('/').next().unwrap_or(pair.as_str());
+                        let requires_transfer = self.fee_model
+                            .requires_transfer(&buy_exchange.exchange, &sell_exchange.exchange)
+                            .await;
+                        let breakdown = self.fee_model.evaluate_two_leg(
+                            PricedLeg { venue: &buy_exchange.exchange, price: buy_price },
+                            PricedLeg { venue: &sell_exchange.exchange, price: sell_price },
+                            Decimal::from(1000),
+                            base_asset,
+                            requires_transfer,
+                        ).await;
+                        let net_profit_pct = breakdown.net_profit_percentage.round_dp(4);
                         
                         // Only include if profitable after fees
                         if net_profit_pct > Decimal::from_f64(0.1).unwrap() {
@@ -447,7 +461,7 @@ impl UniversalPriceAggregator {
                                 profit_percentage: gross_profit_pct,
                                 profit_usd: (sell_price - buy_price) * Decimal::from(1000), // $1000 trade
                                 required_capital: buy_price * Decimal::from(1000),
-                                total_fees: total_fees * Decimal::from(1000),
+                                total_fees: breakdown.total_costs_usd,
                                 net_profit: net_profit_pct,
                                 execution_path: vec![
                                     format!("Buy on {} at {}", buy_exchange.exchange, buy_price),
@@ -456,6 +470,7 @@ impl UniversalPriceAggregator {
                                 ],
                                 expires_at: Utc::now() + chrono::Duration::seconds(30),
                                 confidence_score: Self::calculate_confidence(buy_exchange, sell_exchange),
+                                profit_breakdown: breakdown,
                             };
                             
                             opportunities.push(opportunity);
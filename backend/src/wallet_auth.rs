@@ -0,0 +1,629 @@
+// Wallet Authentication - Signature verification for Solana (ed25519) and EVM (EIP-191, EIP-712) wallets
+// Sign-In-With-Ethereum / Sign-In-With-Solana message formats with domain, time window and nonce checks
+
+use std::collections::BTreeSet;
+use serde::{Deserialize, Serialize};
+use anyhow::{Result, anyhow, Context};
+use chrono::{DateTime, Utc};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use k256::ecdsa::{RecoveryId, Signature as EcdsaSignature, VerifyingKey as EcdsaVerifyingKey};
+use ed25519_dalek::{Signature as Ed25519Signature, Verifier, VerifyingKey as Ed25519VerifyingKey};
+
+use crate::evm_tx::{self, Address};
+
+// ============================================================================
+// WALLET ADDRESSES & SIGNATURE ENCODINGS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChainFamily {
+    Ethereum,
+    Solana,
+}
+
+impl ChainFamily {
+    /// 0x-prefixed 20-byte hex is EVM, anything else is treated as a base58 Solana key
+    pub fn of_address(address: &str) -> Self {
+        if address.starts_with("0x") && address.len() == 42 {
+            ChainFamily::Ethereum
+        } else {
+            ChainFamily::Solana
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ChainFamily::Ethereum => "Ethereum",
+            ChainFamily::Solana => "Solana",
+        }
+    }
+}
+
+/// Wallets hand back signatures as 0x-hex (MetaMask), base58 (Phantom/Solflare adapters) or base64
+pub fn decode_signature(signature: &str) -> Result<Vec<u8>> {
+    let signature = signature.trim();
+    if let Some(hex_sig) = signature.strip_prefix("0x") {
+        return hex::decode(hex_sig).context("Signature is not valid hex");
+    }
+    if let Ok(bytes) = bs58::decode(signature).into_vec() {
+        return Ok(bytes);
+    }
+    BASE64.decode(signature).context("Signature is not hex, base58 or base64")
+}
+
+// ============================================================================
+// ED25519 (SOLANA)
+// ============================================================================
+
+/// Verify a Phantom/Solflare `signMessage` signature; the public key is the base58 wallet address
+pub fn verify_ed25519(public_key_b58: &str, message: &[u8], signature: &[u8]) -> Result<bool> {
+    let key_bytes: [u8; 32] = bs58::decode(public_key_b58.trim()).into_vec()
+        .context("Public key is not valid base58")?
+        .try_into()
+        .map_err(|_| anyhow!("Public key is not 32 bytes"))?;
+    let signature_bytes: [u8; 64] = signature.try_into()
+        .map_err(|_| anyhow!("Ed25519 signature is not 64 bytes"))?;
+    let key = Ed25519VerifyingKey::from_bytes(&key_bytes).map_err(|e| anyhow!("Invalid ed25519 key: {}", e))?;
+    Ok(key.verify(message, &Ed25519Signature::from_bytes(&signature_bytes)).is_ok())
+}
+
+// ============================================================================
+// ECDSA RECOVERY (EVM)
+// ============================================================================
+
+/// Signer address from a 65-byte r | s | v signature over a 32-byte digest; high-s signatures are rejected
+pub fn recover_address(hash: &[u8; 32], signature: &[u8]) -> Result<Address> {
+    if signature.len() != 65 {
+        return Err(anyhow!("ECDSA signature must be 65 bytes, got {}", signature.len()));
+    }
+    let v = signature[64];
+    let recovery = RecoveryId::from_byte(if v >= 27 { v - 27 } else { v })
+        .ok_or_else(|| anyhow!("Invalid recovery id {}", v))?;
+    let sig = EcdsaSignature::from_slice(&signature[..64]).map_err(|e| anyhow!("Invalid signature: {}", e))?;
+    if sig.normalize_s().is_some() {
+        return Err(anyhow!("Malleable high-s signature"));
+    }
+    let key = EcdsaVerifyingKey::recover_from_prehash(hash, &sig, recovery)
+        .map_err(|e| anyhow!("Signature recovery failed: {}", e))?;
+    Ok(evm_tx::address_from_public_key(&key))
+}
+
+/// EIP-191 `personal_sign`
+pub fn recover_personal_sign(message: &[u8], signature: &[u8]) -> Result<Address> {
+    recover_address(&evm_tx::hash_personal_message(message), signature)
+}
+
+/// EIP-712 `eth_signTypedData_v4`
+pub fn recover_typed_data(typed_data: &serde_json::Value, signature: &[u8]) -> Result<Address> {
+    recover_address(&eip712_hash(typed_data)?, signature)
+}
+
+// ============================================================================
+// EIP-712 TYPED DATA
+// ============================================================================
+
+/// keccak256(0x1901 | domainSeparator | hashStruct(message)) for a v4 typed-data document
+pub fn eip712_hash(typed_data: &serde_json::Value) -> Result<[u8; 32]> {
+    let types = typed_data["types"].as_object().ok_or_else(|| anyhow!("Typed data is missing types"))?;
+    let primary = typed_data["primaryType"].as_str().ok_or_else(|| anyhow!("Typed data is missing primaryType"))?;
+    if !types.contains_key("EIP712Domain") {
+        return Err(anyhow!("Typed data is missing the EIP712Domain type"));
+    }
+
+    let mut data = vec![0x19, 0x01];
+    data.extend(hash_struct("EIP712Domain", &typed_data["domain"], types)?);
+    data.extend(hash_struct(primary, &typed_data["message"], types)?);
+    Ok(evm_tx::keccak256(&data))
+}
+
+type TypeMap = serde_json::Map<String, serde_json::Value>;
+
+fn base_type(ty: &str) -> &str {
+    ty.find('[').map(|i| &ty[..i]).unwrap_or(ty)
+}
+
+fn type_fields<'a>(name: &str, types: &'a TypeMap) -> Result<&'a Vec<serde_json::Value>> {
+    types.get(name).and_then(|t| t.as_array()).ok_or_else(|| anyhow!("Unknown EIP-712 type {}", name))
+}
+
+fn collect_dependencies(name: &str, types: &TypeMap, found: &mut BTreeSet<String>) -> Result<()> {
+    if found.contains(name) || !types.contains_key(name) {
+        return Ok(());
+    }
+    found.insert(name.to_string());
+    for field in type_fields(name, types)? {
+        let ty = field["type"].as_str().unwrap_or_default();
+        collect_dependencies(base_type(ty), types, found)?;
+    }
+    Ok(())
+}
+
+/// `Primary(fields)` followed by referenced struct types in alphabetical order
+fn encode_type(primary: &str, types: &TypeMap) -> Result<String> {
+    let mut deps = BTreeSet::new();
+    collect_dependencies(primary, types, &mut deps)?;
+    deps.remove(primary);
+
+    let mut encoded = String::new();
+    for name in std::iter::once(primary.to_string()).chain(deps) {
+        let fields: Vec<String> = type_fields(&name, types)?.iter()
+            .map(|f| format!("{} {}", f["type"].as_str().unwrap_or_default(), f["name"].as_str().unwrap_or_default()))
+            .collect();
+        encoded.push_str(&format!("{}({})", name, fields.join(",")));
+    }
+    Ok(encoded)
+}
+
+fn hash_struct(name: &str, value: &serde_json::Value, types: &TypeMap) -> Result<[u8; 32]> {
+    let mut data = evm_tx::keccak256(encode_type(name, types)?.as_bytes()).to_vec();
+    for field in type_fields(name, types)? {
+        let field_name = field["name"].as_str().unwrap_or_default();
+        let ty = field["type"].as_str().unwrap_or_default();
+        data.extend(encode_field(ty, &value[field_name], types)?);
+    }
+    Ok(evm_tx::keccak256(&data))
+}
+
+fn encode_field(ty: &str, value: &serde_json::Value, types: &TypeMap) -> Result<[u8; 32]> {
+    if ty.ends_with(']') {
+        let element = &ty[..ty.rfind('[').expect("array type has a bracket")];
+        let items = value.as_array().ok_or_else(|| anyhow!("Expected array for {}", ty))?;
+        let mut data = Vec::with_capacity(items.len() * 32);
+        for item in items {
+            data.extend(encode_field(element, item, types)?);
+        }
+        return Ok(evm_tx::keccak256(&data));
+    }
+    if types.contains_key(ty) {
+        return hash_struct(ty, value, types);
+    }
+
+    let mut word = [0u8; 32];
+    match ty {
+        "string" => return Ok(evm_tx::keccak256(value.as_str().unwrap_or_default().as_bytes())),
+        "bytes" => return Ok(evm_tx::keccak256(&hex_value(value)?)),
+        "bool" => word[31] = value.as_bool().ok_or_else(|| anyhow!("Expected bool"))? as u8,
+        "address" => {
+            let address = evm_tx::parse_address(value.as_str().ok_or_else(|| anyhow!("Expected address string"))?)?;
+            word[12..].copy_from_slice(&address);
+        }
+        _ if ty.starts_with("bytes") => {
+            let bytes = hex_value(value)?;
+            if bytes.len() > 32 {
+                return Err(anyhow!("{} value longer than 32 bytes", ty));
+            }
+            word[..bytes.len()].copy_from_slice(&bytes);
+        }
+        _ if ty.starts_with("uint") || ty.starts_with("int") => word = integer_word(value, ty.starts_with("int"))?,
+        _ => return Err(anyhow!("Unsupported EIP-712 type {}", ty)),
+    }
+    Ok(word)
+}
+
+fn hex_value(value: &serde_json::Value) -> Result<Vec<u8>> {
+    let text = value.as_str().ok_or_else(|| anyhow!("Expected hex string"))?;
+    hex::decode(text.trim_start_matches("0x")).context("Invalid hex value")
+}
+
+/// Numbers arrive as JSON numbers, decimal strings or 0x-hex strings; signed values are two's complement
+fn integer_word(value: &serde_json::Value, signed: bool) -> Result<[u8; 32]> {
+    let mut word = [0u8; 32];
+    if let Some(text) = value.as_str().and_then(|t| t.strip_prefix("0x")) {
+        let bytes = hex::decode(format!("{:0>64}", text)).context("Invalid hex integer")?;
+        if bytes.len() > 32 {
+            return Err(anyhow!("Integer wider than 256 bits"));
+        }
+        word.copy_from_slice(&bytes);
+        return Ok(word);
+    }
+
+    let text = match value {
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.clone(),
+        _ => return Err(anyhow!("Expected integer, got {}", value)),
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+    let magnitude = decimal_word(digits).with_context(|| format!("Invalid integer {}", text))?;
+    if !negative {
+        if signed && magnitude[0] & 0x80 != 0 {
+            return Err(anyhow!("Integer {} overflows int256", text));
+        }
+        return Ok(magnitude);
+    }
+    if !signed {
+        return Err(anyhow!("Negative value {} for an unsigned integer", text));
+    }
+
+    // -x = !x + 1; anything below -2^255 wraps back to a positive word
+    word = magnitude.map(|b| !b);
+    for byte in word.iter_mut().rev() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            break;
+        }
+    }
+    if word[0] & 0x80 == 0 && magnitude != [0u8; 32] {
+        return Err(anyhow!("Integer {} overflows int256", text));
+    }
+    Ok(word)
+}
+
+/// Unsigned decimal digits as a 256-bit big-endian word
+fn decimal_word(digits: &str) -> Result<[u8; 32]> {
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(anyhow!("Not a decimal integer"));
+    }
+    let mut word = [0u8; 32];
+    for digit in digits.bytes() {
+        let mut carry = (digit - b'0') as u32;
+        for byte in word.iter_mut().rev() {
+            let value = *byte as u32 * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+        if carry != 0 {
+            return Err(anyhow!("Integer wider than 256 bits"));
+        }
+    }
+    Ok(word)
+}
+
+// ============================================================================
+// SIGN-IN MESSAGES (EIP-4361 / SIWS)
+// ============================================================================
+
+/// Sign-In-With-Ethereum message; Solana wallets use the same layout with "Solana account"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignInMessage {
+    pub chain: ChainFamily,
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: String, // Numeric for EVM, cluster name ("mainnet") for Solana
+    pub nonce: String,
+    pub issued_at: String, // RFC 3339
+    pub expiration_time: Option<String>,
+    pub not_before: Option<String>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SignInMessage {
+    pub fn parse(message: &str) -> Result<Self> {
+        let mut lines = message.lines();
+        let header = lines.next().ok_or_else(|| anyhow!("Empty sign-in message"))?;
+        let (domain, chain) = if let Some(domain) = header.strip_suffix(" wants you to sign in with your Ethereum account:") {
+            (domain, ChainFamily::Ethereum)
+        } else if let Some(domain) = header.strip_suffix(" wants you to sign in with your Solana account:") {
+            (domain, ChainFamily::Solana)
+        } else {
+            return Err(anyhow!("Not a sign-in message"));
+        };
+        let address = lines.next().ok_or_else(|| anyhow!("Sign-in message is missing the address"))?.trim();
+
+        let mut parsed = SignInMessage {
+            chain,
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement: None,
+            uri: String::new(),
+            version: String::new(),
+            chain_id: String::new(),
+            nonce: String::new(),
+            issued_at: String::new(),
+            expiration_time: None,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        };
+
+        let mut in_fields = false;
+        let mut in_resources = false;
+        for line in lines {
+            if line.is_empty() {
+                continue;
+            }
+            if in_resources {
+                if let Some(resource) = line.strip_prefix("- ") {
+                    parsed.resources.push(resource.to_string());
+                    continue;
+                }
+            }
+            let field = line.split_once(": ").map(|(k, v)| (k, v.to_string()));
+            match field {
+                Some(("URI", v)) => parsed.uri = v,
+                Some(("Version", v)) => parsed.version = v,
+                Some(("Chain ID", v)) => parsed.chain_id = v,
+                Some(("Nonce", v)) => parsed.nonce = v,
+                Some(("Issued At", v)) => parsed.issued_at = v,
+                Some(("Expiration Time", v)) => parsed.expiration_time = Some(v),
+                Some(("Not Before", v)) => parsed.not_before = Some(v),
+                Some(("Request ID", v)) => parsed.request_id = Some(v),
+                _ if line == "Resources:" => {
+                    in_resources = true;
+                    continue;
+                }
+                _ if !in_fields && parsed.statement.is_none() => {
+                    parsed.statement = Some(line.to_string());
+                    continue;
+                }
+                _ => return Err(anyhow!("Unexpected sign-in message line: {}", line)),
+            }
+            in_fields = true;
+        }
+
+        if parsed.uri.is_empty() || parsed.nonce.is_empty() || parsed.issued_at.is_empty() {
+            return Err(anyhow!("Sign-in message is missing URI, Nonce or Issued At"));
+        }
+        if parsed.version != "1" {
+            return Err(anyhow!("Unsupported sign-in message version {}", parsed.version));
+        }
+        Ok(parsed)
+    }
+
+    /// Canonical text the wallet signs
+    pub fn to_message(&self) -> String {
+        let mut message = format!(
+            "{} wants you to sign in with your {} account:\n{}\n\n",
+            self.domain, self.chain.label(), self.address
+        );
+        if let Some(statement) = &self.statement {
+            message.push_str(&format!("{}\n", statement));
+        }
+        message.push_str(&format!(
+            "\nURI: {}\nVersion: {}\nChain ID: {}\nNonce: {}\nIssued At: {}",
+            self.uri, self.version, self.chain_id, self.nonce, self.issued_at
+        ));
+        if let Some(expiration) = &self.expiration_time {
+            message.push_str(&format!("\nExpiration Time: {}", expiration));
+        }
+        if let Some(not_before) = &self.not_before {
+            message.push_str(&format!("\nNot Before: {}", not_before));
+        }
+        if let Some(request_id) = &self.request_id {
+            message.push_str(&format!("\nRequest ID: {}", request_id));
+        }
+        if !self.resources.is_empty() {
+            message.push_str("\nResources:");
+            for resource in &self.resources {
+                message.push_str(&format!("\n- {}", resource));
+            }
+        }
+        message
+    }
+
+    /// Domain binding and validity window; `max_skew_secs` tolerates client clocks running ahead
+    pub fn validate(&self, expected_domain: &str, now: DateTime<Utc>, max_skew_secs: i64) -> Result<()> {
+        if self.domain != expected_domain {
+            return Err(anyhow!("Message is for domain {}, expected {}", self.domain, expected_domain));
+        }
+        let skew = chrono::Duration::seconds(max_skew_secs);
+        if parse_time(&self.issued_at)? > now + skew {
+            return Err(anyhow!("Message issued in the future"));
+        }
+        if let Some(expiration) = &self.expiration_time {
+            if parse_time(expiration)? <= now {
+                return Err(anyhow!("Message expired at {}", expiration));
+            }
+        }
+        if let Some(not_before) = &self.not_before {
+            if parse_time(not_before)? > now + skew {
+                return Err(anyhow!("Message not valid before {}", not_before));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expiration_time.as_deref()
+            .and_then(|t| parse_time(t).ok())
+            .map(|t| t.timestamp() as u64)
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("Invalid timestamp {}", value))
+}
+
+/// Check a signature over `message` against the wallet address of either chain family
+pub fn verify_wallet_signature(address: &str, message: &[u8], signature: &str) -> Result<bool> {
+    let signature = decode_signature(signature)?;
+    match ChainFamily::of_address(address) {
+        ChainFamily::Ethereum => {
+            let expected = evm_tx::parse_address(address)?;
+            Ok(recover_personal_sign(message, &signature)? == expected)
+        }
+        ChainFamily::Solana => verify_ed25519(address, message, &signature),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ether_mail() -> serde_json::Value {
+        serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"},
+                ],
+                "Person": [
+                    {"name": "name", "type": "string"},
+                    {"name": "wallet", "type": "address"},
+                ],
+                "Mail": [
+                    {"name": "from", "type": "Person"},
+                    {"name": "to", "type": "Person"},
+                    {"name": "contents", "type": "string"},
+                ],
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC",
+            },
+            "message": {
+                "from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+                "to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+                "contents": "Hello, Bob!",
+            },
+        })
+    }
+
+    fn permit(value: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"},
+                ],
+                "Permit": [
+                    {"name": "owner", "type": "address"},
+                    {"name": "spender", "type": "address"},
+                    {"name": "value", "type": "uint256"},
+                    {"name": "nonce", "type": "uint256"},
+                    {"name": "deadline", "type": "uint256"},
+                ],
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": "USD Coin",
+                "version": "2",
+                "chainId": 1,
+                "verifyingContract": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            },
+            "message": {
+                "owner": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                "spender": "0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45",
+                "value": value,
+                "nonce": 0,
+                "deadline": "1700000000",
+            },
+        })
+    }
+
+    const MAX_UINT256: &str = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+    const MIN_INT256: &str = "-57896044618658097711785492504343953926634992332820282019728792003956564819968";
+
+    #[test]
+    fn ed25519_matches_rfc8032() {
+        // RFC 8032 section 7.1, tests 1 and 2
+        let rfc8032 = [
+            ("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a", "",
+             "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"),
+            ("3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c", "72",
+             "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"),
+        ];
+        for (public_key, message, signature) in rfc8032 {
+            let address = bs58::encode(hex::decode(public_key).unwrap()).into_string();
+            let message = hex::decode(message).unwrap();
+            let signature = hex::decode(signature).unwrap();
+            assert!(verify_ed25519(&address, &message, &signature).unwrap());
+
+            let mut tampered = message.clone();
+            tampered.push(0);
+            assert!(!verify_ed25519(&address, &tampered, &signature).unwrap());
+        }
+    }
+
+    #[test]
+    fn personal_sign_digest_and_round_trip() {
+        // ethers.js hashMessage("Hello World")
+        assert_eq!(
+            hex::encode(evm_tx::hash_personal_message(b"Hello World")),
+            "a1de988600a42c4b4ab089b619297c17d53cffae5d5120d82d8a92d0bb3b78f2",
+        );
+
+        let cow = evm_tx::EvmSigner::from_hex(&hex::encode(evm_tx::keccak256(b"cow"))).unwrap();
+        let signature = cow.sign_message(b"Hello World").unwrap();
+        assert_eq!(recover_personal_sign(b"Hello World", &signature).unwrap(), cow.address());
+        assert_ne!(recover_personal_sign(b"Hello World!", &signature).unwrap(), cow.address());
+    }
+
+    #[test]
+    fn eip712_matches_the_ether_mail_example() {
+        let mail = ether_mail();
+        assert_eq!(
+            hex::encode(eip712_hash(&mail).unwrap()),
+            "be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2",
+        );
+
+        // Signed with keccak256("cow")
+        let signature = hex::decode(concat!(
+            "4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d",
+            "07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562",
+            "1c",
+        )).unwrap();
+        assert_eq!(
+            recover_typed_data(&mail, &signature).unwrap(),
+            evm_tx::parse_address("0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826").unwrap(),
+        );
+    }
+
+    #[test]
+    fn integers_fill_the_whole_256_bit_word() {
+        assert_eq!(integer_word(&serde_json::json!(MAX_UINT256), false).unwrap(), [0xff; 32]);
+        assert_eq!(integer_word(&serde_json::json!(format!("0x{}", "f".repeat(64))), false).unwrap(), [0xff; 32]);
+        assert!(integer_word(&serde_json::json!("115792089237316195423570985008687907853269984665640564039457584007913129639936"), false).is_err());
+
+        let mut one_e30 = [0u8; 32];
+        one_e30[19..].copy_from_slice(&hex::decode("0c9f2c9cd04674edea40000000").unwrap());
+        assert_eq!(integer_word(&serde_json::json!("1000000000000000000000000000000"), false).unwrap(), one_e30);
+        let mut small = [0u8; 32];
+        small[31] = 42;
+        assert_eq!(integer_word(&serde_json::json!(42), false).unwrap(), small);
+
+        assert_eq!(integer_word(&serde_json::json!(-1), true).unwrap(), [0xff; 32]);
+        let mut min = [0u8; 32];
+        min[0] = 0x80;
+        assert_eq!(integer_word(&serde_json::json!(MIN_INT256), true).unwrap(), min);
+        assert!(integer_word(&serde_json::json!("-57896044618658097711785492504343953926634992332820282019728792003956564819969"), true).is_err());
+        assert!(integer_word(&serde_json::json!(MAX_UINT256), true).is_err());
+        assert!(integer_word(&serde_json::json!("-1"), false).is_err());
+        assert!(integer_word(&serde_json::json!("1.5"), false).is_err());
+        assert!(integer_word(&serde_json::json!(""), false).is_err());
+    }
+
+    #[test]
+    fn unlimited_permit_hashes_the_same_in_decimal_and_hex() {
+        let decimal = permit(serde_json::json!(MAX_UINT256));
+        let hex_value = permit(serde_json::json!(format!("0x{}", "f".repeat(64))));
+        assert_eq!(eip712_hash(&decimal).unwrap(), eip712_hash(&hex_value).unwrap());
+        assert_ne!(eip712_hash(&decimal).unwrap(), eip712_hash(&permit(serde_json::json!("1"))).unwrap());
+
+        let cow = evm_tx::EvmSigner::from_hex(&hex::encode(evm_tx::keccak256(b"cow"))).unwrap();
+        let (recovery_id, r, s) = cow.sign_hash(&eip712_hash(&decimal).unwrap()).unwrap();
+        let signature = [r.as_slice(), s.as_slice(), &[27 + recovery_id]].concat();
+        assert_eq!(recover_typed_data(&hex_value, &signature).unwrap(), cow.address());
+    }
+
+    #[test]
+    fn sign_in_message_round_trips() {
+        let siwe = "example.com wants you to sign in with your Ethereum account:\n\
+                    0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826\n\n\
+                    Sign in to Dexter\n\n\
+                    URI: https://example.com/login\nVersion: 1\nChain ID: 1\nNonce: 32891756\n\
+                    Issued At: 2021-09-30T16:25:24Z\nExpiration Time: 2021-10-01T16:25:24Z";
+        let parsed = SignInMessage::parse(siwe).unwrap();
+        assert_eq!(parsed.to_message(), siwe);
+        assert_eq!(parsed.nonce, "32891756");
+        assert_eq!(parsed.chain, ChainFamily::Ethereum);
+        assert_eq!(parsed.expires_at(), Some(1633105524));
+    }
+}
@@ -1,7 +1,16 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tokio::sync::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tokio::sync::{broadcast, RwLock};
 use std::sync::Arc;
+use log::{debug, info, warn};
+use solana_sdk::{instruction::Instruction, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Keypair};
+
+use crate::balances::{self, BalanceProvider, UsdPriceSource};
+use crate::keystore::Keystore;
+use crate::solana_tx::{self, Outflow, SolanaTxSender};
+use crate::tx_policy::{PolicyDecision, SimulationCheck, TransactionIntent, TxPolicyEngine};
+use crate::wallet_auth::{self, ChainFamily, SignInMessage};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Wallet {
@@ -25,11 +34,42 @@ pub enum WalletType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenBalance {
     pub symbol: String,
-    pub address: String,
+    pub address: String, // Mint / contract, or "native"
     pub balance: f64,
     pub decimals: u8,
     pub price_usd: f64,
     pub value_usd: f64,
+    #[serde(default)]
+    pub chain: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceRefreshConfig {
+    pub refresh_interval_secs: u64,
+    pub price_ttl_secs: u64,
+    pub min_change: f64, // Smaller moves are not reported as change events
+}
+
+impl Default for BalanceRefreshConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_secs: 30,
+            price_ttl_secs: 60,
+            min_change: 1e-9,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceChangeEvent {
+    pub wallet: String,
+    pub chain: String,
+    pub token: String, // Key in `Wallet.tokens`
+    pub symbol: String,
+    pub previous: f64,
+    pub current: f64,
+    pub value_usd: f64,
+    pub timestamp: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,21 +103,79 @@ pub enum TransactionStatus {
     Confirmed,
     Failed,
     Cancelled,
+    AwaitingApproval, // Held by the policy engine until an admin decides
+    Blocked,          // Denied by the policy engine; never signed
 }
 
+type PriceCache = Option<(u64, HashMap<String, f64>)>; // (fetched at, symbol -> USD)
+
 pub struct WalletManager {
     wallets: Arc<RwLock<HashMap<String, Wallet>>>,
     transactions: Arc<RwLock<Vec<Transaction>>>,
+    balance_providers: Arc<RwLock<Vec<Arc<dyn BalanceProvider>>>>,
+    price_source: Arc<RwLock<Option<Arc<dyn UsdPriceSource>>>>,
+    price_cache: Arc<RwLock<PriceCache>>,
+    balance_config: Arc<RwLock<BalanceRefreshConfig>>,
+    balance_events: broadcast::Sender<BalanceChangeEvent>,
+    policy_engine: Arc<TxPolicyEngine>,
+    solana_sender: Option<SolanaTxSender>,
+    keystore: Option<Arc<Keystore>>,
 }
 
 impl WalletManager {
     pub fn new() -> Self {
+        let (balance_tx, _) = broadcast::channel(1000);
         Self {
             wallets: Arc::new(RwLock::new(HashMap::new())),
             transactions: Arc::new(RwLock::new(Vec::new())),
+            balance_providers: Arc::new(RwLock::new(Vec::new())),
+            price_source: Arc::new(RwLock::new(None)),
+            price_cache: Arc::new(RwLock::new(None)),
+            balance_config: Arc::new(RwLock::new(BalanceRefreshConfig::default())),
+            balance_events: balance_tx,
+            policy_engine: Arc::new(TxPolicyEngine::default()),
+            solana_sender: None,
+            keystore: None,
         }
     }
 
+    pub fn with_policy_engine(mut self, engine: Arc<TxPolicyEngine>) -> Self {
+        self.policy_engine = engine;
+        self
+    }
+
+    /// Solana transactions are signed with keystore accounts and submitted through `sender`
+    pub fn with_solana_signer(mut self, sender: SolanaTxSender, keystore: Arc<Keystore>) -> Self {
+        self.solana_sender = Some(sender);
+        self.keystore = Some(keystore);
+        self
+    }
+
+    pub fn policy_engine(&self) -> Arc<TxPolicyEngine> {
+        self.policy_engine.clone()
+    }
+
+    pub async fn add_balance_provider(&self, provider: Arc<dyn BalanceProvider>) {
+        self.balance_providers.write().await.push(provider);
+    }
+
+    pub async fn set_price_source(&self, source: Arc<dyn UsdPriceSource>) {
+        *self.price_source.write().await = Some(source);
+        *self.price_cache.write().await = None;
+    }
+
+    pub async fn get_balance_config(&self) -> BalanceRefreshConfig {
+        self.balance_config.read().await.clone()
+    }
+
+    pub async fn set_balance_config(&self, config: BalanceRefreshConfig) {
+        *self.balance_config.write().await = config;
+    }
+
+    pub fn subscribe_balance_changes(&self) -> broadcast::Receiver<BalanceChangeEvent> {
+        self.balance_events.subscribe()
+    }
+
     pub async fn connect_wallet(&self, wallet_type: WalletType, address: String) -> Result<Wallet, String> {
         let mut wallets = self.wallets.write().await;
         
@@ -97,11 +195,14 @@ impl WalletManager {
         };
 
         wallets.insert(address.clone(), wallet.clone());
+        drop(wallets);
         
-        // Fetch balances asynchronously
-        self.update_wallet_balances(&address).await?;
+        // A failed first fetch is retried by the refresh loop; it must not block the connection
+        if let Err(e) = self.update_wallet_balances(&address).await {
+            warn!("⚠️ Initial balance fetch for {} failed: {}", address, e);
+        }
         
-        Ok(wallet)
+        Ok(self.get_wallet(&address).await.unwrap_or(wallet))
     }
 
     pub async fn disconnect_wallet(&self, address: &str) -> Result<(), String> {
@@ -120,36 +221,183 @@ impl WalletManager {
         wallets.get(address).cloned()
     }
 
+    /// Fetch native and token balances from every provider for the wallet's chain family.
+    /// Tokens are keyed by symbol; when several chains serve the wallet the key is `SYMBOL@chain`.
     pub async fn update_wallet_balances(&self, address: &str) -> Result<(), String> {
-        let mut wallets = self.wallets.write().await;
-        
-        if let Some(wallet) = wallets.get_mut(address) {
-            // Simulate balance updates - will be replaced with actual RPC calls
-            wallet.balance = 10.5; // SOL balance
-            
-            // Update token balances
-            wallet.tokens.insert("USDC".to_string(), TokenBalance {
-                symbol: "USDC".to_string(),
-                address: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
-                balance: 1000.0,
-                decimals: 6,
-                price_usd: 1.0,
-                value_usd: 1000.0,
-            });
-            
-            wallet.tokens.insert("USDT".to_string(), TokenBalance {
-                symbol: "USDT".to_string(),
-                address: "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB".to_string(),
-                balance: 500.0,
-                decimals: 6,
-                price_usd: 1.0,
-                value_usd: 500.0,
-            });
-            
-            wallet.last_activity = chrono::Utc::now().timestamp() as u64;
-            Ok(())
-        } else {
-            Err("Wallet not found".to_string())
+        if !self.wallets.read().await.contains_key(address) {
+            return Err("Wallet not found".to_string());
+        }
+
+        let family = ChainFamily::of_address(address);
+        let providers: Vec<Arc<dyn BalanceProvider>> = self.balance_providers.read().await
+            .iter()
+            .filter(|p| p.family() == family)
+            .cloned()
+            .collect();
+        if providers.is_empty() {
+            return Err(format!("No balance provider configured for {:?} wallets", family));
+        }
+
+        let mut fetched = Vec::new();
+        let mut failed_chains = HashSet::new();
+        for provider in &providers {
+            match provider.fetch(address).await {
+                Ok(balances) => fetched.push(balances),
+                Err(e) => {
+                    warn!("⚠️ {} balance fetch failed for {}: {}", provider.chain(), address, e);
+                    failed_chains.insert(provider.chain().to_string());
+                }
+            }
+        }
+        if fetched.is_empty() {
+            return Err(format!("All balance providers failed for {}", address));
+        }
+
+        let prices = self.usd_prices().await;
+        let multi_chain = providers.len() > 1;
+        let key = |symbol: &str, chain: &str| {
+            if multi_chain { format!("{}@{}", symbol, chain) } else { symbol.to_string() }
+        };
+        let priced = |symbol: &str, address: &str, amount: f64, decimals: u8, chain: &str| {
+            let price_usd = balances::usd_price(&prices, symbol).unwrap_or(0.0);
+            TokenBalance {
+                symbol: symbol.to_string(),
+                address: address.to_string(),
+                balance: amount,
+                decimals,
+                price_usd,
+                value_usd: amount * price_usd,
+                chain: chain.to_string(),
+            }
+        };
+
+        let mut tokens = HashMap::new();
+        for chain in &fetched {
+            tokens.insert(
+                key(&chain.native_symbol, &chain.chain),
+                priced(&chain.native_symbol, "native", chain.native, chain.native_decimals, &chain.chain),
+            );
+            for token in &chain.tokens {
+                tokens.insert(
+                    key(&token.symbol, &chain.chain),
+                    priced(&token.symbol, &token.address, token.amount, token.decimals, &chain.chain),
+                );
+            }
+        }
+
+        let min_change = self.balance_config.read().await.min_change;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut events = Vec::new();
+        {
+            let mut wallets = self.wallets.write().await;
+            let wallet = wallets.get_mut(address).ok_or_else(|| "Wallet not found".to_string())?;
+
+            // Keep the last known balances of chains that failed this round
+            for (token_key, balance) in &wallet.tokens {
+                if failed_chains.contains(&balance.chain) {
+                    tokens.entry(token_key.clone()).or_insert_with(|| balance.clone());
+                }
+            }
+
+            for (token_key, balance) in &tokens {
+                let previous = wallet.tokens.get(token_key).map(|t| t.balance).unwrap_or(0.0);
+                if (balance.balance - previous).abs() > min_change {
+                    events.push(BalanceChangeEvent {
+                        wallet: address.to_string(),
+                        chain: balance.chain.clone(),
+                        token: token_key.clone(),
+                        symbol: balance.symbol.clone(),
+                        previous,
+                        current: balance.balance,
+                        value_usd: balance.value_usd,
+                        timestamp: now,
+                    });
+                }
+            }
+            for (token_key, balance) in &wallet.tokens {
+                if !tokens.contains_key(token_key) && balance.balance.abs() > min_change {
+                    events.push(BalanceChangeEvent {
+                        wallet: address.to_string(),
+                        chain: balance.chain.clone(),
+                        token: token_key.clone(),
+                        symbol: balance.symbol.clone(),
+                        previous: balance.balance,
+                        current: 0.0,
+                        value_usd: 0.0,
+                        timestamp: now,
+                    });
+                }
+            }
+
+            wallet.balance = fetched[0].native;
+            wallet.tokens = tokens;
+            wallet.last_activity = now;
+        }
+
+        for event in events {
+            debug!("💼 {} {} balance {} -> {}", event.wallet, event.token, event.previous, event.current);
+            let _ = self.balance_events.send(event);
+        }
+        Ok(())
+    }
+
+    /// Refresh every connected wallet; returns how many refreshed cleanly
+    pub async fn refresh_all_balances(&self) -> usize {
+        let addresses: Vec<String> = self.wallets.read().await
+            .values()
+            .filter(|w| w.connected)
+            .map(|w| w.address.clone())
+            .collect();
+
+        let mut refreshed = 0;
+        for address in addresses {
+            match self.update_wallet_balances(&address).await {
+                Ok(()) => refreshed += 1,
+                Err(e) => debug!("Balance refresh for {} failed: {}", address, e),
+            }
+        }
+        refreshed
+    }
+
+    pub async fn start_balance_refresh(self: Arc<Self>) {
+        info!("💼 Starting wallet balance refresh loop");
+        loop {
+            let interval = self.balance_config.read().await.refresh_interval_secs.max(1);
+            tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+            if self.balance_providers.read().await.is_empty() {
+                continue;
+            }
+            self.refresh_all_balances().await;
+        }
+    }
+
+    /// USD value of `amount` of `token` at the shared price cache
+    pub async fn usd_value(&self, token: &str, amount: f64) -> Option<f64> {
+        balances::usd_price(&self.usd_prices().await, token).map(|price| price * amount)
+    }
+
+    /// Prices are shared across wallets and refetched at most once per `price_ttl_secs`
+    async fn usd_prices(&self) -> HashMap<String, f64> {
+        let ttl = self.balance_config.read().await.price_ttl_secs;
+        let now = chrono::Utc::now().timestamp() as u64;
+        if let Some((fetched_at, prices)) = self.price_cache.read().await.as_ref() {
+            if now.saturating_sub(*fetched_at) < ttl {
+                return prices.clone();
+            }
+        }
+
+        let Some(source) = self.price_source.read().await.clone() else {
+            return HashMap::new();
+        };
+        match source.usd_prices().await {
+            Ok(prices) => {
+                *self.price_cache.write().await = Some((now, prices.clone()));
+                prices
+            }
+            Err(e) => {
+                warn!("⚠️ Price fetch for wallet valuation failed: {}", e);
+                self.price_cache.read().await.as_ref().map(|(_, p)| p.clone()).unwrap_or_default()
+            }
         }
     }
 
@@ -197,6 +445,99 @@ impl WalletManager {
         }
     }
 
+    pub async fn get_transaction(&self, tx_id: &str) -> Option<Transaction> {
+        self.transactions.read().await.iter().find(|t| t.id == tx_id).cloned()
+    }
+
+    /// Runs the wallet's transaction policy for a transaction built and signed elsewhere (a DEX connector).
+    /// Nothing is decoded or simulated here, so wallets with a program or counterparty allowlist or required
+    /// simulation are denied.
+    pub async fn authorize_transaction(&self, transaction: &Transaction) -> Result<(), String> {
+        let value_usd = self.transaction_value_usd(transaction).await;
+        let intent = Self::transaction_intent(transaction, value_usd, DecodedTransaction::default(), None);
+        self.authorize_intent(transaction, intent).await
+    }
+
+    /// Programs, counterparties and value all come from the instructions that will be signed, so nothing in
+    /// `details` can widen what the policy allows; an instruction that does not decode is denied
+    async fn authorize_checked(
+        &self,
+        transaction: &Transaction,
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        simulation: SimulationCheck,
+    ) -> Result<(), String> {
+        let decoded = decode_transaction(instructions, payer);
+        let value_usd = decoded_value_usd(&self.usd_prices().await, &decoded);
+        let intent = Self::transaction_intent(transaction, value_usd, decoded, Some(simulation));
+        self.authorize_intent(transaction, intent).await
+    }
+
+    /// Only the `client_order_id` approval key is read from `details`
+    fn transaction_intent(
+        transaction: &Transaction,
+        value_usd: Option<f64>,
+        decoded: DecodedTransaction,
+        simulation: Option<SimulationCheck>,
+    ) -> TransactionIntent {
+        TransactionIntent {
+            tx_id: transaction.id.clone(),
+            client_id: transaction.details.get("client_order_id").and_then(|v| v.as_str()).unwrap_or(&transaction.id).to_string(),
+            wallet: transaction.wallet.clone(),
+            token: transaction.token.clone(),
+            amount: transaction.amount,
+            value_usd,
+            programs: decoded.programs,
+            counterparties: decoded.counterparties,
+            undecoded_programs: decoded.undecoded_programs,
+            simulation,
+        }
+    }
+
+    async fn authorize_intent(&self, transaction: &Transaction, intent: TransactionIntent) -> Result<(), String> {
+
+        match self.policy_engine.evaluate(intent).await {
+            PolicyDecision::Allow => {
+                self.update_transaction_status(&transaction.id, TransactionStatus::Pending, None).await?;
+                Ok(())
+            }
+            PolicyDecision::PendingApproval { approval_id } => {
+                self.update_transaction_status(&transaction.id, TransactionStatus::AwaitingApproval, None).await?;
+                Err(format!("Transaction awaiting manual approval ({})", approval_id))
+            }
+            PolicyDecision::Deny { violations } => {
+                self.update_transaction_status(&transaction.id, TransactionStatus::Blocked, None).await?;
+                let reasons: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                Err(format!("Transaction blocked by policy: {}", reasons.join("; ")))
+            }
+        }
+    }
+
+    /// Both legs of an undecoded swap priced from the platform feed. The input leg must price; the output leg
+    /// and a declared `value_usd` can only raise the value, never stand in for it.
+    async fn transaction_value_usd(&self, transaction: &Transaction) -> Option<f64> {
+        let prices = self.usd_prices().await;
+        let details = &transaction.details;
+        let input = balances::usd_price(&prices, &transaction.token)? * transaction.amount.abs();
+        let output = match (details.get("token_out").and_then(|v| v.as_str()), details.get("amount_out").and_then(|v| v.as_f64())) {
+            (Some(token), Some(amount)) => balances::usd_price(&prices, token).map(|price| price * amount.abs()),
+            _ => None,
+        };
+        let declared = details.get("value_usd").and_then(|v| v.as_f64()).filter(|v| v.is_finite());
+        Some([output, declared].into_iter().flatten().fold(input, f64::max))
+    }
+
+    /// Records the outcome of an authorized transaction; failures give their spend back to the daily limit
+    pub async fn settle_transaction(&self, tx_id: &str, success: bool, hash: Option<String>) -> Result<(), String> {
+        if success {
+            self.policy_engine.settle(tx_id).await;
+            self.update_transaction_status(tx_id, TransactionStatus::Confirmed, hash).await
+        } else {
+            self.policy_engine.release(tx_id).await;
+            self.update_transaction_status(tx_id, TransactionStatus::Failed, hash).await
+        }
+    }
+
     pub async fn get_wallet_transactions(&self, wallet: &str) -> Vec<Transaction> {
         let transactions = self.transactions.read().await;
         transactions
@@ -219,20 +560,137 @@ impl WalletManager {
         }
     }
 
+    /// Policy check, then sign with the wallet's keystore account and submit; returns the confirmed signature
     pub async fn sign_and_send_transaction(
         &self,
         wallet: &str,
         transaction: &Transaction,
     ) -> Result<String, String> {
-        // Placeholder for actual transaction signing and sending
-        // This will integrate with wallet SDKs
-        
-        // Simulate transaction hash
-        let hash = format!("0x{}", uuid::Uuid::new_v4().to_string().replace("-", ""));
-        
-        self.update_transaction_status(&transaction.id, TransactionStatus::Confirmed, Some(hash.clone())).await?;
-        
-        Ok(hash)
+        if transaction.wallet != wallet {
+            return Err("Transaction belongs to a different wallet".to_string());
+        }
+        let sender = self.solana_sender.as_ref().ok_or("No Solana transaction sender configured")?;
+        let payer = Pubkey::from_str(wallet).map_err(|e| format!("Invalid Solana wallet {}: {}", wallet, e))?;
+        let (instructions, lookup_tables) = solana_instructions(transaction, &payer)?;
+        let keypair = self.signing_keypair(wallet).await?;
+
+        // The policy judges the instructions that will actually be signed, simulated against the cluster
+        let simulation = match sender.simulate(instructions.clone(), &lookup_tables, &[&keypair]).await {
+            Ok(outcome) => SimulationCheck { success: outcome.err.is_none(), error: outcome.err },
+            Err(e) => SimulationCheck { success: false, error: Some(e.to_string()) },
+        };
+        self.authorize_checked(transaction, &instructions, &payer, simulation).await?;
+
+        match sender.submit(instructions, &lookup_tables, &[&keypair]).await {
+            Ok(submitted) => {
+                info!("✅ Wallet transaction {} confirmed as {} (slot {})", transaction.id, submitted.signature, submitted.slot);
+                self.settle_transaction(&transaction.id, true, Some(submitted.signature.clone())).await?;
+                Ok(submitted.signature)
+            }
+            Err(e) => {
+                warn!("❌ Wallet transaction {} failed: {}", transaction.id, e);
+                self.settle_transaction(&transaction.id, false, None).await?;
+                Err(format!("Transaction failed: {}", e))
+            }
+        }
+    }
+
+    /// Keypair of the unlocked Solana keystore account whose address is `wallet`
+    async fn signing_keypair(&self, wallet: &str) -> Result<Keypair, String> {
+        let keystore = self.keystore.as_ref().ok_or("No keystore configured for signing")?;
+        let account = keystore.unlocked_accounts().await
+            .into_iter()
+            .find(|a| a.chain == ChainFamily::Solana && a.address == wallet)
+            .ok_or_else(|| format!("No unlocked keystore account for wallet {}", wallet))?;
+        keystore.solana_keypair(Some(&account.name)).await.map_err(|e| e.to_string())
+    }
+}
+
+/// What the policy sees of a set of instructions
+#[derive(Debug, Default)]
+struct DecodedTransaction {
+    programs: Vec<String>,
+    counterparties: Vec<String>, // Recipients and written accounts other than the payer's own
+    undecoded_programs: Vec<String>,
+    outflows: Vec<Outflow>,
+}
+
+fn decode_transaction(instructions: &[Instruction], payer: &Pubkey) -> DecodedTransaction {
+    fn push_unique(items: &mut Vec<String>, item: String) {
+        if !items.contains(&item) {
+            items.push(item);
+        }
+    }
+
+    let mut decoded = DecodedTransaction::default();
+    for instruction in instructions {
+        let program = instruction.program_id.to_string();
+        push_unique(&mut decoded.programs, program.clone());
+        match solana_tx::decode_instruction(instruction, payer) {
+            Some(effect) => {
+                for recipient in effect.recipients.iter().filter(|r| *r != payer) {
+                    push_unique(&mut decoded.counterparties, recipient.to_string());
+                }
+                decoded.outflows.extend(effect.outflow);
+            }
+            None => push_unique(&mut decoded.undecoded_programs, program),
+        }
+    }
+    decoded
+}
+
+/// USD leaving the payer: lamports at the SOL price, token transfers by mint and decimals. `None` when any
+/// instruction did not decode or any outflow cannot be priced, so the policy treats the limits as exceeded.
+fn decoded_value_usd(prices: &HashMap<String, f64>, decoded: &DecodedTransaction) -> Option<f64> {
+    if !decoded.undecoded_programs.is_empty() {
+        return None;
+    }
+    let tokens = balances::default_solana_tokens();
+    decoded.outflows.iter().map(|outflow| match outflow {
+        Outflow::Lamports(lamports) => balances::usd_price(prices, "SOL").map(|price| price * *lamports as f64 / LAMPORTS_PER_SOL as f64),
+        Outflow::Token { mint: Some(mint), amount, decimals: Some(decimals) } => tokens.iter()
+            .find(|token| token.address == mint.to_string())
+            .and_then(|token| balances::usd_price(prices, &token.symbol))
+            .map(|price| price * *amount as f64 / 10f64.powi(*decimals as i32)),
+        Outflow::Token { .. } => None,
+    }).sum()
+}
+
+/// Instructions and lookup tables to sign: `details.instructions` / `details.lookup_tables` from a swap API,
+/// or a native SOL transfer of `amount` to `details.to`
+fn solana_instructions(transaction: &Transaction, payer: &Pubkey) -> Result<(Vec<Instruction>, Vec<Pubkey>), String> {
+    let details = &transaction.details;
+    let lookup_tables = details.get("lookup_tables")
+        .and_then(|v| v.as_array())
+        .map(|keys| keys.iter()
+            .map(|k| k.as_str().and_then(|k| Pubkey::from_str(k).ok()).ok_or(format!("Invalid lookup table {}", k)))
+            .collect::<Result<Vec<_>, String>>())
+        .transpose()?
+        .unwrap_or_default();
+
+    if let Some(items) = details.get("instructions").and_then(|v| v.as_array()) {
+        let instructions = items.iter()
+            .map(|item| solana_tx::instruction_from_json(item).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, String>>()?
+            .into_iter()
+            .filter(|ix| !solana_tx::is_compute_budget_instruction(ix))
+            .collect::<Vec<_>>();
+        if instructions.is_empty() {
+            return Err("Transaction has no instructions to sign".to_string());
+        }
+        return Ok((instructions, lookup_tables));
+    }
+
+    match (&transaction.tx_type, details.get("to").and_then(|v| v.as_str())) {
+        (TransactionType::Transfer, Some(to)) if transaction.token.eq_ignore_ascii_case("SOL") => {
+            let to = Pubkey::from_str(to).map_err(|e| format!("Invalid recipient {}: {}", to, e))?;
+            let lamports = (transaction.amount * LAMPORTS_PER_SOL as f64).round() as u64;
+            if lamports == 0 {
+                return Err("Transfer amount is zero".to_string());
+            }
+            Ok((vec![solana_tx::transfer_instruction(payer, &to, lamports)], lookup_tables))
+        }
+        _ => Err("Transaction has no instructions to sign".to_string()),
     }
 }
 
@@ -254,37 +712,608 @@ pub struct WalletResponse {
 }
 
 // Security module for wallet operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletSecurityConfig {
+    pub nonce_ttl_secs: u64,
+    pub max_clock_skew_secs: i64,
+    pub max_pending_nonces: usize,
+    #[serde(default = "default_nonces_per_wallet")]
+    pub max_nonces_per_wallet: usize, // Oldest is dropped when a wallet asks for more
+    #[serde(default = "default_sign_in_chain_ids")]
+    pub sign_in_chain_ids: Vec<String>, // EVM chain ids and Solana clusters a sign-in message may name
+}
+
+fn default_nonces_per_wallet() -> usize {
+    5
+}
+
+fn default_sign_in_chain_ids() -> Vec<String> {
+    ["1", "56", "137", "43114", "mainnet"].iter().map(|id| id.to_string()).collect()
+}
+
+impl Default for WalletSecurityConfig {
+    fn default() -> Self {
+        Self {
+            nonce_ttl_secs: 300,
+            max_clock_skew_secs: 60,
+            max_pending_nonces: 10_000,
+            max_nonces_per_wallet: default_nonces_per_wallet(),
+            sign_in_chain_ids: default_sign_in_chain_ids(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IssuedNonce {
+    nonce: String,
+    expires_at: u64,
+}
+
+/// Wallet proven by a verified sign-in message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignInSession {
+    pub address: String,
+    pub chain: ChainFamily,
+    pub domain: String,
+    pub chain_id: String,
+    pub issued_at: String,
+    pub expires_at: Option<u64>,
+}
+
 pub struct WalletSecurity {
-    nonce_store: Arc<RwLock<HashMap<String, String>>>,
+    nonce_store: Arc<RwLock<HashMap<String, Vec<IssuedNonce>>>>, // wallet -> outstanding nonces, oldest first
+    config: Arc<RwLock<WalletSecurityConfig>>,
 }
 
 impl WalletSecurity {
     pub fn new() -> Self {
         Self {
             nonce_store: Arc::new(RwLock::new(HashMap::new())),
+            config: Arc::new(RwLock::new(WalletSecurityConfig::default())),
         }
     }
 
-    pub async fn generate_nonce(&self, wallet: &str) -> String {
-        let nonce = uuid::Uuid::new_v4().to_string();
+    pub async fn get_config(&self) -> WalletSecurityConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn set_config(&self, config: WalletSecurityConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Alphanumeric nonce bound to the wallet, valid once and only until the TTL runs out.
+    /// A wallet holds at most `max_nonces_per_wallet`; once `max_pending_nonces` are outstanding
+    /// across all wallets, new wallets are refused rather than evicting anyone else's nonce.
+    pub async fn generate_nonce(&self, wallet: &str) -> Result<String, String> {
+        if !is_wallet_address(wallet) {
+            return Err(format!("{} is not a wallet address", wallet));
+        }
+        let config = self.config.read().await.clone();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let wallet = normalize_wallet(wallet);
+
         let mut store = self.nonce_store.write().await;
-        store.insert(wallet.to_string(), nonce.clone());
-        nonce
+        store.retain(|_, issued| {
+            issued.retain(|n| n.expires_at > now);
+            !issued.is_empty()
+        });
+        let at_wallet_cap = store.get(&wallet)
+            .map(|issued| issued.len() >= config.max_nonces_per_wallet.max(1))
+            .unwrap_or(false);
+        let pending: usize = store.values().map(Vec::len).sum();
+        if !at_wallet_cap && pending >= config.max_pending_nonces {
+            return Err("Too many pending sign-in requests".to_string());
+        }
+
+        let issued = store.entry(wallet).or_default();
+        if at_wallet_cap {
+            issued.remove(0);
+        }
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        issued.push(IssuedNonce {
+            nonce: nonce.clone(),
+            expires_at: now + config.nonce_ttl_secs,
+        });
+        Ok(nonce)
     }
 
+    /// Signature over `message` by `wallet`: EIP-191 for 0x addresses, ed25519 for base58 keys
     pub async fn verify_signature(
         &self,
         wallet: &str,
         message: &str,
         signature: &str,
     ) -> Result<bool, String> {
-        // Placeholder for signature verification
-        // Will integrate with wallet SDKs for actual verification
-        Ok(true)
+        wallet_auth::verify_wallet_signature(wallet, message.as_bytes(), signature).map_err(|e| e.to_string())
+    }
+
+    /// EIP-712 typed-data signature (eth_signTypedData_v4)
+    pub async fn verify_typed_data(
+        &self,
+        wallet: &str,
+        typed_data: &serde_json::Value,
+        signature: &str,
+    ) -> Result<bool, String> {
+        let expected = crate::evm_tx::parse_address(wallet).map_err(|e| e.to_string())?;
+        let signature = wallet_auth::decode_signature(signature).map_err(|e| e.to_string())?;
+        let signer = wallet_auth::recover_typed_data(typed_data, &signature).map_err(|e| e.to_string())?;
+        Ok(signer == expected)
     }
 
+    /// Consumes the nonce: a second call with the same value always fails
     pub async fn verify_nonce(&self, wallet: &str, nonce: &str) -> bool {
-        let store = self.nonce_store.read().await;
-        store.get(wallet).map(|n| n == nonce).unwrap_or(false)
+        let now = chrono::Utc::now().timestamp() as u64;
+        let wallet = normalize_wallet(wallet);
+        let mut store = self.nonce_store.write().await;
+        let Some(issued) = store.get_mut(&wallet) else {
+            return false;
+        };
+        let Some(index) = issued.iter().position(|n| n.nonce == nonce) else {
+            return false;
+        };
+        let valid = issued.remove(index).expires_at > now;
+        if issued.is_empty() {
+            store.remove(&wallet);
+        }
+        valid
+    }
+
+    /// Full SIWE/SIWS check: format, domain, chain, validity window, signature, then the single-use nonce
+    pub async fn sign_in(
+        &self,
+        message: &str,
+        signature: &str,
+        expected_domain: &str,
+    ) -> Result<SignInSession, String> {
+        let config = self.config.read().await.clone();
+        let parsed = SignInMessage::parse(message).map_err(|e| e.to_string())?;
+        if ChainFamily::of_address(&parsed.address) != parsed.chain {
+            return Err(format!("{} is not a {:?} address", parsed.address, parsed.chain));
+        }
+        parsed.validate(expected_domain, chrono::Utc::now(), config.max_clock_skew_secs)
+            .map_err(|e| e.to_string())?;
+        if !config.sign_in_chain_ids.contains(&parsed.chain_id) {
+            return Err(format!("Chain ID {} is not accepted for sign-in", parsed.chain_id));
+        }
+
+        if !self.verify_signature(&parsed.address, message, signature).await? {
+            return Err("Invalid signature".to_string());
+        }
+        if !self.verify_nonce(&parsed.address, &parsed.nonce).await {
+            return Err("Unknown, expired or already used nonce".to_string());
+        }
+
+        Ok(SignInSession {
+            expires_at: parsed.expires_at(),
+            address: parsed.address,
+            chain: parsed.chain,
+            domain: parsed.domain,
+            chain_id: parsed.chain_id,
+            issued_at: parsed.issued_at,
+        })
+    }
+}
+
+/// EVM addresses are case-insensitive (EIP-55 only adds a checksum), base58 keys are not
+/// 20-byte hex EVM address or base58 Solana public key
+pub fn is_wallet_address(wallet: &str) -> bool {
+    match ChainFamily::of_address(wallet) {
+        ChainFamily::Ethereum => crate::evm_tx::parse_address(wallet).is_ok(),
+        ChainFamily::Solana => Pubkey::from_str(wallet).is_ok(),
+    }
+}
+
+fn normalize_wallet(wallet: &str) -> String {
+    match ChainFamily::of_address(wallet) {
+        ChainFamily::Ethereum => wallet.to_lowercase(),
+        ChainFamily::Solana => wallet.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use solana_sdk::signature::Signer;
+    use crate::keystore::KeystoreConfig;
+    use crate::solana_tx::tests::MockSubmitter;
+    use crate::tx_policy::WalletPolicy;
+
+    struct FixedPrices(HashMap<String, f64>);
+
+    #[async_trait]
+    impl UsdPriceSource for FixedPrices {
+        async fn usd_prices(&self) -> anyhow::Result<HashMap<String, f64>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Wallet manager signing through a mock chain with one unlocked Solana keystore account
+    async fn signing_wallet(mock: Arc<MockSubmitter>) -> (WalletManager, String, std::path::PathBuf) {
+        let directory = std::env::temp_dir().join(format!("dexter-wallet-test-{}", uuid::Uuid::new_v4().simple()));
+        let keystore = Arc::new(Keystore::new(KeystoreConfig {
+            directory: directory.to_string_lossy().to_string(),
+            scrypt_log_n: 10,
+            ..KeystoreConfig::default()
+        }));
+        let account = keystore.create_account("solana-test", ChainFamily::Solana, "hunter2").await.unwrap();
+        keystore.unlock("solana-test", "hunter2").await.unwrap();
+
+        let manager = WalletManager::new().with_solana_signer(SolanaTxSender::new(mock), keystore);
+        manager.set_price_source(Arc::new(FixedPrices(HashMap::from([("SOL".to_string(), 150.0)])))).await;
+        (manager, account.address, directory)
+    }
+
+    fn instruction_json(instruction: &Instruction) -> serde_json::Value {
+        serde_json::json!({
+            "programId": instruction.program_id.to_string(),
+            "accounts": instruction.accounts.iter().map(|meta| serde_json::json!({
+                "pubkey": meta.pubkey.to_string(),
+                "isSigner": meta.is_signer,
+                "isWritable": meta.is_writable,
+            })).collect::<Vec<_>>(),
+            "data": base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &instruction.data),
+        })
+    }
+
+    fn compute_unit_limit_json(units: u32) -> serde_json::Value {
+        instruction_json(&solana_sdk::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(units))
+    }
+
+    /// SPL `TransferChecked` of `amount` USDC (6 decimals) from the wallet's token account to `destination`
+    fn usdc_transfer_json(wallet: &str, destination: &Pubkey, amount: u64) -> serde_json::Value {
+        let mut data = vec![12];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.push(6);
+        instruction_json(&Instruction {
+            program_id: solana_tx::TOKEN_PROGRAM_ID,
+            accounts: vec![
+                solana_sdk::instruction::AccountMeta::new(Pubkey::new_unique(), false),
+                solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap(), false),
+                solana_sdk::instruction::AccountMeta::new(*destination, false),
+                solana_sdk::instruction::AccountMeta::new_readonly(Pubkey::from_str(wallet).unwrap(), true),
+            ],
+            data,
+        })
+    }
+
+    fn today() -> u64 {
+        chrono::Utc::now().timestamp() as u64 / 86_400
+    }
+
+    #[tokio::test]
+    async fn sol_transfer_is_signed_by_the_keystore_account_and_confirmed() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        let recipient = Pubkey::new_unique();
+
+        let transaction = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.5, "SOL".to_string(),
+            serde_json::json!({ "to": recipient.to_string() }),
+        ).await.unwrap();
+        let signature = manager.sign_and_send_transaction(&wallet, &transaction).await.unwrap();
+
+        let sent = mock.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(signature, sent[0].signatures[0].to_string());
+        assert!(sent[0].verify_with_results().iter().all(|ok| *ok));
+        let keys = sent[0].message.static_account_keys();
+        assert_eq!(keys[0].to_string(), wallet);
+        assert!(keys.contains(&recipient));
+        let transfer = sent[0].message.instructions().last().unwrap();
+        assert_eq!(transfer.data[4..12], 500_000_000u64.to_le_bytes());
+
+        let stored = manager.get_transaction(&transaction.id).await.unwrap();
+        assert!(matches!(stored.status, TransactionStatus::Confirmed));
+        assert_eq!(stored.hash, Some(signature));
+        assert_eq!(manager.policy_engine().spent_today(&wallet, today()).await, 75.0);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn wallets_without_an_unlocked_key_are_never_sent() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, _, directory) = signing_wallet(mock.clone()).await;
+        let stranger = solana_sdk::signature::Keypair::new().pubkey().to_string();
+
+        let transaction = manager.create_transaction(
+            stranger.clone(), TransactionType::Transfer, 0.5, "SOL".to_string(),
+            serde_json::json!({ "to": Pubkey::new_unique().to_string() }),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&stranger, &transaction).await.unwrap_err();
+
+        assert!(error.contains("No unlocked keystore account"), "{}", error);
+        assert!(mock.sent.lock().unwrap().is_empty());
+        assert!(matches!(manager.get_transaction(&transaction.id).await.unwrap().status, TransactionStatus::Pending));
+        assert_eq!(manager.policy_engine().spent_today(&stranger, today()).await, 0.0);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn failed_submission_marks_the_transaction_failed_and_releases_its_spend() {
+        let mut mock = MockSubmitter::new();
+        mock.simulation_error = Some("InsufficientFundsForRent".to_string());
+        let mock = Arc::new(mock);
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+
+        let transaction = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.5, "SOL".to_string(),
+            serde_json::json!({ "to": Pubkey::new_unique().to_string() }),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&wallet, &transaction).await.unwrap_err();
+
+        assert!(error.contains("InsufficientFundsForRent"), "{}", error);
+        assert!(matches!(manager.get_transaction(&transaction.id).await.unwrap().status, TransactionStatus::Failed));
+        assert_eq!(manager.policy_engine().spent_today(&wallet, today()).await, 0.0);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn instructions_come_from_the_transaction_details_and_are_priced_as_decoded() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        let budget = compute_unit_limit_json(400_000);
+
+        // Declared as 1 SOL ($150); the instruction actually moves 1,000 USDC
+        let transaction = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 1.0, "SOL".to_string(),
+            serde_json::json!({ "instructions": [budget, usdc_transfer_json(&wallet, &Pubkey::new_unique(), 1_000_000_000)] }),
+        ).await.unwrap();
+        manager.sign_and_send_transaction(&wallet, &transaction).await.unwrap();
+
+        // The caller's compute budget is replaced by the sender's simulated one
+        let sent = mock.sent.lock().unwrap().clone();
+        let message = &sent[0].message;
+        let programs: Vec<Pubkey> = message.instructions().iter()
+            .map(|ix| message.static_account_keys()[ix.program_id_index as usize])
+            .collect();
+        assert_eq!(programs, vec![solana_sdk::compute_budget::id(), solana_tx::TOKEN_PROGRAM_ID]);
+        assert_eq!(manager.policy_engine().spent_today(&wallet, today()).await, 1_000.0);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn undecoded_instructions_are_denied() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        let program = Pubkey::new_unique();
+
+        let swap = manager.create_transaction(
+            wallet.clone(), TransactionType::Swap, 0.01, "SOL".to_string(),
+            serde_json::json!({ "instructions": [{
+                "programId": program.to_string(),
+                "accounts": [{ "pubkey": wallet, "isSigner": true, "isWritable": true }],
+                "data": "AQID",
+            }]}),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&wallet, &swap).await.unwrap_err();
+
+        assert!(error.contains(&format!("instruction for program {} could not be decoded", program)), "{}", error);
+        assert!(mock.sent.lock().unwrap().is_empty());
+        assert!(matches!(manager.get_transaction(&swap.id).await.unwrap().status, TransactionStatus::Blocked));
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn counterparties_come_from_the_instructions_not_the_details() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        let (trusted, stranger) = (Pubkey::new_unique(), Pubkey::new_unique());
+        manager.policy_engine().set_wallet_policy(&wallet, WalletPolicy {
+            allowed_counterparties: Some(HashSet::from([trusted.to_string()])),
+            ..WalletPolicy::default()
+        }).await;
+
+        let transfer = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.1, "USDC".to_string(),
+            serde_json::json!({
+                "counterparties": [trusted.to_string()],
+                "instructions": [usdc_transfer_json(&wallet, &stranger, 100_000)],
+            }),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&wallet, &transfer).await.unwrap_err();
+        assert!(error.contains(&format!("counterparty {} is not allowlisted", stranger)), "{}", error);
+
+        let transfer = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.1, "USDC".to_string(),
+            serde_json::json!({ "instructions": [usdc_transfer_json(&wallet, &trusted, 100_000)] }),
+        ).await.unwrap();
+        manager.sign_and_send_transaction(&wallet, &transfer).await.unwrap();
+        assert_eq!(mock.sent.lock().unwrap().len(), 1);
+
+        // Nothing is decoded for a transaction signed elsewhere, so its counterparties are unknown
+        let swap = manager.create_transaction(
+            wallet.clone(), TransactionType::Swap, 0.1, "USDC".to_string(),
+            serde_json::json!({ "counterparties": [trusted.to_string()] }),
+        ).await.unwrap();
+        let error = manager.authorize_transaction(&swap).await.unwrap_err();
+        assert!(error.contains("accounts the transaction pays or writes to could not be decoded"), "{}", error);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn policy_ignores_declared_programs_and_value() {
+        let mock = Arc::new(MockSubmitter::new());
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        let system_program = solana_tx::transfer_instruction(&Pubkey::new_unique(), &Pubkey::new_unique(), 1).program_id;
+        manager.policy_engine().set_wallet_policy(&wallet, WalletPolicy {
+            allowed_programs: Some(HashSet::from([system_program.to_string()])),
+            ..WalletPolicy::default()
+        }).await;
+
+        let unlisted = Pubkey::new_unique();
+        let swap = manager.create_transaction(
+            wallet.clone(), TransactionType::Swap, 1.0, "SOL".to_string(),
+            serde_json::json!({
+                "programs": [system_program.to_string()],
+                "instructions": [{ "programId": unlisted.to_string(), "accounts": [], "data": "AQID" }],
+            }),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&wallet, &swap).await.unwrap_err();
+        assert!(error.contains(&format!("program {} is not allowlisted", unlisted)), "{}", error);
+        assert!(matches!(manager.get_transaction(&swap.id).await.unwrap().status, TransactionStatus::Blocked));
+
+        // Declaring $1 does not lower the $75 the feed prices 0.5 SOL at
+        let transfer = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.5, "SOL".to_string(),
+            serde_json::json!({ "to": Pubkey::new_unique().to_string(), "value_usd": 1.0 }),
+        ).await.unwrap();
+        manager.sign_and_send_transaction(&wallet, &transfer).await.unwrap();
+        assert_eq!(manager.policy_engine().spent_today(&wallet, today()).await, 75.0);
+        assert_eq!(mock.sent.lock().unwrap().len(), 1);
+        std::fs::remove_dir_all(directory).ok();
+    }
+
+    #[tokio::test]
+    async fn required_simulation_is_run_not_declared() {
+        let mut mock = MockSubmitter::new();
+        mock.simulation_error = Some("custom program error: 0x1771".to_string());
+        let mock = Arc::new(mock);
+        let (manager, wallet, directory) = signing_wallet(mock.clone()).await;
+        manager.policy_engine().set_wallet_policy(&wallet, WalletPolicy {
+            require_simulation: true,
+            ..WalletPolicy::default()
+        }).await;
+
+        let transaction = manager.create_transaction(
+            wallet.clone(), TransactionType::Transfer, 0.5, "SOL".to_string(),
+            serde_json::json!({ "to": Pubkey::new_unique().to_string(), "simulation": { "success": true } }),
+        ).await.unwrap();
+        let error = manager.sign_and_send_transaction(&wallet, &transaction).await.unwrap_err();
+
+        assert!(error.contains("simulation failed: custom program error: 0x1771"), "{}", error);
+        assert_eq!(mock.simulated.lock().unwrap().len(), 1);
+        assert!(mock.sent.lock().unwrap().is_empty());
+        assert!(matches!(manager.get_transaction(&transaction.id).await.unwrap().status, TransactionStatus::Blocked));
+
+        // Transactions signed elsewhere are never simulated here, so the same policy denies them
+        let swap = manager.create_transaction(
+            wallet.clone(), TransactionType::Swap, 0.5, "SOL".to_string(),
+            serde_json::json!({ "simulation": { "success": true } }),
+        ).await.unwrap();
+        let error = manager.authorize_transaction(&swap).await.unwrap_err();
+        assert!(error.contains("simulation required but not run"), "{}", error);
+        std::fs::remove_dir_all(directory).ok();
     }
-}
\ No newline at end of file
+
+    const SIGN_IN_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn sign_in_message(address: &str, nonce: &str, domain: &str, chain_id: &str, expiration_time: Option<String>) -> String {
+        SignInMessage {
+            chain: ChainFamily::Ethereum,
+            domain: domain.to_string(),
+            address: address.to_string(),
+            statement: Some("Sign in to Dexter".to_string()),
+            uri: format!("https://{}", domain),
+            version: "1".to_string(),
+            chain_id: chain_id.to_string(),
+            nonce: nonce.to_string(),
+            issued_at: (chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339(),
+            expiration_time,
+            not_before: None,
+            request_id: None,
+            resources: Vec::new(),
+        }.to_message()
+    }
+
+    fn sign(message: &str) -> String {
+        let signer = crate::evm_tx::EvmSigner::from_hex(SIGN_IN_KEY).unwrap();
+        format!("0x{}", hex::encode(signer.sign_message(message.as_bytes()).unwrap()))
+    }
+
+    fn sign_in_wallet() -> (WalletSecurity, String) {
+        let signer = crate::evm_tx::EvmSigner::from_hex(SIGN_IN_KEY).unwrap();
+        (WalletSecurity::new(), crate::evm_tx::format_address(&signer.address()))
+    }
+
+    #[tokio::test]
+    async fn sign_in_consumes_the_nonce() {
+        let (security, address) = sign_in_wallet();
+        let nonce = security.generate_nonce(&address).await.unwrap();
+        let message = sign_in_message(&address, &nonce, "dexter.app", "1", None);
+
+        let session = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap();
+        assert_eq!(session.address, address);
+        assert_eq!(session.chain_id, "1");
+
+        let error = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap_err();
+        assert!(error.contains("already used nonce"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn sign_in_rejects_another_domain_without_burning_the_nonce() {
+        let (security, address) = sign_in_wallet();
+        let nonce = security.generate_nonce(&address).await.unwrap();
+
+        let phished = sign_in_message(&address, &nonce, "dexter.app.evil.example", "1", None);
+        let error = security.sign_in(&phished, &sign(&phished), "dexter.app").await.unwrap_err();
+        assert!(error.contains("expected dexter.app"), "{}", error);
+
+        let message = sign_in_message(&address, &nonce, "dexter.app", "1", None);
+        assert!(security.sign_in(&message, &sign(&message), "dexter.app").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn sign_in_rejects_unaccepted_chain_ids() {
+        let (security, address) = sign_in_wallet();
+        let nonce = security.generate_nonce(&address).await.unwrap();
+
+        let message = sign_in_message(&address, &nonce, "dexter.app", "5", None);
+        let error = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap_err();
+        assert!(error.contains("Chain ID 5 is not accepted"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn sign_in_rejects_expired_messages() {
+        let (security, address) = sign_in_wallet();
+        let nonce = security.generate_nonce(&address).await.unwrap();
+
+        let expired = (chrono::Utc::now() - chrono::Duration::seconds(1)).to_rfc3339();
+        let message = sign_in_message(&address, &nonce, "dexter.app", "1", Some(expired));
+        let error = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap_err();
+        assert!(error.contains("expired"), "{}", error);
+
+        let valid = (chrono::Utc::now() + chrono::Duration::minutes(5)).to_rfc3339();
+        let message = sign_in_message(&address, &nonce, "dexter.app", "1", Some(valid));
+        let session = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap();
+        assert!(session.expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn sign_in_rejects_a_signature_by_another_key() {
+        let (security, _) = sign_in_wallet();
+        let other = crate::evm_tx::format_address(&[0x11; 20]);
+        let nonce = security.generate_nonce(&other).await.unwrap();
+
+        let message = sign_in_message(&other, &nonce, "dexter.app", "1", None);
+        let error = security.sign_in(&message, &sign(&message), "dexter.app").await.unwrap_err();
+        assert_eq!(error, "Invalid signature");
+    }
+
+    #[tokio::test]
+    async fn nonces_are_capped_per_wallet_and_in_total() {
+        let (security, address) = sign_in_wallet();
+        security.set_config(WalletSecurityConfig {
+            max_pending_nonces: 3,
+            max_nonces_per_wallet: 2,
+            ..WalletSecurityConfig::default()
+        }).await;
+        assert!(security.generate_nonce("not-a-wallet").await.is_err());
+
+        let first = security.generate_nonce(&address).await.unwrap();
+        let second = security.generate_nonce(&address).await.unwrap();
+        let third = security.generate_nonce(&address).await.unwrap();
+        // Asking again drops the wallet's oldest nonce instead of growing the store
+        assert!(!security.verify_nonce(&address, &first).await);
+        assert!(security.verify_nonce(&address, &second).await);
+
+        let other = Pubkey::new_unique().to_string();
+        security.generate_nonce(&other).await.unwrap();
+        security.generate_nonce(&other).await.unwrap();
+        let error = security.generate_nonce(&Pubkey::new_unique().to_string()).await.unwrap_err();
+        assert_eq!(error, "Too many pending sign-in requests");
+        // A wallet already at its own cap can still rotate
+        assert!(security.generate_nonce(&other).await.is_ok());
+        assert!(security.verify_nonce(&address, &third).await);
+    }
+}
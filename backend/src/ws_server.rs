@@ -6,13 +6,23 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_tungstenite::accept_hdr_async;
+use tokio_tungstenite::tungstenite::{
+    Message,
+    handshake::server::{ErrorResponse, Request, Response},
+    http::StatusCode,
+    protocol::{CloseFrame, frame::coding::CloseCode},
+};
 use futures_util::{SinkExt, StreamExt};
 use log::{info, warn, error, debug};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::FromPrimitive;
 use rand;
 
+use crate::circuit_breaker::{BreakerEvent, CircuitBreaker};
+use crate::auth::{self, AuthManager, Role};
+use crate::wallet_manager::{BalanceChangeEvent, WalletManager};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketMessage {
     pub message_type: String,
@@ -98,7 +108,7 @@ pub enum AlphaStrategyDetails {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SubscriptionRequest {
     pub action: String, // "subscribe" | "unsubscribe"
-    pub channels: Vec<String>, // ["prices", "opportunities", "mev", "depth", "alpha"]
+    pub channels: Vec<String>, // ["prices", "opportunities", "mev", "depth", "alpha", "risk", "balances"]
     pub pairs: Option<Vec<String>>, // Optional filter for specific pairs
 }
 
@@ -116,6 +126,17 @@ pub struct WebSocketServer {
     mev_broadcaster: broadcast::Sender<LiveMevAlert>,
     depth_broadcaster: broadcast::Sender<serde_json::Value>,
     alpha_broadcaster: broadcast::Sender<AlphaStrategyUpdate>,
+    risk_broadcaster: broadcast::Sender<BreakerEvent>,
+    balance_broadcaster: broadcast::Sender<BalanceChangeEvent>,
+    
+    // Kill switch / circuit breaker events
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    
+    // Session check on connect; connections are refused without it
+    auth: Option<Arc<AuthManager>>,
+    
+    // On-chain wallet balance changes
+    wallet_manager: Option<Arc<WalletManager>>,
     
     // Universal price aggregator broadcaster
     universal_price_aggregator: Option<Arc<crate::universal_price_aggregator::UniversalPriceAggregator>>,
@@ -128,6 +149,8 @@ pub struct ClientConnection {
     pub subscriptions: Vec<String>,
     pub connected_at: u64,
     pub last_ping: u64,
+    pub subject: String,
+    pub role: Role,
 }
 
 impl WebSocketServer {
@@ -141,6 +164,8 @@ impl WebSocketServer {
         let (mev_tx, _) = broadcast::channel(1000);
         let (depth_tx, _) = broadcast::channel(1000);
         let (alpha_tx, _) = broadcast::channel(1000);
+        let (risk_tx, _) = broadcast::channel(100);
+        let (balance_tx, _) = broadcast::channel(1000);
 
         Self {
             port,
@@ -152,6 +177,11 @@ impl WebSocketServer {
             mev_broadcaster: mev_tx,
             depth_broadcaster: depth_tx,
             alpha_broadcaster: alpha_tx,
+            risk_broadcaster: risk_tx,
+            balance_broadcaster: balance_tx,
+            circuit_breaker: None,
+            auth: None,
+            wallet_manager: None,
             universal_price_aggregator: None,
             price_broadcaster_universal: None,
         }
@@ -162,6 +192,18 @@ impl WebSocketServer {
         self.price_broadcaster_universal = Some(broadcaster);
     }
     
+    pub fn set_circuit_breaker(&mut self, circuit_breaker: Arc<CircuitBreaker>) {
+        self.circuit_breaker = Some(circuit_breaker);
+    }
+    
+    pub fn set_auth_manager(&mut self, auth: Arc<AuthManager>) {
+        self.auth = Some(auth);
+    }
+    
+    pub fn set_wallet_manager(&mut self, wallet_manager: Arc<WalletManager>) {
+        self.wallet_manager = Some(wallet_manager);
+    }
+    
     pub fn get_alpha_broadcaster(&self) -> broadcast::Sender<AlphaStrategyUpdate> {
         self.alpha_broadcaster.clone()
     }
@@ -174,6 +216,8 @@ impl WebSocketServer {
         tokio::spawn(self.clone().opportunity_forwarding_loop());
         tokio::spawn(self.clone().generate_live_data_loop());
         tokio::spawn(self.clone().universal_price_broadcasting_loop());
+        tokio::spawn(self.clone().risk_forwarding_loop());
+        tokio::spawn(self.clone().balance_forwarding_loop());
 
         // Start WebSocket server
         let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?;
@@ -196,11 +240,62 @@ impl WebSocketServer {
         stream: tokio::net::TcpStream,
         addr: String,
     ) -> Result<()> {
-        let ws_stream = accept_async(stream).await?;
+        let auth_config = match &self.auth {
+            Some(auth) => Some(auth.get_config().await),
+            None => None,
+        };
+
+        // Browsers cannot set headers on WebSocket upgrades, so accept `?token=` as well
+        let mut presented_token: Option<String> = None;
+        // The handshake callback's error type is fixed by tungstenite
+        #[allow(clippy::result_large_err)]
+        let ws_stream = accept_hdr_async(stream, |request: &Request, response: Response| {
+            let origin = request.headers().get("origin").and_then(|o| o.to_str().ok());
+            if let Some(config) = &auth_config {
+                if !config.origin_allowed(origin) {
+                    warn!("🚫 WebSocket upgrade from disallowed origin {:?}", origin);
+                    let mut rejection = ErrorResponse::new(Some("Origin not allowed".to_string()));
+                    *rejection.status_mut() = StatusCode::FORBIDDEN;
+                    return Err(rejection);
+                }
+            }
+
+            let header_token = request.headers().get("authorization")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|h| auth::bearer_token(Some(h)))
+                .map(str::to_string);
+            let query_token = request.uri().query().and_then(|query| {
+                query.split('&')
+                    .find_map(|pair| pair.strip_prefix("token="))
+                    .map(str::to_string)
+            });
+            presented_token = header_token.or(query_token);
+            Ok(response)
+        }).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+        let session = match &self.auth {
+            Some(auth) => {
+                let revocations = auth.subscribe_revocations();
+                auth.authorize(presented_token.as_deref(), Role::Viewer).await
+                    .map(|session| (session, auth.clone(), revocations))
+            }
+            None => Err(auth::AuthError::MissingToken),
+        };
+        let (session, auth, mut revocations) = match session {
+            Ok(session) => session,
+            Err(e) => {
+                warn!("🚫 Closing unauthenticated WebSocket connection from {}: {}", addr, e);
+                let _ = ws_sender.send(Message::Close(Some(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: e.to_string().into(),
+                }))).await;
+                return Ok(());
+            }
+        };
         
         let client_id = format!("client_{}_{}", addr, chrono::Utc::now().timestamp_millis());
-        info!("🔗 New WebSocket connection: {}", client_id);
+        info!("🔗 New WebSocket connection: {} ({} as {:?})", client_id, session.subject, session.role);
 
         // Register client
         let client = ClientConnection {
@@ -208,6 +303,8 @@ impl WebSocketServer {
             subscriptions: Vec::new(),
             connected_at: chrono::Utc::now().timestamp() as u64,
             last_ping: chrono::Utc::now().timestamp() as u64,
+            subject: session.subject.clone(),
+            role: session.role,
         };
 
         {
@@ -221,6 +318,8 @@ impl WebSocketServer {
         let mut mev_rx = self.mev_broadcaster.subscribe();
         let mut depth_rx = self.depth_broadcaster.subscribe();
         let mut alpha_rx = self.alpha_broadcaster.subscribe();
+        let mut risk_rx = self.risk_broadcaster.subscribe();
+        let mut balance_rx = self.balance_broadcaster.subscribe();
 
         // Handle incoming messages from client
         let client_id_clone = client_id.clone();
@@ -267,12 +366,35 @@ impl WebSocketServer {
             }
         });
 
-        // Handle outgoing messages to client
+        // Handle outgoing messages to client; the socket closes when its session expires or is revoked
         let client_id_clone2 = client_id.clone();
         let connections_clone2 = self.active_connections.clone();
+        let session_ttl = session.expires_at.saturating_sub(chrono::Utc::now().timestamp() as u64);
         let message_forwarder = tokio::spawn(async move {
+            let session_expiry = tokio::time::sleep(tokio::time::Duration::from_secs(session_ttl));
+            tokio::pin!(session_expiry);
             loop {
                 tokio::select! {
+                    _ = &mut session_expiry => {
+                        info!("⏰ Session of {} expired, closing {}", session.subject, client_id_clone2);
+                        let _ = ws_sender.send(Message::Close(Some(CloseFrame {
+                            code: CloseCode::Policy,
+                            reason: "Session expired".into(),
+                        }))).await;
+                        break;
+                    }
+                    revoked = revocations.recv() => {
+                        // A lagged receiver may have missed this subject, so re-check on any gap
+                        let affected = revoked.map(|subject| subject == session.subject).unwrap_or(true);
+                        if affected && auth.authenticate(presented_token.as_deref()).await.is_err() {
+                            info!("🔒 Session of {} revoked, closing {}", session.subject, client_id_clone2);
+                            let _ = ws_sender.send(Message::Close(Some(CloseFrame {
+                                code: CloseCode::Policy,
+                                reason: "Session revoked".into(),
+                            }))).await;
+                            break;
+                        }
+                    }
                     Ok(price_update) = price_rx.recv() => {
                         if Self::client_subscribed_to(&connections_clone2, &client_id_clone2, "prices").await {
                             let msg = WebSocketMessage {
@@ -341,6 +463,38 @@ impl WebSocketServer {
                                 timestamp: chrono::Utc::now().timestamp() as u64,
                             };
                             
+                            if let Ok(json) = serde_json::to_string(&msg) {
+                                if ws_sender.send(Message::Text(json)).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Ok(risk_event) = risk_rx.recv() => {
+                        if Self::client_subscribed_to(&connections_clone2, &client_id_clone2, "risk").await {
+                            let msg = WebSocketMessage {
+                                message_type: "circuit_breaker".to_string(),
+                                data: serde_json::to_value(&risk_event).unwrap_or_default(),
+                                timestamp: chrono::Utc::now().timestamp() as u64,
+                            };
+                            
+                            if let Ok(json) = serde_json::to_string(&msg) {
+                                if ws_sender.send(Message::Text(json)).await.is_err() {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                    Ok(balance_event) = balance_rx.recv() => {
+                        if Self::client_subscribed_to(&connections_clone2, &client_id_clone2, "balances").await
+                            && Self::client_may_see_wallet(&connections_clone2, &client_id_clone2, &balance_event.wallet).await
+                        {
+                            let msg = WebSocketMessage {
+                                message_type: "balance_change".to_string(),
+                                data: serde_json::to_value(&balance_event).unwrap_or_default(),
+                                timestamp: chrono::Utc::now().timestamp() as u64,
+                            };
+                            
                             if let Ok(json) = serde_json::to_string(&msg) {
                                 if ws_sender.send(Message::Text(json)).await.is_err() {
                                     break;
@@ -399,6 +553,53 @@ impl WebSocketServer {
         }
     }
 
+    /// Wallet owners see their own balances; traders and admins see every wallet
+    async fn client_may_see_wallet(
+        connections: &Arc<RwLock<HashMap<String, ClientConnection>>>,
+        client_id: &str,
+        wallet: &str,
+    ) -> bool {
+        let connections = connections.read().await;
+        connections
+            .get(client_id)
+            .map(|client| client.role.allows(Role::Trader) || client.subject.eq_ignore_ascii_case(wallet))
+            .unwrap_or(false)
+    }
+
+    async fn balance_forwarding_loop(self: Arc<Self>) {
+        let Some(wallet_manager) = self.wallet_manager.clone() else {
+            return;
+        };
+        let mut receiver = wallet_manager.subscribe_balance_changes();
+        
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let _ = self.balance_broadcaster.send(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn risk_forwarding_loop(self: Arc<Self>) {
+        let Some(circuit_breaker) = self.circuit_breaker.clone() else {
+            return;
+        };
+        let mut receiver = circuit_breaker.subscribe();
+        
+        loop {
+            match receiver.recv().await {
+                Ok(event) => {
+                    let _ = self.risk_broadcaster.send(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
     async fn opportunity_forwarding_loop(self: Arc<Self>) {
         let mut receiver = self.opportunity_receiver.resubscribe();
         
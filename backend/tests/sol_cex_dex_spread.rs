@@ -0,0 +1,131 @@
+// Scenario Pipeline Test - sol_cex_dex_spread replayed through detection and execution
+// Loads the scenario into the exchange simulator, detects the CEX/AMM spread and trades both legs
+
+// The simulator modules are compiled straight from src/, without the rest of the platform;
+// they are public so items the scenario doesn't exercise aren't reported as dead code
+#[path = "../src/types.rs"]
+pub mod types;
+#[path = "../src/fee_model.rs"]
+pub mod fee_model;
+#[path = "../src/opportunity_scoring.rs"]
+pub mod opportunity_scoring;
+#[path = "../src/exchange_simulator.rs"]
+pub mod exchange_simulator;
+#[path = "../src/client_arbitrage.rs"]
+pub mod client_arbitrage;
+
+use std::sync::Arc;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromStr;
+use tokio::time::Duration;
+
+use types::*;
+use exchange_simulator::{ExchangeSimulator, SimOrderStatus, SimSide, SimVenueKind};
+use fee_model::FeeModel;
+
+const PAIR: &str = "SOL/USDC";
+
+fn dec(value: &str) -> Decimal {
+    Decimal::from_str(value).unwrap()
+}
+
+fn load_scenario() -> ExchangeSimulator {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios/sol_cex_dex_spread.json");
+    ExchangeSimulator::from_file(path).expect("scenario loads")
+}
+
+async fn detect(simulator: &ExchangeSimulator, fee_model: &FeeModel) -> Vec<ArbitrageOpportunity> {
+    let mut quotes = Vec::new();
+    for client in simulator.dex_clients().values() {
+        quotes.push(client.get_price(PAIR).await.expect("AMM quote"));
+    }
+    for client in simulator.cex_clients().values() {
+        quotes.push(client.get_price(PAIR).await.expect("CEX quote"));
+    }
+    client_arbitrage::find_opportunities(fee_model, PAIR, &quotes, Decimal::from(10_000)).await
+}
+
+async fn usdc_total(simulator: &ExchangeSimulator) -> Decimal {
+    let mut total = Decimal::ZERO;
+    for client in simulator.cex_clients().values() {
+        total += client.get_balance("USDC").await.unwrap();
+    }
+    for venue in simulator.venues().iter().filter(|v| v.kind() == SimVenueKind::Dex) {
+        total += CexClient::get_balance(venue, "USDC").await.unwrap();
+    }
+    total
+}
+
+#[tokio::test(start_paused = true)]
+async fn detects_and_executes_the_cex_to_amm_spread() {
+    let simulator = Arc::new(load_scenario());
+    assert_eq!(simulator.name(), "sol_cex_dex_spread");
+    let fee_model = FeeModel::new();
+    simulator.register_fee_schedules(&fee_model).await;
+
+    // Until the first event the AMM trades inside the CEX spread
+    assert!(detect(&simulator, &fee_model).await.is_empty());
+
+    // 5s in, the scenario reprices the AMM to 174.50
+    let events = simulator.clone();
+    tokio::spawn(async move { events.run_events().await });
+    tokio::time::sleep(Duration::from_millis(5_001)).await;
+
+    let opportunities = detect(&simulator, &fee_model).await;
+    assert_eq!(opportunities.len(), 1, "{:?}", opportunities);
+    let opportunity = &opportunities[0];
+    assert_eq!(opportunity.buy_exchange, "SimCEX");
+    assert_eq!(opportunity.sell_exchange, "SimAMM");
+    assert_eq!(opportunity.buy_price, dec("171.10"));
+    // AMM bid is the pool price less its 0.25% fee
+    assert_eq!(opportunity.sell_price, dec("174.50") * dec("0.9975"));
+    assert!(opportunity.estimated_profit_usd > Decimal::ZERO);
+    assert_eq!(opportunity.profit_breakdown.costs_for_venue("SimCEX"), dec("10"));
+
+    let usdc_before = usdc_total(&simulator).await;
+    let cex = simulator.venue("SimCEX").unwrap();
+    let amm = simulator.venue("SimAMM").unwrap();
+
+    // Buy leg: market order against the CEX book; the seeded RNG rolls a partial fill
+    let buy = &opportunity.trade_route[0];
+    let bought = cex.submit_order(&Order {
+        pair: PAIR.to_string(),
+        side: buy.action.clone(),
+        amount: buy.amount,
+        price: None,
+        order_type: "market".to_string(),
+    }).await.unwrap();
+    assert_eq!(bought.status, SimOrderStatus::PartiallyFilled);
+    assert!(bought.filled_quantity > Decimal::ZERO && bought.filled_quantity < buy.amount);
+    assert_eq!(bought.average_price, Some(dec("171.10")));
+    assert_eq!(bought.fills.len(), 1);
+    let buy_fill = &bought.fills[0];
+    assert_eq!(buy_fill.side, SimSide::Buy);
+    assert_eq!(buy_fill.fee, buy_fill.quantity * buy_fill.price * dec("0.001"));
+
+    // Sell leg: swap exactly what was bought into the AMM pool
+    let sell = &opportunity.trade_route[1];
+    let sold = amm.submit_swap(&TradeStep { amount: bought.filled_quantity, ..sell.clone() }).await.unwrap();
+    assert_eq!(sold.status, SimOrderStatus::Filled);
+    assert_eq!(sold.filled_quantity, bought.filled_quantity);
+    let sell_price = sold.average_price.unwrap();
+    assert!(sell_price < opportunity.sell_price, "pool impact moves the price against us");
+    assert!((opportunity.sell_price - sell_price) / opportunity.sell_price < dec("0.01"));
+
+    // Realized P&L is the USDC gained across both venues; the SOL legs net to zero
+    let realized = usdc_total(&simulator).await - usdc_before;
+    let expected = bought.filled_quantity * (sell_price - buy_fill.price) - buy_fill.fee;
+    assert!((realized - expected).abs() < dec("0.000001"), "realized {} expected {}", realized, expected);
+    let fill_ratio = bought.filled_quantity / buy.amount;
+    assert!(realized >= opportunity.estimated_profit_usd * fill_ratio, "realized {} below estimate", realized);
+    assert_eq!(cex.get_fills().await.len(), 1);
+    assert_eq!(amm.get_fills().await.len(), 1);
+
+    // 45s in, the AMM drops to 171.00 under the 173.80 bid rested at 15s, reversing the trade
+    tokio::time::sleep(Duration::from_millis(40_000)).await;
+    let reversed = detect(&simulator, &fee_model).await;
+    assert_eq!(reversed.len(), 1, "{:?}", reversed);
+    assert_eq!(reversed[0].buy_exchange, "SimAMM");
+    assert_eq!(reversed[0].sell_exchange, "SimCEX");
+    assert_eq!(reversed[0].sell_price, dec("173.80"));
+}